@@ -17,6 +17,7 @@ from .react import ReActAgent
 from llm_client import LLMTransientError
 from tools.base import Tool, ToolRegistry
 from tools.context import set_tool_context, reset_tool_context
+from temp_files import current_owner, release_owner
 
 
 class AgentExecutor:
@@ -155,6 +156,7 @@ class AgentExecutor:
             )
         finally:
             if token is not None:
+                release_owner(current_owner())
                 reset_tool_context(token)
 
 
@@ -4,6 +4,8 @@ import copy
 from pathlib import Path
 from typing import Any, Dict, Optional
 
+from atomic_io import atomic_write_text, recover_temp_file
+
 
 _DEFAULT_APP_CONFIG: Dict[str, Any] = {
     "llm": {
@@ -172,6 +174,7 @@ def get_app_config_path() -> str:
 
 def _load_config_file() -> Dict[str, Any]:
     path = _get_config_file_path()
+    recover_temp_file(path)
     if path.exists() and not path.is_dir():
         try:
             return json.loads(path.read_text(encoding="utf-8"))
@@ -550,13 +553,11 @@ def update_app_config(patch: Dict[str, Any]) -> Dict[str, Any]:
     path = _get_config_file_path()
     content = json.dumps(merged_file, ensure_ascii=False, indent=2)
     try:
-        path.parent.mkdir(parents=True, exist_ok=True)
-        path.write_text(content, encoding="utf-8")
+        atomic_write_text(path, content)
     except OSError:
         fallback_path = Path.home() / ".tauri-agent" / "app_config.json"
         if fallback_path != path:
-            fallback_path.parent.mkdir(parents=True, exist_ok=True)
-            fallback_path.write_text(content, encoding="utf-8")
+            atomic_write_text(fallback_path, content)
             _CONFIG_PATH_OVERRIDE = fallback_path
         else:
             raise
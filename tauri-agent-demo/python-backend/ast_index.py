@@ -48,6 +48,23 @@ def _collect_files(root: Path, max_files: Optional[int] = None) -> List[Path]:
     )
 
 
+# Cleared while the user is away so background scans wait instead of competing
+# for battery and disk; on-demand lookups are unaffected.
+_BACKGROUND_ALLOWED = threading.Event()
+_BACKGROUND_ALLOWED.set()
+
+
+def set_background_idle(idle: bool) -> None:
+    if idle:
+        _BACKGROUND_ALLOWED.clear()
+    else:
+        _BACKGROUND_ALLOWED.set()
+
+
+def background_idle() -> bool:
+    return not _BACKGROUND_ALLOWED.is_set()
+
+
 def _ast_enabled() -> bool:
     app_cfg = get_app_config()
     agent_cfg = app_cfg.get("agent", {}) if isinstance(app_cfg, dict) else {}
@@ -125,6 +142,7 @@ class AstRootIndex:
         for path in files:
             if not _is_within_root(path, self.root):
                 continue
+            _BACKGROUND_ALLOWED.wait()
             self.ensure_file(path)
         with self.lock:
             self.last_scan = time.time()
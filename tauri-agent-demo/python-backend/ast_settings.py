@@ -4,6 +4,7 @@ from pathlib import Path
 from typing import Any, Dict, List, Optional
 
 from app_config import get_app_config_path
+from atomic_io import atomic_write_text, recover_temp_file
 
 
 DEFAULT_MAX_FILES = 500
@@ -70,6 +71,7 @@ def _get_settings_path() -> Path:
 
 def _load_settings_file() -> Dict[str, Any]:
     path = _get_settings_path()
+    recover_temp_file(path)
     if path.exists() and path.is_file():
         try:
             return json.loads(path.read_text(encoding="utf-8"))
@@ -80,8 +82,7 @@ def _load_settings_file() -> Dict[str, Any]:
 
 def _save_settings_file(data: Dict[str, Any]) -> None:
     path = _get_settings_path()
-    path.parent.mkdir(parents=True, exist_ok=True)
-    path.write_text(json.dumps(data, ensure_ascii=False, indent=2), encoding="utf-8")
+    atomic_write_text(path, json.dumps(data, ensure_ascii=False, indent=2))
 
 
 def _get_store() -> Dict[str, Any]:
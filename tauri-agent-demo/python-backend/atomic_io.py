@@ -0,0 +1,72 @@
+import json
+import os
+from pathlib import Path
+
+# Shared with the shell's `atomic_file` module, which cleans up the same leftovers.
+TEMP_SUFFIX = ".tmp"
+
+
+def _temp_path(path: Path) -> Path:
+    return path.with_name(path.name + TEMP_SUFFIX)
+
+
+def _sync_dir(directory: Path) -> None:
+    # Windows cannot open directories; the rename is durable there once it returns.
+    if os.name == "nt":
+        return
+    try:
+        fd = os.open(str(directory), os.O_RDONLY)
+    except OSError:
+        return
+    try:
+        os.fsync(fd)
+    except OSError:
+        pass
+    finally:
+        os.close(fd)
+
+
+def atomic_write_text(path: Path, content: str, encoding: str = "utf-8") -> None:
+    """Replace `path` via `<path>.tmp`, fsync and rename so a crash never leaves half a file."""
+    path = Path(path)
+    path.parent.mkdir(parents=True, exist_ok=True)
+    temp = _temp_path(path)
+    try:
+        with open(temp, "w", encoding=encoding) as handle:
+            handle.write(content)
+            handle.flush()
+            os.fsync(handle.fileno())
+        os.replace(temp, path)
+    except BaseException:
+        try:
+            temp.unlink()
+        except OSError:
+            pass
+        raise
+    _sync_dir(path.parent)
+
+
+def recover_temp_file(path: Path) -> None:
+    """Deal with a `.tmp` a crash left beside `path` before it is read.
+
+    Next to an existing file the leftover is an unfinished write and is dropped; if
+    the target was never written, a leftover holding complete JSON takes its place.
+    """
+    path = Path(path)
+    temp = _temp_path(path)
+    if not temp.is_file():
+        return
+    try:
+        if not path.exists():
+            try:
+                json.loads(temp.read_text(encoding="utf-8"))
+            except (OSError, ValueError):
+                temp.unlink()
+                return
+            os.replace(temp, path)
+            print(f"[Config] Recovered {path}")
+            return
+        temp.unlink()
+        print(f"[Config] Discarded unfinished write {temp}")
+    except OSError as exc:
+        print(f"[Config] Failed to clean up {temp}: {exc}")
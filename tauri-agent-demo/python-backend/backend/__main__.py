@@ -0,0 +1,13 @@
+"""`python -m backend`: runs main.py as the bundled sidecar binary does.
+
+The shell uses this when it runs the backend from source in its virtualenv, with
+this folder's parent on PYTHONPATH and the usual arguments and environment.
+"""
+
+import runpy
+import sys
+from pathlib import Path
+
+sys.path.insert(0, str(Path(__file__).resolve().parent.parent))
+
+runpy.run_module("main", run_name="__main__", alter_sys=True)
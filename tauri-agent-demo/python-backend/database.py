@@ -14,6 +14,7 @@ from models import (
     ChatSession,
     ChatSessionCreate,
     ChatSessionUpdate,
+    SessionImport,
     AgentInstance,
     AgentTask,
     AgentTaskEvent,
@@ -23,8 +24,19 @@ from models import (
 )
 
 DATABASE_PATH = os.getenv("TAURI_AGENT_DB_PATH", "chat_app.db")
+# Raw SQLCipher key, hex, passed by the shell when the database is encrypted.
+DATABASE_KEY = os.getenv("TAURI_AGENT_DB_KEY", "").strip()
+if DATABASE_KEY:
+    if not all(char in "0123456789abcdefABCDEF" for char in DATABASE_KEY):
+        raise ValueError("TAURI_AGENT_DB_KEY must be a hex key.")
+    # Same DB-API as sqlite3, so everything below works on it unchanged.
+    from sqlcipher3 import dbapi2 as sqlite3  # type: ignore[no-redef]
 SCHEMA_VERSION = 20260306
+# Change-log entries kept for readers that tail it (the shell's search index);
+# one that falls further behind rebuilds from chat_messages.
+MESSAGE_CHANGES_KEPT = 50000
 CORE_TABLES = (
+    'message_changes',
     'session_tool_call_history',
     'file_snapshots',
     'tool_permission_requests',
@@ -54,9 +66,16 @@ class Database:
             os.makedirs(parent, exist_ok=True)
         self.init_database()
 
+    def _connect(self, timeout: float) -> sqlite3.Connection:
+        conn = sqlite3.connect(self.db_path, timeout=timeout)
+        if DATABASE_KEY:
+            # Has to come before anything reads the file.
+            conn.execute(f"PRAGMA key = \"x'{DATABASE_KEY}'\"")
+        return conn
+
     def get_connection(self) -> sqlite3.Connection:
         """Get database connection."""
-        conn = sqlite3.connect(self.db_path, timeout=30.0)
+        conn = self._connect(30.0)
         conn.row_factory = sqlite3.Row
         conn.execute('PRAGMA foreign_keys = ON')
         conn.execute('PRAGMA busy_timeout = 30000')
@@ -89,11 +108,13 @@ class Database:
         current_version = self._read_schema_version()
         if current_version != SCHEMA_VERSION:
             self.rebuild_schema()
+        with self.transaction() as conn:
+            self._create_change_log(conn.cursor())
 
     def _read_schema_version(self) -> Optional[int]:
         if not os.path.exists(self.db_path):
             return None
-        conn = sqlite3.connect(self.db_path, timeout=5.0)
+        conn = self._connect(5.0)
         conn.row_factory = sqlite3.Row
         try:
             cursor = conn.cursor()
@@ -335,6 +356,46 @@ class Database:
             '''
         )
 
+    def _create_change_log(self, cursor: sqlite3.Cursor) -> None:
+        """Record every insert, edit and delete of a message, however it happens.
+
+        Added outside the schema version so existing databases get it without a
+        rebuild; cascaded deletes fire the delete trigger too.
+        """
+        cursor.execute(
+            '''
+            CREATE TABLE IF NOT EXISTS message_changes (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                message_id INTEGER NOT NULL,
+                changed_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+            '''
+        )
+        for name, event in (
+            ('message_changes_insert', 'AFTER INSERT'),
+            ('message_changes_update', 'AFTER UPDATE OF content, role, session_id'),
+        ):
+            cursor.execute(
+                f'''
+                CREATE TRIGGER IF NOT EXISTS {name} {event} ON chat_messages
+                BEGIN
+                    INSERT INTO message_changes (message_id) VALUES (NEW.id);
+                END
+                '''
+            )
+        cursor.execute(
+            '''
+            CREATE TRIGGER IF NOT EXISTS message_changes_delete AFTER DELETE ON chat_messages
+            BEGIN
+                INSERT INTO message_changes (message_id) VALUES (OLD.id);
+            END
+            '''
+        )
+        cursor.execute(
+            'DELETE FROM message_changes WHERE seq <= (SELECT MAX(seq) FROM message_changes) - ?',
+            (MESSAGE_CHANGES_KEPT,)
+        )
+
     def _create_core_indexes(self, cursor: sqlite3.Cursor) -> None:
         cursor.execute('CREATE INDEX IF NOT EXISTS idx_messages_session ON chat_messages(session_id)')
         cursor.execute('CREATE INDEX IF NOT EXISTS idx_attachments_message ON message_attachments(message_id)')
@@ -710,6 +771,33 @@ class Database:
         
         return self.get_session(session_id)
     
+    def import_session(self, payload: SessionImport, config_id: str) -> ChatSession:
+        """Create a session with its messages in one go, keeping their original times"""
+        session_id = str(uuid.uuid4())
+        now = datetime.now().isoformat()
+        created_at = payload.created_at or payload.messages[0].timestamp or now
+        updated_at = payload.messages[-1].timestamp or created_at
+        with self.transaction() as conn:
+            cursor = conn.cursor()
+            cursor.execute('''
+                INSERT INTO chat_sessions (id, title, config_id, created_at, updated_at)
+                VALUES (?, ?, ?, ?, ?)
+            ''', (session_id, payload.title, config_id, created_at, updated_at))
+            cursor.executemany('''
+                INSERT INTO chat_messages (session_id, role, content, timestamp, metadata)
+                VALUES (?, ?, ?, ?, ?)
+            ''', [
+                (
+                    session_id,
+                    message.role,
+                    message.content,
+                    message.timestamp or created_at,
+                    json.dumps(message.metadata) if message.metadata else None,
+                )
+                for message in payload.messages
+            ])
+        return self.get_session(session_id)
+
     def get_session(self, session_id: str, include_count: bool = True) -> Optional[ChatSession]:
         """Get session"""
         conn = self.get_connection()
@@ -2679,6 +2767,14 @@ class Database:
             raise RuntimeError("Failed to save agent artifact")
         return self._artifact_from_row(row)
 
+    def get_agent_artifact(self, artifact_id: int) -> Optional[AgentArtifact]:
+        conn = self.get_connection()
+        cursor = conn.cursor()
+        cursor.execute('SELECT * FROM agent_artifacts WHERE id = ?', (artifact_id,))
+        row = cursor.fetchone()
+        conn.close()
+        return self._artifact_from_row(row) if row else None
+
     def list_agent_artifacts(self, task_id: str) -> List[AgentArtifact]:
         conn = self.get_connection()
         cursor = conn.cursor()
@@ -7,6 +7,7 @@ from pathlib import Path
 from typing import Optional, Dict, Tuple
 
 from database import db
+from temp_files import temp_root
 
 
 def _run_git(args: list, cwd: str, env: Optional[dict] = None) -> str:
@@ -113,7 +114,7 @@ def _build_index_env(base_env: Optional[Dict[str, str]], index_file: str) -> Dic
 
 
 def _create_temp_index_path() -> Tuple[str, str]:
-    temp_dir = tempfile.mkdtemp(prefix="tauri-agent-index-")
+    temp_dir = tempfile.mkdtemp(prefix="tauri-agent-index-", dir=temp_root())
     return temp_dir, os.path.join(temp_dir, "index")
 
 
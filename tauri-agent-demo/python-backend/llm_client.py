@@ -371,6 +371,54 @@ class LLMClient:
         print(f"[LLM HTTP Error] {exc} | status={status} | body={log_text}")
         return detail_text, detail_json, status
 
+    async def transcribe(self, audio: bytes, filename: str = "recording.wav", language: Optional[str] = None) -> str:
+        """OpenAI-compatible audio transcription of one complete file."""
+        base_url = self._get_base_url()
+        app_config = get_app_config()
+        model = app_config.get("audio", {}).get("transcription_model") or "whisper-1"
+        data = {"model": model}
+        if language:
+            data["language"] = language
+        async with httpx.AsyncClient(timeout=self.timeout) as client:
+            response = await client.post(
+                f"{base_url}/audio/transcriptions",
+                headers={"Authorization": f"Bearer {self.config.api_key}"},
+                data=data,
+                files={"file": (filename, audio, "audio/wav")}
+            )
+            try:
+                response.raise_for_status()
+            except httpx.HTTPStatusError as exc:
+                await self._log_http_error(exc)
+                raise
+            return (response.json().get("text") or "").strip()
+
+    async def speak(self, text: str, voice: Optional[str] = None) -> bytes:
+        """OpenAI-compatible speech synthesis, as a WAV file."""
+        base_url = self._get_base_url()
+        audio_config = get_app_config().get("audio", {})
+        payload = {
+            "model": audio_config.get("speech_model") or "tts-1",
+            "input": text,
+            "voice": voice or audio_config.get("speech_voice") or "alloy",
+            "response_format": "wav"
+        }
+        async with httpx.AsyncClient(timeout=self.timeout) as client:
+            response = await client.post(
+                f"{base_url}/audio/speech",
+                headers={
+                    "Authorization": f"Bearer {self.config.api_key}",
+                    "Content-Type": "application/json"
+                },
+                json=payload
+            )
+            try:
+                response.raise_for_status()
+            except httpx.HTTPStatusError as exc:
+                await self._log_http_error(exc)
+                raise
+            return response.content
+
     async def _chat_openai(self, messages: List[Dict[str, Any]], request_overrides: Optional[Dict[str, Any]] = None) -> Dict[str, Any]:
         """OpenAI-compatible Chat Completions API."""
         base_url = self._get_base_url()
@@ -1,11 +1,13 @@
-from fastapi import FastAPI, HTTPException, Response, Query, WebSocket, WebSocketDisconnect
+from fastapi import FastAPI, HTTPException, Request, Response, Query, WebSocket, WebSocketDisconnect
 from fastapi.responses import StreamingResponse
 from fastapi.responses import JSONResponse
 from fastapi.middleware.cors import CORSMiddleware
 from pydantic import BaseModel
 import asyncio
 from contextlib import asynccontextmanager
+from contextvars import ContextVar
 import uvicorn
+import httpx
 import json
 import os
 import argparse
@@ -15,6 +17,10 @@ import re
 import time
 import atexit
 import signal
+import hmac
+import hashlib
+import mimetypes
+import wave
 from io import BytesIO
 from typing import List, Optional, Dict, Any, Tuple
 from datetime import datetime
@@ -26,10 +32,10 @@ from PIL import Image
 from models import (
     LLMConfig, LLMConfigCreate, LLMConfigUpdate,
     ChatMessage, ChatMessageCreate,
-    ChatSession, ChatSessionCreate, ChatSessionUpdate,
+    ChatSession, ChatSessionCreate, ChatSessionUpdate, SessionImport,
     ChatRequest, ChatResponse, ExportRequest,
     ToolPermissionRequest, ToolPermissionRequestUpdate,
-    ChatStopRequest, RollbackRequest, PatchRevertRequest, AstRequest, AstNotifyRequest, AstSettingsRequest,
+    ChatStopRequest, RollbackRequest, PatchRevertRequest, AstRequest, AstNotifyRequest, SystemIdleRequest, SystemBudgetRequest, AstSettingsRequest,
     AgentInstance, AgentTask, AgentTaskCreateRequest, AgentTaskHandoffRequest, AgentTaskCancelRequest, AgentTaskEvent,
     TaskStatus, TaskErrorCode
 )
@@ -54,7 +60,7 @@ from app_config import get_app_config, update_app_config, get_app_config_path
 from mcp_tools import register_mcp_tools_from_config, refresh_mcp_tools
 from ghost_snapshot import restore_snapshot
 from code_map import build_code_map_prompt
-from ast_index import get_ast_index
+from ast_index import background_idle, get_ast_index, set_background_idle
 from ast_settings import get_ast_settings, update_ast_settings, get_all_ast_settings
 from context_compress import build_history_for_llm, maybe_compress_context
 from subagent_runner import cancel_subagent_task, suppress_subagent_parent_notify
@@ -148,6 +154,121 @@ async def lifespan(_: FastAPI):
 
 app = FastAPI(title="Tauri Agent Chat Backend", lifespan=lifespan)
 
+# Set by the desktop shell in kiosk mode: history can be browsed but nothing changes.
+READ_ONLY_MODE = os.getenv("TAURI_AGENT_READ_ONLY", "").strip().lower() in ("1", "true", "yes")
+READ_ONLY_SAFE_METHODS = {"GET", "HEAD", "OPTIONS"}
+# POST endpoints that only read or stop work.
+READ_ONLY_ALLOWED_PATHS = {"/pty/read", "/tools/ast", "/chat/stop", "/system/idle", "/system/budget", "/shutdown"}
+
+
+@app.middleware("http")
+async def enforce_read_only(request: Request, call_next):
+    if (
+        READ_ONLY_MODE
+        and request.method not in READ_ONLY_SAFE_METHODS
+        and request.url.path not in READ_ONLY_ALLOWED_PATHS
+    ):
+        return JSONResponse(status_code=403, content={"detail": "The app is in read-only mode."})
+    return await call_next(request)
+
+
+# Set by the shell once the monthly budget is spent with its hard stop on; updated
+# through /system/budget. Covers callers that reach the backend without the proxy.
+BUDGET_PAUSED = os.getenv("TAURI_AGENT_BUDGET_PAUSED", "").strip().lower() in ("1", "true", "yes")
+
+
+def _is_llm_call(method: str, path: str) -> bool:
+    return method == "POST" and path.startswith("/chat") and path != "/chat/stop"
+
+
+@app.middleware("http")
+async def enforce_budget_stop(request: Request, call_next):
+    if BUDGET_PAUSED and _is_llm_call(request.method, request.url.path):
+        return JSONResponse(
+            status_code=402,
+            content={"detail": "This month's budget is spent; LLM calls are paused until you resume them."},
+        )
+    return await call_next(request)
+
+
+# Set by the shell for a sidecar it spawns on a port; every request must then carry it.
+AUTH_TOKEN = os.getenv("TAURI_AGENT_AUTH_TOKEN", "").strip()
+AUTH_HEADER = "x-agent-token"
+
+
+def _token_ok(presented: Optional[str]) -> bool:
+    return not AUTH_TOKEN or hmac.compare_digest(presented or "", AUTH_TOKEN)
+
+
+@app.middleware("http")
+async def require_auth_token(request: Request, call_next):
+    # CORS preflights never carry custom headers; the CORS middleware answers them.
+    # <img> and similar GETs cannot set headers, so those may pass it as ?token=.
+    presented = request.headers.get(AUTH_HEADER)
+    if presented is None and request.method == "GET":
+        presented = request.query_params.get("token")
+    if request.method != "OPTIONS" and not _token_ok(presented):
+        return JSONResponse(status_code=401, content={"detail": "Missing or invalid backend token."})
+    return await call_next(request)
+
+
+# The shell's locale at spawn; requests carry the current one as Accept-Language,
+# so generated strings and dates can follow a change without a restart.
+DEFAULT_LOCALE = os.getenv("TAURI_AGENT_LOCALE", "").strip() or "en-US"
+REQUEST_LOCALE: ContextVar[str] = ContextVar("request_locale", default=DEFAULT_LOCALE)
+
+
+def current_locale() -> str:
+    return REQUEST_LOCALE.get()
+
+
+@app.middleware("http")
+async def track_locale(request: Request, call_next):
+    header = request.headers.get("accept-language", "")
+    tag = header.split(",")[0].split(";")[0].strip()
+    token = REQUEST_LOCALE.set(tag or DEFAULT_LOCALE)
+    try:
+        return await call_next(request)
+    finally:
+        REQUEST_LOCALE.reset(token)
+
+
+# Cumulative counters for /health; the shell diffs successive samples.
+SERVER_STARTED_AT = time.time()
+# Echoed by /health, so the shell can tell this process from another on the same port.
+INSTANCE_ID = os.getenv("TAURI_AGENT_INSTANCE_ID", "").strip()
+# Set on the extra workers the shell starts for parallel sessions; echoed by /health.
+SESSION_ID = os.getenv("TAURI_AGENT_SESSION_ID", "").strip()
+# Left behind only when the process dies without exiting; the shell then finds it
+# on its next launch and stops the backend it lost track of.
+PID_FILE = os.getenv("TAURI_AGENT_PID_FILE", "").strip()
+REQUEST_METRICS = {"requests": 0, "errors": 0, "in_flight": 0}
+HEALTH_PATH = "/health"
+BACKEND_VERSION = "2.2"
+# Bumped on any change to the routes or payloads the shell relies on; the shell
+# refuses to run against a backend outside the range it was built for.
+API_VERSION = 1
+
+
+@app.middleware("http")
+async def count_requests(request: Request, call_next):
+    # The shell's own polling would otherwise dominate the counts.
+    if request.url.path == HEALTH_PATH:
+        return await call_next(request)
+    REQUEST_METRICS["in_flight"] += 1
+    failed = True
+    try:
+        response = await call_next(request)
+        failed = response.status_code >= 500
+        return response
+    finally:
+        REQUEST_METRICS["in_flight"] -= 1
+        REQUEST_METRICS["requests"] += 1
+        if failed:
+            REQUEST_METRICS["errors"] += 1
+
+
+# Added last so CORS stays outermost and read-only rejections still carry its headers.
 app.add_middleware(
     CORSMiddleware,
     allow_origins=["*"],
@@ -604,14 +725,21 @@ def _prepare_attachment_input(item: Any) -> Optional[Dict[str, Any]]:
         return None
     raw_data = getattr(item, "data_base64", None) or ""
     inferred_mime, payload = _split_data_url(raw_data)
-    mime = (getattr(item, "mime", None) or inferred_mime or "application/octet-stream").strip()
     payload = payload.strip()
-    if not payload:
-        return None
-    try:
-        decoded = base64.b64decode(payload)
-    except Exception:
+    upload_id = getattr(item, "upload_id", None)
+    if payload:
+        try:
+            decoded = base64.b64decode(payload)
+        except Exception:
+            return None
+    elif upload_id:
+        upload = _read_upload(upload_id)
+        if not upload:
+            return None
+        inferred_mime, decoded = upload
+    else:
         return None
+    mime = (getattr(item, "mime", None) or inferred_mime or "application/octet-stream").strip()
 
     width = getattr(item, "width", None)
     height = getattr(item, "height", None)
@@ -822,7 +950,59 @@ async def _maybe_update_session_title(
 
 @app.get("/")
 def read_root():
-    return {"status": "FastAPI is running!", "version": "2.2", "app_config": True}
+    return {"status": "FastAPI is running!", "version": BACKEND_VERSION, "app_config": True}
+
+@app.get("/version")
+def version():
+    return {"version": BACKEND_VERSION, "api": API_VERSION}
+
+@app.get(HEALTH_PATH)
+def health():
+    return {
+        "status": "ok",
+        "uptime_sec": round(time.time() - SERVER_STARTED_AT, 1),
+        **REQUEST_METRICS,
+        **TASK_ORCHESTRATOR.queue_stats(),
+        "idle": background_idle(),
+        "instance": INSTANCE_ID or None,
+        "session": SESSION_ID or None,
+        "locale": current_locale(),
+    }
+
+
+@app.post("/system/idle")
+def set_system_idle(request: SystemIdleRequest):
+    """The shell reports the user away or back; background scans pause meanwhile."""
+    if request.idle != background_idle():
+        print(f"[Idle] User {'away' if request.idle else 'back'}; background work {'paused' if request.idle else 'resumed'}.")
+    set_background_idle(request.idle)
+    return {"idle": request.idle}
+
+@app.post("/system/budget")
+def set_system_budget(request: SystemBudgetRequest):
+    """The shell pauses or resumes LLM calls around the monthly budget's hard stop."""
+    global BUDGET_PAUSED
+    if request.paused != BUDGET_PAUSED:
+        print(f"[Budget] LLM calls {'paused' if request.paused else 'resumed'} by the shell.")
+    BUDGET_PAUSED = request.paused
+    return {"paused": request.paused}
+
+# Set when serving over TCP, so /shutdown can stop uvicorn the way Ctrl+C would.
+UVICORN_SERVER: Optional[uvicorn.Server] = None
+
+
+@app.post("/shutdown")
+def shutdown():
+    """The shell asks for a clean exit before it falls back to killing the process."""
+    print("[Shutdown] Requested by the shell; finishing in-flight requests.")
+    if UVICORN_SERVER is not None:
+        UVICORN_SERVER.should_exit = True
+    else:
+        from stdio_transport import request_shutdown
+
+        if not request_shutdown():
+            return {"status": "unsupported"}
+    return {"status": "stopping"}
 
 @app.get("/__debug/info")
 def debug_info():
@@ -840,6 +1020,10 @@ def debug_info():
 
 @app.websocket("/ws")
 async def websocket_endpoint(websocket: WebSocket):
+    # Browsers cannot set headers on a WebSocket, so the token may come as a query parameter.
+    if not _token_ok(websocket.query_params.get("token") or websocket.headers.get(AUTH_HEADER)):
+        await websocket.close(code=1008)
+        return
     await websocket.accept()
     conn = await WS_HUB.register(websocket)
     try:
@@ -942,6 +1126,15 @@ def create_session(session: ChatSessionCreate):
     _schedule_ast_scan(created.work_path)
     return created
 
+@app.post("/sessions/import", response_model=ChatSession)
+def import_session(payload: SessionImport):
+    if not payload.messages:
+        raise HTTPException(status_code=400, detail="Nothing to import")
+    config = db.get_config(payload.config_id) if payload.config_id else db.get_default_config()
+    if not config:
+        raise HTTPException(status_code=404, detail="Config not found")
+    return db.import_session(payload, config.id)
+
 @app.put("/sessions/{session_id}", response_model=ChatSession)
 def update_session(session_id: str, update: ChatSessionUpdate):
     if update.config_id is not None:
@@ -1216,6 +1409,27 @@ async def cancel_task_route(task_id: str, request: AgentTaskCancelRequest):
             {"task_id": task_id, "error": str(exc)}
         )
 
+
+@app.get("/artifacts/{artifact_id}")
+def get_artifact(artifact_id: int):
+    artifact = db.get_agent_artifact(artifact_id)
+    if not artifact:
+        raise HTTPException(status_code=404, detail="Artifact not found")
+    return artifact
+
+
+@app.get("/artifacts/{artifact_id}/content")
+def get_artifact_content(artifact_id: int):
+    artifact = db.get_agent_artifact(artifact_id)
+    if not artifact:
+        raise HTTPException(status_code=404, detail="Artifact not found")
+    # Only file artifacts have content; others point at sessions or URIs.
+    path = Path(artifact.path) if artifact.path else None
+    if not path or not path.is_file():
+        raise HTTPException(status_code=404, detail="Artifact has no file")
+    mime = mimetypes.guess_type(path.name)[0] or "application/octet-stream"
+    return Response(content=path.read_bytes(), media_type=mime)
+
 # ==================== Attachments ====================
 
 @app.get("/attachments/{attachment_id}")
@@ -1234,6 +1448,175 @@ def get_attachment(attachment_id: int, thumbnail: bool = False, max_size: int =
             mime, data = thumb
     return Response(content=data, media_type=mime)
 
+# Files the shell ingests (such as drops onto the window) are sent here once and
+# referenced from chat requests by their SHA-256 instead of inline base64.
+UPLOAD_ID_PATTERN = re.compile(r"^[0-9a-f]{64}$")
+MAX_UPLOAD_BYTES = 100 * 1024 * 1024
+
+
+def _uploads_dir() -> Path:
+    return Path(os.getenv("TAURI_AGENT_DATA_DIR") or ".") / "uploads"
+
+
+def _read_upload(upload_id: str) -> Optional[Tuple[str, bytes]]:
+    if not UPLOAD_ID_PATTERN.match(upload_id or ""):
+        return None
+    path = _uploads_dir() / upload_id
+    try:
+        meta = json.loads((_uploads_dir() / f"{upload_id}.json").read_text(encoding="utf-8"))
+        return meta.get("mime") or "application/octet-stream", path.read_bytes()
+    except (OSError, ValueError):
+        return None
+
+
+@app.post("/attachments/uploads")
+async def upload_attachment(request: Request, name: Optional[str] = None):
+    data = await request.body()
+    if not data:
+        raise HTTPException(status_code=400, detail="Upload is empty")
+    if len(data) > MAX_UPLOAD_BYTES:
+        raise HTTPException(status_code=413, detail="Upload is too large")
+    upload_id = hashlib.sha256(data).hexdigest()
+    mime = (request.headers.get("content-type") or "application/octet-stream").split(";")[0].strip()
+    meta = {"id": upload_id, "name": name, "mime": mime, "size": len(data)}
+    uploads_dir = _uploads_dir()
+    uploads_dir.mkdir(parents=True, exist_ok=True)
+    path = uploads_dir / upload_id
+    # Content-addressed, so an existing file already holds these bytes.
+    if not path.exists():
+        staged = uploads_dir / f"{upload_id}.tmp"
+        staged.write_bytes(data)
+        os.replace(staged, path)
+    (uploads_dir / f"{upload_id}.json").write_text(json.dumps(meta), encoding="utf-8")
+    return meta
+
+
+@app.get("/attachments/uploads/{upload_id}")
+def get_upload(upload_id: str):
+    upload = _read_upload(upload_id)
+    if not upload:
+        raise HTTPException(status_code=404, detail="Upload not found")
+    mime, data = upload
+    return Response(content=data, media_type=mime)
+
+# ==================== Audio ====================
+# The shell streams the microphone here as 16-bit mono PCM while recording, then
+# asks for the transcript once the user stops.
+TRANSCRIPTION_SESSIONS: Dict[str, Dict[str, Any]] = {}
+# The upload limit of OpenAI's transcription endpoint.
+MAX_TRANSCRIPTION_BYTES = 25 * 1024 * 1024
+TRANSCRIPTION_IDLE_SECONDS = 600
+
+
+class TranscriptionCreate(BaseModel):
+    sample_rate: int = 16000
+    language: Optional[str] = None
+    config_id: Optional[str] = None
+
+
+class TranscriptionChunk(BaseModel):
+    data_base64: str
+
+
+def _pcm_to_wav(pcm: bytes, sample_rate: int) -> bytes:
+    buffer = BytesIO()
+    with wave.open(buffer, "wb") as wav:
+        wav.setnchannels(1)
+        wav.setsampwidth(2)
+        wav.setframerate(sample_rate)
+        wav.writeframes(pcm)
+    return buffer.getvalue()
+
+
+def _transcription_session(session_id: str) -> Dict[str, Any]:
+    session = TRANSCRIPTION_SESSIONS.get(session_id)
+    if not session:
+        raise HTTPException(status_code=404, detail="Transcription session not found")
+    return session
+
+
+@app.post("/audio/transcriptions")
+def create_transcription(request: TranscriptionCreate):
+    # Sessions whose recorder went away without finishing or cancelling.
+    cutoff = time.time() - TRANSCRIPTION_IDLE_SECONDS
+    for stale in [key for key, value in TRANSCRIPTION_SESSIONS.items() if value["updated"] < cutoff]:
+        TRANSCRIPTION_SESSIONS.pop(stale, None)
+    if not 8000 <= request.sample_rate <= 48000:
+        raise HTTPException(status_code=400, detail="Sample rate must be between 8000 and 48000 Hz")
+    session_id = os.urandom(16).hex()
+    TRANSCRIPTION_SESSIONS[session_id] = {
+        "sample_rate": request.sample_rate,
+        "language": request.language or current_locale().split("-")[0],
+        "config_id": request.config_id,
+        "pcm": bytearray(),
+        "updated": time.time(),
+    }
+    return {"id": session_id}
+
+
+@app.post("/audio/transcriptions/{session_id}/chunks")
+def append_transcription_chunk(session_id: str, chunk: TranscriptionChunk):
+    session = _transcription_session(session_id)
+    try:
+        data = base64.b64decode(chunk.data_base64, validate=True)
+    except ValueError:
+        raise HTTPException(status_code=400, detail="Chunk is not valid base64")
+    if len(session["pcm"]) + len(data) > MAX_TRANSCRIPTION_BYTES:
+        raise HTTPException(status_code=413, detail="Recording is too long to transcribe")
+    session["pcm"].extend(data)
+    session["updated"] = time.time()
+    return {"received": len(session["pcm"])}
+
+
+@app.post("/audio/transcriptions/{session_id}/finish")
+async def finish_transcription(session_id: str):
+    session = _transcription_session(session_id)
+    TRANSCRIPTION_SESSIONS.pop(session_id, None)
+    if not session["pcm"]:
+        return {"text": ""}
+    config_id = session.get("config_id")
+    config = db.get_config(config_id) if config_id else db.get_default_config()
+    if not config:
+        raise HTTPException(status_code=404, detail="No config available for transcription")
+    audio = _pcm_to_wav(bytes(session["pcm"]), session["sample_rate"])
+    try:
+        text = await create_llm_client(config).transcribe(audio, language=session["language"])
+    except httpx.HTTPError as exc:
+        raise HTTPException(status_code=502, detail=f"Transcription failed: {exc}")
+    return {"text": text}
+
+
+@app.post("/audio/transcriptions/{session_id}/cancel")
+def cancel_transcription(session_id: str):
+    return {"cancelled": TRANSCRIPTION_SESSIONS.pop(session_id, None) is not None}
+
+
+# The provider's limit for one request.
+MAX_SPEECH_CHARS = 4096
+
+
+class SpeechRequest(BaseModel):
+    text: str
+    voice: Optional[str] = None
+    config_id: Optional[str] = None
+
+
+@app.post("/audio/speech")
+async def synthesize_speech(request: SpeechRequest):
+    text = request.text.strip()
+    if not text:
+        raise HTTPException(status_code=400, detail="There is nothing to speak")
+    if len(text) > MAX_SPEECH_CHARS:
+        raise HTTPException(status_code=400, detail=f"Text is limited to {MAX_SPEECH_CHARS} characters")
+    config = db.get_config(request.config_id) if request.config_id else db.get_default_config()
+    if not config:
+        raise HTTPException(status_code=404, detail="No config available for speech")
+    try:
+        audio = await create_llm_client(config).speak(text, voice=request.voice)
+    except httpx.HTTPError as exc:
+        raise HTTPException(status_code=502, detail=f"Speech synthesis failed: {exc}")
+    return Response(content=audio, media_type="audio/wav")
+
 # ==================== Chat ====================
 
 @app.post("/chat", response_model=ChatResponse)
@@ -2867,13 +3250,64 @@ def update_tool_permission(request_id: int, update: ToolPermissionRequestUpdate)
                     pass
     return updated
 
+def _write_pid_file(host: str, port: Optional[int]) -> None:
+    if not PID_FILE:
+        return
+    record = {
+        "pid": os.getpid(),
+        "host": host,
+        "port": port,
+        "instance": INSTANCE_ID or None,
+        "token": AUTH_TOKEN or None,
+        "started_at": SERVER_STARTED_AT,
+    }
+    try:
+        # It holds the auth token, so only this user may read it.
+        fd = os.open(PID_FILE, os.O_WRONLY | os.O_CREAT | os.O_TRUNC, 0o600)
+        with os.fdopen(fd, "w", encoding="utf-8") as handle:
+            json.dump(record, handle)
+        atexit.register(_remove_pid_file)
+    except Exception as exc:
+        print(f"[PID] Failed to write {PID_FILE}: {exc}")
+
+
+def _remove_pid_file() -> None:
+    try:
+        with open(PID_FILE, "r", encoding="utf-8") as handle:
+            owner = json.load(handle).get("pid")
+        # A restarted sidecar may already have written its own.
+        if owner == os.getpid():
+            os.remove(PID_FILE)
+    except Exception:
+        pass
+
+
 if __name__ == "__main__":
     parser = argparse.ArgumentParser(description="Tauri Agent Backend")
     parser.add_argument("--host", default="127.0.0.1")
     parser.add_argument("--port", type=int, default=8000)
     parser.add_argument("--reload", action="store_true")
+    parser.add_argument("--stdio", action="store_true", help="Serve over stdin/stdout instead of TCP")
     args = parser.parse_args()
     print("Starting FastAPI server...")
     print("Supported LLMs: OpenAI, ZhipuAI, Deepseek")
     print(f"Database: SQLite ({os.getenv('TAURI_AGENT_DB_PATH', 'chat_app.db')})")
-    uvicorn.run(app, host=args.host, port=args.port, reload=args.reload)
+    debug_port = os.getenv("TAURI_AGENT_DEBUGPY_PORT", "").strip()
+    if debug_port:
+        try:
+            import debugpy
+
+            debugpy.listen((args.host, int(debug_port)))
+            print(f"[DEBUGPY] Listening on {args.host}:{debug_port}")
+        except Exception as exc:
+            print(f"[DEBUGPY] Failed to start debugger: {exc}")
+    _write_pid_file(args.host, None if args.stdio else args.port)
+    if args.stdio:
+        from stdio_transport import serve
+
+        asyncio.run(serve(app))
+    elif args.reload:
+        uvicorn.run(app, host=args.host, port=args.port, reload=True)
+    else:
+        UVICORN_SERVER = uvicorn.Server(uvicorn.Config(app, host=args.host, port=args.port))
+        UVICORN_SERVER.run()
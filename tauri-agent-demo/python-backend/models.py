@@ -122,10 +122,26 @@ class ChatSessionUpdate(BaseModel):
     parent_session_id: Optional[str] = None
 
 
+class ImportedMessage(BaseModel):
+    role: Literal["user", "assistant"]
+    content: str
+    timestamp: Optional[str] = None
+    metadata: Optional[Dict[str, Any]] = None
+
+
+class SessionImport(BaseModel):
+    title: str
+    config_id: Optional[str] = None
+    created_at: Optional[str] = None
+    messages: List[ImportedMessage]
+
+
 class AttachmentInput(BaseModel):
     name: Optional[str] = None
     mime: Optional[str] = None
-    data_base64: str
+    data_base64: Optional[str] = None
+    # Set instead of data_base64 for a file already sent to /attachments/uploads.
+    upload_id: Optional[str] = None
     width: Optional[int] = None
     height: Optional[int] = None
     size: Optional[int] = None
@@ -207,6 +223,14 @@ class AstRequest(BaseModel):
     agent_mode: Optional[AgentMode] = None
 
 
+class SystemIdleRequest(BaseModel):
+    idle: bool
+
+
+class SystemBudgetRequest(BaseModel):
+    paused: bool
+
+
 class AstNotifyRequest(BaseModel):
     root: str
     paths: Optional[List[str]] = None
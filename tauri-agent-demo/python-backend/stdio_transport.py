@@ -0,0 +1,198 @@
+"""Serve the FastAPI app over stdin/stdout instead of a TCP socket.
+
+Frames are a 4-byte big-endian length followed by a UTF-8 JSON-RPC 2.0 message.
+The shell sends `http.request` calls carrying one HTTP request each; the backend
+answers with the full response and pushes WebSocket hub events as `event`
+notifications.
+"""
+import asyncio
+import base64
+import json
+import os
+import struct
+import sys
+import threading
+from typing import Any, BinaryIO, Callable, Dict, List, Optional, Tuple
+
+from ws_hub import get_ws_hub
+
+
+MAX_FRAME_BYTES = 256 * 1024 * 1024
+# Tells the shell to stop treating stdout as log lines; must match rpc.rs.
+HANDSHAKE = b"GYY-RPC/1"
+# How long in-flight requests get to finish once the shell asks for a shutdown.
+SHUTDOWN_GRACE_SEC = 3.0
+
+_SHUTDOWN = object()
+_shutdown_hook: Optional[Callable[[], None]] = None
+
+
+class FrameChannel:
+    def __init__(self, reader: BinaryIO, writer: BinaryIO) -> None:
+        self._reader = reader
+        self._writer = writer
+        self._write_lock = threading.Lock()
+
+    def read(self) -> Optional[Dict[str, Any]]:
+        header = self._reader.read(4)
+        if len(header) < 4:
+            return None
+        (length,) = struct.unpack(">I", header)
+        if length > MAX_FRAME_BYTES:
+            raise ValueError(f"Frame of {length} bytes is too large")
+        body = self._reader.read(length)
+        if len(body) < length:
+            return None
+        return json.loads(body.decode("utf-8"))
+
+    def write(self, message: Dict[str, Any]) -> None:
+        body = json.dumps(message, ensure_ascii=False).encode("utf-8")
+        with self._write_lock:
+            self._writer.write(struct.pack(">I", len(body)) + body)
+            self._writer.flush()
+
+
+def _take_stdio() -> FrameChannel:
+    # Everything else in the backend prints to stdout; keep that off the channel.
+    reader = os.fdopen(os.dup(sys.stdin.fileno()), "rb", buffering=0)
+    writer = os.fdopen(os.dup(sys.stdout.fileno()), "wb")
+    sys.stdout.flush()
+    os.dup2(sys.stderr.fileno(), sys.stdout.fileno())
+    sys.stdout = sys.stderr
+    writer.write(b"\n" + HANDSHAKE + b"\n")
+    writer.flush()
+    return FrameChannel(reader, writer)
+
+
+async def _call_app(app: Any, params: Dict[str, Any]) -> Dict[str, Any]:
+    method = str(params.get("method") or "GET").upper()
+    path = str(params.get("path") or "/")
+    query = str(params.get("query") or "")
+    headers: List[Tuple[bytes, bytes]] = [
+        (str(name).lower().encode("latin-1"), str(value).encode("latin-1"))
+        for name, value in params.get("headers") or []
+    ]
+    body = base64.b64decode(params.get("body") or "")
+    scope = {
+        "type": "http",
+        "asgi": {"version": "3.0"},
+        "http_version": "1.1",
+        "method": method,
+        "scheme": "http",
+        "path": path,
+        "raw_path": path.encode("utf-8"),
+        "query_string": query.encode("latin-1"),
+        "root_path": "",
+        "headers": headers,
+        "client": ("stdio", 0),
+        "server": ("stdio", 0),
+    }
+    request_sent = False
+
+    async def receive() -> Dict[str, Any]:
+        nonlocal request_sent
+        if not request_sent:
+            request_sent = True
+            return {"type": "http.request", "body": body, "more_body": False}
+        # Streaming responses wait here until they finish; never report a disconnect.
+        await asyncio.Event().wait()
+        return {"type": "http.disconnect"}
+
+    status = 500
+    response_headers: List[List[str]] = []
+    chunks: List[bytes] = []
+
+    async def send(message: Dict[str, Any]) -> None:
+        nonlocal status, response_headers
+        if message["type"] == "http.response.start":
+            status = int(message["status"])
+            response_headers = [
+                [name.decode("latin-1"), value.decode("latin-1")]
+                for name, value in message.get("headers") or []
+            ]
+        elif message["type"] == "http.response.body":
+            chunks.append(message.get("body") or b"")
+
+    await app(scope, receive, send)
+    return {
+        "status": status,
+        "headers": response_headers,
+        "body": base64.b64encode(b"".join(chunks)).decode("ascii"),
+    }
+
+
+async def _handle(app: Any, channel: FrameChannel, message: Dict[str, Any]) -> None:
+    request_id = message.get("id")
+    if message.get("method") != "http.request":
+        if request_id is not None:
+            channel.write({
+                "jsonrpc": "2.0",
+                "id": request_id,
+                "error": {"code": -32601, "message": f"Unknown method {message.get('method')}"},
+            })
+        return
+    try:
+        result = await _call_app(app, message.get("params") or {})
+        reply: Dict[str, Any] = {"jsonrpc": "2.0", "id": request_id, "result": result}
+    except Exception as exc:
+        reply = {"jsonrpc": "2.0", "id": request_id, "error": {"code": -32000, "message": str(exc)}}
+    channel.write(reply)
+
+
+def request_shutdown() -> bool:
+    """Stops serving once in-flight requests finish; False when not serving over stdio."""
+    hook = _shutdown_hook
+    if hook is None:
+        return False
+    hook()
+    return True
+
+
+def _pump(channel: FrameChannel, loop: asyncio.AbstractEventLoop, inbox: "asyncio.Queue[Any]") -> None:
+    # A daemon thread rather than the default executor, so a blocked read does not
+    # hold up interpreter exit after a requested shutdown.
+    while True:
+        try:
+            message = channel.read()
+        except ValueError as exc:
+            print(f"[STDIO] Dropping malformed frame: {exc}")
+            continue
+        loop.call_soon_threadsafe(inbox.put_nowait, message)
+        if message is None:
+            return
+
+
+async def serve(app: Any) -> None:
+    global _shutdown_hook
+    channel = _take_stdio()
+    loop = asyncio.get_running_loop()
+    inbox: "asyncio.Queue[Any]" = asyncio.Queue()
+    _shutdown_hook = lambda: loop.call_soon_threadsafe(inbox.put_nowait, _SHUTDOWN)
+
+    def forward_event(session_id: str, payload: Dict[str, Any]) -> None:
+        channel.write({
+            "jsonrpc": "2.0",
+            "method": "event",
+            "params": {"session_id": session_id, "payload": payload},
+        })
+
+    get_ws_hub().add_listener(forward_event)
+    async with app.router.lifespan_context(app):
+        print("[STDIO] Serving requests over stdin/stdout")
+        pending = set()
+        threading.Thread(target=_pump, args=(channel, loop, inbox), daemon=True).start()
+        while True:
+            message = await inbox.get()
+            if message is None:
+                print("[STDIO] Shell closed the channel; shutting down")
+                break
+            if message is _SHUTDOWN:
+                print("[STDIO] Shutdown requested; finishing in-flight requests")
+                if pending:
+                    await asyncio.wait(set(pending), timeout=SHUTDOWN_GRACE_SEC)
+                break
+            task = asyncio.create_task(_handle(app, channel, message))
+            pending.add(task)
+            task.add_done_callback(pending.discard)
+        for task in pending:
+            task.cancel()
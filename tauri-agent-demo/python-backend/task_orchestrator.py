@@ -132,6 +132,9 @@ class TaskOrchestrator:
                 pass
         self._dispatcher_task = None
 
+    def queue_stats(self) -> Dict[str, int]:
+        return {"queued": self._queue.qsize(), "running": self._running_total}
+
     async def enqueue_task(self, task_id: str) -> None:
         if not task_id:
             return
@@ -0,0 +1,56 @@
+import os
+import re
+import shutil
+import tempfile
+import threading
+from pathlib import Path
+from typing import Dict, Optional
+
+from tools.context import get_tool_context
+
+# The shell points this at its own scratch folder and empties it on exit and startup.
+_ROOT_ENV = "TAURI_AGENT_TEMP_DIR"
+_UNSAFE_CHARS = re.compile(r"[^A-Za-z0-9_.-]+")
+
+_lock = threading.Lock()
+_allocated: Dict[str, Path] = {}
+
+
+def temp_root() -> Path:
+    configured = os.getenv(_ROOT_ENV, "").strip()
+    root = Path(configured) if configured else Path(tempfile.gettempdir()) / "tauri-agent"
+    root.mkdir(parents=True, exist_ok=True)
+    return root
+
+
+def current_owner() -> Optional[str]:
+    """The task (or, outside the task center, the chat turn) the running tool belongs to."""
+    context = get_tool_context()
+    if context.get("task_id"):
+        return f"task-{context['task_id']}"
+    if context.get("message_id") is not None:
+        return f"message-{context['message_id']}"
+    return None
+
+
+def task_temp_dir(namespace: str, owner: Optional[str] = None) -> Path:
+    """A fresh dir for `namespace` (e.g. "pdf", "archive"), removed when its owner finishes.
+
+    Without an owner the dir lives until the shell clears the scratch root.
+    """
+    owner = owner or current_owner() or "unowned"
+    owner_dir = temp_root() / _UNSAFE_CHARS.sub("_", owner)
+    namespace_dir = owner_dir / _UNSAFE_CHARS.sub("_", namespace or "misc")
+    namespace_dir.mkdir(parents=True, exist_ok=True)
+    with _lock:
+        _allocated[owner] = owner_dir
+    return Path(tempfile.mkdtemp(dir=namespace_dir))
+
+
+def release_owner(owner: Optional[str]) -> None:
+    if not owner:
+        return
+    with _lock:
+        owner_dir = _allocated.pop(owner, None)
+    if owner_dir is not None:
+        shutil.rmtree(owner_dir, ignore_errors=True)
@@ -8,12 +8,23 @@ This module provides basic tools that are included by default:
 - ReadFileTool / WriteFileTool: Project file access
 - ListFilesTool: Directory listing (BFS tree output)
 - RunShellTool: Shell execution with allowlist
+- Native tools: clipboard, screenshot, file search and commands run by the desktop shell
+- Plugin tools: tools of the plugins installed from the desktop shell
 """
 
 from ..base import Tool, ToolParameter
 from ..config import is_tool_enabled
 from .system_tools import ReadFileTool, WriteFileTool, RunShellTool, TavilySearchTool, RgTool, ApplyPatchTool, CodeAstTool, ListFilesTool
 from .subagent_tool import SpawnSubagentTool, SpawnSubagentsParallelTool
+from .native_tools import (
+    ClipboardReadTool,
+    ClipboardWriteTool,
+    ScreenshotTool,
+    NativeFileSearchTool,
+    NativeCommandTool,
+)
+from ..tool_host import is_tool_host_available
+from .plugin_tools import plugin_tools_from_config
 
 
 class CalculatorTool(Tool):
@@ -129,3 +140,19 @@ def register_builtin_tools():
         ToolRegistry.register(SpawnSubagentTool())
     if is_tool_enabled("spawn_subagents_parallel"):
         ToolRegistry.register(SpawnSubagentsParallelTool())
+    # Only offered when the desktop shell started this backend with its tool host.
+    if is_tool_host_available():
+        for tool in (
+            ClipboardReadTool(),
+            ClipboardWriteTool(),
+            ScreenshotTool(),
+            NativeFileSearchTool(),
+            NativeCommandTool(),
+        ):
+            if is_tool_enabled(tool.name):
+                ToolRegistry.register(tool)
+    for tool in plugin_tools_from_config():
+        if ToolRegistry.get(tool.name) is None:
+            ToolRegistry.register(tool)
+        else:
+            print(f"[Plugins] Skipping {tool.name}: a tool with that name is already registered.")
@@ -0,0 +1,145 @@
+import asyncio
+import json
+from typing import Any, Dict
+
+from ..base import Tool, ToolParameter
+from ..tool_host import call_native_tool
+from .system_tools import _get_root_path, _parse_json_input
+
+
+class NativeTool(Tool):
+    """A capability of the desktop shell, run there after its permission check."""
+
+    host_tool = ""
+
+    def build_args(self, data: Dict[str, Any]) -> Dict[str, Any]:
+        return data
+
+    async def execute(self, input_data: str) -> str:
+        args = self.build_args(_parse_json_input(input_data))
+        try:
+            result = await call_native_tool(self.host_tool, args, str(_get_root_path()))
+        except asyncio.TimeoutError:
+            return "Native tool call timed out waiting for the desktop app."
+        except Exception as exc:
+            return f"Native tool call failed: {exc}"
+        return json.dumps(result, ensure_ascii=False, indent=2)
+
+
+class ClipboardReadTool(NativeTool):
+    host_tool = "clipboard_read"
+
+    def __init__(self):
+        super().__init__()
+        self.name = "clipboard_read"
+        self.description = "Read the text currently on the user's clipboard. The user may be asked to allow it."
+        self.parameters = []
+
+
+class ClipboardWriteTool(NativeTool):
+    host_tool = "clipboard_write"
+
+    def __init__(self):
+        super().__init__()
+        self.name = "clipboard_write"
+        self.description = "Put text on the user's clipboard. The user may be asked to allow it."
+        self.parameters = [
+            ToolParameter(
+                name="text",
+                type="string",
+                description="Text to copy.",
+                required=True
+            )
+        ]
+
+    def build_args(self, data: Dict[str, Any]) -> Dict[str, Any]:
+        return {"text": str(data.get("text") or "")}
+
+
+class ScreenshotTool(NativeTool):
+    host_tool = "screenshot"
+
+    def __init__(self):
+        super().__init__()
+        self.name = "screenshot"
+        self.description = (
+            "Capture the user's primary screen as a PNG and return the file path. "
+            "The user may be asked to allow it."
+        )
+        self.parameters = []
+
+
+class NativeFileSearchTool(NativeTool):
+    host_tool = "file_search"
+
+    def __init__(self):
+        super().__init__()
+        self.name = "native_file_search"
+        self.description = (
+            "Find files anywhere on the user's computer whose names contain a query. "
+            "Hidden folders and build output are skipped. The user may be asked to allow it."
+        )
+        self.parameters = [
+            ToolParameter(
+                name="root",
+                type="string",
+                description="Absolute path of the folder to search under.",
+                required=True
+            ),
+            ToolParameter(
+                name="query",
+                type="string",
+                description="Case-insensitive part of the file name.",
+                required=True
+            ),
+            ToolParameter(
+                name="max_results",
+                type="number",
+                description="Max number of matches (default and max 200).",
+                required=False
+            )
+        ]
+
+    def build_args(self, data: Dict[str, Any]) -> Dict[str, Any]:
+        args: Dict[str, Any] = {
+            "root": str(data.get("root") or ""),
+            "query": str(data.get("query") or ""),
+        }
+        try:
+            args["max_results"] = int(data["max_results"])
+        except (KeyError, TypeError, ValueError):
+            pass
+        return args
+
+
+class NativeCommandTool(NativeTool):
+    host_tool = "run_command"
+
+    def __init__(self):
+        super().__init__()
+        self.name = "native_command"
+        self.description = (
+            "Run a shell command on the user's computer outside the project sandbox. "
+            "The user is asked to approve each command before it runs; approvals are never remembered."
+        )
+        self.parameters = [
+            ToolParameter(
+                name="command",
+                type="string",
+                description="Command line to run.",
+                required=True
+            ),
+            ToolParameter(
+                name="cwd",
+                type="string",
+                description="Absolute working directory (optional).",
+                required=False
+            )
+        ]
+
+    def build_args(self, data: Dict[str, Any]) -> Dict[str, Any]:
+        args: Dict[str, Any] = {"command": str(data.get("command") or "")}
+        cwd = str(data.get("cwd") or "").strip()
+        if cwd:
+            args["cwd"] = cwd
+        return args
@@ -0,0 +1,100 @@
+import asyncio
+import json
+import shutil
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from ..base import Tool, ToolParameter
+from ..config import get_tool_config, is_tool_enabled
+from .system_tools import _get_root_path, _parse_json_input
+
+PLUGIN_TIMEOUT_SEC = 60.0
+MAX_PLUGIN_OUTPUT_CHARS = 100_000
+
+
+def _plugin_command(plugin: Dict[str, Any]) -> Optional[List[str]]:
+    """How to start a plugin's entry, or None when it cannot run here."""
+    entry = Path(str(plugin.get("path") or ""))
+    if not entry.is_file():
+        return None
+    if plugin.get("kind") == "wasm":
+        runtime = shutil.which("wasmtime")
+        if not runtime:
+            return None
+        return [runtime, "run", "--dir", str(_get_root_path()), str(entry)]
+    return [str(entry)]
+
+
+class PluginTool(Tool):
+    """
+    A tool provided by an installed plugin. The entry is started with the tool
+    name as its only argument, reads the arguments as JSON on stdin and writes
+    the result to stdout.
+    """
+
+    def __init__(self, plugin: Dict[str, Any], tool_name: str, command: List[str]):
+        super().__init__()
+        self.command = command
+        self.name = tool_name
+        label = str(plugin.get("name") or plugin.get("id") or "plugin")
+        description = str(plugin.get("description") or "").strip()
+        self.description = f"{tool_name} from the {label} plugin."
+        if description:
+            self.description = f"{self.description} {description}"
+        self.parameters = [
+            ToolParameter(
+                name="arguments",
+                type="object",
+                description="Plugin tool arguments.",
+                required=False
+            )
+        ]
+
+    async def execute(self, input_data: str) -> str:
+        data = _parse_json_input(input_data)
+        args = data.get("arguments") if isinstance(data.get("arguments"), dict) else data
+        try:
+            proc = await asyncio.create_subprocess_exec(
+                *self.command,
+                self.name,
+                cwd=str(_get_root_path()),
+                stdin=asyncio.subprocess.PIPE,
+                stdout=asyncio.subprocess.PIPE,
+                stderr=asyncio.subprocess.PIPE,
+            )
+        except OSError as exc:
+            return f"Plugin tool failed to start: {exc}"
+        payload = json.dumps(args, ensure_ascii=False).encode("utf-8")
+        try:
+            stdout, stderr = await asyncio.wait_for(proc.communicate(payload), timeout=PLUGIN_TIMEOUT_SEC)
+        except asyncio.TimeoutError:
+            proc.kill()
+            await proc.wait()
+            return f"Plugin tool timed out after {PLUGIN_TIMEOUT_SEC:.0f}s."
+        output = stdout.decode("utf-8", errors="replace")
+        if proc.returncode != 0:
+            error = stderr.decode("utf-8", errors="replace").strip() or output.strip()
+            output = f"Plugin tool exited with code {proc.returncode}: {error}"
+        if len(output) > MAX_PLUGIN_OUTPUT_CHARS:
+            output = output[:MAX_PLUGIN_OUTPUT_CHARS] + "\n... (output truncated)"
+        return output
+
+
+def plugin_tools_from_config() -> List[PluginTool]:
+    """Tools of the enabled plugins registered under `plugins` in the tools config."""
+    plugins = get_tool_config().get("plugins", {})
+    if not isinstance(plugins, dict):
+        return []
+    tools: List[PluginTool] = []
+    for plugin_id, plugin in plugins.items():
+        if not isinstance(plugin, dict) or not plugin.get("enabled"):
+            continue
+        command = _plugin_command(plugin)
+        if command is None:
+            print(f"[Plugins] Skipping {plugin_id}: its entry cannot run here.")
+            continue
+        for tool_name in plugin.get("tools") or []:
+            tool_name = str(tool_name).strip()
+            if tool_name and is_tool_enabled(tool_name):
+                tools.append(PluginTool(plugin, tool_name, command))
+    return tools
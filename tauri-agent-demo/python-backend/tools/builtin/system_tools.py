@@ -19,6 +19,7 @@ import httpx
 from ..base import Tool, ToolParameter
 from ..config import get_tool_config, update_tool_config
 from ..context import get_tool_context
+from ..tool_host import request_shell_permission
 from ..pty_manager import (
     get_pty_manager,
     PtyProcess,
@@ -112,6 +113,13 @@ def _is_within_root(path: Path, root: Path) -> bool:
     return path_str.startswith(root_str + os.sep)
 
 
+def _get_quarantine_dir() -> Optional[Path]:
+    raw = os.getenv("TAURI_AGENT_QUARANTINE_DIR", "").strip()
+    if not raw:
+        return None
+    return Path(raw).expanduser().resolve()
+
+
 def _log_permission_request(tool_name: str, action: str, path: Path, reason: str) -> Optional[int]:
     try:
         from database import db
@@ -152,6 +160,9 @@ def _resolve_path(raw_path: str, tool_name: str, action: str) -> Path:
     if not path.is_absolute():
         path = root / path
     path = path.resolve()
+    quarantine_dir = _get_quarantine_dir()
+    if quarantine_dir and _is_within_root(path, quarantine_dir):
+        raise PermissionError("This file was quarantined by the attachment scan and cannot be read.")
     if not any(_is_within_root(path, allowed_root) for allowed_root in roots):
         mode = _get_agent_mode()
         if mode == "super":
@@ -3396,8 +3407,10 @@ class WriteFileTool(Tool):
             raise ValueError("Missing path or content.")
         mode = (data.get("mode") or "write").lower()
         encoding = data.get("encoding") or "utf-8"
-        _maybe_create_snapshot()
         file_path = _resolve_path(str(path), self.name, "write")
+        if not await request_shell_permission(self.name, str(_get_root_path()), f"{mode}: {file_path}"):
+            return "Permission denied."
+        _maybe_create_snapshot()
         file_path.parent.mkdir(parents=True, exist_ok=True)
         file_mode = "a" if mode == "append" else "w"
         with open(file_path, file_mode, encoding=encoding) as f:
@@ -3553,6 +3566,8 @@ class RunShellTool(Tool):
                 return "PTY not found."
             if action == "send":
                 stdin_value = data.get("stdin")
+                if stdin_value and not await request_shell_permission(self.name, str(_get_root_path()), str(stdin_value)):
+                    return "Permission denied."
                 if _is_windows() and isinstance(stdin_value, str) and stdin_value:
                     stdin_value = _normalize_windows_stdin(stdin_value)
                 if _pty_debug_enabled():
@@ -3719,6 +3734,12 @@ class RunShellTool(Tool):
             if status == "approved" and agent_mode == "default" and not shell_unrestricted and not persistent_bootstrap and cmd_name not in allowset:
                 _ensure_shell_allowlist_entry(cmd_name)
 
+        # The desktop shell has the final say over anything that runs; an empty
+        # persistent shell runs nothing until input is sent to it, which asks then.
+        if not persistent_bootstrap:
+            if not await request_shell_permission(self.name, str(root), command_text):
+                return "Permission denied."
+
         # NOTE: We intentionally do not enforce the "work path" restriction on
         # `cwd` for `run_shell`. The shell sandbox/unrestricted allowlist already
         # gates what can be executed; blocking `cwd` outside work_path makes it
@@ -4129,6 +4150,8 @@ class ApplyPatchTool(Tool):
         patch_text = data.get("patch") or input_data
         if not patch_text:
             raise ValueError("Missing patch content.")
+        if not await request_shell_permission(self.name, str(_get_root_path()), str(patch_text)):
+            return json.dumps({"ok": False, "error": "Permission denied."}, ensure_ascii=False)
         try:
             _maybe_create_snapshot()
             result = _apply_patch_text(patch_text)
@@ -3,6 +3,8 @@ import os
 from pathlib import Path
 from typing import Any, Dict
 
+from atomic_io import atomic_write_text, recover_temp_file
+
 
 _DEFAULT_CONFIG: Dict[str, Any] = {
     "enabled": {
@@ -16,6 +18,11 @@ _DEFAULT_CONFIG: Dict[str, Any] = {
         "code_ast": True,
         "spawn_subagent": True,
         "spawn_subagents_parallel": True,
+        "clipboard_read": True,
+        "clipboard_write": True,
+        "screenshot": True,
+        "native_file_search": True,
+        "native_command": True,
         "calculator": False,
         "weather": False
     },
@@ -107,6 +114,7 @@ def get_tool_config_path() -> str:
 
 def _load_config_file() -> Dict[str, Any]:
     path = _get_config_file_path()
+    recover_temp_file(path)
     if path.exists():
         return json.loads(path.read_text(encoding="utf-8"))
     return {}
@@ -139,8 +147,7 @@ def update_tool_config(patch: Dict[str, Any]) -> Dict[str, Any]:
     current_file = _load_config_file()
     merged_file = _deep_merge(current_file, patch)
     path = _get_config_file_path()
-    path.parent.mkdir(parents=True, exist_ok=True)
-    path.write_text(json.dumps(merged_file, ensure_ascii=False, indent=2), encoding="utf-8")
+    atomic_write_text(path, json.dumps(merged_file, ensure_ascii=False, indent=2))
     _TOOL_CONFIG = _load_config()
     return _TOOL_CONFIG
 
@@ -0,0 +1,77 @@
+import asyncio
+import json
+import logging
+import os
+from typing import Any, Dict, Optional, Tuple
+
+
+logger = logging.getLogger(__name__)
+
+TOOL_HOST_ENV = "TAURI_AGENT_TOOL_HOST"
+TOOL_HOST_TOKEN_ENV = "TAURI_AGENT_TOOL_HOST_TOKEN"
+# Calls can wait on a permission dialog, so allow the user time to answer.
+_CALL_TIMEOUT_SEC = 300
+_MAX_REPLY_BYTES = 4 * 1024 * 1024
+
+
+def _tool_host() -> Optional[Tuple[str, int, str]]:
+    address = os.getenv(TOOL_HOST_ENV, "").strip()
+    token = os.getenv(TOOL_HOST_TOKEN_ENV, "").strip()
+    if not address or not token or ":" not in address:
+        return None
+    host, _, port = address.rpartition(":")
+    try:
+        return host, int(port), token
+    except ValueError:
+        return None
+
+
+def is_tool_host_available() -> bool:
+    return _tool_host() is not None
+
+
+async def call_native_tool(tool: str, args: Dict[str, Any], workspace: str = "") -> Any:
+    """Send one call to the desktop shell's tool host and return its result.
+
+    `workspace` scopes the shell's "always allow" grants for the call."""
+    target = _tool_host()
+    if target is None:
+        raise RuntimeError("The desktop tool host is not available.")
+    host, port, token = target
+    reader, writer = await asyncio.open_connection(host, port, limit=_MAX_REPLY_BYTES)
+    try:
+        request = json.dumps(
+            {"token": token, "tool": tool, "args": args, "workspace": workspace},
+            ensure_ascii=False,
+        )
+        writer.write(request.encode("utf-8") + b"\n")
+        await writer.drain()
+        line = await asyncio.wait_for(reader.readline(), timeout=_CALL_TIMEOUT_SEC)
+    finally:
+        writer.close()
+        try:
+            await writer.wait_closed()
+        except Exception:
+            pass
+    if not line:
+        raise RuntimeError("The desktop tool host closed the connection.")
+    reply = json.loads(line.decode("utf-8"))
+    if not reply.get("ok"):
+        raise RuntimeError(str(reply.get("error") or "Native tool call failed."))
+    return reply.get("result")
+
+
+async def request_shell_permission(tool: str, workspace: str, detail: str) -> bool:
+    """Ask the shell whether `tool` may run; True when no shell is watching."""
+    if not is_tool_host_available():
+        return True
+    try:
+        result = await call_native_tool(
+            "request_permission",
+            {"tool": tool, "workspace": workspace, "detail": detail},
+        )
+    except Exception as exc:
+        logger.warning("Permission request failed: %s", exc)
+        return False
+    verdict = result.get("verdict") if isinstance(result, dict) else None
+    return verdict in ("allowed", "granted")
@@ -1,7 +1,7 @@
 import asyncio
 import uuid
 from dataclasses import dataclass, field
-from typing import Any, Dict, Optional, Set, List
+from typing import Any, Callable, Dict, Optional, Set, List
 
 from fastapi import WebSocket
 
@@ -21,10 +21,15 @@ class WsHub:
         self._connections: Dict[str, WsConnection] = {}
         self._lock = asyncio.Lock()
         self._loop: Optional[asyncio.AbstractEventLoop] = None
+        self._listeners: List[Callable[[str, Dict[str, Any]], None]] = []
 
     def set_loop(self, loop: asyncio.AbstractEventLoop) -> None:
         self._loop = loop
 
+    def add_listener(self, listener: Callable[[str, Dict[str, Any]], None]) -> None:
+        """Receives every emitted payload, whoever is subscribed (the stdio transport)."""
+        self._listeners.append(listener)
+
     async def register(self, websocket: WebSocket) -> WsConnection:
         conn = WsConnection(
             id=uuid.uuid4().hex[:12],
@@ -77,6 +82,11 @@ class WsHub:
     async def emit(self, session_id: str, payload: Dict[str, Any]) -> None:
         if not session_id:
             return
+        for listener in list(self._listeners):
+            try:
+                listener(session_id, payload)
+            except Exception as exc:
+                print(f"[WS] Event listener failed: {exc}")
         async with self._lock:
             candidates = list(self._connections.values())
         if not candidates:
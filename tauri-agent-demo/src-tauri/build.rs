@@ -1,5 +1,50 @@
+use std::{fs, io};
+
+use sha2::{Digest, Sha256};
+
+/// Where `bundle.externalBin` takes the backend from.
+const SIDECAR_DIR: &str = "../python-backend/target";
+const SIDECAR_BINARY: &str = "tauri-agent-backend";
+
+/// SHA-256 of every backend binary the bundle can ship, so the shell can refuse one
+/// that was swapped after the build.
+fn sidecar_digests() -> io::Result<Vec<String>> {
+    println!("cargo:rerun-if-changed={SIDECAR_DIR}");
+    let Ok(entries) = fs::read_dir(SIDECAR_DIR) else {
+        return Ok(Vec::new());
+    };
+    let mut digests = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_sidecar = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(SIDECAR_BINARY));
+        if !is_sidecar || !path.is_file() {
+            continue;
+        }
+        println!("cargo:rerun-if-changed={}", path.display());
+        let mut hasher = Sha256::new();
+        io::copy(&mut fs::File::open(&path)?, &mut hasher)?;
+        let digest: String = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        digests.push(digest);
+    }
+    digests.sort();
+    digests.dedup();
+    Ok(digests)
+}
+
 fn main() {
     println!("cargo:rerun-if-changed=windows-app-manifest.xml");
+    let digests = sidecar_digests().expect("failed to hash the backend sidecar");
+    println!(
+        "cargo:rustc-env=TAURI_AGENT_SIDECAR_SHA256={}",
+        digests.join(",")
+    );
     let windows = tauri_build::WindowsAttributes::new()
         .app_manifest(include_str!("windows-app-manifest.xml"));
     let attrs = tauri_build::Attributes::new().windows_attributes(windows);
@@ -0,0 +1,365 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    encryption,
+    error::{AppError, ErrorCode},
+    events, idle, quick_ask,
+    settings::SettingsStore,
+    tray, watchdog,
+};
+
+/// How often the idle time is checked against the timeout.
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+const MAX_AUTO_LOCK_MINUTES: u32 = 24 * 60;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const UNLOCK_REASON: &str = "unlock GYY";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LockSettings {
+    /// Minutes without input before the app locks; `None` turns auto-lock off.
+    pub auto_lock_minutes: Option<u32>,
+    /// Also stop the agent runs in flight when locking, so nothing keeps working
+    /// unattended.
+    pub stop_runs: bool,
+}
+
+events::payload_enum! {
+    pub enum LockReason {
+        Idle,
+        /// `lock_now`, or `lock_app` with the database key.
+        Manual,
+    }
+}
+
+events::payload! {
+    pub struct LockEvent {
+        reason: LockReason,
+    }
+}
+
+/// Whether the windows are locked. Not persisted: a fresh launch starts unlocked.
+#[derive(Default)]
+pub struct AppLock {
+    locked: AtomicBool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LockStatus {
+    locked: bool,
+    auto_lock_minutes: Option<u32>,
+    stop_runs: bool,
+    /// The OS can verify the user, one way to unlock again.
+    verification_available: bool,
+    /// The database passphrase is set, the other way to unlock.
+    passphrase_available: bool,
+}
+
+fn settings<R: Runtime>(app: &AppHandle<R>) -> LockSettings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().lock)
+        .unwrap_or_default()
+}
+
+fn auto_lock_minutes<R: Runtime>(app: &AppHandle<R>) -> Option<u32> {
+    settings(app).auto_lock_minutes
+}
+
+fn is_locked<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.try_state::<AppLock>()
+        .is_some_and(|lock| lock.locked.load(Ordering::SeqCst))
+}
+
+fn status<R: Runtime>(app: &AppHandle<R>) -> LockStatus {
+    let settings = settings(app);
+    LockStatus {
+        locked: is_locked(app),
+        auto_lock_minutes: settings.auto_lock_minutes,
+        stop_runs: settings.stop_runs,
+        verification_available: can_verify(),
+        passphrase_available: encryption::has_passphrase(app),
+    }
+}
+
+/// Keeps the windows out of screenshots and screen sharing while locked, on top of
+/// the webviews blanking themselves.
+fn protect_windows<R: Runtime>(app: &AppHandle<R>, protected: bool) {
+    for window in app.webview_windows().values() {
+        if let Err(err) = window.set_content_protected(protected) {
+            tracing::warn!("[Lock] Failed to protect {}: {err}", window.label());
+        }
+    }
+}
+
+/// Locks the windows: the webviews blank their contents on `app-lock://locked`,
+/// the quick-reply popup and quick ask are closed and, if so set, runs in flight
+/// are stopped.
+pub fn lock<R: Runtime>(app: &AppHandle<R>, reason: LockReason) {
+    let Some(state) = app.try_state::<AppLock>() else {
+        return;
+    };
+    if state.locked.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tracing::info!("[Lock] Locking the windows ({reason:?}).");
+    for label in [tray::POPUP_LABEL, quick_ask::LABEL] {
+        if let Some(window) = app.get_webview_window(label) {
+            let _ = window.hide();
+        }
+    }
+    protect_windows(app, true);
+    events::app_locked(app, &LockEvent { reason });
+    if settings(app).stop_runs {
+        watchdog::stop_all(app, "app locked");
+    }
+}
+
+/// Shows the windows' contents again, once the user has been verified.
+pub fn release<R: Runtime>(app: &AppHandle<R>) {
+    let Some(state) = app.try_state::<AppLock>() else {
+        return;
+    };
+    if state.locked.swap(false, Ordering::SeqCst) {
+        protect_windows(app, false);
+        events::app_unlocked(app, &events::Empty {});
+    }
+}
+
+/// Polls the OS idle time and locks once it passes the configured timeout.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut warned = false;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let Some(minutes) = auto_lock_minutes(&app) else {
+                continue;
+            };
+            if is_locked(&app) {
+                continue;
+            }
+            match idle::idle_time(&app).await {
+                Ok(idle) if idle >= Duration::from_secs(u64::from(minutes) * 60) => {
+                    lock(&app, LockReason::Idle)
+                }
+                Ok(_) => {}
+                Err(err) if !warned => {
+                    warned = true;
+                    tracing::warn!("[Lock] Auto-lock is paused: {err}");
+                }
+                Err(_) => {}
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_lock_status(app: AppHandle) -> LockStatus {
+    status(&app)
+}
+
+/// Sets the inactivity timeout; `None` turns auto-lock off, and `stop_runs`, when
+/// given, whether locking ends runs in flight. Refused while neither the OS nor a
+/// passphrase can verify the user, since the app could not be unlocked again.
+#[tauri::command]
+pub fn set_idle_timeout(
+    app: AppHandle,
+    minutes: Option<u32>,
+    stop_runs: Option<bool>,
+) -> Result<LockStatus, AppError> {
+    if let Some(minutes) = minutes {
+        if !(1..=MAX_AUTO_LOCK_MINUTES).contains(&minutes) {
+            return Err(AppError::invalid_input(format!(
+                "Auto-lock timeout must be between 1 and {MAX_AUTO_LOCK_MINUTES} minutes."
+            )));
+        }
+        if !can_verify() && !encryption::has_passphrase(&app) {
+            return Err(AppError::unavailable(
+                "This system cannot verify you and no passphrase is set, so auto-lock cannot be turned on.",
+            ));
+        }
+    }
+    app.state::<SettingsStore>().update(|settings| {
+        settings.lock.auto_lock_minutes = minutes;
+        if let Some(stop_runs) = stop_runs {
+            settings.lock.stop_runs = stop_runs;
+        }
+    })?;
+    Ok(status(&app))
+}
+
+/// Locks right away, for a shortcut or the settings UI.
+#[tauri::command]
+pub fn lock_now(app: AppHandle) -> LockStatus {
+    lock(&app, LockReason::Manual);
+    status(&app)
+}
+
+/// With `passphrase`, checks it against the database passphrase, which also brings
+/// a locked database back; otherwise asks the OS to verify the user (Touch ID or
+/// password, Windows Hello, polkit). Unlocks on success.
+#[tauri::command]
+pub async fn unlock_app(
+    app: AppHandle,
+    passphrase: Option<String>,
+) -> Result<LockStatus, AppError> {
+    if !is_locked(&app) && !encryption::is_locked(&app) {
+        return Ok(status(&app));
+    }
+    if let Some(passphrase) = passphrase {
+        let handle = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            encryption::unlock_with_passphrase(&handle, &passphrase)
+        })
+        .await
+        .map_err(|err| AppError::from(format!("Verification task failed: {err}")))??;
+        release(&app);
+        return Ok(status(&app));
+    }
+    if encryption::is_locked(&app) {
+        return Err(AppError::new(
+            ErrorCode::Unauthorized,
+            "The chat database is locked; enter the passphrase to unlock.",
+        ));
+    }
+    let handle = app.clone();
+    let verified = tauri::async_runtime::spawn_blocking(move || verify_user(&handle))
+        .await
+        .map_err(|err| AppError::from(format!("Verification task failed: {err}")))?
+        .map_err(AppError::unavailable)?;
+    if !verified {
+        return Err(AppError::new(
+            ErrorCode::Unauthorized,
+            "You could not be verified.",
+        ));
+    }
+    release(&app);
+    Ok(status(&app))
+}
+
+#[cfg(target_os = "macos")]
+mod verify {
+    use std::sync::mpsc;
+
+    use block2::RcBlock;
+    use objc2::{class, msg_send, rc::Retained, runtime::AnyObject, runtime::Bool};
+    use objc2_foundation::NSString;
+
+    /// Biometrics with the account password as fallback.
+    const DEVICE_OWNER_AUTHENTICATION: isize = 2;
+
+    #[link(name = "LocalAuthentication", kind = "framework")]
+    extern "C" {}
+
+    pub fn available() -> bool {
+        unsafe {
+            let context: Retained<AnyObject> = msg_send![class!(LAContext), new];
+            msg_send![
+                &*context,
+                canEvaluatePolicy: DEVICE_OWNER_AUTHENTICATION,
+                error: std::ptr::null_mut::<*mut AnyObject>()
+            ]
+        }
+    }
+
+    /// Blocks until the user answers the system prompt.
+    pub fn user(reason: &str) -> Result<bool, String> {
+        let (sender, receiver) = mpsc::channel();
+        let reply = RcBlock::new(move |success: Bool, _error: *mut AnyObject| {
+            let _ = sender.send(success.as_bool());
+        });
+        let context: Retained<AnyObject> = unsafe { msg_send![class!(LAContext), new] };
+        unsafe {
+            let _: () = msg_send![
+                &*context,
+                evaluatePolicy: DEVICE_OWNER_AUTHENTICATION,
+                localizedReason: &*NSString::from_str(reason),
+                reply: &*reply
+            ];
+        }
+        receiver
+            .recv()
+            .map_err(|_| "The verification prompt closed unexpectedly.".to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn can_verify() -> bool {
+    verify::available()
+}
+
+#[cfg(target_os = "macos")]
+fn verify_user(_app: &AppHandle) -> Result<bool, String> {
+    verify::user(UNLOCK_REASON)
+}
+
+#[cfg(target_os = "windows")]
+fn can_verify() -> bool {
+    use windows::Security::Credentials::UI::{
+        UserConsentVerifier, UserConsentVerifierAvailability,
+    };
+
+    UserConsentVerifier::CheckAvailabilityAsync()
+        .and_then(|operation| operation.join())
+        .is_ok_and(|availability| availability == UserConsentVerifierAvailability::Available)
+}
+
+#[cfg(target_os = "windows")]
+fn verify_user(app: &AppHandle) -> Result<bool, String> {
+    use windows::{
+        core::{factory, HSTRING},
+        Security::Credentials::UI::{UserConsentVerificationResult, UserConsentVerifier},
+        Win32::System::WinRT::IUserConsentVerifierInterop,
+    };
+    use windows_future::IAsyncOperation;
+
+    let hwnd = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window is not available.".to_string())?
+        .hwnd()
+        .map_err(|err| format!("Failed to access the main window: {err}"))?;
+    // The interop call parents the Windows Hello prompt to our window.
+    let result = factory::<UserConsentVerifier, IUserConsentVerifierInterop>()
+        .and_then(|interop| unsafe {
+            interop.RequestVerificationForWindowAsync::<IAsyncOperation<UserConsentVerificationResult>>(
+                hwnd,
+                &HSTRING::from(UNLOCK_REASON),
+            )
+        })
+        .and_then(|operation| operation.join())
+        .map_err(|err| format!("Windows Hello is unavailable: {err}"))?;
+    Ok(result == UserConsentVerificationResult::Verified)
+}
+
+#[cfg(target_os = "linux")]
+fn can_verify() -> bool {
+    crate::scan::find_program("pkexec").is_some()
+}
+
+/// polkit prompts for the user's password through the desktop's agent.
+#[cfg(target_os = "linux")]
+fn verify_user(_app: &AppHandle) -> Result<bool, String> {
+    let pkexec = crate::scan::find_program("pkexec")
+        .ok_or_else(|| "polkit is not installed.".to_string())?;
+    let status = std::process::Command::new(pkexec)
+        .arg("true")
+        .status()
+        .map_err(|err| format!("Failed to start polkit: {err}"))?;
+    Ok(status.success())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn can_verify() -> bool {
+    false
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn verify_user(_app: &AppHandle) -> Result<bool, String> {
+    Err("User verification is not available on this platform.".to_string())
+}
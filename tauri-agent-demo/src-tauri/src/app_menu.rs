@@ -0,0 +1,311 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use tauri::{
+    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
+    AppHandle, Manager, Runtime, WebviewWindow,
+};
+
+use crate::{
+    events, kiosk, locale, main_window,
+    settings::SettingsStore,
+    shortcuts::{self, ShortcutAction},
+};
+
+const MENU_NEW_CHAT: &str = "menu-new-chat";
+const MENU_EXPORT: &str = "menu-export";
+const MENU_SETTINGS: &str = "menu-settings";
+const MENU_ZOOM_IN: &str = "menu-zoom-in";
+const MENU_ZOOM_OUT: &str = "menu-zoom-out";
+const MENU_ZOOM_RESET: &str = "menu-zoom-reset";
+#[cfg(not(target_os = "macos"))]
+const MENU_FULLSCREEN: &str = "menu-fullscreen";
+#[cfg(debug_assertions)]
+const MENU_DEVTOOLS: &str = "menu-devtools";
+const MENU_DIAGNOSTICS: &str = "menu-diagnostics";
+#[cfg(not(target_os = "macos"))]
+const MENU_QUIT: &str = "menu-quit";
+/// Also reserved in `shortcuts`, so no binding is swallowed by the menu.
+pub const EXPORT_ACCELERATOR: &str = "CmdOrCtrl+Shift+E";
+pub const ZOOM_IN_ACCELERATOR: &str = "CmdOrCtrl+=";
+pub const ZOOM_OUT_ACCELERATOR: &str = "CmdOrCtrl+-";
+pub const ZOOM_RESET_ACCELERATOR: &str = "CmdOrCtrl+0";
+pub const FULLSCREEN_ACCELERATOR: &str = "F11";
+pub const DEVTOOLS_ACCELERATOR: &str = "CmdOrCtrl+Alt+I";
+const ZOOM_STEP: f64 = 0.1;
+const ZOOM_MIN: f64 = 0.5;
+const ZOOM_MAX: f64 = 3.0;
+
+events::payload_enum! {
+    /// Menu items the frontend carries out, sent to the main window on `menu://action`.
+    pub enum MenuAction {
+        NewChat,
+        /// The conversation on screen.
+        Export,
+        OpenSettings,
+        Diagnostics,
+    }
+}
+
+events::payload! {
+    pub struct MenuActionEvent {
+        action: MenuAction,
+    }
+}
+
+/// Zoom level per window label; the webview does not report its own.
+#[derive(Default)]
+pub struct ZoomLevels(Mutex<HashMap<String, f64>>);
+
+/// The user's binding, so the menu shows the keys that actually start the action.
+/// App-scoped bindings reach the frontend through these items once the menu is up.
+fn binding<R: Runtime>(app: &AppHandle<R>, action: ShortcutAction) -> Option<String> {
+    let store = app.try_state::<SettingsStore>()?;
+    shortcuts::accelerator(&store, action)
+}
+
+/// Kiosk mode leaves out Export and Settings, as the command palette does.
+fn build<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Menu<R>> {
+    let kiosk = kiosk::is_enabled(app);
+    let new_chat = MenuItem::with_id(
+        app,
+        MENU_NEW_CHAT,
+        locale::t(app, "menu.new_chat"),
+        true,
+        binding(app, ShortcutAction::NewChat),
+    )?;
+    let export = MenuItem::with_id(
+        app,
+        MENU_EXPORT,
+        locale::t(app, "menu.export"),
+        true,
+        Some(EXPORT_ACCELERATOR),
+    )?;
+    let settings = MenuItem::with_id(
+        app,
+        MENU_SETTINGS,
+        locale::t(app, "menu.settings"),
+        true,
+        binding(app, ShortcutAction::OpenSettings),
+    )?;
+    let edit = Submenu::with_items(
+        app,
+        locale::t(app, "menu.edit"),
+        true,
+        &[
+            &PredefinedMenuItem::undo(app, None)?,
+            &PredefinedMenuItem::redo(app, None)?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::cut(app, None)?,
+            &PredefinedMenuItem::copy(app, None)?,
+            &PredefinedMenuItem::paste(app, None)?,
+            &PredefinedMenuItem::select_all(app, None)?,
+        ],
+    )?;
+    let view = Submenu::with_items(
+        app,
+        locale::t(app, "menu.view"),
+        true,
+        &[
+            &MenuItem::with_id(
+                app,
+                MENU_ZOOM_IN,
+                locale::t(app, "menu.zoom_in"),
+                true,
+                Some(ZOOM_IN_ACCELERATOR),
+            )?,
+            &MenuItem::with_id(
+                app,
+                MENU_ZOOM_OUT,
+                locale::t(app, "menu.zoom_out"),
+                true,
+                Some(ZOOM_OUT_ACCELERATOR),
+            )?,
+            &MenuItem::with_id(
+                app,
+                MENU_ZOOM_RESET,
+                locale::t(app, "menu.actual_size"),
+                true,
+                Some(ZOOM_RESET_ACCELERATOR),
+            )?,
+            &PredefinedMenuItem::separator(app)?,
+            #[cfg(target_os = "macos")]
+            &PredefinedMenuItem::fullscreen(app, None)?,
+            #[cfg(not(target_os = "macos"))]
+            &MenuItem::with_id(
+                app,
+                MENU_FULLSCREEN,
+                locale::t(app, "menu.fullscreen"),
+                true,
+                Some(FULLSCREEN_ACCELERATOR),
+            )?,
+            #[cfg(debug_assertions)]
+            &MenuItem::with_id(
+                app,
+                MENU_DEVTOOLS,
+                locale::t(app, "menu.devtools"),
+                true,
+                Some(DEVTOOLS_ACCELERATOR),
+            )?,
+        ],
+    )?;
+    let help = Submenu::with_items(
+        app,
+        locale::t(app, "menu.help"),
+        true,
+        &[&MenuItem::with_id(
+            app,
+            MENU_DIAGNOSTICS,
+            locale::t(app, "menu.diagnostics"),
+            true,
+            None::<&str>,
+        )?],
+    )?;
+
+    #[cfg(target_os = "macos")]
+    {
+        let app_menu = Submenu::with_items(
+            app,
+            app.package_info().name.clone(),
+            true,
+            &[
+                &PredefinedMenuItem::about(app, None, None)?,
+                &PredefinedMenuItem::separator(app)?,
+            ],
+        )?;
+        if !kiosk {
+            app_menu.append_items(&[&settings, &PredefinedMenuItem::separator(app)?])?;
+        }
+        app_menu.append_items(&[
+            &PredefinedMenuItem::services(app, None)?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::hide(app, None)?,
+            &PredefinedMenuItem::hide_others(app, None)?,
+            &PredefinedMenuItem::show_all(app, None)?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::quit(app, None)?,
+        ])?;
+        let file = Submenu::with_items(app, locale::t(app, "menu.file"), true, &[&new_chat])?;
+        if !kiosk {
+            file.append(&export)?;
+        }
+        file.append_items(&[
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::close_window(app, None)?,
+        ])?;
+        let window = Submenu::with_items(
+            app,
+            locale::t(app, "menu.window"),
+            true,
+            &[
+                &PredefinedMenuItem::minimize(app, None)?,
+                &PredefinedMenuItem::maximize(app, None)?,
+                &PredefinedMenuItem::separator(app)?,
+                &PredefinedMenuItem::close_window(app, None)?,
+            ],
+        )?;
+        Menu::with_items(app, &[&app_menu, &file, &edit, &view, &window, &help])
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let file = Submenu::with_items(app, locale::t(app, "menu.file"), true, &[&new_chat])?;
+        if !kiosk {
+            file.append_items(&[&export, &PredefinedMenuItem::separator(app)?, &settings])?;
+        }
+        file.append_items(&[
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(
+                app,
+                MENU_QUIT,
+                locale::t(app, "menu.quit"),
+                true,
+                None::<&str>,
+            )?,
+        ])?;
+        Menu::with_items(app, &[&file, &edit, &view, &help])
+    }
+}
+
+/// Replaces the default menu: the application menu on macOS, the main window's
+/// menu bar elsewhere, so the splash and popups get none. Called again after
+/// shortcut edits to refresh the accelerators shown.
+pub fn install<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let menu = build(app).map_err(|err| format!("Failed to build the menu: {err}"))?;
+    #[cfg(target_os = "macos")]
+    app.set_menu(menu)
+        .map_err(|err| format!("Failed to set the application menu: {err}"))?;
+    #[cfg(not(target_os = "macos"))]
+    if let Some(window) = app.get_webview_window(main_window::LABEL) {
+        window
+            .set_menu(menu)
+            .map_err(|err| format!("Failed to set the window menu: {err}"))?;
+    }
+    Ok(())
+}
+
+/// Where view items apply: the focused window, else the main one.
+fn target<R: Runtime>(app: &AppHandle<R>) -> Option<WebviewWindow<R>> {
+    app.webview_windows()
+        .into_values()
+        .find(|window| window.is_focused().unwrap_or(false))
+        .or_else(|| app.get_webview_window(main_window::LABEL))
+}
+
+fn zoom<R: Runtime>(app: &AppHandle<R>, change: impl Fn(f64) -> f64) {
+    let (Some(window), Some(levels)) = (target(app), app.try_state::<ZoomLevels>()) else {
+        return;
+    };
+    let Ok(mut levels) = levels.0.lock() else {
+        return;
+    };
+    let level = levels.entry(window.label().to_string()).or_insert(1.0);
+    *level = change(*level).clamp(ZOOM_MIN, ZOOM_MAX);
+    if let Err(err) = window.set_zoom(*level) {
+        tracing::warn!("[Menu] Failed to zoom {}: {err}", window.label());
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn toggle_fullscreen<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = target(app) {
+        let fullscreen = window.is_fullscreen().unwrap_or(false);
+        let _ = window.set_fullscreen(!fullscreen);
+    }
+}
+
+#[cfg(debug_assertions)]
+fn toggle_devtools<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = target(app) {
+        if window.is_devtools_open() {
+            window.close_devtools();
+        } else {
+            window.open_devtools();
+        }
+    }
+}
+
+/// Brings the main window up first, since the menu stays usable while it is hidden
+/// on macOS.
+fn forward<R: Runtime>(app: &AppHandle<R>, action: MenuAction) {
+    main_window::reveal(app);
+    events::menu_action(app, main_window::LABEL, &MenuActionEvent { action });
+}
+
+/// App-wide menu handler; tray items have ids of their own and fall through.
+pub fn handle<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+    match event.id().as_ref() {
+        MENU_NEW_CHAT => forward(app, MenuAction::NewChat),
+        MENU_EXPORT => forward(app, MenuAction::Export),
+        MENU_SETTINGS => forward(app, MenuAction::OpenSettings),
+        MENU_DIAGNOSTICS => forward(app, MenuAction::Diagnostics),
+        MENU_ZOOM_IN => zoom(app, |level| level + ZOOM_STEP),
+        MENU_ZOOM_OUT => zoom(app, |level| level - ZOOM_STEP),
+        MENU_ZOOM_RESET => zoom(app, |_| 1.0),
+        #[cfg(not(target_os = "macos"))]
+        MENU_FULLSCREEN => toggle_fullscreen(app),
+        #[cfg(debug_assertions)]
+        MENU_DEVTOOLS => toggle_devtools(app),
+        #[cfg(not(target_os = "macos"))]
+        MENU_QUIT => app.exit(0),
+        _ => {}
+    }
+}
@@ -0,0 +1,243 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{
+    utils::config::WindowEffectsConfig,
+    window::{Effect, EffectState, EffectsBuilder},
+    AppHandle, Manager, Runtime, Theme, WebviewWindow,
+};
+
+use crate::{error::AppError, events, main_window, settings::SettingsStore, tray};
+
+/// Windows other than `main` that get the effect, matched by label prefix.
+pub const QUICK_CHAT_PREFIX: &str = "quick-chat";
+
+events::payload_enum! {
+    #[derive(Default, Deserialize)]
+    pub enum WindowEffect {
+        #[default]
+        None,
+        /// macOS sidebar vibrancy.
+        Vibrancy,
+        /// Windows 10/11 acrylic blur.
+        Acrylic,
+        /// Windows 11 mica.
+        Mica,
+    }
+}
+
+events::payload! {
+    pub struct WindowEffectChanged {
+        effect: WindowEffect,
+    }
+}
+
+events::payload_enum! {
+    /// The user's choice; `System` follows the OS.
+    #[derive(Default, Deserialize)]
+    pub enum ThemePreference {
+        #[default]
+        System,
+        Light,
+        Dark,
+    }
+}
+
+impl ThemePreference {
+    fn theme(self) -> Option<Theme> {
+        match self {
+            ThemePreference::System => None,
+            ThemePreference::Light => Some(Theme::Light),
+            ThemePreference::Dark => Some(Theme::Dark),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppearanceSettings {
+    pub window_effect: WindowEffect,
+    pub theme: ThemePreference,
+}
+
+events::payload! {
+    pub struct ThemeInfo {
+        preference: ThemePreference,
+        system: Theme,
+        /// What the windows show.
+        theme: Theme,
+    }
+}
+
+/// The OS theme as last seen. Windows report the override while one is set, so
+/// changes are only picked up while following the system and once it is cleared.
+#[derive(Default)]
+pub struct SystemTheme(Mutex<Option<Theme>>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowEffectInfo {
+    effect: WindowEffect,
+    supported: &'static [WindowEffect],
+}
+
+#[cfg(target_os = "macos")]
+const SUPPORTED: &[WindowEffect] = &[WindowEffect::None, WindowEffect::Vibrancy];
+#[cfg(target_os = "windows")]
+const SUPPORTED: &[WindowEffect] = &[
+    WindowEffect::None,
+    WindowEffect::Acrylic,
+    WindowEffect::Mica,
+];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const SUPPORTED: &[WindowEffect] = &[WindowEffect::None];
+
+fn effects(effect: WindowEffect) -> Option<WindowEffectsConfig> {
+    let material = match effect {
+        WindowEffect::None => return None,
+        WindowEffect::Vibrancy => Effect::Sidebar,
+        WindowEffect::Acrylic => Effect::Acrylic,
+        WindowEffect::Mica => Effect::Mica,
+    };
+    Some(
+        EffectsBuilder::new()
+            .effect(material)
+            .state(EffectState::FollowsWindowActiveState)
+            .build(),
+    )
+}
+
+fn has_effect(label: &str) -> bool {
+    label == "main" || label.starts_with(QUICK_CHAT_PREFIX)
+}
+
+fn current<R: Runtime>(app: &AppHandle<R>) -> WindowEffect {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().appearance.window_effect)
+        .filter(|effect| SUPPORTED.contains(effect))
+        .unwrap_or_default()
+}
+
+/// Applies the saved effect to one window; called for windows created later.
+pub fn apply<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    let effect = current(window.app_handle());
+    window
+        .set_effects(effects(effect))
+        .map_err(|err| format!("Failed to set window effect: {err}"))
+}
+
+/// Applies the saved effect to every open window that takes one. The webview is told
+/// through `appearance://window-effect` so it can make its background translucent.
+pub fn restore<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    for (label, window) in app.webview_windows() {
+        if has_effect(&label) {
+            apply(&window)?;
+        }
+    }
+    events::window_effect_changed(
+        app,
+        &WindowEffectChanged {
+            effect: current(app),
+        },
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_window_effect(app: AppHandle) -> WindowEffectInfo {
+    WindowEffectInfo {
+        effect: current(&app),
+        supported: SUPPORTED,
+    }
+}
+
+#[tauri::command]
+pub fn set_window_effect(
+    app: AppHandle,
+    effect: WindowEffect,
+) -> Result<WindowEffectInfo, AppError> {
+    if !SUPPORTED.contains(&effect) {
+        return Err(AppError::invalid_input(
+            "This window effect is not available on this platform.",
+        ));
+    }
+    app.state::<SettingsStore>()
+        .update(|settings| settings.appearance.window_effect = effect)?;
+    restore(&app)?;
+    Ok(get_window_effect(app))
+}
+
+pub fn theme_preference<R: Runtime>(app: &AppHandle<R>) -> ThemePreference {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().appearance.theme)
+        .unwrap_or_default()
+}
+
+/// For windows built before the override is applied app-wide, like the splash.
+pub fn theme_override<R: Runtime>(app: &AppHandle<R>) -> Option<Theme> {
+    theme_preference(app).theme()
+}
+
+fn system_theme<R: Runtime>(app: &AppHandle<R>) -> Theme {
+    app.try_state::<SystemTheme>()
+        .and_then(|state| *state.0.lock().ok()?)
+        .unwrap_or(Theme::Light)
+}
+
+fn theme_info<R: Runtime>(app: &AppHandle<R>) -> ThemeInfo {
+    let preference = theme_preference(app);
+    let system = system_theme(app);
+    ThemeInfo {
+        preference,
+        system,
+        theme: preference.theme().unwrap_or(system),
+    }
+}
+
+fn record_system<R: Runtime>(app: &AppHandle<R>, theme: Theme) {
+    if let Some(state) = app.try_state::<SystemTheme>() {
+        if let Ok(mut system) = state.0.lock() {
+            *system = Some(theme);
+        }
+    }
+}
+
+/// Announces the effective theme on `theme://changed` and repaints the tray icon.
+fn announce<R: Runtime>(app: &AppHandle<R>) {
+    let info = theme_info(app);
+    tray::follow_theme(app, info.theme);
+    events::theme_changed(app, &info);
+}
+
+/// Notes the OS theme while nothing overrides it, then applies the saved choice.
+pub fn restore_theme<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(theme) = app
+        .get_webview_window(main_window::LABEL)
+        .and_then(|window| window.theme().ok())
+    {
+        record_system(app, theme);
+    }
+    app.set_theme(theme_override(app));
+    announce(app);
+}
+
+/// From the main window's `ThemeChanged`, which also fires when an override lands.
+pub fn theme_changed<R: Runtime>(app: &AppHandle<R>, theme: Theme) {
+    if theme_preference(app) == ThemePreference::System {
+        record_system(app, theme);
+    }
+    announce(app);
+}
+
+#[tauri::command]
+pub fn get_system_theme(app: AppHandle) -> ThemeInfo {
+    theme_info(&app)
+}
+
+#[tauri::command]
+pub fn set_window_theme(app: AppHandle, theme: ThemePreference) -> Result<ThemeInfo, AppError> {
+    app.state::<SettingsStore>()
+        .update(|settings| settings.appearance.theme = theme)?;
+    app.set_theme(theme.theme());
+    announce(&app);
+    Ok(theme_info(&app))
+}
@@ -0,0 +1,59 @@
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_dialog::{
+    DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult,
+};
+
+use crate::locale;
+
+/// Longest action detail the dialog shows. Longer ones are refused rather than cut,
+/// so "Allow" never covers text the user could not see.
+pub const MAX_DETAIL_CHARS: usize = 4000;
+
+/// The answer to [`ask`]. Closing the dialog counts as `Deny`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    AllowOnce,
+    AlwaysAllow,
+    Deny,
+}
+
+/// Asks the user to approve a privileged action with a native dialog. With
+/// `rememberable`, a third button lets the caller remember the answer. Blocks until answered, so it
+/// must run off the main thread. Errs without asking when `detail` is too long to
+/// show in full.
+pub fn ask<R: Runtime>(
+    app: &AppHandle<R>,
+    title: &str,
+    summary: &str,
+    detail: &str,
+    rememberable: bool,
+) -> Result<Decision, String> {
+    let len = detail.chars().count();
+    if len > MAX_DETAIL_CHARS {
+        return Err(format!(
+            "The action is {len} characters long, more than the {MAX_DETAIL_CHARS} the approval dialog can show; split it into smaller steps."
+        ));
+    }
+    let allow_once = locale::t(app, "dialog.allow_once");
+    let always_allow = locale::t(app, "dialog.always_allow");
+    let deny = locale::t(app, "dialog.deny");
+    let buttons = if rememberable {
+        MessageDialogButtons::YesNoCancelCustom(allow_once.clone(), always_allow.clone(), deny)
+    } else {
+        MessageDialogButtons::OkCancelCustom(allow_once.clone(), deny)
+    };
+    let result = app
+        .dialog()
+        .message(format!("{summary}\n\n{detail}"))
+        .title(title)
+        .kind(MessageDialogKind::Warning)
+        .buttons(buttons)
+        .blocking_show_with_result();
+    Ok(match result {
+        MessageDialogResult::Custom(label) if label == allow_once => Decision::AllowOnce,
+        MessageDialogResult::Custom(label) if rememberable && label == always_allow => {
+            Decision::AlwaysAllow
+        }
+        _ => Decision::Deny,
+    })
+}
@@ -0,0 +1,205 @@
+use std::{fmt, fs::File, io::Read, path::Path};
+
+/// CPU architectures a sidecar may be built for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    X86,
+    X86_64,
+    Aarch64,
+}
+
+impl Arch {
+    /// Matches `std::env::consts::ARCH`, which is also the suffix of per-arch sidecars.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Arch::X86 => "x86",
+            Arch::X86_64 => "x86_64",
+            Arch::Aarch64 => "aarch64",
+        }
+    }
+
+    fn from_std(value: &str) -> Option<Self> {
+        match value {
+            "x86" => Some(Arch::X86),
+            "x86_64" => Some(Arch::X86_64),
+            "aarch64" => Some(Arch::Aarch64),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The machine's real architecture, which differs from the app's own when an x64
+/// build runs under Rosetta or Windows on ARM emulation.
+pub fn host() -> Option<Arch> {
+    let own = Arch::from_std(std::env::consts::ARCH)?;
+    #[cfg(target_os = "macos")]
+    if own == Arch::X86_64 && translated_by_rosetta() {
+        return Some(Arch::Aarch64);
+    }
+    #[cfg(target_os = "windows")]
+    if let Some(native) = windows_native_machine() {
+        return Some(native);
+    }
+    Some(own)
+}
+
+#[cfg(target_os = "macos")]
+fn translated_by_rosetta() -> bool {
+    std::process::Command::new("sysctl")
+        .args(["-n", "sysctl.proc_translated"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "1")
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+fn rosetta_installed() -> bool {
+    Path::new("/Library/Apple/usr/libexec/oah/libRosettaRuntime").exists()
+}
+
+#[cfg(target_os = "windows")]
+fn windows_native_machine() -> Option<Arch> {
+    use windows::Win32::System::{
+        SystemInformation::{
+            IMAGE_FILE_MACHINE, IMAGE_FILE_MACHINE_AMD64, IMAGE_FILE_MACHINE_ARM64,
+            IMAGE_FILE_MACHINE_I386,
+        },
+        Threading::{GetCurrentProcess, IsWow64Process2},
+    };
+
+    let mut process = IMAGE_FILE_MACHINE::default();
+    let mut native = IMAGE_FILE_MACHINE::default();
+    unsafe { IsWow64Process2(GetCurrentProcess(), &mut process, Some(&mut native)) }.ok()?;
+    match native {
+        IMAGE_FILE_MACHINE_ARM64 => Some(Arch::Aarch64),
+        IMAGE_FILE_MACHINE_AMD64 => Some(Arch::X86_64),
+        IMAGE_FILE_MACHINE_I386 => Some(Arch::X86),
+        _ => None,
+    }
+}
+
+/// Architectures `host` can execute, best first.
+pub fn runnable(host: Arch) -> Vec<Arch> {
+    match host {
+        #[cfg(target_os = "macos")]
+        Arch::Aarch64 if rosetta_installed() => vec![Arch::Aarch64, Arch::X86_64],
+        // Windows 11 on ARM emulates both x64 and x86.
+        #[cfg(target_os = "windows")]
+        Arch::Aarch64 => vec![Arch::Aarch64, Arch::X86_64, Arch::X86],
+        #[cfg(target_os = "windows")]
+        Arch::X86_64 => vec![Arch::X86_64, Arch::X86],
+        other => vec![other],
+    }
+}
+
+/// What an unrunnable binary needs, for the error shown to the user.
+pub fn mismatch_hint(host: Arch, built_for: &[Arch]) -> &'static str {
+    if cfg!(target_os = "macos") && host == Arch::Aarch64 && built_for.contains(&Arch::X86_64) {
+        "Install Rosetta 2 (softwareupdate --install-rosetta) or use the Apple silicon build."
+    } else {
+        "Download the build made for this computer's processor."
+    }
+}
+
+fn u16_at(bytes: &[u8], offset: usize, big_endian: bool) -> Option<u16> {
+    let raw: [u8; 2] = bytes.get(offset..offset + 2)?.try_into().ok()?;
+    Some(if big_endian {
+        u16::from_be_bytes(raw)
+    } else {
+        u16::from_le_bytes(raw)
+    })
+}
+
+fn u32_at(bytes: &[u8], offset: usize, big_endian: bool) -> Option<u32> {
+    let raw: [u8; 4] = bytes.get(offset..offset + 4)?.try_into().ok()?;
+    Some(if big_endian {
+        u32::from_be_bytes(raw)
+    } else {
+        u32::from_le_bytes(raw)
+    })
+}
+
+fn elf_arch(machine: u16) -> Option<Arch> {
+    match machine {
+        3 => Some(Arch::X86),
+        62 => Some(Arch::X86_64),
+        183 => Some(Arch::Aarch64),
+        _ => None,
+    }
+}
+
+fn pe_arch(machine: u16) -> Option<Arch> {
+    match machine {
+        0x014c => Some(Arch::X86),
+        0x8664 => Some(Arch::X86_64),
+        0xaa64 => Some(Arch::Aarch64),
+        _ => None,
+    }
+}
+
+fn mach_arch(cpu_type: u32) -> Option<Arch> {
+    match cpu_type {
+        0x0000_0007 => Some(Arch::X86),
+        0x0100_0007 => Some(Arch::X86_64),
+        0x0100_000c => Some(Arch::Aarch64),
+        _ => None,
+    }
+}
+
+/// Architectures an ELF, PE or Mach-O (including universal) file was built for.
+/// `Ok(None)` means the file is a format we do not inspect, such as a script.
+pub fn of_binary(path: &Path) -> Result<Option<Vec<Arch>>, String> {
+    let mut head = Vec::with_capacity(4096);
+    File::open(path)
+        .and_then(|file| file.take(4096).read_to_end(&mut head))
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    let unreadable = || format!("{} has a damaged executable header.", path.display());
+
+    if head.starts_with(b"\x7fELF") {
+        let big_endian = head.get(5) == Some(&2);
+        let machine = u16_at(&head, 18, big_endian).ok_or_else(unreadable)?;
+        return Ok(Some(elf_arch(machine).into_iter().collect()));
+    }
+    if head.starts_with(b"MZ") {
+        let offset = u32_at(&head, 0x3c, false).ok_or_else(unreadable)? as usize;
+        if head.get(offset..offset + 4) != Some(b"PE\0\0") {
+            return Err(unreadable());
+        }
+        let machine = u16_at(&head, offset + 4, false).ok_or_else(unreadable)?;
+        return Ok(Some(pe_arch(machine).into_iter().collect()));
+    }
+    let magic = u32_at(&head, 0, true).ok_or_else(unreadable)?;
+    match magic {
+        // Thin Mach-O, 32 or 64 bit, either byte order.
+        0xfeed_face | 0xfeed_facf => Ok(Some(
+            mach_arch(u32_at(&head, 4, true).ok_or_else(unreadable)?)
+                .into_iter()
+                .collect(),
+        )),
+        0xcefa_edfe | 0xcffa_edfe => Ok(Some(
+            mach_arch(u32_at(&head, 4, false).ok_or_else(unreadable)?)
+                .into_iter()
+                .collect(),
+        )),
+        // Universal binary: a big-endian table of slices.
+        0xcafe_babe | 0xcafe_babf => {
+            let entry_size = if magic == 0xcafe_babe { 20 } else { 32 };
+            let count = u32_at(&head, 4, true).ok_or_else(unreadable)? as usize;
+            // Java class files share the magic but have a large version number here.
+            if count == 0 || count > 16 {
+                return Ok(None);
+            }
+            (0..count)
+                .map(|index| u32_at(&head, 8 + index * entry_size, true).ok_or_else(unreadable))
+                .collect::<Result<Vec<u32>, String>>()
+                .map(|types| Some(types.into_iter().filter_map(mach_arch).collect()))
+        }
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,431 @@
+use std::{
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::Local;
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    attachments::hex_digest,
+    encryption,
+    error::{AppError, ErrorCode},
+    proxy, temp_files, workspace, BackendState,
+};
+
+const BUNDLE_VERSION: u32 = 1;
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DATABASE_ENTRY: &str = "conversations.db";
+const SESSION_SCOPE: &str = "SELECT id FROM temp.archive_sessions";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedConversation {
+    pub id: String,
+    pub title: String,
+}
+
+/// Stored next to the row snapshot; `sha256` covers the uncompressed `conversations.db`.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct BundleManifest {
+    version: u32,
+    pub created_at: String,
+    pub conversations: Vec<ArchivedConversation>,
+    sha256: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveSummary {
+    pub path: PathBuf,
+    sha256: String,
+    pub size: u64,
+    pub conversations: Vec<ArchivedConversation>,
+}
+
+fn open_db(path: &Path) -> Result<Connection, String> {
+    if !path.exists() {
+        return Err(format!("Chat database not found at {}.", path.display()));
+    }
+    let conn = encryption::open(path, OpenFlags::default())
+        .map_err(|err| format!("Failed to open chat database: {err}"))?;
+    conn.busy_timeout(Duration::from_secs(30))
+        .map_err(|err| format!("Failed to configure chat database: {err}"))?;
+    Ok(conn)
+}
+
+fn list_tables(conn: &Connection, schema: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT name FROM {schema}.sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name"
+        ))
+        .map_err(|err| format!("Failed to list tables: {err}"))?;
+    let rows = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|err| format!("Failed to list tables: {err}"))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("Failed to list tables: {err}"))
+}
+
+fn table_columns(conn: &Connection, schema: &str, table: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name FROM pragma_table_info(?1, ?2)")
+        .map_err(|err| format!("Failed to inspect {table}: {err}"))?;
+    let rows = stmt
+        .query_map(params![table, schema], |row| row.get(0))
+        .map_err(|err| format!("Failed to inspect {table}: {err}"))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("Failed to inspect {table}: {err}"))
+}
+
+/// Which rows of `table` belong to the archived sessions. Tables without a session,
+/// message or task key (LLM configs, schema metadata) stay out of the bundle.
+fn scope_filter(table: &str, columns: &[String]) -> Option<String> {
+    let has = |name: &str| columns.iter().any(|column| column == name);
+    let messages =
+        format!("SELECT id FROM main.chat_messages WHERE session_id IN ({SESSION_SCOPE})");
+    let tasks = format!("SELECT id FROM main.agent_tasks WHERE session_id IN ({SESSION_SCOPE})");
+    if table == "chat_sessions" {
+        Some(format!("id IN ({SESSION_SCOPE})"))
+    } else if has("session_id") {
+        Some(format!("session_id IN ({SESSION_SCOPE})"))
+    } else if has("message_id") {
+        Some(format!("message_id IN ({messages})"))
+    } else if has("task_id") {
+        Some(format!("task_id IN ({tasks})"))
+    } else if has("from_task_id") {
+        Some(format!("from_task_id IN ({tasks})"))
+    } else {
+        None
+    }
+}
+
+/// Copies the selected sessions, their sub-agent sessions and every dependent row
+/// (messages, attachment blobs, LLM calls, tasks...) into a standalone SQLite file.
+fn snapshot_sessions(
+    db_path: &Path,
+    ids: &[String],
+    snapshot: &Path,
+) -> Result<Vec<ArchivedConversation>, String> {
+    let conn = open_db(db_path)?;
+    conn.execute_batch("CREATE TEMP TABLE archive_roots (id TEXT PRIMARY KEY);")
+        .map_err(|err| format!("Failed to prepare archive: {err}"))?;
+    for id in ids {
+        conn.execute(
+            "INSERT OR IGNORE INTO temp.archive_roots (id) VALUES (?1)",
+            params![id],
+        )
+        .map_err(|err| format!("Failed to prepare archive: {err}"))?;
+    }
+    conn.execute_batch(
+        "CREATE TEMP TABLE archive_sessions AS
+         WITH RECURSIVE tree(id) AS (
+             SELECT id FROM main.chat_sessions WHERE id IN (SELECT id FROM temp.archive_roots)
+             UNION
+             SELECT child.id FROM main.chat_sessions child
+             JOIN tree ON child.parent_session_id = tree.id
+         )
+         SELECT id FROM tree;",
+    )
+    .map_err(|err| format!("Failed to collect sessions: {err}"))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title FROM main.chat_sessions
+             WHERE id IN (SELECT id FROM temp.archive_roots) ORDER BY created_at",
+        )
+        .map_err(|err| format!("Failed to read sessions: {err}"))?;
+    let conversations = stmt
+        .query_map([], |row| {
+            Ok(ArchivedConversation {
+                id: row.get(0)?,
+                title: row.get(1)?,
+            })
+        })
+        .map_err(|err| format!("Failed to read sessions: {err}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("Failed to read sessions: {err}"))?;
+    drop(stmt);
+    let missing: Vec<&str> = ids
+        .iter()
+        .filter(|id| !conversations.iter().any(|found| &found.id == *id))
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Conversations not found: {}.", missing.join(", ")));
+    }
+
+    conn.execute(
+        "ATTACH DATABASE ?1 AS archive",
+        params![snapshot.to_string_lossy()],
+    )
+    .map_err(|err| format!("Failed to create archive snapshot: {err}"))?;
+    for table in list_tables(&conn, "main")? {
+        let columns = table_columns(&conn, "main", &table)?;
+        let Some(filter) = scope_filter(&table, &columns) else {
+            continue;
+        };
+        conn.execute_batch(&format!(
+            "CREATE TABLE archive.\"{table}\" AS SELECT * FROM main.\"{table}\" WHERE {filter};"
+        ))
+        .map_err(|err| format!("Failed to archive {table}: {err}"))?;
+    }
+    conn.execute_batch("DETACH DATABASE archive;")
+        .map_err(|err| format!("Failed to finalize archive snapshot: {err}"))?;
+    Ok(conversations)
+}
+
+fn write_bundle(destination: &Path, manifest: &BundleManifest, data: &[u8]) -> Result<(), String> {
+    let partial = destination.with_extension("partial");
+    let file =
+        File::create(&partial).map_err(|err| format!("Failed to create archive file: {err}"))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let manifest_json = serde_json::to_vec_pretty(manifest)
+        .map_err(|err| format!("Failed to serialize archive manifest: {err}"))?;
+    for (name, bytes) in [
+        (MANIFEST_ENTRY, manifest_json.as_slice()),
+        (DATABASE_ENTRY, data),
+    ] {
+        zip.start_file(name, options)
+            .and_then(|_| zip.write_all(bytes).map_err(Into::into))
+            .map_err(|err| format!("Failed to write archive: {err}"))?;
+    }
+    zip.finish()
+        .map_err(|err| format!("Failed to write archive: {err}"))?;
+    fs::rename(&partial, destination).map_err(|err| format!("Failed to save archive: {err}"))
+}
+
+fn read_entry(zip: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    zip.by_name(name)
+        .map_err(|err| format!("Archive is missing {name}: {err}"))?
+        .read_to_end(&mut bytes)
+        .map_err(|err| format!("Failed to read {name} from archive: {err}"))?;
+    Ok(bytes)
+}
+
+fn open_bundle(path: &Path) -> Result<(ZipArchive<File>, BundleManifest), String> {
+    let file = File::open(path).map_err(|err| format!("Failed to open archive: {err}"))?;
+    let mut zip = ZipArchive::new(file).map_err(|err| format!("Invalid archive: {err}"))?;
+    let manifest: BundleManifest = serde_json::from_slice(&read_entry(&mut zip, MANIFEST_ENTRY)?)
+        .map_err(|err| format!("Invalid archive manifest: {err}"))?;
+    if manifest.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Archive format {} is newer than this app supports.",
+            manifest.version
+        ));
+    }
+    Ok((zip, manifest))
+}
+
+/// Reads only the manifest, without unpacking or verifying the snapshot.
+pub(crate) fn read_manifest(path: &Path) -> Result<BundleManifest, String> {
+    open_bundle(path).map(|(_, manifest)| manifest)
+}
+
+/// Reads a bundle and verifies its checksum before anything is trusted.
+fn read_bundle(path: &Path) -> Result<(BundleManifest, Vec<u8>), String> {
+    let (mut zip, manifest) = open_bundle(path)?;
+    let data = read_entry(&mut zip, DATABASE_ENTRY)?;
+    if hex_digest(&data) != manifest.sha256 {
+        return Err("Archive checksum mismatch; the file is corrupted.".to_string());
+    }
+    Ok((manifest, data))
+}
+
+pub(crate) fn export_bundle(
+    db_path: &Path,
+    ids: &[String],
+    destination: &Path,
+) -> Result<ArchiveSummary, String> {
+    let snapshot = destination.with_extension("db.partial");
+    let _ = fs::remove_file(&snapshot);
+    let snapshotted = snapshot_sessions(db_path, ids, &snapshot).and_then(|conversations| {
+        fs::read(&snapshot)
+            .map(|data| (conversations, data))
+            .map_err(|err| format!("Failed to read archive snapshot: {err}"))
+    });
+    let _ = fs::remove_file(&snapshot);
+    let (conversations, data) = snapshotted?;
+
+    let manifest = BundleManifest {
+        version: BUNDLE_VERSION,
+        created_at: Local::now().to_rfc3339(),
+        conversations,
+        sha256: hex_digest(&data),
+    };
+    write_bundle(destination, &manifest, &data)?;
+    // Only a bundle that reads back intact is allowed to replace the live rows.
+    read_bundle(destination)?;
+    let size = fs::metadata(destination)
+        .map(|meta| meta.len())
+        .unwrap_or_default();
+    Ok(ArchiveSummary {
+        path: destination.to_path_buf(),
+        sha256: manifest.sha256,
+        size,
+        conversations: manifest.conversations,
+    })
+}
+
+/// Writes a consistent copy of the whole database, safe while the backend is writing.
+pub(crate) fn export_database(db_path: &Path, destination: &Path) -> Result<(), String> {
+    let conn = open_db(db_path)?;
+    if destination.exists() {
+        fs::remove_file(destination)
+            .map_err(|err| format!("Failed to replace {}: {err}", destination.display()))?;
+    }
+    conn.execute(
+        "VACUUM INTO ?1",
+        params![destination.to_string_lossy().as_ref()],
+    )
+    .map_err(|err| format!("Failed to export database: {err}"))?;
+    Ok(())
+}
+
+fn restore_snapshot(db_path: &Path, snapshot: &Path) -> Result<(), String> {
+    let mut conn = open_db(db_path)?;
+    conn.execute(
+        "ATTACH DATABASE ?1 AS archive",
+        params![snapshot.to_string_lossy()],
+    )
+    .map_err(|err| format!("Failed to open archive snapshot: {err}"))?;
+    let existing: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM main.chat_sessions WHERE id IN (SELECT id FROM archive.chat_sessions)",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|err| format!("Failed to check existing conversations: {err}"))?;
+    if existing > 0 {
+        return Err(
+            "Some archived conversations already exist; delete them before re-importing."
+                .to_string(),
+        );
+    }
+    let tx = conn
+        .transaction()
+        .map_err(|err| format!("Failed to start import: {err}"))?;
+    for table in list_tables(&tx, "archive")? {
+        let live_columns = table_columns(&tx, "main", &table)?;
+        if live_columns.is_empty() {
+            tracing::warn!("[Archive] Skipping {table}; it no longer exists in the chat database.");
+            continue;
+        }
+        // Only shared columns are copied so bundles survive later schema additions.
+        let columns = table_columns(&tx, "archive", &table)?
+            .into_iter()
+            .filter(|column| live_columns.contains(column))
+            .map(|column| format!("\"{column}\""))
+            .collect::<Vec<_>>()
+            .join(", ");
+        tx.execute_batch(&format!(
+            "INSERT INTO main.\"{table}\" ({columns}) SELECT {columns} FROM archive.\"{table}\";"
+        ))
+        .map_err(|err| format!("Failed to restore {table}: {err}"))?;
+    }
+    tx.commit()
+        .map_err(|err| format!("Failed to commit import: {err}"))?;
+    conn.execute_batch("DETACH DATABASE archive;")
+        .map_err(|err| format!("Failed to close archive snapshot: {err}"))
+}
+
+/// Unpacks into `scratch`, which the caller owns and removes.
+pub(crate) fn import_bundle(
+    db_path: &Path,
+    path: &Path,
+    scratch: &Path,
+) -> Result<Vec<ArchivedConversation>, String> {
+    let (manifest, data) = read_bundle(path)?;
+    let snapshot = scratch.join(format!("agent-archive-{}.db", manifest.sha256));
+    fs::write(&snapshot, data).map_err(|err| format!("Failed to unpack archive: {err}"))?;
+    restore_snapshot(db_path, &snapshot).map(|_| manifest.conversations)
+}
+
+/// Moves conversations out of the live chat database into a compressed bundle.
+/// The rows are removed through the backend only after the bundle verifies.
+#[tauri::command]
+pub async fn archive_conversations(
+    app: AppHandle,
+    ids: Vec<String>,
+    destination: Option<String>,
+) -> Result<ArchiveSummary, AppError> {
+    if ids.is_empty() {
+        return Err(AppError::invalid_input("No conversations selected."));
+    }
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| AppError::unavailable("Backend is not available."))?;
+    let app_data_dir = workspace::data_dir(&app)?;
+    let db_path = workspace::db_path(&app)?;
+    let destination = match destination {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let dir = app_data_dir.join("archives");
+            fs::create_dir_all(&dir)
+                .map_err(|err| format!("Failed to create archive directory: {err}"))?;
+            dir.join(format!(
+                "conversations-{}.zip",
+                Local::now().format("%Y%m%d-%H%M%S")
+            ))
+        }
+    };
+    let summary =
+        tauri::async_runtime::spawn_blocking(move || export_bundle(&db_path, &ids, &destination))
+            .await
+            .map_err(|err| AppError::from(format!("Archive task failed: {err}")))??;
+
+    remove_from_backend(&proxy::client(&app), &base_url, &summary)
+        .await
+        .map_err(|err| AppError::new(ErrorCode::Network, err))?;
+    Ok(summary)
+}
+
+/// Deletes bundled conversations through the backend so it also closes their terminals.
+pub(crate) async fn remove_from_backend(
+    client: &reqwest::Client,
+    base_url: &str,
+    summary: &ArchiveSummary,
+) -> Result<(), String> {
+    for conversation in &summary.conversations {
+        let response = client
+            .delete(format!("{base_url}/sessions/{}", conversation.id))
+            .send()
+            .await
+            .map_err(|err| format!("Failed to remove conversation {}: {err}", conversation.id))?;
+        // A selected sub-agent session may already be gone with its archived parent.
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            continue;
+        }
+        response.error_for_status().map_err(|err| {
+            format!(
+                "Archived to {} but failed to remove conversation {}: {err}",
+                summary.path.display(),
+                conversation.id
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Restores a bundle written by `archive_conversations` into the live chat database.
+#[tauri::command]
+pub async fn import_archive(
+    app: AppHandle,
+    path: String,
+) -> Result<Vec<ArchivedConversation>, AppError> {
+    let db_path = workspace::db_path(&app)?;
+    let scratch = temp_files::scoped(&app, "archive")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        import_bundle(&db_path, Path::new(&path), scratch.path())
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Import task failed: {err}")))?
+    .map_err(AppError::from)
+}
@@ -0,0 +1,156 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::{
+    atomic_file, dialogs, dialogs::DialogPurpose, error::AppError, proxy, rpc, BackendState,
+};
+
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const MAX_NAME_CHARS: usize = 120;
+
+/// Keeps only the last path component and drops characters file systems reject.
+fn file_name(suggested: &str, artifact_id: i64) -> String {
+    let name: String = Path::new(suggested.trim())
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+        .chars()
+        .filter(|char| !char.is_control() && !r#"<>:"/\|?*"#.contains(*char))
+        .take(MAX_NAME_CHARS)
+        .collect();
+    let name = name.trim().trim_matches('.').to_string();
+    if name.is_empty() {
+        format!("artifact-{artifact_id}")
+    } else {
+        name
+    }
+}
+
+/// The backend's `detail`, or the status when the body has none.
+fn failure(status: u16, body: &[u8]) -> AppError {
+    let detail = serde_json::from_slice::<Value>(body)
+        .ok()
+        .and_then(|body| body.get("detail")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("The backend answered {status}."));
+    match status {
+        404 => AppError::not_found(detail),
+        _ => AppError::from(detail),
+    }
+}
+
+/// A GET on the backend over whichever transport is attached.
+async fn get<R: Runtime>(app: &AppHandle<R>, path: String) -> Result<Vec<u8>, AppError> {
+    let (status, body) = if rpc::is_attached(app) {
+        let handle = app.clone();
+        tauri::async_runtime::spawn_blocking(move || rpc::get(&handle, &path, DOWNLOAD_TIMEOUT))
+            .await
+            .map_err(|err| AppError::from(format!("Download task failed: {err}")))??
+    } else {
+        let base_url = app
+            .try_state::<BackendState>()
+            .map(|state| state.base_url())
+            .ok_or_else(|| AppError::unavailable("Backend is not configured yet."))?;
+        let response = proxy::client(app)
+            .get(format!("{base_url}{path}"))
+            .timeout(DOWNLOAD_TIMEOUT)
+            .send()
+            .await
+            .map_err(|err| {
+                AppError::unavailable("Failed to reach the backend.").with_details(err)
+            })?;
+        let status = response.status().as_u16();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|err| AppError::from(format!("Failed to download {path}: {err}")))?;
+        (status, body.to_vec())
+    };
+    if status != 200 {
+        return Err(failure(status, &body));
+    }
+    Ok(body)
+}
+
+async fn download<R: Runtime>(app: &AppHandle<R>, artifact_id: i64) -> Result<Vec<u8>, AppError> {
+    get(app, format!("/artifacts/{artifact_id}/content")).await
+}
+
+/// The backend's record of an artifact: its session and, for files, the path.
+pub async fn metadata<R: Runtime>(app: &AppHandle<R>, artifact_id: i64) -> Result<Value, AppError> {
+    let body = get(app, format!("/artifacts/{artifact_id}")).await?;
+    serde_json::from_slice(&body)
+        .map_err(|err| AppError::from(format!("Unreadable artifact {artifact_id}: {err}")))
+}
+
+fn pick_file<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<Option<PathBuf>, AppError> {
+    let mut dialog = app.dialog().file();
+    if let Some(dir) = dialogs::default_dir(app, DialogPurpose::Exports) {
+        dialog = dialog.set_directory(dir);
+    }
+    dialog = dialog.set_file_name(name);
+    if let Some(extension) = Path::new(name).extension().and_then(|ext| ext.to_str()) {
+        dialog = dialog.add_filter(extension.to_ascii_uppercase(), &[extension]);
+    }
+    let Some(chosen) = dialog.blocking_save_file() else {
+        return Ok(None);
+    };
+    let chosen = chosen
+        .into_path()
+        .map_err(|err| AppError::invalid_input(format!("Invalid save path: {err}")))?;
+    dialogs::remember(app, DialogPurpose::Exports, &chosen);
+    Ok(Some(chosen))
+}
+
+/// Downloads a file the agent produced, asks where to save it and writes it there
+/// atomically. Resolves with the path, or `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn save_artifact(
+    app: AppHandle,
+    artifact_id: i64,
+    suggested_name: String,
+) -> Result<Option<String>, AppError> {
+    // Fetched first, so a missing artifact fails before the dialog opens.
+    let contents = download(&app, artifact_id).await?;
+    let name = file_name(&suggested_name, artifact_id);
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some(dest) = pick_file(&app, &name)? else {
+            return Ok(None);
+        };
+        atomic_file::write(&dest, contents)
+            .map_err(|err| AppError::from(format!("Failed to write {}: {err}", dest.display())))?;
+        tracing::info!(
+            "[Artifacts] Saved artifact {artifact_id} to {}",
+            dest.display()
+        );
+        Ok(Some(dest.display().to_string()))
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Save task failed: {err}")))?
+}
+
+/// Opens Finder, Explorer or the file manager with `path` selected.
+#[tauri::command]
+pub fn reveal_in_folder(app: AppHandle, path: String) -> Result<(), AppError> {
+    let path = PathBuf::from(path.trim());
+    if !path.is_absolute() {
+        return Err(AppError::invalid_input(
+            "Only absolute paths can be revealed.",
+        ));
+    }
+    if !path.exists() {
+        return Err(AppError::not_found(format!(
+            "{} does not exist.",
+            path.display()
+        )));
+    }
+    app.opener()
+        .reveal_item_in_dir(&path)
+        .map_err(|err| AppError::from(format!("Failed to reveal {}: {err}", path.display())))
+}
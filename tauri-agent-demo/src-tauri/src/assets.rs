@@ -0,0 +1,319 @@
+use std::{
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+use tauri::{
+    http::{header, Method, Request, Response},
+    AppHandle, Manager, Runtime, UriSchemeContext, UriSchemeResponder,
+};
+
+use crate::{
+    artifacts, attachments, config_files, conversation_windows,
+    error::{AppError, ErrorCode},
+    main_window,
+    temp_files::TempFiles,
+};
+
+/// Serves the attachment and artifact stores to the webview, so media and previews
+/// load without widening the fs plugin's scopes to wherever the files live.
+pub const SCHEME: &str = "app-asset";
+/// Longest slice sent for an open-ended range; players ask again for the rest.
+const MAX_RANGE_BYTES: u64 = 8 * 1024 * 1024;
+/// Larger files are answered with their first slice even without a Range header,
+/// so a single request never reads a huge file into memory.
+const MAX_WHOLE_BYTES: u64 = 32 * 1024 * 1024;
+
+/// What a window may load: the main window shows every conversation, a detached
+/// one only its own.
+enum Access {
+    All,
+    Conversation(String),
+}
+
+fn access(label: &str) -> Option<Access> {
+    if label == main_window::LABEL {
+        return Some(Access::All);
+    }
+    label
+        .strip_prefix(conversation_windows::LABEL_PREFIX)
+        .map(|id| Access::Conversation(id.to_string()))
+}
+
+pub fn base_url() -> String {
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{SCHEME}.localhost")
+    } else {
+        format!("{SCHEME}://localhost")
+    }
+}
+
+fn error_response(err: AppError) -> Response<Vec<u8>> {
+    let status = match err.code {
+        ErrorCode::NotFound => 404,
+        ErrorCode::InvalidInput => 400,
+        ErrorCode::Unauthorized | ErrorCode::ReadOnly | ErrorCode::Permission => 403,
+        ErrorCode::Unavailable => 503,
+        _ => 500,
+    };
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(err.message.into_bytes())
+        .unwrap_or_default()
+}
+
+/// `bytes=a-b`, `bytes=a-` or `bytes=-n`. `None` serves the whole file, which is
+/// also the answer to multiple ranges; `Err` is unsatisfiable.
+fn parse_range(value: &str, len: u64) -> Option<Result<(u64, u64), ()>> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let range = match (start.trim(), end.trim()) {
+        ("", "") => return None,
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            if suffix == 0 || len == 0 {
+                return Some(Err(()));
+            }
+            (len.saturating_sub(suffix), len - 1)
+        }
+        (start, end) => {
+            let start: u64 = start.parse().ok()?;
+            if start >= len {
+                return Some(Err(()));
+            }
+            let end = if end.is_empty() {
+                (start + MAX_RANGE_BYTES - 1).min(len - 1)
+            } else {
+                end.parse::<u64>().ok()?.min(len - 1)
+            };
+            if end < start {
+                return Some(Err(()));
+            }
+            (start, end)
+        }
+    };
+    Some(Ok(range))
+}
+
+fn read_slice(path: &Path, start: u64, len: u64) -> std::io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let mut body = Vec::with_capacity(len as usize);
+    file.take(len).read_to_end(&mut body)?;
+    Ok(body)
+}
+
+/// The file with its MIME type, or the requested slice of it. Scripts in served
+/// HTML or SVG never run, and the type is not second-guessed.
+fn file_response(request: &Request<Vec<u8>>, path: &Path, mime: &str) -> Response<Vec<u8>> {
+    let len = match path.metadata() {
+        Ok(meta) if meta.is_file() => meta.len(),
+        _ => return error_response(AppError::not_found("The file is gone.")),
+    };
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| parse_range(value, len))
+        .or_else(|| (len > MAX_WHOLE_BYTES).then_some(Ok((0, MAX_RANGE_BYTES - 1))));
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, mime)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(header::CONTENT_SECURITY_POLICY, "sandbox");
+    let (builder, start, count) = match range {
+        None => (builder.status(200), 0, len),
+        Some(Ok((start, end))) => (
+            builder
+                .status(206)
+                .header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{len}")),
+            start,
+            end - start + 1,
+        ),
+        Some(Err(())) => {
+            return builder
+                .status(416)
+                .header(header::CONTENT_RANGE, format!("bytes */{len}"))
+                .body(Vec::new())
+                .unwrap_or_default();
+        }
+    };
+    let builder = builder.header(header::CONTENT_LENGTH, count);
+    if request.method() == Method::HEAD {
+        return builder.body(Vec::new()).unwrap_or_default();
+    }
+    match read_slice(path, start, count) {
+        Ok(body) => builder.body(body).unwrap_or_default(),
+        Err(err) => error_response(AppError::from(format!("Failed to read the file: {err}"))),
+    }
+}
+
+fn thumbnail_size(request: &Request<Vec<u8>>) -> Option<u32> {
+    request.uri().query()?.split('&').find_map(|pair| {
+        pair.strip_prefix("thumbnail=")
+            .and_then(|size| size.parse().ok())
+    })
+}
+
+/// Where artifact files may live: the backend's scratch folder and the tools'
+/// working folder.
+fn artifact_roots<R: Runtime>(app: &AppHandle<R>) -> Vec<PathBuf> {
+    let scratch = app
+        .try_state::<TempFiles>()
+        .map(|files| files.backend_dir());
+    scratch
+        .into_iter()
+        .chain(config_files::project_root(app))
+        .filter_map(|root| fs::canonicalize(root).ok())
+        .collect()
+}
+
+async fn artifact<R: Runtime>(
+    app: &AppHandle<R>,
+    access: &Access,
+    id: &str,
+) -> Result<(String, String), AppError> {
+    let id: i64 = id
+        .parse()
+        .map_err(|_| AppError::invalid_input(format!("'{id}' is not an artifact id.")))?;
+    let record = artifacts::metadata(app, id).await?;
+    if let Access::Conversation(conversation) = access {
+        let session = record.get("session_id").and_then(Value::as_str);
+        if session != Some(conversation.as_str()) {
+            return Err(AppError::new(
+                ErrorCode::Unauthorized,
+                "The artifact belongs to another conversation.",
+            ));
+        }
+    }
+    let path = record
+        .get("path")
+        .and_then(Value::as_str)
+        .filter(|path| !path.is_empty())
+        .ok_or_else(|| AppError::not_found("The artifact has no file."))?;
+    // The record comes from the backend; links and `..` must not lead out of the stores.
+    let path =
+        fs::canonicalize(path).map_err(|_| AppError::not_found("The artifact file is gone."))?;
+    if !artifact_roots(app)
+        .iter()
+        .any(|root| path.starts_with(root))
+    {
+        return Err(AppError::new(
+            ErrorCode::Unauthorized,
+            "The artifact is outside the app's stores.",
+        ));
+    }
+    let path = path.to_string_lossy().into_owned();
+    let mime = attachments::mime_for(&path).to_string();
+    Ok((path, mime))
+}
+
+async fn serve<R: Runtime>(
+    app: &AppHandle<R>,
+    label: &str,
+    request: Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return error_response(AppError::invalid_input("Only GET and HEAD are served."));
+    }
+    let Some(access) = access(label) else {
+        return error_response(AppError::new(
+            ErrorCode::Unauthorized,
+            format!("The {label} window cannot load assets."),
+        ));
+    };
+    let path = request.uri().path().trim_start_matches('/');
+    let resolved = match path.split_once('/') {
+        Some(("attachments", file)) => {
+            let file = file.to_string();
+            let thumbnail = thumbnail_size(&request);
+            let conversation = match &access {
+                Access::All => None,
+                Access::Conversation(id) => Some(id.clone()),
+            };
+            let handle = app.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                attachments::resolve(&handle, &file, thumbnail, conversation.as_deref())
+                    .map(|(path, mime)| (path.to_string_lossy().into_owned(), mime))
+            })
+            .await
+            .unwrap_or_else(|err| Err(AppError::from(format!("Asset task failed: {err}"))))
+        }
+        Some(("artifacts", id)) => artifact(app, &access, id).await,
+        _ => Err(AppError::not_found(format!("No asset at /{path}."))),
+    };
+    match resolved {
+        Ok((path, mime)) => tauri::async_runtime::spawn_blocking(move || {
+            file_response(&request, Path::new(&path), &mime)
+        })
+        .await
+        .unwrap_or_else(|err| error_response(AppError::from(format!("Asset task failed: {err}")))),
+        Err(err) => error_response(err),
+    }
+}
+
+/// Handler for `SCHEME`: `/attachments/<file>[?thumbnail=<px>]` and
+/// `/artifacts/<id>`, for the main window and detached conversations only.
+pub fn handle<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    let label = ctx.webview_label().to_string();
+    tauri::async_runtime::spawn(
+        async move { responder.respond(serve(&app, &label, request).await) },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_range_reads_each_form() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some(Ok((0, 99))));
+        assert_eq!(parse_range(" bytes=900- ", 1000), Some(Ok((900, 999))));
+        assert_eq!(parse_range("bytes=-100", 1000), Some(Ok((900, 999))));
+        assert_eq!(parse_range("bytes=-5000", 1000), Some(Ok((0, 999))));
+        assert_eq!(parse_range("bytes=0-5000", 1000), Some(Ok((0, 999))));
+    }
+
+    #[test]
+    fn open_ranges_are_capped() {
+        let len = 3 * MAX_RANGE_BYTES;
+        assert_eq!(
+            parse_range("bytes=0-", len),
+            Some(Ok((0, MAX_RANGE_BYTES - 1)))
+        );
+        assert_eq!(
+            parse_range("bytes=0-", MAX_RANGE_BYTES / 2),
+            Some(Ok((0, MAX_RANGE_BYTES / 2 - 1)))
+        );
+    }
+
+    #[test]
+    fn parse_range_refuses_unsatisfiable_ranges() {
+        assert_eq!(parse_range("bytes=1000-", 1000), Some(Err(())));
+        assert_eq!(parse_range("bytes=5-1", 1000), Some(Err(())));
+        assert_eq!(parse_range("bytes=-0", 1000), Some(Err(())));
+        assert_eq!(parse_range("bytes=-5", 0), Some(Err(())));
+    }
+
+    #[test]
+    fn parse_range_serves_the_whole_file_otherwise() {
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+        assert_eq!(parse_range("bytes=a-b", 1000), None);
+        assert_eq!(parse_range("bytes=-", 1000), None);
+        assert_eq!(parse_range("bytes=5", 1000), None);
+    }
+}
@@ -0,0 +1,88 @@
+use std::{
+    ffi::OsStr,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Suffix of the file a write is staged in; shared with the backend's `atomic_io`.
+const TEMP_SUFFIX: &str = ".tmp";
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(TEMP_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Replaces `path` with `contents` by writing `<path>.tmp`, syncing it to disk and
+/// renaming it over the original, so a crash leaves either the old file or the new.
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let temp = temp_path(path);
+    let result = (|| {
+        let mut file = File::create(&temp)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&temp, path)?;
+        sync_parent(path);
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// `fs::copy` with the guarantees of `write`.
+pub fn copy(from: &Path, to: &Path) -> io::Result<()> {
+    write(to, fs::read(from)?)
+}
+
+/// The rename is only durable once the directory entry itself is on disk.
+#[cfg(unix)]
+fn sync_parent(path: &Path) {
+    if let Some(dir) = path.parent().and_then(|dir| File::open(dir).ok()) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) {}
+
+/// Cleans up `.tmp` files a crash left in `dir`. A leftover next to its target is
+/// an unfinished write and is dropped; one whose target never got written is kept
+/// if it holds complete JSON.
+pub fn recover(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let temp = entry.path();
+        let Some(target) = temp
+            .file_name()
+            .and_then(OsStr::to_str)
+            .and_then(|name| name.strip_suffix(TEMP_SUFFIX))
+            .filter(|name| !name.is_empty())
+            .map(|name| dir.join(name))
+        else {
+            continue;
+        };
+        if !temp.is_file() {
+            continue;
+        }
+        let complete = !target.exists()
+            && fs::read(&temp)
+                .ok()
+                .is_some_and(|raw| serde_json::from_slice::<serde_json::Value>(&raw).is_ok());
+        let result = if complete {
+            tracing::info!("[Files] Recovered {}", target.display());
+            fs::rename(&temp, &target)
+        } else {
+            tracing::warn!("[Files] Discarded unfinished write {}", temp.display());
+            fs::remove_file(&temp)
+        };
+        if let Err(err) = result {
+            tracing::warn!("[Files] Failed to clean up {}: {err}", temp.display());
+        }
+    }
+}
@@ -0,0 +1,660 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, RwLock,
+    },
+    time::Duration,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use image::{imageops::FilterType, ImageFormat};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    assets, atomic_file, conversation_windows,
+    error::{AppError, ErrorCode},
+    events,
+    scan::{Scanner, Verdict},
+    settings::SettingsStore,
+};
+
+/// Subfolder of the store holding files that are being scanned or were flagged.
+/// The backend refuses to read anything inside it.
+pub const QUARANTINE_DIR: &str = "quarantine";
+const THUMBNAIL_DIR: &str = "thumbnails";
+const INDEX_FILE: &str = "index.json";
+const DEFAULT_QUOTA_MB: u64 = 1024;
+const DEFAULT_THUMBNAIL_SIZE: u32 = 256;
+const MAX_THUMBNAIL_SIZE: u32 = 1024;
+/// Files touched this recently may still be on their way into a draft and are
+/// never evicted.
+const EVICTION_GRACE_SECS: i64 = 60 * 60;
+const GC_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttachmentSettings {
+    /// Size the store may grow to before the least recently used files go.
+    pub quota_mb: u64,
+}
+
+impl Default for AttachmentSettings {
+    fn default() -> Self {
+        Self {
+            quota_mb: DEFAULT_QUOTA_MB,
+        }
+    }
+}
+
+/// What the store knows about a file beyond its content-addressed name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    id: String,
+    name: String,
+    mime: String,
+    size: u64,
+    created_at: i64,
+    last_used_at: i64,
+    /// Detached conversations that staged the file; other conversation windows
+    /// cannot load it. The main window loads everything.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    conversations: BTreeSet<String>,
+}
+
+/// Keyed by file name: the same content saved with another extension is a
+/// separate file.
+type Index = BTreeMap<String, IndexEntry>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AttachmentInfo {
+    id: String,
+    file: String,
+    name: String,
+    mime: String,
+    size: u64,
+    created_at: i64,
+    last_used_at: i64,
+    url: String,
+    /// Only for images the shell can decode.
+    thumbnail_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AttachmentList {
+    attachments: Vec<AttachmentInfo>,
+    total_bytes: u64,
+    quota_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GcReport {
+    removed: usize,
+    freed_bytes: u64,
+    /// Files found without an index entry, e.g. from an older version, and kept.
+    adopted: usize,
+}
+
+events::payload! {
+    pub struct AttachmentHandle {
+        pub id: String,
+        pub name: String,
+        pub mime: String,
+        pub size: u64,
+        pub path: PathBuf,
+        /// Why the scan flagged the file; quarantined files must not reach the agent.
+        pub quarantine: Option<String>,
+    }
+}
+
+pub struct AttachmentStore {
+    /// Moves with the active workspace.
+    root: RwLock<PathBuf>,
+    scanner: Scanner,
+    quota_bytes: AtomicU64,
+    /// Serializes read-modify-write of the index file.
+    index: Mutex<()>,
+}
+
+impl AttachmentStore {
+    pub fn new(root: PathBuf, settings: &AttachmentSettings) -> Self {
+        Self {
+            root: RwLock::new(root),
+            scanner: Scanner::default(),
+            quota_bytes: AtomicU64::new(quota_bytes(settings)),
+            index: Mutex::new(()),
+        }
+    }
+
+    fn root(&self) -> PathBuf {
+        self.root
+            .read()
+            .map(|root| root.clone())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().clone())
+    }
+
+    /// Takes effect for the next read or write; stored files are not moved.
+    pub fn set_root(&self, root: PathBuf) {
+        match self.root.write() {
+            Ok(mut current) => *current = root,
+            Err(poisoned) => *poisoned.into_inner() = root,
+        }
+    }
+
+    pub fn quarantine_dir(&self) -> PathBuf {
+        self.root().join(QUARANTINE_DIR)
+    }
+
+    /// Writes `bytes` under a content-addressed name; identical content is stored once.
+    /// New content is written to the quarantine folder and only moved into the store
+    /// once the scan passes.
+    pub fn store_bytes(
+        &self,
+        bytes: &[u8],
+        name: &str,
+        mime: &str,
+    ) -> Result<AttachmentHandle, String> {
+        let quarantine_dir = self.quarantine_dir();
+        fs::create_dir_all(&quarantine_dir)
+            .map_err(|err| format!("Failed to create attachment directory: {err}"))?;
+        let id = hex_digest(bytes);
+        let file_name = file_name_for(&id, name);
+        let mut handle = AttachmentHandle {
+            id,
+            name: name.to_string(),
+            mime: mime.to_string(),
+            size: bytes.len() as u64,
+            path: self.root().join(&file_name),
+            quarantine: None,
+        };
+        if handle.path.exists() {
+            self.record(&file_name, &handle);
+            return Ok(handle);
+        }
+        let staged = quarantine_dir.join(&file_name);
+        let reason_path = quarantine_dir.join(format!("{}.reason", handle.id));
+        if let Ok(reason) = fs::read_to_string(&reason_path) {
+            handle.path = staged;
+            handle.quarantine = Some(reason);
+            return Ok(handle);
+        }
+        fs::write(&staged, bytes).map_err(|err| format!("Failed to write attachment: {err}"))?;
+        match self.scanner.scan(&staged) {
+            Verdict::Clean => {
+                fs::rename(&staged, &handle.path)
+                    .map_err(|err| format!("Failed to store attachment: {err}"))?;
+                self.record(&file_name, &handle);
+                if let Err(err) = self.collect_garbage() {
+                    tracing::warn!("[Attachments] {err}");
+                }
+            }
+            Verdict::Flagged(reason) => {
+                tracing::warn!("[Attachments] Quarantined {name}: {reason}");
+                let _ = fs::write(&reason_path, &reason);
+                handle.path = staged;
+                handle.quarantine = Some(reason);
+            }
+        }
+        Ok(handle)
+    }
+
+    pub fn set_quota(&self, settings: &AttachmentSettings) {
+        self.quota_bytes
+            .store(quota_bytes(settings), Ordering::SeqCst);
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.root().join(INDEX_FILE)
+    }
+
+    fn load_index(&self) -> Index {
+        let path = self.index_path();
+        match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|err| {
+                tracing::warn!(
+                    "[Attachments] Rebuilding unreadable {}: {err}",
+                    path.display()
+                );
+                Index::new()
+            }),
+            Err(_) => Index::new(),
+        }
+    }
+
+    fn save_index(&self, index: &Index) -> Result<(), String> {
+        let raw = serde_json::to_vec_pretty(index)
+            .map_err(|err| format!("Failed to encode the attachment index: {err}"))?;
+        atomic_file::write(&self.index_path(), raw)
+            .map_err(|err| format!("Failed to write the attachment index: {err}"))
+    }
+
+    /// Applies `change` to the index under the lock and saves it.
+    fn update_index<T>(&self, change: impl FnOnce(&mut Index) -> T) -> Result<T, String> {
+        let _guard = self
+            .index
+            .lock()
+            .map_err(|_| "Attachment index is unavailable.".to_string())?;
+        let mut index = self.load_index();
+        let result = change(&mut index);
+        self.save_index(&index)?;
+        Ok(result)
+    }
+
+    /// Adds or refreshes the entry for a stored file; a failure only costs its
+    /// metadata, which garbage collection re-adopts.
+    fn record(&self, file_name: &str, handle: &AttachmentHandle) {
+        let now = Utc::now().timestamp();
+        let recorded = self.update_index(|index| {
+            index
+                .entry(file_name.to_string())
+                .and_modify(|entry| entry.last_used_at = now)
+                .or_insert_with(|| IndexEntry {
+                    id: handle.id.clone(),
+                    name: handle.name.clone(),
+                    mime: handle.mime.clone(),
+                    size: handle.size,
+                    created_at: now,
+                    last_used_at: now,
+                    conversations: BTreeSet::new(),
+                });
+        });
+        if let Err(err) = recorded {
+            tracing::warn!("[Attachments] {err}");
+        }
+    }
+
+    /// Lets `conversation`'s detached window load a stored file.
+    fn claim(&self, handle: &AttachmentHandle, conversation: &str) {
+        let Some(file_name) = handle.path.file_name().and_then(|name| name.to_str()) else {
+            return;
+        };
+        let claimed = self.update_index(|index| {
+            if let Some(entry) = index.get_mut(file_name) {
+                entry.conversations.insert(conversation.to_string());
+            }
+        });
+        if let Err(err) = claimed {
+            tracing::warn!("[Attachments] {err}");
+        }
+    }
+
+    fn touch(&self, file_name: &str) {
+        let now = Utc::now().timestamp();
+        let touched = self.update_index(|index| {
+            if let Some(entry) = index.get_mut(file_name) {
+                entry.last_used_at = now;
+            }
+        });
+        if let Err(err) = touched {
+            tracing::warn!("[Attachments] {err}");
+        }
+    }
+
+    pub fn list(&self) -> AttachmentList {
+        let index = self.load_index();
+        let total_bytes = index.values().map(|entry| entry.size).sum();
+        let mut attachments: Vec<AttachmentInfo> = index
+            .into_iter()
+            .map(|(file, entry)| AttachmentInfo {
+                url: url(&file, None),
+                thumbnail_url: thumbnailable(&entry.mime)
+                    .then(|| url(&file, Some(DEFAULT_THUMBNAIL_SIZE))),
+                id: entry.id,
+                file,
+                name: entry.name,
+                mime: entry.mime,
+                size: entry.size,
+                created_at: entry.created_at,
+                last_used_at: entry.last_used_at,
+            })
+            .collect();
+        attachments.sort_by_key(|attachment| std::cmp::Reverse(attachment.last_used_at));
+        AttachmentList {
+            attachments,
+            total_bytes,
+            quota_bytes: self.quota_bytes.load(Ordering::SeqCst),
+        }
+    }
+
+    fn remove_file(&self, file_name: &str) -> u64 {
+        let root = self.root();
+        let size = fs::metadata(root.join(file_name))
+            .map(|meta| meta.len())
+            .unwrap_or(0);
+        let _ = fs::remove_file(root.join(file_name));
+        self.remove_thumbnails(file_name);
+        size
+    }
+
+    fn remove_thumbnails(&self, file_name: &str) {
+        let Ok(entries) = fs::read_dir(self.root().join(THUMBNAIL_DIR)) else {
+            return;
+        };
+        let prefix = format!("{file_name}-");
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+
+    /// Removes every file with content `id`, under whichever extensions it was saved.
+    pub fn delete(&self, id: &str) -> Result<u64, AppError> {
+        let removed = self.update_index(|index| {
+            let files: Vec<String> = index
+                .iter()
+                .filter(|(_, entry)| entry.id == id)
+                .map(|(file, _)| file.clone())
+                .collect();
+            for file in &files {
+                index.remove(file);
+            }
+            files
+        })?;
+        if removed.is_empty() {
+            return Err(AppError::not_found(format!("No attachment {id}.")));
+        }
+        Ok(removed.iter().map(|file| self.remove_file(file)).sum())
+    }
+
+    /// Adopts files the index does not know, drops thumbnails of files that are
+    /// gone, then evicts the least recently used files until the store fits its
+    /// quota. The quarantine folder is left to the scanner.
+    pub fn collect_garbage(&self) -> Result<GcReport, String> {
+        let root = self.root();
+        let quota = self.quota_bytes.load(Ordering::SeqCst);
+        let mut report = GcReport::default();
+        let evicted = self.update_index(|index| {
+            for entry in fs::read_dir(&root).into_iter().flatten().flatten() {
+                let file = entry.file_name().to_string_lossy().to_string();
+                let Ok(meta) = entry.metadata() else {
+                    continue;
+                };
+                if !meta.is_file() || !is_stored_name(&file) || index.contains_key(&file) {
+                    continue;
+                }
+                let modified = meta
+                    .modified()
+                    .ok()
+                    .map(|time| chrono::DateTime::<Utc>::from(time).timestamp())
+                    .unwrap_or_else(|| Utc::now().timestamp());
+                index.insert(
+                    file.clone(),
+                    IndexEntry {
+                        id: file.split('.').next().unwrap_or_default().to_string(),
+                        mime: mime_for(&file).to_string(),
+                        name: file,
+                        size: meta.len(),
+                        created_at: modified,
+                        last_used_at: modified,
+                        conversations: BTreeSet::new(),
+                    },
+                );
+                report.adopted += 1;
+            }
+            index.retain(|file, _| root.join(file).is_file());
+            let mut total: u64 = index.values().map(|entry| entry.size).sum();
+            let cutoff = Utc::now().timestamp() - EVICTION_GRACE_SECS;
+            let mut by_age: Vec<(String, i64, u64)> = index
+                .iter()
+                .filter(|(_, entry)| entry.last_used_at < cutoff)
+                .map(|(file, entry)| (file.clone(), entry.last_used_at, entry.size))
+                .collect();
+            by_age.sort_by_key(|(_, last_used, _)| *last_used);
+            let mut evicted = Vec::new();
+            for (file, _, size) in by_age {
+                if total <= quota {
+                    break;
+                }
+                index.remove(&file);
+                total = total.saturating_sub(size);
+                evicted.push(file);
+            }
+            evicted
+        })?;
+        for file in &evicted {
+            report.freed_bytes += self.remove_file(file);
+        }
+        report.removed = evicted.len();
+        let index = self.load_index();
+        for entry in fs::read_dir(root.join(THUMBNAIL_DIR))
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let original = name.rsplit_once('-').map(|(original, _)| original);
+            if !original.is_some_and(|original| index.contains_key(original)) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+        if report.removed > 0 {
+            tracing::info!(
+                "[Attachments] Evicted {} files ({} bytes) to stay under the quota.",
+                report.removed,
+                report.freed_bytes
+            );
+        }
+        Ok(report)
+    }
+
+    /// A cached PNG no larger than `size` on either side, made on first request.
+    fn thumbnail(&self, file_name: &str, size: u32) -> Result<PathBuf, String> {
+        let dir = self.root().join(THUMBNAIL_DIR);
+        let path = dir.join(format!("{file_name}-{size}.png"));
+        if path.is_file() {
+            return Ok(path);
+        }
+        let image = image::open(self.root().join(file_name))
+            .map_err(|err| format!("Failed to decode {file_name}: {err}"))?;
+        let thumbnail = if image.width() > size || image.height() > size {
+            image.resize(size, size, FilterType::Triangle)
+        } else {
+            image
+        };
+        fs::create_dir_all(&dir)
+            .map_err(|err| format!("Failed to create the thumbnail folder: {err}"))?;
+        thumbnail
+            .save_with_format(&path, ImageFormat::Png)
+            .map_err(|err| format!("Failed to save the thumbnail: {err}"))?;
+        Ok(path)
+    }
+}
+
+/// `<sha256>` or `<sha256>.<ext>`, as `store_bytes` names files.
+fn is_stored_name(file: &str) -> bool {
+    let stem = file.split('.').next().unwrap_or_default();
+    stem.len() == 64
+        && stem.chars().all(|c| c.is_ascii_hexdigit())
+        && file.matches('.').count() <= 1
+}
+
+fn quota_bytes(settings: &AttachmentSettings) -> u64 {
+    settings.quota_mb.saturating_mul(1024 * 1024)
+}
+
+/// Formats the `image` crate is built with here.
+fn thumbnailable(mime: &str) -> bool {
+    matches!(mime, "image/png" | "image/jpeg")
+}
+
+/// Where the webview loads a stored file or its thumbnail from.
+fn url(file: &str, thumbnail: Option<u32>) -> String {
+    let base = format!("{}/attachments/{file}", assets::base_url());
+    match thumbnail {
+        Some(size) => format!("{base}?thumbnail={size}"),
+        None => base,
+    }
+}
+
+/// The stored file behind a URL from `url` and its MIME type, or a PNG thumbnail
+/// of it for `thumbnail`. Serving the original counts as a use. With `conversation`,
+/// only files that conversation staged are served.
+pub fn resolve<R: Runtime>(
+    app: &AppHandle<R>,
+    file: &str,
+    thumbnail: Option<u32>,
+    conversation: Option<&str>,
+) -> Result<(PathBuf, String), AppError> {
+    let store = app
+        .try_state::<AttachmentStore>()
+        .ok_or_else(|| AppError::unavailable("Attachments are not ready."))?;
+    // Content-addressed names only, so no path can leave the store.
+    let entry = is_stored_name(file)
+        .then(|| store.load_index().get(file).cloned())
+        .flatten()
+        .ok_or_else(|| AppError::not_found("No such attachment."))?;
+    if conversation.is_some_and(|conversation| !entry.conversations.contains(conversation)) {
+        return Err(AppError::new(
+            ErrorCode::Unauthorized,
+            "The attachment belongs to another conversation.",
+        ));
+    }
+    match thumbnail {
+        Some(_) if !thumbnailable(&entry.mime) => {
+            Err(AppError::invalid_input("No thumbnail for this type."))
+        }
+        Some(size) => {
+            let size = size.clamp(16, MAX_THUMBNAIL_SIZE);
+            Ok((store.thumbnail(file, size)?, "image/png".to_string()))
+        }
+        None => {
+            store.touch(file);
+            Ok((store.root().join(file), entry.mime))
+        }
+    }
+}
+
+/// Collects garbage at launch and then every few hours.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let handle = app.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || {
+                handle.state::<AttachmentStore>().collect_garbage()
+            })
+            .await;
+            if let Ok(Err(err)) = result {
+                tracing::warn!("[Attachments] {err}");
+            }
+            tokio::time::sleep(GC_INTERVAL).await;
+        }
+    });
+}
+
+/// Stores a file the webview read itself, such as a drop onto the chat, so it passes
+/// the same scan as the inbox and the clipboard.
+#[tauri::command]
+pub fn stage_attachment(
+    webview: tauri::Webview,
+    store: tauri::State<AttachmentStore>,
+    name: String,
+    mime: Option<String>,
+    data_base64: String,
+) -> Result<AttachmentHandle, AppError> {
+    let bytes = STANDARD.decode(data_base64.trim()).map_err(|err| {
+        AppError::invalid_input("Attachment data is not valid base64.").with_details(err)
+    })?;
+    let mime = mime
+        .filter(|mime| !mime.is_empty())
+        .unwrap_or_else(|| mime_for(&name).to_string());
+    let handle = store.store_bytes(&bytes, &name, &mime)?;
+    let conversation = webview
+        .label()
+        .strip_prefix(conversation_windows::LABEL_PREFIX);
+    if let (Some(conversation), None) = (conversation, &handle.quarantine) {
+        store.claim(&handle, conversation);
+    }
+    Ok(handle)
+}
+
+#[tauri::command]
+pub async fn list_attachments(app: AppHandle) -> Result<AttachmentList, AppError> {
+    tauri::async_runtime::spawn_blocking(move || app.state::<AttachmentStore>().list())
+        .await
+        .map_err(|err| AppError::from(format!("Attachment task failed: {err}")))
+}
+
+/// Deletes a stored file and its thumbnails; returns the bytes freed.
+#[tauri::command]
+pub async fn delete_attachment(app: AppHandle, id: String) -> Result<u64, AppError> {
+    tauri::async_runtime::spawn_blocking(move || app.state::<AttachmentStore>().delete(&id))
+        .await
+        .map_err(|err| AppError::from(format!("Attachment task failed: {err}")))?
+}
+
+#[tauri::command]
+pub async fn set_attachment_quota(app: AppHandle, quota_mb: u64) -> Result<GcReport, AppError> {
+    if quota_mb == 0 {
+        return Err(AppError::invalid_input("The quota must be at least 1 MB."));
+    }
+    let settings = app
+        .state::<SettingsStore>()
+        .update(|settings| settings.attachments.quota_mb = quota_mb)?
+        .attachments;
+    tauri::async_runtime::spawn_blocking(move || {
+        let store = app.state::<AttachmentStore>();
+        store.set_quota(&settings);
+        store.collect_garbage()
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Attachment task failed: {err}")))?
+    .map_err(AppError::from)
+}
+
+/// Best-effort MIME type from a file name, for files that arrive without one.
+pub fn mime_for(name: &str) -> &'static str {
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "json" => "application/json",
+        "csv" => "text/csv",
+        "md" => "text/markdown",
+        "txt" | "log" => "text/plain",
+        "html" | "htm" => "text/html",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "wav" => "audio/wav",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "m4a" => "audio/mp4",
+        "flac" => "audio/flac",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mov" => "video/quicktime",
+        _ => "application/octet-stream",
+    }
+}
+
+pub(crate) fn hex_digest(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn file_name_for(id: &str, name: &str) -> String {
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .filter(|ext| !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric()));
+    match extension {
+        Some(ext) => format!("{id}.{}", ext.to_ascii_lowercase()),
+        None => id.to_string(),
+    }
+}
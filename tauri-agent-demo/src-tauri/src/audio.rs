@@ -0,0 +1,707 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    FromSample, SizedSample,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    attachments::{AttachmentHandle, AttachmentStore},
+    error::AppError,
+    events, proxy, rpc, BackendState,
+};
+
+const LEVEL_WINDOW_MS: u64 = 100;
+const DEFAULT_SILENCE_THRESHOLD: f32 = 0.01;
+const TRANSCRIPTION_PATH: &str = "/audio/transcriptions";
+/// What speech models are trained on; anything more is upload size for nothing.
+const TRANSCRIPTION_SAMPLE_RATE: u32 = 16_000;
+const CHUNK_INTERVAL: Duration = Duration::from_millis(500);
+const CHUNK_TIMEOUT: Duration = Duration::from_secs(10);
+const FINISH_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Default)]
+pub struct AudioRecorder(Mutex<Option<ActiveRecording>>);
+
+struct ActiveRecording {
+    stop: Arc<AtomicBool>,
+    worker: JoinHandle<Result<CapturedAudio, String>>,
+    mode: Option<RecordingMode>,
+}
+
+struct CapturedAudio {
+    sample_rate: u32,
+    channels: u16,
+    samples: Vec<f32>,
+    stopped_on_silence: bool,
+    transcript: Option<String>,
+}
+
+/// Where the audio goes once captured; without one only the summary comes back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingMode {
+    /// A WAV file in the attachment store.
+    Attachment,
+    /// Streamed to a backend transcription session while recording.
+    Transcribe,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RecordingOptions {
+    /// RMS level below which a 100ms window counts as silence.
+    silence_threshold: Option<f32>,
+    /// Stop automatically after this much continuous silence following speech.
+    silence_timeout_ms: Option<u64>,
+    mode: Option<RecordingMode>,
+    /// Transcription language; the backend falls back to the app locale.
+    language: Option<String>,
+    /// The LLM config whose provider transcribes; the default one otherwise.
+    config_id: Option<String>,
+}
+
+events::payload! {
+    pub struct AudioLevel {
+        rms: f32,
+        peak: f32,
+        silent: bool,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingSummary {
+    duration_ms: u64,
+    sample_rate: u32,
+    channels: u16,
+    stopped_on_silence: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attachment: Option<AttachmentHandle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transcript: Option<String>,
+}
+
+events::payload! {
+    pub struct TranscriptEvent {
+        text: String,
+    }
+}
+
+struct LevelMeter {
+    window_len: usize,
+    count: usize,
+    sum_squares: f32,
+    peak: f32,
+    threshold: f32,
+    silence_timeout_ms: Option<u64>,
+    silent_ms: u64,
+    heard_speech: bool,
+}
+
+impl LevelMeter {
+    fn new(sample_rate: u32, channels: u16, options: &RecordingOptions) -> Self {
+        let window_len = (sample_rate as u64 * LEVEL_WINDOW_MS / 1000) as usize * channels as usize;
+        Self {
+            window_len: window_len.max(1),
+            count: 0,
+            sum_squares: 0.0,
+            peak: 0.0,
+            threshold: options
+                .silence_threshold
+                .unwrap_or(DEFAULT_SILENCE_THRESHOLD),
+            silence_timeout_ms: options.silence_timeout_ms,
+            silent_ms: 0,
+            heard_speech: false,
+        }
+    }
+
+    fn push(&mut self, sample: f32) -> Option<AudioLevel> {
+        self.sum_squares += sample * sample;
+        self.peak = self.peak.max(sample.abs());
+        self.count += 1;
+        if self.count < self.window_len {
+            return None;
+        }
+        let rms = (self.sum_squares / self.count as f32).sqrt();
+        let level = AudioLevel {
+            rms,
+            peak: self.peak,
+            silent: rms < self.threshold,
+        };
+        if level.silent {
+            self.silent_ms += LEVEL_WINDOW_MS;
+        } else {
+            self.silent_ms = 0;
+            self.heard_speech = true;
+        }
+        self.count = 0;
+        self.sum_squares = 0.0;
+        self.peak = 0.0;
+        Some(level)
+    }
+
+    fn silence_elapsed(&self) -> bool {
+        match self.silence_timeout_ms {
+            Some(timeout) => self.heard_speech && self.silent_ms >= timeout,
+            None => false,
+        }
+    }
+}
+
+struct CaptureSink {
+    samples: Vec<f32>,
+    meter: LevelMeter,
+    stopped_on_silence: bool,
+}
+
+/// Downmixes interleaved frames to mono and resamples them linearly to 16-bit PCM,
+/// carrying the position across chunks so their seams do not click.
+struct PcmEncoder {
+    channels: usize,
+    step: f64,
+    /// Next output position in input frames, relative to the chunk being encoded;
+    /// -1 up to 0 falls between the previous chunk's last frame and this one's first.
+    position: f64,
+    previous: f32,
+}
+
+impl PcmEncoder {
+    fn new(sample_rate: u32, channels: u16, target_rate: u32) -> Self {
+        Self {
+            channels: channels.max(1) as usize,
+            step: sample_rate as f64 / target_rate as f64,
+            position: 0.0,
+            previous: 0.0,
+        }
+    }
+
+    fn encode(&mut self, interleaved: &[f32]) -> Vec<u8> {
+        let mono: Vec<f32> = interleaved
+            .chunks_exact(self.channels)
+            .map(|frame| frame.iter().sum::<f32>() / self.channels as f32)
+            .collect();
+        let sample = |index: isize| {
+            if index < 0 {
+                self.previous
+            } else {
+                mono[index as usize]
+            }
+        };
+        let mut out = Vec::new();
+        let mut position = self.position;
+        while position + 1.0 < mono.len() as f64 {
+            let base = position.floor();
+            let index = base as isize;
+            let fraction = (position - base) as f32;
+            let value = sample(index) + (sample(index + 1) - sample(index)) * fraction;
+            out.extend_from_slice(&pcm16(value).to_le_bytes());
+            position += self.step;
+        }
+        if let Some(last) = mono.last() {
+            self.position = position - mono.len() as f64;
+            self.previous = *last;
+        }
+        out
+    }
+}
+
+fn pcm16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+/// 16-bit PCM WAV of the capture as recorded.
+fn wav(samples: &[f32], sample_rate: u32, channels: u16) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let block_align = channels * 2;
+    let mut out = Vec::with_capacity(44 + data_len as usize);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&channels.to_le_bytes());
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    out.extend_from_slice(&block_align.to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for &sample in samples {
+        out.extend_from_slice(&pcm16(sample).to_le_bytes());
+    }
+    out
+}
+
+/// A POST to the backend over whichever transport is attached, from a thread that
+/// may block; the body of a 2xx answer.
+pub fn post_bytes(
+    app: &AppHandle,
+    path: &str,
+    body: &Value,
+    timeout: Duration,
+) -> Result<Vec<u8>, String> {
+    let (status, bytes) = if rpc::is_attached(app) {
+        rpc::post(app, path, body, timeout)?
+    } else {
+        let base_url = app
+            .try_state::<BackendState>()
+            .map(|state| state.base_url())
+            .ok_or_else(|| "Backend is not available.".to_string())?;
+        let request = proxy::client(app)
+            .post(format!("{base_url}{path}"))
+            .json(body)
+            .timeout(timeout);
+        tauri::async_runtime::block_on(async move {
+            let response = request
+                .send()
+                .await
+                .map_err(|err| format!("Failed to reach the backend: {err}"))?;
+            let status = response.status().as_u16();
+            let bytes = response
+                .bytes()
+                .await
+                .map_err(|err| format!("Failed to read the backend's answer: {err}"))?;
+            Ok::<_, String>((status, bytes.to_vec()))
+        })?
+    };
+    if !(200..300).contains(&status) {
+        let detail = serde_json::from_slice::<Value>(&bytes)
+            .ok()
+            .and_then(|value| value.get("detail")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("status {status}"));
+        return Err(format!("Backend rejected {path}: {detail}"));
+    }
+    Ok(bytes)
+}
+
+fn backend_post(
+    app: &AppHandle,
+    path: &str,
+    body: &Value,
+    timeout: Duration,
+) -> Result<Value, String> {
+    let bytes = post_bytes(app, path, body, timeout)?;
+    serde_json::from_slice(&bytes).map_err(|err| format!("Backend sent unreadable JSON: {err}"))
+}
+
+/// A backend session fed the capture every `CHUNK_INTERVAL`, so only the tail is
+/// left to send when the user stops.
+struct Transcription {
+    id: String,
+    encoder: PcmEncoder,
+    /// Interleaved samples of the capture already sent.
+    sent: usize,
+}
+
+impl Transcription {
+    fn begin(
+        app: &AppHandle,
+        sample_rate: u32,
+        channels: u16,
+        options: &RecordingOptions,
+    ) -> Result<Self, String> {
+        let created = backend_post(
+            app,
+            TRANSCRIPTION_PATH,
+            &json!({
+                "sample_rate": TRANSCRIPTION_SAMPLE_RATE,
+                "language": options.language,
+                "config_id": options.config_id,
+            }),
+            CHUNK_TIMEOUT,
+        )?;
+        let id = created
+            .get("id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| "Backend did not open a transcription session.".to_string())?
+            .to_string();
+        Ok(Self {
+            id,
+            encoder: PcmEncoder::new(sample_rate, channels, TRANSCRIPTION_SAMPLE_RATE),
+            sent: 0,
+        })
+    }
+
+    fn send_pending(&mut self, app: &AppHandle, sink: &Mutex<CaptureSink>) -> Result<(), String> {
+        let pending = {
+            let guard = sink
+                .lock()
+                .map_err(|_| "Recording buffer is unavailable.".to_string())?;
+            // Whole frames only; the rest goes with the next chunk.
+            let end = guard.samples.len() - guard.samples.len() % self.encoder.channels;
+            guard.samples[self.sent..end].to_vec()
+        };
+        self.sent += pending.len();
+        let pcm = self.encoder.encode(&pending);
+        if pcm.is_empty() {
+            return Ok(());
+        }
+        backend_post(
+            app,
+            &format!("{TRANSCRIPTION_PATH}/{}/chunks", self.id),
+            &json!({ "data_base64": STANDARD.encode(pcm) }),
+            CHUNK_TIMEOUT,
+        )
+        .map(|_| ())
+    }
+
+    fn finish(mut self, app: &AppHandle, sink: &Mutex<CaptureSink>) -> Result<String, String> {
+        if let Err(err) = self.send_pending(app, sink) {
+            self.cancel(app);
+            return Err(err);
+        }
+        let done = backend_post(
+            app,
+            &format!("{TRANSCRIPTION_PATH}/{}/finish", self.id),
+            &json!({}),
+            FINISH_TIMEOUT,
+        )?;
+        Ok(done
+            .get("text")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    fn cancel(&self, app: &AppHandle) {
+        let path = format!("{TRANSCRIPTION_PATH}/{}/cancel", self.id);
+        if let Err(err) = backend_post(app, &path, &json!({}), CHUNK_TIMEOUT) {
+            tracing::warn!("[Audio] {err}");
+        }
+    }
+}
+
+/// Async because `transcribe` mode opens its backend session before returning.
+#[tauri::command]
+pub async fn start_recording(
+    app: AppHandle,
+    options: Option<RecordingOptions>,
+) -> Result<(), AppError> {
+    let in_progress = || -> Result<bool, AppError> {
+        Ok(app
+            .state::<AudioRecorder>()
+            .0
+            .lock()
+            .map_err(|_| AppError::unavailable("Recorder state is unavailable."))?
+            .is_some())
+    };
+    if in_progress()? {
+        return Err(AppError::invalid_input(
+            "A recording is already in progress.",
+        ));
+    }
+    let stop = Arc::new(AtomicBool::new(false));
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let worker_stop = stop.clone();
+    let options = options.unwrap_or_default();
+    let mode = options.mode;
+    let handle = app.clone();
+    let worker = std::thread::spawn(move || record(handle, worker_stop, options, ready_tx));
+    let ready = tauri::async_runtime::spawn_blocking(move || ready_rx.recv())
+        .await
+        .map_err(|err| AppError::from(format!("Recording task failed: {err}")))?;
+    match ready {
+        Ok(Ok(())) => {
+            let recorder = app.state::<AudioRecorder>();
+            let mut guard = recorder
+                .0
+                .lock()
+                .map_err(|_| AppError::unavailable("Recorder state is unavailable."))?;
+            if guard.is_some() {
+                // Another start won the race while this one was opening.
+                stop.store(true, Ordering::SeqCst);
+                return Err(AppError::invalid_input(
+                    "A recording is already in progress.",
+                ));
+            }
+            *guard = Some(ActiveRecording { stop, worker, mode });
+            Ok(())
+        }
+        Ok(Err(err)) => Err(AppError::unavailable(err)),
+        Err(_) => Err(AppError::from(
+            worker
+                .join()
+                .ok()
+                .and_then(|result| result.err())
+                .unwrap_or_else(|| "Recording thread exited unexpectedly.".to_string()),
+        )),
+    }
+}
+
+/// Waits for the transcript in `transcribe` mode, so it is async to keep the main
+/// thread free.
+#[tauri::command]
+pub async fn stop_recording(app: AppHandle) -> Result<RecordingSummary, AppError> {
+    let active = app
+        .state::<AudioRecorder>()
+        .0
+        .lock()
+        .map_err(|_| AppError::unavailable("Recorder state is unavailable."))?
+        .take()
+        .ok_or_else(|| AppError::not_found("No recording is in progress."))?;
+    active.stop.store(true, Ordering::SeqCst);
+    tauri::async_runtime::spawn_blocking(move || finish(&app, active))
+        .await
+        .map_err(|err| AppError::from(format!("Recording task failed: {err}")))?
+        .map_err(AppError::from)
+}
+
+fn finish(app: &AppHandle, active: ActiveRecording) -> Result<RecordingSummary, String> {
+    let captured = active
+        .worker
+        .join()
+        .map_err(|_| "Recording thread panicked.".to_string())??;
+    let frames = captured.samples.len() as u64 / captured.channels.max(1) as u64;
+    let attachment = match active.mode {
+        Some(RecordingMode::Attachment) if !captured.samples.is_empty() => {
+            let bytes = wav(&captured.samples, captured.sample_rate, captured.channels);
+            let name = format!(
+                "recording-{}.wav",
+                chrono::Local::now().format("%Y%m%d-%H%M%S")
+            );
+            Some(
+                app.state::<AttachmentStore>()
+                    .store_bytes(&bytes, &name, "audio/wav")?,
+            )
+        }
+        _ => None,
+    };
+    Ok(RecordingSummary {
+        duration_ms: frames * 1000 / captured.sample_rate.max(1) as u64,
+        sample_rate: captured.sample_rate,
+        channels: captured.channels,
+        stopped_on_silence: captured.stopped_on_silence,
+        attachment,
+        transcript: captured.transcript,
+    })
+}
+
+fn record(
+    app: AppHandle,
+    stop: Arc<AtomicBool>,
+    options: RecordingOptions,
+    ready: mpsc::Sender<Result<(), String>>,
+) -> Result<CapturedAudio, String> {
+    let opened = open_input(&app, &stop, &options).and_then(|input| {
+        let transcription = match options.mode {
+            Some(RecordingMode::Transcribe) => {
+                Some(Transcription::begin(&app, input.2, input.3, &options)?)
+            }
+            _ => None,
+        };
+        Ok((input, transcription))
+    });
+    let _ = ready.send(opened.as_ref().map(|_| ()).map_err(|err| err.clone()));
+    let ((stream, sink, sample_rate, channels), mut transcription) = opened?;
+
+    let mut last_chunk = Instant::now();
+    while !stop.load(Ordering::SeqCst) {
+        std::thread::sleep(Duration::from_millis(20));
+        if let Some(session) = transcription.as_mut() {
+            if last_chunk.elapsed() >= CHUNK_INTERVAL {
+                last_chunk = Instant::now();
+                if let Err(err) = session.send_pending(&app, &sink) {
+                    // Keep recording; the transcript cannot be trusted with a gap in it.
+                    tracing::warn!("[Audio] {err}");
+                    session.cancel(&app);
+                    transcription = None;
+                }
+            }
+        }
+    }
+    drop(stream);
+
+    let stopped_on_silence = sink
+        .lock()
+        .map_err(|_| "Recording buffer is unavailable.".to_string())?
+        .stopped_on_silence;
+    if stopped_on_silence {
+        // The frontend finishes the recording with `stop_recording` once it sees this.
+        events::audio_silence(&app, &events::Empty {});
+    }
+    let transcript = match transcription {
+        Some(session) => {
+            let text = session.finish(&app, &sink)?;
+            events::audio_transcript(&app, &TranscriptEvent { text: text.clone() });
+            Some(text)
+        }
+        None if options.mode == Some(RecordingMode::Transcribe) => {
+            return Err("Transcription was interrupted; the backend stopped answering.".into())
+        }
+        None => None,
+    };
+    let mut guard = sink
+        .lock()
+        .map_err(|_| "Recording buffer is unavailable.".to_string())?;
+    Ok(CapturedAudio {
+        sample_rate,
+        channels,
+        samples: std::mem::take(&mut guard.samples),
+        stopped_on_silence,
+        transcript,
+    })
+}
+
+type OpenedInput = (cpal::Stream, Arc<Mutex<CaptureSink>>, u32, u16);
+
+fn open_input(
+    app: &AppHandle,
+    stop: &Arc<AtomicBool>,
+    options: &RecordingOptions,
+) -> Result<OpenedInput, String> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or_else(|| "No microphone input device is available.".to_string())?;
+    let config = device
+        .default_input_config()
+        .map_err(|err| format!("Failed to read microphone config: {err}"))?;
+    let sample_rate = config.sample_rate().0;
+    let channels = config.channels();
+    let sink = Arc::new(Mutex::new(CaptureSink {
+        samples: Vec::new(),
+        meter: LevelMeter::new(sample_rate, channels, options),
+        stopped_on_silence: false,
+    }));
+    let stream_config = config.config();
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => build_stream::<f32>(&device, &stream_config, &sink, app, stop),
+        cpal::SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, &sink, app, stop),
+        cpal::SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, &sink, app, stop),
+        cpal::SampleFormat::I32 => build_stream::<i32>(&device, &stream_config, &sink, app, stop),
+        other => Err(format!("Unsupported microphone sample format '{other}'.")),
+    }?;
+    stream
+        .play()
+        .map_err(|err| format!("Failed to start microphone stream: {err}"))?;
+    Ok((stream, sink, sample_rate, channels))
+}
+
+/// Opens and drops an input stream; on macOS this is what surfaces the microphone prompt.
+#[cfg(target_os = "macos")]
+pub fn probe_input_device() {
+    let Some(device) = cpal::default_host().default_input_device() else {
+        return;
+    };
+    let Ok(config) = device.default_input_config() else {
+        return;
+    };
+    let stream = device.build_input_stream_raw(
+        &config.config(),
+        config.sample_format(),
+        |_: &cpal::Data, _: &cpal::InputCallbackInfo| {},
+        |err| tracing::warn!("[Audio] Microphone probe failed: {err}"),
+        None,
+    );
+    if let Ok(stream) = stream {
+        let _ = stream.play();
+    }
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    sink: &Arc<Mutex<CaptureSink>>,
+    app: &AppHandle,
+    stop: &Arc<AtomicBool>,
+) -> Result<cpal::Stream, String>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let sink = sink.clone();
+    let app = app.clone();
+    let stop = stop.clone();
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let Ok(mut guard) = sink.lock() else {
+                    return;
+                };
+                if guard.stopped_on_silence {
+                    return;
+                }
+                for &sample in data {
+                    let value = sample.to_sample::<f32>();
+                    guard.samples.push(value);
+                    if let Some(level) = guard.meter.push(value) {
+                        events::audio_level(&app, &level);
+                    }
+                }
+                if guard.meter.silence_elapsed() {
+                    guard.stopped_on_silence = true;
+                    stop.store(true, Ordering::SeqCst);
+                }
+            },
+            |err| tracing::warn!("[Audio] Input stream error: {err}"),
+            None,
+        )
+        .map_err(|err| format!("Failed to open microphone stream: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(bytes: &[u8]) -> Vec<i16> {
+        bytes
+            .chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+            .collect()
+    }
+
+    #[test]
+    fn encoder_downmixes_stereo_frames() {
+        let mut encoder = PcmEncoder::new(16_000, 2, 16_000);
+        let out = encoder.encode(&[0.5, -0.5, 1.0, 1.0, 0.0, 0.0]);
+        assert_eq!(samples(&out), [0, i16::MAX]);
+    }
+
+    #[test]
+    fn encoder_resamples_to_the_target_rate() {
+        let mut encoder = PcmEncoder::new(48_000, 1, 16_000);
+        let input: Vec<f32> = (0..9).map(|index| index as f32 / 10.0).collect();
+        let expected: Vec<i16> = [0.0, 0.3, 0.6].into_iter().map(pcm16).collect();
+        assert_eq!(samples(&encoder.encode(&input)), expected);
+    }
+
+    #[test]
+    fn encoder_carries_its_position_across_chunks() {
+        let input: Vec<f32> = (0..12).map(|index| (index as f32 * 0.7).sin()).collect();
+        let whole = PcmEncoder::new(24_000, 1, 16_000).encode(&input);
+        let mut encoder = PcmEncoder::new(24_000, 1, 16_000);
+        let mut chunked = encoder.encode(&input[..5]);
+        chunked.extend(encoder.encode(&input[5..]));
+        assert_eq!(samples(&whole).len(), 8);
+        assert_eq!(chunked, whole);
+    }
+
+    #[test]
+    fn pcm16_clamps_out_of_range_samples() {
+        assert_eq!(pcm16(0.0), 0);
+        assert_eq!(pcm16(2.0), i16::MAX);
+        assert_eq!(pcm16(-2.0), -i16::MAX);
+    }
+
+    #[test]
+    fn wav_header_describes_the_data() {
+        let out = wav(&[0.0, 0.5, -0.5, 1.0], 16_000, 2);
+        assert_eq!(out.len(), 44 + 8);
+        assert_eq!(&out[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(out[4..8].try_into().unwrap()), 36 + 8);
+        assert_eq!(u16::from_le_bytes(out[22..24].try_into().unwrap()), 2);
+        assert_eq!(u32::from_le_bytes(out[24..28].try_into().unwrap()), 16_000);
+        assert_eq!(u32::from_le_bytes(out[40..44].try_into().unwrap()), 8);
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    io::{Read, Write},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::{
+    error::{AppError, ErrorCode},
+    locale,
+    tool_policy::{self, PermissionRequest, Verdict},
+};
+
+const SCRIPT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Leaves room for the summary in the approval dialog, which shows the whole script.
+const MAX_SCRIPT_LEN: usize = 3_000;
+
+/// COM servers the Windows bridge may instantiate.
+const ALLOWED_COM_SERVERS: &[&str] = &[
+    "Outlook.Application",
+    "Excel.Application",
+    "Word.Application",
+    "PowerPoint.Application",
+    "iTunes.Application",
+];
+
+/// Cmdlets and APIs that would let a COM script escape to arbitrary code or the network.
+const BLOCKED_POWERSHELL: &[&str] = &[
+    "new-object",
+    "start-process",
+    "invoke-expression",
+    "iex ",
+    "add-type",
+    "invoke-webrequest",
+    "invoke-restmethod",
+    "downloadstring",
+    "downloadfile",
+    "[system.reflection",
+    "-encodedcommand",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutomationOutput {
+    stdout: String,
+    stderr: String,
+    exit_code: Option<i32>,
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut text);
+        }
+        text
+    })
+}
+
+fn run_with_timeout(mut command: Command, stdin: &str) -> Result<AutomationOutput, String> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Failed to start automation: {err}"))?;
+    if let Some(mut pipe) = child.stdin.take() {
+        pipe.write_all(stdin.as_bytes())
+            .map_err(|err| format!("Failed to send script: {err}"))?;
+    }
+    // Read both pipes concurrently so a chatty script cannot fill a buffer and stall.
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+    let deadline = Instant::now() + SCRIPT_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "Automation timed out after {} seconds.",
+                    SCRIPT_TIMEOUT.as_secs()
+                ));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(100)),
+            Err(err) => return Err(format!("Failed to wait for automation: {err}")),
+        }
+    };
+    Ok(AutomationOutput {
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+        exit_code: status.code(),
+    })
+}
+
+fn apple_script_command() -> Result<Command, AppError> {
+    if !cfg!(target_os = "macos") {
+        return Err(AppError::unavailable(
+            "AppleScript automation is only available on macOS.",
+        ));
+    }
+    let mut command = Command::new("osascript");
+    command.arg("-");
+    Ok(command)
+}
+
+fn com_script_command(prog_id: &str, script: &str) -> Result<Command, AppError> {
+    if !cfg!(target_os = "windows") {
+        return Err(AppError::unavailable(
+            "COM automation is only available on Windows.",
+        ));
+    }
+    let lowered = script.to_ascii_lowercase();
+    if let Some(blocked) = BLOCKED_POWERSHELL
+        .iter()
+        .find(|pattern| lowered.contains(*pattern))
+    {
+        return Err(AppError::invalid_input(format!(
+            "'{}' is not allowed in automation scripts.",
+            blocked.trim()
+        )));
+    }
+    let wrapped = format!(
+        "$ErrorActionPreference = 'Stop'\n$app = New-Object -ComObject '{prog_id}'\n{script}"
+    );
+    // -EncodedCommand takes the script as base64 UTF-16LE, which sidesteps quoting entirely.
+    let utf16: Vec<u8> = wrapped.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let mut command = Command::new("powershell.exe");
+    command.args([
+        "-NoProfile",
+        "-NonInteractive",
+        "-EncodedCommand",
+        &STANDARD.encode(utf16),
+    ]);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    Ok(command)
+}
+
+/// What `run_approved` runs, and what the approval covers.
+struct Automation {
+    command: Command,
+    stdin: String,
+    /// The name "always allow" grants are kept under.
+    tool: String,
+    workspace: String,
+    summary: String,
+    script: String,
+}
+
+/// Runs `automation` once the workspace's tool policy allows it, asking the user
+/// unless an earlier "always allow" covers it.
+async fn run_approved(
+    app: AppHandle,
+    automation: Automation,
+) -> Result<AutomationOutput, AppError> {
+    let Automation {
+        command,
+        stdin,
+        tool,
+        workspace,
+        summary,
+        script,
+    } = automation;
+    if script.len() > MAX_SCRIPT_LEN {
+        return Err(AppError::invalid_input(format!(
+            "Script is longer than {MAX_SCRIPT_LEN} characters."
+        )));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let request = PermissionRequest::new(&tool, &workspace, &format!("{summary}\n\n{script}"));
+        if let Verdict::Denied = tool_policy::decide(&app, request)? {
+            return Err(AppError::new(
+                ErrorCode::Permission,
+                locale::t(&app, "automation.denied"),
+            ));
+        }
+        let output =
+            run_with_timeout(command, &stdin).map_err(|err| AppError::new(ErrorCode::Tool, err))?;
+        tracing::info!("[Automation] {tool} exited with {:?}", output.exit_code);
+        Ok(output)
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Automation task failed: {err}")))?
+}
+
+/// Runs an AppleScript once the tool policy for `workspace` allows it (macOS only).
+#[tauri::command]
+pub async fn run_apple_script(
+    app: AppHandle,
+    script: String,
+    workspace: String,
+) -> Result<AutomationOutput, AppError> {
+    let command = apple_script_command()?;
+    let summary = locale::t(&app, "automation.apple_script");
+    run_approved(
+        app,
+        Automation {
+            command,
+            stdin: script.clone(),
+            tool: "run_apple_script".to_string(),
+            workspace,
+            summary,
+            script,
+        },
+    )
+    .await
+}
+
+/// Runs a PowerShell snippet against one allow-listed COM server, exposed to the
+/// script as `$app`, once the tool policy for `workspace` allows it (Windows only).
+/// Grants are kept per server.
+#[tauri::command]
+pub async fn run_com_automation(
+    app: AppHandle,
+    prog_id: String,
+    script: String,
+    workspace: String,
+) -> Result<AutomationOutput, AppError> {
+    let Some(prog_id) = ALLOWED_COM_SERVERS
+        .iter()
+        .find(|allowed| allowed.eq_ignore_ascii_case(prog_id.trim()))
+    else {
+        return Err(AppError::invalid_input(format!(
+            "COM server '{prog_id}' is not allowed; expected one of {}.",
+            ALLOWED_COM_SERVERS.join(", ")
+        )));
+    };
+    let command = com_script_command(prog_id, &script)?;
+    let summary = locale::t_with(&app, "automation.com", &[("server", prog_id)]);
+    run_approved(
+        app,
+        Automation {
+            command,
+            stdin: String::new(),
+            tool: format!("run_com_automation:{prog_id}"),
+            workspace,
+            summary,
+            script,
+        },
+    )
+    .await
+}
@@ -0,0 +1,274 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{error::AppError, settings::SettingsStore};
+
+/// Added to the command line of the login entry, so a launch at login can tell
+/// itself apart from one the user started.
+pub const LAUNCH_ARG: &str = "--autostart";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutostartSettings {
+    /// A launch at login keeps the window hidden behind the tray icon while the
+    /// backend and the webview load, so opening it later is instant.
+    pub background: bool,
+}
+
+impl Default for AutostartSettings {
+    fn default() -> Self {
+        Self { background: true }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutostartStatus {
+    /// Whether the login entry exists; the OS settings can remove it behind our back.
+    enabled: bool,
+    background: bool,
+}
+
+fn settings<R: Runtime>(app: &AppHandle<R>) -> AutostartSettings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().autostart)
+        .unwrap_or_default()
+}
+
+/// True when this process was started by the login entry in background mode; the
+/// startup gate and the splash screen leave the window hidden then.
+pub fn starts_hidden<R: Runtime>(app: &AppHandle<R>) -> bool {
+    std::env::args().any(|arg| arg == LAUNCH_ARG) && settings(app).background
+}
+
+/// The binary the login entry should start; an AppImage is started through the
+/// image rather than its temporary mount.
+fn executable() -> Result<PathBuf, String> {
+    if let Some(image) = std::env::var_os("APPIMAGE").filter(|_| cfg!(target_os = "linux")) {
+        return Ok(PathBuf::from(image));
+    }
+    std::env::current_exe().map_err(|err| format!("Failed to locate the app: {err}"))
+}
+
+fn status<R: Runtime>(app: &AppHandle<R>) -> Result<AutostartStatus, AppError> {
+    Ok(AutostartStatus {
+        enabled: platform::is_enabled(app).map_err(AppError::unavailable)?,
+        background: settings(app).background,
+    })
+}
+
+#[tauri::command]
+pub fn get_autostart(app: AppHandle) -> Result<AutostartStatus, AppError> {
+    status(&app)
+}
+
+/// Adds or removes the login entry: a launch agent on macOS, a `Run` registry
+/// value on Windows, an XDG autostart `.desktop` file on Linux. `background`
+/// is kept as it was when omitted.
+#[tauri::command]
+pub fn set_autostart(
+    app: AppHandle,
+    store: tauri::State<SettingsStore>,
+    enabled: bool,
+    background: Option<bool>,
+) -> Result<AutostartStatus, AppError> {
+    if let Some(background) = background {
+        store.update(|settings| settings.autostart.background = background)?;
+    }
+    if enabled {
+        let exe = executable().map_err(AppError::unavailable)?;
+        platform::enable(&app, &exe).map_err(AppError::from)?;
+        tracing::info!("[Autostart] Starting at login from {}.", exe.display());
+    } else {
+        platform::disable(&app).map_err(AppError::from)?;
+        tracing::info!("[Autostart] No longer starting at login.");
+    }
+    status(&app)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use tauri::{AppHandle, Manager, Runtime};
+
+    fn plist_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+        let home = app
+            .path()
+            .home_dir()
+            .map_err(|_| "Failed to resolve the home folder.".to_string())?;
+        Ok(home
+            .join("Library/LaunchAgents")
+            .join(format!("{}.plist", app.config().identifier)))
+    }
+
+    fn escape(value: &str) -> String {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+
+    pub fn is_enabled<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
+        Ok(plist_path(app)?.exists())
+    }
+
+    /// Loaded by launchd at the next login; nothing is started now.
+    pub fn enable<R: Runtime>(app: &AppHandle<R>, exe: &Path) -> Result<(), String> {
+        let path = plist_path(app)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("Failed to create {}: {err}", dir.display()))?;
+        }
+        let plist = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \
+             \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+             <plist version=\"1.0\">\n<dict>\n\
+             \t<key>Label</key>\n\t<string>{}</string>\n\
+             \t<key>ProgramArguments</key>\n\t<array>\n\
+             \t\t<string>{}</string>\n\t\t<string>{}</string>\n\t</array>\n\
+             \t<key>RunAtLoad</key>\n\t<true/>\n\
+             </dict>\n</plist>\n",
+            escape(&app.config().identifier),
+            escape(&exe.to_string_lossy()),
+            super::LAUNCH_ARG,
+        );
+        fs::write(&path, plist).map_err(|err| format!("Failed to write {}: {err}", path.display()))
+    }
+
+    pub fn disable<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+        let path = plist_path(app)?;
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to remove {}: {err}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::{os::windows::process::CommandExt, path::Path, process::Command};
+
+    use tauri::{AppHandle, Runtime};
+
+    const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    fn value_name<R: Runtime>(app: &AppHandle<R>) -> String {
+        app.config()
+            .product_name
+            .clone()
+            .unwrap_or_else(|| app.config().identifier.clone())
+    }
+
+    fn reg(args: &[&str]) -> Result<bool, String> {
+        Command::new("reg")
+            .args(args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map(|output| output.status.success())
+            .map_err(|err| format!("Failed to run reg: {err}"))
+    }
+
+    pub fn is_enabled<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
+        reg(&["query", RUN_KEY, "/v", &value_name(app)])
+    }
+
+    pub fn enable<R: Runtime>(app: &AppHandle<R>, exe: &Path) -> Result<(), String> {
+        let command = format!("\"{}\" {}", exe.display(), super::LAUNCH_ARG);
+        let name = value_name(app);
+        if reg(&[
+            "add", RUN_KEY, "/v", &name, "/t", "REG_SZ", "/d", &command, "/f",
+        ])? {
+            Ok(())
+        } else {
+            Err("Failed to add the login entry to the registry.".to_string())
+        }
+    }
+
+    pub fn disable<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+        if !is_enabled(app)? {
+            return Ok(());
+        }
+        if reg(&["delete", RUN_KEY, "/v", &value_name(app), "/f"])? {
+            Ok(())
+        } else {
+            Err("Failed to remove the login entry from the registry.".to_string())
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use tauri::{AppHandle, Manager, Runtime};
+
+    /// `$XDG_CONFIG_HOME/autostart`, read by GNOME, KDE and most other desktops.
+    fn entry_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+        let config = app
+            .path()
+            .config_dir()
+            .map_err(|_| "Failed to resolve the config folder.".to_string())?;
+        Ok(config
+            .join("autostart")
+            .join(format!("{}.desktop", app.config().identifier)))
+    }
+
+    /// Quoting rules of the desktop entry spec for an `Exec` argument.
+    fn quote(value: &str) -> String {
+        let mut quoted = String::from("\"");
+        for char in value.chars() {
+            if matches!(char, '"' | '`' | '$' | '\\') {
+                quoted.push('\\');
+            }
+            quoted.push(char);
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    pub fn is_enabled<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
+        Ok(entry_path(app)?.exists())
+    }
+
+    pub fn enable<R: Runtime>(app: &AppHandle<R>, exe: &Path) -> Result<(), String> {
+        let path = entry_path(app)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("Failed to create {}: {err}", dir.display()))?;
+        }
+        let name = app
+            .config()
+            .product_name
+            .clone()
+            .unwrap_or_else(|| app.config().identifier.clone());
+        let entry = format!(
+            "[Desktop Entry]\nType=Application\nName={name}\nExec={} {}\n\
+             Terminal=false\nX-GNOME-Autostart-enabled=true\n",
+            quote(&exe.to_string_lossy()),
+            super::LAUNCH_ARG,
+        );
+        fs::write(&path, entry).map_err(|err| format!("Failed to write {}: {err}", path.display()))
+    }
+
+    pub fn disable<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+        let path = entry_path(app)?;
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to remove {}: {err}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,507 @@
+use std::{
+    collections::BTreeMap,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Listener, Manager, Runtime};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+use crate::{
+    compat,
+    error::AppError,
+    events, health, locale, proxy, replay, rpc,
+    settings::SettingsStore,
+    sidecar::{self, EnvVar, SidecarCrash, SidecarEvent, SidecarInfo, SidecarRestart},
+    stale_backend::{self, Takeover},
+    startup::{self, Stage},
+    BackendState, BACKEND_SIDECAR,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const MAX_TIMEOUT_SECS: u64 = 600;
+pub const SHUTDOWN_PATH: &str = "/shutdown";
+const SHUTDOWN_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_SHUTDOWN_SECS: u64 = 120;
+
+/// How long startup waits for the backend's first `/health` answer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReadinessSettings {
+    pub timeout_secs: u64,
+    /// Show a splash window with the startup stages from launch until the main
+    /// window is ready, and a retry button if the backend fails.
+    pub splash: bool,
+}
+
+impl Default for ReadinessSettings {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 45,
+            splash: true,
+        }
+    }
+}
+
+/// How long the backend gets to exit after `/shutdown` before it is killed, so
+/// a write to `chat_app.db` is not cut off halfway.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShutdownSettings {
+    pub timeout_secs: u64,
+}
+
+impl Default for ShutdownSettings {
+    fn default() -> Self {
+        Self { timeout_secs: 5 }
+    }
+}
+
+/// Additions to the sidecar's environment, which otherwise holds only the
+/// allowlisted parent variables and what the shell sets itself.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendEnvSettings {
+    /// More parent variables to pass through; an entry ending in `*` is a prefix.
+    pub pass: Vec<String>,
+    /// Fixed values. Names the shell sets itself, like `TAURI_AGENT_DATA_DIR`, keep
+    /// the shell's value.
+    pub set: BTreeMap<String, String>,
+}
+
+events::payload_enum! {
+    #[derive(Default)]
+    pub enum BackendPhase {
+        #[default]
+        Starting,
+        Ready,
+        Failed,
+    }
+}
+
+events::payload! {
+    #[derive(Default)]
+    pub struct BackendStatus {
+        phase: BackendPhase,
+        /// When the phase last changed.
+        since: Option<String>,
+        /// Time from launch to the first healthy answer.
+        ready_after_ms: Option<u64>,
+        base_url: Option<String>,
+        error: Option<String>,
+    }
+}
+
+#[derive(Default)]
+struct Readiness {
+    status: BackendStatus,
+    /// Bumped by `retry`, so a poll from an earlier attempt stops.
+    attempt: u32,
+}
+
+#[derive(Default)]
+pub struct BackendReadiness(Mutex<Readiness>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendInfo {
+    /// False for remote and external backends, which this app does not launch.
+    managed: bool,
+    #[serde(flatten)]
+    process: Option<SidecarInfo>,
+    url: Option<String>,
+    port: Option<u16>,
+    /// What startup did about a backend left running by an earlier launch.
+    takeover: Option<Takeover>,
+}
+
+pub fn readiness_settings<R: Runtime>(app: &AppHandle<R>) -> ReadinessSettings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().backend.readiness)
+        .unwrap_or_default()
+}
+
+fn current<R: Runtime>(app: &AppHandle<R>) -> (BackendStatus, u32) {
+    app.try_state::<BackendReadiness>()
+        .and_then(|readiness| {
+            readiness
+                .0
+                .lock()
+                .ok()
+                .map(|current| (current.status.clone(), current.attempt))
+        })
+        .unwrap_or_default()
+}
+
+fn status<R: Runtime>(app: &AppHandle<R>) -> BackendStatus {
+    current(app).0
+}
+
+/// Moves out of `Starting` once; later calls are ignored.
+fn settle<R: Runtime>(
+    app: &AppHandle<R>,
+    phase: BackendPhase,
+    started: Instant,
+    error: Option<String>,
+) {
+    let Some(readiness) = app.try_state::<BackendReadiness>() else {
+        return;
+    };
+    let Ok(mut current) = readiness.0.lock() else {
+        return;
+    };
+    if current.status.phase != BackendPhase::Starting {
+        return;
+    }
+    current.status = BackendStatus {
+        phase,
+        since: Some(Local::now().to_rfc3339()),
+        ready_after_ms: (phase == BackendPhase::Ready)
+            .then(|| started.elapsed().as_millis() as u64),
+        base_url: app
+            .try_state::<BackendState>()
+            .map(|state| state.base_url()),
+        error,
+    };
+    let payload = current.status.clone();
+    drop(current);
+    match phase {
+        BackendPhase::Ready => tracing::info!(
+            "[Backend] Healthy after {}ms.",
+            payload.ready_after_ms.unwrap_or(0)
+        ),
+        _ => tracing::warn!(
+            "[Backend] Not ready: {}",
+            payload.error.as_deref().unwrap_or("unknown error")
+        ),
+    }
+    let ready = phase == BackendPhase::Ready;
+    let (stage, detail) = if ready {
+        (Stage::Ready, None)
+    } else {
+        (Stage::Failed, payload.error.clone())
+    };
+    if ready {
+        events::backend_ready(app, &payload);
+    } else {
+        events::backend_failed(app, &payload);
+    }
+    startup::stage(app, stage, detail);
+    startup::backend_settled(app, ready);
+}
+
+/// Polls `/health` until the backend answers or the readiness timeout passes, then
+/// emits `backend://ready` or `backend://failed` and releases the startup gate.
+/// Call right after spawning (or deciding not to spawn) the backend.
+pub fn start<R: Runtime>(app: &AppHandle<R>, sidecar: &'static str) {
+    let started = Instant::now();
+    let handle = app.clone();
+    app.listen_any("sidecar://failed", move |event| {
+        if let Ok(failed) = serde_json::from_str::<SidecarEvent>(event.payload()) {
+            if failed.name == sidecar {
+                let error = failed
+                    .error
+                    .unwrap_or_else(|| "The backend failed to start.".to_string());
+                settle(&handle, BackendPhase::Failed, started, Some(error));
+            }
+        }
+    });
+    // The frontend only cares about the backend, under its own event names.
+    let handle = app.clone();
+    app.listen_any("sidecar://crashed", move |event| {
+        if let Ok(crash) = serde_json::from_str::<SidecarCrash>(event.payload()) {
+            if crash.name == sidecar {
+                events::backend_crashed(&handle, &crash);
+            }
+        }
+    });
+    let handle = app.clone();
+    app.listen_any("sidecar://restarted", move |event| {
+        if let Ok(restart) = serde_json::from_str::<SidecarRestart>(event.payload()) {
+            if restart.name == sidecar {
+                events::backend_restarted(&handle, &restart);
+            }
+        }
+    });
+    poll(app, started);
+}
+
+fn poll<R: Runtime>(app: &AppHandle<R>, started: Instant) {
+    // Nothing is running to ask; the tape answers whatever the webview sends.
+    if replay::is_replaying(app) {
+        settle(app, BackendPhase::Ready, started, None);
+        return;
+    }
+    let attempt = current(app).1;
+    let app = app.clone();
+    let timeout = Duration::from_secs(readiness_settings(&app).timeout_secs.max(1));
+    tauri::async_runtime::spawn(async move {
+        while started.elapsed() < timeout {
+            let (status, latest) = current(&app);
+            if status.phase != BackendPhase::Starting || latest != attempt {
+                return;
+            }
+            if health::probe(&app).await {
+                match compat::check(&app).await {
+                    Ok(report) => match report.refusal() {
+                        Some(refusal) => refuse(&app, started, refusal),
+                        None => settle(&app, BackendPhase::Ready, started, None),
+                    },
+                    // Up but not answering yet; the next round asks again.
+                    Err(err) => {
+                        tracing::warn!("[Backend] {err}");
+                        tokio::time::sleep(POLL_INTERVAL).await;
+                        continue;
+                    }
+                }
+                return;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        settle(
+            &app,
+            BackendPhase::Failed,
+            started,
+            Some(format!(
+                "The backend did not answer /health within {}s.",
+                timeout.as_secs()
+            )),
+        );
+    });
+}
+
+/// Fails startup against a backend the shell cannot talk to, with a dialog unless
+/// the splash is up to show the error itself.
+fn refuse<R: Runtime>(app: &AppHandle<R>, started: Instant, refusal: String) {
+    if !startup::splash_open(app) {
+        app.dialog()
+            .message(refusal.clone())
+            .title(locale::t(app, "dialog.incompatible_backend"))
+            .kind(MessageDialogKind::Error)
+            .show(|_| {});
+    }
+    settle(app, BackendPhase::Failed, started, Some(refusal));
+}
+
+/// Waits for the backend once more after startup failed, relaunching the sidecar
+/// first when it is the selected backend.
+pub fn retry<R: Runtime>(app: &AppHandle<R>) -> Result<(), AppError> {
+    let readiness = app
+        .try_state::<BackendReadiness>()
+        .ok_or_else(|| AppError::unavailable("Backend readiness is not tracked."))?;
+    {
+        let mut current = readiness
+            .0
+            .lock()
+            .map_err(|_| AppError::unavailable("Backend readiness is unavailable."))?;
+        if current.status.phase != BackendPhase::Failed {
+            return Ok(());
+        }
+        *current = Readiness {
+            status: BackendStatus::default(),
+            attempt: current.attempt + 1,
+        };
+    }
+    tracing::info!("[Backend] Retrying startup.");
+    let started = Instant::now();
+    let remote = app
+        .try_state::<BackendState>()
+        .is_some_and(|state| state.remote_url().is_some());
+    let launched = if remote {
+        Ok(())
+    } else if sidecar::is_supervised(app, BACKEND_SIDECAR) {
+        startup::stage(app, Stage::Spawning, None);
+        crate::restart_backend(app)
+    } else {
+        crate::start_backend(app)
+    };
+    if let Err(err) = launched {
+        settle(app, BackendPhase::Failed, started, Some(err.clone()));
+        return Err(AppError::from(err));
+    }
+    startup::stage(app, Stage::Waiting, None);
+    poll(app, started);
+    Ok(())
+}
+
+pub fn shutdown_settings<R: Runtime>(app: &AppHandle<R>) -> ShutdownSettings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().backend.shutdown)
+        .unwrap_or_default()
+}
+
+/// Asks the backend to exit on its own; the sidecar's stop hook.
+pub fn request_shutdown<R: Runtime>(app: &AppHandle<R>) -> bool {
+    if rpc::is_attached(app) {
+        return rpc::post(app, SHUTDOWN_PATH, &json!({}), SHUTDOWN_REQUEST_TIMEOUT)
+            .is_ok_and(|(status, _)| status < 300);
+    }
+    let Some(base_url) = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+    else {
+        return false;
+    };
+    let client = proxy::client(app);
+    // A thread of its own, since callers may already be on the async runtime.
+    thread::spawn(move || {
+        tauri::async_runtime::block_on(async move {
+            client
+                .post(format!("{base_url}{SHUTDOWN_PATH}"))
+                .timeout(SHUTDOWN_REQUEST_TIMEOUT)
+                .send()
+                .await
+                .is_ok_and(|response| response.status().is_success())
+        })
+    })
+    .join()
+    .unwrap_or(false)
+}
+
+/// For a spawn that failed outright, so startup does not wait out the timeout.
+pub fn spawn_failed<R: Runtime>(app: &AppHandle<R>, error: String) {
+    settle(app, BackendPhase::Failed, Instant::now(), Some(error));
+}
+
+/// Where startup stands; the webview checks this before its first request.
+#[tauri::command]
+pub fn backend_status(app: AppHandle) -> BackendStatus {
+    status(&app)
+}
+
+/// Launches the bundled backend; a no-op while it is running.
+#[tauri::command]
+pub async fn start_backend(app: AppHandle) -> Result<(), AppError> {
+    if sidecar::is_running(&app, BACKEND_SIDECAR) {
+        return Ok(());
+    }
+    tauri::async_runtime::spawn_blocking(move || crate::start_backend(&app))
+        .await
+        .map_err(|err| AppError::from(format!("Start task failed: {err}")))?
+        .map_err(AppError::from)
+}
+
+/// Shuts the bundled backend down until `start_backend` or `restart_backend`.
+#[tauri::command]
+pub async fn stop_backend(app: AppHandle) -> Result<(), AppError> {
+    let handle = app.clone();
+    let was_running =
+        tauri::async_runtime::spawn_blocking(move || sidecar::stop(&handle, BACKEND_SIDECAR))
+            .await
+            .map_err(|err| AppError::from(format!("Stop task failed: {err}")))??;
+    if was_running {
+        events::backend_stopped(&app, &events::Empty {});
+    }
+    Ok(())
+}
+
+/// Manual recovery, e.g. after automatic restarts gave up.
+#[tauri::command]
+pub async fn restart_backend(app: AppHandle) -> Result<(), AppError> {
+    tauri::async_runtime::spawn_blocking(move || crate::restart_backend(&app))
+        .await
+        .map_err(|err| AppError::from(format!("Restart task failed: {err}")))?
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn backend_info(app: AppHandle) -> BackendInfo {
+    let process = sidecar::info(&app, BACKEND_SIDECAR);
+    let endpoint = app
+        .try_state::<BackendState>()
+        .map(|state| state.endpoint(&app));
+    BackendInfo {
+        managed: crate::runs_sidecar(&app),
+        process,
+        port: endpoint.as_ref().and_then(|endpoint| endpoint.port),
+        url: endpoint.map(|endpoint| endpoint.url),
+        takeover: stale_backend::last(&app),
+    }
+}
+
+/// Persists the startup wait; it takes effect on the next launch.
+#[tauri::command]
+pub fn set_backend_readiness(
+    store: tauri::State<SettingsStore>,
+    readiness: ReadinessSettings,
+) -> Result<ReadinessSettings, AppError> {
+    if !(1..=MAX_TIMEOUT_SECS).contains(&readiness.timeout_secs) {
+        return Err(AppError::invalid_input(format!(
+            "The startup timeout must be between 1 and {MAX_TIMEOUT_SECS} seconds."
+        )));
+    }
+    let saved = readiness.clone();
+    store.update(|settings| settings.backend.readiness = readiness)?;
+    Ok(saved)
+}
+
+/// Persists the shutdown grace period; it applies from the next backend start.
+#[tauri::command]
+pub fn set_backend_shutdown(
+    store: tauri::State<SettingsStore>,
+    shutdown: ShutdownSettings,
+) -> Result<ShutdownSettings, AppError> {
+    if shutdown.timeout_secs > MAX_SHUTDOWN_SECS {
+        return Err(AppError::invalid_input(format!(
+            "The shutdown timeout must be at most {MAX_SHUTDOWN_SECS} seconds."
+        )));
+    }
+    let saved = shutdown.clone();
+    store.update(|settings| settings.backend.shutdown = shutdown)?;
+    Ok(saved)
+}
+
+fn check_env_name(name: &str, prefix_allowed: bool) -> Result<(), AppError> {
+    let bare = if prefix_allowed {
+        name.strip_suffix('*').unwrap_or(name)
+    } else {
+        name
+    };
+    if bare.is_empty() || bare.contains(['=', '*', '\0']) || bare.chars().any(char::is_whitespace) {
+        return Err(AppError::invalid_input(format!(
+            "'{name}' is not a valid environment variable name."
+        )));
+    }
+    Ok(())
+}
+
+/// Persists the sidecar's extra environment; it applies from the next backend start.
+#[tauri::command]
+pub fn set_backend_env(
+    store: tauri::State<SettingsStore>,
+    env: BackendEnvSettings,
+) -> Result<BackendEnvSettings, AppError> {
+    let env = BackendEnvSettings {
+        pass: env
+            .pass
+            .iter()
+            .map(|name| name.trim().to_string())
+            .collect(),
+        set: env
+            .set
+            .into_iter()
+            .map(|(name, value)| (name.trim().to_string(), value))
+            .collect(),
+    };
+    for name in &env.pass {
+        check_env_name(name, true)?;
+    }
+    for name in env.set.keys() {
+        check_env_name(name, false)?;
+    }
+    let saved = env.clone();
+    store.update(|settings| settings.backend.env = env)?;
+    Ok(saved)
+}
+
+/// The bundled backend's environment with credentials redacted, for debugging
+/// what reached the Python process.
+#[tauri::command]
+pub fn get_effective_backend_env(app: AppHandle) -> Result<Vec<EnvVar>, AppError> {
+    sidecar::environment(&app, BACKEND_SIDECAR).ok_or_else(|| {
+        AppError::unavailable("The bundled backend has not been started by this app.")
+    })
+}
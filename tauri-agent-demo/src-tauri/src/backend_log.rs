@@ -0,0 +1,236 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tauri::{AppHandle, Runtime};
+
+use crate::{events, log_files, sidecar::OutputStream};
+
+const TRACEBACK_START: &str = "Traceback (most recent call last):";
+const MAX_TRACEBACK_LINES: usize = 200;
+/// The same error repeated within this window is reported once.
+const REPEAT_WINDOW: Duration = Duration::from_secs(10);
+
+events::payload_enum! {
+    pub enum BackendErrorKind {
+        MissingApiKey,
+        RateLimited,
+        DatabaseLocked,
+        PortInUse,
+        MissingDependency,
+        Traceback,
+        ErrorRecord,
+    }
+}
+
+events::payload! {
+    pub struct BackendError {
+        kind: BackendErrorKind,
+        /// The final exception line, or the logged error itself.
+        message: String,
+        /// The full traceback, when there was one.
+        details: Option<String>,
+        hint: Option<&'static str>,
+        stream: OutputStream,
+    }
+}
+
+/// Known failures, matched case-insensitively anywhere in a record.
+const SIGNATURES: &[(BackendErrorKind, &[&str])] = &[
+    (
+        BackendErrorKind::MissingApiKey,
+        &[
+            "api key not configured",
+            "api key is missing",
+            "missing api key",
+            "invalid api key",
+            "incorrect api key",
+            "invalid x-api-key",
+            "authenticationerror",
+            "status=401",
+        ],
+    ),
+    (
+        BackendErrorKind::RateLimited,
+        &["ratelimiterror", "rate limit", "status=429"],
+    ),
+    (BackendErrorKind::DatabaseLocked, &["database is locked"]),
+    (
+        BackendErrorKind::PortInUse,
+        &[
+            "address already in use",
+            "only one usage of each socket address",
+            "errno 10048",
+        ],
+    ),
+    (
+        BackendErrorKind::MissingDependency,
+        &["modulenotfounderror", "no module named"],
+    ),
+];
+
+fn hint(kind: BackendErrorKind) -> Option<&'static str> {
+    match kind {
+        BackendErrorKind::MissingApiKey => {
+            Some("Check the API key of the model configuration in use, or add one in the model settings.")
+        }
+        BackendErrorKind::RateLimited => {
+            Some("The provider is rate limiting requests. Wait a moment, or switch to another model.")
+        }
+        BackendErrorKind::DatabaseLocked => Some(
+            "Another process is holding chat_app.db. Close other copies of the app, then restart the backend.",
+        ),
+        BackendErrorKind::PortInUse => {
+            Some("Another program is using the backend port. Restart the app to pick a free one.")
+        }
+        BackendErrorKind::MissingDependency => {
+            Some("The backend is missing a Python package. Reinstall the app, or run SetupEnv in development.")
+        }
+        BackendErrorKind::Traceback | BackendErrorKind::ErrorRecord => None,
+    }
+}
+
+fn signature(text: &str) -> Option<BackendErrorKind> {
+    let lower = text.to_lowercase();
+    SIGNATURES
+        .iter()
+        .find(|(_, needles)| needles.iter().any(|needle| lower.contains(needle)))
+        .map(|(kind, _)| *kind)
+}
+
+/// `ERROR:` from uvicorn, `[ERROR]`/` ERROR ` from logging, and the backend's own
+/// `[... Error]` prints.
+fn is_error_record(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("ERROR:")
+        || trimmed.contains("[ERROR]")
+        || trimmed.contains(" ERROR ")
+        || trimmed.contains(" - ERROR - ")
+        || (trimmed.starts_with('[')
+            && trimmed
+                .split(']')
+                .next()
+                .is_some_and(|tag| tag.ends_with("Error")))
+}
+
+#[derive(Default)]
+struct StreamState {
+    /// An error record that may introduce the traceback on the next line.
+    header: Option<String>,
+    traceback: Vec<String>,
+}
+
+/// Turns raw sidecar output into `BackendError`s, one state machine per stream.
+#[derive(Default)]
+struct Classifier {
+    stdout: StreamState,
+    stderr: StreamState,
+    last: Option<(BackendErrorKind, String, Instant)>,
+}
+
+impl Classifier {
+    fn record(
+        &mut self,
+        stream: OutputStream,
+        message: String,
+        details: Option<String>,
+    ) -> Option<BackendError> {
+        let text = details.as_deref().unwrap_or(&message);
+        let kind = signature(text).unwrap_or(if details.is_some() {
+            BackendErrorKind::Traceback
+        } else {
+            BackendErrorKind::ErrorRecord
+        });
+        let now = Instant::now();
+        let repeated = self
+            .last
+            .as_ref()
+            .is_some_and(|(last_kind, last_message, at)| {
+                *last_kind == kind
+                    && *last_message == message
+                    && now.duration_since(*at) < REPEAT_WINDOW
+            });
+        if repeated {
+            return None;
+        }
+        self.last = Some((kind, message.clone(), now));
+        Some(BackendError {
+            kind,
+            message,
+            details,
+            hint: hint(kind),
+            stream,
+        })
+    }
+
+    fn feed(&mut self, stream: OutputStream, line: &str) -> Vec<BackendError> {
+        let state = match stream {
+            OutputStream::Stdout => &mut self.stdout,
+            OutputStream::Stderr => &mut self.stderr,
+        };
+        let mut found = Vec::new();
+        let mut events: Vec<(String, Option<String>)> = Vec::new();
+
+        if !state.traceback.is_empty() {
+            let continues = line.starts_with(' ')
+                || line.starts_with('\t')
+                || line.is_empty()
+                || line.starts_with(TRACEBACK_START)
+                // Chained exceptions keep going with another traceback.
+                || line.starts_with("During handling of the above exception")
+                || line.starts_with("The above exception was the direct cause");
+            if continues && state.traceback.len() < MAX_TRACEBACK_LINES {
+                state.traceback.push(line.to_string());
+                return found;
+            }
+            // The first unindented line is the exception itself.
+            state.traceback.push(line.to_string());
+            let mut details = state
+                .header
+                .take()
+                .map(|header| vec![header])
+                .unwrap_or_default();
+            details.append(&mut state.traceback);
+            events.push((line.trim().to_string(), Some(details.join("\n"))));
+        } else if line.trim_start().starts_with(TRACEBACK_START) {
+            state.traceback.push(line.to_string());
+        } else {
+            if let Some(header) = state.header.take() {
+                events.push((header, None));
+            }
+            if is_error_record(line) || signature(line).is_some() {
+                let trimmed = line.trim().to_string();
+                // uvicorn logs "Exception in ASGI application" right before the traceback.
+                if trimmed.ends_with(':') || trimmed.contains("Exception in") {
+                    state.header = Some(trimmed);
+                } else {
+                    events.push((trimmed, None));
+                }
+            }
+        }
+
+        for (message, details) in events {
+            found.extend(self.record(stream, message, details));
+        }
+        found
+    }
+}
+
+/// Output handler for the backend sidecar: writes every line to the log files and
+/// emits `backend-error` events for the ones that look like failures.
+pub fn observer<R: Runtime>(
+    app: &AppHandle<R>,
+) -> impl Fn(OutputStream, &str) + Send + Sync + 'static {
+    let app = app.clone();
+    let classifier = Mutex::new(Classifier::default());
+    move |stream, line| {
+        log_files::append(&app, stream, line);
+        let Ok(mut classifier) = classifier.lock() else {
+            return;
+        };
+        for error in classifier.feed(stream, line) {
+            events::backend_error(&app, &error);
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::{fs, path::PathBuf};
+
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    error::AppError, events, network, settings::SettingsStore, sidecar, tool_host, watchdog,
+    BackendState, BACKEND_SIDECAR, DEFAULT_BACKEND_PORT,
+};
+
+const FILE_NAME: &str = "backends.json";
+const BUNDLED: &str = "bundled";
+const EXTERNAL: &str = "external";
+/// Stands for `backend.remote_url` from before profiles existed.
+const REMOTE: &str = "remote";
+
+events::payload_enum! {
+    #[derive(Deserialize)]
+    pub enum BackendKind {
+        /// The backend shipped with the app, spawned and supervised by it.
+        Sidecar,
+        /// A backend started by hand on this machine, e.g. from a checkout.
+        External,
+        /// A backend on another machine.
+        Remote,
+    }
+}
+
+/// One entry of `backends.json`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackendProfile {
+    name: String,
+    kind: BackendKind,
+    /// Required for remote profiles; external ones default to port 8000 on loopback.
+    #[serde(default)]
+    url: Option<String>,
+    /// Sent as the backend token header, for a backend started with one.
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct BackendsFile {
+    profiles: Vec<BackendProfile>,
+}
+
+/// Where requests go while a profile is selected.
+#[derive(Debug, Clone)]
+pub struct BackendTarget {
+    pub profile: String,
+    /// `None` for the sidecar, which is reached at the address picked at startup.
+    pub url: Option<String>,
+    pub token: Option<String>,
+}
+
+events::payload! {
+    pub struct BackendProfileInfo {
+        name: String,
+        kind: BackendKind,
+        url: Option<String>,
+        /// The token itself never leaves the shell.
+        has_token: bool,
+        active: bool,
+    }
+}
+
+impl BackendProfile {
+    fn builtin(name: &str, kind: BackendKind, url: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            url,
+            token: None,
+        }
+    }
+
+    fn target(&self) -> Result<BackendTarget, String> {
+        let url = match self.kind {
+            BackendKind::Sidecar => None,
+            BackendKind::External => Some(
+                self.url
+                    .clone()
+                    .unwrap_or_else(|| format!("http://127.0.0.1:{DEFAULT_BACKEND_PORT}")),
+            ),
+            BackendKind::Remote => Some(
+                self.url
+                    .clone()
+                    .ok_or_else(|| format!("Backend profile '{}' has no url.", self.name))?,
+            ),
+        };
+        let url = url
+            .map(|url| normalize_url(&url))
+            .transpose()
+            .map_err(|err| format!("Backend profile '{}': {err}", self.name))?;
+        Ok(BackendTarget {
+            profile: self.name.clone(),
+            url,
+            token: self.token.clone().filter(|token| !token.is_empty()),
+        })
+    }
+
+    fn info(&self, active: &str) -> BackendProfileInfo {
+        BackendProfileInfo {
+            name: self.name.clone(),
+            kind: self.kind,
+            url: self.target().ok().and_then(|target| target.url),
+            has_token: self.token.as_deref().is_some_and(|token| !token.is_empty()),
+            active: self.name == active,
+        }
+    }
+}
+
+/// Trims a trailing slash and accepts only http and https.
+fn normalize_url(value: &str) -> Result<String, String> {
+    let url = value.trim().trim_end_matches('/').to_string();
+    let parsed = reqwest::Url::parse(&url).map_err(|_| format!("Invalid backend URL '{url}'."))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("The backend URL must start with http:// or https://.".to_string());
+    }
+    Ok(url)
+}
+
+fn file_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    Ok(crate::resolve_app_data_dir(app)?.join(FILE_NAME))
+}
+
+/// The built-in profiles followed by those in `backends.json`, which replace a
+/// built-in of the same name. Read on every call, so edits apply without a restart.
+fn load<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<BackendProfile>, String> {
+    let settings = app.try_state::<SettingsStore>();
+    let mut profiles = vec![
+        BackendProfile::builtin(BUNDLED, BackendKind::Sidecar, None),
+        BackendProfile::builtin(EXTERNAL, BackendKind::External, None),
+    ];
+    if let Some(url) = network::resolve_remote_url(settings.as_deref()) {
+        profiles.push(BackendProfile::builtin(
+            REMOTE,
+            BackendKind::Remote,
+            Some(url),
+        ));
+    }
+    let path = file_path(app)?;
+    let file: BackendsFile = match fs::read_to_string(&path) {
+        Ok(raw) => serde_json::from_str(&raw)
+            .map_err(|err| format!("Failed to parse {}: {err}", path.display()))?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => BackendsFile::default(),
+        Err(err) => return Err(format!("Failed to read {}: {err}", path.display())),
+    };
+    for profile in file.profiles {
+        let name = profile.name.trim();
+        if name.is_empty() {
+            return Err(format!("{FILE_NAME} has a profile without a name."));
+        }
+        let profile = BackendProfile {
+            name: name.to_string(),
+            ..profile
+        };
+        match profiles.iter_mut().find(|known| known.name == profile.name) {
+            Some(known) => *known = profile,
+            None => profiles.push(profile),
+        }
+    }
+    Ok(profiles)
+}
+
+/// The profile to start on: `TAURI_AGENT_EXTERNAL_BACKEND` forces the external one,
+/// then the saved selection, then a legacy remote URL, then the bundled sidecar.
+pub fn initial<R: Runtime>(app: &AppHandle<R>) -> BackendTarget {
+    let forced_external = std::env::var("TAURI_AGENT_EXTERNAL_BACKEND")
+        .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    let settings = app.try_state::<SettingsStore>();
+    let name = if forced_external {
+        EXTERNAL.to_string()
+    } else {
+        settings
+            .as_deref()
+            .and_then(|store| store.get().backend.profile)
+            .unwrap_or_else(|| {
+                if network::resolve_remote_url(settings.as_deref()).is_some() {
+                    REMOTE.to_string()
+                } else {
+                    BUNDLED.to_string()
+                }
+            })
+    };
+    let profile = load(app).and_then(|profiles| {
+        profiles
+            .into_iter()
+            .find(|profile| profile.name == name)
+            .ok_or_else(|| format!("No backend profile named '{name}'."))?
+            .target()
+    });
+    match profile {
+        Ok(target) => target,
+        Err(err) => {
+            tracing::warn!("[Backend] {err} Falling back to the bundled backend.");
+            BackendTarget {
+                profile: BUNDLED.to_string(),
+                url: None,
+                token: None,
+            }
+        }
+    }
+}
+
+fn active<R: Runtime>(app: &AppHandle<R>) -> String {
+    app.try_state::<BackendState>()
+        .map(|state| state.target().profile)
+        .unwrap_or_else(|| BUNDLED.to_string())
+}
+
+fn select<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<BackendProfileInfo, AppError> {
+    let profile = load(app)
+        .map_err(AppError::from)?
+        .into_iter()
+        .find(|profile| profile.name == name)
+        .ok_or_else(|| AppError::not_found(format!("No backend profile named '{name}'.")))?;
+    if active(app) == name {
+        return Ok(profile.info(name));
+    }
+    let target = profile.target().map_err(AppError::invalid_input)?;
+    if watchdog::has_active_runs(app) {
+        return Err(AppError::invalid_input(
+            "An agent run is still going; stop it before switching backends.",
+        ));
+    }
+    let state = app
+        .try_state::<BackendState>()
+        .ok_or_else(|| AppError::unavailable("Backend is not configured yet."))?;
+    // The sidecar is stopped first so nothing reaches it once the switch is announced.
+    if state.remote_url().is_none() && sidecar::is_supervised(app, BACKEND_SIDECAR) {
+        sidecar::stop(app, BACKEND_SIDECAR)?;
+    }
+    let to_sidecar = target.url.is_none();
+    state.set_target(target);
+    if let Some(store) = app.try_state::<SettingsStore>() {
+        store.update(|settings| settings.backend.profile = Some(name.to_string()))?;
+    }
+    if to_sidecar {
+        // Only started at launch when the sidecar was selected then.
+        if app.try_state::<tool_host::ToolHost>().is_none() {
+            match tool_host::start(app) {
+                Ok(host) => {
+                    app.manage(host);
+                }
+                Err(err) => tracing::warn!("[ToolHost] {err}"),
+            }
+        }
+        crate::start_backend(app).map_err(AppError::from)?;
+    }
+    tracing::info!("[Backend] Switched to the {name} backend profile.");
+    let info = profile.info(name);
+    crate::announce_backend_url(app);
+    events::backend_profile(app, &info);
+    Ok(info)
+}
+
+/// Built-in profiles and those from `backends.json` in the app data folder.
+#[tauri::command]
+pub fn list_backend_profiles(app: AppHandle) -> Result<Vec<BackendProfileInfo>, AppError> {
+    let active = active(&app);
+    Ok(load(&app)
+        .map_err(AppError::from)?
+        .iter()
+        .map(|profile| profile.info(&active))
+        .collect())
+}
+
+/// Points the shell and the webview at another backend, stopping or starting the
+/// sidecar as needed. Announced on `backend://url` and `backend://profile`.
+#[tauri::command]
+pub async fn select_backend_profile(
+    app: AppHandle,
+    name: String,
+) -> Result<BackendProfileInfo, AppError> {
+    tauri::async_runtime::spawn_blocking(move || select(&app, &name))
+        .await
+        .map_err(|err| AppError::from(format!("Switch task failed: {err}")))?
+}
@@ -0,0 +1,347 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    atomic_file,
+    error::AppError,
+    events, locale,
+    notifications::{self, NotificationKind},
+    settings::SettingsStore,
+    snapshots::{self, SnapshotInfo},
+};
+
+const STATUS_FILE: &str = "backup_status.json";
+/// Scheduled snapshots are named `auto-<timestamp>` so they can be pruned safely.
+const AUTO_PREFIX: &str = "auto-";
+/// Scheduled snapshots kept locally and in the mirror; named ones are never pruned.
+const KEEP_AUTO_SNAPSHOTS: usize = 7;
+const DEFAULT_INTERVAL_HOURS: u32 = 24;
+const MAX_INTERVAL_HOURS: u32 = 24 * 7;
+/// How often the schedule is checked; also how soon an unplugged drive is retried.
+const POLL_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// Extra folder snapshots are mirrored to; `None` keeps them local only.
+    pub destination: Option<String>,
+    pub interval_hours: u32,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            destination: None,
+            interval_hours: DEFAULT_INTERVAL_HOURS,
+        }
+    }
+}
+
+/// Outcome of the last runs, kept next to the settings so it survives restarts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct BackupRecord {
+    last_success: Option<String>,
+    last_attempt: Option<String>,
+    last_error: Option<String>,
+    last_snapshot: Option<String>,
+}
+
+/// Held for the length of a run so the schedule and a manual run never overlap.
+#[derive(Default)]
+pub struct BackupState {
+    running: Mutex<()>,
+}
+
+events::payload! {
+    pub struct BackupStatus {
+        destination: Option<String>,
+        interval_hours: u32,
+        last_success: Option<String>,
+        last_attempt: Option<String>,
+        last_error: Option<String>,
+        last_snapshot: Option<String>,
+        /// Snapshots currently verified in the destination.
+        mirrored: usize,
+    }
+}
+
+fn settings<R: Runtime>(app: &AppHandle<R>) -> BackupSettings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().backup)
+        .unwrap_or_default()
+}
+
+fn record_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    Ok(crate::resolve_app_data_dir(app)?.join(STATUS_FILE))
+}
+
+fn load_record<R: Runtime>(app: &AppHandle<R>) -> BackupRecord {
+    record_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_record<R: Runtime>(app: &AppHandle<R>, record: &BackupRecord) -> Result<(), String> {
+    let raw = serde_json::to_string_pretty(record)
+        .map_err(|err| format!("Failed to serialize backup status: {err}"))?;
+    atomic_file::write(&record_path(app)?, raw)
+        .map_err(|err| format!("Failed to write backup status: {err}"))
+}
+
+/// Snapshots are kept in a named subfolder so a shared Dropbox or iCloud folder
+/// stays tidy.
+fn mirror_root<R: Runtime>(app: &AppHandle<R>, destination: &Path) -> PathBuf {
+    destination.join(format!("{} Backups", app.package_info().name))
+}
+
+fn status<R: Runtime>(app: &AppHandle<R>) -> BackupStatus {
+    let settings = settings(app);
+    let record = load_record(app);
+    let mirrored = settings
+        .destination
+        .as_deref()
+        .map(|destination| list(&mirror_root(app, Path::new(destination))).len())
+        .unwrap_or(0);
+    BackupStatus {
+        destination: settings.destination,
+        interval_hours: settings.interval_hours,
+        last_success: record.last_success,
+        last_attempt: record.last_attempt,
+        last_error: record.last_error,
+        last_snapshot: record.last_snapshot,
+        mirrored,
+    }
+}
+
+fn list(root: &Path) -> Vec<SnapshotInfo> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| snapshots::read_info(&entry.path()).ok())
+        .collect()
+}
+
+fn hash_file(path: &Path) -> Result<Vec<u8>, String> {
+    let mut file =
+        File::open(path).map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Copies one snapshot into the mirror and checks every file against the original
+/// before the copy is given its real name.
+fn mirror_snapshot(source: &Path, root: &Path, name: &str) -> Result<(), String> {
+    let staging = root.join(format!(".{name}.partial"));
+    let _ = fs::remove_dir_all(&staging);
+    fs::create_dir_all(&staging).map_err(|err| format!("Failed to create backup folder: {err}"))?;
+    let result = (|| {
+        let entries =
+            fs::read_dir(source).map_err(|err| format!("Failed to read snapshot: {err}"))?;
+        for entry in entries.flatten() {
+            let from = entry.path();
+            if !from.is_file() {
+                continue;
+            }
+            let to = staging.join(entry.file_name());
+            fs::copy(&from, &to).map_err(|err| format!("Failed to copy {name}: {err}"))?;
+            if hash_file(&from)? != hash_file(&to)? {
+                return Err(format!(
+                    "The copy of {} in '{name}' does not match the original.",
+                    entry.file_name().to_string_lossy()
+                ));
+            }
+        }
+        fs::rename(&staging, root.join(name))
+            .map_err(|err| format!("Failed to finish backup of '{name}': {err}"))
+    })();
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result
+}
+
+fn prune(root: &Path) {
+    let mut auto: Vec<SnapshotInfo> = list(root)
+        .into_iter()
+        .filter(|info| info.name.starts_with(AUTO_PREFIX))
+        .collect();
+    auto.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    for info in auto.iter().skip(KEEP_AUTO_SNAPSHOTS) {
+        if let Err(err) = fs::remove_dir_all(root.join(&info.name)) {
+            tracing::warn!("[Backup] Failed to prune '{}': {err}", info.name);
+        }
+    }
+}
+
+fn mirror<R: Runtime>(app: &AppHandle<R>, destination: &Path) -> Result<String, String> {
+    // An external drive that is not plugged in must not be recreated as an empty
+    // folder on the system disk.
+    if !destination.is_dir() {
+        return Err(format!(
+            "Backup destination {} is not reachable.",
+            destination.display()
+        ));
+    }
+    let name = format!("{AUTO_PREFIX}{}", Local::now().format("%Y%m%d-%H%M%S"));
+    snapshots::capture(app, &name)?;
+    let local = snapshots::snapshots_dir(app)?;
+    prune(&local);
+    let root = mirror_root(app, destination);
+    fs::create_dir_all(&root).map_err(|err| format!("Failed to create backup folder: {err}"))?;
+    let mirrored: Vec<String> = list(&root).into_iter().map(|info| info.name).collect();
+    for info in list(&local) {
+        if !mirrored.contains(&info.name) {
+            mirror_snapshot(&local.join(&info.name), &root, &info.name)?;
+        }
+    }
+    prune(&root);
+    Ok(name)
+}
+
+fn run<R: Runtime>(app: &AppHandle<R>) -> Result<BackupStatus, String> {
+    let state = app.state::<BackupState>();
+    let Ok(_running) = state.running.try_lock() else {
+        return Err("A backup is already running.".to_string());
+    };
+    let destination = settings(app)
+        .destination
+        .ok_or_else(|| "No backup destination is set.".to_string())?;
+    let mut record = load_record(app);
+    let now = Local::now().to_rfc3339();
+    record.last_attempt = Some(now.clone());
+    let result = mirror(app, Path::new(&destination));
+    match &result {
+        Ok(name) => {
+            tracing::info!("[Backup] Mirrored snapshots to {destination}.");
+            record.last_success = Some(now);
+            record.last_error = None;
+            record.last_snapshot = Some(name.clone());
+        }
+        Err(err) => {
+            tracing::warn!("[Backup] {err}");
+            record.last_error = Some(err.clone());
+        }
+    }
+    save_record(app, &record)?;
+    let status = status(app);
+    events::backup_status(app, &status);
+    result.map(|_| status)
+}
+
+fn due<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let settings = settings(app);
+    if settings.destination.is_none() {
+        return false;
+    }
+    let Some(last_success) = load_record(app).last_success else {
+        return true;
+    };
+    DateTime::parse_from_rfc3339(&last_success).map_or(true, |last| {
+        Local::now().signed_duration_since(last)
+            >= chrono::Duration::hours(i64::from(settings.interval_hours))
+    })
+}
+
+/// Runs a backup whenever the interval has passed since the last successful one.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut notified_failure = false;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            if !due(&app) {
+                continue;
+            }
+            let handle = app.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || run(&handle)).await;
+            match result {
+                Ok(Ok(_)) => notified_failure = false,
+                // Failures repeat every poll while a drive is unplugged; say so once.
+                Ok(Err(err)) if !notified_failure => {
+                    notified_failure = true;
+                    if let Err(err) = notifications::notify(
+                        &app,
+                        NotificationKind::ScheduledJob,
+                        &locale::t(&app, "notification.backup_failed"),
+                        &err,
+                    ) {
+                        tracing::warn!("[Backup] {err}");
+                    }
+                }
+                Ok(Err(_)) => {}
+                Err(err) => tracing::warn!("[Backup] Backup task failed: {err}"),
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_backup_status(app: AppHandle) -> Result<BackupStatus, AppError> {
+    tauri::async_runtime::spawn_blocking(move || status(&app))
+        .await
+        .map_err(|err| AppError::from(format!("Backup status task failed: {err}")))
+}
+
+/// Sets where snapshots are mirrored and how often; `None` stops mirroring and
+/// leaves existing copies in place.
+#[tauri::command]
+pub fn set_backup_destination(
+    app: AppHandle,
+    path: Option<String>,
+    interval_hours: Option<u32>,
+) -> Result<BackupStatus, AppError> {
+    let interval_hours = interval_hours.unwrap_or_else(|| settings(&app).interval_hours);
+    if !(1..=MAX_INTERVAL_HOURS).contains(&interval_hours) {
+        return Err(AppError::invalid_input(format!(
+            "Backup interval must be between 1 and {MAX_INTERVAL_HOURS} hours."
+        )));
+    }
+    let destination = match path.map(|path| path.trim().to_string()) {
+        Some(path) if !path.is_empty() => {
+            let dir = PathBuf::from(&path);
+            if !dir.is_dir() {
+                return Err(AppError::not_found(format!("{path} is not a folder.")));
+            }
+            let app_data_dir = crate::resolve_app_data_dir(&app)?;
+            if dir.starts_with(&app_data_dir) {
+                return Err(AppError::invalid_input(
+                    "Backups must go outside the app's own data folder.",
+                ));
+            }
+            Some(path)
+        }
+        _ => None,
+    };
+    app.state::<SettingsStore>().update(|settings| {
+        settings.backup.destination = destination;
+        settings.backup.interval_hours = interval_hours;
+    })?;
+    Ok(status(&app))
+}
+
+/// Takes a snapshot and mirrors it now, whatever the schedule says.
+#[tauri::command]
+pub async fn run_backup_now(app: AppHandle) -> Result<BackupStatus, AppError> {
+    tauri::async_runtime::spawn_blocking(move || run(&app))
+        .await
+        .map_err(|err| AppError::from(format!("Backup task failed: {err}")))?
+        .map_err(AppError::from)
+}
@@ -0,0 +1,511 @@
+use std::{
+    io::Cursor,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use image::{imageops::FilterType, DynamicImage, ImageFormat, RgbaImage};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Monitor, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{
+    attachments::{AttachmentHandle, AttachmentStore},
+    clipboard,
+    error::{AppError, ErrorCode},
+    events, file_drop, locale, main_window,
+    permissions::{self, PermissionKind, PermissionStatus},
+    proxy, BackendState,
+};
+
+pub const INDICATOR_LABEL: &str = "capture-indicator";
+/// The transparent overlay a region is dragged out on.
+pub const SELECTION_LABEL: &str = "capture-selection";
+/// Time for the compositor to take the overlay and the main window off the screen
+/// before it is captured.
+const HIDE_DELAY: Duration = Duration::from_millis(200);
+const MIN_INTERVAL_MS: u64 = 2000;
+const DEFAULT_INTERVAL_MS: u64 = 5000;
+const DEFAULT_MAX_WIDTH: u32 = 1280;
+
+#[derive(Default)]
+pub struct ContextCapture(Mutex<Option<Arc<AtomicBool>>>);
+
+events::payload! {
+    /// A frame that could not be taken or sent; the capture carries on.
+    pub struct CaptureError {
+        message: String,
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CaptureTarget {
+    Screen {
+        monitor_id: Option<u32>,
+        region: Option<CaptureRegion>,
+    },
+    Window {
+        window_id: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct CaptureRegion {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+/// A rectangle in the coordinates the OS lays screens out in: points on macOS,
+/// pixels elsewhere.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Rect {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+/// The region dragged out on the overlay, as fractions of the screen it covered.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Selection {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+/// Where the overlay's answer goes while one is open; dropping it cancels.
+#[derive(Default)]
+pub struct ScreenSelection(Mutex<Option<mpsc::Sender<Selection>>>);
+
+#[derive(Debug, Deserialize)]
+pub struct CaptureOptions {
+    /// Must be set by a UI flow where the user explicitly agreed to share their screen.
+    consent: bool,
+    interval_ms: Option<u64>,
+    max_width: Option<u32>,
+    session_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureSource {
+    kind: &'static str,
+    id: u32,
+    name: String,
+}
+
+#[tauri::command]
+pub fn list_capture_sources() -> Result<Vec<CaptureSource>, AppError> {
+    let mut sources = Vec::new();
+    let monitors = xcap::Monitor::all()
+        .map_err(|err| AppError::unavailable("Failed to list screens.").with_details(err))?;
+    for monitor in monitors {
+        if let Ok(id) = monitor.id() {
+            sources.push(CaptureSource {
+                kind: "screen",
+                id,
+                name: monitor.name().unwrap_or_else(|_| format!("Screen {id}")),
+            });
+        }
+    }
+    let windows = xcap::Window::all()
+        .map_err(|err| AppError::unavailable("Failed to list windows.").with_details(err))?;
+    for window in windows {
+        if window.is_minimized().unwrap_or(false) {
+            continue;
+        }
+        let (Ok(id), Ok(title)) = (window.id(), window.title()) else {
+            continue;
+        };
+        if title.trim().is_empty() {
+            continue;
+        }
+        let app_name = window.app_name().unwrap_or_default();
+        sources.push(CaptureSource {
+            kind: "window",
+            id,
+            name: if app_name.is_empty() {
+                title
+            } else {
+                format!("{app_name} - {title}")
+            },
+        });
+    }
+    Ok(sources)
+}
+
+#[tauri::command]
+pub fn start_context_capture(
+    app: AppHandle,
+    capture: tauri::State<ContextCapture>,
+    target: CaptureTarget,
+    options: CaptureOptions,
+) -> Result<(), AppError> {
+    if !options.consent {
+        return Err(AppError::invalid_input(
+            "Screen capture requires explicit user consent.",
+        ));
+    }
+    let mut guard = capture
+        .0
+        .lock()
+        .map_err(|_| AppError::unavailable("Capture state is unavailable."))?;
+    if guard.is_some() {
+        return Err(AppError::invalid_input(
+            "A capture session is already running.",
+        ));
+    }
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| AppError::unavailable("Backend is not available."))?;
+    ensure_permission(&app)?;
+    show_indicator(&app)?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    *guard = Some(stop.clone());
+    let interval = Duration::from_millis(
+        options
+            .interval_ms
+            .unwrap_or(DEFAULT_INTERVAL_MS)
+            .max(MIN_INTERVAL_MS),
+    );
+    let max_width = options.max_width.unwrap_or(DEFAULT_MAX_WIDTH).max(1);
+    let session_id = options.session_id.unwrap_or_default();
+    let loop_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let client = proxy::client(&loop_app);
+        let url = format!("{base_url}/context/frames");
+        while !stop.load(Ordering::SeqCst) {
+            let frame_target = target.clone();
+            let frame = tauri::async_runtime::spawn_blocking(move || {
+                capture_frame(&frame_target, max_width)
+            })
+            .await
+            .map_err(|err| format!("Capture task failed: {err}"))
+            .and_then(|result| result);
+            let sent = match frame {
+                Ok(jpeg) => client
+                    .post(&url)
+                    .query(&[("session_id", session_id.as_str())])
+                    .header(reqwest::header::CONTENT_TYPE, "image/jpeg")
+                    .body(jpeg)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map(|_| ())
+                    .map_err(|err| format!("Failed to send frame to backend: {err}")),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = sent {
+                tracing::warn!("[Capture] {err}");
+                events::capture_error(&loop_app, &CaptureError { message: err });
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+    events::capture_started(&app, &events::Empty {});
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_context_capture(app: AppHandle) {
+    stop(&app);
+}
+
+/// Ends the running capture session, if any. Also called when the indicator window closes.
+pub fn stop<R: tauri::Runtime>(app: &AppHandle<R>) {
+    let Some(state) = app.try_state::<ContextCapture>() else {
+        return;
+    };
+    let Some(flag) = state.0.lock().ok().and_then(|mut guard| guard.take()) else {
+        return;
+    };
+    flag.store(true, Ordering::SeqCst);
+    if let Some(window) = app.get_webview_window(INDICATOR_LABEL) {
+        let _ = window.close();
+    }
+    events::capture_stopped(app, &events::Empty {});
+}
+
+/// Asks for screen recording where the OS gates it. macOS only applies a grant
+/// after a relaunch, so a missing one fails with a hint rather than waiting.
+fn ensure_permission(app: &AppHandle) -> Result<(), AppError> {
+    match permissions::status(app, PermissionKind::ScreenRecording) {
+        PermissionStatus::Granted | PermissionStatus::NotRequired | PermissionStatus::Unknown => {
+            Ok(())
+        }
+        _ => {
+            permissions::request_permission(app.clone(), PermissionKind::ScreenRecording)?;
+            Err(AppError::new(
+                ErrorCode::Permission,
+                locale::t(app, "capture.permission_hint"),
+            ))
+        }
+    }
+}
+
+/// The xcap screen shown on `monitor`, matched by its centre, which both place in
+/// the same spot whatever their units.
+fn xcap_monitor(monitor: &Monitor) -> Result<xcap::Monitor, String> {
+    let position = monitor.position();
+    let size = monitor.size();
+    let scale = if cfg!(target_os = "macos") {
+        monitor.scale_factor()
+    } else {
+        1.0
+    };
+    let x = (position.x as f64 + size.width as f64 / 2.0) / scale;
+    let y = (position.y as f64 + size.height as f64 / 2.0) / scale;
+    xcap::Monitor::from_point(x as i32, y as i32)
+        .map_err(|err| format!("Failed to find the screen to capture: {err}"))
+}
+
+fn capture_rect(rect: Rect) -> Result<RgbaImage, String> {
+    if rect.width == 0 || rect.height == 0 {
+        return Err("The region to capture is empty.".to_string());
+    }
+    let monitor = xcap::Monitor::from_point(rect.x, rect.y)
+        .map_err(|err| format!("No screen shows that region: {err}"))?;
+    let origin = (
+        monitor.x().unwrap_or_default(),
+        monitor.y().unwrap_or_default(),
+    );
+    let bounds = (
+        monitor.width().unwrap_or_default(),
+        monitor.height().unwrap_or_default(),
+    );
+    let x = (rect.x - origin.0).max(0) as u32;
+    let y = (rect.y - origin.1).max(0) as u32;
+    // A region running off the screen is cut at its edge.
+    let width = rect.width.min(bounds.0.saturating_sub(x));
+    let height = rect.height.min(bounds.1.saturating_sub(y));
+    monitor
+        .capture_region(x, y, width, height)
+        .map_err(|err| format!("Failed to capture the screen: {err}"))
+}
+
+fn crop(image: RgbaImage, selection: Selection) -> Result<RgbaImage, String> {
+    let (width, height) = image.dimensions();
+    let fraction = |value: f64| value.clamp(0.0, 1.0);
+    let x = (fraction(selection.x) * width as f64) as u32;
+    let y = (fraction(selection.y) * height as f64) as u32;
+    let crop_width = ((fraction(selection.width) * width as f64) as u32).min(width - x);
+    let crop_height = ((fraction(selection.height) * height as f64) as u32).min(height - y);
+    if crop_width == 0 || crop_height == 0 {
+        return Err("The selected region is empty.".to_string());
+    }
+    Ok(image::imageops::crop_imm(&image, x, y, crop_width, crop_height).to_image())
+}
+
+/// Covers the screen under the cursor with the selection overlay, with the main
+/// window out of the way, and waits for the region dragged out on it. `None` when
+/// the user cancelled.
+fn select(app: &AppHandle) -> Result<Option<(RgbaImage, Selection)>, String> {
+    let cursor = app
+        .cursor_position()
+        .map_err(|err| format!("Failed to locate the cursor: {err}"))?;
+    let monitor = app
+        .monitor_from_point(cursor.x, cursor.y)
+        .ok()
+        .flatten()
+        .or_else(|| app.primary_monitor().ok().flatten())
+        .ok_or("No screen is connected.")?;
+    let (sender, receiver) = mpsc::channel();
+    if let Some(state) = app.try_state::<ScreenSelection>() {
+        if let Ok(mut pending) = state.0.lock() {
+            *pending = Some(sender);
+        }
+    }
+    let main = app
+        .get_webview_window(main_window::LABEL)
+        .filter(|window| window.is_visible().unwrap_or(false));
+    if let Some(main) = &main {
+        let _ = main.hide();
+    }
+    let shown = WebviewWindowBuilder::new(
+        app,
+        SELECTION_LABEL,
+        WebviewUrl::App("index.html?window=capture-selection".into()),
+    )
+    .title(locale::t(app, "window.capture_selection"))
+    .decorations(false)
+    .transparent(true)
+    .shadow(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .resizable(false)
+    .visible(false)
+    .build()
+    .and_then(|overlay| {
+        overlay.set_position(*monitor.position())?;
+        overlay.set_size(*monitor.size())?;
+        overlay.show()?;
+        overlay.set_focus()
+    });
+    let answer = match shown {
+        Ok(()) => receiver.recv().ok(),
+        Err(err) => {
+            tracing::warn!("[Capture] Failed to show the selection overlay: {err}");
+            None
+        }
+    };
+    if let Some(overlay) = app.get_webview_window(SELECTION_LABEL) {
+        let _ = overlay.destroy();
+    }
+    let captured = match answer {
+        Some(selection) => {
+            thread::sleep(HIDE_DELAY);
+            xcap_monitor(&monitor)?
+                .capture_image()
+                .map(|image| Some((image, selection)))
+                .map_err(|err| format!("Failed to capture the screen: {err}"))
+        }
+        None => Ok(None),
+    };
+    if let Some(main) = &main {
+        let _ = main.show();
+    }
+    captured
+}
+
+/// Captures `region`, or the region the user drags out on an overlay over the screen
+/// under the cursor, into the attachment store and adds it to the main window's
+/// next prompt. `None` when the user cancelled the selection.
+#[tauri::command]
+pub async fn capture_screen(
+    app: AppHandle,
+    region: Option<Rect>,
+) -> Result<Option<AttachmentHandle>, AppError> {
+    ensure_permission(&app)?;
+    if app.get_webview_window(SELECTION_LABEL).is_some() {
+        return Err(AppError::invalid_input(
+            "A region is already being selected.",
+        ));
+    }
+    let handle = app.clone();
+    let captured = tauri::async_runtime::spawn_blocking(move || {
+        let image = match region {
+            Some(region) => capture_rect(region)?,
+            None => match select(&handle)? {
+                Some((image, selection)) => crop(image, selection)?,
+                None => return Ok(None),
+            },
+        };
+        let (width, height) = image.dimensions();
+        let png = clipboard::encode_png(width, height, image.into_raw())?;
+        let name = format!(
+            "screenshot-{}.png",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        );
+        let attachment = handle
+            .state::<AttachmentStore>()
+            .store_bytes(&png, &name, "image/png")?;
+        if let Some(reason) = &attachment.quarantine {
+            return Err(format!("The screenshot was quarantined: {reason}"));
+        }
+        Ok(Some((attachment, png)))
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Capture task failed: {err}")))??;
+    let Some((attachment, png)) = captured else {
+        return Ok(None);
+    };
+    // The handle still lets the caller attach it once the backend is back.
+    if let Err(err) = file_drop::add_to_prompt(&app, attachment.clone(), png).await {
+        tracing::warn!("[Capture] {err}");
+    }
+    Ok(Some(attachment))
+}
+
+/// The overlay's answer: the dragged-out region, or `None` to cancel.
+#[tauri::command]
+pub fn finish_screen_selection(app: AppHandle, selection: Option<Selection>) {
+    let sender = app
+        .try_state::<ScreenSelection>()
+        .and_then(|state| state.0.lock().ok()?.take());
+    if let (Some(sender), Some(selection)) = (sender, selection) {
+        let _ = sender.send(selection);
+    }
+}
+
+/// Closing the overlay any other way cancels the selection.
+pub fn selection_closed<R: tauri::Runtime>(app: &AppHandle<R>) {
+    if let Some(state) = app.try_state::<ScreenSelection>() {
+        if let Ok(mut pending) = state.0.lock() {
+            pending.take();
+        }
+    }
+}
+
+fn show_indicator(app: &AppHandle) -> Result<(), String> {
+    if app.get_webview_window(INDICATOR_LABEL).is_some() {
+        return Ok(());
+    }
+    WebviewWindowBuilder::new(
+        app,
+        INDICATOR_LABEL,
+        WebviewUrl::App("capture-indicator.html".into()),
+    )
+    .title(locale::t(app, "window.capture_indicator"))
+    .inner_size(340.0, 40.0)
+    .resizable(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .build()
+    .map(|_| ())
+    .map_err(|err| format!("Failed to show capture indicator: {err}"))
+}
+
+fn capture_frame(target: &CaptureTarget, max_width: u32) -> Result<Vec<u8>, String> {
+    let image = match target {
+        CaptureTarget::Screen { monitor_id, region } => {
+            let monitors =
+                xcap::Monitor::all().map_err(|err| format!("Failed to list screens: {err}"))?;
+            let monitor = monitors
+                .into_iter()
+                .find(|monitor| match monitor_id {
+                    Some(id) => monitor.id().ok() == Some(*id),
+                    None => monitor.is_primary().unwrap_or(false),
+                })
+                .ok_or_else(|| "Requested screen is no longer connected.".to_string())?;
+            match region {
+                Some(region) => {
+                    monitor.capture_region(region.x, region.y, region.width, region.height)
+                }
+                None => monitor.capture_image(),
+            }
+        }
+        CaptureTarget::Window { window_id } => xcap::Window::all()
+            .map_err(|err| format!("Failed to list windows: {err}"))?
+            .into_iter()
+            .find(|window| window.id().ok() == Some(*window_id))
+            .ok_or_else(|| "Requested window is no longer open.".to_string())?
+            .capture_image(),
+    }
+    .map_err(|err| format!("Failed to capture frame: {err}"))?;
+    encode_jpeg(downscale(image, max_width))
+}
+
+fn downscale(image: RgbaImage, max_width: u32) -> RgbaImage {
+    if image.width() <= max_width {
+        return image;
+    }
+    let height = (image.height() as u64 * max_width as u64 / image.width() as u64).max(1) as u32;
+    image::imageops::resize(&image, max_width, height, FilterType::Triangle)
+}
+
+fn encode_jpeg(image: RgbaImage) -> Result<Vec<u8>, String> {
+    let mut jpeg = Cursor::new(Vec::new());
+    DynamicImage::ImageRgba8(image)
+        .to_rgb8()
+        .write_to(&mut jpeg, ImageFormat::Jpeg)
+        .map_err(|err| format!("Failed to encode frame: {err}"))?;
+    Ok(jpeg.into_inner())
+}
@@ -0,0 +1,231 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{costs, error::AppError, events, proxy, rpc, watchdog, BackendState};
+
+/// Agent runs can go on for a long time; the stream stays open throughout.
+const CALL_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// How often a quiet stream checks whether it was cancelled.
+const CANCEL_POLL: Duration = Duration::from_millis(250);
+
+/// Streams the shell is reading on the webview's behalf, keyed by request id.
+#[derive(Default)]
+pub struct ChatStreams(Mutex<HashMap<String, Arc<AtomicBool>>>);
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatStreamKind {
+    Chat,
+    Agent,
+}
+
+impl ChatStreamKind {
+    fn path(self) -> &'static str {
+        match self {
+            Self::Chat => "/chat/stream",
+            Self::Agent => "/chat/agent/stream",
+        }
+    }
+}
+
+events::payload! {
+    pub struct ChatDelta {
+        request_id: String,
+        /// One SSE `data:` payload, as the backend sent it.
+        data: Value,
+    }
+}
+
+events::payload! {
+    pub struct ChatDone {
+        request_id: String,
+        cancelled: bool,
+        error: Option<String>,
+    }
+}
+
+/// Splits complete `data:` lines off the front of `buffer`, leaving a partial one.
+/// Works on bytes so a character split across chunks is not mangled.
+pub fn drain_events(buffer: &mut Vec<u8>) -> Vec<Value> {
+    let Some(end) = buffer.iter().rposition(|byte| *byte == b'\n') else {
+        return Vec::new();
+    };
+    let complete: Vec<u8> = buffer.drain(..=end).collect();
+    String::from_utf8_lossy(&complete)
+        .lines()
+        .filter_map(|line| line.trim_end_matches('\r').strip_prefix("data: "))
+        .filter_map(|data| serde_json::from_str(data).ok())
+        .collect()
+}
+
+fn emit_events<R: Runtime>(app: &AppHandle<R>, request_id: &str, buffer: &mut Vec<u8>) {
+    for data in drain_events(buffer) {
+        let delta = ChatDelta {
+            request_id: request_id.to_string(),
+            data,
+        };
+        events::chat_delta(app, &delta);
+    }
+}
+
+fn status_error(status: u16, body: &[u8]) -> String {
+    let detail = serde_json::from_slice::<Value>(body).ok().and_then(|body| {
+        body.get("detail")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    });
+    match detail {
+        Some(detail) => format!("Backend answered {status}: {detail}"),
+        None => format!("Backend answered {status}."),
+    }
+}
+
+/// Over stdio the reply only arrives once the run is over, so the events come
+/// out in one burst at the end.
+async fn pump_rpc<R: Runtime>(
+    app: &AppHandle<R>,
+    request_id: &str,
+    path: &'static str,
+    body: Value,
+) -> Result<(), String> {
+    let handle = app.clone();
+    let (status, reply) =
+        tauri::async_runtime::spawn_blocking(move || rpc::post(&handle, path, &body, CALL_TIMEOUT))
+            .await
+            .map_err(|err| format!("Stream task failed: {err}"))??;
+    if status >= 300 {
+        return Err(status_error(status, &reply));
+    }
+    let mut buffer = reply;
+    buffer.push(b'\n');
+    emit_events(app, request_id, &mut buffer);
+    Ok(())
+}
+
+async fn pump_http<R: Runtime>(
+    app: &AppHandle<R>,
+    request_id: &str,
+    path: &'static str,
+    body: Value,
+    cancelled: &AtomicBool,
+) -> Result<(), String> {
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| "Backend is not available.".to_string())?;
+    let mut response = proxy::client(app)
+        .post(format!("{base_url}{path}"))
+        .timeout(CALL_TIMEOUT)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|err| format!("Failed to open the stream: {err}"))?;
+    if !response.status().is_success() {
+        let status = response.status().as_u16();
+        let reply = response.bytes().await.unwrap_or_default();
+        return Err(status_error(status, &reply));
+    }
+    let mut buffer = Vec::new();
+    while !cancelled.load(Ordering::SeqCst) {
+        // Dropping a pending read loses nothing, so a short timeout is a cheap way
+        // to notice cancellation while the backend is thinking.
+        let chunk = match tokio::time::timeout(CANCEL_POLL, response.chunk()).await {
+            Err(_) => continue,
+            Ok(chunk) => chunk.map_err(|err| format!("The stream was cut off: {err}"))?,
+        };
+        let Some(chunk) = chunk else {
+            buffer.push(b'\n');
+            emit_events(app, request_id, &mut buffer);
+            break;
+        };
+        buffer.extend_from_slice(&chunk);
+        emit_events(app, request_id, &mut buffer);
+    }
+    Ok(())
+}
+
+/// Opens a streaming chat request from the shell and re-emits each event as
+/// `chat://delta`, then `chat://done`, so the stream outlives webview navigation.
+/// The webview picks `request_id` so it can listen before anything is sent.
+#[tauri::command]
+pub fn stream_chat(
+    app: AppHandle,
+    streams: tauri::State<ChatStreams>,
+    request_id: String,
+    kind: ChatStreamKind,
+    request: Value,
+) -> Result<(), AppError> {
+    if costs::is_paused(&app) {
+        return Err(AppError::unavailable(
+            "This month's budget is spent; LLM calls are paused until you resume them.",
+        ));
+    }
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let mut active = streams
+            .0
+            .lock()
+            .map_err(|_| AppError::unavailable("Stream state is unavailable."))?;
+        if active.contains_key(&request_id) {
+            return Err(AppError::invalid_input(format!(
+                "A stream with id {request_id} is already running."
+            )));
+        }
+        active.insert(request_id.clone(), cancelled.clone());
+    }
+    tauri::async_runtime::spawn(async move {
+        let session_id = request
+            .get("session_id")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let run = watchdog::track(&app, kind.path(), Some(request_id.clone()), session_id);
+        let result = if rpc::is_attached(&app) {
+            pump_rpc(&app, &request_id, kind.path(), request).await
+        } else {
+            pump_http(&app, &request_id, kind.path(), request, &cancelled).await
+        };
+        drop(run);
+        if let Some(streams) = app.try_state::<ChatStreams>() {
+            if let Ok(mut active) = streams.0.lock() {
+                active.remove(&request_id);
+            }
+        }
+        let error = result.err();
+        if let Some(err) = &error {
+            tracing::warn!("[ChatStream] {request_id}: {err}");
+        }
+        let done = ChatDone {
+            request_id,
+            cancelled: cancelled.load(Ordering::SeqCst),
+            error,
+        };
+        events::chat_done(&app, &done);
+    });
+    Ok(())
+}
+
+/// Stops reading a stream; returns false if it had already finished. The run
+/// itself keeps going on the backend, so use `/chat/stop` to end it there.
+#[tauri::command]
+pub fn cancel_stream(streams: tauri::State<ChatStreams>, request_id: String) -> bool {
+    let Ok(active) = streams.0.lock() else {
+        return false;
+    };
+    match active.get(&request_id) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
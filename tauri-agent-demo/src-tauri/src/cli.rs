@@ -0,0 +1,324 @@
+use std::{
+    io::{self, Read, Write},
+    sync::{atomic::AtomicU16, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+use tauri::{AppHandle, Context, Manager, RunEvent, Runtime, Wry};
+
+use crate::{
+    attachments::AttachmentStore,
+    backend, backend_profiles, chat_stream, encryption, health, network,
+    proxy::{self, Proxy},
+    settings::{BackendTransport, SettingsStore},
+    sidecar::{self, Sidecars},
+    stale_backend,
+    temp_files::{self, TempFiles},
+    workspace, BackendState,
+};
+
+/// `agent ask "prompt"` runs headless; anything else starts the app as usual.
+const COMMAND: &str = "ask";
+const USAGE: &str = "Usage: agent ask <prompt|-> [--workspace <id or name>] [--json]
+
+Sends the prompt to the agent and streams the answer to stdout. A prompt of `-`
+is read from stdin. With --json, every event of the run is printed as one JSON
+line instead. While the chat database is locked, its passphrase is read from
+TAURI_AGENT_DB_PASSPHRASE. Exits with 0 on an answer, 1 when the run fails, 2 on
+bad usage and 3 when no backend could be reached.";
+const EXIT_FAILED: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_BACKEND: i32 = 3;
+const RUN_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Beside the app's own `backend.pid`, which stays the running app's.
+const PID_FILE: &str = "backend-cli.pid";
+/// Unlocks an encrypted chat database for this run; never written anywhere.
+const PASSPHRASE_ENV: &str = "TAURI_AGENT_DB_PASSPHRASE";
+
+pub struct Ask {
+    prompt: String,
+    workspace: Option<String>,
+    json: bool,
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Ask, String> {
+    let mut prompt = None;
+    let mut workspace = None;
+    let mut json = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--workspace" => {
+                workspace = Some(args.next().ok_or("--workspace needs a value.")?);
+            }
+            "-h" | "--help" => return Err(String::new()),
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {flag}.")),
+            _ if prompt.is_some() => {
+                return Err("Pass the prompt as one argument; quote it.".to_string())
+            }
+            _ => prompt = Some(arg),
+        }
+    }
+    let mut prompt = prompt.ok_or("No prompt given.")?;
+    if prompt == "-" {
+        prompt.clear();
+        io::stdin()
+            .read_to_string(&mut prompt)
+            .map_err(|err| format!("Failed to read the prompt from stdin: {err}"))?;
+    }
+    if prompt.trim().is_empty() {
+        return Err("The prompt is empty.".to_string());
+    }
+    Ok(Ask {
+        prompt,
+        workspace,
+        json,
+    })
+}
+
+/// The command-line request, when the process was started as `agent ask …`.
+pub fn invocation() -> Option<Result<Ask, String>> {
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() != Some(COMMAND) {
+        return None;
+    }
+    Some(parse(args))
+}
+
+/// Release builds on Windows have no console of their own; borrow the terminal's.
+fn attach_console() {
+    #[cfg(windows)]
+    unsafe {
+        use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// `--workspace` for this run only; the app keeps its own selection.
+fn select_workspace<R: Runtime>(app: &AppHandle<R>, wanted: &str) -> Result<(), String> {
+    let store = app.state::<SettingsStore>();
+    let settings = store.get().workspaces;
+    let active = if wanted == workspace::DEFAULT_ID {
+        None
+    } else {
+        let found = settings
+            .list
+            .iter()
+            .find(|workspace| workspace.id == wanted || workspace.name.eq_ignore_ascii_case(wanted))
+            .ok_or_else(|| format!("No workspace named {wanted}."))?;
+        Some(found.id.clone())
+    };
+    store.amend(move |settings| settings.workspaces.active = active.clone());
+    Ok(())
+}
+
+/// The selected profile's backend, the running app's when it serves the same
+/// workspace, or a sidecar of our own that only lives as long as this run.
+async fn connect(app: &AppHandle, workspace_changed: bool) -> Result<(), String> {
+    let settings = app.state::<SettingsStore>();
+    let host = network::resolve_bind_host(Some(&settings));
+    let mut target = backend_profiles::initial(app);
+    let shared = if target.url.is_none() && !workspace_changed {
+        let handle = app.clone();
+        tauri::async_runtime::spawn_blocking(move || stale_backend::running(&handle))
+            .await
+            .ok()
+            .flatten()
+    } else {
+        None
+    };
+    if let Some((url, token)) = shared {
+        target.url = Some(url);
+        target.token = token;
+    }
+    let spawn = target.url.is_none();
+    let port = if spawn {
+        crate::pick_backend_port(host)?
+    } else {
+        0
+    };
+    app.manage(Proxy::new(false, spawn)?);
+    app.manage(BackendState {
+        host,
+        port: AtomicU16::new(port),
+        target: Mutex::new(target),
+        transport: BackendTransport::Tcp,
+    });
+    if spawn {
+        let attachments = settings.get().attachments;
+        app.manage(AttachmentStore::new(
+            workspace::data_dir(app)?.join("attachments"),
+            &attachments,
+        ));
+        app.manage(TempFiles::new(temp_files::resolve_root(app)?));
+        let mut spec = crate::backend_spec(app, host, port, BackendTransport::Tcp)?.env(
+            stale_backend::PID_FILE_ENV,
+            crate::resolve_app_data_dir(app)?.join(PID_FILE),
+        );
+        spec.quiet = true;
+        sidecar::start(app, spec)?;
+    }
+    let timeout = Duration::from_secs(backend::readiness_settings(app).timeout_secs.max(1));
+    let started = Instant::now();
+    while !health::probe(app).await {
+        if started.elapsed() >= timeout {
+            return Err(format!(
+                "The backend did not answer /health within {}s.",
+                timeout.as_secs()
+            ));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    Ok(())
+}
+
+/// Prints the run as it streams; `Ok` once an answer came without an error.
+async fn stream(app: &AppHandle, ask: &Ask) -> Result<(), String> {
+    let base_url = app.state::<BackendState>().base_url();
+    let work_path = std::env::current_dir()
+        .ok()
+        .map(|dir| dir.to_string_lossy().into_owned());
+    let mut response = proxy::client(app)
+        .post(format!("{base_url}/chat/agent/stream"))
+        .timeout(RUN_TIMEOUT)
+        .json(&json!({ "message": ask.prompt, "work_path": work_path }))
+        .send()
+        .await
+        .map_err(|err| format!("Backend is unreachable: {err}"))?;
+    if !response.status().is_success() {
+        return Err(format!("Backend answered {}.", response.status().as_u16()));
+    }
+    let mut stdout = io::stdout();
+    let mut buffer = Vec::new();
+    let mut answered = false;
+    let mut streamed = false;
+    let mut failure = None;
+    loop {
+        let chunk = response
+            .chunk()
+            .await
+            .map_err(|err| format!("The stream was cut off: {err}"))?;
+        let finished = chunk.is_none();
+        match chunk {
+            Some(chunk) => buffer.extend_from_slice(&chunk),
+            None => buffer.push(b'\n'),
+        }
+        for event in chat_stream::drain_events(&mut buffer) {
+            let content = event
+                .get("content")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let step = event.get("step_type").and_then(Value::as_str);
+            match step {
+                Some("answer") => answered = true,
+                Some("error") => failure = Some(content.to_string()),
+                _ => {}
+            }
+            let _ = if ask.json {
+                writeln!(stdout, "{event}")
+            } else {
+                match step {
+                    Some("answer_delta") => {
+                        streamed = true;
+                        write!(stdout, "{content}")
+                    }
+                    Some("answer") if !streamed => write!(stdout, "{content}"),
+                    _ => Ok(()),
+                }
+            };
+            let _ = stdout.flush();
+        }
+        if finished {
+            break;
+        }
+    }
+    if !ask.json && answered {
+        let _ = writeln!(stdout);
+    }
+    match failure {
+        Some(error) => Err(error),
+        None if answered => Ok(()),
+        None => Err("The run ended without an answer.".to_string()),
+    }
+}
+
+async fn ask(app: &AppHandle, ask: Ask) -> i32 {
+    let app_data_dir = match crate::resolve_app_data_dir(app) {
+        Ok(dir) => dir,
+        Err(err) => {
+            eprintln!("{err}");
+            return EXIT_BACKEND;
+        }
+    };
+    app.manage(SettingsStore::load(
+        app_data_dir.join("shell_settings.json"),
+    ));
+    if encryption::init(app) {
+        let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) else {
+            eprintln!(
+                "The chat database is locked; unlock it in the app or pass the passphrase in {PASSPHRASE_ENV}."
+            );
+            return EXIT_BACKEND;
+        };
+        if let Err(err) = encryption::unlock_for_run(app, &passphrase) {
+            eprintln!("Could not unlock the chat database: {err}");
+            return EXIT_BACKEND;
+        }
+    }
+    let before = app.state::<SettingsStore>().get().workspaces.active;
+    if let Some(wanted) = &ask.workspace {
+        if let Err(err) = select_workspace(app, wanted) {
+            eprintln!("{err}");
+            return EXIT_USAGE;
+        }
+    }
+    let changed = app.state::<SettingsStore>().get().workspaces.active != before;
+    if let Err(err) = connect(app, changed).await {
+        eprintln!("{err}");
+        return EXIT_BACKEND;
+    }
+    match stream(app, &ask).await {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("{err}");
+            EXIT_FAILED
+        }
+    }
+}
+
+/// Runs `invocation` without opening a window and exits with its status. The app
+/// still starts its event loop, so Linux needs a display, as under `xvfb-run`.
+pub fn run(invocation: Result<Ask, String>, mut context: Context<Wry>) -> ! {
+    attach_console();
+    let ask = match invocation {
+        Ok(ask) => ask,
+        Err(err) => {
+            if !err.is_empty() {
+                eprintln!("{err}\n");
+            }
+            eprintln!("{USAGE}");
+            std::process::exit(if err.is_empty() { 0 } else { EXIT_USAGE });
+        }
+    };
+    context.config_mut().app.windows.clear();
+    let app = tauri::Builder::default()
+        .manage(Sidecars::default())
+        .setup(move |app| {
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let code = self::ask(&handle, ask).await;
+                handle.exit(code);
+            });
+            Ok(())
+        })
+        .build(context)
+        .expect("error while building tauri application");
+    let code = app.run_return(|app_handle, event| {
+        if let RunEvent::Exit = event {
+            sidecar::stop_all(app_handle);
+        }
+    });
+    std::process::exit(code)
+}
@@ -0,0 +1,128 @@
+use std::io::Cursor;
+
+use image::{ImageFormat, RgbaImage};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    attachments::{AttachmentHandle, AttachmentStore},
+    error::AppError,
+    file_drop, proxy, BackendState,
+};
+
+/// A pasted image, stored and handed to the backend like a dropped file.
+#[derive(Debug, Clone, Serialize)]
+pub struct PastedImage {
+    attachment: AttachmentHandle,
+    /// What chat requests pass as `upload_id` instead of the image's bytes.
+    upload_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardContent {
+    text: Option<String>,
+    image: Option<PastedImage>,
+}
+
+fn open() -> Result<arboard::Clipboard, String> {
+    arboard::Clipboard::new().map_err(|err| format!("Failed to open clipboard: {err}"))
+}
+
+/// Stores the clipboard image as a PNG attachment; `None` when there is none.
+fn store_image(
+    clipboard: &mut arboard::Clipboard,
+    store: &AttachmentStore,
+) -> Result<Option<(AttachmentHandle, Vec<u8>)>, String> {
+    let image = match clipboard.get_image() {
+        Ok(image) => image,
+        Err(arboard::Error::ContentNotAvailable) => return Ok(None),
+        Err(err) => return Err(format!("Failed to read the clipboard image: {err}")),
+    };
+    let png = encode_png(
+        image.width as u32,
+        image.height as u32,
+        image.bytes.into_owned(),
+    )?;
+    let name = format!(
+        "clipboard-{}.png",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let attachment = store.store_bytes(&png, &name, "image/png")?;
+    Ok(Some((attachment, png)))
+}
+
+/// Saves the image currently on the clipboard as a PNG attachment. The webview only
+/// sees text on paste, so screenshots have to be read natively.
+#[tauri::command]
+pub async fn paste_image_from_clipboard(
+    store: tauri::State<'_, AttachmentStore>,
+) -> Result<AttachmentHandle, AppError> {
+    store_image(&mut open()?, &store)?
+        .map(|(attachment, _)| attachment)
+        .ok_or_else(|| AppError::not_found("Clipboard does not contain an image."))
+}
+
+/// Puts a message on the clipboard. With `html`, rich editors get the formatting
+/// and `text` is the plain fallback.
+#[tauri::command]
+pub async fn copy_message_to_clipboard(text: String, html: Option<String>) -> Result<(), AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut clipboard = open()?;
+        match html.filter(|html| !html.trim().is_empty()) {
+            Some(html) => clipboard.set_html(html, Some(text)),
+            None => clipboard.set_text(text),
+        }
+        .map_err(|err| format!("Failed to write to the clipboard: {err}"))
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Clipboard task failed: {err}")))?
+    .map_err(AppError::from)
+}
+
+/// What a paste into the composer should insert: the clipboard text and, for a
+/// screenshot, the image already registered with the backend. Saves the webview
+/// from asking for clipboard permission.
+#[tauri::command]
+pub async fn read_clipboard_for_prompt(app: AppHandle) -> Result<ClipboardContent, AppError> {
+    let handle = app.clone();
+    let (text, image) = tauri::async_runtime::spawn_blocking(move || -> Result<_, String> {
+        let mut clipboard = open()?;
+        let image = store_image(&mut clipboard, &handle.state::<AttachmentStore>())?;
+        let text = clipboard.get_text().ok().filter(|text| !text.is_empty());
+        Ok((text, image))
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Clipboard task failed: {err}")))??;
+    let image = match image {
+        Some((attachment, png)) => {
+            if let Some(reason) = &attachment.quarantine {
+                return Err(AppError::invalid_input(format!(
+                    "The clipboard image was quarantined: {reason}"
+                )));
+            }
+            let base_url = app
+                .try_state::<BackendState>()
+                .map(|state| state.base_url())
+                .ok_or_else(|| AppError::unavailable("Backend is not available."))?;
+            let upload_id = file_drop::upload(&proxy::client(&app), &base_url, &attachment, png)
+                .await
+                .map_err(AppError::from)?;
+            Some(PastedImage {
+                attachment,
+                upload_id,
+            })
+        }
+        None => None,
+    };
+    Ok(ClipboardContent { text, image })
+}
+
+pub fn encode_png(width: u32, height: u32, rgba: Vec<u8>) -> Result<Vec<u8>, String> {
+    let image = RgbaImage::from_raw(width, height, rgba)
+        .ok_or_else(|| "Clipboard image has an unexpected size.".to_string())?;
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|err| format!("Failed to encode PNG: {err}"))?;
+    Ok(png.into_inner())
+}
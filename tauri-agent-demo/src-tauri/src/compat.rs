@@ -0,0 +1,158 @@
+use std::{sync::Mutex, time::Duration};
+
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{events, proxy, rpc, BackendState};
+
+const VERSION_PATH: &str = "/version";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// The backend API versions this shell was built against; bump alongside
+/// `API_VERSION` in the backend.
+const MIN_API: u32 = 1;
+const MAX_API: u32 = 1;
+/// Older APIs the shell still runs against, with the features they lack turned
+/// off. 0 stands for backends from before `/version` existed.
+const SHIMMED_APIS: &[u32] = &[0];
+
+events::payload_enum! {
+    pub enum Compatibility {
+        Compatible,
+        /// Outside the range but known; the webview hides what the backend cannot do.
+        Shimmed,
+        Incompatible,
+    }
+}
+
+events::payload! {
+    pub struct VersionReport {
+        /// The backend's own version; absent from backends that predate `/version`.
+        version: Option<String>,
+        api: u32,
+        min_api: u32,
+        max_api: u32,
+        compatibility: Compatibility,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionResponse {
+    version: Option<String>,
+    api: u32,
+}
+
+/// The result of the last handshake; `None` until one has succeeded.
+#[derive(Default)]
+pub struct BackendVersion(Mutex<Option<VersionReport>>);
+
+/// `None` for a backend that answers 404, i.e. one from before `/version`.
+async fn fetch<R: Runtime>(app: &AppHandle<R>) -> Result<Option<VersionResponse>, String> {
+    let raw: Value = if rpc::is_attached(app) {
+        let handle = app.clone();
+        let (status, body) = tauri::async_runtime::spawn_blocking(move || {
+            rpc::get(&handle, VERSION_PATH, REQUEST_TIMEOUT)
+        })
+        .await
+        .map_err(|err| format!("Version check task failed: {err}"))??;
+        match status {
+            200 => {}
+            404 => return Ok(None),
+            _ => return Err(format!("Backend answered {VERSION_PATH} with {status}.")),
+        }
+        serde_json::from_slice(&body).map_err(|err| format!("Unreadable version: {err}"))?
+    } else {
+        let base_url = app
+            .try_state::<BackendState>()
+            .map(|state| state.base_url())
+            .ok_or_else(|| "Backend is not configured yet.".to_string())?;
+        let response = proxy::client(app)
+            .get(format!("{base_url}{VERSION_PATH}"))
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|err| format!("Version check failed: {err}"))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        response
+            .error_for_status()
+            .map_err(|err| format!("Version check failed: {err}"))?
+            .json()
+            .await
+            .map_err(|err| format!("Unreadable version: {err}"))?
+    };
+    serde_json::from_value(raw)
+        .map(Some)
+        .map_err(|err| format!("Unreadable version: {err}"))
+}
+
+fn compatibility(api: u32) -> Compatibility {
+    if (MIN_API..=MAX_API).contains(&api) {
+        Compatibility::Compatible
+    } else if SHIMMED_APIS.contains(&api) {
+        Compatibility::Shimmed
+    } else {
+        Compatibility::Incompatible
+    }
+}
+
+impl VersionReport {
+    /// Why the shell cannot work with this backend, for the user.
+    pub fn refusal(&self) -> Option<String> {
+        if self.compatibility != Compatibility::Incompatible {
+            return None;
+        }
+        let advice = if self.api < MIN_API {
+            "Update the backend to match the app."
+        } else {
+            "Update the app to match the backend."
+        };
+        Some(format!(
+            "The backend speaks API version {}, but this app supports {MIN_API} to {MAX_API}. {advice}",
+            self.api
+        ))
+    }
+}
+
+/// Asks the freshly started backend for its API version and records the verdict.
+/// Errs only when the backend could not be asked.
+pub async fn check<R: Runtime>(app: &AppHandle<R>) -> Result<VersionReport, String> {
+    let (version, api) = match fetch(app).await? {
+        Some(response) => (response.version, response.api),
+        None => (None, 0),
+    };
+    let report = VersionReport {
+        version,
+        api,
+        min_api: MIN_API,
+        max_api: MAX_API,
+        compatibility: compatibility(api),
+    };
+    match report.compatibility {
+        Compatibility::Compatible => tracing::info!("[Compat] Backend API {api} is supported."),
+        Compatibility::Shimmed => tracing::warn!(
+            "[Compat] Backend API {api} is older than {MIN_API}; running with a compatibility shim."
+        ),
+        Compatibility::Incompatible => tracing::error!(
+            "[Compat] Backend API {api} is outside the supported range {MIN_API}-{MAX_API}."
+        ),
+    }
+    if let Some(state) = app.try_state::<BackendVersion>() {
+        if let Ok(mut current) = state.0.lock() {
+            *current = Some(report.clone());
+        }
+    }
+    events::backend_version(app, &report);
+    Ok(report)
+}
+
+pub fn report<R: Runtime>(app: &AppHandle<R>) -> Option<VersionReport> {
+    app.try_state::<BackendVersion>()
+        .and_then(|state| state.0.lock().ok().and_then(|report| report.clone()))
+}
+
+#[tauri::command]
+pub fn backend_version(app: AppHandle) -> Option<VersionReport> {
+    report(&app)
+}
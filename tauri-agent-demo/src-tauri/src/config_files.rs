@@ -0,0 +1,352 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use notify_debouncer_full::{
+    new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+    DebounceEventResult, Debouncer, RecommendedCache,
+};
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{atomic_file, error::AppError, events, proxy, workspace, BackendState};
+
+/// Errors reported back per write; the first few are enough to fix the file.
+const MAX_REPORTED_ERRORS: usize = 5;
+
+events::payload_enum! {
+    /// The two backend config files under `app_data_dir`, passed to the sidecar as
+    /// `APP_CONFIG_PATH` and `TOOLS_CONFIG_PATH`.
+    #[derive(Hash)]
+    pub enum ConfigFile {
+        App,
+        Tools,
+    }
+}
+
+impl ConfigFile {
+    const ALL: [Self; 2] = [Self::App, Self::Tools];
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::App => "app_config.json",
+            Self::Tools => "tools_config.json",
+        }
+    }
+
+    /// The backend route whose PUT merges a patch into the file and reloads it.
+    fn reload_path(self) -> &'static str {
+        match self {
+            Self::App => "/app/config",
+            Self::Tools => "/tools/config",
+        }
+    }
+
+    /// Both files are partial overrides of the backend's defaults, so every key is
+    /// optional; only the shape of the keys that are present is checked.
+    fn schema(self) -> Value {
+        let positive = json!({ "type": "number", "exclusiveMinimum": 0 });
+        let string_list = json!({ "type": "array", "items": { "type": "string" } });
+        match self {
+            Self::App => json!({
+                "type": "object",
+                "properties": {
+                    "llm": {
+                        "type": "object",
+                        "properties": {
+                            "timeout_sec": positive,
+                            "reasoning_summary": { "type": "string" },
+                            "auto_title_enabled": { "type": "boolean" }
+                        }
+                    },
+                    "context": {
+                        "type": "object",
+                        "properties": {
+                            "compression_enabled": { "type": "boolean" },
+                            "compress_start_pct": { "type": "number", "minimum": 0, "maximum": 100 },
+                            "compress_target_pct": { "type": "number", "minimum": 0, "maximum": 100 },
+                            "min_keep_messages": { "type": "integer", "minimum": 0 },
+                            "truncate_long_data": { "type": "boolean" }
+                        }
+                    },
+                    "agent": {
+                        "type": "object",
+                        "properties": {
+                            "base_system_prompt": { "type": "string" },
+                            "react_max_iterations": { "type": "integer", "minimum": 1 },
+                            "default_profile": { "type": "string" },
+                            "subagent_profile": { "type": "string" },
+                            "abilities": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["id"],
+                                    "properties": {
+                                        "id": { "type": "string", "minLength": 1 },
+                                        "tools": string_list
+                                    }
+                                }
+                            },
+                            "profiles": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["id"],
+                                    "properties": {
+                                        "id": { "type": "string", "minLength": 1 },
+                                        "abilities": string_list,
+                                        "spawnable": { "type": "boolean" }
+                                    }
+                                }
+                            },
+                            "mcp": {
+                                "type": "object",
+                                "properties": {
+                                    "servers": { "type": "array", "items": { "type": "object" } }
+                                }
+                            }
+                        }
+                    }
+                }
+            }),
+            Self::Tools => json!({
+                "type": "object",
+                "properties": {
+                    "enabled": {
+                        "type": "object",
+                        "additionalProperties": { "type": "boolean" }
+                    },
+                    "files": {
+                        "type": "object",
+                        "properties": { "max_bytes": positive }
+                    },
+                    "shell": {
+                        "type": "object",
+                        "properties": {
+                            "allowlist": string_list,
+                            "unrestricted_allowlist": string_list,
+                            "timeout_sec": positive,
+                            "max_output": positive
+                        }
+                    },
+                    "search": {
+                        "type": "object",
+                        "properties": {
+                            "provider": { "type": "string" },
+                            "max_results": { "type": "integer", "minimum": 1 }
+                        }
+                    },
+                    "ast": { "type": "object" },
+                    "plugins": { "type": "object" },
+                    "project_root": { "type": "string" }
+                }
+            }),
+        }
+    }
+}
+
+/// Watches the config files for edits from outside the shell.
+#[derive(Default)]
+pub struct ConfigWatcher {
+    debouncer: Mutex<Option<Debouncer<RecommendedWatcher, RecommendedCache>>>,
+    /// What the shell last wrote to each file, so its own writes are not announced.
+    written: Mutex<HashMap<ConfigFile, Vec<u8>>>,
+}
+
+events::payload! {
+    pub struct ConfigChanged {
+        file: ConfigFile,
+        path: PathBuf,
+    }
+}
+
+fn config_path<R: Runtime>(app: &AppHandle<R>, file: ConfigFile) -> Result<PathBuf, AppError> {
+    Ok(workspace::data_dir(app)?.join(file.file_name()))
+}
+
+/// The folder the agent's tools work in, when `tools_config.json` names one.
+pub fn project_root<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    let config = read(
+        &config_path(app, ConfigFile::Tools).ok()?,
+        ConfigFile::Tools,
+    )
+    .ok()?;
+    config
+        .get("project_root")
+        .and_then(Value::as_str)
+        .filter(|root| !root.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+fn validate(file: ConfigFile, config: &Value) -> Result<(), AppError> {
+    let validator = jsonschema::validator_for(&file.schema()).map_err(|err| {
+        AppError::from(format!("The {} schema is invalid: {err}", file.file_name()))
+    })?;
+    let errors: Vec<String> = validator
+        .iter_errors(config)
+        .take(MAX_REPORTED_ERRORS)
+        .map(|err| {
+            let path = err.instance_path().to_string();
+            if path.is_empty() {
+                err.to_string()
+            } else {
+                format!("{path}: {err}")
+            }
+        })
+        .collect();
+    if errors.is_empty() {
+        return Ok(());
+    }
+    Err(
+        AppError::invalid_input(format!("{} does not match its schema.", file.file_name()))
+            .with_details(errors.join("\n")),
+    )
+}
+
+fn read(path: &Path, file: ConfigFile) -> Result<Value, AppError> {
+    match fs::read_to_string(path) {
+        Ok(raw) => serde_json::from_str(&raw).map_err(|err| {
+            AppError::invalid_input(format!("Failed to parse {}.", file.file_name()))
+                .with_details(err)
+        }),
+        // A missing file means the backend runs on its defaults.
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Value::Object(Map::new())),
+        Err(err) => {
+            Err(AppError::from(format!("Failed to read {}.", file.file_name())).with_details(err))
+        }
+    }
+}
+
+fn remember<R: Runtime>(app: &AppHandle<R>, file: ConfigFile, contents: Vec<u8>) {
+    if let Some(watcher) = app.try_state::<ConfigWatcher>() {
+        if let Ok(mut written) = watcher.written.lock() {
+            written.insert(file, contents);
+        }
+    }
+}
+
+async fn write<R: Runtime>(
+    app: &AppHandle<R>,
+    file: ConfigFile,
+    config: Value,
+) -> Result<Value, AppError> {
+    validate(file, &config)?;
+    let path = config_path(app, file)?;
+    let raw = serde_json::to_string_pretty(&config).map_err(|err| {
+        AppError::from(format!("Failed to serialize {}.", file.file_name())).with_details(err)
+    })?;
+    remember(app, file, raw.clone().into_bytes());
+    atomic_file::write(&path, &raw).map_err(|err| {
+        AppError::from(format!("Failed to write {}.", file.file_name())).with_details(err)
+    })?;
+    if let Some(base_url) = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+    {
+        // An empty patch makes the backend re-read the file, which it then saves
+        // back normalized; that rewrite is ours too.
+        let reloaded = proxy::client(app)
+            .put(format!("{base_url}{}", file.reload_path()))
+            .json(&json!({}))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        match reloaded {
+            Ok(_) => {
+                if let Ok(saved) = fs::read(&path) {
+                    remember(app, file, saved);
+                }
+            }
+            Err(err) => tracing::warn!(
+                "[Config] Backend did not reload {}: {err}",
+                file.file_name()
+            ),
+        }
+    }
+    Ok(config)
+}
+
+fn handle_events<R: Runtime>(app: &AppHandle<R>, dir: &Path, result: DebounceEventResult) {
+    let events = match result {
+        Ok(events) => events,
+        Err(errors) => {
+            for err in errors {
+                tracing::warn!("[Config] Watch error: {err}");
+            }
+            return;
+        }
+    };
+    for file in ConfigFile::ALL {
+        // Compared by name, since some platforms report canonicalized paths.
+        let touched = events.iter().any(|event| {
+            event.paths.iter().any(|path| {
+                path.file_name()
+                    .is_some_and(|name| name == file.file_name())
+            })
+        });
+        if !touched {
+            continue;
+        }
+        let path = dir.join(file.file_name());
+        let current = fs::read(&path).ok();
+        let own_write = app.try_state::<ConfigWatcher>().is_some_and(|watcher| {
+            watcher
+                .written
+                .lock()
+                .is_ok_and(|written| written.get(&file) == current.as_ref())
+        });
+        if !own_write {
+            tracing::info!("[Config] {} changed on disk.", file.file_name());
+            events::config_changed(app, &ConfigChanged { file, path });
+        }
+    }
+}
+
+/// Starts watching `app_data_dir` for changes to the config files.
+pub fn watch<R: Runtime>(app: &AppHandle<R>, dir: PathBuf) -> Result<(), String> {
+    let state = app.state::<ConfigWatcher>();
+    let mut guard = state
+        .debouncer
+        .lock()
+        .map_err(|_| "Config watcher is unavailable.".to_string())?;
+    let handle = app.clone();
+    let watched = dir.clone();
+    let mut debouncer = new_debouncer(Duration::from_secs(1), None, move |result| {
+        handle_events(&handle, &watched, result)
+    })
+    .map_err(|err| format!("Failed to start the config watcher: {err}"))?;
+    // The directory rather than the files, since atomic writes replace them.
+    debouncer
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|err| format!("Failed to watch {}: {err}", dir.display()))?;
+    *guard = Some(debouncer);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn read_app_config(app: AppHandle) -> Result<Value, AppError> {
+    read(&config_path(&app, ConfigFile::App)?, ConfigFile::App)
+}
+
+/// Replaces `app_config.json` after checking it against the schema, then has the
+/// backend reload it.
+#[tauri::command]
+pub async fn write_app_config(app: AppHandle, config: Value) -> Result<Value, AppError> {
+    write(&app, ConfigFile::App, config).await
+}
+
+#[tauri::command]
+pub fn read_tools_config(app: AppHandle) -> Result<Value, AppError> {
+    read(&config_path(&app, ConfigFile::Tools)?, ConfigFile::Tools)
+}
+
+/// Like `write_app_config`, for `tools_config.json`.
+#[tauri::command]
+pub async fn write_tools_config(app: AppHandle, config: Value) -> Result<Value, AppError> {
+    write(&app, ConfigFile::Tools, config).await
+}
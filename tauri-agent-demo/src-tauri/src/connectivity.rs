@@ -0,0 +1,247 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::Local;
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{error::AppError, events, health, idle, proxy, proxy_config, rpc, BackendState};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Used instead while the user is away.
+const AWAY_CHECK_INTERVAL: Duration = Duration::from_secs(120);
+const CHECK_TIMEOUT: Duration = Duration::from_secs(8);
+const CONFIG_PATH: &str = "/configs/default";
+
+events::payload! {
+    /// One target's result from the latest check.
+    #[derive(Default)]
+    pub struct Reachability {
+        /// `None` when there was nothing to check, e.g. no LLM is configured yet.
+        reachable: Option<bool>,
+        latency_ms: Option<u64>,
+        error: Option<String>,
+    }
+}
+
+impl Reachability {
+    fn up(latency: Duration) -> Self {
+        Self {
+            reachable: Some(true),
+            latency_ms: Some(latency.as_millis() as u64),
+            error: None,
+        }
+    }
+
+    fn down(error: String) -> Self {
+        Self {
+            reachable: Some(false),
+            latency_ms: None,
+            error: Some(error),
+        }
+    }
+
+    fn is_down(&self) -> bool {
+        self.reachable == Some(false)
+    }
+}
+
+events::payload! {
+    pub struct ConnectivityStatus {
+        /// False when the backend or the LLM endpoint cannot be reached.
+        online: bool,
+        backend: Reachability,
+        llm: Reachability,
+        /// Base URL of the default LLM config, as the backend would call it.
+        llm_endpoint: Option<String>,
+        checked_at: String,
+    }
+}
+
+/// The latest check, shared by the watcher and `connectivity_status`.
+#[derive(Default)]
+pub struct Connectivity(Mutex<Option<ConnectivityStatus>>);
+
+/// The fields of `/configs/default` that decide where the backend sends LLM calls.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct LlmConfig {
+    base_url: Option<String>,
+    api_profile: Option<String>,
+}
+
+impl LlmConfig {
+    /// Mirrors `LLMClient._get_base_url` in the backend.
+    fn endpoint(self) -> String {
+        if let Some(url) = self.base_url.filter(|url| !url.trim().is_empty()) {
+            return url.trim().to_string();
+        }
+        match self.api_profile.as_deref() {
+            Some("deepseek") => "https://api.deepseek.com/v1",
+            Some("zhipu") => "https://open.bigmodel.cn/api/paas/v4",
+            _ => "https://api.openai.com/v1",
+        }
+        .to_string()
+    }
+}
+
+/// The default config's endpoint; `Ok(None)` when no LLM is configured yet.
+async fn llm_endpoint<R: Runtime>(app: &AppHandle<R>) -> Result<Option<String>, String> {
+    let (status, body) = if rpc::is_attached(app) {
+        let handle = app.clone();
+        tauri::async_runtime::spawn_blocking(move || rpc::get(&handle, CONFIG_PATH, CHECK_TIMEOUT))
+            .await
+            .map_err(|err| format!("Config lookup task failed: {err}"))??
+    } else {
+        let base_url = app
+            .try_state::<BackendState>()
+            .map(|state| state.base_url())
+            .ok_or_else(|| "Backend is not configured yet.".to_string())?;
+        let response = proxy::client(app)
+            .get(format!("{base_url}{CONFIG_PATH}"))
+            .timeout(CHECK_TIMEOUT)
+            .send()
+            .await
+            .map_err(|err| format!("Config lookup failed: {err}"))?;
+        let status = response.status().as_u16();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|err| format!("Config lookup failed: {err}"))?;
+        (status, body.to_vec())
+    };
+    match status {
+        200 => {}
+        404 => return Ok(None),
+        _ => return Err(format!("Backend answered {CONFIG_PATH} with {status}.")),
+    }
+    let config: LlmConfig =
+        serde_json::from_slice(&body).map_err(|err| format!("Unreadable LLM config: {err}"))?;
+    Ok(Some(config.endpoint()))
+}
+
+/// Any HTTP answer counts: a 401 still proves the provider can be reached.
+async fn probe_endpoint<R: Runtime>(app: &AppHandle<R>, endpoint: &str) -> Reachability {
+    let settings = proxy_config::saved(app);
+    let proxy = tauri::async_runtime::spawn_blocking({
+        let settings = settings.clone();
+        move || proxy_config::effective_url(&settings)
+    })
+    .await
+    .unwrap_or_default();
+    let client = match proxy_config::client(&settings, proxy.as_deref(), CHECK_TIMEOUT) {
+        Ok(client) => client,
+        Err(err) => return Reachability::down(err),
+    };
+    let started = Instant::now();
+    match client.get(endpoint).send().await {
+        Ok(_) => Reachability::up(started.elapsed()),
+        Err(err) => Reachability::down(format!("{endpoint} is unreachable: {err}")),
+    }
+}
+
+async fn check<R: Runtime>(app: &AppHandle<R>) -> ConnectivityStatus {
+    let started = Instant::now();
+    let backend = if health::probe(app).await {
+        Reachability::up(started.elapsed())
+    } else {
+        Reachability::down("The backend is not answering.".to_string())
+    };
+    // Without the backend there is no config to read; keep the last known endpoint.
+    let previous = app
+        .try_state::<Connectivity>()
+        .and_then(|state| state.0.lock().ok()?.as_ref()?.llm_endpoint.clone());
+    let (llm_endpoint, lookup_error) = if backend.is_down() {
+        (previous, None)
+    } else {
+        match llm_endpoint(app).await {
+            Ok(endpoint) => (endpoint, None),
+            Err(err) => (previous, Some(err)),
+        }
+    };
+    let llm = match (&llm_endpoint, lookup_error) {
+        (Some(endpoint), _) => probe_endpoint(app, endpoint).await,
+        (None, Some(err)) => Reachability {
+            error: Some(err),
+            ..Reachability::default()
+        },
+        (None, None) => Reachability::default(),
+    };
+    ConnectivityStatus {
+        online: !backend.is_down() && !llm.is_down(),
+        backend,
+        llm,
+        llm_endpoint,
+        checked_at: Local::now().to_rfc3339(),
+    }
+}
+
+/// Stores `status` and emits `net://online` or `net://offline` when it flips.
+fn record<R: Runtime>(app: &AppHandle<R>, status: ConnectivityStatus) {
+    let Some(state) = app.try_state::<Connectivity>() else {
+        return;
+    };
+    let Ok(mut latest) = state.0.lock() else {
+        return;
+    };
+    // The app starts out assumed online, so only a first check that fails is news.
+    let was_online = latest.as_ref().is_none_or(|previous| previous.online);
+    *latest = Some(status.clone());
+    drop(latest);
+    if was_online == status.online {
+        return;
+    }
+    if status.online {
+        tracing::info!("[Connectivity] Back online.");
+        events::net_online(app, &status);
+    } else {
+        let reason = status
+            .backend
+            .error
+            .as_deref()
+            .or(status.llm.error.as_deref())
+            .unwrap_or("unknown");
+        tracing::warn!("[Connectivity] Offline: {reason}");
+        events::net_offline(app, &status);
+    }
+}
+
+/// Checks the backend and the LLM endpoint for as long as the app runs, less
+/// often while the user is away.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(if idle::is_away(&app) {
+                AWAY_CHECK_INTERVAL
+            } else {
+                CHECK_INTERVAL
+            })
+            .await;
+            let status = check(&app).await;
+            record(&app, status);
+        }
+    });
+}
+
+/// The latest check, or a fresh one when `refresh` is set or none has run yet.
+#[tauri::command]
+pub async fn connectivity_status(
+    app: AppHandle,
+    refresh: Option<bool>,
+) -> Result<ConnectivityStatus, AppError> {
+    let cached = app
+        .state::<Connectivity>()
+        .0
+        .lock()
+        .map_err(|_| AppError::unavailable("Connectivity status is unavailable."))?
+        .clone();
+    if let Some(status) = cached.filter(|_| !refresh.unwrap_or(false)) {
+        return Ok(status);
+    }
+    let status = check(&app).await;
+    record(&app, status.clone());
+    Ok(status)
+}
@@ -0,0 +1,70 @@
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{error::AppError, monitors};
+
+/// Detached conversations get windows labelled `conversation-<id>`, so each has at
+/// most one and its geometry is remembered under that label.
+pub const LABEL_PREFIX: &str = "conversation-";
+const WIDTH: f64 = 900.0;
+const HEIGHT: f64 = 760.0;
+
+pub fn is_conversation_window(label: &str) -> bool {
+    label.starts_with(LABEL_PREFIX)
+}
+
+fn label_for(conversation_id: &str) -> Result<String, AppError> {
+    let valid = !conversation_id.is_empty()
+        && conversation_id.len() <= 128
+        && conversation_id
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_');
+    if !valid {
+        return Err(AppError::invalid_input(format!(
+            "'{conversation_id}' is not a conversation id."
+        )));
+    }
+    Ok(format!("{LABEL_PREFIX}{conversation_id}"))
+}
+
+/// Remembers where every detached window is, for when the app exits with them open.
+pub fn save_geometry<R: Runtime>(app: &AppHandle<R>) {
+    for label in app.webview_windows().into_keys() {
+        if is_conversation_window(&label) {
+            monitors::save_geometry(app, &label);
+        }
+    }
+}
+
+/// Opens `conversation_id` in a window of its own, or brings its window forward if
+/// it already has one. Returns the window's label.
+#[tauri::command]
+pub fn open_conversation_window(
+    app: AppHandle,
+    conversation_id: String,
+) -> Result<String, AppError> {
+    let label = label_for(&conversation_id)?;
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(label);
+    }
+    let window = WebviewWindowBuilder::new(
+        &app,
+        &label,
+        WebviewUrl::App(format!("index.html?window=conversation&session={conversation_id}").into()),
+    )
+    .title("GYY")
+    .inner_size(WIDTH, HEIGHT)
+    .min_inner_size(480.0, 360.0)
+    .decorations(false)
+    .visible(false)
+    .build()
+    .map_err(|err| AppError::from(format!("Failed to open the conversation window: {err}")))?;
+    if let Err(err) = monitors::restore_geometry(&app, &label) {
+        tracing::warn!("[Windows] {err}");
+    }
+    let _ = window.show();
+    let _ = window.set_focus();
+    Ok(label)
+}
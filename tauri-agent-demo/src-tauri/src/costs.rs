@@ -0,0 +1,440 @@
+use std::{collections::BTreeMap, path::PathBuf, time::Duration};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    atomic_file,
+    error::AppError,
+    events, locale,
+    notifications::{self, NotificationKind},
+    proxy, rpc, sessions,
+    settings::SettingsStore,
+    usage::{UsageRange, UsageRow, UsageStore},
+    BackendState,
+};
+
+/// Set for a backend spawned while the hard stop is on, so it refuses LLM calls
+/// from its first request; later changes arrive on `BUDGET_PATH`.
+pub const BUDGET_PAUSED_ENV: &str = "TAURI_AGENT_BUDGET_PAUSED";
+const BUDGET_PATH: &str = "/system/budget";
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Price of one million tokens for models whose name starts with `model`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub provider: String,
+    pub model: String,
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CostSettings {
+    pub monthly_budget_usd: Option<f64>,
+    /// Percentages of the budget that trigger a `budget-threshold` event.
+    pub alert_thresholds: Vec<u8>,
+    /// Pause outbound LLM traffic once the budget is fully spent.
+    pub hard_stop: bool,
+    pub pricing: Vec<ModelPrice>,
+    /// The month (`YYYY-MM`) the hard stop paused LLM calls in; a new month or
+    /// `resume_after_budget_stop` lifts it.
+    pub paused_month: Option<String>,
+}
+
+impl Default for CostSettings {
+    fn default() -> Self {
+        Self {
+            monthly_budget_usd: None,
+            alert_thresholds: vec![50, 80, 100],
+            hard_stop: false,
+            pricing: Vec::new(),
+            paused_month: None,
+        }
+    }
+}
+
+impl CostSettings {
+    fn price_for(&self, model: &str) -> Option<&ModelPrice> {
+        self.pricing
+            .iter()
+            .filter(|price| model.starts_with(price.model.as_str()))
+            .max_by_key(|price| price.model.len())
+    }
+
+    /// What the tokens cost in USD, or `None` when the model has no price.
+    fn cost(&self, model: &str, prompt_tokens: u64, completion_tokens: u64) -> Option<f64> {
+        self.price_for(model).map(|price| {
+            (prompt_tokens as f64 * price.input_per_million
+                + completion_tokens as f64 * price.output_per_million)
+                / 1_000_000.0
+        })
+    }
+}
+
+fn current_month() -> String {
+    Local::now().format("%Y-%m").to_string()
+}
+
+/// Whether the hard stop holds LLM calls back right now.
+pub fn is_paused<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.try_state::<SettingsStore>().is_some_and(|store| {
+        store.get().costs.paused_month.as_deref() == Some(current_month().as_str())
+    })
+}
+
+/// Whether `method` and `path` start an LLM call the hard stop must refuse.
+pub fn is_llm_call(method: &str, path: &str) -> bool {
+    let path = path.split('?').next().unwrap_or(path);
+    method == "POST" && path.starts_with("/chat") && path != "/chat/stop"
+}
+
+async fn post_budget(
+    client: reqwest::Client,
+    base_url: String,
+    body: serde_json::Value,
+) -> Result<(), String> {
+    client
+        .post(format!("{base_url}{BUDGET_PATH}"))
+        .timeout(NOTIFY_TIMEOUT)
+        .json(&body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|err| format!("Failed to tell the backend about the budget: {err}"))
+}
+
+/// Tells the main backend and every session worker whether to refuse LLM calls,
+/// so requests that skip the proxy are held back too.
+async fn notify_backends<R: Runtime>(app: &AppHandle<R>, paused: bool) -> Result<(), String> {
+    let body = json!({ "paused": paused });
+    for (base_url, token) in sessions::endpoints(app) {
+        post_budget(
+            proxy::client_with_token(Some(&token)),
+            base_url,
+            body.clone(),
+        )
+        .await?;
+    }
+    if rpc::is_attached(app) {
+        let handle = app.clone();
+        let (status, _) = tauri::async_runtime::spawn_blocking(move || {
+            rpc::post(&handle, BUDGET_PATH, &body, NOTIFY_TIMEOUT)
+        })
+        .await
+        .map_err(|err| format!("Budget notify task failed: {err}"))??;
+        return match status {
+            200..=299 => Ok(()),
+            _ => Err(format!("Backend answered {BUDGET_PATH} with {status}.")),
+        };
+    }
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| "Backend is not configured yet.".to_string())?;
+    post_budget(proxy::client(app), base_url, body).await
+}
+
+fn spawn_notify<R: Runtime>(app: &AppHandle<R>, paused: bool) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = notify_backends(&app, paused).await {
+            tracing::warn!("[Costs] {err}");
+        }
+    });
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelCost {
+    model: String,
+    cost_usd: f64,
+    priced: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CostSummary {
+    month: String,
+    cost_usd: f64,
+    budget_usd: Option<f64>,
+    percent_used: Option<f64>,
+    paused: bool,
+    by_model: Vec<ModelCost>,
+}
+
+/// Tokens and spend for one day or model of a usage summary.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageCost {
+    key: String,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    total_tokens: u64,
+    requests: u64,
+    cost_usd: f64,
+    /// Every model in it has a price; otherwise `cost_usd` leaves some out.
+    priced: bool,
+}
+
+impl UsageCost {
+    fn add(&mut self, row: &UsageRow, cost: Option<f64>) {
+        if self.requests == 0 {
+            self.priced = true;
+        }
+        self.prompt_tokens += row.prompt_tokens;
+        self.completion_tokens += row.completion_tokens;
+        self.total_tokens += row.total_tokens;
+        self.requests += row.requests;
+        self.cost_usd += cost.unwrap_or(0.0);
+        self.priced &= cost.is_some();
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageSummary {
+    total: UsageCost,
+    by_day: Vec<UsageCost>,
+    by_model: Vec<UsageCost>,
+}
+
+events::payload! {
+    pub struct BudgetThresholdEvent {
+        month: String,
+        threshold: u8,
+        cost_usd: f64,
+        budget_usd: f64,
+        paused: bool,
+    }
+}
+
+fn summarize(
+    usage: &UsageStore,
+    settings: &CostSettings,
+    paused: bool,
+) -> Result<CostSummary, String> {
+    let month = current_month();
+    let mut by_model = Vec::new();
+    let mut total = 0.0;
+    for (model, prompt_tokens, completion_tokens) in usage.month_tokens_by_model(&month)? {
+        let cost = settings.cost(&model, prompt_tokens, completion_tokens);
+        total += cost.unwrap_or(0.0);
+        by_model.push(ModelCost {
+            model,
+            cost_usd: cost.unwrap_or(0.0),
+            priced: cost.is_some(),
+        });
+    }
+    let budget = settings.monthly_budget_usd.filter(|budget| *budget > 0.0);
+    Ok(CostSummary {
+        month,
+        cost_usd: total,
+        budget_usd: budget,
+        percent_used: budget.map(|budget| total / budget * 100.0),
+        paused,
+        by_model,
+    })
+}
+
+/// Recomputes this month's spend and, for each newly crossed threshold, emits
+/// `budget-threshold` and shows a notification. Called after new usage is metered.
+pub fn check_budget<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let (Some(store), Some(usage)) = (
+        app.try_state::<SettingsStore>(),
+        app.try_state::<UsageStore>(),
+    ) else {
+        return Ok(());
+    };
+    let settings = store.get().costs;
+    let summary = summarize(&usage, &settings, is_paused(app))?;
+    let (Some(budget), Some(percent)) = (summary.budget_usd, summary.percent_used) else {
+        return Ok(());
+    };
+    let mut thresholds = settings.alert_thresholds.clone();
+    thresholds.sort_unstable();
+    for threshold in thresholds {
+        if percent < threshold as f64 || !usage.mark_budget_alert(&summary.month, threshold)? {
+            continue;
+        }
+        let pause = settings.hard_stop && threshold >= 100;
+        if pause {
+            store.update(|current| current.costs.paused_month = Some(summary.month.clone()))?;
+            spawn_notify(app, true);
+            tracing::warn!("[Costs] Monthly budget exhausted; pausing outbound LLM calls.");
+        }
+        events::budget_threshold(
+            app,
+            &BudgetThresholdEvent {
+                month: summary.month.clone(),
+                threshold,
+                cost_usd: summary.cost_usd,
+                budget_usd: budget,
+                paused: pause,
+            },
+        );
+        let spent = format!("{:.2}", summary.cost_usd);
+        let budget = format!("{budget:.2}");
+        let body = locale::t_with(
+            app,
+            if pause {
+                "notification.budget_paused"
+            } else {
+                "notification.budget_body"
+            },
+            &[("spent", &spent), ("budget", &budget)],
+        );
+        if let Err(err) = notifications::notify(
+            app,
+            NotificationKind::Budget,
+            &locale::t_with(
+                app,
+                "notification.budget_title",
+                &[("percent", &threshold.to_string())],
+            ),
+            &body,
+        ) {
+            tracing::warn!("[Notifications] {err}");
+        }
+    }
+    Ok(())
+}
+
+fn summarize_range(rows: &[UsageRow], settings: &CostSettings) -> UsageSummary {
+    let mut total = UsageCost::default();
+    let mut by_day: BTreeMap<&str, UsageCost> = BTreeMap::new();
+    let mut by_model: BTreeMap<&str, UsageCost> = BTreeMap::new();
+    for row in rows {
+        let cost = settings.cost(&row.model, row.prompt_tokens, row.completion_tokens);
+        total.add(row, cost);
+        by_day.entry(&row.day).or_default().add(row, cost);
+        by_model.entry(&row.model).or_default().add(row, cost);
+    }
+    let keyed = |buckets: BTreeMap<&str, UsageCost>| {
+        buckets
+            .into_iter()
+            .map(|(key, bucket)| UsageCost {
+                key: key.to_string(),
+                ..bucket
+            })
+            .collect()
+    };
+    UsageSummary {
+        total,
+        by_day: keyed(by_day),
+        by_model: keyed(by_model),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn usage_csv(rows: &[UsageRow], settings: &CostSettings) -> String {
+    let mut csv = String::from(
+        "day,conversation_id,model,prompt_tokens,completion_tokens,total_tokens,requests,cost_usd\n",
+    );
+    for row in rows {
+        let cost = settings
+            .cost(&row.model, row.prompt_tokens, row.completion_tokens)
+            .map(|cost| format!("{cost:.6}"))
+            .unwrap_or_default();
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{cost}\n",
+            row.day,
+            csv_field(&row.conversation_id),
+            csv_field(&row.model),
+            row.prompt_tokens,
+            row.completion_tokens,
+            row.total_tokens,
+            row.requests,
+        ));
+    }
+    csv
+}
+
+#[tauri::command]
+pub fn get_cost_summary(
+    app: AppHandle,
+    store: tauri::State<SettingsStore>,
+    usage: tauri::State<UsageStore>,
+) -> Result<CostSummary, AppError> {
+    Ok(summarize(&usage, &store.get().costs, is_paused(&app))?)
+}
+
+#[tauri::command]
+pub fn set_cost_settings(
+    app: AppHandle,
+    store: tauri::State<SettingsStore>,
+    settings: CostSettings,
+) -> Result<CostSettings, AppError> {
+    if settings
+        .monthly_budget_usd
+        .is_some_and(|budget| budget < 0.0)
+    {
+        return Err(AppError::invalid_input(
+            "Monthly budget cannot be negative.",
+        ));
+    }
+    // The pause is not the webview's to set; only resuming lifts it.
+    let updated = store
+        .update(|current| {
+            current.costs = CostSettings {
+                paused_month: current.costs.paused_month.take(),
+                ..settings
+            }
+        })?
+        .costs;
+    check_budget(&app)?;
+    Ok(updated)
+}
+
+/// Tokens and spend over `range`, in total, per day and per model, priced with the
+/// current price list.
+#[tauri::command]
+pub fn get_usage_summary(
+    store: tauri::State<SettingsStore>,
+    usage: tauri::State<UsageStore>,
+    range: Option<UsageRange>,
+) -> Result<UsageSummary, AppError> {
+    let rows = usage.rows(&range.unwrap_or_default())?;
+    Ok(summarize_range(&rows, &store.get().costs))
+}
+
+/// Writes the daily usage in `range` to `dest` as CSV, one row per conversation and
+/// model, with the cost left empty for models without a price. A folder for `dest`
+/// gets a dated file inside it.
+#[tauri::command]
+pub fn export_usage_csv(
+    store: tauri::State<SettingsStore>,
+    usage: tauri::State<UsageStore>,
+    dest: PathBuf,
+    range: Option<UsageRange>,
+) -> Result<PathBuf, AppError> {
+    let rows = usage.rows(&range.unwrap_or_default())?;
+    let dest = if dest.is_dir() {
+        dest.join(format!("usage-{}.csv", Local::now().format("%Y%m%d")))
+    } else {
+        dest
+    };
+    atomic_file::write(&dest, usage_csv(&rows, &store.get().costs))
+        .map_err(|err| AppError::from(format!("Failed to write {}: {err}", dest.display())))?;
+    tracing::info!("[Costs] Exported usage to {}", dest.display());
+    Ok(dest)
+}
+
+/// Lifts a budget hard-stop for the rest of the month.
+#[tauri::command]
+pub fn resume_after_budget_stop(
+    app: AppHandle,
+    store: tauri::State<SettingsStore>,
+) -> Result<(), AppError> {
+    store.update(|current| current.costs.paused_month = None)?;
+    spawn_notify(&app, false);
+    tracing::info!("[Costs] LLM calls resumed after the budget stop.");
+    Ok(())
+}
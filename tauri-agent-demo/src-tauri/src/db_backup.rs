@@ -0,0 +1,285 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
+    time::Duration,
+};
+
+use chrono::Local;
+use rusqlite::{
+    backup::{Backup, StepResult},
+    Connection, OpenFlags,
+};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{encryption, error::AppError, events, workspace};
+
+const BACKUP_PREFIX: &str = "chat_app-";
+const BACKUP_SUFFIX: &str = ".db";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+/// Timestamped backups kept in `backups/`; copies written elsewhere are left alone.
+const KEEP_BACKUPS: usize = 10;
+/// About 1 MB per step at SQLite's default page size, so the backend is only ever
+/// locked out briefly and progress moves often enough to show.
+const PAGES_PER_STEP: i32 = 256;
+/// Pause between steps while the backend holds a lock.
+const BUSY_PAUSE: Duration = Duration::from_millis(50);
+
+/// Held during a backup or restore so the two never interleave.
+#[derive(Default)]
+pub struct DatabaseBackups(Mutex<()>);
+
+events::payload_enum! {
+    pub enum Operation {
+        Backup,
+        Restore,
+    }
+}
+
+events::payload! {
+    pub struct BackupProgress {
+        operation: Operation,
+        copied_pages: i32,
+        total_pages: i32,
+    }
+}
+
+events::payload! {
+    pub struct DatabaseBackup {
+        path: PathBuf,
+        size_bytes: u64,
+        created_at: String,
+    }
+}
+
+fn backups_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, AppError> {
+    let dir = workspace::data_dir(app)?.join("backups");
+    fs::create_dir_all(&dir)
+        .map_err(|err| AppError::from(format!("Failed to create the backup folder: {err}")))?;
+    Ok(dir)
+}
+
+fn db_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, AppError> {
+    let path = workspace::db_path(app)?;
+    if !path.exists() {
+        return Err(AppError::not_found(format!(
+            "Chat database not found at {}.",
+            path.display()
+        )));
+    }
+    Ok(path)
+}
+
+fn open(path: &Path, flags: OpenFlags) -> Result<Connection, AppError> {
+    encryption::open(path, flags)
+        .map_err(|err| AppError::database("Failed to open the database.", err))
+}
+
+/// Copies `from` into `to` a few pages at a time, emitting
+/// `database://backup-progress` as it goes. The source stays usable throughout.
+fn copy_pages<R: Runtime>(
+    app: &AppHandle<R>,
+    operation: Operation,
+    from: &Connection,
+    to: &mut Connection,
+) -> Result<(), AppError> {
+    let backup = Backup::new(from, to)
+        .map_err(|err| AppError::database("Failed to start the copy.", err))?;
+    loop {
+        let step = backup
+            .step(PAGES_PER_STEP)
+            .map_err(|err| AppError::database("Failed to copy the database.", err))?;
+        let progress = backup.progress();
+        events::database_backup_progress(
+            app,
+            &BackupProgress {
+                operation,
+                copied_pages: progress.pagecount - progress.remaining,
+                total_pages: progress.pagecount,
+            },
+        );
+        match step {
+            StepResult::Done => return Ok(()),
+            StepResult::More => {}
+            // The backend is mid-write; the next step picks up from here.
+            _ => thread::sleep(BUSY_PAUSE),
+        }
+    }
+}
+
+fn is_timestamped(name: &str) -> bool {
+    name.strip_prefix(BACKUP_PREFIX)
+        .and_then(|rest| rest.strip_suffix(BACKUP_SUFFIX))
+        .is_some_and(|stamp| chrono::NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT).is_ok())
+}
+
+/// Drops the oldest timestamped backups beyond `KEEP_BACKUPS`.
+fn prune(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| is_timestamped(name))
+        .collect();
+    // The timestamp format sorts chronologically.
+    names.sort();
+    let excess = names.len().saturating_sub(KEEP_BACKUPS);
+    for name in names.into_iter().take(excess) {
+        if let Err(err) = fs::remove_file(dir.join(&name)) {
+            tracing::warn!("[Database] Failed to remove old backup {name}: {err}");
+        }
+    }
+}
+
+fn backup<R: Runtime>(
+    app: &AppHandle<R>,
+    dest: Option<PathBuf>,
+) -> Result<DatabaseBackup, AppError> {
+    let source = db_path(app)?;
+    let stamped = format!(
+        "{BACKUP_PREFIX}{}{BACKUP_SUFFIX}",
+        Local::now().format(TIMESTAMP_FORMAT)
+    );
+    let path = match dest {
+        Some(dest) if dest.is_dir() => dest.join(stamped),
+        Some(dest) => dest,
+        None => backups_dir(app)?.join(stamped),
+    };
+    if path == source {
+        return Err(AppError::invalid_input(
+            "A backup cannot overwrite the live database.",
+        ));
+    }
+    // Written next to the target first, so an interrupted backup never
+    // replaces a good one.
+    let mut staging = path.as_os_str().to_owned();
+    staging.push(".partial");
+    let staging = PathBuf::from(staging);
+    let _ = fs::remove_file(&staging);
+    let result = (|| {
+        let from = open(&source, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut to = open(&staging, OpenFlags::default())?;
+        copy_pages(app, Operation::Backup, &from, &mut to)?;
+        drop(to);
+        fs::rename(&staging, &path)
+            .map_err(|err| AppError::from(format!("Failed to finish the backup: {err}")))
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&staging);
+    }
+    result?;
+    if let Some(dir) = path.parent() {
+        if dir == backups_dir(app)? {
+            prune(dir);
+        }
+    }
+    let size_bytes = fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+    tracing::info!("[Database] Backed up to {}", path.display());
+    Ok(DatabaseBackup {
+        path,
+        size_bytes,
+        created_at: Local::now().to_rfc3339(),
+    })
+}
+
+/// Refuses files that are not a readable chat database before anything is touched.
+fn check_restorable(src: &Path) -> Result<Connection, AppError> {
+    if !src.is_file() {
+        return Err(AppError::not_found(format!(
+            "No backup found at {}.",
+            src.display()
+        )));
+    }
+    let conn = open(src, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let check: String = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|_| {
+            AppError::invalid_input(format!("{} is not a SQLite database.", src.display()))
+        })?;
+    if check != "ok" {
+        return Err(
+            AppError::invalid_input("The backup is damaged and cannot be restored.")
+                .with_details(check),
+        );
+    }
+    let has_sessions: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'chat_sessions')",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|err| AppError::database("Failed to inspect the backup.", err))?;
+    if !has_sessions {
+        return Err(AppError::invalid_input(format!(
+            "{} is not a chat database backup.",
+            src.display()
+        )));
+    }
+    Ok(conn)
+}
+
+fn restore<R: Runtime>(app: &AppHandle<R>, src: &Path) -> Result<DatabaseBackup, AppError> {
+    let from = check_restorable(src)?;
+    let live = db_path(app)?;
+    // Restoring is itself risky, so keep a way back.
+    let safety = backup(app, None)?;
+    crate::with_backend_stopped(app, || {
+        // Going through a connection keeps any WAL consistent with the new pages.
+        let mut to = open(&live, OpenFlags::default())?;
+        copy_pages(app, Operation::Restore, &from, &mut to)
+    })?;
+    tracing::info!(
+        "[Database] Restored from {}; previous database saved to {}.",
+        src.display(),
+        safety.path.display()
+    );
+    events::database_restored(app, &safety);
+    Ok(safety)
+}
+
+/// Takes a timestamped backup now, unless a backup or restore is already running.
+pub fn backup_now<R: Runtime>(app: &AppHandle<R>) -> Result<DatabaseBackup, AppError> {
+    let state = app.state::<DatabaseBackups>();
+    let _running = state
+        .0
+        .try_lock()
+        .map_err(|_| AppError::unavailable("A database backup or restore is already running."))?;
+    backup(app, None)
+}
+
+/// Copies the live chat database with SQLite's online backup, so the backend keeps
+/// running. `dest` may be a file or a folder; by default a timestamped file goes
+/// under `app_data_dir/backups/`, where only the newest ten are kept.
+#[tauri::command]
+pub async fn backup_database(
+    app: AppHandle,
+    dest: Option<PathBuf>,
+) -> Result<DatabaseBackup, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<DatabaseBackups>();
+        let _running = state.0.try_lock().map_err(|_| {
+            AppError::unavailable("A database backup or restore is already running.")
+        })?;
+        backup(&app, dest)
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Backup task failed: {err}")))?
+}
+
+/// Replaces the chat database with `src`, stopping the backend around the swap.
+/// Returns the backup of the database that was replaced.
+#[tauri::command]
+pub async fn restore_database(app: AppHandle, src: PathBuf) -> Result<DatabaseBackup, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<DatabaseBackups>();
+        let _running = state.0.try_lock().map_err(|_| {
+            AppError::unavailable("A database backup or restore is already running.")
+        })?;
+        restore(&app, &src)
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Restore task failed: {err}")))?
+}
@@ -0,0 +1,79 @@
+use std::{
+    net::SocketAddr,
+    sync::atomic::{AtomicU16, Ordering},
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    error::{AppError, ErrorCode},
+    network, sidecar, BackendState, BACKEND_SIDECAR,
+};
+
+/// Port the respawned backend hands to debugpy; 0 while debugging is off. Kept in
+/// memory only, so a relaunch always starts without the debugger.
+#[derive(Default)]
+pub struct BackendDebugger {
+    port: AtomicU16,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DebuggerInfo {
+    host: String,
+    port: u16,
+    /// `host:port` to paste into a debugpy "attach" configuration.
+    address: String,
+}
+
+/// The port to pass as `TAURI_AGENT_DEBUGPY_PORT`, if debugging was turned on.
+pub fn port<R: Runtime>(app: &AppHandle<R>) -> Option<u16> {
+    app.try_state::<BackendDebugger>()
+        .map(|debugger| debugger.port.load(Ordering::SeqCst))
+        .filter(|port| *port != 0)
+}
+
+/// Respawns the backend sidecar with debugpy listening on `port`, or on a free one,
+/// next to the API. Only available in development builds.
+#[tauri::command]
+pub async fn enable_backend_debugging(
+    app: AppHandle,
+    port: Option<u16>,
+) -> Result<DebuggerInfo, AppError> {
+    if !(tauri::is_dev() || cfg!(debug_assertions)) {
+        return Err(AppError::new(
+            ErrorCode::Unavailable,
+            "Backend debugging is only available in development builds.",
+        ));
+    }
+    if !sidecar::is_supervised(&app, BACKEND_SIDECAR) {
+        return Err(AppError::unavailable(
+            "The backend is not managed by this app.",
+        ));
+    }
+    let host = app
+        .try_state::<BackendState>()
+        .map(|state| state.host)
+        .ok_or_else(|| AppError::unavailable("Backend is not available."))?;
+    let port = match port.filter(|port| *port != 0) {
+        Some(port) => port,
+        None => crate::pick_backend_port(host)?,
+    };
+    app.state::<BackendDebugger>()
+        .port
+        .store(port, Ordering::SeqCst);
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::with_backend_stopped::<_, _, AppError>(&handle, || Ok(()))
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Restart task failed: {err}")))??;
+
+    let address = SocketAddr::new(network::connect_host(host), port);
+    tracing::info!("[Backend] debugpy listening for attach on {address}.");
+    Ok(DebuggerInfo {
+        host: address.ip().to_string(),
+        port,
+        address: address.to_string(),
+    })
+}
@@ -0,0 +1,146 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::{events, main_window};
+
+/// Registered in `tauri.conf.json` under `plugins.deep-link`.
+pub const SCHEME: &str = "agentapp";
+/// Links can come from any web page, so prompts are only ever prefilled, and kept
+/// to a size that fits the composer.
+const MAX_PROMPT_CHARS: usize = 8000;
+
+/// What an `agentapp://` link asks for, sent on `deeplink://open`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DeepLink {
+    /// `agentapp://chat/new?prompt=...`: a new conversation with the composer
+    /// prefilled, never sent.
+    NewChat { prompt: Option<String> },
+    /// `agentapp://conversation/<id>`
+    OpenConversation { session_id: String },
+}
+
+/// Written out by hand, since `events::payload_enum!` has no tagged form.
+#[cfg(test)]
+impl events::TsType for DeepLink {
+    fn ts() -> String {
+        "DeepLink".to_string()
+    }
+
+    fn declare(out: &mut Vec<(String, String)>) {
+        if events::is_declared(out, "DeepLink") {
+            return;
+        }
+        out.push((
+            "DeepLink".to_string(),
+            "export type DeepLink =\n    \
+             | { action: 'new_chat'; prompt: string | null }\n    \
+             | { action: 'open_conversation'; session_id: string };"
+                .to_string(),
+        ));
+    }
+}
+
+/// Links that arrive before the webview is listening wait here.
+#[derive(Default)]
+pub struct DeepLinks {
+    pending: Mutex<Vec<DeepLink>>,
+    listening: AtomicBool,
+}
+
+fn parse(url: &Url) -> Result<DeepLink, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Not an {SCHEME}:// link."));
+    }
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+        .unwrap_or_default();
+    match (url.host_str(), segments.as_slice()) {
+        (Some("chat"), ["new"]) => {
+            let prompt = url
+                .query_pairs()
+                .find(|(key, _)| key == "prompt")
+                .map(|(_, value)| value.chars().take(MAX_PROMPT_CHARS).collect::<String>())
+                .filter(|prompt| !prompt.trim().is_empty());
+            Ok(DeepLink::NewChat { prompt })
+        }
+        (Some("conversation"), [id]) => {
+            let valid = id.len() <= 128
+                && id
+                    .chars()
+                    .all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_');
+            if !valid {
+                return Err(format!("'{id}' is not a conversation id."));
+            }
+            Ok(DeepLink::OpenConversation {
+                session_id: id.to_string(),
+            })
+        }
+        _ => Err("Unknown link.".to_string()),
+    }
+}
+
+fn open<R: Runtime>(app: &AppHandle<R>, urls: Vec<Url>) {
+    let Some(links) = app.try_state::<DeepLinks>() else {
+        return;
+    };
+    for url in urls {
+        let link = match parse(&url) {
+            Ok(link) => link,
+            Err(err) => {
+                tracing::warn!("[DeepLink] Ignoring {url}: {err}");
+                continue;
+            }
+        };
+        // Checked under the lock so a link cannot slip in while the webview collects
+        // the pending ones.
+        let Ok(mut pending) = links.pending.lock() else {
+            continue;
+        };
+        if links.listening.load(Ordering::SeqCst) {
+            main_window::reveal(app);
+            events::deeplink_open(app, main_window::LABEL, &link);
+        } else {
+            // Still starting up; the startup gate shows the window when it is ready.
+            pending.push(link);
+        }
+    }
+}
+
+/// Handles `agentapp://` links: the one the app was launched with, and any opened
+/// while it runs. Links clicked while another instance is running reach this
+/// one through the single-instance plugin.
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    // Installers register the scheme on Windows and Linux, but dev builds and
+    // AppImages have to do it themselves.
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(err) = app.deep_link().register_all() {
+        tracing::warn!("[DeepLink] Failed to register {SCHEME}://: {err}");
+    }
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => open(app, urls),
+        Ok(None) => {}
+        Err(err) => tracing::warn!("[DeepLink] {err}"),
+    }
+    let handle = app.clone();
+    app.deep_link()
+        .on_open_url(move |event| open(&handle, event.urls()));
+}
+
+/// Returns the links that arrived before the main window was listening; from then
+/// on they are emitted on `deeplink://open` as they come.
+#[tauri::command]
+pub fn take_pending_deep_links(links: tauri::State<DeepLinks>) -> Vec<DeepLink> {
+    let Ok(mut pending) = links.pending.lock() else {
+        return Vec::new();
+    };
+    links.listening.store(true, Ordering::SeqCst);
+    std::mem::take(&mut *pending)
+}
@@ -0,0 +1,656 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+
+use crate::{
+    error::AppError,
+    events,
+    notifications::{self, NotificationAction, NotificationKind},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RichNotification {
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub actions: Vec<NotificationAction>,
+    /// 0-100; shown as a progress bar where the notification server supports it.
+    pub progress: Option<u8>,
+    /// Id returned by an earlier call, to update that notification in place.
+    pub replaces_id: Option<u32>,
+    /// Conversation the notification is about, for quick replies from the tray.
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+events::payload! {
+    /// A media key grabbed for the app through GNOME's media keys service.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub struct MediaKeyEvent {
+        key: String,
+    }
+}
+
+/// A global shortcut requested through the XDG GlobalShortcuts portal.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Debug, Clone)]
+pub struct PortalShortcut {
+    pub id: String,
+    pub description: String,
+    /// Only a hint: the compositor asks the user and may pick different keys.
+    pub preferred_trigger: String,
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        collections::HashMap,
+        future::Future,
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Mutex,
+        },
+        time::Duration,
+    };
+
+    use futures_util::StreamExt;
+    use tauri::{AppHandle, Manager, Runtime};
+    use zbus::{
+        zvariant::{ObjectPath, OwnedFd, OwnedObjectPath, OwnedValue, Value},
+        Connection,
+    };
+
+    use super::{MediaKeyEvent, PortalShortcut, RichNotification};
+    use crate::{
+        events,
+        notifications::{self, NotificationActionEvent},
+    };
+
+    const APP_NAME: &str = "GYY";
+
+    static PORTAL_TOKEN: AtomicU32 = AtomicU32::new(0);
+
+    #[zbus::proxy(
+        interface = "org.freedesktop.Notifications",
+        default_service = "org.freedesktop.Notifications",
+        default_path = "/org/freedesktop/Notifications"
+    )]
+    trait Notifications {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            app_name: &str,
+            replaces_id: u32,
+            app_icon: &str,
+            summary: &str,
+            body: &str,
+            actions: &[&str],
+            hints: HashMap<&str, Value<'_>>,
+            expire_timeout: i32,
+        ) -> zbus::Result<u32>;
+
+        #[zbus(signal)]
+        fn action_invoked(&self, id: u32, action_key: String) -> zbus::Result<()>;
+    }
+
+    #[zbus::proxy(
+        interface = "org.freedesktop.login1.Manager",
+        default_service = "org.freedesktop.login1",
+        default_path = "/org/freedesktop/login1"
+    )]
+    trait Login1Manager {
+        fn inhibit(&self, what: &str, who: &str, why: &str, mode: &str) -> zbus::Result<OwnedFd>;
+    }
+
+    #[zbus::proxy(
+        interface = "org.gnome.SettingsDaemon.MediaKeys",
+        default_service = "org.gnome.SettingsDaemon.MediaKeys",
+        default_path = "/org/gnome/SettingsDaemon/MediaKeys"
+    )]
+    trait MediaKeys {
+        fn grab_media_player_keys(&self, application: &str, time: u32) -> zbus::Result<()>;
+
+        #[zbus(signal)]
+        fn media_player_key_pressed(&self, application: String, key: String) -> zbus::Result<()>;
+    }
+
+    #[zbus::proxy(
+        interface = "org.freedesktop.portal.GlobalShortcuts",
+        default_service = "org.freedesktop.portal.Desktop",
+        default_path = "/org/freedesktop/portal/desktop"
+    )]
+    trait GlobalShortcuts {
+        fn create_session(
+            &self,
+            options: HashMap<&str, Value<'_>>,
+        ) -> zbus::Result<OwnedObjectPath>;
+
+        fn bind_shortcuts(
+            &self,
+            session_handle: &ObjectPath<'_>,
+            shortcuts: &[(&str, HashMap<&str, Value<'_>>)],
+            parent_window: &str,
+            options: HashMap<&str, Value<'_>>,
+        ) -> zbus::Result<OwnedObjectPath>;
+
+        #[zbus(signal)]
+        fn activated(
+            &self,
+            session_handle: OwnedObjectPath,
+            shortcut_id: String,
+            timestamp: u64,
+            options: HashMap<String, OwnedValue>,
+        ) -> zbus::Result<()>;
+    }
+
+    #[zbus::proxy(
+        interface = "org.gnome.Mutter.IdleMonitor",
+        default_service = "org.gnome.Mutter.IdleMonitor",
+        default_path = "/org/gnome/Mutter/IdleMonitor/Core"
+    )]
+    trait MutterIdleMonitor {
+        /// Milliseconds.
+        fn get_idletime(&self) -> zbus::Result<u64>;
+    }
+
+    #[zbus::proxy(
+        interface = "org.freedesktop.ScreenSaver",
+        default_service = "org.freedesktop.ScreenSaver",
+        default_path = "/org/freedesktop/ScreenSaver"
+    )]
+    trait ScreenSaver {
+        /// Seconds; implemented by KDE and a few others.
+        fn get_session_idle_time(&self) -> zbus::Result<u32>;
+    }
+
+    #[zbus::proxy(
+        interface = "org.freedesktop.portal.Request",
+        default_service = "org.freedesktop.portal.Desktop"
+    )]
+    trait PortalRequest {
+        #[zbus(signal)]
+        fn response(&self, response: u32, results: HashMap<String, OwnedValue>)
+            -> zbus::Result<()>;
+    }
+
+    #[zbus::proxy(
+        interface = "org.freedesktop.portal.Session",
+        default_service = "org.freedesktop.portal.Desktop"
+    )]
+    trait PortalSession {
+        fn close(&self) -> zbus::Result<()>;
+    }
+
+    pub struct LinuxDesktop {
+        session: Connection,
+        /// Notification ids this app created, with the conversation each is about;
+        /// action signals for others are ignored.
+        sent: Mutex<HashMap<u32, Option<String>>>,
+        /// login1 releases the inhibitor lock as soon as this descriptor is closed.
+        sleep_lock: Mutex<Option<OwnedFd>>,
+        /// Current GlobalShortcuts portal session; replaced whenever shortcuts are rebound.
+        portal_session: Mutex<Option<String>>,
+        portal_error: Mutex<Option<String>>,
+    }
+
+    fn next_token() -> String {
+        format!("gyy{}", PORTAL_TOKEN.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Portal request and session objects live at a path derived from our unique bus
+    /// name and a caller-chosen token, which lets us subscribe before making the call.
+    fn portal_path(session: &Connection, kind: &str, token: &str) -> Result<String, String> {
+        let sender = session
+            .unique_name()
+            .ok_or_else(|| "Session bus has no unique name.".to_string())?
+            .as_str()
+            .trim_start_matches(':')
+            .replace('.', "_");
+        Ok(format!(
+            "/org/freedesktop/portal/desktop/{kind}/{sender}/{token}"
+        ))
+    }
+
+    async fn portal_call<F>(session: &Connection, token: &str, call: F) -> Result<(), String>
+    where
+        F: Future<Output = zbus::Result<OwnedObjectPath>>,
+    {
+        let path = portal_path(session, "request", token)?;
+        let request = PortalRequestProxy::builder(session)
+            .path(path)
+            .map_err(|err| format!("Invalid portal request path: {err}"))?
+            .build()
+            .await
+            .map_err(|err| format!("Shortcut portal is unavailable: {err}"))?;
+        let mut responses = request
+            .receive_response()
+            .await
+            .map_err(|err| format!("Failed to watch portal request: {err}"))?;
+        call.await
+            .map_err(|err| format!("Shortcut portal call failed: {err}"))?;
+        let signal = responses
+            .next()
+            .await
+            .ok_or_else(|| "Shortcut portal closed the request.".to_string())?;
+        let args = signal
+            .args()
+            .map_err(|err| format!("Unexpected portal response: {err}"))?;
+        match args.response {
+            0 => Ok(()),
+            1 => Err("The shortcut request was cancelled.".to_string()),
+            _ => Err("The shortcut portal rejected the request.".to_string()),
+        }
+    }
+
+    impl LinuxDesktop {
+        pub async fn notify(
+            &self,
+            notification: &RichNotification,
+            sound: Option<&str>,
+        ) -> Result<u32, String> {
+            let proxy = NotificationsProxy::new(&self.session)
+                .await
+                .map_err(|err| format!("Notification service is unavailable: {err}"))?;
+            let actions: Vec<&str> = notification
+                .actions
+                .iter()
+                .flat_map(|action| [action.id.as_str(), action.label.as_str()])
+                .collect();
+            let mut hints: HashMap<&str, Value<'_>> = HashMap::new();
+            if let Some(progress) = notification.progress {
+                hints.insert("value", Value::from(i32::from(progress.min(100))));
+            }
+            if let Some(sound) = sound {
+                hints.insert("sound-name", Value::from(sound));
+            }
+            let id = proxy
+                .notify(
+                    APP_NAME,
+                    notification.replaces_id.unwrap_or(0),
+                    "",
+                    &notification.title,
+                    &notification.body,
+                    &actions,
+                    hints,
+                    -1,
+                )
+                .await
+                .map_err(|err| format!("Failed to show notification: {err}"))?;
+            if let Ok(mut sent) = self.sent.lock() {
+                sent.insert(id, notification.session_id.clone());
+            }
+            Ok(id)
+        }
+
+        pub async fn set_sleep_inhibited(&self, inhibit: bool, reason: &str) -> Result<(), String> {
+            let mut lock = self
+                .sleep_lock
+                .lock()
+                .map_err(|_| "Sleep inhibitor is unavailable.".to_string())?
+                .take();
+            if inhibit && lock.is_none() {
+                let system = Connection::system()
+                    .await
+                    .map_err(|err| format!("System bus is unavailable: {err}"))?;
+                let manager = Login1ManagerProxy::new(&system)
+                    .await
+                    .map_err(|err| format!("login1 is unavailable: {err}"))?;
+                lock = Some(
+                    manager
+                        .inhibit("sleep:idle", APP_NAME, reason, "block")
+                        .await
+                        .map_err(|err| format!("Failed to inhibit sleep: {err}"))?,
+                );
+            } else if !inhibit {
+                lock = None;
+            }
+            if let Ok(mut slot) = self.sleep_lock.lock() {
+                *slot = lock;
+            }
+            Ok(())
+        }
+
+        /// Rebinds shortcuts and remembers the outcome for `shortcut_portal_status`.
+        pub async fn bind_shortcuts(&self, shortcuts: Vec<PortalShortcut>) {
+            let result = self.open_shortcut_session(shortcuts).await;
+            if let Err(err) = &result {
+                tracing::warn!("[Shortcuts] {err}");
+            }
+            if let Ok(mut error) = self.portal_error.lock() {
+                *error = result.err();
+            }
+        }
+
+        /// Asks GNOME's idle monitor first and the freedesktop screensaver second.
+        pub async fn idle_time(&self) -> Result<Duration, String> {
+            let gnome = match MutterIdleMonitorProxy::new(&self.session).await {
+                Ok(monitor) => monitor.get_idletime().await,
+                Err(err) => Err(err),
+            };
+            if let Ok(millis) = gnome {
+                return Ok(Duration::from_millis(millis));
+            }
+            let screensaver = ScreenSaverProxy::new(&self.session)
+                .await
+                .map_err(|err| format!("No idle monitor on the session bus: {err}"))?;
+            screensaver
+                .get_session_idle_time()
+                .await
+                .map(|seconds| Duration::from_secs(u64::from(seconds)))
+                .map_err(|err| format!("No idle monitor on the session bus: {err}"))
+        }
+
+        pub fn portal_status(&self) -> Result<bool, String> {
+            if let Some(err) = self.portal_error.lock().ok().and_then(|err| err.clone()) {
+                return Err(err);
+            }
+            Ok(self
+                .portal_session
+                .lock()
+                .map(|current| current.is_some())
+                .unwrap_or(false))
+        }
+
+        async fn open_shortcut_session(
+            &self,
+            shortcuts: Vec<PortalShortcut>,
+        ) -> Result<(), String> {
+            let portal = GlobalShortcutsProxy::new(&self.session)
+                .await
+                .map_err(|err| format!("Shortcut portal is unavailable: {err}"))?;
+            let previous = self
+                .portal_session
+                .lock()
+                .ok()
+                .and_then(|mut current| current.take());
+            if let Some(previous) = previous {
+                // A session can only be bound once, so rebinding starts a new one.
+                if let Ok(builder) = PortalSessionProxy::builder(&self.session).path(previous) {
+                    if let Ok(old) = builder.build().await {
+                        let _ = old.close().await;
+                    }
+                }
+            }
+
+            let session_token = next_token();
+            let session_path = portal_path(&self.session, "session", &session_token)?;
+            let create_token = next_token();
+            let mut options: HashMap<&str, Value<'_>> = HashMap::new();
+            options.insert("handle_token", Value::from(create_token.as_str()));
+            options.insert("session_handle_token", Value::from(session_token.as_str()));
+            portal_call(&self.session, &create_token, portal.create_session(options)).await?;
+
+            let bind_token = next_token();
+            let entries: Vec<(&str, HashMap<&str, Value<'_>>)> = shortcuts
+                .iter()
+                .map(|shortcut| {
+                    let mut details: HashMap<&str, Value<'_>> = HashMap::new();
+                    details.insert("description", Value::from(shortcut.description.as_str()));
+                    details.insert(
+                        "preferred_trigger",
+                        Value::from(shortcut.preferred_trigger.as_str()),
+                    );
+                    (shortcut.id.as_str(), details)
+                })
+                .collect();
+            let mut options: HashMap<&str, Value<'_>> = HashMap::new();
+            options.insert("handle_token", Value::from(bind_token.as_str()));
+            let handle = ObjectPath::try_from(session_path.as_str())
+                .map_err(|err| format!("Invalid portal session path: {err}"))?;
+            portal_call(
+                &self.session,
+                &bind_token,
+                portal.bind_shortcuts(&handle, &entries, "", options),
+            )
+            .await?;
+            if let Ok(mut current) = self.portal_session.lock() {
+                *current = Some(session_path);
+            }
+            Ok(())
+        }
+    }
+
+    async fn listen_for_actions<R: Runtime>(
+        app: AppHandle<R>,
+        session: Connection,
+    ) -> zbus::Result<()> {
+        let proxy = NotificationsProxy::new(&session).await?;
+        let mut actions = proxy.receive_action_invoked().await?;
+        while let Some(signal) = actions.next().await {
+            let Ok(args) = signal.args() else {
+                continue;
+            };
+            let Some(session_id) = app
+                .try_state::<LinuxDesktop>()
+                .and_then(|desktop| desktop.sent.lock().ok()?.get(&args.id).cloned())
+            else {
+                continue;
+            };
+            notifications::handle_action(&app, &args.action_key, session_id.as_deref());
+            events::notification_action(
+                &app,
+                &NotificationActionEvent {
+                    id: Some(args.id),
+                    action: args.action_key.clone(),
+                    session_id,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    async fn listen_for_media_keys<R: Runtime>(
+        app: AppHandle<R>,
+        session: Connection,
+    ) -> zbus::Result<()> {
+        let proxy = MediaKeysProxy::new(&session).await?;
+        proxy.grab_media_player_keys(APP_NAME, 0).await?;
+        let mut keys = proxy.receive_media_player_key_pressed().await?;
+        while let Some(signal) = keys.next().await {
+            let Ok(args) = signal.args() else {
+                continue;
+            };
+            if args.application == APP_NAME {
+                events::media_key(
+                    &app,
+                    &MediaKeyEvent {
+                        key: args.key.clone(),
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn listen_for_portal_shortcuts<R: Runtime>(
+        app: AppHandle<R>,
+        session: Connection,
+    ) -> zbus::Result<()> {
+        let portal = GlobalShortcutsProxy::new(&session).await?;
+        let mut activations = portal.receive_activated().await?;
+        while let Some(signal) = activations.next().await {
+            let Ok(args) = signal.args() else {
+                continue;
+            };
+            let current = app.try_state::<LinuxDesktop>().and_then(|desktop| {
+                desktop
+                    .portal_session
+                    .lock()
+                    .ok()
+                    .and_then(|current| current.clone())
+            });
+            if current.as_deref() == Some(args.session_handle.as_str()) {
+                crate::shortcuts::trigger(&app, &args.shortcut_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Connects to the session bus and starts the signal listeners.
+    pub async fn start<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+        let session = Connection::session()
+            .await
+            .map_err(|err| format!("Session bus is unavailable: {err}"))?;
+        app.manage(LinuxDesktop {
+            session: session.clone(),
+            sent: Mutex::new(HashMap::new()),
+            sleep_lock: Mutex::new(None),
+            portal_session: Mutex::new(None),
+            portal_error: Mutex::new(None),
+        });
+        if crate::shortcuts::is_wayland() {
+            let portal_app = app.clone();
+            let portal_session = session.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = listen_for_portal_shortcuts(portal_app, portal_session).await {
+                    tracing::warn!("[Desktop] Shortcut portal unavailable: {err}");
+                }
+            });
+            // Setup registered shortcuts before the bus was up; bind them now.
+            if let Err(err) = crate::shortcuts::apply(&app) {
+                tracing::warn!("[Shortcuts] {err}");
+            }
+        }
+        let actions_app = app.clone();
+        let actions_session = session.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = listen_for_actions(actions_app, actions_session).await {
+                tracing::warn!("[Desktop] Notification actions unavailable: {err}");
+            }
+        });
+        tauri::async_runtime::spawn(async move {
+            // Only GNOME's settings daemon offers this; other desktops simply skip it.
+            if let Err(err) = listen_for_media_keys(app, session).await {
+                tracing::warn!("[Desktop] Media keys unavailable: {err}");
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Starts DBus integration on Linux; a no-op elsewhere.
+pub fn start(app: &AppHandle) {
+    #[cfg(target_os = "linux")]
+    {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = linux::start(app).await {
+                tracing::warn!("[Desktop] {err}");
+            }
+        });
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = app;
+}
+
+/// Binds global shortcuts through the portal, for Wayland sessions where the
+/// compositor does not let apps grab keys directly. No-op before DBus is up.
+pub fn bind_portal_shortcuts<R: Runtime>(app: &AppHandle<R>, shortcuts: Vec<PortalShortcut>) {
+    #[cfg(target_os = "linux")]
+    {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            use tauri::Manager;
+            if let Some(desktop) = app.try_state::<linux::LinuxDesktop>() {
+                desktop.bind_shortcuts(shortcuts).await;
+            }
+        });
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (app, shortcuts);
+}
+
+/// `Ok(false)` while DBus is still connecting or on platforms without the portal.
+pub fn shortcut_portal_status<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
+    #[cfg(target_os = "linux")]
+    {
+        use tauri::Manager;
+        app.try_state::<linux::LinuxDesktop>()
+            .map_or(Ok(false), |desktop| desktop.portal_status())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = app;
+        Ok(false)
+    }
+}
+
+/// Session idle time from the desktop's DBus idle monitor (Linux only).
+#[cfg(target_os = "linux")]
+pub async fn session_idle_time<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<std::time::Duration, String> {
+    use tauri::Manager;
+    let desktop = app
+        .try_state::<linux::LinuxDesktop>()
+        .ok_or_else(|| "DBus session is not available.".to_string())?;
+    desktop.idle_time().await
+}
+
+/// Shows a notification with actions and progress where the desktop supports them
+/// (DBus on Linux) and a plain notification elsewhere. Returns the notification id,
+/// or `None` when preferences suppressed it or the platform has no ids.
+#[tauri::command]
+pub async fn show_rich_notification(
+    app: AppHandle,
+    mut notification: RichNotification,
+) -> Result<Option<u32>, AppError> {
+    let settings = notifications::current_settings(&app);
+    if !notifications::allowed(&settings, notification.kind) {
+        return Ok(None);
+    }
+    if notification.actions.is_empty() {
+        notification.actions =
+            notifications::conversation_actions(notification.session_id.as_deref());
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use tauri::Manager;
+        if let Some(desktop) = app.try_state::<linux::LinuxDesktop>() {
+            let sound = settings.sound.as_deref().filter(|sound| !sound.is_empty());
+            let id = desktop.notify(&notification, sound).await?;
+            notifications::remember(
+                &app,
+                notification.kind,
+                &notification.title,
+                &notification.body,
+                notification.session_id.clone(),
+            );
+            return Ok(Some(id));
+        }
+    }
+    notifications::notify_with_actions(
+        &app,
+        notification.kind,
+        &notification.title,
+        &notification.body,
+        notification.session_id,
+        notification.actions,
+    )?;
+    Ok(None)
+}
+
+/// Keeps the machine awake while long agent runs are in progress.
+#[tauri::command]
+pub async fn set_sleep_inhibited(
+    app: AppHandle,
+    inhibit: bool,
+    reason: Option<String>,
+) -> Result<(), AppError> {
+    #[cfg(target_os = "linux")]
+    {
+        use tauri::Manager;
+        let desktop = app
+            .try_state::<linux::LinuxDesktop>()
+            .ok_or_else(|| AppError::unavailable("DBus session is not available."))?;
+        let reason = reason.as_deref().unwrap_or("An agent task is running");
+        Ok(desktop.set_sleep_inhibited(inhibit, reason).await?)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (app, inhibit, reason);
+        Err(AppError::unavailable(
+            "Sleep prevention is not supported on this platform yet.",
+        ))
+    }
+}
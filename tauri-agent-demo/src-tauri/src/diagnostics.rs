@@ -0,0 +1,299 @@
+use std::{
+    backtrace::Backtrace,
+    cmp::Reverse,
+    fs::{self, File},
+    io::{self, Write},
+    panic,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_dialog::DialogExt;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    arch, compat,
+    config_files::ConfigFile,
+    dialogs::{self, DialogPurpose},
+    error::AppError,
+    kiosk,
+    log_files::LogFiles,
+    logging::Logging,
+    proxy_config,
+    settings::SettingsStore,
+    sidecar::{self, SECRET_MARKERS},
+    workspace,
+};
+
+/// Log files are added newest first until the bundle holds this much of them.
+const MAX_LOG_BYTES: u64 = 32 * 1024 * 1024;
+const MAX_CRASH_REPORTS: usize = 20;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiagnosticsSettings {
+    /// Write a report to `app_data_dir/crash-reports` when the shell panics.
+    pub crash_reports: bool,
+}
+
+/// Read by the panic hook, which cannot reach the settings store.
+static CRASH_REPORTS: AtomicBool = AtomicBool::new(false);
+static CRASH_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+fn write_crash_report(info: &panic::PanicHookInfo<'_>) -> io::Result<PathBuf> {
+    let dir = CRASH_DIR
+        .get()
+        .ok_or_else(|| io::Error::other("no crash report folder"))?;
+    fs::create_dir_all(dir)?;
+    let now = Local::now();
+    let path = dir.join(format!("crash-{}.txt", now.format("%Y%m%d-%H%M%S%.3f")));
+    let thread = std::thread::current();
+    let report = format!(
+        "time: {}\nversion: {}\nos: {} {}\nthread: {}\n\n{info}\n\n{}\n",
+        now.to_rfc3339(),
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        thread.name().unwrap_or("unnamed"),
+        Backtrace::force_capture(),
+    );
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+/// Chains a hook in front of the default one that writes a crash report when the
+/// user has opted in. Called once from setup.
+pub fn install_panic_hook<R: Runtime>(app: &AppHandle<R>, dir: PathBuf) {
+    let _ = CRASH_DIR.set(dir);
+    let enabled = app
+        .try_state::<SettingsStore>()
+        .is_some_and(|store| store.get().diagnostics.crash_reports);
+    CRASH_REPORTS.store(enabled, Ordering::SeqCst);
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if CRASH_REPORTS.load(Ordering::SeqCst) {
+            match write_crash_report(info) {
+                Ok(path) => eprintln!("[Diagnostics] Crash report saved to {}", path.display()),
+                Err(err) => eprintln!("[Diagnostics] Failed to save a crash report: {err}"),
+            }
+        }
+        previous(info);
+    }));
+}
+
+/// Blanks every string held under a name that looks like a secret.
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, entry) in map.iter_mut() {
+                let upper = key.to_ascii_uppercase();
+                let secret = SECRET_MARKERS.iter().any(|marker| upper.contains(marker));
+                match entry {
+                    Value::String(text) if secret && !text.is_empty() => {
+                        *text = "<redacted>".to_string();
+                    }
+                    _ => redact(entry),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+fn os_version() -> Option<String> {
+    let output = if cfg!(target_os = "linux") {
+        let release = fs::read_to_string("/etc/os-release").ok()?;
+        return release
+            .lines()
+            .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+            .map(|name| name.trim_matches('"').to_string());
+    } else if cfg!(target_os = "macos") {
+        Command::new("sw_vers").arg("-productVersion").output()
+    } else if cfg!(windows) {
+        Command::new("cmd").args(["/C", "ver"]).output()
+    } else {
+        return None;
+    };
+    let text = String::from_utf8_lossy(&output.ok()?.stdout)
+        .trim()
+        .to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+fn system_info<R: Runtime>(app: &AppHandle<R>) -> Value {
+    let package = app.package_info();
+    json!({
+        "created_at": Local::now().to_rfc3339(),
+        "app": package.name,
+        "version": package.version.to_string(),
+        "tauri": tauri::VERSION,
+        "os": std::env::consts::OS,
+        "os_version": os_version(),
+        "arch": std::env::consts::ARCH,
+        "host_arch": arch::host().map(|arch| arch.as_str()),
+        "kiosk": kiosk::is_enabled(app),
+        "backend": sidecar::info(app, crate::BACKEND_SIDECAR),
+        "backend_version": compat::report(app),
+    })
+}
+
+fn options() -> SimpleFileOptions {
+    SimpleFileOptions::default().compression_method(CompressionMethod::Deflated)
+}
+
+fn add_bytes(zip: &mut ZipWriter<File>, name: &str, bytes: &[u8]) -> Result<(), String> {
+    zip.start_file(name, options())
+        .map_err(|err| format!("Failed to pack {name}: {err}"))?;
+    zip.write_all(bytes)
+        .map_err(|err| format!("Failed to pack {name}: {err}"))
+}
+
+fn add_json(zip: &mut ZipWriter<File>, name: &str, value: &Value) -> Result<(), String> {
+    let raw = serde_json::to_string_pretty(value)
+        .map_err(|err| format!("Failed to serialize {name}: {err}"))?;
+    add_bytes(zip, name, raw.as_bytes())
+}
+
+/// Files in `dir`, newest first.
+fn newest_first(dir: &Path) -> Vec<(PathBuf, u64)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok().filter(|meta| meta.is_file())?;
+            Some((entry.path(), meta.len(), meta.modified().ok()))
+        })
+        .collect();
+    files.sort_by_key(|(_, _, modified)| Reverse(*modified));
+    files
+        .into_iter()
+        .map(|(path, size, _)| (path, size))
+        .collect()
+}
+
+fn add_folder(
+    zip: &mut ZipWriter<File>,
+    dir: &Path,
+    prefix: &str,
+    max_bytes: u64,
+    max_files: usize,
+) -> Result<(), String> {
+    let mut total = 0;
+    for (path, size) in newest_first(dir).into_iter().take(max_files) {
+        if total + size > max_bytes {
+            break;
+        }
+        let Ok(bytes) = fs::read(&path) else {
+            continue;
+        };
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        add_bytes(zip, &format!("{prefix}/{name}"), &bytes)?;
+        total += size;
+    }
+    Ok(())
+}
+
+fn pack<R: Runtime>(app: &AppHandle<R>, dest: &Path) -> Result<(), String> {
+    let app_data_dir = workspace::data_dir(app)?;
+    let mut zip = ZipWriter::new(
+        File::create(dest).map_err(|err| format!("Failed to create the bundle: {err}"))?,
+    );
+    add_json(&mut zip, "system.json", &system_info(app))?;
+    if let Some(logging) = app.try_state::<Logging>() {
+        add_bytes(
+            &mut zip,
+            "logs/recent.log",
+            logging.lines().join("\n").as_bytes(),
+        )?;
+    }
+    if let Some(files) = app.try_state::<LogFiles>() {
+        add_folder(&mut zip, files.dir(), "logs", MAX_LOG_BYTES, usize::MAX)?;
+    }
+    if let Some(store) = app.try_state::<SettingsStore>() {
+        let mut settings = serde_json::to_value(store.get())
+            .map_err(|err| format!("Failed to serialize settings: {err}"))?;
+        redact(&mut settings);
+        if let Some(Value::String(url)) = settings.pointer_mut("/outbound_proxy/url") {
+            *url = proxy_config::strip_credentials(url);
+        }
+        add_json(&mut zip, "config/shell_settings.json", &settings)?;
+    }
+    for file in [ConfigFile::App, ConfigFile::Tools] {
+        let name = file.file_name();
+        let Ok(raw) = fs::read_to_string(app_data_dir.join(name)) else {
+            continue;
+        };
+        // Secrets in a file that does not parse cannot be found, so only the error goes in.
+        let mut config = serde_json::from_str(&raw)
+            .unwrap_or_else(|err| json!({ "unreadable": err.to_string() }));
+        redact(&mut config);
+        add_json(&mut zip, &format!("config/{name}"), &config)?;
+    }
+    if let Some(dir) = CRASH_DIR.get() {
+        add_folder(&mut zip, dir, "crash-reports", u64::MAX, MAX_CRASH_REPORTS)?;
+    }
+    zip.finish()
+        .map_err(|err| format!("Failed to finish the bundle: {err}"))?;
+    Ok(())
+}
+
+fn pick_file<R: Runtime>(app: &AppHandle<R>) -> Result<Option<PathBuf>, AppError> {
+    let mut dialog = app.dialog().file();
+    if let Some(dir) = dialogs::default_dir(app, DialogPurpose::Exports) {
+        dialog = dialog.set_directory(dir);
+    }
+    let name = format!(
+        "gyy-diagnostics-{}.zip",
+        Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let Some(chosen) = dialog
+        .set_file_name(&name)
+        .add_filter("Zip archive", &["zip"])
+        .blocking_save_file()
+    else {
+        return Ok(None);
+    };
+    let chosen = chosen
+        .into_path()
+        .map_err(|err| AppError::invalid_input(format!("Invalid bundle path: {err}")))?;
+    dialogs::remember(app, DialogPurpose::Exports, &chosen);
+    Ok(Some(chosen))
+}
+
+/// Asks where to save, then zips logs, redacted configs, version info, the last
+/// backend exit and any crash reports into one file to attach to a bug report.
+/// Resolves with the path, or `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn create_diagnostics_bundle(app: AppHandle) -> Result<Option<String>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some(dest) = pick_file(&app)? else {
+            return Ok(None);
+        };
+        pack(&app, &dest).map_err(AppError::from)?;
+        tracing::info!("[Diagnostics] Wrote {}", dest.display());
+        Ok(Some(dest.display().to_string()))
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Diagnostics task failed: {err}")))?
+}
+
+#[tauri::command]
+pub fn set_crash_reports(
+    store: tauri::State<SettingsStore>,
+    enabled: bool,
+) -> Result<DiagnosticsSettings, AppError> {
+    let settings = store.update(|settings| settings.diagnostics.crash_reports = enabled)?;
+    CRASH_REPORTS.store(enabled, Ordering::SeqCst);
+    Ok(settings.diagnostics)
+}
@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{error::AppError, settings::SettingsStore};
+
+/// What a native file dialog is for; each remembers its own last directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DialogPurpose {
+    Attachments,
+    Exports,
+    Backups,
+    Workspace,
+}
+
+/// Where a purpose starts before the user has picked anything.
+fn fallback<R: Runtime>(app: &AppHandle<R>, purpose: DialogPurpose) -> Option<PathBuf> {
+    let path = app.path();
+    match purpose {
+        DialogPurpose::Attachments => path.download_dir().ok(),
+        DialogPurpose::Exports | DialogPurpose::Backups => path.document_dir().ok(),
+        DialogPurpose::Workspace => None,
+    }
+    .or_else(|| path.home_dir().ok())
+}
+
+/// The folder a `purpose` dialog should open in: the last one used if it still
+/// exists, otherwise a sensible default for that purpose.
+pub fn default_dir<R: Runtime>(app: &AppHandle<R>, purpose: DialogPurpose) -> Option<PathBuf> {
+    app.try_state::<SettingsStore>()
+        .and_then(|store| store.get().dialog_dirs.get(&purpose).cloned())
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_dir())
+        .or_else(|| fallback(app, purpose))
+}
+
+/// Records where a `purpose` dialog ended up; a chosen file stands for its folder.
+pub fn remember<R: Runtime>(app: &AppHandle<R>, purpose: DialogPurpose, chosen: &Path) {
+    let dir = if chosen.is_dir() {
+        chosen
+    } else {
+        match chosen.parent() {
+            Some(parent) => parent,
+            None => return,
+        }
+    };
+    let Some(store) = app.try_state::<SettingsStore>() else {
+        return;
+    };
+    if store.get().dialog_dirs.get(&purpose).map(String::as_str) == dir.to_str() {
+        return;
+    }
+    let dir = dir.to_string_lossy().into_owned();
+    if let Err(err) = store.update(|settings| {
+        settings.dialog_dirs.insert(purpose, dir);
+    }) {
+        tracing::warn!("[Dialogs] {err}");
+    }
+}
+
+#[tauri::command]
+pub fn get_dialog_dir(app: AppHandle, purpose: DialogPurpose) -> Option<String> {
+    default_dir(&app, purpose).map(|dir| dir.to_string_lossy().into_owned())
+}
+
+/// Called by the webview after a dialog it opened returns a path.
+#[tauri::command]
+pub fn remember_dialog_dir(
+    app: AppHandle,
+    purpose: DialogPurpose,
+    path: String,
+) -> Result<(), AppError> {
+    let path = PathBuf::from(path.trim());
+    if !path.is_absolute() {
+        return Err(AppError::invalid_input("Dialog paths must be absolute."));
+    }
+    remember(&app, purpose, &path);
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use std::{fs, path::PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Local;
+use serde::Serialize;
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::{attachments, error::AppError};
+
+/// Mail clients start truncating or rejecting mailto links around this length.
+const MAX_MAILTO_LEN: usize = 2000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DraftMethod {
+    Mailto,
+    Eml,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailDraft {
+    method: DraftMethod,
+    path: Option<PathBuf>,
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'@' => {
+                (byte as char).to_string()
+            }
+            other => format!("%{other:02X}"),
+        })
+        .collect()
+}
+
+fn mailto_url(to: &[String], subject: &str, body: &str) -> String {
+    let recipients = to
+        .iter()
+        .map(|address| percent_encode(address.trim()))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        "mailto:{recipients}?subject={}&body={}",
+        percent_encode(subject),
+        percent_encode(&body.replace('\n', "\r\n"))
+    )
+}
+
+/// RFC 2047 encoded-word for header values that are not plain ASCII.
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        value.replace(['\r', '\n'], " ")
+    } else {
+        format!("=?UTF-8?B?{}?=", STANDARD.encode(value))
+    }
+}
+
+fn wrapped_base64(bytes: &[u8]) -> String {
+    let encoded = STANDARD.encode(bytes);
+    encoded
+        .as_bytes()
+        .chunks(76)
+        .map(|line| String::from_utf8_lossy(line).into_owned())
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+fn build_eml(
+    to: &[String],
+    subject: &str,
+    body: &str,
+    files: &[PathBuf],
+) -> Result<String, String> {
+    let boundary = format!(
+        "agent-draft-{}",
+        Local::now().timestamp_nanos_opt().unwrap_or_default()
+    );
+    let mut message = String::new();
+    message.push_str(&format!("To: {}\r\n", encode_header(&to.join(", "))));
+    message.push_str(&format!("Subject: {}\r\n", encode_header(subject)));
+    message.push_str(&format!("Date: {}\r\n", Local::now().to_rfc2822()));
+    // Outlook and Apple Mail open messages carrying this header as editable drafts.
+    message.push_str("X-Unsent: 1\r\n");
+    message.push_str("MIME-Version: 1.0\r\n");
+    message.push_str(&format!(
+        "Content-Type: multipart/mixed; boundary=\"{boundary}\"\r\n\r\n"
+    ));
+    message.push_str(&format!("--{boundary}\r\n"));
+    message.push_str("Content-Type: text/plain; charset=utf-8\r\n");
+    message.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+    message.push_str(&wrapped_base64(body.as_bytes()));
+    message.push_str("\r\n");
+    for file in files {
+        let bytes = fs::read(file)
+            .map_err(|err| format!("Failed to read attachment {}: {err}", file.display()))?;
+        let name = file
+            .file_name()
+            .map(|name| name.to_string_lossy().replace(['"', '\\'], "_"))
+            .unwrap_or_else(|| "attachment".to_string());
+        message.push_str(&format!("--{boundary}\r\n"));
+        message.push_str(&format!(
+            "Content-Type: {}; name=\"{}\"\r\n",
+            attachments::mime_for(&name),
+            encode_header(&name)
+        ));
+        message.push_str(&format!(
+            "Content-Disposition: attachment; filename=\"{}\"\r\n",
+            encode_header(&name)
+        ));
+        message.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+        message.push_str(&wrapped_base64(&bytes));
+        message.push_str("\r\n");
+    }
+    message.push_str(&format!("--{boundary}--\r\n"));
+    Ok(message)
+}
+
+fn open_eml(
+    app: &AppHandle,
+    to: &[String],
+    subject: &str,
+    body: &str,
+    files: &[PathBuf],
+) -> Result<EmailDraft, String> {
+    let dir = crate::resolve_app_data_dir(app)?.join("drafts");
+    fs::create_dir_all(&dir).map_err(|err| format!("Failed to create drafts directory: {err}"))?;
+    let path = dir.join(format!(
+        "draft-{}.eml",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::write(&path, build_eml(to, subject, body, files)?)
+        .map_err(|err| format!("Failed to write email draft: {err}"))?;
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|err| format!("Failed to open email draft: {err}"))?;
+    Ok(EmailDraft {
+        method: DraftMethod::Eml,
+        path: Some(path),
+    })
+}
+
+/// Opens a pre-filled draft in the default mail client. Uses a mailto link when possible
+/// and falls back to an `.eml` file when there are attachments or the link is too long.
+#[tauri::command]
+pub fn compose_email(
+    app: AppHandle,
+    to: Vec<String>,
+    subject: String,
+    body: String,
+    attachments: Option<Vec<String>>,
+) -> Result<EmailDraft, AppError> {
+    let files: Vec<PathBuf> = attachments
+        .unwrap_or_default()
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+    if let Some(missing) = files.iter().find(|file| !file.is_file()) {
+        return Err(AppError::not_found(format!(
+            "Attachment not found: {}.",
+            missing.display()
+        )));
+    }
+    if files.is_empty() {
+        let url = mailto_url(&to, &subject, &body);
+        if url.len() <= MAX_MAILTO_LEN {
+            match app.opener().open_url(url, None::<&str>) {
+                Ok(()) => {
+                    return Ok(EmailDraft {
+                        method: DraftMethod::Mailto,
+                        path: None,
+                    })
+                }
+                Err(err) => tracing::warn!("[Email] mailto failed, writing .eml instead: {err}"),
+            }
+        }
+    }
+    Ok(open_eml(&app, &to, &subject, &body, &files)?)
+}
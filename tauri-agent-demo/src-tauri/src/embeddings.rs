@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    error::{AppError, ErrorCode},
+    settings::SettingsStore,
+};
+
+/// Inputs sent per request; local servers slow down sharply past this.
+const BATCH_SIZE: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingSettings {
+    /// OpenAI-compatible API root of a local server, e.g. Ollama or llama.cpp.
+    pub base_url: String,
+    pub model: String,
+}
+
+impl Default for EmbeddingSettings {
+    fn default() -> Self {
+        Self {
+            base_url: "http://127.0.0.1:11434/v1".to_string(),
+            model: "nomic-embed-text".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// Embeds `texts` in order with the configured local model.
+pub async fn embed<R: Runtime>(
+    app: &AppHandle<R>,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, String> {
+    let settings = app.state::<SettingsStore>().get().embeddings;
+    let url = format!("{}/embeddings", settings.base_url.trim_end_matches('/'));
+    let client = reqwest::Client::new();
+    let mut vectors = Vec::with_capacity(texts.len());
+    for batch in texts.chunks(BATCH_SIZE) {
+        let mut response: EmbeddingResponse = client
+            .post(&url)
+            .json(&json!({ "model": settings.model, "input": batch }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| format!("Embedding request failed: {err}"))?
+            .json()
+            .await
+            .map_err(|err| format!("Failed to parse embeddings: {err}"))?;
+        if response.data.len() != batch.len() {
+            return Err(format!(
+                "Embedding server returned {} vectors for {} inputs.",
+                response.data.len(),
+                batch.len()
+            ));
+        }
+        response.data.sort_by_key(|item| item.index);
+        vectors.extend(response.data.into_iter().map(|item| item.embedding));
+    }
+    Ok(vectors)
+}
+
+#[tauri::command]
+pub fn get_embedding_settings(store: tauri::State<SettingsStore>) -> EmbeddingSettings {
+    store.get().embeddings
+}
+
+#[tauri::command]
+pub fn set_embedding_settings(
+    store: tauri::State<SettingsStore>,
+    settings: EmbeddingSettings,
+) -> Result<(), AppError> {
+    if settings.model.trim().is_empty() {
+        return Err(AppError::invalid_input("Choose an embedding model."));
+    }
+    reqwest::Url::parse(&settings.base_url).map_err(|err| {
+        AppError::invalid_input("Invalid embedding server URL.").with_details(err)
+    })?;
+    store.update(|current| current.embeddings = settings)?;
+    Ok(())
+}
+
+/// Embeds one query, for callers of `query_similar`.
+#[tauri::command]
+pub async fn embed_text(app: AppHandle, text: String) -> Result<Vec<f32>, AppError> {
+    embed(&app, &[text])
+        .await
+        .map_err(|err| AppError::new(ErrorCode::Network, err))?
+        .pop()
+        .ok_or_else(|| AppError::new(ErrorCode::Network, "Embedding server returned no vector."))
+}
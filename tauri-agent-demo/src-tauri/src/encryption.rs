@@ -0,0 +1,478 @@
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use argon2::Argon2;
+use keyring::Entry;
+use ring::{
+    digest::{digest, SHA256},
+    rand::{SecureRandom, SystemRandom},
+};
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{
+    app_lock::{self, LockReason},
+    backend,
+    error::{AppError, ErrorCode},
+    events, locale, main_window, search,
+    settings::SettingsStore,
+    sidecar, workspace, BackendState, BACKEND_SIDECAR,
+};
+
+/// The sidecar opens its database with SQLCipher while this holds the hex key.
+pub const KEY_ENV: &str = "TAURI_AGENT_DB_KEY";
+pub const UNLOCK_LABEL: &str = "unlock";
+/// Beside the secrets, under the app identifier; secret names are upper-case, so
+/// they cannot clash.
+const KEYCHAIN_ENTRY: &str = "database-key";
+/// Plain SQLite files start with this; SQLCipher ones look random from byte 0.
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+const MIN_PASSPHRASE_CHARS: usize = 8;
+const KEY_BYTES: usize = 32;
+const SALT_BYTES: usize = 16;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncryptionSettings {
+    pub enabled: bool,
+    /// Argon2 salt of the current passphrase, hex.
+    pub salt: Option<String>,
+    /// SHA-256 of the derived key, to check a passphrase before any database is touched.
+    pub key_check: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EncryptionStatus {
+    enabled: bool,
+    /// Encrypted and without its key: the backend is stopped until unlocked.
+    locked: bool,
+}
+
+/// The derived key while unlocked. A static rather than managed state, since the
+/// shell's readers of the chat database open it from a bare path.
+static KEY: Mutex<Option<String>> = Mutex::new(None);
+
+fn current_key() -> Option<String> {
+    KEY.lock().ok().and_then(|key| key.clone())
+}
+
+fn set_key(key: Option<String>) {
+    if let Ok(mut current) = KEY.lock() {
+        *current = key;
+    }
+}
+
+fn settings<R: Runtime>(app: &AppHandle<R>) -> EncryptionSettings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().encryption)
+        .unwrap_or_default()
+}
+
+/// Encryption is on and the key is not loaded; only the passphrase unlocks then.
+pub fn is_locked<R: Runtime>(app: &AppHandle<R>) -> bool {
+    settings(app).enabled && current_key().is_none()
+}
+
+/// A passphrase is set, so it can unlock the app as well as the database.
+pub fn has_passphrase<R: Runtime>(app: &AppHandle<R>) -> bool {
+    settings(app).enabled
+}
+
+fn status<R: Runtime>(app: &AppHandle<R>) -> EncryptionStatus {
+    EncryptionStatus {
+        enabled: settings(app).enabled,
+        locked: is_locked(app),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(text.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+fn derive(passphrase: &str, salt: &[u8]) -> Result<String, AppError> {
+    let mut key = [0u8; KEY_BYTES];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| AppError::from(format!("Failed to derive the database key: {err}")))?;
+    Ok(hex(&key))
+}
+
+fn key_check(key: &str) -> String {
+    hex(digest(&SHA256, key.as_bytes()).as_ref())
+}
+
+fn validate_passphrase(passphrase: &str) -> Result<(), AppError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(AppError::invalid_input(format!(
+            "The passphrase needs at least {MIN_PASSPHRASE_CHARS} characters."
+        )));
+    }
+    Ok(())
+}
+
+/// The key for `passphrase` under the stored salt, if it is the right one.
+fn verify(settings: &EncryptionSettings, passphrase: &str) -> Result<String, AppError> {
+    let salt = settings
+        .salt
+        .as_deref()
+        .and_then(unhex)
+        .ok_or_else(|| AppError::unavailable("The encryption settings are incomplete."))?;
+    let key = derive(passphrase, &salt)?;
+    if settings.key_check.as_deref() != Some(key_check(&key).as_str()) {
+        return Err(AppError::new(
+            ErrorCode::Unauthorized,
+            "The passphrase is wrong.",
+        ));
+    }
+    Ok(key)
+}
+
+fn entry<R: Runtime>(app: &AppHandle<R>) -> Result<Entry, AppError> {
+    Entry::new(&app.config().identifier, KEYCHAIN_ENTRY).map_err(|err| {
+        AppError::unavailable("The system keychain is unavailable.").with_details(err)
+    })
+}
+
+fn remember<R: Runtime>(app: &AppHandle<R>, key: &str) -> Result<(), AppError> {
+    entry(app)?.set_password(key).map_err(|err| {
+        AppError::unavailable("Failed to store the database key.").with_details(err)
+    })?;
+    set_key(Some(key.to_string()));
+    Ok(())
+}
+
+fn forget<R: Runtime>(app: &AppHandle<R>) -> Result<(), AppError> {
+    set_key(None);
+    match entry(app)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => {
+            Err(AppError::unavailable("Failed to remove the database key.").with_details(err))
+        }
+    }
+}
+
+/// Whether `path` is an ordinary SQLite file. Missing and empty files are not, so a
+/// database created while encryption is on starts out encrypted.
+fn is_plaintext(path: &Path) -> bool {
+    let mut header = [0u8; SQLITE_HEADER.len()];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|_| &header == SQLITE_HEADER)
+}
+
+fn apply_key(conn: &Connection, key: &str) -> rusqlite::Result<()> {
+    conn.execute_batch(&format!("PRAGMA key = \"x'{key}'\";"))
+}
+
+/// Opens a chat database, keyed when it is encrypted. Everything in the shell that
+/// reads the backend's database goes through here.
+pub fn open(path: &Path, flags: OpenFlags) -> rusqlite::Result<Connection> {
+    let key = current_key().filter(|_| !is_plaintext(path));
+    let conn = Connection::open_with_flags(path, flags)?;
+    if let Some(key) = key {
+        apply_key(&conn, &key)?;
+    }
+    Ok(conn)
+}
+
+fn remove_wal(path: &Path) {
+    for suffix in ["-wal", "-shm"] {
+        let mut file = path.as_os_str().to_owned();
+        file.push(suffix);
+        let _ = fs::remove_file(PathBuf::from(file));
+    }
+}
+
+/// Rewrites `path` under `to`, reading it with `from` or as plain SQLite. The copy
+/// is built beside it and renamed over, so a failure leaves the original as it was.
+fn rewrite(path: &Path, from: Option<&str>, to: &str) -> Result<(), String> {
+    let mut scratch = path.as_os_str().to_owned();
+    scratch.push(".encrypting");
+    let scratch = PathBuf::from(scratch);
+    let _ = fs::remove_file(&scratch);
+    let exported = (|| -> rusqlite::Result<()> {
+        let conn = Connection::open(path)?;
+        if let Some(from) = from {
+            apply_key(&conn, from)?;
+        }
+        conn.execute(
+            "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+            (scratch.to_string_lossy(), format!("x'{to}'")),
+        )?;
+        conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))?;
+        conn.execute_batch("DETACH DATABASE encrypted")
+    })();
+    if let Err(err) = exported {
+        let _ = fs::remove_file(&scratch);
+        return Err(format!("Failed to encrypt {}: {err}", path.display()));
+    }
+    remove_wal(path);
+    fs::rename(&scratch, path).map_err(|err| {
+        format!(
+            "Failed to replace {} with its encrypted copy: {err}",
+            path.display()
+        )
+    })
+}
+
+/// Encrypts `path` with `key` unless it already is; called with the backend stopped.
+fn encrypt(path: &Path, key: &str) -> Result<(), String> {
+    if !is_plaintext(path) {
+        return Ok(());
+    }
+    rewrite(path, None, key)?;
+    tracing::info!("[Encryption] Encrypted {}.", path.display());
+    Ok(())
+}
+
+/// The key for the sidecar's environment, or `None` with encryption off. A workspace
+/// database still in plain SQLite is encrypted first; the backend is not running
+/// while its spec is built.
+pub fn sidecar_key<R: Runtime>(app: &AppHandle<R>) -> Result<Option<String>, String> {
+    if !settings(app).enabled {
+        return Ok(None);
+    }
+    let key = current_key().ok_or_else(|| "The chat database is locked.".to_string())?;
+    encrypt(&workspace::db_path(app)?, &key)?;
+    Ok(Some(key))
+}
+
+/// Loads the key from the keychain at startup. Returns true while encryption is on
+/// and the key is not there, which means the passphrase has to be entered first.
+pub fn init<R: Runtime>(app: &AppHandle<R>) -> bool {
+    if !settings(app).enabled {
+        return false;
+    }
+    let key = entry(app).and_then(|entry| {
+        entry.get_password().map_err(|err| {
+            AppError::unavailable("Failed to read the database key.").with_details(err)
+        })
+    });
+    match key {
+        Ok(key) => {
+            set_key(Some(key));
+            false
+        }
+        Err(err) => {
+            tracing::info!("[Encryption] Chat database is locked: {err}");
+            true
+        }
+    }
+}
+
+/// Asks for the passphrase, in place of the main window until the backend can start.
+pub fn open_unlock_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window(UNLOCK_LABEL) {
+        let _ = window.set_focus();
+        return;
+    }
+    let built = WebviewWindowBuilder::new(
+        app,
+        UNLOCK_LABEL,
+        WebviewUrl::App("index.html?window=unlock".into()),
+    )
+    .title(locale::t(app, "window.unlock"))
+    .inner_size(420.0, 300.0)
+    .resizable(false)
+    .center()
+    .build();
+    if let Err(err) = built {
+        tracing::warn!("[Encryption] Failed to open the unlock window: {err}");
+    }
+}
+
+/// Closing the unlock window before the app ever got going quits it, since there
+/// is nothing to show without the database.
+pub fn unlock_window_closed<R: Runtime>(app: &AppHandle<R>) {
+    let started = app
+        .get_webview_window(main_window::LABEL)
+        .is_some_and(|window| window.is_visible().unwrap_or(false));
+    if is_locked(app) && !started {
+        app.exit(0);
+    }
+}
+
+/// Starts the sidecar again after an unlock; a startup held back by the lock ended
+/// as failed, which `retry` picks up.
+fn resume_backend<R: Runtime>(app: &AppHandle<R>) -> Result<(), AppError> {
+    let remote = app
+        .try_state::<BackendState>()
+        .is_some_and(|state| state.remote_url().is_some());
+    if remote {
+        return Ok(());
+    }
+    backend::retry(app)?;
+    if !sidecar::is_running(app, BACKEND_SIDECAR) {
+        crate::start_backend(app)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_encryption_status(app: AppHandle) -> EncryptionStatus {
+    status(&app)
+}
+
+/// Turns encryption on: derives the key from `passphrase`, keeps it in the keychain
+/// and encrypts the active workspace's database with the backend stopped. Other
+/// workspaces are encrypted the next time the backend starts on them; existing
+/// backups and snapshots are left as they are.
+#[tauri::command]
+pub async fn enable_encryption(
+    app: AppHandle,
+    passphrase: String,
+) -> Result<EncryptionStatus, AppError> {
+    if settings(&app).enabled {
+        return Err(AppError::invalid_input(
+            "Encryption is already on; change the passphrase instead.",
+        ));
+    }
+    validate_passphrase(&passphrase)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut salt = [0u8; SALT_BYTES];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .map_err(|_| AppError::from("Failed to generate a salt.".to_string()))?;
+        let key = derive(&passphrase, &salt)?;
+        remember(&app, &key)?;
+        app.state::<SettingsStore>().update(|settings| {
+            settings.encryption = EncryptionSettings {
+                enabled: true,
+                salt: Some(hex(&salt)),
+                key_check: Some(key_check(&key)),
+            };
+        })?;
+        let db_path = workspace::db_path(&app)?;
+        crate::with_backend_stopped(&app, || encrypt(&db_path, &key).map_err(AppError::from))?;
+        // The search index holds the same text in plain SQLite.
+        search::discard(&app);
+        tracing::info!("[Encryption] Encryption is on.");
+        Ok(status(&app))
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Encryption task failed: {err}")))?
+}
+
+/// Re-encrypts every workspace's database under a key from `new_passphrase`.
+#[tauri::command]
+pub async fn change_passphrase(
+    app: AppHandle,
+    current_passphrase: String,
+    new_passphrase: String,
+) -> Result<EncryptionStatus, AppError> {
+    let current = settings(&app);
+    if !current.enabled {
+        return Err(AppError::invalid_input("Encryption is off."));
+    }
+    validate_passphrase(&new_passphrase)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let old_key = verify(&current, &current_passphrase)?;
+        let mut salt = [0u8; SALT_BYTES];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .map_err(|_| AppError::from("Failed to generate a salt.".to_string()))?;
+        let new_key = derive(&new_passphrase, &salt)?;
+        crate::with_backend_stopped(&app, || {
+            for path in workspace::db_paths(&app) {
+                if !is_plaintext(&path) && path.is_file() {
+                    rewrite(&path, Some(&old_key), &new_key)?;
+                }
+            }
+            app.state::<SettingsStore>().update(|settings| {
+                settings.encryption.salt = Some(hex(&salt));
+                settings.encryption.key_check = Some(key_check(&new_key));
+            })?;
+            // Before the respawn, which goes by the key in memory.
+            remember(&app, &new_key)
+        })?;
+        search::discard(&app);
+        tracing::info!("[Encryption] Passphrase changed.");
+        Ok(status(&app))
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Passphrase task failed: {err}")))?
+}
+
+/// Locks the windows and, with encryption on, removes the key from the keychain and
+/// stops the backend, so nothing can read the database until `unlock_database`.
+#[tauri::command]
+pub async fn lock_app(app: AppHandle) -> Result<EncryptionStatus, AppError> {
+    app_lock::lock(&app, LockReason::Manual);
+    if !settings(&app).enabled {
+        return Ok(status(&app));
+    }
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        forget(&handle)?;
+        if crate::runs_sidecar(&handle) && sidecar::stop(&handle, BACKEND_SIDECAR)? {
+            events::backend_stopped(&handle, &events::Empty {});
+        }
+        Ok::<_, AppError>(())
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Lock task failed: {err}")))??;
+    tracing::info!("[Encryption] Locked; the database key was removed from the keychain.");
+    open_unlock_window(&app);
+    Ok(status(&app))
+}
+
+/// Checks `passphrase`; while the database is locked, also puts its key back in the
+/// keychain and starts the backend. Blocks for the key derivation.
+pub fn unlock_with_passphrase<R: Runtime>(
+    app: &AppHandle<R>,
+    passphrase: &str,
+) -> Result<(), AppError> {
+    let current = settings(app);
+    if !current.enabled {
+        return Err(AppError::unavailable("No passphrase is set."));
+    }
+    let key = verify(&current, passphrase)?;
+    if current_key().is_none() {
+        remember(app, &key)?;
+        resume_backend(app)?;
+    }
+    if let Some(window) = app.get_webview_window(UNLOCK_LABEL) {
+        let _ = window.close();
+    }
+    Ok(())
+}
+
+/// Checks `passphrase` and holds its key for this process only, leaving the
+/// keychain alone; for headless runs while the app is locked.
+pub fn unlock_for_run<R: Runtime>(app: &AppHandle<R>, passphrase: &str) -> Result<(), AppError> {
+    let key = verify(&settings(app), passphrase)?;
+    set_key(Some(key));
+    Ok(())
+}
+
+/// Checks `passphrase`, puts the key back in the keychain and starts the backend.
+#[tauri::command]
+pub async fn unlock_database(
+    app: AppHandle,
+    passphrase: String,
+) -> Result<EncryptionStatus, AppError> {
+    if !settings(&app).enabled {
+        return Ok(status(&app));
+    }
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || unlock_with_passphrase(&handle, &passphrase))
+        .await
+        .map_err(|err| AppError::from(format!("Unlock task failed: {err}")))??;
+    app_lock::release(&app);
+    Ok(status(&app))
+}
@@ -0,0 +1,119 @@
+use std::fmt;
+
+use serde::Serialize;
+
+/// What went wrong, so the webview can branch without matching on message text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// A sidecar failed to launch.
+    Spawn,
+    /// A sidecar binary does not match the checksum recorded at build time.
+    Integrity,
+    /// Shell settings could not be read or saved.
+    Settings,
+    Database,
+    /// A tool or plugin could not be installed, registered or changed.
+    Tool,
+    /// The backend or another service could not be reached.
+    Network,
+    NotFound,
+    InvalidInput,
+    /// Blocked by kiosk mode.
+    ReadOnly,
+    /// The OS could not verify the user.
+    Unauthorized,
+    /// The user or the OS has not allowed it, as with screen recording or a denied
+    /// tool prompt.
+    Permission,
+    /// Managed state is missing or poisoned.
+    Unavailable,
+    Internal,
+}
+
+impl ErrorCode {
+    fn retryable(self) -> bool {
+        matches!(self, Self::Spawn | Self::Network | Self::Unavailable)
+    }
+}
+
+/// Error returned by commands; serialized as
+/// `{ code, message, details, retryable }`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AppError {
+    pub code: ErrorCode,
+    /// Short sentence fit to show as-is.
+    pub message: String,
+    /// Underlying cause, for logs and an expandable "details" view.
+    pub details: Option<String>,
+    /// Trying the same call again may succeed.
+    pub retryable: bool,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+            retryable: code.retryable(),
+        }
+    }
+
+    pub fn with_details(mut self, details: impl fmt::Display) -> Self {
+        self.details = Some(details.to_string());
+        self
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn unavailable(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Unavailable, message)
+    }
+
+    pub fn tool(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Tool, message)
+    }
+
+    /// A SQLite failure; busy and locked databases are worth retrying.
+    pub fn database(context: &str, err: rusqlite::Error) -> Self {
+        let busy = matches!(
+            err.sqlite_error_code(),
+            Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked)
+        );
+        let mut error = Self::new(ErrorCode::Database, format!("{context}.")).with_details(err);
+        error.retryable = busy;
+        error
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.details {
+            Some(details) => write!(f, "{} ({details})", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+/// Helpers that still report plain strings surface as internal errors.
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+}
+
+/// Lets `?` carry an `AppError` out of the helpers that still return strings.
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.to_string()
+    }
+}
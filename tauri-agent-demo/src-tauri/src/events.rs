@@ -0,0 +1,539 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Runtime};
+
+use crate::{
+    app_lock::LockEvent,
+    app_menu::MenuActionEvent,
+    appearance::{ThemeInfo, WindowEffectChanged},
+    audio::{AudioLevel, TranscriptEvent},
+    backend::BackendStatus,
+    backend_log::BackendError,
+    backend_profiles::BackendProfileInfo,
+    backup::BackupStatus,
+    capture::CaptureError,
+    chat_stream::{ChatDelta, ChatDone},
+    compat::VersionReport,
+    config_files::ConfigChanged,
+    connectivity::ConnectivityStatus,
+    costs::BudgetThresholdEvent,
+    db_backup::{BackupProgress, DatabaseBackup},
+    deep_link::DeepLink,
+    desktop::MediaKeyEvent,
+    export::ExportProgress,
+    file_drop::{AttachmentAdded, AttachmentRejected},
+    health::HealthSample,
+    idle::IdleChanged,
+    import::ImportProgress,
+    inbox::InboxFileEvent,
+    indexer::{IndexingError, IndexingProgress},
+    instance::SecondLaunch,
+    locale::LocaleInfo,
+    migration::{MigrationSummary, OnboardingProgress},
+    monitor::{BackendMetrics, CeilingExceeded},
+    notifications::{NavigateEvent, NotificationActionEvent},
+    outbox::OutboxStatusEvent,
+    python_runtime::SetupProgress,
+    quick_ask::OpenConversation,
+    rpc::RpcEvent,
+    scheduler::ScheduledRun,
+    sessions::SessionList,
+    shortcuts::{ShortcutBindings, ShortcutTriggered},
+    sidecar::{SidecarCrash, SidecarEvent, SidecarRestart},
+    snapshots::SnapshotInfo,
+    speech::SpeechFinished,
+    startup::StageUpdate,
+    taskbar::TaskControlApplied,
+    tool_policy::PermissionPrompt,
+    updater::{UpdateInfo, UpdateProgress},
+    watchdog::RunKilled,
+    workspace::WorkspaceInfo,
+    BackendEndpoint, BaseUrl,
+};
+
+/// The frontend's copy of the payload types, checked against these by `cargo test`.
+#[cfg(test)]
+const TYPESCRIPT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../src/events.gen.ts");
+
+/// A type as the webview sees it once serde is done with it.
+#[cfg(test)]
+pub trait TsType {
+    /// How a field of this type is written.
+    fn ts() -> String;
+    /// Adds the declarations `ts()` refers to, this type's own after its fields'.
+    fn declare(_out: &mut Vec<(String, String)>) {}
+}
+
+#[cfg(test)]
+macro_rules! ts_primitive {
+    ($($ty:ty => $ts:literal),* $(,)?) => {
+        $(impl TsType for $ty {
+            fn ts() -> String {
+                $ts.to_string()
+            }
+        })*
+    };
+}
+
+#[cfg(test)]
+ts_primitive!(
+    String => "string",
+    str => "string",
+    std::path::PathBuf => "string",
+    bool => "boolean",
+    u8 => "number",
+    u16 => "number",
+    u32 => "number",
+    u64 => "number",
+    usize => "number",
+    i32 => "number",
+    i64 => "number",
+    f32 => "number",
+    f64 => "number",
+    serde_json::Value => "any",
+);
+
+#[cfg(test)]
+impl<T: TsType + ?Sized> TsType for &T {
+    fn ts() -> String {
+        T::ts()
+    }
+
+    fn declare(out: &mut Vec<(String, String)>) {
+        T::declare(out)
+    }
+}
+
+#[cfg(test)]
+impl TsType for tauri::Theme {
+    fn ts() -> String {
+        "Theme".to_string()
+    }
+
+    fn declare(out: &mut Vec<(String, String)>) {
+        if !is_declared(out, "Theme") {
+            out.push((
+                "Theme".to_string(),
+                "export type Theme = 'light' | 'dark';".to_string(),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+impl<T: TsType> TsType for Option<T> {
+    fn ts() -> String {
+        format!("{} | null", T::ts())
+    }
+
+    fn declare(out: &mut Vec<(String, String)>) {
+        T::declare(out)
+    }
+}
+
+#[cfg(test)]
+impl<T: TsType> TsType for Vec<T> {
+    fn ts() -> String {
+        let item = T::ts();
+        if item.contains(' ') {
+            format!("({item})[]")
+        } else {
+            format!("{item}[]")
+        }
+    }
+
+    fn declare(out: &mut Vec<(String, String)>) {
+        T::declare(out)
+    }
+}
+
+/// `ChatDone` to `chat_done`, the way `rename_all = "snake_case"` spells variants.
+#[cfg(test)]
+pub fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (index, ch) in name.chars().enumerate() {
+        if ch.is_uppercase() && index > 0 {
+            out.push('_');
+        }
+        out.extend(ch.to_lowercase());
+    }
+    out
+}
+
+/// A TypeScript comment from the lines of a doc comment, indented by `indent`.
+#[cfg(test)]
+pub fn doc_comment(lines: &[&str], indent: &str) -> String {
+    match lines {
+        [] => String::new(),
+        [line] => format!("{indent}/** {} */\n", line.trim()),
+        _ => {
+            let mut out = format!("{indent}/**\n");
+            for line in lines {
+                out.push_str(&format!("{indent} * {}\n", line.trim()).replace(" \n", "\n"));
+            }
+            out.push_str(&format!("{indent} */\n"));
+            out
+        }
+    }
+}
+
+#[cfg(test)]
+pub fn is_declared(out: &[(String, String)], name: &str) -> bool {
+    out.iter().any(|(declared, _)| declared == name)
+}
+
+/// Declares a struct sent to the webview, with its TypeScript interface. Fields take
+/// doc comments only, since a serde attribute would change the shape the
+/// interface describes.
+macro_rules! payload {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[doc = $doc:literal])* $field_vis:vis $field:ident: $ty:ty,)*
+        }
+    ) => {
+        #[derive(Debug, Clone, serde::Serialize)]
+        $(#[$meta])*
+        $vis struct $name {
+            $($(#[doc = $doc])* $field_vis $field: $ty,)*
+        }
+
+        #[cfg(test)]
+        impl $crate::events::TsType for $name {
+            fn ts() -> String {
+                stringify!($name).to_string()
+            }
+
+            fn declare(out: &mut Vec<(String, String)>) {
+                if $crate::events::is_declared(out, stringify!($name)) {
+                    return;
+                }
+                $(<$ty as $crate::events::TsType>::declare(out);)*
+                let body = <[String]>::concat(&[$(format!(
+                    "{}    {}: {};\n",
+                    $crate::events::doc_comment(&[$($doc),*], "    "),
+                    stringify!($field),
+                    <$ty as $crate::events::TsType>::ts()
+                )),*]);
+                out.push((
+                    stringify!($name).to_string(),
+                    format!("export interface {} {{\n{body}}}", stringify!($name)),
+                ));
+            }
+        }
+    };
+}
+
+/// Declares a fieldless enum sent to the webview as snake_case strings, with its
+/// TypeScript union.
+macro_rules! payload_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident,)*
+        }
+    ) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+        #[serde(rename_all = "snake_case")]
+        $(#[$meta])*
+        $vis enum $name {
+            $($(#[$variant_meta])* $variant,)*
+        }
+
+        #[cfg(test)]
+        impl $crate::events::TsType for $name {
+            fn ts() -> String {
+                stringify!($name).to_string()
+            }
+
+            fn declare(out: &mut Vec<(String, String)>) {
+                if $crate::events::is_declared(out, stringify!($name)) {
+                    return;
+                }
+                let variants: Vec<String> = [$(stringify!($variant)),*]
+                    .iter()
+                    .map(|variant| format!("'{}'", $crate::events::snake_case(variant)))
+                    .collect();
+                out.push((
+                    stringify!($name).to_string(),
+                    format!("export type {} = {};", stringify!($name), variants.join(" | ")),
+                ));
+            }
+        }
+    };
+}
+
+pub(crate) use {payload, payload_enum};
+
+/// What goes over the wire: the payload's fields next to its schema version, which
+/// is bumped whenever a field is renamed, retyped or dropped.
+#[derive(Clone, Serialize)]
+struct Versioned<'a, T> {
+    schema_version: u32,
+    #[serde(flatten)]
+    payload: &'a T,
+}
+
+payload! {
+    /// For events that carry nothing beyond their name and schema version.
+    pub struct Empty {}
+}
+
+fn emit<R: Runtime, T: Serialize + Clone>(
+    app: &AppHandle<R>,
+    name: &str,
+    version: u32,
+    payload: &T,
+) {
+    let versioned = Versioned {
+        schema_version: version,
+        payload,
+    };
+    if let Err(err) = app.emit(name, versioned) {
+        tracing::warn!("[Events] Failed to emit {name}: {err}");
+    }
+}
+
+fn emit_to<R: Runtime, T: Serialize + Clone>(
+    app: &AppHandle<R>,
+    window: &str,
+    name: &str,
+    version: u32,
+    payload: &T,
+) {
+    let versioned = Versioned {
+        schema_version: version,
+        payload,
+    };
+    if let Err(err) = app.emit_to(window, name, versioned) {
+        tracing::warn!("[Events] Failed to emit {name} to {window}: {err}");
+    }
+}
+
+#[cfg(test)]
+struct EventSpec {
+    name: &'static str,
+    version: u32,
+    doc: &'static [&'static str],
+    payload: fn() -> String,
+    declare: fn(&mut Vec<(String, String)>),
+}
+
+/// One emitter per event; those marked `window` go to a single window, named by
+/// the caller. Some events only fire on one platform.
+macro_rules! event_fn {
+    ($(#[doc = $doc:literal])* $emit:ident, $name:literal, $version:literal, $payload:ty) => {
+        $(#[doc = $doc])*
+        #[allow(dead_code)]
+        pub fn $emit<R: Runtime>(app: &AppHandle<R>, payload: &$payload) {
+            emit(app, $name, $version, payload)
+        }
+    };
+    ($(#[doc = $doc:literal])* $emit:ident, $name:literal, $version:literal, $payload:ty, window) => {
+        $(#[doc = $doc])*
+        #[allow(dead_code)]
+        pub fn $emit<R: Runtime>(app: &AppHandle<R>, window: &str, payload: &$payload) {
+            emit_to(app, window, $name, $version, payload)
+        }
+    };
+}
+
+macro_rules! events {
+    ($(
+        $(#[doc = $doc:literal])*
+        $emit:ident($name:literal, $version:literal $(, $target:ident)?): $payload:ty;
+    )*) => {
+        $(event_fn!($(#[doc = $doc])* $emit, $name, $version, $payload $(, $target)?);)*
+
+        #[cfg(test)]
+        const EVENTS: &[EventSpec] = &[$(EventSpec {
+            name: $name,
+            version: $version,
+            doc: &[$($doc),*],
+            payload: <$payload as TsType>::ts,
+            declare: <$payload as TsType>::declare,
+        }),*];
+    };
+}
+
+events! {
+    /// The windows were locked; their contents stay hidden until `app-lock://unlocked`.
+    app_locked("app-lock://locked", 1): LockEvent;
+    app_unlocked("app-lock://unlocked", 1): Empty;
+    /// The window effect was applied, so the webview can make its background translucent.
+    window_effect_changed("appearance://window-effect", 1): WindowEffectChanged;
+    /// A file dropped on the main window was staged for the composer.
+    attachment_added("attachment://added", 1, window): AttachmentAdded;
+    attachment_rejected("attachment://rejected", 1, window): AttachmentRejected;
+    /// A level per 100ms of input while recording.
+    audio_level("audio-level", 1): AudioLevel;
+    /// The recording hit its silence timeout; the webview finishes it with `stop_recording`.
+    audio_silence("audio-silence", 1): Empty;
+    audio_transcript("audio://transcript", 1): TranscriptEvent;
+    /// A known failure recognised in the backend's output.
+    backend_error("backend-error", 1): BackendError;
+    /// Relayed hub events from a stdio backend, in place of the WebSocket.
+    backend_rpc_event("backend-rpc://event", 1): RpcEvent;
+    /// The address the webview should call moved, e.g. after a restart on a new port.
+    backend_base_url("backend://base-url", 1): BaseUrl;
+    /// The bundled backend exited on its own.
+    backend_crashed("backend://crashed", 1): SidecarCrash;
+    /// Startup gave up on the backend.
+    backend_failed("backend://failed", 1): BackendStatus;
+    /// Another backend profile was selected.
+    backend_profile("backend://profile", 1): BackendProfileInfo;
+    /// Startup is over and the backend answered `/health`.
+    backend_ready("backend://ready", 1): BackendStatus;
+    /// The bundled backend was brought back after a crash.
+    backend_restarted("backend://restarted", 1): SidecarRestart;
+    /// The bundled backend was stopped on purpose.
+    backend_stopped("backend://stopped", 1): Empty;
+    /// How to reach the backend changed; carries the whole endpoint.
+    backend_url("backend://url", 1): BackendEndpoint;
+    /// The backend answered the version handshake.
+    backend_version("backend://version", 1): VersionReport;
+    backup_status("backup://status", 1): BackupStatus;
+    /// Spending crossed one of the budget's warning thresholds.
+    budget_threshold("budget-threshold", 1): BudgetThresholdEvent;
+    capture_error("capture://error", 1): CaptureError;
+    capture_started("capture://started", 1): Empty;
+    capture_stopped("capture://stopped", 1): Empty;
+    /// One SSE event of a stream the shell reads for the webview.
+    chat_delta("chat://delta", 1): ChatDelta;
+    /// A stream the shell reads for the webview ended.
+    chat_done("chat://done", 1): ChatDone;
+    /// A backend config file was changed by something other than the shell.
+    config_changed("config://changed", 1): ConfigChanged;
+    database_backup_progress("database://backup-progress", 1): BackupProgress;
+    /// The database was restored; carries the backup taken of it beforehand.
+    database_restored("database://restored", 1): DatabaseBackup;
+    /// An `agentapp://` link arrived while the main window was listening.
+    deeplink_open("deeplink://open", 1, window): DeepLink;
+    export_progress("export://progress", 1): ExportProgress;
+    /// A proxied request reported finishing, for the health panel.
+    health_sample("health://sample", 1): HealthSample;
+    idle_changed("idle://changed", 1): IdleChanged;
+    import_progress("import://progress", 1): ImportProgress;
+    /// A file dropped in the inbox folder was staged as an attachment.
+    inbox_file_staged("inbox://file-staged", 1): InboxFileEvent;
+    indexing_error("indexing://error", 1): IndexingError;
+    /// The indexing queue drained.
+    indexing_idle("indexing://idle", 1): Empty;
+    indexing_progress("indexing://progress", 1): IndexingProgress;
+    /// The app was launched again while running; the main window gets its arguments.
+    second_launch("instance://second-launch", 1, window): SecondLaunch;
+    locale_changed("locale://changed", 1): LocaleInfo;
+    /// An application menu item the frontend carries out.
+    menu_action("menu://action", 1, window): MenuActionEvent;
+    /// The backend went over its memory ceiling and is being restarted.
+    metrics_ceiling_exceeded("metrics://ceiling-exceeded", 1): CeilingExceeded;
+    metrics_sample("metrics://sample", 1): BackendMetrics;
+    /// A migration archive was restored into this install.
+    migration_restored("migration://restored", 1): MigrationSummary;
+    /// Media keys grabbed for the app on Linux.
+    media_key("media-key", 1): MediaKeyEvent;
+    net_offline("net://offline", 1): ConnectivityStatus;
+    net_online("net://online", 1): ConnectivityStatus;
+    notification_action("notification://action", 1): NotificationActionEvent;
+    /// A notification about a conversation was acted on.
+    notification_navigate("notification://navigate", 1, window): NavigateEvent;
+    onboarding_progress("onboarding://progress", 1, window): OnboardingProgress;
+    /// A queued chat request moved through the outbox.
+    outbox_status("outbox://status", 1): OutboxStatusEvent;
+    /// The agent asked for a tool and the approval dialog is open.
+    permission_request("permission://request", 1): PermissionPrompt;
+    python_runtime_progress("python-runtime://progress", 1): SetupProgress;
+    /// Open a conversation from the quick ask window in the main one.
+    quick_ask_open("quick-ask://open", 1, window): OpenConversation;
+    /// The quick ask window is about to be shown again.
+    quick_ask_refresh("quick-ask://refresh", 1, window): Empty;
+    /// The tray's quick reply popup is about to be shown again.
+    quick_reply_refresh("quick-reply://refresh", 1, window): Empty;
+    /// A scheduled task started or ended a run.
+    scheduler_run("scheduler://run", 1): ScheduledRun;
+    /// A session was created or terminated; carries all of them.
+    sessions_changed("sessions://changed", 1): SessionList;
+    /// Bindings changed; carries all of them.
+    shortcuts_changed("shortcut://changed", 1): ShortcutBindings;
+    /// A global shortcut fired for an action the webview carries out.
+    shortcut_triggered("shortcut://triggered", 1): ShortcutTriggered;
+    sidecar_crashed("sidecar://crashed", 1): SidecarCrash;
+    sidecar_failed("sidecar://failed", 1): SidecarEvent;
+    sidecar_ready("sidecar://ready", 1): SidecarEvent;
+    sidecar_restarted("sidecar://restarted", 1): SidecarRestart;
+    snapshot_restored("snapshot://restored", 1): SnapshotInfo;
+    /// Launch moved to another stage, for the splash window.
+    startup_stage("startup://stage", 1): StageUpdate;
+    /// A taskbar or dock button paused or aborted a run.
+    task_control_applied("task-control://applied", 1): TaskControlApplied;
+    theme_changed("theme://changed", 1): ThemeInfo;
+    /// An utterance ended, was interrupted or failed.
+    tts_finished("tts://finished", 1): SpeechFinished;
+    update_available("update://available", 1): UpdateInfo;
+    update_progress("update://progress", 1): UpdateProgress;
+    /// A run was stopped for taking too long or on request.
+    watchdog_killed("watchdog://killed", 1): RunKilled;
+    workspace_switched("workspace://switched", 1): WorkspaceInfo;
+}
+
+#[cfg(test)]
+fn typescript() -> String {
+    let mut declarations = Vec::new();
+    for event in EVENTS {
+        (event.declare)(&mut declarations);
+    }
+    let mut out = String::from(
+        "// Generated from src-tauri/src/events.rs by `UPDATE_EVENTS=1 cargo test`; do not edit.\n\n",
+    );
+    out.push_str("/** Payloads carry their event's schema version next to their own fields. */\n");
+    out.push_str("export type Versioned<T> = T & { schema_version: number };\n\n");
+    for (_, declaration) in &declarations {
+        out.push_str(declaration);
+        out.push_str("\n\n");
+    }
+    out.push_str("export interface ShellEvents {\n");
+    for event in EVENTS {
+        out.push_str(&doc_comment(event.doc, "    "));
+        out.push_str(&format!(
+            "    '{}': Versioned<{}>;\n",
+            event.name,
+            (event.payload)()
+        ));
+    }
+    out.push_str("}\n\n");
+    out.push_str("export const EVENT_VERSIONS: { [K in keyof ShellEvents]: number } = {\n");
+    for event in EVENTS {
+        out.push_str(&format!("    '{}': {},\n", event.name, event.version));
+    }
+    out.push_str("};\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails when a payload changed without `src/events.gen.ts` following it;
+    /// `UPDATE_EVENTS=1` rewrites the file instead.
+    #[test]
+    fn typescript_matches_checked_in_file() {
+        let expected = typescript();
+        if std::env::var_os("UPDATE_EVENTS").is_some() {
+            std::fs::write(TYPESCRIPT_PATH, &expected).expect("write events.gen.ts");
+            return;
+        }
+        let current = std::fs::read_to_string(TYPESCRIPT_PATH).expect("read events.gen.ts");
+        assert!(
+            current == expected,
+            "src/events.gen.ts is out of date; rerun with UPDATE_EVENTS=1 cargo test"
+        );
+    }
+
+    #[test]
+    fn event_names_are_unique() {
+        let mut names: Vec<_> = EVENTS.iter().map(|event| event.name).collect();
+        names.sort_unstable();
+        let count = names.len();
+        names.dedup();
+        assert_eq!(names.len(), count);
+    }
+}
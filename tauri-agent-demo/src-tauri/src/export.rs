@@ -0,0 +1,493 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Local;
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::{
+    atomic_file, dialogs,
+    dialogs::DialogPurpose,
+    encryption,
+    error::AppError,
+    events,
+    markdown::{self, RenderOptions},
+    workspace,
+};
+
+const MAX_TITLE_CHARS: usize = 60;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+    Html,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Json => "json",
+            Self::Html => "html",
+        }
+    }
+
+    fn filter_name(self) -> &'static str {
+        match self {
+            Self::Markdown => "Markdown",
+            Self::Json => "JSON",
+            Self::Html => "HTML",
+        }
+    }
+}
+
+struct Attachment {
+    name: String,
+    mime: String,
+    data: Vec<u8>,
+}
+
+struct Message {
+    role: String,
+    content: String,
+    timestamp: String,
+    attachments: Vec<Attachment>,
+}
+
+struct Conversation {
+    id: String,
+    title: String,
+    created_at: String,
+    updated_at: String,
+    work_path: Option<String>,
+    messages: Vec<Message>,
+}
+
+events::payload! {
+    pub struct ExportProgress {
+        done: usize,
+        total: usize,
+        session_id: String,
+        error: Option<String>,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportSummary {
+    dir: PathBuf,
+    exported: usize,
+    failed: usize,
+}
+
+fn open_db<R: Runtime>(app: &AppHandle<R>) -> Result<Connection, AppError> {
+    let path = workspace::db_path(app)?;
+    if !path.exists() {
+        return Err(AppError::not_found(format!(
+            "Chat database not found at {}.",
+            path.display()
+        )));
+    }
+    let conn = encryption::open(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|err| AppError::database("Failed to open the chat database.", err))?;
+    conn.busy_timeout(Duration::from_secs(30))
+        .map_err(|err| AppError::database("Failed to configure the chat database.", err))?;
+    Ok(conn)
+}
+
+fn load(conn: &Connection, id: &str) -> Result<Conversation, AppError> {
+    let mut conversation = conn
+        .query_row(
+            "SELECT id, title, created_at, updated_at, work_path FROM chat_sessions WHERE id = ?1",
+            params![id],
+            |row| {
+                Ok(Conversation {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    created_at: row.get(2)?,
+                    updated_at: row.get(3)?,
+                    work_path: row.get(4)?,
+                    messages: Vec::new(),
+                })
+            },
+        )
+        .map_err(|err| match err {
+            rusqlite::Error::QueryReturnedNoRows => {
+                AppError::not_found(format!("No conversation with id {id}."))
+            }
+            err => AppError::database("Failed to read the conversation.", err),
+        })?;
+    let mut messages = conn
+        .prepare(
+            "SELECT id, role, content, timestamp FROM chat_messages WHERE session_id = ?1 ORDER BY id",
+        )
+        .map_err(|err| AppError::database("Failed to read messages.", err))?;
+    let mut attachments = conn
+        .prepare(
+            "SELECT name, mime, data FROM message_attachments WHERE message_id = ?1 ORDER BY id",
+        )
+        .map_err(|err| AppError::database("Failed to read attachments.", err))?;
+    let rows = messages
+        .query_map(params![id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                Message {
+                    role: row.get(1)?,
+                    content: row.get(2)?,
+                    timestamp: row.get(3)?,
+                    attachments: Vec::new(),
+                },
+            ))
+        })
+        .map_err(|err| AppError::database("Failed to read messages.", err))?;
+    for row in rows {
+        let (message_id, mut message) =
+            row.map_err(|err| AppError::database("Failed to read messages.", err))?;
+        message.attachments = attachments
+            .query_map(params![message_id], |row| {
+                Ok(Attachment {
+                    name: row
+                        .get::<_, Option<String>>(0)?
+                        .unwrap_or_else(|| "attachment".to_string()),
+                    mime: row
+                        .get::<_, Option<String>>(1)?
+                        .unwrap_or_else(|| "application/octet-stream".to_string()),
+                    data: row.get(2)?,
+                })
+            })
+            .and_then(|rows| rows.collect())
+            .map_err(|err| AppError::database("Failed to read attachments.", err))?;
+        conversation.messages.push(message);
+    }
+    Ok(conversation)
+}
+
+fn safe_name(text: &str) -> String {
+    let cleaned: String = text
+        .chars()
+        .map(|ch| {
+            if ch.is_alphanumeric() || matches!(ch, ' ' | '-' | '_') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_TITLE_CHARS)
+        .collect();
+    let cleaned = cleaned.trim();
+    if cleaned.is_empty() {
+        "conversation".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+fn file_name(conversation: &Conversation, format: ExportFormat) -> String {
+    let short_id: String = conversation.id.chars().take(8).collect();
+    format!(
+        "{}-{short_id}.{}",
+        safe_name(&conversation.title),
+        format.extension()
+    )
+}
+
+/// Attachments go in a `<name>_files` folder beside the Markdown file, since
+/// Markdown has no way to embed them.
+fn render_markdown(conversation: &Conversation, dest: &Path) -> Result<String, AppError> {
+    let stem = dest
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "conversation".to_string());
+    let files_dir_name = format!("{stem}_files");
+    let files_dir = dest.with_file_name(&files_dir_name);
+    let mut out = format!("# {}\n\n", conversation.title);
+    let _ = writeln!(out, "- Created: {}", conversation.created_at);
+    let _ = writeln!(out, "- Updated: {}", conversation.updated_at);
+    if let Some(work_path) = &conversation.work_path {
+        let _ = writeln!(out, "- Workspace: `{work_path}`");
+    }
+    let mut index = 0;
+    for message in &conversation.messages {
+        let _ = write!(
+            out,
+            "\n## {} \u{b7} {}\n\n{}\n",
+            message.role, message.timestamp, message.content
+        );
+        for attachment in &message.attachments {
+            index += 1;
+            fs::create_dir_all(&files_dir).map_err(|err| {
+                AppError::from(format!("Failed to create {}: {err}", files_dir.display()))
+            })?;
+            let original = Path::new(&attachment.name);
+            let stem = original
+                .file_stem()
+                .map(|stem| stem.to_string_lossy())
+                .unwrap_or_default();
+            let name = match original.extension() {
+                Some(ext) => format!(
+                    "{index}-{}.{}",
+                    safe_name(&stem),
+                    safe_name(&ext.to_string_lossy())
+                ),
+                None => format!("{index}-{}", safe_name(&stem)),
+            };
+            fs::write(files_dir.join(&name), &attachment.data)
+                .map_err(|err| AppError::from(format!("Failed to write {name}: {err}")))?;
+            let link = format!("{files_dir_name}/{name}").replace(' ', "%20");
+            if attachment.mime.starts_with("image/") {
+                let _ = writeln!(out, "\n![{}]({link})", attachment.name);
+            } else {
+                let _ = writeln!(out, "\n[{}]({link})", attachment.name);
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn render_json(conversation: &Conversation) -> Result<String, AppError> {
+    let messages: Vec<_> = conversation
+        .messages
+        .iter()
+        .map(|message| {
+            json!({
+                "role": message.role,
+                "content": message.content,
+                "timestamp": message.timestamp,
+                "attachments": message.attachments.iter().map(|attachment| json!({
+                    "name": attachment.name,
+                    "mime": attachment.mime,
+                    "data_base64": STANDARD.encode(&attachment.data),
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&json!({
+        "session": {
+            "id": conversation.id,
+            "title": conversation.title,
+            "created_at": conversation.created_at,
+            "updated_at": conversation.updated_at,
+            "work_path": conversation.work_path,
+        },
+        "messages": messages,
+    }))
+    .map_err(|err| AppError::from(format!("Failed to serialize the conversation: {err}")))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A single self-contained page; attachments are inlined as data URLs.
+fn render_html(conversation: &Conversation) -> Result<String, AppError> {
+    let css = markdown::highlight_css(None)?;
+    let options = RenderOptions::default();
+    let title = escape_html(&conversation.title);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n\
+         body {{ font-family: system-ui, sans-serif; max-width: 860px; margin: 2rem auto; padding: 0 1rem; line-height: 1.5; }}\n\
+         .message {{ border-top: 1px solid #ddd; padding: 0.5rem 0; }}\n\
+         .meta {{ color: #666; font-size: 0.85em; }}\n\
+         pre {{ overflow-x: auto; background: #f6f8fa; padding: 0.75rem; }}\n\
+         img {{ max-width: 100%; }}\n{css}</style>\n</head>\n<body>\n<h1>{title}</h1>\n\
+         <p class=\"meta\">Created {} \u{b7} Updated {}</p>\n",
+        escape_html(&conversation.created_at),
+        escape_html(&conversation.updated_at),
+    );
+    for message in &conversation.messages {
+        let _ = write!(
+            out,
+            "<section class=\"message {role}\">\n<p class=\"meta\"><strong>{role}</strong> \u{b7} {}</p>\n{}",
+            escape_html(&message.timestamp),
+            markdown::render(&message.content, &options),
+            role = escape_html(&message.role),
+        );
+        for attachment in &message.attachments {
+            let url = format!(
+                "data:{};base64,{}",
+                escape_html(&attachment.mime),
+                STANDARD.encode(&attachment.data)
+            );
+            let name = escape_html(&attachment.name);
+            if attachment.mime.starts_with("image/") {
+                let _ = writeln!(out, "<p><img src=\"{url}\" alt=\"{name}\"></p>");
+            } else {
+                let _ = writeln!(
+                    out,
+                    "<p><a href=\"{url}\" download=\"{name}\">{name}</a></p>"
+                );
+            }
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    Ok(out)
+}
+
+fn write(conversation: &Conversation, format: ExportFormat, dest: &Path) -> Result<(), AppError> {
+    let contents = match format {
+        ExportFormat::Markdown => render_markdown(conversation, dest)?,
+        ExportFormat::Json => render_json(conversation)?,
+        ExportFormat::Html => render_html(conversation)?,
+    };
+    atomic_file::write(dest, contents)
+        .map_err(|err| AppError::from(format!("Failed to write {}: {err}", dest.display())))
+}
+
+fn pick_file<R: Runtime>(
+    app: &AppHandle<R>,
+    suggested: &str,
+    format: ExportFormat,
+) -> Result<Option<PathBuf>, AppError> {
+    let mut dialog = app.dialog().file();
+    if let Some(dir) = dialogs::default_dir(app, DialogPurpose::Exports) {
+        dialog = dialog.set_directory(dir);
+    }
+    let Some(chosen) = dialog
+        .set_file_name(suggested)
+        .add_filter(format.filter_name(), &[format.extension()])
+        .blocking_save_file()
+    else {
+        return Ok(None);
+    };
+    let chosen = chosen
+        .into_path()
+        .map_err(|err| AppError::invalid_input(format!("Invalid export path: {err}")))?;
+    dialogs::remember(app, DialogPurpose::Exports, &chosen);
+    Ok(Some(chosen))
+}
+
+fn pick_folder<R: Runtime>(app: &AppHandle<R>) -> Result<Option<PathBuf>, AppError> {
+    let mut dialog = app.dialog().file();
+    if let Some(dir) = dialogs::default_dir(app, DialogPurpose::Exports) {
+        dialog = dialog.set_directory(dir);
+    }
+    let Some(chosen) = dialog.blocking_pick_folder() else {
+        return Ok(None);
+    };
+    let chosen = chosen
+        .into_path()
+        .map_err(|err| AppError::invalid_input(format!("Invalid export folder: {err}")))?;
+    dialogs::remember(app, DialogPurpose::Exports, &chosen);
+    Ok(Some(chosen))
+}
+
+fn export_one<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    format: ExportFormat,
+    dest: Option<PathBuf>,
+) -> Result<Option<PathBuf>, AppError> {
+    let conversation = load(&open_db(app)?, id)?;
+    let name = file_name(&conversation, format);
+    let dest = match dest {
+        Some(dest) if dest.is_dir() => dest.join(name),
+        Some(dest) => dest,
+        None => match pick_file(app, &name, format)? {
+            Some(dest) => dest,
+            None => return Ok(None),
+        },
+    };
+    write(&conversation, format, &dest)?;
+    tracing::info!("[Export] Wrote {}", dest.display());
+    Ok(Some(dest))
+}
+
+fn export_all<R: Runtime>(
+    app: &AppHandle<R>,
+    format: ExportFormat,
+    dest: Option<PathBuf>,
+) -> Result<Option<ExportSummary>, AppError> {
+    let dir = match dest {
+        Some(dest) => dest,
+        None => match pick_folder(app)? {
+            Some(dir) => dir,
+            None => return Ok(None),
+        },
+    };
+    // One dated folder per run, so repeated exports never overwrite each other.
+    let dir = dir.join(format!(
+        "conversations-{}",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::create_dir_all(&dir)
+        .map_err(|err| AppError::from(format!("Failed to create {}: {err}", dir.display())))?;
+    let conn = open_db(app)?;
+    let ids: Vec<String> = conn
+        .prepare("SELECT id FROM chat_sessions ORDER BY created_at")
+        .and_then(|mut statement| {
+            statement
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<_, _>>()
+        })
+        .map_err(|err| AppError::database("Failed to list conversations.", err))?;
+    let total = ids.len();
+    let mut failed = 0;
+    for (index, id) in ids.into_iter().enumerate() {
+        let result = load(&conn, &id).and_then(|conversation| {
+            write(
+                &conversation,
+                format,
+                &dir.join(file_name(&conversation, format)),
+            )
+        });
+        let error = result.err().map(|err| err.message);
+        if let Some(err) = &error {
+            failed += 1;
+            tracing::warn!("[Export] Skipped {id}: {err}");
+        }
+        events::export_progress(
+            app,
+            &ExportProgress {
+                done: index + 1,
+                total,
+                session_id: id,
+                error,
+            },
+        );
+    }
+    Ok(Some(ExportSummary {
+        dir,
+        exported: total - failed,
+        failed,
+    }))
+}
+
+/// Writes one conversation as Markdown, JSON or HTML, including its attachments.
+/// Without `dest` a save dialog asks where; `None` means the user cancelled.
+#[tauri::command]
+pub async fn export_conversation(
+    app: AppHandle,
+    id: String,
+    format: ExportFormat,
+    dest: Option<PathBuf>,
+) -> Result<Option<PathBuf>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || export_one(&app, &id, format, dest))
+        .await
+        .map_err(|err| AppError::from(format!("Export task failed: {err}")))?
+}
+
+/// Exports every conversation into a new folder under `dest` (or one picked in a
+/// dialog), emitting `export://progress` after each.
+#[tauri::command]
+pub async fn export_all_conversations(
+    app: AppHandle,
+    format: ExportFormat,
+    dest: Option<PathBuf>,
+) -> Result<Option<ExportSummary>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || export_all(&app, format, dest))
+        .await
+        .map_err(|err| AppError::from(format!("Export task failed: {err}")))?
+}
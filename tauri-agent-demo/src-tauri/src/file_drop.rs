@@ -0,0 +1,186 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    attachments::{self, AttachmentHandle, AttachmentStore},
+    events, kiosk, main_window, proxy, BackendState,
+};
+
+/// Dropped files larger than this are refused rather than copied into the store.
+const MAX_DROP_FILE_BYTES: u64 = 100 * 1024 * 1024;
+/// Files beyond this many in one drop are refused, so a dropped folder listing
+/// cannot flood the composer.
+const MAX_DROP_FILES: usize = 20;
+
+events::payload! {
+    pub struct AttachmentAdded {
+        source: PathBuf,
+        attachment: AttachmentHandle,
+        /// What chat requests pass as `upload_id` instead of the file's bytes.
+        upload_id: String,
+    }
+}
+
+events::payload! {
+    pub struct AttachmentRejected {
+        source: PathBuf,
+        reason: String,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct UploadResponse {
+    id: String,
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Checks the file can become an attachment and copies it into the store.
+fn store<R: Runtime>(
+    app: &AppHandle<R>,
+    path: &Path,
+) -> Result<(AttachmentHandle, Vec<u8>), String> {
+    let name = file_name(path);
+    let metadata =
+        fs::metadata(path).map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    if !metadata.is_file() {
+        return Err(format!("{name} is not a file."));
+    }
+    if metadata.len() > MAX_DROP_FILE_BYTES {
+        return Err(format!(
+            "{name} is larger than {} MB.",
+            MAX_DROP_FILE_BYTES / (1024 * 1024)
+        ));
+    }
+    let mime = attachments::mime_for(&name);
+    if mime == "application/octet-stream" {
+        return Err(format!("{name} is not a supported file type."));
+    }
+    let bytes = fs::read(path).map_err(|err| format!("Failed to read {name}: {err}"))?;
+    let attachment = app
+        .state::<AttachmentStore>()
+        .store_bytes(&bytes, &name, mime)?;
+    if let Some(reason) = &attachment.quarantine {
+        return Err(format!("{name} was quarantined: {reason}"));
+    }
+    Ok((attachment, bytes))
+}
+
+/// Hands the file to the backend; it keeps uploads by content hash, so a file
+/// dropped twice is only stored once there too.
+pub async fn upload(
+    client: &reqwest::Client,
+    base_url: &str,
+    attachment: &AttachmentHandle,
+    bytes: Vec<u8>,
+) -> Result<String, String> {
+    let response = client
+        .post(format!("{base_url}/attachments/uploads"))
+        .query(&[("name", attachment.name.as_str())])
+        .header(reqwest::header::CONTENT_TYPE, attachment.mime.as_str())
+        .body(bytes)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| format!("Failed to upload {}: {err}", attachment.name))?;
+    let upload: UploadResponse = response
+        .json()
+        .await
+        .map_err(|err| format!("Failed to read the upload of {}: {err}", attachment.name))?;
+    if upload.id != attachment.id {
+        return Err(format!(
+            "The backend stored {} under a different hash.",
+            attachment.name
+        ));
+    }
+    Ok(upload.id)
+}
+
+/// Uploads an attachment the shell made itself, such as a screenshot, and adds it
+/// to the main window's next prompt as if it had been dropped there.
+pub async fn add_to_prompt<R: Runtime>(
+    app: &AppHandle<R>,
+    attachment: AttachmentHandle,
+    bytes: Vec<u8>,
+) -> Result<(), String> {
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| "Backend is not available.".to_string())?;
+    let upload_id = upload(&proxy::client(app), &base_url, &attachment, bytes).await?;
+    let added = AttachmentAdded {
+        source: attachment.path.clone(),
+        attachment,
+        upload_id,
+    };
+    events::attachment_added(app, main_window::LABEL, &added);
+    Ok(())
+}
+
+async fn ingest<R: Runtime>(app: &AppHandle<R>, path: &Path) -> Result<AttachmentAdded, String> {
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| "Backend is not available.".to_string())?;
+    let handle = app.clone();
+    let source = path.to_path_buf();
+    let (attachment, bytes) = tauri::async_runtime::spawn_blocking(move || store(&handle, &source))
+        .await
+        .map_err(|err| format!("Attachment task failed: {err}"))??;
+    let upload_id = upload(&proxy::client(app), &base_url, &attachment, bytes).await?;
+    Ok(AttachmentAdded {
+        source: path.to_path_buf(),
+        attachment,
+        upload_id,
+    })
+}
+
+/// Takes files dropped onto the main window: each is checked, stored in the
+/// attachment store, registered with the backend, and announced on
+/// `attachment://added`; refused files are announced on `attachment://rejected`.
+pub fn handle_drop<R: Runtime>(app: &AppHandle<R>, paths: Vec<PathBuf>) {
+    if paths.is_empty() {
+        return;
+    }
+    if kiosk::is_enabled(app) {
+        tracing::warn!("[Drop] Ignoring dropped files in kiosk mode.");
+        return;
+    }
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for (index, path) in paths.into_iter().enumerate() {
+            let result = if index < MAX_DROP_FILES {
+                ingest(&handle, &path).await
+            } else {
+                Err(format!(
+                    "Only {MAX_DROP_FILES} files can be dropped at once."
+                ))
+            };
+            match result {
+                Ok(added) => {
+                    events::attachment_added(&handle, main_window::LABEL, &added);
+                }
+                Err(reason) => {
+                    tracing::warn!("[Drop] {reason}");
+                    events::attachment_rejected(
+                        &handle,
+                        main_window::LABEL,
+                        &AttachmentRejected {
+                            source: path,
+                            reason,
+                        },
+                    );
+                }
+            }
+        }
+    });
+}
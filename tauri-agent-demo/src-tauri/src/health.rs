@@ -0,0 +1,251 @@
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{error::AppError, events, idle, proxy, rpc, BackendState};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+/// Used instead while the user is away.
+const AWAY_POLL_INTERVAL: Duration = Duration::from_secs(60);
+const POLL_TIMEOUT: Duration = Duration::from_secs(5);
+/// A day of samples at `POLL_INTERVAL`.
+const MAX_SAMPLES: usize = 24 * 60 * 60 / 15;
+
+events::payload! {
+    /// One poll of the backend's `/health` endpoint.
+    pub struct HealthSample {
+        at: String,
+        /// Round trip of the poll itself; `None` when it failed.
+        latency_ms: Option<u64>,
+        /// Share of the requests answered since the previous sample that failed with a 5xx.
+        error_rate: Option<f64>,
+        requests: u64,
+        in_flight: u64,
+        /// Agent tasks waiting for a slot.
+        queue_depth: u64,
+        running_tasks: u64,
+        error: Option<String>,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+struct Counters {
+    requests: u64,
+    errors: u64,
+    in_flight: u64,
+    queued: u64,
+    running: u64,
+}
+
+#[derive(Default)]
+struct History {
+    samples: VecDeque<(DateTime<Local>, HealthSample)>,
+    /// Counters from the last successful poll; reset when the backend restarts.
+    last: Option<Counters>,
+}
+
+#[derive(Default)]
+pub struct HealthMonitor(Mutex<History>);
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub enum HealthRange {
+    #[serde(rename = "15m")]
+    FifteenMinutes,
+    #[default]
+    #[serde(rename = "1h")]
+    Hour,
+    #[serde(rename = "6h")]
+    SixHours,
+    #[serde(rename = "24h")]
+    Day,
+}
+
+impl HealthRange {
+    fn duration(self) -> chrono::Duration {
+        match self {
+            HealthRange::FifteenMinutes => chrono::Duration::minutes(15),
+            HealthRange::Hour => chrono::Duration::hours(1),
+            HealthRange::SixHours => chrono::Duration::hours(6),
+            HealthRange::Day => chrono::Duration::hours(24),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthHistory {
+    samples: Vec<HealthSample>,
+    /// Polls that got no answer within the range.
+    failures: usize,
+    p50_latency_ms: Option<u64>,
+    p95_latency_ms: Option<u64>,
+    /// Failed share of every request answered within the range.
+    error_rate: Option<f64>,
+    max_queue_depth: u64,
+}
+
+fn percentile(sorted: &[u64], pct: usize) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    Some(sorted[(sorted.len() - 1) * pct / 100])
+}
+
+async fn fetch<R: Runtime>(app: &AppHandle<R>) -> Result<Counters, String> {
+    let raw: Value = if rpc::is_attached(app) {
+        let handle = app.clone();
+        let (status, body) = tauri::async_runtime::spawn_blocking(move || {
+            rpc::get(&handle, "/health", POLL_TIMEOUT)
+        })
+        .await
+        .map_err(|err| format!("Health poll task failed: {err}"))??;
+        if status != 200 {
+            return Err(format!("Backend answered /health with {status}."));
+        }
+        serde_json::from_slice(&body).map_err(|err| format!("Unreadable health report: {err}"))?
+    } else {
+        let base_url = app
+            .try_state::<BackendState>()
+            .map(|state| state.base_url())
+            .ok_or_else(|| "Backend is not configured yet.".to_string())?;
+        proxy::client(app)
+            .get(format!("{base_url}/health"))
+            .timeout(POLL_TIMEOUT)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| format!("Health poll failed: {err}"))?
+            .json()
+            .await
+            .map_err(|err| format!("Unreadable health report: {err}"))?
+    };
+    serde_json::from_value(raw).map_err(|err| format!("Unreadable health report: {err}"))
+}
+
+/// One out-of-band poll, for callers waiting on the backend to come back.
+pub async fn probe<R: Runtime>(app: &AppHandle<R>) -> bool {
+    matches!(
+        tokio::time::timeout(POLL_TIMEOUT, fetch(app)).await,
+        Ok(Ok(_))
+    )
+}
+
+fn record<R: Runtime>(app: &AppHandle<R>, latency: Duration, result: Result<Counters, String>) {
+    let Some(monitor) = app.try_state::<HealthMonitor>() else {
+        return;
+    };
+    let Ok(mut history) = monitor.0.lock() else {
+        return;
+    };
+    let now = Local::now();
+    let sample = match result {
+        Ok(counters) => {
+            // Counters shrink when the sidecar restarts; start the diff over.
+            let previous = history.last.filter(|last| {
+                last.requests <= counters.requests && last.errors <= counters.errors
+            });
+            let error_rate = previous.and_then(|last| {
+                let requests = counters.requests - last.requests;
+                (requests > 0).then(|| (counters.errors - last.errors) as f64 / requests as f64)
+            });
+            history.last = Some(counters);
+            HealthSample {
+                at: now.to_rfc3339(),
+                latency_ms: Some(latency.as_millis() as u64),
+                error_rate,
+                requests: counters.requests - previous.map_or(0, |last| last.requests),
+                in_flight: counters.in_flight,
+                queue_depth: counters.queued,
+                running_tasks: counters.running,
+                error: None,
+            }
+        }
+        Err(err) => {
+            history.last = None;
+            HealthSample {
+                at: now.to_rfc3339(),
+                latency_ms: None,
+                error_rate: None,
+                requests: 0,
+                in_flight: 0,
+                queue_depth: 0,
+                running_tasks: 0,
+                error: Some(err),
+            }
+        }
+    };
+    if history.samples.len() >= MAX_SAMPLES {
+        history.samples.pop_front();
+    }
+    history.samples.push_back((now, sample.clone()));
+    drop(history);
+    events::health_sample(app, &sample);
+}
+
+/// Polls the backend for as long as the app runs, less often while the user is away.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let interval = if idle::is_away(&app) {
+                AWAY_POLL_INTERVAL
+            } else {
+                POLL_INTERVAL
+            };
+            tokio::time::sleep(interval).await;
+            let started = Instant::now();
+            let result = tokio::time::timeout(POLL_TIMEOUT, fetch(&app))
+                .await
+                .unwrap_or_else(|_| Err("Health poll timed out.".to_string()));
+            record(&app, started.elapsed(), result);
+        }
+    });
+}
+
+/// Samples from the last `range` (default an hour), oldest first, with a summary
+/// for the whole window.
+#[tauri::command]
+pub fn get_health_history(
+    monitor: tauri::State<HealthMonitor>,
+    range: Option<HealthRange>,
+) -> Result<HealthHistory, AppError> {
+    let cutoff = Local::now() - range.unwrap_or_default().duration();
+    let history = monitor
+        .0
+        .lock()
+        .map_err(|_| AppError::unavailable("Health history is unavailable."))?;
+    let samples: Vec<HealthSample> = history
+        .samples
+        .iter()
+        .filter(|(at, _)| *at >= cutoff)
+        .map(|(_, sample)| sample.clone())
+        .collect();
+    drop(history);
+
+    let mut latencies: Vec<u64> = samples.iter().filter_map(|s| s.latency_ms).collect();
+    latencies.sort_unstable();
+    let requests: u64 = samples
+        .iter()
+        .filter(|s| s.error_rate.is_some())
+        .map(|s| s.requests)
+        .sum();
+    let errors: f64 = samples
+        .iter()
+        .filter_map(|s| Some(s.error_rate? * s.requests as f64))
+        .sum();
+    Ok(HealthHistory {
+        failures: samples.iter().filter(|s| s.error.is_some()).count(),
+        p50_latency_ms: percentile(&latencies, 50),
+        p95_latency_ms: percentile(&latencies, 95),
+        error_rate: (requests > 0).then(|| errors / requests as f64),
+        max_queue_depth: samples.iter().map(|s| s.queue_depth).max().unwrap_or(0),
+        samples,
+    })
+}
@@ -0,0 +1,164 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use serde_json::json;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{error::AppError, events, indexer, proxy, rpc, BackendState};
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(30);
+/// Input-free time after which the user counts as away.
+const AWAY_AFTER: Duration = Duration::from_secs(5 * 60);
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+events::payload! {
+    pub struct IdleChanged {
+        away: bool,
+    }
+}
+
+/// Whether the user is currently away from the machine.
+#[derive(Default)]
+pub struct Presence(AtomicBool);
+
+pub fn is_away<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.try_state::<Presence>()
+        .is_some_and(|presence| presence.0.load(Ordering::Relaxed))
+}
+
+/// Time since the last keyboard or mouse input anywhere in the user's session, not
+/// just in this app.
+pub async fn idle_time<R: Runtime>(app: &AppHandle<R>) -> Result<Duration, String> {
+    platform_idle_time(app).await
+}
+
+#[cfg(target_os = "windows")]
+async fn platform_idle_time<R: Runtime>(_app: &AppHandle<R>) -> Result<Duration, String> {
+    use windows::Win32::{
+        System::SystemInformation::GetTickCount,
+        UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
+    };
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+        return Err("Failed to read the last input time.".to_string());
+    }
+    // Both are milliseconds since boot and wrap together after 49.7 days.
+    let idle = unsafe { GetTickCount() }.wrapping_sub(info.dwTime);
+    Ok(Duration::from_millis(u64::from(idle)))
+}
+
+#[cfg(target_os = "macos")]
+async fn platform_idle_time<R: Runtime>(_app: &AppHandle<R>) -> Result<Duration, String> {
+    const COMBINED_SESSION_STATE: i32 = 0;
+    const ANY_INPUT_EVENT: u32 = u32::MAX;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceSecondsSinceLastEventType(source_state: i32, event_type: u32) -> f64;
+    }
+
+    let seconds =
+        unsafe { CGEventSourceSecondsSinceLastEventType(COMBINED_SESSION_STATE, ANY_INPUT_EVENT) };
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|err| format!("Failed to read the last input time: {err}"))
+}
+
+#[cfg(target_os = "linux")]
+async fn platform_idle_time<R: Runtime>(app: &AppHandle<R>) -> Result<Duration, String> {
+    crate::desktop::session_idle_time(app).await
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+async fn platform_idle_time<R: Runtime>(_app: &AppHandle<R>) -> Result<Duration, String> {
+    Err("Idle time is not available on this platform.".to_string())
+}
+
+/// Tells the backend to hold back its own background work while the user is away.
+async fn notify_backend<R: Runtime>(app: &AppHandle<R>, away: bool) -> Result<(), String> {
+    let body = json!({ "idle": away });
+    if rpc::is_attached(app) {
+        let handle = app.clone();
+        let (status, _) = tauri::async_runtime::spawn_blocking(move || {
+            rpc::post(&handle, "/system/idle", &body, NOTIFY_TIMEOUT)
+        })
+        .await
+        .map_err(|err| format!("Idle notify task failed: {err}"))??;
+        return match status {
+            200..=299 => Ok(()),
+            _ => Err(format!("Backend answered /system/idle with {status}.")),
+        };
+    }
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| "Backend is not configured yet.".to_string())?;
+    proxy::client(app)
+        .post(format!("{base_url}/system/idle"))
+        .timeout(NOTIFY_TIMEOUT)
+        .json(&body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|err| format!("Failed to notify the backend: {err}"))
+}
+
+fn set_away<R: Runtime>(app: &AppHandle<R>, away: bool) {
+    tracing::info!(
+        "[Idle] User is {}; {} background work.",
+        if away { "away" } else { "back" },
+        if away { "throttling" } else { "resuming" }
+    );
+    indexer::set_idle(app, away);
+    events::idle_changed(app, &IdleChanged { away });
+}
+
+/// Watches the OS idle time and throttles background work while the user is away:
+/// health polls slow down, indexing waits and the backend is asked to hold off.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut warned = false;
+        // Whether the backend has heard about the current state; retried each tick.
+        let mut backend_synced = true;
+        loop {
+            tokio::time::sleep(WATCH_INTERVAL).await;
+            let away = match idle_time(&app).await {
+                Ok(idle) => idle >= AWAY_AFTER,
+                Err(err) => {
+                    if !warned {
+                        warned = true;
+                        tracing::warn!("[Idle] Idle throttling is off: {err}");
+                    }
+                    continue;
+                }
+            };
+            let Some(presence) = app.try_state::<Presence>() else {
+                continue;
+            };
+            if presence.0.swap(away, Ordering::Relaxed) != away {
+                set_away(&app, away);
+                backend_synced = false;
+            }
+            if !backend_synced {
+                match notify_backend(&app, away).await {
+                    Ok(()) => backend_synced = true,
+                    Err(err) => tracing::warn!("[Idle] {err}"),
+                }
+            }
+        }
+    });
+}
+
+/// Milliseconds since the user last touched the keyboard or mouse.
+#[tauri::command]
+pub async fn get_idle_time(app: AppHandle) -> Result<u64, AppError> {
+    let idle = idle_time(&app).await.map_err(AppError::unavailable)?;
+    Ok(idle.as_millis() as u64)
+}
@@ -0,0 +1,733 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, NaiveDateTime};
+use ring::rand::{SecureRandom, SystemRandom};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, Runtime};
+use zip::ZipArchive;
+
+use crate::{
+    attachments::hex_digest, encryption, error::AppError, events, health, proxy, rpc, workspace,
+    BackendState,
+};
+
+const IMPORT_PATH: &str = "/sessions/import";
+const IMPORT_TIMEOUT: Duration = Duration::from_secs(60);
+/// The entry ChatGPT and Claude exports keep their conversations in.
+const EXPORT_ENTRY: &str = "conversations.json";
+const UNTITLED: &str = "Imported conversation";
+/// Role headings a Markdown transcript may use, our own export's included.
+const USER_HEADINGS: &[&str] = &["user", "you", "me", "human", "prompt"];
+const ASSISTANT_HEADINGS: &[&str] = &["assistant", "chatgpt", "claude", "ai", "agent", "bot"];
+/// Timestamps are stored the way the backend writes them: local time, no offset.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.6f";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    /// `conversations.json`, or the zip it comes in.
+    Chatgpt,
+    /// Claude's data export, laid out the same way.
+    Claude,
+    /// A `.md` transcript or a folder of them, with `## User` / `## Assistant`
+    /// headings as our own Markdown export writes.
+    Markdown,
+}
+
+struct Message {
+    role: &'static str,
+    content: String,
+    timestamp: Option<String>,
+}
+
+struct Conversation {
+    /// The export's own id, or a digest for Markdown; marks what was imported.
+    source_id: String,
+    title: String,
+    created_at: Option<String>,
+    messages: Vec<Message>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportTarget {
+    /// A dry run; nothing was written.
+    Preview,
+    /// Through the running backend's API.
+    Backend,
+    /// Straight into the workspace database, with the backend stopped.
+    Database,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportPreview {
+    source_id: String,
+    title: String,
+    created_at: Option<String>,
+    messages: usize,
+    /// Skipped, since an earlier import brought it in already.
+    already_imported: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportFailure {
+    title: String,
+    error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    target: ImportTarget,
+    conversations: Vec<ImportPreview>,
+    imported: usize,
+    skipped: usize,
+    failed: Vec<ImportFailure>,
+}
+
+events::payload! {
+    pub struct ImportProgress {
+        done: usize,
+        total: usize,
+        title: String,
+    }
+}
+
+fn local_timestamp(time: DateTime<Local>) -> String {
+    time.naive_local().format(TIMESTAMP_FORMAT).to_string()
+}
+
+fn from_epoch(value: &Value) -> Option<String> {
+    let seconds = value.as_f64()?;
+    let time = DateTime::from_timestamp_millis((seconds * 1000.0) as i64)?;
+    Some(local_timestamp(time.with_timezone(&Local)))
+}
+
+/// RFC 3339 is converted to local time; a time without an offset is taken as local.
+fn normalize_timestamp(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(raw) {
+        return Some(local_timestamp(time.with_timezone(&Local)));
+    }
+    [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(raw, format).ok())
+    .map(|time| time.format(TIMESTAMP_FORMAT).to_string())
+}
+
+fn title_or_untitled(value: Option<&str>) -> String {
+    value
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .unwrap_or(UNTITLED)
+        .to_string()
+}
+
+/// The export from the zip's `conversations.json`, or from the file itself.
+fn read_export(path: &Path) -> Result<Vec<u8>, String> {
+    if path.is_dir() {
+        return Err("Pick the export's zip or its conversations.json, not a folder.".to_string());
+    }
+    let is_zip = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"));
+    if !is_zip {
+        return fs::read(path).map_err(|err| format!("Failed to read {}: {err}", path.display()));
+    }
+    let file = File::open(path).map_err(|err| format!("Failed to open the export: {err}"))?;
+    let mut zip = ZipArchive::new(file).map_err(|err| format!("Invalid zip: {err}"))?;
+    // The shallowest match, in case attachments carry a file of the same name.
+    let entry = zip
+        .file_names()
+        .filter(|name| name.rsplit('/').next() == Some(EXPORT_ENTRY))
+        .min_by_key(|name| name.matches('/').count())
+        .map(str::to_string)
+        .ok_or_else(|| format!("The zip has no {EXPORT_ENTRY}."))?;
+    let mut bytes = Vec::new();
+    zip.by_name(&entry)
+        .map_err(|err| format!("Failed to read {entry}: {err}"))?
+        .read_to_end(&mut bytes)
+        .map_err(|err| format!("Failed to read {entry}: {err}"))?;
+    Ok(bytes)
+}
+
+fn export_list(raw: &[u8]) -> Result<Vec<Value>, String> {
+    serde_json::from_slice(raw).map_err(|err| format!("{EXPORT_ENTRY} is not a list: {err}"))
+}
+
+/// Text of a ChatGPT message; images, code runs and browsing results are left out.
+fn chatgpt_text(message: &Value) -> Option<String> {
+    let content = message.get("content")?;
+    match content.get("content_type").and_then(Value::as_str) {
+        Some("text" | "multimodal_text") => {}
+        _ => return None,
+    }
+    let parts: Vec<&str> = content
+        .get("parts")?
+        .as_array()?
+        .iter()
+        .filter_map(Value::as_str)
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+/// ChatGPT stores a tree of edits and regenerations; the branch ending at
+/// `current_node` is the one that was on screen.
+fn chatgpt(raw: &[u8]) -> Result<Vec<Conversation>, String> {
+    Ok(export_list(raw)?
+        .iter()
+        .filter_map(|conversation| {
+            let mapping = conversation.get("mapping")?.as_object()?;
+            let mut branch = Vec::new();
+            let mut node = conversation.get("current_node").and_then(Value::as_str);
+            while let Some(id) = node {
+                let Some(entry) = mapping.get(id) else {
+                    break;
+                };
+                if branch.len() > mapping.len() {
+                    break;
+                }
+                branch.push(entry);
+                node = entry.get("parent").and_then(Value::as_str);
+            }
+            branch.reverse();
+            let messages = branch
+                .iter()
+                .filter_map(|entry| {
+                    let message = entry.get("message")?;
+                    let hidden = message
+                        .pointer("/metadata/is_visually_hidden_from_conversation")
+                        .and_then(Value::as_bool)
+                        .unwrap_or(false);
+                    let role = match message.pointer("/author/role").and_then(Value::as_str) {
+                        Some("user") if !hidden => "user",
+                        Some("assistant") if !hidden => "assistant",
+                        _ => return None,
+                    };
+                    Some(Message {
+                        role,
+                        content: chatgpt_text(message)?,
+                        timestamp: message.get("create_time").and_then(from_epoch),
+                    })
+                })
+                .collect::<Vec<_>>();
+            let id = conversation
+                .get("conversation_id")
+                .or_else(|| conversation.get("id"))
+                .and_then(Value::as_str)?;
+            Some(Conversation {
+                source_id: format!("chatgpt:{id}"),
+                title: title_or_untitled(conversation.get("title").and_then(Value::as_str)),
+                created_at: conversation.get("create_time").and_then(from_epoch),
+                messages,
+            })
+        })
+        .collect())
+}
+
+fn claude(raw: &[u8]) -> Result<Vec<Conversation>, String> {
+    Ok(export_list(raw)?
+        .iter()
+        .filter_map(|conversation| {
+            let id = conversation.get("uuid").and_then(Value::as_str)?;
+            let messages = conversation
+                .get("chat_messages")?
+                .as_array()?
+                .iter()
+                .filter_map(|message| {
+                    let role = match message.get("sender").and_then(Value::as_str) {
+                        Some("human") => "user",
+                        Some("assistant") => "assistant",
+                        _ => return None,
+                    };
+                    let text = message
+                        .get("text")
+                        .and_then(Value::as_str)
+                        .map(str::trim)
+                        .filter(|text| !text.is_empty())
+                        .map(str::to_string)
+                        .or_else(|| {
+                            let parts: Vec<&str> = message
+                                .get("content")?
+                                .as_array()?
+                                .iter()
+                                .filter(|part| {
+                                    part.get("type").and_then(Value::as_str) == Some("text")
+                                })
+                                .filter_map(|part| part.get("text").and_then(Value::as_str))
+                                .collect();
+                            (!parts.is_empty()).then(|| parts.join("\n\n"))
+                        })?;
+                    Some(Message {
+                        role,
+                        content: text,
+                        timestamp: message
+                            .get("created_at")
+                            .and_then(Value::as_str)
+                            .and_then(normalize_timestamp),
+                    })
+                })
+                .collect();
+            Some(Conversation {
+                source_id: format!("claude:{id}"),
+                title: title_or_untitled(conversation.get("name").and_then(Value::as_str)),
+                created_at: conversation
+                    .get("created_at")
+                    .and_then(Value::as_str)
+                    .and_then(normalize_timestamp),
+                messages,
+            })
+        })
+        .collect())
+}
+
+fn role_heading(line: &str) -> Option<&'static str> {
+    let rest = line.strip_prefix("##")?.trim_start_matches('#').trim();
+    let name = rest.trim_end_matches(':').trim().to_lowercase();
+    if USER_HEADINGS.contains(&name.as_str()) {
+        Some("user")
+    } else if ASSISTANT_HEADINGS.contains(&name.as_str()) {
+        Some("assistant")
+    } else {
+        None
+    }
+}
+
+/// Content of a message without the blank lines and `---` rules around it.
+fn finish_message(mut message: Message) -> Option<Message> {
+    let mut content = message.content.trim();
+    while let Some(rest) = content.strip_suffix("---") {
+        content = rest.trim_end();
+    }
+    message.content = content.trim().to_string();
+    (!message.content.is_empty()).then_some(message)
+}
+
+/// One conversation per `# Title`; a file without one is a single conversation
+/// named after it. Inside a message only a title after a `---` rule counts, so
+/// headings in replies stay part of them.
+fn markdown_file(text: &str, fallback_title: &str) -> Vec<Conversation> {
+    let mut conversations = Vec::new();
+    let mut current: Option<Conversation> = None;
+    let mut message: Option<Message> = None;
+    let mut awaiting_timestamp = false;
+    let mut after_rule = false;
+    let new_conversation = |title: &str| Conversation {
+        source_id: String::new(),
+        title: title_or_untitled(Some(title)),
+        created_at: None,
+        messages: Vec::new(),
+    };
+    let flush = |current: &mut Option<Conversation>, message: &mut Option<Message>| {
+        if let (Some(conversation), Some(done)) = (current.as_mut(), message.take()) {
+            conversation.messages.extend(finish_message(done));
+        }
+    };
+    for line in text.lines() {
+        let title = line
+            .strip_prefix("# ")
+            .filter(|_| message.is_none() || after_rule);
+        if !line.trim().is_empty() {
+            after_rule = line.trim() == "---";
+        }
+        if let Some(title) = title {
+            flush(&mut current, &mut message);
+            conversations.extend(current.take());
+            current = Some(new_conversation(title));
+        } else if let Some(role) = role_heading(line) {
+            flush(&mut current, &mut message);
+            current.get_or_insert_with(|| new_conversation(fallback_title));
+            message = Some(Message {
+                role,
+                content: String::new(),
+                timestamp: None,
+            });
+            awaiting_timestamp = true;
+        } else if let Some(open) = message.as_mut() {
+            let trimmed = line.trim();
+            if awaiting_timestamp && trimmed.is_empty() {
+                continue;
+            }
+            let stamp = trimmed
+                .strip_prefix('*')
+                .and_then(|rest| rest.strip_suffix('*'))
+                .filter(|_| awaiting_timestamp)
+                .and_then(normalize_timestamp);
+            awaiting_timestamp = false;
+            match stamp {
+                Some(stamp) => open.timestamp = Some(stamp),
+                None => {
+                    open.content.push_str(line);
+                    open.content.push('\n');
+                }
+            }
+        } else if let (Some(conversation), Some(created)) =
+            (current.as_mut(), line.trim().strip_prefix("**Created:**"))
+        {
+            conversation.created_at = normalize_timestamp(created);
+        }
+    }
+    flush(&mut current, &mut message);
+    conversations.extend(current);
+    conversations.retain(|conversation| !conversation.messages.is_empty());
+    for conversation in &mut conversations {
+        let mut fingerprint = conversation.title.clone();
+        for message in &conversation.messages {
+            fingerprint.push('\0');
+            fingerprint.push_str(message.role);
+            fingerprint.push_str(&message.content);
+        }
+        conversation.source_id = format!("markdown:{}", hex_digest(fingerprint.as_bytes()));
+    }
+    conversations
+}
+
+fn markdown_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|err| format!("Failed to read {}: {err}", dir.display()))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            markdown_files(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("md"))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn markdown(path: &Path) -> Result<Vec<Conversation>, String> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        markdown_files(path, &mut files)?;
+        files.sort();
+    } else {
+        files.push(path.to_path_buf());
+    }
+    let mut conversations = Vec::new();
+    for file in files {
+        let text = fs::read_to_string(&file)
+            .map_err(|err| format!("Failed to read {}: {err}", file.display()))?;
+        let stem = file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        conversations.extend(markdown_file(&text, &stem));
+    }
+    Ok(conversations)
+}
+
+fn parse(path: &Path, format: ImportFormat) -> Result<Vec<Conversation>, String> {
+    let mut conversations = match format {
+        ImportFormat::Chatgpt => chatgpt(&read_export(path)?)?,
+        ImportFormat::Claude => claude(&read_export(path)?)?,
+        ImportFormat::Markdown => markdown(path)?,
+    };
+    conversations.retain(|conversation| !conversation.messages.is_empty());
+    Ok(conversations)
+}
+
+/// Source ids already in the local database; empty for a remote backend, which
+/// then gets duplicates if an export is imported twice.
+fn imported_ids<R: Runtime>(app: &AppHandle<R>) -> HashSet<String> {
+    let Ok(db_path) = workspace::db_path(app) else {
+        return HashSet::new();
+    };
+    let Ok(conn) = encryption::open(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY) else {
+        return HashSet::new();
+    };
+    let ids = conn
+        .prepare(
+            "SELECT json_extract(metadata, '$.import.source_id') FROM chat_messages
+             WHERE metadata LIKE '%\"import\"%'",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, Option<String>>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()
+        });
+    ids.map(|ids| ids.into_iter().flatten().collect())
+        .unwrap_or_default()
+}
+
+fn payload(conversation: &Conversation, format: ImportFormat) -> Value {
+    let messages: Vec<Value> = conversation
+        .messages
+        .iter()
+        .enumerate()
+        .map(|(index, message)| {
+            // The first message carries the marker that later imports skip on.
+            let metadata = (index == 0).then(
+                || json!({ "import": { "format": format, "source_id": conversation.source_id } }),
+            );
+            json!({
+                "role": message.role,
+                "content": message.content,
+                "timestamp": message.timestamp.as_ref().or(conversation.created_at.as_ref()),
+                "metadata": metadata,
+            })
+        })
+        .collect();
+    json!({
+        "title": conversation.title,
+        "created_at": conversation.created_at,
+        "messages": messages,
+    })
+}
+
+async fn post<R: Runtime>(app: &AppHandle<R>, body: Value) -> Result<(), String> {
+    let (status, reply) = if rpc::is_attached(app) {
+        let handle = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            rpc::post(&handle, IMPORT_PATH, &body, IMPORT_TIMEOUT)
+        })
+        .await
+        .map_err(|err| format!("Import task failed: {err}"))??
+    } else {
+        let base_url = app
+            .try_state::<BackendState>()
+            .map(|state| state.base_url())
+            .ok_or_else(|| "Backend is not configured yet.".to_string())?;
+        let response = proxy::client(app)
+            .post(format!("{base_url}{IMPORT_PATH}"))
+            .timeout(IMPORT_TIMEOUT)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|err| format!("Backend is unreachable: {err}"))?;
+        let status = response.status().as_u16();
+        let reply = response
+            .bytes()
+            .await
+            .map_err(|err| format!("Backend response was cut off: {err}"))?;
+        (status, reply.to_vec())
+    };
+    if (200..300).contains(&status) {
+        return Ok(());
+    }
+    let detail = serde_json::from_slice::<Value>(&reply)
+        .ok()
+        .and_then(|body| {
+            body.get("detail")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| format!("status {status}"));
+    Err(format!("Backend rejected the conversation: {detail}"))
+}
+
+fn session_id() -> String {
+    let mut bytes = [0u8; 16];
+    let _ = SystemRandom::new().fill(&mut bytes);
+    // A version 4 UUID, like the backend's own session ids.
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// The same rows `POST /sessions/import` writes, for when the backend is stopped.
+fn insert(conn: &mut Connection, config_id: &str, body: &Value) -> Result<(), String> {
+    let now = local_timestamp(Local::now());
+    let messages = body["messages"].as_array().cloned().unwrap_or_default();
+    let created_at = body["created_at"]
+        .as_str()
+        .or_else(|| {
+            messages
+                .first()
+                .and_then(|message| message["timestamp"].as_str())
+        })
+        .unwrap_or(&now)
+        .to_string();
+    let updated_at = messages
+        .last()
+        .and_then(|message| message["timestamp"].as_str())
+        .unwrap_or(&created_at)
+        .to_string();
+    let id = session_id();
+    let mut write = || -> rusqlite::Result<()> {
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO chat_sessions (id, title, config_id, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id,
+                body["title"].as_str(),
+                config_id,
+                created_at,
+                updated_at
+            ],
+        )?;
+        for message in &messages {
+            let metadata = Some(&message["metadata"])
+                .filter(|metadata| !metadata.is_null())
+                .map(Value::to_string);
+            tx.execute(
+                "INSERT INTO chat_messages (session_id, role, content, timestamp, metadata)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    id,
+                    message["role"].as_str(),
+                    message["content"].as_str(),
+                    message["timestamp"].as_str().unwrap_or(&created_at),
+                    metadata
+                ],
+            )?;
+        }
+        tx.commit()
+    };
+    write().map_err(|err| format!("Failed to write the conversation: {err}"))
+}
+
+fn open_database(db_path: &Path) -> Result<(Connection, String), String> {
+    if !db_path.exists() {
+        return Err("Start the backend once before importing.".to_string());
+    }
+    let conn = encryption::open(db_path, OpenFlags::default())
+        .map_err(|err| format!("Failed to open chat database: {err}"))?;
+    conn.busy_timeout(Duration::from_secs(30))
+        .map_err(|err| format!("Failed to configure chat database: {err}"))?;
+    let config_id: Option<String> = conn
+        .query_row(
+            "SELECT id FROM llm_configs ORDER BY is_default DESC, created_at LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|err| format!("Failed to read model configurations: {err}"))?;
+    let config_id = config_id.ok_or("Add a model configuration before importing conversations.")?;
+    Ok((conn, config_id))
+}
+
+fn progress<R: Runtime>(app: &AppHandle<R>, done: usize, total: usize, title: &str) {
+    events::import_progress(
+        app,
+        &ImportProgress {
+            done,
+            total,
+            title: title.to_string(),
+        },
+    );
+}
+
+/// Reads `path` as `format` and brings its conversations into the active workspace,
+/// through the backend when it is up and straight into the database when it is
+/// stopped. `dry_run` only reports what would be imported. Conversations imported
+/// before are skipped; `import://progress` follows the rest.
+#[tauri::command]
+pub async fn import_conversations(
+    app: AppHandle,
+    path: String,
+    format: ImportFormat,
+    dry_run: Option<bool>,
+) -> Result<ImportReport, AppError> {
+    let handle = app.clone();
+    let (conversations, known) = tauri::async_runtime::spawn_blocking(move || {
+        parse(Path::new(&path), format).map(|conversations| (conversations, imported_ids(&handle)))
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Import task failed: {err}")))?
+    .map_err(AppError::invalid_input)?;
+    let previews: Vec<ImportPreview> = conversations
+        .iter()
+        .map(|conversation| ImportPreview {
+            source_id: conversation.source_id.clone(),
+            title: conversation.title.clone(),
+            created_at: conversation.created_at.clone(),
+            messages: conversation.messages.len(),
+            already_imported: known.contains(&conversation.source_id),
+        })
+        .collect();
+    let skipped = previews
+        .iter()
+        .filter(|preview| preview.already_imported)
+        .count();
+    let mut report = ImportReport {
+        target: ImportTarget::Preview,
+        conversations: previews,
+        imported: 0,
+        skipped,
+        failed: Vec::new(),
+    };
+    if dry_run.unwrap_or(false) {
+        return Ok(report);
+    }
+    let fresh: Vec<(String, Value)> = conversations
+        .iter()
+        .filter(|conversation| !known.contains(&conversation.source_id))
+        .map(|conversation| (conversation.title.clone(), payload(conversation, format)))
+        .collect();
+    let total = fresh.len();
+    if health::probe(&app).await {
+        report.target = ImportTarget::Backend;
+        for (done, (title, body)) in fresh.into_iter().enumerate() {
+            match post(&app, body).await {
+                Ok(()) => report.imported += 1,
+                Err(error) => report.failed.push(ImportFailure {
+                    title: title.clone(),
+                    error,
+                }),
+            }
+            progress(&app, done + 1, total, &title);
+        }
+    } else {
+        let local = app
+            .try_state::<BackendState>()
+            .is_some_and(|state| state.remote_url().is_none());
+        if !local {
+            return Err(AppError::unavailable(
+                "The backend is unreachable; import once it is back.",
+            ));
+        }
+        report.target = ImportTarget::Database;
+        let db_path = workspace::db_path(&app)?;
+        let handle = app.clone();
+        let (imported, failed) = tauri::async_runtime::spawn_blocking(move || {
+            let (mut conn, config_id) = open_database(&db_path)?;
+            let mut imported = 0;
+            let mut failed = Vec::new();
+            for (done, (title, body)) in fresh.into_iter().enumerate() {
+                match insert(&mut conn, &config_id, &body) {
+                    Ok(()) => imported += 1,
+                    Err(error) => failed.push(ImportFailure {
+                        title: title.clone(),
+                        error,
+                    }),
+                }
+                progress(&handle, done + 1, total, &title);
+            }
+            Ok::<_, String>((imported, failed))
+        })
+        .await
+        .map_err(|err| AppError::from(format!("Import task failed: {err}")))??;
+        report.imported = imported;
+        report.failed = failed;
+    }
+    tracing::info!(
+        "[Import] Imported {} of {total} conversation(s) from a {format:?} export.",
+        report.imported
+    );
+    Ok(report)
+}
@@ -0,0 +1,188 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use notify_debouncer_full::{
+    new_debouncer,
+    notify::{
+        event::{ModifyKind, RenameMode},
+        EventKind, RecommendedWatcher, RecursiveMode,
+    },
+    DebounceEventResult, Debouncer, RecommendedCache,
+};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    attachments::{self, AttachmentHandle, AttachmentStore},
+    error::AppError,
+    events, locale,
+    notifications::{self, NotificationKind},
+    settings::SettingsStore,
+};
+
+/// Files larger than this are left in place rather than copied into the attachment store.
+const MAX_INBOX_FILE_BYTES: u64 = 100 * 1024 * 1024;
+/// Partial downloads and editor swap files that should never be staged.
+const IGNORED_SUFFIXES: &[&str] = &[".part", ".crdownload", ".download", ".tmp", "~"];
+
+#[derive(Default)]
+pub struct InboxWatcher(Mutex<Option<Debouncer<RecommendedWatcher, RecommendedCache>>>);
+
+events::payload! {
+    pub struct InboxFileEvent {
+        source: PathBuf,
+        attachment: AttachmentHandle,
+    }
+}
+
+fn should_stage(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    !name.starts_with('.')
+        && !IGNORED_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+        && path.is_file()
+}
+
+fn stage_file<R: Runtime>(app: &AppHandle<R>, path: &Path) -> Result<(), String> {
+    let size = fs::metadata(path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?
+        .len();
+    if size > MAX_INBOX_FILE_BYTES {
+        return Err(format!(
+            "Skipping {}; it is larger than {} MB.",
+            path.display(),
+            MAX_INBOX_FILE_BYTES / (1024 * 1024)
+        ));
+    }
+    let bytes =
+        fs::read(path).map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let attachment =
+        app.state::<AttachmentStore>()
+            .store_bytes(&bytes, &name, attachments::mime_for(&name))?;
+    if let Some(reason) = &attachment.quarantine {
+        notifications::notify(
+            app,
+            NotificationKind::InboxFile,
+            &locale::t(app, "notification.inbox_quarantined"),
+            &locale::t_with(
+                app,
+                "notification.inbox_quarantined_body",
+                &[("name", &name), ("reason", reason)],
+            ),
+        )?;
+        return Ok(());
+    }
+    events::inbox_file_staged(
+        app,
+        &InboxFileEvent {
+            source: path.to_path_buf(),
+            attachment,
+        },
+    );
+    notifications::notify(
+        app,
+        NotificationKind::InboxFile,
+        &locale::t(app, "notification.inbox_new"),
+        &locale::t_with(app, "notification.inbox_new_body", &[("name", &name)]),
+    )?;
+    Ok(())
+}
+
+fn handle_events<R: Runtime>(app: &AppHandle<R>, result: DebounceEventResult) {
+    let events = match result {
+        Ok(events) => events,
+        Err(errors) => {
+            for err in errors {
+                tracing::warn!("[Inbox] Watch error: {err}");
+            }
+            return;
+        }
+    };
+    let arrived: BTreeSet<PathBuf> = events
+        .iter()
+        .filter(|event| {
+            matches!(
+                event.kind,
+                EventKind::Create(_)
+                    | EventKind::Modify(ModifyKind::Name(RenameMode::To | RenameMode::Both))
+            )
+        })
+        .filter_map(|event| event.paths.last().cloned())
+        .filter(|path| should_stage(path))
+        .collect();
+    for path in arrived {
+        if let Err(err) = stage_file(app, &path) {
+            tracing::warn!("[Inbox] {err}");
+        }
+    }
+}
+
+/// (Re)starts watching the folder from shell settings; stops watching when none is set.
+pub fn restart<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let state = app.state::<InboxWatcher>();
+    let mut guard = state
+        .0
+        .lock()
+        .map_err(|_| "Inbox watcher is unavailable.".to_string())?;
+    // Dropping the previous debouncer stops its watcher thread.
+    guard.take();
+    let Some(folder) = app.state::<SettingsStore>().get().inbox.folder else {
+        return Ok(());
+    };
+    let folder = PathBuf::from(folder);
+    fs::create_dir_all(&folder)
+        .map_err(|err| format!("Failed to create inbox folder {}: {err}", folder.display()))?;
+    let handle = app.clone();
+    let mut debouncer = new_debouncer(Duration::from_secs(2), None, move |result| {
+        handle_events(&handle, result)
+    })
+    .map_err(|err| format!("Failed to start inbox watcher: {err}"))?;
+    debouncer
+        .watch(&folder, RecursiveMode::NonRecursive)
+        .map_err(|err| format!("Failed to watch {}: {err}", folder.display()))?;
+    tracing::info!("[Inbox] Watching {}", folder.display());
+    *guard = Some(debouncer);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_inbox_folder(store: tauri::State<SettingsStore>) -> Option<String> {
+    store.get().inbox.folder
+}
+
+/// Sets or clears the watched inbox folder; the folder is created if it does not exist.
+#[tauri::command]
+pub fn set_inbox_folder(
+    app: AppHandle,
+    store: tauri::State<SettingsStore>,
+    folder: Option<String>,
+) -> Result<Option<String>, AppError> {
+    let folder = folder
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if let Some(path) = folder.as_deref().map(Path::new) {
+        if !path.is_absolute() {
+            return Err(AppError::invalid_input(
+                "Inbox folder must be an absolute path.",
+            ));
+        }
+        if path.exists() && !path.is_dir() {
+            return Err(AppError::invalid_input(format!(
+                "{} is not a folder.",
+                path.display()
+            )));
+        }
+    }
+    store.update(|settings| settings.inbox.folder = folder.clone())?;
+    restart(&app)?;
+    Ok(folder)
+}
@@ -0,0 +1,750 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    io::Read,
+    panic,
+    path::{Path, PathBuf},
+    sync::{Condvar, Mutex},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use notify_debouncer_full::{
+    new_debouncer, notify::RecommendedWatcher, notify::RecursiveMode, DebounceEventResult,
+    Debouncer, RecommendedCache,
+};
+use rusqlite::OpenFlags;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager, Runtime};
+use zip::ZipArchive;
+
+use crate::{
+    atomic_file, embeddings, encryption,
+    error::AppError,
+    events,
+    settings::SettingsStore,
+    vector_store::{EmbeddingItem, VectorStore},
+    workspace,
+};
+
+const MAX_FILE_BYTES: u64 = 20 * 1024 * 1024;
+const MAX_FILES_PER_FOLDER: usize = 5000;
+const CHUNK_CHARS: usize = 1200;
+const CHUNK_OVERLAP: usize = 200;
+/// Build output, dependencies and VCS metadata are never worth embedding.
+const IGNORED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "venv",
+    "__pycache__",
+];
+const TEXT_EXTENSIONS: &[&str] = &["md", "markdown", "mdx", "txt", "rst"];
+const OFFICE_EXTENSIONS: &[&str] = &["docx", "pptx", "xlsx", "odt", "odp", "ods"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndexingSettings {
+    pub paused: bool,
+    /// Workspaces the user opted out of; every other workspace is indexed.
+    pub excluded_folders: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct FileState {
+    modified: u64,
+    size: u64,
+    chunks: usize,
+}
+
+/// Indexed files per folder, keyed by path relative to the folder.
+type IndexState = BTreeMap<String, BTreeMap<String, FileState>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Job {
+    Scan(PathBuf),
+    File { folder: PathBuf, path: PathBuf },
+    Forget(PathBuf),
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: VecDeque<Job>,
+    paused: bool,
+    /// Held back while the user is away; separate from the user's own pause.
+    idle: bool,
+    processed: usize,
+    current: Option<PathBuf>,
+    last_error: Option<String>,
+}
+
+pub struct Indexer {
+    queue: Mutex<Queue>,
+    wake: Condvar,
+    watchers: Mutex<BTreeMap<PathBuf, Debouncer<RecommendedWatcher, RecommendedCache>>>,
+    state_path: PathBuf,
+    state: Mutex<IndexState>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FolderStatus {
+    path: String,
+    enabled: bool,
+    files: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexingStatus {
+    paused: bool,
+    idle: bool,
+    queued: usize,
+    current: Option<PathBuf>,
+    last_error: Option<String>,
+    folders: Vec<FolderStatus>,
+}
+
+events::payload! {
+    pub struct IndexingProgress {
+        folder: PathBuf,
+        path: PathBuf,
+        processed: usize,
+        remaining: usize,
+    }
+}
+
+events::payload! {
+    /// Indexing paused on this error until it is resumed.
+    pub struct IndexingError {
+        message: String,
+    }
+}
+
+fn collection_name(folder: &Path) -> String {
+    format!("workspace:{}", folder.display())
+}
+
+fn chunk_key(relative: &str, index: usize) -> String {
+    format!("{relative}#{index}")
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn is_supported(path: &Path) -> bool {
+    let ext = extension(path);
+    ext == "pdf"
+        || TEXT_EXTENSIONS.contains(&ext.as_str())
+        || OFFICE_EXTENSIONS.contains(&ext.as_str())
+}
+
+fn is_ignored_dir(name: &str) -> bool {
+    name.starts_with('.') || IGNORED_DIRS.contains(&name)
+}
+
+/// Whether `path` sits in an ignored directory below `folder`.
+fn in_ignored_dir(folder: &Path, path: &Path) -> bool {
+    path.strip_prefix(folder).is_ok_and(|relative| {
+        relative
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .any(|part| is_ignored_dir(&part.as_os_str().to_string_lossy()))
+    })
+}
+
+fn file_state(path: &Path) -> Option<FileState> {
+    let metadata = fs::metadata(path).ok().filter(|meta| meta.is_file())?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|since| since.as_secs())
+        .unwrap_or(0);
+    Some(FileState {
+        modified,
+        size: metadata.len(),
+        chunks: 0,
+    })
+}
+
+fn walk(folder: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![folder.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(kind) = entry.file_type() else {
+                continue;
+            };
+            if kind.is_dir() {
+                if !is_ignored_dir(&entry.file_name().to_string_lossy()) {
+                    pending.push(path);
+                }
+            } else if kind.is_file() && is_supported(&path) {
+                files.push(path);
+                if files.len() >= MAX_FILES_PER_FOLDER {
+                    return files;
+                }
+            }
+        }
+    }
+    files
+}
+
+/// Text content of Office Open XML / OpenDocument markup, paragraphs separated by blank lines.
+fn xml_text(xml: &str) -> String {
+    let mut text = String::with_capacity(xml.len() / 4);
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        if matches!(tag, "/w:p" | "/a:p" | "/text:p" | "/text:h") {
+            text.push_str("\n\n");
+        } else if tag == "/si" || tag.starts_with("w:br") {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn office_text(path: &Path) -> Result<String, String> {
+    let file = fs::File::open(path).map_err(|err| format!("Failed to open: {err}"))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|err| format!("Not a valid Office file: {err}"))?;
+    let mut parts: Vec<String> = archive
+        .file_names()
+        .filter(|name| {
+            *name == "word/document.xml"
+                || *name == "xl/sharedStrings.xml"
+                || *name == "content.xml"
+                || (name.starts_with("ppt/slides/slide") && name.ends_with(".xml"))
+        })
+        .map(str::to_string)
+        .collect();
+    // Natural order so slide10 follows slide9.
+    parts.sort_by_key(|name| (name.len(), name.clone()));
+    let mut text = String::new();
+    for part in parts {
+        let mut xml = String::new();
+        archive
+            .by_name(&part)
+            .map_err(|err| format!("Failed to read {part}: {err}"))?
+            .read_to_string(&mut xml)
+            .map_err(|err| format!("Failed to read {part}: {err}"))?;
+        text.push_str(&xml_text(&xml));
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+fn pdf_text(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|err| format!("Failed to read: {err}"))?;
+    // The PDF parser panics on some malformed files rather than returning an error.
+    panic::catch_unwind(|| pdf_extract::extract_text_from_mem(&bytes))
+        .map_err(|_| "PDF could not be parsed.".to_string())?
+        .map_err(|err| format!("Failed to extract PDF text: {err}"))
+}
+
+fn extract_text(path: &Path) -> Result<String, String> {
+    let ext = extension(path);
+    if ext == "pdf" {
+        pdf_text(path)
+    } else if OFFICE_EXTENSIONS.contains(&ext.as_str()) {
+        office_text(path)
+    } else {
+        fs::read(path)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .map_err(|err| format!("Failed to read: {err}"))
+    }
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Splits on paragraph breaks into ~`CHUNK_CHARS` pieces that overlap slightly, so a
+/// passage cut at a boundary is still whole in one of the two chunks.
+fn chunk(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let mut paragraph = paragraph;
+        while !paragraph.is_empty() {
+            let room = CHUNK_CHARS.saturating_sub(current.len());
+            if paragraph.len() <= room {
+                if !current.is_empty() {
+                    current.push_str("\n\n");
+                }
+                current.push_str(paragraph);
+                paragraph = "";
+                continue;
+            }
+            if current.is_empty() || current.len() < CHUNK_OVERLAP * 2 {
+                let cut = floor_char_boundary(paragraph, room.max(CHUNK_OVERLAP));
+                current.push_str(&paragraph[..cut]);
+                paragraph = &paragraph[cut..];
+            }
+            let tail = floor_char_boundary(&current, current.len().saturating_sub(CHUNK_OVERLAP));
+            let overlap = current[tail..].to_string();
+            chunks.push(std::mem::replace(&mut current, overlap));
+        }
+    }
+    if current.len() > CHUNK_OVERLAP || (chunks.is_empty() && !current.is_empty()) {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Workspace folders of every conversation, most recently used first.
+fn workspaces(db_path: &Path) -> Result<Vec<String>, String> {
+    if !db_path.exists() {
+        return Ok(Vec::new());
+    }
+    let conn = encryption::open(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|err| format!("Failed to open chat database: {err}"))?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT work_path FROM chat_sessions
+             WHERE work_path IS NOT NULL AND work_path <> ''
+             GROUP BY work_path ORDER BY MAX(updated_at) DESC",
+        )
+        .map_err(|err| format!("Failed to load workspaces: {err}"))?;
+    stmt.query_map([], |row| row.get(0))
+        .and_then(|rows| rows.collect())
+        .map_err(|err| format!("Failed to load workspaces: {err}"))
+}
+
+impl Indexer {
+    pub fn new(state_path: PathBuf, paused: bool) -> Self {
+        let state = fs::read_to_string(&state_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        Self {
+            queue: Mutex::new(Queue {
+                paused,
+                ..Queue::default()
+            }),
+            wake: Condvar::new(),
+            watchers: Mutex::new(BTreeMap::new()),
+            state_path,
+            state: Mutex::new(state),
+        }
+    }
+
+    fn push(&self, job: Job) {
+        if let Ok(mut queue) = self.queue.lock() {
+            if !queue.jobs.contains(&job) {
+                queue.jobs.push_back(job);
+                self.wake.notify_one();
+            }
+        }
+    }
+
+    /// Blocks until a job is available and indexing is neither paused nor idle.
+    fn next(&self) -> Option<(Job, usize)> {
+        let mut queue = self.queue.lock().ok()?;
+        while queue.paused || queue.idle || queue.jobs.is_empty() {
+            queue = self.wake.wait(queue).ok()?;
+        }
+        let job = queue.jobs.pop_front()?;
+        queue.processed += 1;
+        Some((job, queue.jobs.len()))
+    }
+
+    fn set_paused(&self, paused: bool, error: Option<String>) {
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.paused = paused;
+            if error.is_some() || !paused {
+                queue.last_error = error;
+            }
+            self.wake.notify_one();
+        }
+    }
+
+    fn set_idle(&self, idle: bool) {
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.idle = idle;
+            self.wake.notify_one();
+        }
+    }
+
+    fn save_state(&self, state: &IndexState) -> Result<(), String> {
+        let raw = serde_json::to_string(state)
+            .map_err(|err| format!("Failed to serialize index state: {err}"))?;
+        atomic_file::write(&self.state_path, raw)
+            .map_err(|err| format!("Failed to save index state: {err}"))
+    }
+
+    fn indexed(&self, folder: &Path) -> BTreeMap<String, FileState> {
+        self.state
+            .lock()
+            .ok()
+            .and_then(|state| state.get(&folder.to_string_lossy().into_owned()).cloned())
+            .unwrap_or_default()
+    }
+
+    fn record(&self, folder: &Path, relative: &str, file: Option<FileState>) -> Result<(), String> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| "Index state is unavailable.".to_string())?;
+        let files = state
+            .entry(folder.to_string_lossy().into_owned())
+            .or_default();
+        match file {
+            Some(file) => files.insert(relative.to_string(), file),
+            None => files.remove(relative),
+        };
+        self.save_state(&state)
+    }
+
+    fn forget(&self, folder: &Path) -> Result<(), String> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| "Index state is unavailable.".to_string())?;
+        state.remove(&folder.to_string_lossy().into_owned());
+        self.save_state(&state)
+    }
+}
+
+fn enabled_folders<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<(String, bool)>, String> {
+    let excluded = app.state::<SettingsStore>().get().indexing.excluded_folders;
+    let db_path = workspace::db_path(app)?;
+    Ok(workspaces(&db_path)?
+        .into_iter()
+        .filter(|folder| Path::new(folder).is_dir())
+        .map(|folder| {
+            let enabled = !excluded.contains(&folder);
+            (folder, enabled)
+        })
+        .collect())
+}
+
+fn handle_events<R: Runtime>(app: &AppHandle<R>, folder: &Path, result: DebounceEventResult) {
+    let events = match result {
+        Ok(events) => events,
+        Err(errors) => {
+            for err in errors {
+                tracing::warn!("[Indexer] Watch error: {err}");
+            }
+            return;
+        }
+    };
+    let indexer = app.state::<Indexer>();
+    for path in events.iter().flat_map(|event| event.paths.iter()) {
+        if in_ignored_dir(folder, path) {
+            continue;
+        }
+        if path.is_dir() {
+            indexer.push(Job::Scan(folder.to_path_buf()));
+        } else if is_supported(path) {
+            indexer.push(Job::File {
+                folder: folder.to_path_buf(),
+                path: path.clone(),
+            });
+        }
+    }
+}
+
+/// Holds the queue while the user is away; jobs keep queueing and run on return.
+pub fn set_idle<R: Runtime>(app: &AppHandle<R>, idle: bool) {
+    if let Some(indexer) = app.try_state::<Indexer>() {
+        indexer.set_idle(idle);
+    }
+}
+
+/// Watches every enabled workspace, queues a scan of each, and drops opted-out ones.
+pub fn sync_folders<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let folders = enabled_folders(app)?;
+    let indexer = app.state::<Indexer>();
+    let mut watchers = indexer
+        .watchers
+        .lock()
+        .map_err(|_| "Indexer is unavailable.".to_string())?;
+    for (folder, enabled) in folders {
+        let folder = PathBuf::from(folder);
+        if !enabled {
+            if watchers.remove(&folder).is_some() || !indexer.indexed(&folder).is_empty() {
+                indexer.push(Job::Forget(folder));
+            }
+            continue;
+        }
+        if watchers.contains_key(&folder) {
+            continue;
+        }
+        let handle = app.clone();
+        let watched = folder.clone();
+        let mut debouncer = new_debouncer(Duration::from_secs(3), None, move |result| {
+            handle_events(&handle, &watched, result)
+        })
+        .map_err(|err| format!("Failed to start folder watcher: {err}"))?;
+        if let Err(err) = debouncer.watch(&folder, RecursiveMode::Recursive) {
+            tracing::warn!("[Indexer] Failed to watch {}: {err}", folder.display());
+            continue;
+        }
+        watchers.insert(folder.clone(), debouncer);
+        indexer.push(Job::Scan(folder));
+    }
+    Ok(())
+}
+
+fn scan<R: Runtime>(app: &AppHandle<R>, folder: &Path) -> Result<(), String> {
+    let indexer = app.state::<Indexer>();
+    let indexed = indexer.indexed(folder);
+    let files = walk(folder);
+    for path in &files {
+        let relative = path.strip_prefix(folder).unwrap_or(path).to_string_lossy();
+        let unchanged = indexed
+            .get(relative.as_ref())
+            .zip(file_state(path))
+            .is_some_and(|(known, current)| {
+                known.modified == current.modified && known.size == current.size
+            });
+        if !unchanged {
+            indexer.push(Job::File {
+                folder: folder.to_path_buf(),
+                path: path.clone(),
+            });
+        }
+    }
+    for relative in indexed.keys() {
+        let path = folder.join(relative);
+        if !files.contains(&path) {
+            indexer.push(Job::File {
+                folder: folder.to_path_buf(),
+                path,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn index_file<R: Runtime>(app: &AppHandle<R>, folder: &Path, path: &Path) -> Result<(), String> {
+    let indexer = app.state::<Indexer>();
+    let store = app.state::<VectorStore>();
+    let collection = collection_name(folder);
+    let relative = path
+        .strip_prefix(folder)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned();
+    let mut previous = indexer.indexed(folder).get(&relative).copied();
+
+    let current = file_state(path).filter(|file| file.size <= MAX_FILE_BYTES);
+    let chunks = match current {
+        Some(_) => extract_text(path)
+            .map(|text| chunk(&text))
+            .unwrap_or_else(|err| {
+                // Unreadable files are recorded as empty so they are retried only once changed.
+                tracing::warn!("[Indexer] Skipping {}: {err}", path.display());
+                Vec::new()
+            }),
+        None => Vec::new(),
+    };
+    let count = chunks.len();
+
+    if !chunks.is_empty() {
+        let vectors = tauri::async_runtime::block_on(embeddings::embed(app, &chunks))?;
+        let dimensions = vectors.first().map(Vec::len).unwrap_or(0);
+        match store.dimensions(&collection)? {
+            Some(existing) if existing != dimensions => {
+                // The embedding model changed; vectors from the old model are useless.
+                store.delete(&collection)?;
+                indexer.forget(folder)?;
+                store.create(&collection, dimensions)?;
+                indexer.push(Job::Scan(folder.to_path_buf()));
+                previous = None;
+            }
+            Some(_) => {}
+            None => store.create(&collection, dimensions)?,
+        }
+        let items: Vec<EmbeddingItem> = chunks
+            .into_iter()
+            .zip(vectors)
+            .enumerate()
+            .map(|(index, (text, embedding))| EmbeddingItem {
+                key: chunk_key(&relative, index),
+                text,
+                metadata: Some(json!({ "path": path, "chunk": index })),
+                embedding,
+            })
+            .collect();
+        store.upsert(&collection, &items)?;
+    }
+
+    let stale: Vec<String> = (count..previous.map_or(0, |file| file.chunks))
+        .map(|index| chunk_key(&relative, index))
+        .collect();
+    if !stale.is_empty() && store.dimensions(&collection)?.is_some() {
+        store.remove(&collection, &stale)?;
+    }
+    indexer.record(
+        folder,
+        &relative,
+        current.map(|file| FileState {
+            chunks: count,
+            ..file
+        }),
+    )
+}
+
+fn run<R: Runtime>(app: &AppHandle<R>, job: &Job) -> Result<(), String> {
+    match job {
+        Job::Scan(folder) => scan(app, folder),
+        Job::File { folder, path } => index_file(app, folder, path),
+        Job::Forget(folder) => {
+            let store = app.state::<VectorStore>();
+            let collection = collection_name(folder);
+            if store.dimensions(&collection)?.is_some() {
+                store.delete(&collection)?;
+            }
+            app.state::<Indexer>().forget(folder)
+        }
+    }
+}
+
+fn worker<R: Runtime>(app: AppHandle<R>) {
+    let indexer = app.state::<Indexer>();
+    while let Some((job, remaining)) = indexer.next() {
+        if let Ok(mut queue) = indexer.queue.lock() {
+            queue.current = match &job {
+                Job::File { path, .. } => Some(path.clone()),
+                Job::Scan(folder) | Job::Forget(folder) => Some(folder.clone()),
+            };
+        }
+        let result = run(&app, &job);
+        let processed = indexer.queue.lock().map_or(0, |mut queue| {
+            queue.current = None;
+            queue.processed
+        });
+        match result {
+            Ok(()) => {
+                if let Job::File { folder, path } = &job {
+                    events::indexing_progress(
+                        &app,
+                        &IndexingProgress {
+                            folder: folder.clone(),
+                            path: path.clone(),
+                            processed,
+                            remaining,
+                        },
+                    );
+                }
+            }
+            Err(err) => {
+                // Usually the embedding server is down; retrying every file would
+                // only repeat the failure, so hold the queue until the user resumes.
+                tracing::warn!("[Indexer] {err}");
+                if let Ok(mut queue) = indexer.queue.lock() {
+                    queue.jobs.push_front(job);
+                    queue.processed -= 1;
+                }
+                indexer.set_paused(true, Some(err.clone()));
+                events::indexing_error(&app, &IndexingError { message: err });
+            }
+        }
+        let idle = indexer.queue.lock().is_ok_and(|mut queue| {
+            let idle = queue.jobs.is_empty();
+            if idle {
+                queue.processed = 0;
+            }
+            idle
+        });
+        if idle {
+            events::indexing_idle(&app, &events::Empty {});
+        }
+    }
+}
+
+/// Starts the indexing worker and begins watching workspaces.
+pub fn start<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let handle = app.clone();
+    std::thread::Builder::new()
+        .name("indexer".into())
+        .spawn(move || worker(handle))
+        .map_err(|err| format!("Failed to start indexer: {err}"))?;
+    sync_folders(app)
+}
+
+#[tauri::command]
+pub fn get_indexing_status(app: AppHandle) -> Result<IndexingStatus, AppError> {
+    let indexer = app.state::<Indexer>();
+    let folders = enabled_folders(&app)?
+        .into_iter()
+        .map(|(path, enabled)| FolderStatus {
+            files: indexer.indexed(Path::new(&path)).len(),
+            path,
+            enabled,
+        })
+        .collect();
+    let queue = indexer
+        .queue
+        .lock()
+        .map_err(|_| AppError::unavailable("Indexer is unavailable."))?;
+    Ok(IndexingStatus {
+        paused: queue.paused,
+        idle: queue.idle,
+        queued: queue.jobs.len(),
+        current: queue.current.clone(),
+        last_error: queue.last_error.clone(),
+        folders,
+    })
+}
+
+#[tauri::command]
+pub fn pause_indexing(app: AppHandle) -> Result<(), AppError> {
+    app.state::<SettingsStore>()
+        .update(|settings| settings.indexing.paused = true)?;
+    app.state::<Indexer>().set_paused(true, None);
+    Ok(())
+}
+
+/// Resumes the queue, also after an error paused it, and picks up new workspaces.
+#[tauri::command]
+pub fn resume_indexing(app: AppHandle) -> Result<(), AppError> {
+    app.state::<SettingsStore>()
+        .update(|settings| settings.indexing.paused = false)?;
+    app.state::<Indexer>().set_paused(false, None);
+    Ok(sync_folders(&app)?)
+}
+
+/// Opts a workspace in or out; opting out removes its vectors.
+#[tauri::command]
+pub fn set_folder_indexing(app: AppHandle, folder: String, enabled: bool) -> Result<(), AppError> {
+    app.state::<SettingsStore>().update(|settings| {
+        let excluded = &mut settings.indexing.excluded_folders;
+        excluded.retain(|existing| existing != &folder);
+        if !enabled {
+            excluded.push(folder.clone());
+        }
+    })?;
+    Ok(sync_folders(&app)?)
+}
+
+/// Queues a full rescan of `folder`, re-embedding only files that changed.
+#[tauri::command]
+pub fn reindex_folder(app: AppHandle, folder: String) -> Result<(), AppError> {
+    app.state::<Indexer>()
+        .push(Job::Scan(PathBuf::from(folder)));
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use tauri::{plugin::TauriPlugin, AppHandle, Runtime};
+
+use crate::{events, main_window};
+
+events::payload! {
+    /// What a second launch was started with, passed on to the running instance.
+    pub struct SecondLaunch {
+        /// Command line without the executable; deep links arrive here on Windows and Linux.
+        args: Vec<String>,
+        cwd: String,
+    }
+}
+
+fn forward<R: Runtime>(app: &AppHandle<R>, args: Vec<String>, cwd: String) {
+    let args: Vec<String> = args.into_iter().skip(1).collect();
+    tracing::info!(
+        "[Instance] Second launch forwarded with {} argument(s).",
+        args.len()
+    );
+    main_window::reveal(app);
+    events::second_launch(app, main_window::LABEL, &SecondLaunch { args, cwd });
+}
+
+/// Keeps one instance per user: a second launch exits before it spawns a backend
+/// on the same `chat_app.db`, and the running one comes forward and receives its
+/// arguments on `instance://second-launch`. Must be the first plugin registered.
+pub fn plugin<R: Runtime>() -> TauriPlugin<R> {
+    tauri_plugin_single_instance::init(|app, args, cwd| forward(app, args, cwd))
+}
@@ -0,0 +1,228 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+use tauri::{ipc::CapabilityBuilder, AppHandle, Manager, Runtime};
+
+use crate::{
+    error::{AppError, ErrorCode},
+    settings::SettingsStore,
+};
+
+const KIOSK_FLAG: &str = "--kiosk";
+/// Clears a persisted kiosk setting; the only way out once it is on.
+const UNLOCK_FLAG: &str = "--no-kiosk";
+
+/// The invoke handler `commands!` builds.
+pub type Handler = fn(tauri::ipc::Invoke<tauri::Wry>) -> bool;
+
+/// A command registered through `commands!`.
+pub struct CommandAccess {
+    /// As written in the registration, module path included.
+    pub path: &'static str,
+    /// Changes state, runs code or writes files, so kiosk mode blocks it.
+    pub mutating: bool,
+}
+
+#[doc(hidden)]
+macro_rules! access {
+    (mutating) => {
+        true
+    };
+    (read_only) => {
+        false
+    };
+}
+
+/// `generate_handler!` for commands tagged `#[mutating]` or `#[read_only]`, paired
+/// with the table `blocked` checks. A command without a tag does not compile, so
+/// each one is classified where it is registered.
+macro_rules! commands {
+    ($(#[$access:ident] $($segment:ident)::+),* $(,)?) => {
+        (
+            {
+                let handler: $crate::kiosk::Handler = tauri::generate_handler![$($($segment)::+),*];
+                handler
+            },
+            &[$($crate::kiosk::CommandAccess {
+                path: stringify!($($segment)::+),
+                mutating: $crate::kiosk::access!($access),
+            }),*],
+        )
+    };
+}
+
+pub(crate) use {access, commands};
+
+/// Plugin-fs commands the webviews may otherwise use to write anywhere.
+const FS_WRITE_DENIALS: &[&str] = &[
+    "fs:deny-write-text-file",
+    "fs:deny-write-file",
+    "fs:deny-write",
+    "fs:deny-mkdir",
+    "fs:deny-create",
+    "fs:deny-remove",
+    "fs:deny-rename",
+    "fs:deny-copy-file",
+    "fs:deny-truncate",
+];
+
+#[derive(Default)]
+pub struct KioskMode(AtomicBool);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KioskSource {
+    Flag,
+    Setting,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KioskStatus {
+    enabled: bool,
+    source: Option<KioskSource>,
+}
+
+fn env_flag() -> bool {
+    std::env::var("TAURI_AGENT_KIOSK")
+        .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+fn launch_flag() -> bool {
+    std::env::args().any(|arg| arg == KIOSK_FLAG) || env_flag()
+}
+
+fn source<R: Runtime>(app: &AppHandle<R>) -> Option<KioskSource> {
+    if launch_flag() {
+        return Some(KioskSource::Flag);
+    }
+    app.try_state::<SettingsStore>()
+        .filter(|store| store.get().kiosk)
+        .map(|_| KioskSource::Setting)
+}
+
+pub fn is_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.try_state::<KioskMode>()
+        .is_some_and(|mode| mode.0.load(Ordering::SeqCst))
+}
+
+fn lock_down<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let capability = FS_WRITE_DENIALS.iter().fold(
+        CapabilityBuilder::new("kiosk").windows(["main", "workdir-*", "conversation-*"]),
+        |builder, permission| builder.permission(*permission),
+    );
+    app.add_capability(capability)
+        .map_err(|err| format!("Failed to restrict file access: {err}"))?;
+    if let Some(mode) = app.try_state::<KioskMode>() {
+        mode.0.store(true, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+/// Resolves kiosk mode at startup, before the backend is spawned.
+pub fn init<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    if std::env::args().any(|arg| arg == UNLOCK_FLAG) {
+        if let Some(store) = app.try_state::<SettingsStore>() {
+            store.update(|settings| settings.kiosk = false)?;
+        }
+    }
+    if source(app).is_some() {
+        lock_down(app)?;
+        tracing::info!("[Kiosk] Running in read-only mode.");
+    }
+    Ok(())
+}
+
+fn is_mutating(commands: &[CommandAccess], command: &str) -> bool {
+    commands.iter().any(|access| {
+        access.mutating && access.path.rsplit("::").next().map(str::trim) == Some(command)
+    })
+}
+
+fn refusal(enabled: bool, commands: &[CommandAccess], command: &str) -> Option<AppError> {
+    (enabled && is_mutating(commands, command)).then(|| {
+        AppError::new(
+            ErrorCode::ReadOnly,
+            "This action is disabled in kiosk mode.",
+        )
+    })
+}
+
+/// Rejection for `command` when kiosk mode forbids it.
+pub fn blocked<R: Runtime>(
+    app: &AppHandle<R>,
+    commands: &[CommandAccess],
+    command: &str,
+) -> Option<AppError> {
+    refusal(is_enabled(app), commands, command)
+}
+
+#[tauri::command]
+pub fn get_kiosk_mode(app: AppHandle) -> KioskStatus {
+    let enabled = is_enabled(&app);
+    KioskStatus {
+        enabled,
+        source: if enabled { source(&app) } else { None },
+    }
+}
+
+/// Locks the app down now and on every later launch, restarting the backend in
+/// read-only mode. Turning it off requires relaunching with `--no-kiosk`.
+#[tauri::command]
+pub async fn enable_kiosk_mode(app: AppHandle) -> Result<KioskStatus, AppError> {
+    if !is_enabled(&app) {
+        app.state::<SettingsStore>()
+            .update(|settings| settings.kiosk = true)?;
+        lock_down(&app)?;
+        let handle = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            crate::with_backend_stopped::<_, _, AppError>(&handle, || Ok(()))
+        })
+        .await
+        .map_err(|err| AppError::from(format!("Restart task failed: {err}")))??;
+    }
+    Ok(get_kiosk_mode(app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMANDS: &[CommandAccess] = &[
+        CommandAccess {
+            path: "secrets::set_secret",
+            mutating: true,
+        },
+        CommandAccess {
+            path: "secrets::list_secret_keys",
+            mutating: false,
+        },
+        CommandAccess {
+            path: "restart_backend",
+            mutating: true,
+        },
+    ];
+
+    #[test]
+    fn commands_match_on_their_last_path_segment() {
+        assert!(is_mutating(COMMANDS, "set_secret"));
+        assert!(is_mutating(COMMANDS, "restart_backend"));
+        assert!(!is_mutating(COMMANDS, "secrets::set_secret"));
+        assert!(!is_mutating(COMMANDS, "secret"));
+    }
+
+    #[test]
+    fn read_only_and_unknown_commands_are_not_mutating() {
+        assert!(!is_mutating(COMMANDS, "list_secret_keys"));
+        assert!(!is_mutating(COMMANDS, "get_kiosk_mode"));
+        assert!(!is_mutating(&[], "set_secret"));
+    }
+
+    #[test]
+    fn only_mutating_commands_are_refused_and_only_in_kiosk_mode() {
+        let error = refusal(true, COMMANDS, "set_secret").expect("refused");
+        assert_eq!(error.code, ErrorCode::ReadOnly);
+        assert!(refusal(true, COMMANDS, "list_secret_keys").is_none());
+        assert!(refusal(false, COMMANDS, "set_secret").is_none());
+    }
+}
@@ -1,11 +1,157 @@
 use std::{
-    net::TcpListener,
-    path::PathBuf,
-    process::{Child, Command, Stdio},
-    sync::Mutex,
+    cell::Cell,
+    net::{IpAddr, SocketAddr, TcpListener},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU16, Ordering},
+        Mutex,
+    },
+    time::Duration,
 };
 
-use tauri::{Manager, RunEvent, WindowEvent};
+use tauri::{DragDropEvent, Manager, RunEvent, WindowEvent};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+mod app_lock;
+mod app_menu;
+mod appearance;
+mod approvals;
+mod arch;
+mod archive;
+mod artifacts;
+mod assets;
+mod atomic_file;
+mod attachments;
+mod audio;
+mod automation;
+mod autostart;
+mod backend;
+mod backend_log;
+mod backend_profiles;
+mod backup;
+mod capture;
+mod chat_stream;
+mod cli;
+mod clipboard;
+mod compat;
+mod config_files;
+mod connectivity;
+mod conversation_windows;
+mod costs;
+mod db_backup;
+mod debugger;
+mod deep_link;
+mod desktop;
+mod diagnostics;
+mod dialogs;
+mod email;
+mod embeddings;
+mod encryption;
+mod error;
+mod events;
+mod export;
+mod file_drop;
+mod health;
+mod idle;
+mod import;
+mod inbox;
+mod indexer;
+mod instance;
+mod kiosk;
+mod locale;
+mod log_files;
+mod logging;
+mod main_window;
+mod markdown;
+mod migration;
+mod monitor;
+mod monitors;
+mod network;
+mod notifications;
+mod outbox;
+mod palette;
+mod permissions;
+mod plugins;
+mod process_tree;
+mod proxy;
+mod proxy_config;
+mod python_runtime;
+mod quick_ask;
+mod replay;
+mod rpc;
+mod scan;
+mod scheduler;
+mod search;
+mod secrets;
+mod sessions;
+mod settings;
+mod shortcuts;
+mod sidecar;
+mod snapshots;
+mod speech;
+mod stale_backend;
+mod startup;
+mod taskbar;
+mod temp_files;
+mod tool_host;
+mod tool_policy;
+mod trash;
+mod tray;
+mod updater;
+mod usage;
+mod vector_store;
+mod watchdog;
+mod webview;
+mod workspace;
+
+use app_lock::AppLock;
+use app_menu::ZoomLevels;
+use appearance::SystemTheme;
+use attachments::AttachmentStore;
+use audio::AudioRecorder;
+use backend::BackendReadiness;
+use backend_profiles::BackendTarget;
+use backup::BackupState;
+use capture::{ContextCapture, ScreenSelection};
+use chat_stream::ChatStreams;
+use compat::BackendVersion;
+use config_files::ConfigWatcher;
+use connectivity::Connectivity;
+use db_backup::DatabaseBackups;
+use debugger::BackendDebugger;
+use deep_link::DeepLinks;
+use error::AppError;
+use health::HealthMonitor;
+use idle::Presence;
+use inbox::InboxWatcher;
+use indexer::Indexer;
+use kiosk::KioskMode;
+use log_files::LogFiles;
+use migration::Onboarding;
+use monitor::ResourceMonitor;
+use monitors::GeometrySaves;
+use notifications::{RecentNotification, RunNotices};
+use outbox::Outbox;
+use proxy::Proxy;
+use replay::Replay;
+use rpc::RpcBridge;
+use scheduler::Scheduler;
+use search::SearchIndex;
+use sessions::Sessions;
+use settings::{BackendTransport, SettingsStore};
+use shortcuts::ShortcutRegistry;
+use sidecar::{Readiness, SidecarSpec, Sidecars};
+use speech::Speaker;
+use stale_backend::StaleBackend;
+use startup::{Stage, StartupGate};
+use taskbar::ActiveRun;
+use temp_files::TempFiles;
+use tool_policy::ToolPolicy;
+use tray::QuickReply;
+use updater::PendingUpdate;
+use usage::{Metering, UsageStore};
+use vector_store::VectorStore;
+use watchdog::Watchdog;
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
@@ -13,20 +159,195 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-struct BackendChild(Mutex<Option<Child>>);
+/// Supervisor name of the Python backend sidecar.
+const BACKEND_SIDECAR: &str = "backend";
+/// File name of the backend executable, without architecture or extension.
+const BACKEND_BINARY: &str = "tauri-agent-backend";
+/// Digests of the backend binaries found at build time, comma-separated.
+const BACKEND_SHA256: &str = env!("TAURI_AGENT_SIDECAR_SHA256");
+/// Parent environment the backend may see; everything else, such as unrelated
+/// tokens in a developer's shell, is dropped. `TAURI_AGENT_PASS_ENV` and `backend.env` add names.
+const BACKEND_ENV_ALLOWLIST: &[&str] = &[
+    // Process basics on every OS.
+    "PATH",
+    "PATHEXT",
+    "HOME",
+    "USER",
+    "USERNAME",
+    "LOGNAME",
+    "SHELL",
+    "TERM",
+    "LANG",
+    "LANGUAGE",
+    "LC_*",
+    "TZ",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "XDG_*",
+    // Windows needs these for sockets, crypto and child shells.
+    "SYSTEMROOT",
+    "SYSTEMDRIVE",
+    "WINDIR",
+    "COMSPEC",
+    "USERPROFILE",
+    "HOMEDRIVE",
+    "HOMEPATH",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "PROGRAMDATA",
+    "PROGRAMFILES",
+    "PROGRAMFILES(X86)",
+    "NUMBER_OF_PROCESSORS",
+    "PROCESSOR_ARCHITECTURE",
+    // Corporate proxies and certificate stores for LLM calls.
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "NO_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+    "no_proxy",
+    "SSL_CERT_FILE",
+    "SSL_CERT_DIR",
+    "REQUESTS_CA_BUNDLE",
+    // Read by the backend itself.
+    "TAURI_AGENT_*",
+    "TOOLS_*",
+    "PTY_*",
+    "CONPTY_*",
+    "AGENT_STREAM_*",
+    "CONTEXT_COMPRESS_DEBUG",
+    "TAVILY_API_KEY",
+    "RG_EXE",
+    "RIPGREP_EXE",
+];
+
+/// Where an external backend is expected to listen, and the fallback when no free
+/// port can be found for the sidecar.
+const DEFAULT_BACKEND_PORT: u16 = 8000;
+
 struct BackendState {
-    port: u16,
+    host: IpAddr,
+    /// Moves when a restart finds the previous port taken.
+    port: AtomicU16,
+    /// The selected backend profile; swapped by `select_backend_profile`.
+    target: Mutex<BackendTarget>,
+    transport: BackendTransport,
 }
 
-#[tauri::command]
-fn get_backend_base_url(state: tauri::State<BackendState>) -> String {
-    if let Ok(value) = std::env::var("VITE_API_BASE_URL") {
-        let trimmed = value.trim();
-        if !trimmed.is_empty() {
-            return trimmed.to_string();
+impl BackendState {
+    fn port(&self) -> u16 {
+        self.port.load(Ordering::SeqCst)
+    }
+
+    fn target(&self) -> BackendTarget {
+        self.target
+            .lock()
+            .map(|target| target.clone())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().clone())
+    }
+
+    fn set_target(&self, target: BackendTarget) {
+        match self.target.lock() {
+            Ok(mut current) => *current = target,
+            Err(poisoned) => *poisoned.into_inner() = target,
         }
     }
-    format!("http://127.0.0.1:{}", state.port)
+
+    /// The external or remote profile's URL, used instead of the sidecar address.
+    fn remote_url(&self) -> Option<String> {
+        self.target().url
+    }
+
+    fn base_url(&self) -> String {
+        if let Ok(value) = std::env::var("VITE_API_BASE_URL") {
+            let trimmed = value.trim();
+            if !trimmed.is_empty() {
+                return trimmed.to_string();
+            }
+        }
+        if let Some(url) = self.remote_url() {
+            return url;
+        }
+        if self.transport == BackendTransport::Stdio {
+            return rpc::base_url();
+        }
+        let addr = SocketAddr::new(network::connect_host(self.host), self.port());
+        format!("http://{addr}")
+    }
+
+    /// What the webview calls: `base_url`, or the proxy scheme in front of it.
+    fn webview_base_url(&self, proxied: bool) -> String {
+        if proxied {
+            proxy::base_url()
+        } else {
+            self.base_url()
+        }
+    }
+
+    fn endpoint<R: tauri::Runtime>(&self, app: &tauri::AppHandle<R>) -> BackendEndpoint {
+        let proxied = proxy::is_enabled(app);
+        let token = proxy::token(app);
+        // Browsers cannot set headers on a WebSocket, and it cannot go through a URI
+        // scheme either, so it keeps the port and carries the token in the query.
+        let events_url = (proxied || token.is_some()).then(|| {
+            let base = self.base_url().replacen("http", "ws", 1);
+            match token {
+                Some(token) => format!("{base}/ws?token={token}"),
+                None => format!("{base}/ws"),
+            }
+        });
+        BackendEndpoint {
+            url: self.webview_base_url(proxied),
+            port: (self.remote_url().is_none() && self.transport == BackendTransport::Tcp)
+                .then(|| self.port()),
+            transport: self.transport,
+            events_url,
+        }
+    }
+}
+
+events::payload! {
+    pub struct BackendEndpoint {
+        url: String,
+        /// The loopback port, when the backend is reached over one.
+        port: Option<u16>,
+        transport: BackendTransport,
+        /// Where to open the event WebSocket, when it is not derived from `url`.
+        events_url: Option<String>,
+    }
+}
+
+events::payload! {
+    pub struct BaseUrl {
+        url: String,
+    }
+}
+
+fn announce_backend_url<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    if let Some(state) = app.try_state::<BackendState>() {
+        events::backend_base_url(
+            app,
+            &BaseUrl {
+                url: state.webview_base_url(proxy::is_enabled(app)),
+            },
+        );
+        events::backend_url(app, &state.endpoint(app));
+    }
+}
+
+/// The address the webview should call; `backend://base-url` announces changes.
+#[tauri::command]
+fn get_backend_base_url(app: tauri::AppHandle, state: tauri::State<BackendState>) -> String {
+    state.webview_base_url(proxy::is_enabled(&app))
+}
+
+/// The address plus the port it was given; `backend://url` carries the same payload.
+#[tauri::command]
+fn get_backend_url(app: tauri::AppHandle, state: tauri::State<BackendState>) -> BackendEndpoint {
+    state.endpoint(&app)
 }
 
 fn log_sandbox_status() {
@@ -36,72 +357,25 @@ fn log_sandbox_status() {
         let home_in_container = home.contains("/Library/Containers/");
         let sandbox_id = std::env::var("APP_SANDBOX_CONTAINER_ID").unwrap_or_default();
         let sandbox_label = if sandbox_id.is_empty() { "(none)" } else { &sandbox_id };
-        eprintln!(
+        tracing::info!(
             "[Sandbox] macos home_in_container={} app_sandbox_id={}",
             home_in_container, sandbox_label
         );
     }
 }
 
-fn resolve_backend_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<PathBuf, String> {
-    let resource_dir = app
-        .path()
-        .resource_dir()
-        .map_err(|_| "Failed to resolve resource directory.".to_string())?;
-    let exe_name = if cfg!(windows) {
-        "tauri-agent-backend.exe"
-    } else {
-        "tauri-agent-backend"
-    };
-    let candidate = resource_dir.join(exe_name);
-    if candidate.exists() {
-        return Ok(candidate);
-    }
-    let fallback = std::env::current_exe()
-        .ok()
-        .and_then(|path| path.parent().map(|parent| parent.join(exe_name)));
-    if let Some(path) = fallback {
-        if path.exists() {
-            return Ok(path);
-        }
-    }
-    Err(format!(
-        "Backend sidecar not found at {}.",
-        candidate.display()
-    ))
-}
-
-fn pick_backend_port() -> Result<u16, String> {
-    let listener = TcpListener::bind("127.0.0.1:0")
-        .map_err(|err| format!("Failed to bind to an ephemeral port: {err}"))?;
-    let port = listener
-        .local_addr()
-        .map_err(|err| format!("Failed to read ephemeral port: {err}"))?
-        .port();
-    drop(listener);
-    Ok(port)
-}
-
-fn spawn_backend<R: tauri::Runtime>(
-    app: &tauri::AppHandle<R>,
-    port: u16,
-) -> Result<Child, String> {
-    if std::env::var("TAURI_AGENT_EXTERNAL_BACKEND")
-        .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
-        .unwrap_or(false)
-    {
-        eprintln!("[Backend] External backend enabled; skipping sidecar spawn.");
-        return Err("External backend enabled; skipping sidecar spawn.".to_string());
-    }
-    eprintln!("[Backend] Spawning sidecar backend.");
+fn resolve_app_data_dir<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<PathBuf, String> {
     let app_data_dir = app
         .path()
         .app_data_dir()
         .map_err(|_| "Failed to resolve app data directory.".to_string())?;
     std::fs::create_dir_all(&app_data_dir)
         .map_err(|err| format!("Failed to create app data directory: {err}"))?;
+    Ok(app_data_dir)
+}
 
-    let db_path = std::env::var("TAURI_AGENT_DB_PATH")
+fn resolve_db_path(app_data_dir: &Path) -> PathBuf {
+    std::env::var("TAURI_AGENT_DB_PATH")
         .map(PathBuf::from)
         .unwrap_or_else(|_| {
             if tauri::is_dev() {
@@ -114,89 +388,766 @@ fn spawn_backend<R: tauri::Runtime>(
                 }
             }
             app_data_dir.join("chat_app.db")
-        });
-    let app_config_path = app_data_dir.join("app_config.json");
-    let tools_config_path = app_data_dir.join("tools_config.json");
-    let backend_path = resolve_backend_path(app)?;
-
-    let mut command = Command::new(backend_path);
-    command
-        .arg("--host")
-        .arg("127.0.0.1")
-        .arg("--port")
-        .arg(port.to_string());
-    command.env("TAURI_AGENT_DATA_DIR", &app_data_dir);
-    command.env("TAURI_AGENT_DB_PATH", &db_path);
-    command.env("APP_CONFIG_PATH", &app_config_path);
-    command.env("TOOLS_CONFIG_PATH", &tools_config_path);
+        })
+}
+
+fn pick_backend_port(host: IpAddr) -> Result<u16, String> {
+    let listener = TcpListener::bind(SocketAddr::new(host, 0))
+        .map_err(|err| format!("Failed to bind to an ephemeral port: {err}"))?;
+    let port = listener
+        .local_addr()
+        .map_err(|err| format!("Failed to read ephemeral port: {err}"))?
+        .port();
+    drop(listener);
+    Ok(port)
+}
+
+/// `BACKEND_ENV_ALLOWLIST`, then the comma-separated names in `extra_env`, then
+/// the names from `backend.env`.
+fn backend_env_allowlist(extra_env: &str, pass: Vec<String>) -> Vec<String> {
+    BACKEND_ENV_ALLOWLIST
+        .iter()
+        .map(|name| name.to_string())
+        .chain(
+            extra_env
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(String::from),
+        )
+        .chain(pass)
+        .collect()
+}
+
+fn backend_spec<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    host: IpAddr,
+    port: u16,
+    transport: BackendTransport,
+) -> Result<SidecarSpec, String> {
+    let data_dir = workspace::data_dir(app)?;
+    let settings = app
+        .try_state::<SettingsStore>()
+        .map(|store| store.get().backend)
+        .unwrap_or_default();
+    let allowlist = backend_env_allowlist(
+        &std::env::var("TAURI_AGENT_PASS_ENV").unwrap_or_default(),
+        settings.env.pass,
+    );
+    let shutdown = backend::shutdown_settings(app);
+    let handle = app.clone();
+    let mut spec =
+        SidecarSpec::new(BACKEND_SIDECAR, BACKEND_BINARY).inherit_only(allowlist);
+    // Set before the shell's own variables, which win on a clash.
+    for (key, value) in &settings.env.set {
+        spec = spec.env(key, value);
+    }
+    spec = match python_runtime::launch(app)? {
+        Some((python, source)) => python_runtime::run_from_source(spec, &python, &source),
+        // Dev builds rebuild the sidecar on their own schedule, so a stale digest
+        // only warns there.
+        None => spec.expect_sha256(
+            BACKEND_SHA256.split(',').filter(|digest| !digest.is_empty()),
+            !tauri::is_dev(),
+        ),
+    };
+    spec = spec
+        .restart(settings.restart)
+        .on_stop(move || backend::request_shutdown(&handle))
+        .stop_timeout(Duration::from_secs(shutdown.timeout_secs))
+        .env("TAURI_AGENT_DATA_DIR", &data_dir)
+        .env("TAURI_AGENT_DB_PATH", workspace::db_path(app)?)
+        .env("APP_CONFIG_PATH", data_dir.join("app_config.json"))
+        .env("TOOLS_CONFIG_PATH", data_dir.join("tools_config.json"))
+        .env(
+            "TAURI_AGENT_QUARANTINE_DIR",
+            app.state::<AttachmentStore>().quarantine_dir(),
+        )
+        .env("TAURI_AGENT_TEMP_DIR", app.state::<TempFiles>().backend_dir())
+        .env(stale_backend::PID_FILE_ENV, stale_backend::path(app)?)
+        .env(stale_backend::INSTANCE_ENV, stale_backend::instance_id())
+        .env(locale::LOCALE_ENV, locale::active(app))
+        .env(
+            costs::BUDGET_PAUSED_ENV,
+            if costs::is_paused(app) { "1" } else { "" },
+        )
+        .current_dir(&data_dir)
+        .on_line(backend_log::observer(app));
+    spec = match transport {
+        BackendTransport::Tcp => spec
+            .arg("--host")
+            .arg(host.to_string())
+            .arg("--port")
+            .arg(port.to_string())
+            .readiness(Readiness::Tcp(SocketAddr::new(
+                network::connect_host(host),
+                port,
+            ))),
+        BackendTransport::Stdio => {
+            let handle = app.clone();
+            spec.arg("--stdio")
+                .on_pipes(move |stdin, stdout| rpc::attach(&handle, stdin, stdout))
+        }
+    };
     if tauri::is_dev() {
-        command.env("TAURI_AGENT_DEV", "1");
+        spec = spec.env("TAURI_AGENT_DEV", "1");
+    }
+    if kiosk::is_enabled(app) {
+        spec = spec.env("TAURI_AGENT_READ_ONLY", "1");
+    }
+    if let Some(port) = debugger::port(app) {
+        spec = spec.env("TAURI_AGENT_DEBUGPY_PORT", port.to_string());
+    }
+    for (key, value) in secrets::environment(app) {
+        spec = spec.env(&key, value);
     }
-    command.current_dir(&app_data_dir);
-    if !tauri::is_dev() {
-        command.stdout(Stdio::null()).stderr(Stdio::null());
+    if let Some(key) = encryption::sidecar_key(app)? {
+        spec = spec.env(encryption::KEY_ENV, key);
     }
+    if let Some(token) = proxy::token(app) {
+        spec = spec.env(proxy::TOKEN_ENV, token);
+    }
+    for (key, value) in proxy_config::environment(app) {
+        spec = spec.env(key, value);
+    }
+    for (key, value) in tool_host::environment(app) {
+        spec = spec.env(key, value);
+    }
+    Ok(spec)
+}
 
-    command
-        .spawn()
-        .map_err(|err| format!("Failed to spawn backend sidecar: {err}"))
+fn spawn_backend<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    host: IpAddr,
+    port: u16,
+    transport: BackendTransport,
+) -> Result<(), String> {
+    startup::stage(app, Stage::Spawning, None);
+    // A backend left running by a force-quit would still hold the database.
+    stale_backend::take_over(app);
+    tracing::info!("[Backend] Spawning sidecar backend.");
+    Ok(sidecar::start(app, backend_spec(app, host, port, transport)?)?)
+}
+
+/// Stops the sidecar, runs `work`, then relaunches it on the same host and port so
+/// the base URL the frontend already holds stays valid. If another process took the
+/// port meanwhile, a new one is picked and announced on `backend://base-url` and
+/// `backend://url`. With
+/// an external backend, or an external or remote profile selected, `work` just runs.
+fn with_backend_stopped<R: tauri::Runtime, T, E: From<AppError> + From<String>>(
+    app: &tauri::AppHandle<R>,
+    work: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    if !runs_sidecar(app) {
+        return work();
+    }
+    let moved = Cell::new(false);
+    // The spec is rebuilt so settings changed by `work`, such as kiosk mode, apply.
+    let respawn = || {
+        let state = app.try_state::<BackendState>()?;
+        let mut port = state.port();
+        if state.transport == BackendTransport::Tcp
+            && TcpListener::bind(SocketAddr::new(state.host, port)).is_err() {
+            port = match pick_backend_port(state.host) {
+                Ok(port) => port,
+                Err(err) => return Some(Err(E::from(err))),
+            };
+            tracing::info!("[Backend] Previous port is taken; moving to {port}.");
+            state.port.store(port, Ordering::SeqCst);
+            moved.set(true);
+        }
+        Some(backend_spec(app, state.host, port, state.transport).map_err(E::from))
+    };
+    let result = sidecar::with_stopped(app, BACKEND_SIDECAR, respawn, work);
+    if moved.get() {
+        announce_backend_url(app);
+    }
+    result
+}
+
+/// Launches the sidecar if it is not running; a sidecar that never started is
+/// spawned from the addresses picked at startup.
+fn start_backend<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<(), String> {
+    let state = app
+        .try_state::<BackendState>()
+        .ok_or_else(|| "Backend is not configured yet.".to_string())?;
+    if state.remote_url().is_some() {
+        return Err(
+            "An external or remote backend is selected; there is no sidecar to start."
+                .to_string(),
+        );
+    }
+    if sidecar::is_supervised(app, BACKEND_SIDECAR) {
+        return with_backend_stopped(app, || Ok(()));
+    }
+    spawn_backend(app, state.host, state.port(), state.transport)
+}
+
+/// Whether requests go to a sidecar this app supervises.
+fn runs_sidecar<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> bool {
+    sidecar::is_supervised(app, BACKEND_SIDECAR)
+        && app
+            .try_state::<BackendState>()
+            .is_none_or(|state| state.remote_url().is_none())
+}
+
+fn restart_backend<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<(), String> {
+    if !runs_sidecar(app) {
+        return Err("The backend is not managed by this app.".to_string());
+    }
+    with_backend_stopped(app, || Ok(()))
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let logging = logging::init();
     let context = tauri::generate_context!();
+    if let Some(invocation) = cli::invocation() {
+        cli::run(invocation, context);
+    }
+    // Each command says whether kiosk mode has to block it.
+    let (handler, commands) = kiosk::commands![
+        #[read_only] greet,
+        #[read_only] get_backend_base_url,
+        #[read_only] get_backend_url,
+        #[mutating] clipboard::paste_image_from_clipboard,
+        #[read_only] clipboard::copy_message_to_clipboard,
+        #[read_only] clipboard::read_clipboard_for_prompt,
+        #[mutating] artifacts::save_artifact,
+        #[read_only] artifacts::reveal_in_folder,
+        #[read_only] autostart::get_autostart,
+        #[mutating] autostart::set_autostart,
+        #[mutating] audio::start_recording,
+        #[read_only] audio::stop_recording,
+        #[read_only] capture::list_capture_sources,
+        #[mutating] capture::start_context_capture,
+        #[read_only] capture::stop_context_capture,
+        #[mutating] capture::capture_screen,
+        #[read_only] capture::finish_screen_selection,
+        #[read_only] notifications::get_notification_settings,
+        #[mutating] notifications::set_notification_settings,
+        #[read_only] notifications::notify_user,
+        #[read_only] notifications::test_notification,
+        #[read_only] notifications::get_latest_notification,
+        #[read_only] network::get_backend_bind_host,
+        #[mutating] network::set_backend_bind_host,
+        #[mutating] network::set_backend_remote_url,
+        #[read_only] network::get_backend_transport,
+        #[mutating] network::set_backend_transport,
+        #[mutating] network::set_backend_proxy,
+        #[read_only] proxy::get_backend_token,
+        #[read_only] proxy_config::detect_system_proxy,
+        #[read_only] proxy_config::get_proxy_settings,
+        #[mutating] proxy_config::set_proxy_settings,
+        #[read_only] proxy_config::test_proxy_connection,
+        #[read_only] usage::get_usage,
+        #[read_only] usage::sync_conversation_usage,
+        #[read_only] costs::get_cost_summary,
+        #[mutating] costs::set_cost_settings,
+        #[mutating] costs::resume_after_budget_stop,
+        #[read_only] costs::get_usage_summary,
+        #[mutating] costs::export_usage_csv,
+        #[mutating] archive::archive_conversations,
+        #[mutating] archive::import_archive,
+        #[mutating] trash::trash_conversation,
+        #[read_only] trash::list_trash,
+        #[mutating] trash::restore_from_trash,
+        #[mutating] trash::purge_trash,
+        #[read_only] inbox::get_inbox_folder,
+        #[mutating] inbox::set_inbox_folder,
+        #[mutating] email::compose_email,
+        #[read_only] shortcuts::get_shortcuts,
+        #[read_only] shortcuts::get_shortcut_support,
+        #[mutating] shortcuts::set_shortcut,
+        #[read_only] permissions::get_permission_status,
+        #[read_only] permissions::request_permission,
+        #[mutating] automation::run_apple_script,
+        #[mutating] automation::run_com_automation,
+        #[read_only] desktop::show_rich_notification,
+        #[read_only] desktop::set_sleep_inhibited,
+        #[read_only] palette::get_palette_items,
+        #[mutating] palette::run_palette_action,
+        #[read_only] snapshots::list_snapshots,
+        #[mutating] snapshots::create_snapshot,
+        #[mutating] snapshots::restore_snapshot,
+        #[mutating] snapshots::delete_snapshot,
+        #[read_only] backup::get_backup_status,
+        #[mutating] backup::set_backup_destination,
+        #[mutating] backup::run_backup_now,
+        #[mutating] migration::export_everything,
+        #[mutating] migration::restore_everything,
+        #[read_only] migration::get_onboarding_state,
+        #[mutating] migration::import_legacy_data,
+        #[mutating] migration::dismiss_legacy_data,
+        #[read_only] migration::finish_onboarding,
+        #[read_only] updater::check_for_updates,
+        #[mutating] updater::install_update,
+        #[read_only] deep_link::take_pending_deep_links,
+        #[read_only] monitors::list_monitors,
+        #[read_only] health::get_health_history,
+        #[read_only] connectivity::connectivity_status,
+        #[read_only] monitor::backend_metrics,
+        #[read_only] monitor::get_resource_monitor,
+        #[mutating] monitor::set_resource_monitor,
+        #[read_only] watchdog::list_active_runs,
+        #[mutating] watchdog::kill_active_run,
+        #[read_only] watchdog::get_watchdog_settings,
+        #[mutating] watchdog::set_watchdog_settings,
+        #[read_only] workspace::list_workspaces,
+        #[mutating] workspace::create_workspace,
+        #[mutating] workspace::switch_workspace,
+        #[read_only] backend_profiles::list_backend_profiles,
+        #[mutating] backend_profiles::select_backend_profile,
+        #[read_only] log_files::get_backend_logs,
+        #[read_only] log_files::open_log_folder,
+        #[read_only] logging::set_log_level,
+        #[read_only] logging::get_recent_logs,
+        #[read_only] diagnostics::create_diagnostics_bundle,
+        #[mutating] diagnostics::set_crash_reports,
+        #[read_only] dialogs::get_dialog_dir,
+        #[read_only] dialogs::remember_dialog_dir,
+        #[mutating] outbox::queue_chat_request,
+        #[read_only] outbox::get_outbox,
+        #[mutating] outbox::cancel_queued_request,
+        #[read_only] monitors::move_window_to_monitor,
+        #[read_only] monitors::reset_window_state,
+        #[read_only] tool_policy::list_tool_grants,
+        #[mutating] tool_policy::revoke_tool_grant,
+        #[read_only] kiosk::get_kiosk_mode,
+        #[read_only] kiosk::enable_kiosk_mode,
+        #[mutating] webview::clear_webview_data,
+        #[mutating] webview::set_clear_on_upgrade,
+        #[read_only] webview::get_spellcheck_languages,
+        #[mutating] webview::set_spellcheck_languages,
+        #[read_only] markdown::render_markdown,
+        #[read_only] markdown::get_markdown_css,
+        #[read_only] vector_store::list_vector_collections,
+        #[mutating] vector_store::create_vector_collection,
+        #[mutating] vector_store::delete_vector_collection,
+        #[mutating] vector_store::upsert_embeddings,
+        #[mutating] vector_store::delete_embeddings,
+        #[read_only] vector_store::query_similar,
+        #[read_only] embeddings::get_embedding_settings,
+        #[mutating] embeddings::set_embedding_settings,
+        #[read_only] embeddings::embed_text,
+        #[read_only] indexer::get_indexing_status,
+        #[read_only] indexer::pause_indexing,
+        #[read_only] indexer::resume_indexing,
+        #[mutating] indexer::set_folder_indexing,
+        #[mutating] indexer::reindex_folder,
+        #[mutating] plugins::install_plugin,
+        #[read_only] plugins::list_plugins,
+        #[mutating] plugins::set_plugin_enabled,
+        #[mutating] plugins::uninstall_plugin,
+        #[read_only] plugins::get_trusted_plugin_keys,
+        #[mutating] plugins::set_trusted_plugin_keys,
+        #[read_only] sidecar::list_sidecars,
+        #[mutating] sessions::create_session,
+        #[read_only] sessions::list_sessions,
+        #[mutating] sessions::terminate_session,
+        #[read_only] startup::frontend_ready,
+        #[read_only] startup::get_startup_stage,
+        #[read_only] startup::retry_startup,
+        #[read_only] startup::dismiss_splash,
+        #[read_only] backend::backend_status,
+        #[mutating] backend::set_backend_readiness,
+        #[mutating] backend::set_backend_shutdown,
+        #[mutating] backend::set_backend_env,
+        #[read_only] backend::get_effective_backend_env,
+        #[read_only] python_runtime::get_python_runtime_status,
+        #[mutating] python_runtime::setup_python_backend,
+        #[mutating] python_runtime::set_python_backend,
+        #[mutating] backend::start_backend,
+        #[mutating] backend::stop_backend,
+        #[mutating] backend::restart_backend,
+        #[read_only] backend::backend_info,
+        #[read_only] compat::backend_version,
+        #[mutating] chat_stream::stream_chat,
+        #[read_only] chat_stream::cancel_stream,
+        #[read_only] config_files::read_app_config,
+        #[mutating] config_files::write_app_config,
+        #[read_only] config_files::read_tools_config,
+        #[mutating] config_files::write_tools_config,
+        #[mutating] secrets::set_secret,
+        #[read_only] secrets::has_secret,
+        #[mutating] secrets::delete_secret,
+        #[read_only] secrets::list_secret_keys,
+        #[mutating] db_backup::backup_database,
+        #[mutating] db_backup::restore_database,
+        #[mutating] export::export_conversation,
+        #[mutating] export::export_all_conversations,
+        #[read_only] appearance::get_window_effect,
+        #[mutating] appearance::set_window_effect,
+        #[read_only] appearance::get_system_theme,
+        #[mutating] appearance::set_window_theme,
+        #[read_only] locale::get_locale,
+        #[mutating] locale::set_locale,
+        #[mutating] tray::send_quick_reply,
+        #[mutating] quick_ask::quick_ask,
+        #[read_only] quick_ask::open_quick_answer,
+        #[read_only] conversation_windows::open_conversation_window,
+        #[read_only] tray::get_tray_settings,
+        #[mutating] tray::set_tray_settings,
+        #[read_only] taskbar::set_active_run,
+        #[mutating] debugger::enable_backend_debugging,
+        #[mutating] attachments::stage_attachment,
+        #[read_only] attachments::list_attachments,
+        #[mutating] attachments::delete_attachment,
+        #[mutating] attachments::set_attachment_quota,
+        #[read_only] idle::get_idle_time,
+        #[read_only] app_lock::get_lock_status,
+        #[mutating] app_lock::set_idle_timeout,
+        #[read_only] app_lock::lock_now,
+        #[read_only] app_lock::unlock_app,
+        #[read_only] encryption::get_encryption_status,
+        #[mutating] encryption::enable_encryption,
+        #[mutating] encryption::change_passphrase,
+        #[read_only] encryption::lock_app,
+        #[read_only] encryption::unlock_database,
+        #[read_only] speech::speak,
+        #[read_only] speech::stop_speaking,
+        #[mutating] speech::set_speech_settings,
+        #[mutating] scheduler::create_scheduled_task,
+        #[read_only] scheduler::list_scheduled_tasks,
+        #[mutating] scheduler::delete_scheduled_task,
+        #[read_only] search::search_messages,
+        #[mutating] import::import_conversations
+    ];
     let app = tauri::Builder::default()
+        .plugin(instance::plugin())
+        .manage(logging)
+        .manage(AudioRecorder::default())
+        .manage(Speaker::default())
+        .manage(SearchIndex::default())
+        .manage(ContextCapture::default())
+        .manage(ScreenSelection::default())
+        .manage(InboxWatcher::default())
+        .manage(ShortcutRegistry::default())
+        .manage(KioskMode::default())
+        .manage(Sidecars::default())
+        .manage(StartupGate::default())
+        .manage(BackendReadiness::default())
+        .manage(BackendVersion::default())
+        .manage(Sessions::default())
+        .manage(RecentNotification::default())
+        .manage(RunNotices::default())
+        .manage(Metering::default())
+        .manage(Onboarding::default())
+        .manage(PendingUpdate::default())
+        .manage(DeepLinks::default())
+        .manage(GeometrySaves::default())
+        .manage(QuickReply::default())
+        .manage(ActiveRun::default())
+        .manage(BackendDebugger::default())
+        .manage(AppLock::default())
+        .manage(BackupState::default())
+        .manage(RpcBridge::default())
+        .manage(HealthMonitor::default())
+        .manage(StaleBackend::default())
+        .manage(Connectivity::default())
+        .manage(ResourceMonitor::default())
+        .manage(Watchdog::default())
+        .manage(Presence::default())
+        .manage(ChatStreams::default())
+        .manage(ConfigWatcher::default())
+        .manage(DatabaseBackups::default())
+        .manage(ZoomLevels::default())
+        .manage(SystemTheme::default())
+        .register_asynchronous_uri_scheme_protocol(rpc::SCHEME, rpc::handle)
+        .register_asynchronous_uri_scheme_protocol(proxy::SCHEME, proxy::handle)
+        .register_asynchronous_uri_scheme_protocol(assets::SCHEME, assets::handle)
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(shortcuts::handle)
+                .build(),
+        )
+        .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet, get_backend_base_url])
+        .invoke_handler(move |invoke| {
+            let app = invoke.message.webview_ref().app_handle().clone();
+            if let Some(err) = kiosk::blocked(&app, commands, invoke.message.command()) {
+                invoke.resolver.reject(err);
+                return true;
+            }
+            handler(invoke)
+        })
         .setup(|app| {
             log_sandbox_status();
-            let mut backend_port = 8000;
-            let external_backend = std::env::var("TAURI_AGENT_EXTERNAL_BACKEND")
-                .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
-                .unwrap_or(false);
-            if !external_backend {
-                if let Ok(selected) = pick_backend_port() {
-                    backend_port = selected;
-                }
+            let app_data_dir = resolve_app_data_dir(app.handle())?;
+            atomic_file::recover(&app_data_dir);
+            app.manage(SettingsStore::load(app_data_dir.join("shell_settings.json")));
+            startup::open_splash(app.handle());
+            startup::stage(app.handle(), Stage::Resolving, None);
+            diagnostics::install_panic_hook(app.handle(), app_data_dir.join("crash-reports"));
+            let workspace_dir = workspace::data_dir(app.handle())?;
+            let attachment_settings = app.state::<SettingsStore>().get().attachments;
+            app.manage(AttachmentStore::new(
+                workspace_dir.join("attachments"),
+                &attachment_settings,
+            ));
+            if let Err(err) = logging::attach_file(&app_data_dir.join("logs")) {
+                tracing::warn!("[Logs] {err}");
+            }
+            app.manage(LogFiles::new(app_data_dir.join("logs")));
+            app.manage(Replay::init(&app_data_dir));
+            app.manage(Outbox::load(app_data_dir.join("outbox.json")));
+            app.manage(Scheduler::load(app_data_dir.join("scheduled_tasks.json")));
+            app.manage(ToolPolicy::load(app_data_dir.join("tool_policy.json")));
+            app.manage(TempFiles::new(temp_files::resolve_root(app.handle())?));
+            app.manage(UsageStore::open(app_data_dir.join("usage.db"))?);
+            app.manage(VectorStore::open(app_data_dir.join("vectors.db"))?);
+            let indexing_paused = app.state::<SettingsStore>().get().indexing.paused;
+            app.manage(Indexer::new(
+                app_data_dir.join("index_state.json"),
+                indexing_paused,
+            ));
+            kiosk::init(app.handle())?;
+            startup::stage(app.handle(), Stage::Migrating, None);
+            if let Err(err) = migration::prepare(app.handle()) {
+                tracing::warn!("[Migration] {err}");
             }
-            match spawn_backend(&app.handle(), backend_port) {
-                Ok(child) => {
-                    app.manage(BackendChild(Mutex::new(Some(child))));
+            if let Err(err) = monitors::restore_geometry(app.handle(), main_window::LABEL) {
+                tracing::warn!("[Monitors] {err}");
+            }
+            if let Err(err) = webview::clear_after_upgrade(app.handle()) {
+                tracing::warn!("[Webview] {err}");
+            }
+            if let Err(err) = webview::restore_spellcheck(app.handle()) {
+                tracing::warn!("[Webview] {err}");
+            }
+            if let Err(err) = appearance::restore(app.handle()) {
+                tracing::warn!("[Appearance] {err}");
+            }
+            let trash_dir = trash::trash_dir(app.handle())?;
+            tauri::async_runtime::spawn_blocking(move || trash::purge_expired(&trash_dir));
+            if let Err(err) = inbox::restart(app.handle()) {
+                tracing::warn!("[Inbox] {err}");
+            }
+            if let Err(err) = config_files::watch(app.handle(), workspace_dir) {
+                tracing::warn!("[Config] {err}");
+            }
+            if let Err(err) = shortcuts::apply(app.handle()) {
+                tracing::warn!("[Shortcuts] {err}");
+            }
+            notifications::watch(app.handle());
+            usage::watch(app.handle());
+            deep_link::init(app.handle());
+            desktop::start(app.handle());
+            if let Err(err) = tray::init(app.handle()) {
+                tracing::warn!("[Tray] {err}");
+            }
+            appearance::restore_theme(app.handle());
+            app.on_menu_event(app_menu::handle);
+            if let Err(err) = app_menu::install(app.handle()) {
+                tracing::warn!("[Menu] {err}");
+            }
+            if let Err(err) = taskbar::init(app.handle()) {
+                tracing::warn!("[Taskbar] {err}");
+            }
+            let locked = encryption::init(app.handle());
+            app_lock::start(app.handle());
+            backup::start(app.handle());
+            attachments::start(app.handle());
+            speech::start(app.handle());
+            updater::start(app.handle());
+            health::start(app.handle());
+            connectivity::start(app.handle());
+            monitor::start(app.handle());
+            watchdog::start(app.handle());
+            idle::start(app.handle());
+            outbox::start(app.handle());
+            scheduler::start(app.handle());
+            search::start(app.handle());
+            if let Err(err) = indexer::start(app.handle()) {
+                tracing::warn!("[Indexer] {err}");
+            }
+            let settings = app.try_state::<SettingsStore>();
+            let backend_host = network::resolve_bind_host(settings.as_deref());
+            let target = backend_profiles::initial(app.handle());
+            let transport = network::resolve_transport(settings.as_deref());
+            let mut backend_port = DEFAULT_BACKEND_PORT;
+            // Picked even for other profiles, in case the sidecar is selected later.
+            if transport == BackendTransport::Tcp {
+                match pick_backend_port(backend_host) {
+                    Ok(selected) => backend_port = selected,
+                    Err(err) => {
+                        tracing::warn!(
+                            "[Backend] {err}; falling back to port {DEFAULT_BACKEND_PORT}."
+                        )
+                    }
                 }
-                Err(err) => {
-                    eprintln!("{err}");
-                    if !tauri::is_dev()
-                        && !err.contains("External backend enabled")
-                    {
-                        return Err(err.into());
+            }
+            let tcp_sidecar = target.url.is_none() && transport == BackendTransport::Tcp;
+            // Tapes are written and read by the proxy, so the webview has to use it.
+            let replaying = replay::is_replaying(app.handle());
+            let recording = replay::is_recording(app.handle())
+                && (target.url.is_some() || transport == BackendTransport::Tcp);
+            let proxied = replaying
+                || recording
+                || (tcp_sidecar
+                    && settings.as_deref().is_some_and(|store| store.get().backend.proxy));
+            app.manage(Proxy::new(proxied, transport == BackendTransport::Tcp)?);
+            // Only the sidecar is told where the tool host is.
+            if target.url.is_none() && !replaying {
+                match tool_host::start(app.handle()) {
+                    Ok(host) => {
+                        app.manage(host);
                     }
+                    Err(err) => tracing::warn!("[ToolHost] {err}"),
+                }
+            }
+            startup::start(app.handle());
+            // Also covers external and remote backends, which are polled the same way.
+            backend::start(app.handle(), BACKEND_SIDECAR);
+            if replaying {
+                tracing::info!("[Backend] Replaying recorded responses; skipping sidecar spawn.");
+            } else if let Some(url) = &target.url {
+                tracing::info!(
+                    "[Backend] Using the {} backend profile at {url}; skipping sidecar spawn.",
+                    target.profile
+                );
+                startup::stage(app.handle(), Stage::Waiting, None);
+            } else if locked {
+                tracing::info!("[Backend] Chat database is locked; waiting for the passphrase.");
+                backend::spawn_failed(app.handle(), "The chat database is locked.".to_string());
+                encryption::open_unlock_window(app.handle());
+            } else if let Err(err) = spawn_backend(app.handle(), backend_host, backend_port, transport) {
+                tracing::error!("{err}");
+                // With the splash up, it shows the error with a retry button instead.
+                if !tauri::is_dev() && !startup::splash_open(app.handle()) {
+                    // The app is useless without its backend; say why before quitting
+                    // rather than vanishing.
+                    let handle = app.handle().clone();
+                    app.dialog()
+                        .message(err)
+                        .title(locale::t(app.handle(), "dialog.cannot_start"))
+                        .kind(MessageDialogKind::Error)
+                        .show(move |_| handle.exit(1));
+                    return Ok(());
                 }
+                backend::spawn_failed(app.handle(), err);
+            } else {
+                startup::stage(app.handle(), Stage::Waiting, None);
             }
-            app.manage(BackendState { port: backend_port });
+            app.manage(BackendState {
+                host: backend_host,
+                port: AtomicU16::new(backend_port),
+                target: Mutex::new(target),
+                transport,
+            });
+            announce_backend_url(app.handle());
             Ok(())
         })
-        .on_window_event(|window, event| {
-            if let WindowEvent::CloseRequested { api, .. } = event {
-                if window.label() == "main" {
-                    api.prevent_close();
+        .on_window_event(|window, event| match event {
+            WindowEvent::CloseRequested { api, .. } if window.label() == "main" => {
+                api.prevent_close();
+                monitors::save_geometry(window.app_handle(), main_window::LABEL);
+                if tray::close_to_tray(window.app_handle()) {
+                    let _ = window.hide();
+                } else {
                     window.app_handle().exit(0);
                 }
             }
+            WindowEvent::ThemeChanged(theme) if window.label() == main_window::LABEL => {
+                appearance::theme_changed(window.app_handle(), *theme);
+            }
+            WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. })
+                if window.label() == main_window::LABEL =>
+            {
+                file_drop::handle_drop(window.app_handle(), paths.clone());
+            }
+            WindowEvent::Moved(_) | WindowEvent::Resized(_)
+                if window.label() == main_window::LABEL
+                    || conversation_windows::is_conversation_window(window.label()) =>
+            {
+                monitors::schedule_save(window.app_handle(), window.label());
+            }
+            // Closing a detached conversation leaves the app and the backend running.
+            WindowEvent::CloseRequested { .. }
+                if conversation_windows::is_conversation_window(window.label()) =>
+            {
+                monitors::save_geometry(window.app_handle(), window.label());
+            }
+            WindowEvent::Focused(false) if window.label() == tray::POPUP_LABEL => {
+                if let Some(webview) = window.get_webview_window(tray::POPUP_LABEL) {
+                    tray::popup_blurred(&webview);
+                }
+            }
+            WindowEvent::Focused(false) if window.label() == quick_ask::LABEL => {
+                if let Some(webview) = window.get_webview_window(quick_ask::LABEL) {
+                    quick_ask::blurred(&webview);
+                }
+            }
+            WindowEvent::Destroyed if window.label() == capture::INDICATOR_LABEL => {
+                capture::stop(window.app_handle());
+            }
+            WindowEvent::Destroyed if window.label() == capture::SELECTION_LABEL => {
+                capture::selection_closed(window.app_handle());
+            }
+            WindowEvent::Destroyed if window.label() == encryption::UNLOCK_LABEL => {
+                encryption::unlock_window_closed(window.app_handle());
+            }
+            _ => {}
         })
         .build(context)
         .expect("error while building tauri application");
 
-    app.run(|app_handle, event| {
-        if matches!(event, RunEvent::Exit | RunEvent::ExitRequested { .. }) {
-            if let Some(state) = app_handle.try_state::<BackendChild>() {
-                if let Ok(mut guard) = state.0.lock() {
-                    if let Some(mut child) = guard.take() {
-                        let _ = child.kill();
-                    }
-                }
-            }
+    app.run(|app_handle, event| match event {
+        RunEvent::ExitRequested { .. } => {
+            monitors::save_geometry(app_handle, main_window::LABEL);
+            conversation_windows::save_geometry(app_handle);
+            sidecar::stop_all(app_handle);
         }
+        RunEvent::Exit => {
+            sidecar::stop_all(app_handle);
+            // Only once the backend is gone, so nothing is writing into it.
+            temp_files::purge(app_handle);
+        }
+        // Clicking the dock icon while the main window is hidden.
+        #[cfg(target_os = "macos")]
+        RunEvent::Reopen { .. } => main_window::reveal(app_handle),
+        _ => {}
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backend_env_allowlist_adds_extra_names_then_settings() {
+        let allowlist =
+            backend_env_allowlist(" FOO_HOME , ,BAR_*", vec!["FROM_SETTINGS".to_string()]);
+        let (builtin, added) = allowlist.split_at(BACKEND_ENV_ALLOWLIST.len());
+        assert_eq!(builtin, BACKEND_ENV_ALLOWLIST);
+        assert_eq!(added, ["FOO_HOME", "BAR_*", "FROM_SETTINGS"]);
+    }
+
+    #[test]
+    fn backend_inherits_only_allowlisted_names() {
+        let allowlist = backend_env_allowlist("", Vec::new());
+        for name in [
+            "PATH",
+            "LC_ALL",
+            "XDG_CONFIG_HOME",
+            "TAURI_AGENT_KIOSK",
+            "TAVILY_API_KEY",
+        ] {
+            assert!(sidecar::is_allowed(&allowlist, name), "{name}");
+        }
+        for name in [
+            "GITHUB_TOKEN",
+            "AWS_SECRET_ACCESS_KEY",
+            "OPENAI_API_KEY",
+            "LC",
+            "PATHS",
+        ] {
+            assert!(!sidecar::is_allowed(&allowlist, name), "{name}");
+        }
+        let allowlist = backend_env_allowlist("OPENAI_API_KEY", Vec::new());
+        assert!(sidecar::is_allowed(&allowlist, "OPENAI_API_KEY"));
+    }
+}
@@ -0,0 +1,195 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{app_menu, error::AppError, events, settings::SettingsStore, tray};
+
+/// Read by the backend at spawn; its requests carry the current one as
+/// `Accept-Language`, so a change applies without a restart.
+pub const LOCALE_ENV: &str = "TAURI_AGENT_LOCALE";
+const FALLBACK_BUNDLE: &str = "en";
+/// Native strings (tray, menu, dialogs) by bundle tag.
+const BUNDLES: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.json")),
+    ("zh-CN", include_str!("../locales/zh-CN.json")),
+];
+
+events::payload! {
+    pub struct LocaleInfo {
+        /// What the shell and backend use: the override, else the OS locale.
+        locale: String,
+        system: String,
+        /// The user's choice; `None` follows the OS.
+        selected: Option<String>,
+        /// The bundle native strings come from.
+        bundle: &'static str,
+        available: Vec<&'static str>,
+    }
+}
+
+fn bundles() -> &'static HashMap<&'static str, HashMap<String, String>> {
+    static PARSED: OnceLock<HashMap<&'static str, HashMap<String, String>>> = OnceLock::new();
+    PARSED.get_or_init(|| {
+        BUNDLES
+            .iter()
+            .filter_map(|(tag, raw)| match serde_json::from_str(raw) {
+                Ok(strings) => Some((*tag, strings)),
+                Err(err) => {
+                    tracing::warn!("[Locale] Ignoring the {tag} bundle: {err}");
+                    None
+                }
+            })
+            .collect()
+    })
+}
+
+/// `zh_CN.UTF-8` and `en_US@euro` style names as BCP 47 tags; `C` and `POSIX` say
+/// nothing.
+fn normalize(raw: &str) -> Option<String> {
+    let name = raw.split(['.', '@']).next().unwrap_or_default().trim();
+    if name.is_empty() || name == "C" || name == "POSIX" {
+        return None;
+    }
+    Some(name.replace('_', "-"))
+}
+
+#[cfg(target_os = "windows")]
+fn platform_locale() -> Option<String> {
+    use windows::Win32::Globalization::GetUserDefaultLocaleName;
+
+    // LOCALE_NAME_MAX_LENGTH
+    let mut buffer = [0u16; 85];
+    let len = unsafe { GetUserDefaultLocaleName(&mut buffer) };
+    // The count includes the terminating NUL; zero is a failure.
+    let len = usize::try_from(len).ok()?.checked_sub(1)?;
+    Some(String::from_utf16_lossy(&buffer[..len]))
+}
+
+/// The first preferred language, which is `LANG` only for apps started from a shell.
+#[cfg(target_os = "macos")]
+fn platform_locale() -> Option<String> {
+    use objc2_foundation::NSLocale;
+
+    NSLocale::preferredLanguages()
+        .firstObject()
+        .map(|language| language.to_string())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn platform_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Read once; the OS setting rarely changes while the app runs.
+pub fn system_locale() -> &'static str {
+    static DETECTED: OnceLock<String> = OnceLock::new();
+    DETECTED.get_or_init(|| {
+        platform_locale()
+            .as_deref()
+            .and_then(normalize)
+            .unwrap_or_else(|| "en-US".to_string())
+    })
+}
+
+fn selected<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    app.try_state::<SettingsStore>()?.get().locale
+}
+
+pub fn active<R: Runtime>(app: &AppHandle<R>) -> String {
+    selected(app).unwrap_or_else(|| system_locale().to_string())
+}
+
+/// The exact bundle, else one for the same language, else English.
+fn bundle_for(tag: &str) -> &'static str {
+    let language = tag.split('-').next().unwrap_or_default();
+    BUNDLES
+        .iter()
+        .map(|(bundle, _)| *bundle)
+        .find(|bundle| bundle.eq_ignore_ascii_case(tag))
+        .or_else(|| {
+            BUNDLES
+                .iter()
+                .map(|(bundle, _)| *bundle)
+                .find(|bundle| bundle.split('-').next() == Some(language))
+        })
+        .unwrap_or(FALLBACK_BUNDLE)
+}
+
+/// The native string for `key` in the active locale; the key itself if no bundle
+/// has it.
+pub fn t<R: Runtime>(app: &AppHandle<R>, key: &str) -> String {
+    let bundles = bundles();
+    [bundle_for(&active(app)), FALLBACK_BUNDLE]
+        .iter()
+        .find_map(|bundle| bundles.get(bundle)?.get(key))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// `t`, with each `{name}` in the string replaced by its value.
+pub fn t_with<R: Runtime>(app: &AppHandle<R>, key: &str, args: &[(&str, &str)]) -> String {
+    args.iter().fold(t(app, key), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), value)
+    })
+}
+
+/// Letters and digits in subtags of up to 8, a language of 2 or 3 letters first.
+fn check_tag(tag: &str) -> Result<(), AppError> {
+    let mut subtags = tag.split('-');
+    let language = subtags.next().unwrap_or_default();
+    let valid = (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_alphabetic())
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::invalid_input(format!(
+            "{tag} is not a language tag like en-US."
+        )))
+    }
+}
+
+fn info<R: Runtime>(app: &AppHandle<R>) -> LocaleInfo {
+    let locale = active(app);
+    LocaleInfo {
+        bundle: bundle_for(&locale),
+        locale,
+        system: system_locale().to_string(),
+        selected: selected(app),
+        available: BUNDLES.iter().map(|(bundle, _)| *bundle).collect(),
+    }
+}
+
+#[tauri::command]
+pub fn get_locale(app: AppHandle) -> LocaleInfo {
+    info(&app)
+}
+
+/// `None` goes back to the OS locale. Native labels change at once; the frontend
+/// and backend follow `locale://changed`.
+#[tauri::command]
+pub fn set_locale(app: AppHandle, tag: Option<String>) -> Result<LocaleInfo, AppError> {
+    let tag = tag
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if let Some(tag) = &tag {
+        check_tag(tag)?;
+    }
+    app.state::<SettingsStore>()
+        .update(|settings| settings.locale = tag)?;
+    if let Err(err) = tray::refresh_menu(&app) {
+        tracing::warn!("[Locale] {err}");
+    }
+    if let Err(err) = app_menu::install(&app) {
+        tracing::warn!("[Locale] {err}");
+    }
+    let info = info(&app);
+    tracing::info!("[Locale] Now using {}.", info.locale);
+    events::locale_changed(&app, &info);
+    Ok(info)
+}
@@ -0,0 +1,239 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use chrono::{Duration, Local, NaiveDate};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::{error::AppError, sidecar::OutputStream};
+
+const PREFIX: &str = "backend-";
+const DATE_FORMAT: &str = "%Y%m%d";
+/// A day's file is rotated to `backend-YYYYMMDD.1.log` once it grows past this.
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Rotated files kept per day; the oldest is dropped on the next rotation.
+const MAX_ROTATIONS: u32 = 4;
+const RETENTION_DAYS: i64 = 7;
+const MAX_TAIL: usize = 10_000;
+
+struct Current {
+    date: String,
+    file: File,
+    size: u64,
+}
+
+/// Daily backend log files under `app_data_dir/logs`, so release builds, which have
+/// no console, still leave something to diagnose.
+pub struct LogFiles {
+    dir: PathBuf,
+    current: Mutex<Option<Current>>,
+    /// Set after the first write failure so a broken disk does not flood stderr.
+    warned: AtomicBool,
+}
+
+/// `backend-YYYYMMDD.log` is index 0, `backend-YYYYMMDD.N.log` index N.
+fn parse_name(name: &str) -> Option<(NaiveDate, u32)> {
+    let stem = name.strip_prefix(PREFIX)?.strip_suffix(".log")?;
+    let (date, index) = match stem.split_once('.') {
+        Some((date, index)) => (date, index.parse().ok()?),
+        None => (stem, 0),
+    };
+    Some((NaiveDate::parse_from_str(date, DATE_FORMAT).ok()?, index))
+}
+
+impl LogFiles {
+    pub fn new(dir: PathBuf) -> Self {
+        let files = Self {
+            dir,
+            current: Mutex::new(None),
+            warned: AtomicBool::new(false),
+        };
+        files.prune();
+        files
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, date: &str, index: u32) -> PathBuf {
+        if index == 0 {
+            self.dir.join(format!("{PREFIX}{date}.log"))
+        } else {
+            self.dir.join(format!("{PREFIX}{date}.{index}.log"))
+        }
+    }
+
+    fn warn(&self, err: String) {
+        if !self.warned.swap(true, Ordering::SeqCst) {
+            tracing::warn!("[Logs] {err}");
+        }
+    }
+
+    fn open(&self, date: &str) -> Result<Current, String> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|err| format!("Failed to create the log folder: {err}"))?;
+        let path = self.path(date, 0);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|err| format!("Failed to open {}: {err}", path.display()))?;
+        let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+        Ok(Current {
+            date: date.to_string(),
+            file,
+            size,
+        })
+    }
+
+    /// Shifts `.N.log` up by one and moves the live file to `.1.log`.
+    fn rotate(&self, date: &str) {
+        let _ = fs::remove_file(self.path(date, MAX_ROTATIONS));
+        for index in (0..MAX_ROTATIONS).rev() {
+            let from = self.path(date, index);
+            if from.exists() {
+                if let Err(err) = fs::rename(&from, self.path(date, index + 1)) {
+                    self.warn(format!("Failed to rotate {}: {err}", from.display()));
+                }
+            }
+        }
+    }
+
+    /// Deletes files from before the retention window.
+    fn prune(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let cutoff = Local::now().date_naive() - Duration::days(RETENTION_DAYS);
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let Some((date, _)) = name.to_str().and_then(parse_name) else {
+                continue;
+            };
+            if date < cutoff {
+                if let Err(err) = fs::remove_file(entry.path()) {
+                    tracing::warn!("[Logs] Failed to remove {}: {err}", entry.path().display());
+                }
+            }
+        }
+    }
+
+    pub fn append(&self, stream: OutputStream, line: &str) {
+        let now = Local::now();
+        let date = now.format(DATE_FORMAT).to_string();
+        let Ok(mut current) = self.current.lock() else {
+            return;
+        };
+        let state = current
+            .as_ref()
+            .map(|open| (open.date != date, open.size >= MAX_FILE_BYTES));
+        // The handle is closed first; Windows cannot rename a file that is open.
+        let stale = match state {
+            None => true,
+            Some((true, _)) => {
+                drop(current.take());
+                self.prune();
+                true
+            }
+            Some((false, true)) => {
+                drop(current.take());
+                self.rotate(&date);
+                true
+            }
+            Some((false, false)) => false,
+        };
+        if stale {
+            match self.open(&date) {
+                Ok(open) => *current = Some(open),
+                Err(err) => return self.warn(err),
+            }
+        }
+        let Some(open) = current.as_mut() else {
+            return;
+        };
+        let stream = match stream {
+            OutputStream::Stdout => "stdout",
+            OutputStream::Stderr => "stderr",
+        };
+        let record = format!(
+            "{} [{stream}] {line}\n",
+            now.format("%Y-%m-%dT%H:%M:%S%.3f")
+        );
+        match open.file.write_all(record.as_bytes()) {
+            Ok(()) => open.size += record.len() as u64,
+            Err(err) => self.warn(format!("Failed to write the backend log: {err}")),
+        }
+    }
+
+    /// Newest day first, and within a day the live file before its rotations.
+    fn files_newest_first(&self) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<_> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let key = entry.file_name().to_str().and_then(parse_name)?;
+                Some((key, entry.path()))
+            })
+            .collect();
+        files.sort_by(|((a_date, a_index), _), ((b_date, b_index), _)| {
+            b_date.cmp(a_date).then(a_index.cmp(b_index))
+        });
+        files.into_iter().map(|(_, path)| path).collect()
+    }
+
+    /// The last `count` lines, oldest first, reading back across rotated files.
+    pub fn tail(&self, count: usize) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        for path in self.files_newest_first() {
+            if lines.len() >= count {
+                break;
+            }
+            let Ok(raw) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let wanted = count - lines.len();
+            let older: Vec<String> = raw.lines().map(str::to_string).collect();
+            let skip = older.len().saturating_sub(wanted);
+            lines.splice(0..0, older.into_iter().skip(skip));
+        }
+        lines
+    }
+}
+
+pub fn append<R: Runtime>(app: &AppHandle<R>, stream: OutputStream, line: &str) {
+    if let Some(files) = app.try_state::<LogFiles>() {
+        files.append(stream, line);
+    }
+}
+
+/// The most recent backend output lines, oldest first.
+#[tauri::command]
+pub async fn get_backend_logs(app: AppHandle, tail: usize) -> Result<Vec<String>, AppError> {
+    let count = tail.clamp(1, MAX_TAIL);
+    tauri::async_runtime::spawn_blocking(move || {
+        app.try_state::<LogFiles>()
+            .map(|files| files.tail(count))
+            .unwrap_or_default()
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Log task failed: {err}")))
+}
+
+#[tauri::command]
+pub fn open_log_folder(app: AppHandle, files: tauri::State<LogFiles>) -> Result<(), AppError> {
+    fs::create_dir_all(files.dir())
+        .map_err(|err| AppError::from(format!("Failed to create the log folder: {err}")))?;
+    app.opener()
+        .open_path(files.dir().to_string_lossy(), None::<&str>)
+        .map_err(|err| AppError::from(format!("Failed to open the log folder: {err}")))
+}
@@ -0,0 +1,242 @@
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Write as _},
+    io,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_appender::rolling::{RollingFileAppender, RollingWriter, Rotation};
+use tracing_subscriber::{
+    fmt::{
+        self,
+        writer::{EitherWriter, MakeWriter},
+    },
+    layer::{Context, SubscriberExt},
+    reload,
+    util::SubscriberInitExt,
+    EnvFilter, Layer, Registry,
+};
+
+use crate::error::AppError;
+
+/// Log files are `shell.YYYY-MM-DD.log`, next to the backend's.
+const FILE_PREFIX: &str = "shell";
+const RETENTION_FILES: usize = 7;
+/// Records kept in memory for `get_recent_logs`.
+const MAX_RECENT: usize = 2000;
+/// Dependencies stay at warnings unless a full filter is given.
+const DEFAULT_LEVEL: &str = "info";
+
+/// Filled in once setup knows the data directory; earlier records reach stderr only.
+static FILE: OnceLock<RollingFileAppender> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRecord {
+    timestamp: String,
+    level: String,
+    target: String,
+    message: String,
+    #[serde(skip)]
+    severity: Level,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct LogFilter {
+    /// Least severe level to include, e.g. `warn` for warnings and errors.
+    level: Option<String>,
+    /// Substring of the module path, e.g. `sidecar`.
+    target: Option<String>,
+    /// Case-insensitive substring of the message.
+    contains: Option<String>,
+    limit: Option<usize>,
+}
+
+type Recent = Arc<Mutex<VecDeque<LogRecord>>>;
+
+/// The runtime handle on the subscriber installed by [`init`].
+pub struct Logging {
+    filter: reload::Handle<EnvFilter, Registry>,
+    recent: Recent,
+}
+
+impl Logging {
+    /// The buffered records as log lines, oldest first.
+    pub fn lines(&self) -> Vec<String> {
+        self.recent
+            .lock()
+            .map(|recent| {
+                recent
+                    .iter()
+                    .map(|record| {
+                        format!(
+                            "{} {:>5} {}: {}",
+                            record.timestamp, record.level, record.target, record.message
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+struct FileWriter;
+
+impl<'a> MakeWriter<'a> for FileWriter {
+    type Writer = EitherWriter<RollingWriter<'a>, io::Sink>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        match FILE.get() {
+            Some(file) => EitherWriter::A(file.make_writer()),
+            None => EitherWriter::B(io::sink()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{value:?}");
+        } else {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+}
+
+struct RecentLayer(Recent);
+
+impl<S: Subscriber> Layer<S> for RecentLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let record = LogRecord {
+            timestamp: Local::now().to_rfc3339(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: visitor.0,
+            severity: *metadata.level(),
+        };
+        if let Ok(mut recent) = self.0.lock() {
+            if recent.len() >= MAX_RECENT {
+                recent.pop_front();
+            }
+            recent.push_back(record);
+        }
+    }
+}
+
+/// A bare level applies to the shell's own modules; anything with `=` or `,` is
+/// taken as a full `EnvFilter` directive.
+fn directive(level: &str) -> String {
+    if level.contains(['=', ',']) {
+        level.to_string()
+    } else {
+        format!("warn,{}={level}", env!("CARGO_CRATE_NAME"))
+    }
+}
+
+/// Installs the global subscriber: stderr, the daily log file once
+/// [`attach_file`] runs, and the in-memory buffer behind `get_recent_logs`.
+/// `RUST_LOG` overrides the starting level.
+pub fn init() -> Logging {
+    let initial = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|value| EnvFilter::try_new(&value).ok())
+        .unwrap_or_else(|| EnvFilter::new(directive(DEFAULT_LEVEL)));
+    let (filter, handle) = reload::Layer::new(initial);
+    let recent = Recent::default();
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(io::stderr))
+        .with(fmt::layer().with_ansi(false).with_writer(FileWriter))
+        .with(RecentLayer(recent.clone()))
+        .try_init();
+    if let Err(err) = installed {
+        eprintln!("[Logs] Failed to install the logger: {err}");
+    }
+    Logging {
+        filter: handle,
+        recent,
+    }
+}
+
+/// Starts writing to `dir`, keeping a week of daily files.
+pub fn attach_file(dir: &Path) -> Result<(), String> {
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(RETENTION_FILES)
+        .build(dir)
+        .map_err(|err| format!("Failed to open the shell log: {err}"))?;
+    let _ = FILE.set(appender);
+    Ok(())
+}
+
+/// Changes verbosity without a restart: `debug`, or a directive such as
+/// `info,tauri_agent_demo_lib::sidecar=trace`. Returns the directive applied.
+#[tauri::command]
+pub fn set_log_level(logging: tauri::State<Logging>, level: String) -> Result<String, AppError> {
+    let directive = directive(level.trim());
+    let filter = EnvFilter::try_new(&directive).map_err(|err| {
+        AppError::invalid_input(format!("'{level}' is not a log level.")).with_details(err)
+    })?;
+    logging
+        .filter
+        .reload(filter)
+        .map_err(|err| AppError::unavailable("The logger is unavailable.").with_details(err))?;
+    tracing::info!("[Logs] Log level set to {directive}.");
+    Ok(directive)
+}
+
+/// Buffered records matching `filter`, oldest first.
+#[tauri::command]
+pub fn get_recent_logs(
+    logging: tauri::State<Logging>,
+    filter: Option<LogFilter>,
+) -> Result<Vec<LogRecord>, AppError> {
+    let filter = filter.unwrap_or_default();
+    let level = filter
+        .level
+        .as_deref()
+        .map(Level::from_str)
+        .transpose()
+        .map_err(|err| AppError::invalid_input("Unknown log level.").with_details(err))?;
+    let contains = filter.contains.map(|text| text.to_lowercase());
+    let recent = logging
+        .recent
+        .lock()
+        .map_err(|_| AppError::unavailable("The log buffer is unavailable."))?;
+    let mut matches: Vec<LogRecord> = recent
+        .iter()
+        .filter(|record| level.is_none_or(|level| record.severity <= level))
+        .filter(|record| {
+            filter
+                .target
+                .as_deref()
+                .is_none_or(|target| record.target.contains(target))
+        })
+        .filter(|record| {
+            contains
+                .as_deref()
+                .is_none_or(|text| record.message.to_lowercase().contains(text))
+        })
+        .cloned()
+        .collect();
+    let limit = filter.limit.unwrap_or(MAX_RECENT).min(MAX_RECENT);
+    let skip = matches.len().saturating_sub(limit);
+    matches.drain(..skip);
+    Ok(matches)
+}
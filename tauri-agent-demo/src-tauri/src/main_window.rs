@@ -0,0 +1,18 @@
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::monitors;
+
+pub const LABEL: &str = "main";
+
+/// Brings the main window back from hidden or minimized and focuses it.
+pub fn reveal<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        let _ = window.unminimize();
+        // Displays may have been unplugged since it was hidden.
+        if let Err(err) = monitors::ensure_visible(&window) {
+            tracing::warn!("[Monitors] {err}");
+        }
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
@@ -0,0 +1,328 @@
+use std::{borrow::Cow, sync::OnceLock};
+
+use pulldown_cmark::{html, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use serde::Deserialize;
+use syntect::{
+    highlighting::ThemeSet,
+    html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+use crate::{assets, error::AppError};
+
+/// Every highlighter class starts with this, so the sanitizer can tell them apart.
+const CLASS_PREFIX: &str = "hl-";
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed {
+    prefix: CLASS_PREFIX,
+};
+const DEFAULT_THEME: &str = "InspiredGitHub";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RenderOptions {
+    /// Wraps fenced code in highlighter spans; style them with `get_markdown_css`.
+    pub highlight: bool,
+    /// Keeps images from other hosts. Off by default so untrusted output cannot
+    /// beacon out.
+    pub remote_images: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            highlight: true,
+            remote_images: false,
+        }
+    }
+}
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn themes() -> &'static ThemeSet {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    THEMES.get_or_init(ThemeSet::load_defaults)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn is_language_class(value: &str) -> bool {
+    value.strip_prefix("language-").is_some_and(|lang| {
+        !lang.is_empty()
+            && lang
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '+' | '#'))
+    })
+}
+
+fn highlight(code: &str, lang: &str) -> Option<String> {
+    let syntaxes = syntaxes();
+    let syntax = syntaxes.find_syntax_by_token(lang)?;
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, syntaxes, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        generator
+            .parse_html_for_line_which_includes_newline(line)
+            .ok()?;
+    }
+    Some(generator.finalize())
+}
+
+fn code_block(code: &str, info: &str, options: &RenderOptions) -> String {
+    let lang = info.split_whitespace().next().unwrap_or("");
+    let class = format!("language-{lang}");
+    let body = options
+        .highlight
+        .then(|| highlight(code, lang))
+        .flatten()
+        .unwrap_or_else(|| escape(code));
+    if is_language_class(&class) {
+        format!("<pre><code class=\"{class}\">{body}</code></pre>\n")
+    } else {
+        format!("<pre><code>{body}</code></pre>\n")
+    }
+}
+
+/// `url` lowercased the way browsers read it: tabs and newlines anywhere dropped,
+/// spaces and control characters around it trimmed.
+fn normalize_url(url: &str) -> String {
+    let url: String = url
+        .chars()
+        .filter(|ch| !matches!(ch, '\t' | '\n' | '\r'))
+        .collect();
+    url.trim_matches(|ch: char| ch <= ' ').to_ascii_lowercase()
+}
+
+/// The scheme of a normalized URL, or `None` for a relative one.
+fn scheme(url: &str) -> Option<&str> {
+    let (scheme, _) = url.split_once(':')?;
+    let valid = scheme.starts_with(|ch: char| ch.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '+' | '-' | '.'));
+    valid.then_some(scheme)
+}
+
+/// Whether an image source stays inside the app: a relative path, the asset store
+/// or inline data.
+fn is_local_image(src: &str) -> bool {
+    let src = normalize_url(src);
+    if src.starts_with(&format!("{}/", assets::base_url())) {
+        return true;
+    }
+    match scheme(&src) {
+        Some(scheme) => scheme == assets::SCHEME || scheme == "data",
+        // `//host/x` is another host; browsers read backslashes as slashes.
+        None => !matches!(src.as_bytes(), [b'/' | b'\\', b'/' | b'\\', ..]),
+    }
+}
+
+fn sanitize(raw: &str, options: &RenderOptions) -> String {
+    let remote_images = options.remote_images;
+    ammonia::Builder::default()
+        .add_url_schemes([assets::SCHEME, "data"])
+        .add_tags(["input"])
+        .add_tag_attributes("input", ["type", "checked", "disabled"])
+        .add_tag_attributes("code", ["class"])
+        .add_tag_attributes("span", ["class"])
+        .link_rel(Some("noopener noreferrer nofollow"))
+        .attribute_filter(move |element, attribute, value| {
+            let keep = match (element, attribute) {
+                ("code", "class") => is_language_class(value),
+                ("span", "class") => value
+                    .split_whitespace()
+                    .all(|class| class.starts_with(CLASS_PREFIX)),
+                // Only the disabled checkboxes pulldown-cmark emits for task lists.
+                ("input", "type") => value == "checkbox",
+                ("img", "src") => remote_images || is_local_image(value),
+                // Inline data is for images; as a link it could open a page.
+                (_, "href") => scheme(&normalize_url(value)) != Some("data"),
+                _ => true,
+            };
+            keep.then_some(Cow::Borrowed(value))
+        })
+        .clean(raw)
+        .to_string()
+}
+
+/// Renders CommonMark (plus tables, task lists, footnotes and strikethrough) to
+/// HTML that is safe to inject, whatever the source put in it.
+pub fn render(markdown: &str, options: &RenderOptions) -> String {
+    let parser = Parser::new_ext(
+        markdown,
+        Options::ENABLE_TABLES
+            | Options::ENABLE_FOOTNOTES
+            | Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_TASKLISTS,
+    );
+    let mut fence: Option<(String, String)> = None;
+    let events = parser.filter_map(|event| match (&mut fence, event) {
+        (None, Event::Start(Tag::CodeBlock(kind))) => {
+            let info = match kind {
+                CodeBlockKind::Fenced(info) => info.into_string(),
+                CodeBlockKind::Indented => String::new(),
+            };
+            fence = Some((info, String::new()));
+            None
+        }
+        (Some((_, code)), Event::Text(text)) => {
+            code.push_str(&text);
+            None
+        }
+        (Some(_), Event::End(TagEnd::CodeBlock)) => {
+            let (info, code) = fence.take()?;
+            Some(Event::Html(code_block(&code, &info, options).into()))
+        }
+        (_, event) => Some(event),
+    });
+    let mut raw = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut raw, events);
+    sanitize(&raw, options)
+}
+
+/// Stylesheet for the highlighter classes in `theme`, or the default theme.
+pub fn highlight_css(theme: Option<&str>) -> Result<String, AppError> {
+    let name = theme.unwrap_or(DEFAULT_THEME);
+    let theme = themes()
+        .themes
+        .get(name)
+        .ok_or_else(|| AppError::invalid_input(format!("Unknown highlight theme: {name}")))?;
+    css_for_theme_with_class_style(theme, CLASS_STYLE).map_err(|err| {
+        AppError::from("Failed to build highlight styles.".to_string()).with_details(err)
+    })
+}
+
+/// Sanitized HTML for agent output; never trust the markdown itself.
+#[tauri::command]
+pub async fn render_markdown(
+    markdown: String,
+    options: Option<RenderOptions>,
+) -> Result<String, AppError> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || render(&markdown, &options))
+        .await
+        .map_err(|err| AppError::from(format!("Render task failed: {err}")))
+}
+
+#[tauri::command]
+pub async fn get_markdown_css(theme: Option<String>) -> Result<String, AppError> {
+    tauri::async_runtime::spawn_blocking(move || highlight_css(theme.as_deref()))
+        .await
+        .map_err(|err| AppError::from(format!("Render task failed: {err}")))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_url_reads_urls_like_a_browser() {
+        assert_eq!(
+            normalize_url(" Java\tScript:\nalert(1)\u{1}"),
+            "javascript:alert(1)"
+        );
+        assert_eq!(
+            normalize_url("HTTPS://Example.com/A"),
+            "https://example.com/a"
+        );
+    }
+
+    #[test]
+    fn scheme_needs_a_valid_name_before_the_colon() {
+        assert_eq!(scheme("https://example.com"), Some("https"));
+        assert_eq!(scheme("app-asset://localhost/x"), Some("app-asset"));
+        assert_eq!(scheme("images/a.png"), None);
+        assert_eq!(scheme("a/b:c"), None);
+        assert_eq!(scheme("1x:y"), None);
+    }
+
+    #[test]
+    fn local_images_stay_inside_the_app() {
+        assert!(is_local_image("images/a.png"));
+        assert!(is_local_image("/a.png"));
+        assert!(is_local_image(&format!("{}/x.png", assets::base_url())));
+        assert!(is_local_image("app-asset://localhost/x.png"));
+        assert!(is_local_image("data:image/png;base64,AAAA"));
+        assert!(!is_local_image("https://example.com/a.png"));
+        assert!(!is_local_image(" HTTPS://example.com/a.png"));
+        assert!(!is_local_image("//example.com/a.png"));
+        assert!(!is_local_image("\\\\example.com\\a.png"));
+        assert!(!is_local_image("/\\example.com/a.png"));
+    }
+
+    #[test]
+    fn sanitizer_drops_scripts_and_dangerous_links() {
+        let options = RenderOptions::default();
+        let html = render("<script>alert(1)</script>hi", &options);
+        assert!(!html.contains("<script"), "{html}");
+        let html = render("[x](javascript:alert(1))", &options);
+        assert!(!html.contains("javascript"), "{html}");
+        let html = render("[x](data:text/html,hi)", &options);
+        assert!(!html.contains("href"), "{html}");
+        let html = render("[x](https://example.com)", &options);
+        assert!(
+            html.contains("rel=\"noopener noreferrer nofollow\""),
+            "{html}"
+        );
+    }
+
+    #[test]
+    fn remote_images_need_opting_in() {
+        let markdown = "![a](https://example.com/a.png)";
+        let html = render(markdown, &RenderOptions::default());
+        assert!(!html.contains("example.com"), "{html}");
+        let options = RenderOptions {
+            remote_images: true,
+            ..RenderOptions::default()
+        };
+        assert!(render(markdown, &options).contains("https://example.com/a.png"));
+        let local = format!("![a]({}/x.png)", assets::base_url());
+        let html = render(&local, &RenderOptions::default());
+        assert!(
+            html.contains(&format!("src=\"{}/x.png\"", assets::base_url())),
+            "{html}"
+        );
+    }
+
+    #[test]
+    fn highlighter_classes_survive_and_others_do_not() {
+        let html = render("```rust\nfn main() {}\n```", &RenderOptions::default());
+        assert!(html.contains("class=\"language-rust\""), "{html}");
+        assert!(html.contains("class=\"hl-"), "{html}");
+        let html = render(
+            "<span class=\"evil hl-x\">x</span>",
+            &RenderOptions::default(),
+        );
+        assert!(!html.contains("evil"), "{html}");
+        let html = render(
+            "<code class=\"language-x onclick\">x</code>",
+            &RenderOptions::default(),
+        );
+        assert!(!html.contains("onclick"), "{html}");
+    }
+
+    #[test]
+    fn task_lists_keep_their_checkboxes() {
+        let html = render("- [x] done\n- [ ] todo", &RenderOptions::default());
+        assert_eq!(html.matches("type=\"checkbox\"").count(), 2, "{html}");
+        let html = render(
+            "<input type=\"text\" value=\"x\">",
+            &RenderOptions::default(),
+        );
+        assert!(!html.contains("type=\"text\""), "{html}");
+    }
+}
@@ -0,0 +1,1168 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::Local;
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use rusqlite::OpenFlags;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    archive, atomic_file,
+    attachments::QUARANTINE_DIR,
+    encryption,
+    error::AppError,
+    events, inbox, locale, secrets,
+    settings::{SettingsStore, ShellSettings},
+    shortcuts, snapshots, workspace,
+};
+
+const MIGRATION_VERSION: u32 = 1;
+/// Leading bytes of an encrypted migration archive.
+const MAGIC: &[u8; 8] = b"GYYMIGR1";
+const SALT_LEN: usize = 16;
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// Archives claiming more work than this are refused before any key is derived.
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+/// Plaintext per sealed chunk, so large attachment folders never sit in memory.
+const CHUNK_LEN: usize = 1024 * 1024;
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// Layout of the app data directory this build expects; see `UPGRADES`.
+const DATA_VERSION: u32 = 1;
+const DATA_VERSION_FILE: &str = "data_version.json";
+pub const ONBOARDING_LABEL: &str = "onboarding";
+/// Suffix a legacy database is renamed with once imported, so it is not offered
+/// again and dev builds stop preferring it.
+const IMPORTED_SUFFIX: &str = ".migrated";
+
+const MANIFEST_ENTRY: &str = "migration.json";
+const DATABASE_ENTRY: &str = "chat_app.db";
+const SETTINGS_ENTRY: &str = "shell_settings.json";
+/// Keychain secrets by name; only packed when the user consents to secrets.
+const SECRETS_ENTRY: &str = "secrets.json";
+const APP_CONFIG_FILE: &str = "app_config.json";
+const TOOLS_CONFIG_FILE: &str = "tools_config.json";
+/// Folders under the app data directory carried over whole.
+const DATA_FOLDERS: &[&str] = &["attachments", "plugins"];
+
+events::payload! {
+    #[derive(Deserialize)]
+    pub struct MigrationSummary {
+        version: u32,
+        created_at: String,
+        app_version: String,
+        /// Whether API keys and keychain secrets were kept; without consent they are left out.
+        includes_secrets: bool,
+        /// App data folder on the exporting machine, used to rebase plugin paths.
+        source_data_dir: PathBuf,
+        files: usize,
+        /// Where the archive is; blank in the manifest packed inside it.
+        path: PathBuf,
+        size: u64,
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<LessSafeKey, String> {
+    let iterations =
+        NonZeroU32::new(iterations).ok_or_else(|| "Invalid key derivation cost.".to_string())?;
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    UnboundKey::new(&AES_256_GCM, &key)
+        .map(LessSafeKey::new)
+        .map_err(|_| "Failed to prepare the encryption key.".to_string())
+}
+
+fn chunk_nonce(counter: u64) -> Nonce {
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[NONCE_LEN - 8..].copy_from_slice(&counter.to_be_bytes());
+    Nonce::assume_unique_for_key(nonce)
+}
+
+fn read_chunk(input: &mut impl Read) -> io::Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(CHUNK_LEN);
+    input.take(CHUNK_LEN as u64).read_to_end(&mut chunk)?;
+    Ok(chunk)
+}
+
+/// Seals `source` in 1 MiB AES-256-GCM chunks, each prefixed with a final-chunk flag
+/// and its length. The nonce is the chunk index and the flag is authenticated, so
+/// reordered or truncated archives fail to open.
+fn encrypt(source: &Path, destination: &Path, passphrase: &str) -> Result<(), String> {
+    let mut salt = [0u8; SALT_LEN];
+    SystemRandom::new()
+        .fill(&mut salt)
+        .map_err(|_| "Failed to generate a salt.".to_string())?;
+    let key = derive_key(passphrase, &salt, PBKDF2_ITERATIONS)?;
+    let write_err = |err: io::Error| format!("Failed to write {}: {err}", destination.display());
+    let mut input =
+        BufReader::new(File::open(source).map_err(|err| format!("Failed to read export: {err}"))?);
+    let file = File::create(destination).map_err(write_err)?;
+    let mut output = BufWriter::new(&file);
+    output.write_all(MAGIC).map_err(write_err)?;
+    output.write_all(&salt).map_err(write_err)?;
+    output
+        .write_all(&PBKDF2_ITERATIONS.to_le_bytes())
+        .map_err(write_err)?;
+    let read_err = |err: io::Error| format!("Failed to read export: {err}");
+    let mut chunk = read_chunk(&mut input).map_err(read_err)?;
+    for counter in 0u64.. {
+        let next = if chunk.len() == CHUNK_LEN {
+            read_chunk(&mut input).map_err(read_err)?
+        } else {
+            Vec::new()
+        };
+        let last = next.is_empty();
+        let flag = u8::from(last);
+        key.seal_in_place_append_tag(chunk_nonce(counter), Aad::from([flag]), &mut chunk)
+            .map_err(|_| "Failed to encrypt the archive.".to_string())?;
+        output
+            .write_all(&[flag])
+            .and_then(|_| output.write_all(&(chunk.len() as u32).to_le_bytes()))
+            .and_then(|_| output.write_all(&chunk))
+            .map_err(write_err)?;
+        if last {
+            break;
+        }
+        chunk = next;
+    }
+    output.flush().map_err(write_err)?;
+    drop(output);
+    file.sync_all().map_err(write_err)
+}
+
+fn decrypt(source: &Path, destination: &Path, passphrase: &str) -> Result<(), String> {
+    let damaged = || "The archive is damaged or incomplete.".to_string();
+    let mut input = BufReader::new(
+        File::open(source).map_err(|err| format!("Failed to open {}: {err}", source.display()))?,
+    );
+    let mut magic = [0u8; 8];
+    let mut salt = [0u8; SALT_LEN];
+    let mut iterations = [0u8; 4];
+    input
+        .read_exact(&mut magic)
+        .and_then(|_| input.read_exact(&mut salt))
+        .and_then(|_| input.read_exact(&mut iterations))
+        .map_err(|_| "Not a migration archive.".to_string())?;
+    if &magic != MAGIC {
+        return Err("Not a migration archive.".to_string());
+    }
+    let iterations = u32::from_le_bytes(iterations);
+    if iterations > MAX_PBKDF2_ITERATIONS {
+        return Err(damaged());
+    }
+    let key = derive_key(passphrase, &salt, iterations)?;
+    let write_err = |err: io::Error| format!("Failed to unpack archive: {err}");
+    let mut output = BufWriter::new(File::create(destination).map_err(write_err)?);
+    for counter in 0u64.. {
+        let mut header = [0u8; 5];
+        input.read_exact(&mut header).map_err(|_| damaged())?;
+        let flag = header[0];
+        let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        if flag > 1 || len > CHUNK_LEN + AES_256_GCM.tag_len() {
+            return Err(damaged());
+        }
+        let mut chunk = vec![0u8; len];
+        input.read_exact(&mut chunk).map_err(|_| damaged())?;
+        let plain = match key.open_in_place(chunk_nonce(counter), Aad::from([flag]), &mut chunk) {
+            Ok(plain) => plain,
+            Err(_) if counter == 0 => {
+                return Err("Wrong passphrase, or the archive is damaged.".to_string())
+            }
+            Err(_) => return Err(damaged()),
+        };
+        output.write_all(plain).map_err(write_err)?;
+        if flag == 1 {
+            break;
+        }
+    }
+    let mut trailing = [0u8; 1];
+    if input.read(&mut trailing).map_err(|_| damaged())? != 0 {
+        return Err(damaged());
+    }
+    output.flush().map_err(write_err)
+}
+
+fn options() -> SimpleFileOptions {
+    SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true)
+}
+
+fn add_bytes(zip: &mut ZipWriter<File>, name: &str, bytes: &[u8]) -> Result<(), String> {
+    zip.start_file(name, options())
+        .map_err(|err| format!("Failed to pack {name}: {err}"))?;
+    zip.write_all(bytes)
+        .map_err(|err| format!("Failed to pack {name}: {err}"))
+}
+
+fn add_file(zip: &mut ZipWriter<File>, name: &str, source: &Path) -> Result<(), String> {
+    zip.start_file(name, options())
+        .map_err(|err| format!("Failed to pack {name}: {err}"))?;
+    let mut file =
+        File::open(source).map_err(|err| format!("Failed to read {}: {err}", source.display()))?;
+    io::copy(&mut file, zip).map_err(|err| format!("Failed to pack {name}: {err}"))?;
+    Ok(())
+}
+
+fn add_folder(
+    zip: &mut ZipWriter<File>,
+    root: &Path,
+    dir: &Path,
+    prefix: &str,
+    files: &mut usize,
+) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|err| format!("Failed to read {}: {err}", dir.display()))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        // Flagged files stay behind; they were never let into the store.
+        if prefix == "attachments" && relative.starts_with(QUARANTINE_DIR) {
+            continue;
+        }
+        let name = format!("{prefix}/{}", relative.to_string_lossy().replace('\\', "/"));
+        if path.is_dir() {
+            add_folder(zip, root, &path, prefix, files)?;
+        } else if path.is_file() {
+            add_file(zip, &name, &path)?;
+            *files += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Blanks every stored API key in an exported copy of the database.
+fn strip_database_secrets(db: &Path) -> Result<(), String> {
+    let conn = encryption::open(db, OpenFlags::default())
+        .map_err(|err| format!("Failed to open exported database: {err}"))?;
+    let has_configs: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'llm_configs'",
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|count| count > 0)
+        .map_err(|err| format!("Failed to inspect exported database: {err}"))?;
+    if has_configs {
+        conn.execute("UPDATE llm_configs SET api_key = ''", [])
+            .map_err(|err| format!("Failed to remove API keys: {err}"))?;
+        conn.execute_batch("VACUUM")
+            .map_err(|err| format!("Failed to compact exported database: {err}"))?;
+    }
+    Ok(())
+}
+
+fn strip_tools_secrets(raw: &str) -> Result<String, String> {
+    let mut config: Value = serde_json::from_str(raw)
+        .map_err(|err| format!("Failed to parse {TOOLS_CONFIG_FILE}: {err}"))?;
+    if let Some(key) = config.pointer_mut("/search/tavily_api_key") {
+        *key = Value::String(String::new());
+    }
+    serde_json::to_string_pretty(&config)
+        .map_err(|err| format!("Failed to serialize {TOOLS_CONFIG_FILE}: {err}"))
+}
+
+fn pack<R: Runtime>(
+    app: &AppHandle<R>,
+    zip_path: &Path,
+    scratch: &Path,
+    include_secrets: bool,
+) -> Result<MigrationSummary, String> {
+    let app_data_dir = workspace::data_dir(app)?;
+    let mut zip = ZipWriter::new(
+        File::create(zip_path).map_err(|err| format!("Failed to create export: {err}"))?,
+    );
+    let mut files = 0;
+
+    let db_path = workspace::db_path(app)?;
+    if db_path.exists() {
+        let copy = scratch.join(DATABASE_ENTRY);
+        archive::export_database(&db_path, &copy)?;
+        if !include_secrets {
+            strip_database_secrets(&copy)?;
+        }
+        add_file(&mut zip, DATABASE_ENTRY, &copy)?;
+        files += 1;
+    }
+    if let Some(store) = app.try_state::<SettingsStore>() {
+        let raw = serde_json::to_string_pretty(&store.get())
+            .map_err(|err| format!("Failed to serialize settings: {err}"))?;
+        add_bytes(&mut zip, SETTINGS_ENTRY, raw.as_bytes())?;
+        files += 1;
+    }
+    if include_secrets {
+        let values: BTreeMap<String, String> = secrets::environment(app).into_iter().collect();
+        let raw = serde_json::to_string_pretty(&values)
+            .map_err(|err| format!("Failed to serialize secrets: {err}"))?;
+        add_bytes(&mut zip, SECRETS_ENTRY, raw.as_bytes())?;
+        files += 1;
+    }
+    for config in [APP_CONFIG_FILE, TOOLS_CONFIG_FILE] {
+        let source = app_data_dir.join(config);
+        if !source.exists() {
+            continue;
+        }
+        let mut raw =
+            fs::read_to_string(&source).map_err(|err| format!("Failed to read {config}: {err}"))?;
+        if config == TOOLS_CONFIG_FILE && !include_secrets {
+            raw = strip_tools_secrets(&raw)?;
+        }
+        add_bytes(&mut zip, config, raw.as_bytes())?;
+        files += 1;
+    }
+    for folder in DATA_FOLDERS {
+        let dir = app_data_dir.join(folder);
+        if dir.is_dir() {
+            add_folder(&mut zip, &dir, &dir, folder, &mut files)?;
+        }
+    }
+
+    let summary = MigrationSummary {
+        version: MIGRATION_VERSION,
+        created_at: Local::now().to_rfc3339(),
+        app_version: app.package_info().version.to_string(),
+        includes_secrets: include_secrets,
+        source_data_dir: app_data_dir,
+        files,
+        path: PathBuf::new(),
+        size: 0,
+    };
+    let manifest = serde_json::to_string_pretty(&summary)
+        .map_err(|err| format!("Failed to serialize manifest: {err}"))?;
+    add_bytes(&mut zip, MANIFEST_ENTRY, manifest.as_bytes())?;
+    zip.finish()
+        .map_err(|err| format!("Failed to finish export: {err}"))?;
+    Ok(summary)
+}
+
+fn validate_passphrase(passphrase: &str) -> Result<(), AppError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(AppError::invalid_input(format!(
+            "The passphrase must be at least {MIN_PASSPHRASE_LEN} characters."
+        )));
+    }
+    Ok(())
+}
+
+/// A private working folder removed on drop, whatever happened in between.
+struct Scratch(PathBuf);
+
+impl Scratch {
+    fn create<R: Runtime>(app: &AppHandle<R>) -> Result<Self, String> {
+        let dir = crate::resolve_app_data_dir(app)?.join(format!(
+            ".migration-{}",
+            Local::now().format("%Y%m%d-%H%M%S%f")
+        ));
+        fs::create_dir_all(&dir).map_err(|err| format!("Failed to create work folder: {err}"))?;
+        Ok(Self(dir))
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn export<R: Runtime>(
+    app: &AppHandle<R>,
+    path: &Path,
+    passphrase: &str,
+    include_secrets: bool,
+) -> Result<MigrationSummary, String> {
+    let scratch = Scratch::create(app)?;
+    let zip_path = scratch.0.join("migration.zip");
+    let mut summary = pack(app, &zip_path, &scratch.0, include_secrets)?;
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    if let Err(err) = encrypt(&zip_path, &partial, passphrase).and_then(|_| {
+        fs::rename(&partial, path).map_err(|err| format!("Failed to finish export: {err}"))
+    }) {
+        let _ = fs::remove_file(&partial);
+        return Err(err);
+    }
+    summary.size = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    summary.path = path.to_path_buf();
+    tracing::info!(
+        "[Migration] Exported {} files to {}",
+        summary.files,
+        path.display()
+    );
+    Ok(summary)
+}
+
+/// Points registered plugins at this machine's plugin folder.
+fn rebase_plugins(raw: &str, from: &Path, to: &Path) -> Result<String, String> {
+    let mut config: Value = serde_json::from_str(raw)
+        .map_err(|err| format!("Failed to parse {TOOLS_CONFIG_FILE}: {err}"))?;
+    if let Some(Value::Object(plugins)) = config.get_mut("plugins") {
+        for plugin in plugins.values_mut() {
+            let Some(Value::String(path)) = plugin.get_mut("path") else {
+                continue;
+            };
+            if let Ok(relative) = Path::new(path.as_str()).strip_prefix(from) {
+                *path = to.join(relative).to_string_lossy().into_owned();
+            }
+        }
+    }
+    serde_json::to_string_pretty(&config)
+        .map_err(|err| format!("Failed to serialize {TOOLS_CONFIG_FILE}: {err}"))
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Option<Vec<u8>>, String> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(err) => return Err(format!("Failed to read {name}: {err}")),
+    };
+    let mut bytes = Vec::new();
+    entry
+        .read_to_end(&mut bytes)
+        .map_err(|err| format!("Failed to read {name}: {err}"))?;
+    Ok(Some(bytes))
+}
+
+/// Extracts `<folder>/...` entries into a staging folder beside the live one.
+fn unpack_folder(
+    archive: &mut ZipArchive<File>,
+    folder: &str,
+    staging: &Path,
+) -> Result<(), String> {
+    let _ = fs::remove_dir_all(staging);
+    fs::create_dir_all(staging).map_err(|err| format!("Failed to unpack {folder}: {err}"))?;
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|err| format!("Failed to read archive: {err}"))?;
+        // Rejects absolute paths and `..` so entries cannot escape the data folder.
+        let Some(name) = entry.enclosed_name() else {
+            return Err(format!("Unsafe path in archive: {}", entry.name()));
+        };
+        let Ok(relative) = name.strip_prefix(folder) else {
+            continue;
+        };
+        if entry.is_dir() || relative.as_os_str().is_empty() {
+            continue;
+        }
+        let target = staging.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to unpack {folder}: {err}"))?;
+        }
+        let mut out =
+            File::create(&target).map_err(|err| format!("Failed to unpack {folder}: {err}"))?;
+        io::copy(&mut entry, &mut out)
+            .map_err(|err| format!("Failed to unpack {folder}: {err}"))?;
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(&target, fs::Permissions::from_mode(mode & 0o755));
+        }
+    }
+    Ok(())
+}
+
+/// Writes the archive's secrets into this machine's keychain and returns the names
+/// that were stored.
+fn restore_secrets<R: Runtime>(
+    app: &AppHandle<R>,
+    archive: &mut ZipArchive<File>,
+) -> Result<BTreeSet<String>, String> {
+    let Some(bytes) = read_entry(archive, SECRETS_ENTRY)? else {
+        return Ok(BTreeSet::new());
+    };
+    let values: BTreeMap<String, String> = serde_json::from_slice(&bytes)
+        .map_err(|err| format!("Invalid secrets in archive: {err}"))?;
+    let mut stored = BTreeSet::new();
+    for (key, value) in values {
+        match secrets::store(app, &key, &value) {
+            Ok(()) => {
+                stored.insert(key);
+            }
+            Err(err) => tracing::warn!("[Migration] Skipping secret {key}: {err}"),
+        }
+    }
+    Ok(stored)
+}
+
+/// Swaps the extracted data into place; runs with the backend stopped.
+fn apply<R: Runtime>(
+    app: &AppHandle<R>,
+    archive: &mut ZipArchive<File>,
+    manifest: &MigrationSummary,
+    scratch: &Path,
+) -> Result<(), String> {
+    let app_data_dir = workspace::data_dir(app)?;
+    if let Some(bytes) = read_entry(archive, DATABASE_ENTRY)? {
+        let copy = scratch.join(DATABASE_ENTRY);
+        fs::write(&copy, bytes).map_err(|err| format!("Failed to unpack database: {err}"))?;
+        snapshots::replace_database(&copy, &workspace::db_path(app)?)?;
+    }
+    if let Some(bytes) = read_entry(archive, APP_CONFIG_FILE)? {
+        atomic_file::write(&app_data_dir.join(APP_CONFIG_FILE), bytes)
+            .map_err(|err| format!("Failed to restore {APP_CONFIG_FILE}: {err}"))?;
+    }
+    if let Some(bytes) = read_entry(archive, TOOLS_CONFIG_FILE)? {
+        let raw = String::from_utf8_lossy(&bytes);
+        let raw = rebase_plugins(
+            &raw,
+            &manifest.source_data_dir.join("plugins"),
+            &app_data_dir.join("plugins"),
+        )?;
+        atomic_file::write(&app_data_dir.join(TOOLS_CONFIG_FILE), raw)
+            .map_err(|err| format!("Failed to restore {TOOLS_CONFIG_FILE}: {err}"))?;
+    }
+    for folder in DATA_FOLDERS {
+        let live = app_data_dir.join(folder);
+        let staging = app_data_dir.join(format!(".{folder}.partial"));
+        unpack_folder(archive, folder, &staging)?;
+        // Keep this machine's quarantine; it was never part of the export.
+        if *folder == "attachments" {
+            let quarantine = live.join(QUARANTINE_DIR);
+            if quarantine.is_dir() {
+                let _ = fs::rename(&quarantine, staging.join(QUARANTINE_DIR));
+            }
+        }
+        let _ = fs::remove_dir_all(&live);
+        fs::rename(&staging, &live).map_err(|err| format!("Failed to restore {folder}: {err}"))?;
+    }
+    let secret_keys = restore_secrets(app, archive)?;
+    if let Some(bytes) = read_entry(archive, SETTINGS_ENTRY)? {
+        let mut restored: ShellSettings = serde_json::from_slice(&bytes)
+            .map_err(|err| format!("Invalid settings in archive: {err}"))?;
+        if let Some(store) = app.try_state::<SettingsStore>() {
+            // The archive's names only count for values that are in this keychain.
+            restored.secret_keys = secret_keys;
+            restored.secret_keys.extend(store.get().secret_keys);
+            store.update(|settings| *settings = restored)?;
+        }
+    }
+    Ok(())
+}
+
+fn restore<R: Runtime>(
+    app: &AppHandle<R>,
+    path: &Path,
+    passphrase: &str,
+) -> Result<MigrationSummary, String> {
+    let scratch = Scratch::create(app)?;
+    let zip_path = scratch.0.join("migration.zip");
+    decrypt(path, &zip_path, passphrase)?;
+    let mut archive = ZipArchive::new(
+        File::open(&zip_path).map_err(|err| format!("Failed to open archive: {err}"))?,
+    )
+    .map_err(|err| format!("The archive is damaged: {err}"))?;
+    let manifest = read_entry(&mut archive, MANIFEST_ENTRY)?
+        .ok_or_else(|| "The archive has no manifest.".to_string())?;
+    let mut manifest: MigrationSummary = serde_json::from_slice(&manifest)
+        .map_err(|err| format!("Invalid migration manifest: {err}"))?;
+    if manifest.version > MIGRATION_VERSION {
+        return Err("The archive was made by a newer version of the app.".to_string());
+    }
+    // Replacing everything is drastic, so keep a way back.
+    let safety = format!("before-migration-{}", Local::now().format("%Y%m%d-%H%M%S"));
+    snapshots::capture(app, &safety)?;
+    crate::with_backend_stopped(app, || apply(app, &mut archive, &manifest, &scratch.0))?;
+    if let Err(err) = shortcuts::apply(app) {
+        tracing::warn!("[Shortcuts] {err}");
+    }
+    if let Err(err) = inbox::restart(app) {
+        tracing::warn!("[Inbox] {err}");
+    }
+    manifest.path = path.to_path_buf();
+    manifest.size = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    tracing::info!(
+        "[Migration] Restored {} files from {}; previous state saved as '{safety}'.",
+        manifest.files,
+        path.display()
+    );
+    events::migration_restored(app, &manifest);
+    Ok(manifest)
+}
+
+/// Packs the chat database, settings, configs, attachments and plugins into one
+/// archive encrypted with `passphrase`. API keys are blanked and keychain secrets
+/// left out unless `include_secrets` is set.
+#[tauri::command]
+pub async fn export_everything(
+    app: AppHandle,
+    path: String,
+    passphrase: String,
+    include_secrets: bool,
+) -> Result<MigrationSummary, AppError> {
+    validate_passphrase(&passphrase)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        export(&app, Path::new(&path), &passphrase, include_secrets)
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Export task failed: {err}")))?
+    .map_err(AppError::from)
+}
+
+/// Replaces this machine's data with an `export_everything` archive, restarting
+/// the backend around the swap. The current state is first saved as a
+/// `before-migration-*` snapshot.
+#[tauri::command]
+pub async fn restore_everything(
+    app: AppHandle,
+    path: String,
+    passphrase: String,
+) -> Result<MigrationSummary, AppError> {
+    let source = PathBuf::from(&path);
+    if !source.is_file() {
+        return Err(AppError::not_found(format!("{path} does not exist.")));
+    }
+    tauri::async_runtime::spawn_blocking(move || restore(&app, &source, &passphrase))
+        .await
+        .map_err(|err| AppError::from(format!("Restore task failed: {err}")))?
+        .map_err(AppError::from)
+}
+
+/// Written to `data_version.json` once the data directory is brought up to date.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct DataVersion {
+    version: u32,
+    app_version: String,
+    updated_at: String,
+    /// Set once the welcome window has been dismissed.
+    onboarded: bool,
+    /// Set once the user imported or declined the legacy data found at startup.
+    legacy_decided: bool,
+}
+
+/// One step of `UPGRADES`, run once when the marker is older than `version`.
+/// Table changes are not made here; the backend upgrades its schema when it opens
+/// the database.
+struct Upgrade {
+    version: u32,
+    description: &'static str,
+    run: fn(&Path) -> Result<(), String>,
+}
+
+const UPGRADES: &[Upgrade] = &[Upgrade {
+    version: 1,
+    description: "Create the data folders",
+    run: create_data_folders,
+}];
+
+fn create_data_folders(app_data_dir: &Path) -> Result<(), String> {
+    for folder in DATA_FOLDERS {
+        fs::create_dir_all(app_data_dir.join(folder))
+            .map_err(|err| format!("Failed to create {folder}: {err}"))?;
+    }
+    Ok(())
+}
+
+/// Data left in a location older builds used, offered for import on first run.
+#[derive(Debug, Clone, Serialize)]
+pub struct LegacyData {
+    dir: PathBuf,
+    database: Option<PathBuf>,
+    database_size: u64,
+    configs: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingState {
+    first_run: bool,
+    data_version: u32,
+    app_data_dir: PathBuf,
+    legacy: Vec<LegacyData>,
+}
+
+#[derive(Default)]
+pub struct Onboarding(Mutex<Option<OnboardingState>>);
+
+events::payload! {
+    pub struct OnboardingProgress {
+        step: String,
+        done: usize,
+        total: usize,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LegacyImport {
+    database: bool,
+    configs: Vec<String>,
+    /// Configs left alone because the data directory already had its own.
+    skipped: Vec<String>,
+    /// Where the database that was replaced went, if there was one.
+    replaced: Option<PathBuf>,
+}
+
+fn read_data_version(app_data_dir: &Path) -> Option<DataVersion> {
+    let raw = fs::read_to_string(app_data_dir.join(DATA_VERSION_FILE)).ok()?;
+    match serde_json::from_str(&raw) {
+        Ok(marker) => Some(marker),
+        Err(err) => {
+            tracing::warn!("[Migration] Ignoring unreadable {DATA_VERSION_FILE}: {err}");
+            None
+        }
+    }
+}
+
+fn write_data_version<R: Runtime>(
+    app: &AppHandle<R>,
+    app_data_dir: &Path,
+    marker: &mut DataVersion,
+) -> Result<(), String> {
+    marker.app_version = app.package_info().version.to_string();
+    marker.updated_at = Local::now().to_rfc3339();
+    let raw = serde_json::to_string_pretty(marker)
+        .map_err(|err| format!("Failed to serialize {DATA_VERSION_FILE}: {err}"))?;
+    atomic_file::write(&app_data_dir.join(DATA_VERSION_FILE), raw)
+        .map_err(|err| format!("Failed to write {DATA_VERSION_FILE}: {err}"))
+}
+
+fn update_data_version<R: Runtime>(
+    app: &AppHandle<R>,
+    change: impl FnOnce(&mut DataVersion),
+) -> Result<(), String> {
+    let app_data_dir = crate::resolve_app_data_dir(app)?;
+    let mut marker = read_data_version(&app_data_dir).unwrap_or_default();
+    change(&mut marker);
+    write_data_version(app, &app_data_dir, &mut marker)
+}
+
+/// Folders older builds kept their data in: the checkout in dev builds, where the
+/// backend ran from the project or `python-backend/`, and the install folder for
+/// portable builds, where it ran beside the executable.
+fn legacy_dirs() -> Vec<PathBuf> {
+    if tauri::is_dev() {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..");
+        return vec![root.join("python-backend"), root];
+    }
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .into_iter()
+        .collect()
+}
+
+fn find_legacy_data(app_data_dir: &Path) -> Vec<LegacyData> {
+    // An explicit database path is the user's own choice; leave it be.
+    if std::env::var_os("TAURI_AGENT_DB_PATH").is_some() {
+        return Vec::new();
+    }
+    legacy_dirs()
+        .into_iter()
+        .filter(|dir| dir.as_path() != app_data_dir)
+        .filter_map(|dir| {
+            let database = Some(dir.join(DATABASE_ENTRY)).filter(|path| path.is_file());
+            let configs: Vec<PathBuf> = [APP_CONFIG_FILE, TOOLS_CONFIG_FILE]
+                .iter()
+                .map(|file| dir.join(file))
+                .filter(|path| path.is_file())
+                .collect();
+            // Configs alone are checked-in defaults, not someone's data.
+            database.as_ref()?;
+            Some(LegacyData {
+                database_size: database
+                    .as_ref()
+                    .and_then(|path| fs::metadata(path).ok())
+                    .map_or(0, |meta| meta.len()),
+                dir,
+                database,
+                configs,
+            })
+        })
+        .collect()
+}
+
+fn open_onboarding_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window(ONBOARDING_LABEL) {
+        let _ = window.set_focus();
+        return;
+    }
+    let built = WebviewWindowBuilder::new(
+        app,
+        ONBOARDING_LABEL,
+        WebviewUrl::App("index.html?window=onboarding".into()),
+    )
+    .title(locale::t(app, "window.onboarding"))
+    .inner_size(560.0, 480.0)
+    .resizable(false)
+    .center()
+    .build();
+    if let Err(err) = built {
+        tracing::warn!("[Migration] Failed to open the onboarding window: {err}");
+    }
+}
+
+/// Brings the data directory up to `DATA_VERSION` and looks for data from older
+/// builds. Runs in `setup` before the backend is spawned; opens the onboarding
+/// window on first run or when legacy data is waiting to be imported.
+pub fn prepare<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let app_data_dir = crate::resolve_app_data_dir(app)?;
+    let existing = read_data_version(&app_data_dir);
+    let unversioned = existing.is_none();
+    // Installs from before the marker existed are not new, just unversioned.
+    let has_data = app_data_dir.join(DATABASE_ENTRY).exists();
+    let mut marker = existing.unwrap_or(DataVersion {
+        onboarded: has_data,
+        ..DataVersion::default()
+    });
+    if marker.version > DATA_VERSION {
+        tracing::warn!(
+            "[Migration] Data directory is at version {}, newer than this build's {DATA_VERSION}.",
+            marker.version
+        );
+    }
+    let from = marker.version;
+    for upgrade in UPGRADES.iter().filter(|upgrade| upgrade.version > from) {
+        tracing::info!(
+            "[Migration] Upgrading data to version {}: {}",
+            upgrade.version,
+            upgrade.description
+        );
+        (upgrade.run)(&app_data_dir)?;
+        marker.version = upgrade.version;
+    }
+    if unversioned || marker.version != from {
+        write_data_version(app, &app_data_dir, &mut marker)?;
+    }
+    let legacy = if marker.legacy_decided {
+        Vec::new()
+    } else {
+        find_legacy_data(&app_data_dir)
+    };
+    let first_run = !marker.onboarded;
+    let show = first_run || !legacy.is_empty();
+    if let Some(state) = app.try_state::<Onboarding>() {
+        if let Ok(mut current) = state.0.lock() {
+            *current = Some(OnboardingState {
+                first_run,
+                data_version: marker.version,
+                app_data_dir,
+                legacy,
+            });
+        }
+    }
+    if show && !crate::kiosk::is_enabled(app) {
+        open_onboarding_window(app);
+    }
+    Ok(())
+}
+
+fn emit_progress<R: Runtime>(app: &AppHandle<R>, step: &str, done: usize, total: usize) {
+    events::onboarding_progress(
+        app,
+        ONBOARDING_LABEL,
+        &OnboardingProgress {
+            step: step.to_string(),
+            done,
+            total,
+        },
+    );
+}
+
+/// Moves the database and configs from `legacy` into the data directory; runs with
+/// the backend stopped.
+fn import_legacy<R: Runtime>(
+    app: &AppHandle<R>,
+    legacy: &LegacyData,
+    scratch: &Path,
+) -> Result<LegacyImport, String> {
+    let app_data_dir = crate::resolve_app_data_dir(app)?;
+    let total = usize::from(legacy.database.is_some()) + legacy.configs.len();
+    let mut done = 0;
+    let mut result = LegacyImport {
+        database: false,
+        configs: Vec::new(),
+        skipped: Vec::new(),
+        replaced: None,
+    };
+    if let Some(source) = &legacy.database {
+        emit_progress(app, DATABASE_ENTRY, done, total);
+        let target = app_data_dir.join(DATABASE_ENTRY);
+        if target.exists() {
+            let aside = app_data_dir.join(format!(
+                "{DATABASE_ENTRY}.before-import-{}",
+                Local::now().format("%Y%m%d-%H%M%S")
+            ));
+            archive::export_database(&target, &aside)?;
+            result.replaced = Some(aside);
+        }
+        let copy = scratch.join(DATABASE_ENTRY);
+        archive::export_database(source, &copy)?;
+        snapshots::replace_database(&copy, &target)?;
+        let mut imported = source.as_os_str().to_owned();
+        imported.push(IMPORTED_SUFFIX);
+        fs::rename(source, PathBuf::from(imported))
+            .map_err(|err| format!("Failed to retire {}: {err}", source.display()))?;
+        for suffix in ["-wal", "-shm"] {
+            let mut sidecar = source.as_os_str().to_owned();
+            sidecar.push(suffix);
+            let _ = fs::remove_file(PathBuf::from(sidecar));
+        }
+        result.database = true;
+        done += 1;
+    }
+    for source in &legacy.configs {
+        let Some(name) = source.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        emit_progress(app, name, done, total);
+        let target = app_data_dir.join(name);
+        if target.exists() {
+            result.skipped.push(name.to_string());
+        } else {
+            let raw = fs::read(source).map_err(|err| format!("Failed to read {name}: {err}"))?;
+            atomic_file::write(&target, raw)
+                .map_err(|err| format!("Failed to import {name}: {err}"))?;
+            result.configs.push(name.to_string());
+        }
+        done += 1;
+    }
+    emit_progress(app, "done", done, total);
+    Ok(result)
+}
+
+fn legacy_at<R: Runtime>(app: &AppHandle<R>, dir: &Path) -> Result<LegacyData, AppError> {
+    app.try_state::<Onboarding>()
+        .and_then(|state| state.0.lock().ok()?.clone())
+        .and_then(|state| state.legacy.into_iter().find(|legacy| legacy.dir == dir))
+        .ok_or_else(|| {
+            AppError::not_found(format!("No legacy data was found in {}.", dir.display()))
+        })
+}
+
+fn forget_legacy<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    update_data_version(app, |marker| marker.legacy_decided = true)?;
+    if let Some(state) = app.try_state::<Onboarding>() {
+        if let Ok(mut current) = state.0.lock() {
+            if let Some(current) = current.as_mut() {
+                current.legacy.clear();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// What the onboarding window shows: whether this is a first run and any data from
+/// older builds that could be imported.
+#[tauri::command]
+pub fn get_onboarding_state(state: tauri::State<Onboarding>) -> Option<OnboardingState> {
+    state.0.lock().ok()?.clone()
+}
+
+/// Imports the legacy data found in `dir`, restarting the backend around it and
+/// emitting `onboarding://progress` to the onboarding window. A database already
+/// in the data directory is kept beside it as `chat_app.db.before-import-*`.
+#[tauri::command]
+pub async fn import_legacy_data(app: AppHandle, dir: PathBuf) -> Result<LegacyImport, AppError> {
+    let legacy = legacy_at(&app, &dir)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let scratch = Scratch::create(&app)?;
+        let imported =
+            crate::with_backend_stopped(&app, || import_legacy(&app, &legacy, &scratch.0))?;
+        forget_legacy(&app)?;
+        tracing::info!(
+            "[Migration] Imported legacy data from {}.",
+            legacy.dir.display()
+        );
+        Ok::<_, String>(imported)
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Import task failed: {err}")))?
+    .map_err(AppError::from)
+}
+
+/// Leaves the legacy data where it is and stops offering it.
+#[tauri::command]
+pub fn dismiss_legacy_data(app: AppHandle) -> Result<(), AppError> {
+    forget_legacy(&app).map_err(AppError::from)
+}
+
+/// Records that onboarding is done and closes its window.
+#[tauri::command]
+pub fn finish_onboarding(app: AppHandle) -> Result<(), AppError> {
+    update_data_version(&app, |marker| marker.onboarded = true)?;
+    if let Some(state) = app.try_state::<Onboarding>() {
+        if let Ok(mut current) = state.0.lock() {
+            if let Some(current) = current.as_mut() {
+                current.first_run = false;
+            }
+        }
+    }
+    if let Some(window) = app.get_webview_window(ONBOARDING_LABEL) {
+        let _ = window.close();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh folder under the system temp dir, removed when dropped.
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("migration-test-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).expect("create scratch dir");
+            Self(dir)
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn roundtrip(scratch: &Scratch, plain: &[u8]) {
+        let source = scratch.0.join("plain");
+        let sealed = scratch.0.join("sealed");
+        let opened = scratch.0.join("opened");
+        fs::write(&source, plain).unwrap();
+        encrypt(&source, &sealed, "correct horse").unwrap();
+        decrypt(&sealed, &opened, "correct horse").unwrap();
+        assert!(fs::read(&opened).unwrap() == plain);
+    }
+
+    #[test]
+    fn archives_open_with_the_passphrase_they_were_sealed_with() {
+        let scratch = Scratch::new("roundtrip");
+        roundtrip(&scratch, b"");
+        roundtrip(&scratch, &vec![7u8; CHUNK_LEN]);
+        let plain: Vec<u8> = (0..CHUNK_LEN * 2 + 123).map(|i| i as u8).collect();
+        roundtrip(&scratch, &plain);
+    }
+
+    #[test]
+    fn archives_refuse_a_wrong_passphrase_or_truncation() {
+        let scratch = Scratch::new("refuse");
+        let source = scratch.0.join("plain");
+        let sealed = scratch.0.join("sealed");
+        let opened = scratch.0.join("opened");
+        fs::write(&source, vec![1u8; CHUNK_LEN + 10]).unwrap();
+        encrypt(&source, &sealed, "correct horse").unwrap();
+        let err = decrypt(&sealed, &opened, "wrong horse").unwrap_err();
+        assert!(err.starts_with("Wrong passphrase"), "{err}");
+
+        let bytes = fs::read(&sealed).unwrap();
+        fs::write(&sealed, &bytes[..bytes.len() - 1]).unwrap();
+        let err = decrypt(&sealed, &opened, "correct horse").unwrap_err();
+        assert!(err.contains("damaged"), "{err}");
+
+        fs::write(&sealed, b"not an archive at all").unwrap();
+        let err = decrypt(&sealed, &opened, "correct horse").unwrap_err();
+        assert_eq!(err, "Not a migration archive.");
+    }
+
+    #[test]
+    fn tools_config_loses_its_search_key() {
+        let raw = r#"{"search": {"tavily_api_key": "tvly-secret", "max_results": 5}}"#;
+        let stripped: Value = serde_json::from_str(&strip_tools_secrets(raw).unwrap()).unwrap();
+        assert_eq!(stripped["search"]["tavily_api_key"], "");
+        assert_eq!(stripped["search"]["max_results"], 5);
+        assert!(strip_tools_secrets("{not json").is_err());
+    }
+
+    #[test]
+    fn plugins_move_to_this_machines_data_folder() {
+        let from = Path::new("/old/data");
+        let to = Path::new("/new/data");
+        let raw = serde_json::json!({
+            "plugins": {
+                "inside": {"path": "/old/data/plugins/inside"},
+                "outside": {"path": "/opt/plugins/outside"},
+                "pathless": {"enabled": true},
+            }
+        })
+        .to_string();
+        let rebased: Value =
+            serde_json::from_str(&rebase_plugins(&raw, from, to).unwrap()).unwrap();
+        assert_eq!(
+            rebased["plugins"]["inside"]["path"],
+            to.join("plugins/inside").to_string_lossy().as_ref()
+        );
+        assert_eq!(
+            rebased["plugins"]["outside"]["path"],
+            "/opt/plugins/outside"
+        );
+        assert_eq!(rebased["plugins"]["pathless"]["enabled"], true);
+    }
+
+    fn zip_with(path: &Path, entries: &[(&str, &[u8])]) -> ZipArchive<File> {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, bytes) in entries {
+            add_bytes(&mut zip, name, bytes).unwrap();
+        }
+        zip.finish().unwrap();
+        ZipArchive::new(File::open(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn unpack_folder_takes_only_its_own_entries() {
+        let scratch = Scratch::new("unpack");
+        let mut archive = zip_with(
+            &scratch.0.join("archive.zip"),
+            &[
+                ("attachments/a/b.txt", b"b"),
+                ("plugins/p.txt", b"p"),
+                (DATABASE_ENTRY, b"db"),
+            ],
+        );
+        let staging = scratch.0.join("staging");
+        unpack_folder(&mut archive, "attachments", &staging).unwrap();
+        assert_eq!(fs::read(staging.join("a/b.txt")).unwrap(), b"b");
+        assert!(!staging.join("p.txt").exists());
+        assert_eq!(fs::read_dir(&staging).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn unpack_folder_refuses_entries_that_escape() {
+        let scratch = Scratch::new("escape");
+        let mut archive = zip_with(
+            &scratch.0.join("archive.zip"),
+            &[("attachments/../../evil.txt", b"x")],
+        );
+        let err =
+            unpack_folder(&mut archive, "attachments", &scratch.0.join("staging")).unwrap_err();
+        assert!(err.starts_with("Unsafe path"), "{err}");
+        assert!(!scratch.0.join("evil.txt").exists());
+    }
+
+    #[test]
+    fn unreadable_data_version_is_ignored() {
+        let scratch = Scratch::new("marker");
+        assert!(read_data_version(&scratch.0).is_none());
+        fs::write(scratch.0.join(DATA_VERSION_FILE), "{broken").unwrap();
+        assert!(read_data_version(&scratch.0).is_none());
+        fs::write(
+            scratch.0.join(DATA_VERSION_FILE),
+            r#"{"version": 1, "onboarded": true}"#,
+        )
+        .unwrap();
+        let marker = read_data_version(&scratch.0).unwrap();
+        assert_eq!(marker.version, 1);
+        assert!(marker.onboarded && !marker.legacy_decided);
+    }
+}
@@ -0,0 +1,243 @@
+use std::{collections::HashSet, sync::Mutex, time::Duration};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{error::AppError, events, settings::SettingsStore, sidecar};
+
+const MIN_INTERVAL_SECS: u64 = 2;
+const MAX_INTERVAL_SECS: u64 = 3600;
+/// Below this the backend can hardly load a model config, let alone run a turn.
+const MIN_CEILING_MB: u64 = 256;
+/// Samples in a row over the ceiling before a restart, so one spike is forgiven.
+const BREACH_SAMPLES: u32 = 3;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorSettings {
+    pub interval_secs: u64,
+    /// Restart the sidecar when its memory stays above this many MiB; `None` never does.
+    pub memory_ceiling_mb: Option<u64>,
+}
+
+impl Default for MonitorSettings {
+    fn default() -> Self {
+        Self {
+            interval_secs: 10,
+            memory_ceiling_mb: None,
+        }
+    }
+}
+
+events::payload! {
+    /// One sample of the backend and the processes it started, such as the Python
+    /// interpreter behind a bundled launcher and its terminals.
+    pub struct BackendMetrics {
+        at: String,
+        pid: u32,
+        processes: usize,
+        /// Summed over cores, so a busy process on four cores can read 400.
+        cpu_percent: f32,
+        memory_bytes: u64,
+        /// `None` where the OS does not report it.
+        open_files: Option<usize>,
+    }
+}
+
+events::payload! {
+    pub struct CeilingExceeded {
+        memory_bytes: u64,
+        ceiling_bytes: u64,
+    }
+}
+
+#[derive(Default)]
+struct Sampler {
+    /// Kept between samples; CPU usage is the difference from the previous refresh.
+    system: System,
+    latest: Option<BackendMetrics>,
+    breaches: u32,
+}
+
+#[derive(Default)]
+pub struct ResourceMonitor(Mutex<Sampler>);
+
+fn settings<R: Runtime>(app: &AppHandle<R>) -> MonitorSettings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().monitor)
+        .unwrap_or_default()
+}
+
+/// `root` and every process below it.
+fn process_tree(system: &System, root: Pid) -> HashSet<Pid> {
+    let mut tree = HashSet::from([root]);
+    loop {
+        let before = tree.len();
+        for (pid, process) in system.processes() {
+            if process
+                .parent()
+                .is_some_and(|parent| tree.contains(&parent))
+            {
+                tree.insert(*pid);
+            }
+        }
+        if tree.len() == before {
+            return tree;
+        }
+    }
+}
+
+fn sample(sampler: &mut Sampler, pid: u32) -> Option<BackendMetrics> {
+    let root = Pid::from_u32(pid);
+    sampler.system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_cpu().with_memory(),
+    );
+    sampler.system.process(root)?;
+    let tree = process_tree(&sampler.system, root);
+    let processes: Vec<_> = tree
+        .iter()
+        .filter_map(|pid| sampler.system.process(*pid))
+        .collect();
+    let open_files = processes
+        .iter()
+        .map(|process| process.open_files())
+        .sum::<Option<usize>>();
+    Some(BackendMetrics {
+        at: Local::now().to_rfc3339(),
+        pid,
+        processes: processes.len(),
+        cpu_percent: processes.iter().map(|process| process.cpu_usage()).sum(),
+        memory_bytes: processes.iter().map(|process| process.memory()).sum(),
+        open_files,
+    })
+}
+
+/// Samples the running backend and stores the result; `None` while it is down.
+fn refresh<R: Runtime>(app: &AppHandle<R>) -> Option<BackendMetrics> {
+    let pid = sidecar::info(app, crate::BACKEND_SIDECAR).and_then(|info| info.pid());
+    let monitor = app.try_state::<ResourceMonitor>()?;
+    let mut sampler = monitor.0.lock().ok()?;
+    let metrics = pid.and_then(|pid| sample(&mut sampler, pid));
+    sampler.latest = metrics.clone();
+    metrics
+}
+
+/// Counts samples over the ceiling; true once a restart is due.
+fn over_ceiling<R: Runtime>(app: &AppHandle<R>, metrics: &BackendMetrics) -> bool {
+    let Some(ceiling_mb) = settings(app).memory_ceiling_mb else {
+        return false;
+    };
+    let Some(monitor) = app.try_state::<ResourceMonitor>() else {
+        return false;
+    };
+    let Ok(mut sampler) = monitor.0.lock() else {
+        return false;
+    };
+    if metrics.memory_bytes <= ceiling_mb * 1024 * 1024 {
+        sampler.breaches = 0;
+        return false;
+    }
+    sampler.breaches += 1;
+    if sampler.breaches < BREACH_SAMPLES {
+        return false;
+    }
+    sampler.breaches = 0;
+    true
+}
+
+fn restart_for_memory<R: Runtime>(app: &AppHandle<R>, metrics: &BackendMetrics) {
+    let ceiling_bytes = settings(app).memory_ceiling_mb.unwrap_or(0) * 1024 * 1024;
+    tracing::warn!(
+        "[Monitor] Backend uses {} MiB, over the {} MiB ceiling; restarting it.",
+        metrics.memory_bytes / 1024 / 1024,
+        ceiling_bytes / 1024 / 1024
+    );
+    events::metrics_ceiling_exceeded(
+        app,
+        &CeilingExceeded {
+            memory_bytes: metrics.memory_bytes,
+            ceiling_bytes,
+        },
+    );
+    if let Err(err) = crate::restart_backend(app) {
+        tracing::error!("[Monitor] Restart after the memory ceiling failed: {err}");
+    }
+}
+
+/// Samples the backend every `interval_secs` and emits `metrics://sample`,
+/// restarting it when the memory ceiling is set and stays exceeded.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let interval = settings(&app)
+                .interval_secs
+                .clamp(MIN_INTERVAL_SECS, MAX_INTERVAL_SECS);
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+            let handle = app.clone();
+            let task = tauri::async_runtime::spawn_blocking(move || {
+                let Some(metrics) = refresh(&handle) else {
+                    return;
+                };
+                events::metrics_sample(&handle, &metrics);
+                if over_ceiling(&handle, &metrics) {
+                    restart_for_memory(&handle, &metrics);
+                }
+            });
+            if let Err(err) = task.await {
+                tracing::warn!("[Monitor] Sampling task failed: {err}");
+            }
+        }
+    });
+}
+
+/// The latest sample, or a fresh one when none has been taken yet. `None` while
+/// the backend is not running or is not managed by this app.
+#[tauri::command]
+pub async fn backend_metrics(app: AppHandle) -> Result<Option<BackendMetrics>, AppError> {
+    let latest = app
+        .state::<ResourceMonitor>()
+        .0
+        .lock()
+        .map_err(|_| AppError::unavailable("Backend metrics are unavailable."))?
+        .latest
+        .clone();
+    if latest.is_some() {
+        return Ok(latest);
+    }
+    tauri::async_runtime::spawn_blocking(move || refresh(&app))
+        .await
+        .map_err(|err| AppError::from(format!("Sampling task failed: {err}")))
+}
+
+#[tauri::command]
+pub fn get_resource_monitor(store: tauri::State<SettingsStore>) -> MonitorSettings {
+    store.get().monitor
+}
+
+/// Applies from the next sample.
+#[tauri::command]
+pub fn set_resource_monitor(
+    store: tauri::State<SettingsStore>,
+    settings: MonitorSettings,
+) -> Result<MonitorSettings, AppError> {
+    if !(MIN_INTERVAL_SECS..=MAX_INTERVAL_SECS).contains(&settings.interval_secs) {
+        return Err(AppError::invalid_input(format!(
+            "The sampling interval must be between {MIN_INTERVAL_SECS} and {MAX_INTERVAL_SECS} seconds."
+        )));
+    }
+    if settings
+        .memory_ceiling_mb
+        .is_some_and(|ceiling| ceiling < MIN_CEILING_MB)
+    {
+        return Err(AppError::invalid_input(format!(
+            "The memory ceiling must be at least {MIN_CEILING_MB} MiB."
+        )));
+    }
+    let saved = store.update(|current| current.monitor = settings)?;
+    Ok(saved.monitor)
+}
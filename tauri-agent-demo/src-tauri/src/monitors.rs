@@ -0,0 +1,375 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tauri::{
+    AppHandle, LogicalSize, Manager, Monitor, PhysicalPosition, PhysicalSize, Runtime,
+    WebviewWindow,
+};
+
+use crate::{error::AppError, main_window, settings::SettingsStore};
+
+/// How much of a window must overlap a display's work area to count as reachable.
+const MIN_VISIBLE: i64 = 96;
+/// Moves and resizes arrive in bursts; geometry is saved once they settle.
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+/// Used by `reset_window_state` when the config does not size the main window.
+const DEFAULT_MAIN_SIZE: (f64, f64) = (1200.0, 950.0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bounds {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl Bounds {
+    fn overlap(&self, other: &Bounds) -> (i64, i64) {
+        let left = i64::from(self.x).max(i64::from(other.x));
+        let top = i64::from(self.y).max(i64::from(other.y));
+        let right = (i64::from(self.x) + i64::from(self.width))
+            .min(i64::from(other.x) + i64::from(other.width));
+        let bottom = (i64::from(self.y) + i64::from(self.height))
+            .min(i64::from(other.y) + i64::from(other.height));
+        ((right - left).max(0), (bottom - top).max(0))
+    }
+
+    /// Shrinks to fit `area` and moves inside it, keeping the position where possible.
+    fn clamp_into(self, area: &Bounds) -> Bounds {
+        let width = self.width.min(area.width);
+        let height = self.height.min(area.height);
+        let max_x = i64::from(area.x) + i64::from(area.width - width);
+        let max_y = i64::from(area.y) + i64::from(area.height - height);
+        Bounds {
+            x: i64::from(self.x).clamp(i64::from(area.x), max_x) as i32,
+            y: i64::from(self.y).clamp(i64::from(area.y), max_y) as i32,
+            width,
+            height,
+        }
+    }
+
+    fn centered_in(self, area: &Bounds) -> Bounds {
+        let width = self.width.min(area.width);
+        let height = self.height.min(area.height);
+        Bounds {
+            x: area.x + ((area.width - width) / 2) as i32,
+            y: area.y + ((area.height - height) / 2) as i32,
+            width,
+            height,
+        }
+    }
+}
+
+/// Where a window was last closed, in physical pixels of the virtual desktop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    bounds: Bounds,
+    maximized: bool,
+    /// The display it was on, by `MonitorInfo::id`.
+    #[serde(default)]
+    monitor: Option<String>,
+}
+
+/// Pending saves by window label; a newer move or resize supersedes an older one.
+#[derive(Default)]
+pub struct GeometrySaves(Mutex<HashMap<String, u64>>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorInfo {
+    /// The OS display name, or its position in the list when it has none.
+    id: String,
+    name: Option<String>,
+    bounds: Bounds,
+    /// The display minus taskbars, docks and menu bars.
+    work_area: Bounds,
+    scale_factor: f64,
+    primary: bool,
+}
+
+fn display_id(monitor: &Monitor, index: usize) -> String {
+    monitor
+        .name()
+        .cloned()
+        .unwrap_or_else(|| format!("display-{}", index + 1))
+}
+
+fn work_area(monitor: &Monitor) -> Bounds {
+    let area = monitor.work_area();
+    Bounds {
+        x: area.position.x,
+        y: area.position.y,
+        width: area.size.width,
+        height: area.size.height,
+    }
+}
+
+fn same_monitor(a: &Monitor, b: &Monitor) -> bool {
+    a.name() == b.name() && a.position() == b.position()
+}
+
+fn monitors<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<Monitor>, String> {
+    app.available_monitors()
+        .map_err(|err| format!("Failed to list displays: {err}"))
+}
+
+fn window_bounds<R: Runtime>(window: &WebviewWindow<R>) -> Result<Bounds, String> {
+    let position = window
+        .outer_position()
+        .map_err(|err| format!("Failed to read window position: {err}"))?;
+    let size = window
+        .outer_size()
+        .map_err(|err| format!("Failed to read window size: {err}"))?;
+    Ok(Bounds {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    })
+}
+
+fn apply_bounds<R: Runtime>(window: &WebviewWindow<R>, bounds: Bounds) -> Result<(), String> {
+    window
+        .set_size(PhysicalSize::new(bounds.width, bounds.height))
+        .and_then(|_| window.set_position(PhysicalPosition::new(bounds.x, bounds.y)))
+        .map_err(|err| format!("Failed to move window: {err}"))
+}
+
+/// Keeps `bounds` where it is if enough of it lands on a connected display, and
+/// otherwise centers it on the primary one.
+fn reachable<R: Runtime>(app: &AppHandle<R>, bounds: Bounds) -> Result<Bounds, String> {
+    let monitors = monitors(app)?;
+    let best = monitors
+        .iter()
+        .map(|monitor| {
+            let area = work_area(monitor);
+            let (width, height) = bounds.overlap(&area);
+            (area, width * height, width.min(height))
+        })
+        .max_by_key(|(_, overlap, _)| *overlap);
+    if let Some((area, _, shortest)) = best {
+        if shortest >= MIN_VISIBLE {
+            return Ok(bounds.clamp_into(&area));
+        }
+    }
+    let primary = app
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .or_else(|| monitors.into_iter().next())
+        .ok_or_else(|| "No display is connected.".to_string())?;
+    Ok(bounds.centered_in(&work_area(&primary)))
+}
+
+/// Remembers the window's geometry so the next launch can put it back.
+pub fn save_geometry<R: Runtime>(app: &AppHandle<R>, label: &str) {
+    let (Some(window), Some(store)) = (
+        app.get_webview_window(label),
+        app.try_state::<SettingsStore>(),
+    ) else {
+        return;
+    };
+    // Minimized windows report a parking spot far off-screen on Windows.
+    if window.is_minimized().unwrap_or(false) {
+        return;
+    }
+    let maximized = window.is_maximized().unwrap_or(false);
+    // Keep the restored bounds of a maximized window rather than the full screen.
+    let previous = store
+        .get()
+        .window_geometry
+        .get(label)
+        .map(|geometry| geometry.bounds);
+    let bounds = match (maximized, previous) {
+        (true, Some(previous)) => previous,
+        _ => match window_bounds(&window) {
+            Ok(bounds) => bounds,
+            Err(err) => return tracing::warn!("[Monitors] {err}"),
+        },
+    };
+    let monitor = window.current_monitor().ok().flatten().and_then(|current| {
+        monitors(app)
+            .ok()?
+            .iter()
+            .enumerate()
+            .find_map(|(index, monitor)| {
+                same_monitor(monitor, &current).then(|| display_id(monitor, index))
+            })
+    });
+    if let Err(err) = store.update(|settings| {
+        settings.window_geometry.insert(
+            label.to_string(),
+            WindowGeometry {
+                bounds,
+                maximized,
+                monitor,
+            },
+        );
+    }) {
+        tracing::warn!("[Monitors] {err}");
+    }
+}
+
+/// Saves the window's geometry once it has stopped moving, so a crash or a forced
+/// quit still finds it where the user left it.
+pub fn schedule_save<R: Runtime>(app: &AppHandle<R>, label: &str) {
+    let Some(saves) = app.try_state::<GeometrySaves>() else {
+        return;
+    };
+    let Ok(mut pending) = saves.0.lock() else {
+        return;
+    };
+    let generation = pending.entry(label.to_string()).or_insert(0);
+    *generation += 1;
+    let expected = *generation;
+    let (handle, label) = (app.clone(), label.to_string());
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DELAY).await;
+        let current = handle
+            .try_state::<GeometrySaves>()
+            .and_then(|saves| saves.0.lock().ok()?.get(&label).copied());
+        if current == Some(expected) {
+            save_geometry(&handle, &label);
+        }
+    });
+}
+
+/// Puts a window back where it was last closed, pulled onto a connected display
+/// if that spot is now off-screen (a laptop undocked from an external monitor).
+pub fn restore_geometry<R: Runtime>(app: &AppHandle<R>, label: &str) -> Result<(), String> {
+    let Some(window) = app.get_webview_window(label) else {
+        return Ok(());
+    };
+    let Some(geometry) = app
+        .try_state::<SettingsStore>()
+        .and_then(|store| store.get().window_geometry.get(label).cloned())
+    else {
+        return Ok(());
+    };
+    let monitors = monitors(app)?;
+    let monitor_gone = geometry.monitor.as_ref().is_some_and(|id| {
+        !monitors
+            .iter()
+            .enumerate()
+            .any(|(index, monitor)| &display_id(monitor, index) == id)
+    });
+    let bounds = match app.primary_monitor().ok().flatten() {
+        // Its display was unplugged; start on the primary one rather than wherever
+        // the old coordinates happen to land.
+        Some(primary) if monitor_gone => geometry.bounds.centered_in(&work_area(&primary)),
+        _ => reachable(app, geometry.bounds)?,
+    };
+    apply_bounds(&window, bounds)?;
+    if geometry.maximized {
+        let _ = window.maximize();
+    }
+    Ok(())
+}
+
+/// Moves a window back onto a connected display if it is currently off-screen.
+pub fn ensure_visible<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    if window.is_maximized().unwrap_or(false) || window.is_fullscreen().unwrap_or(false) {
+        return Ok(());
+    }
+    let bounds = window_bounds(window)?;
+    let target = reachable(window.app_handle(), bounds)?;
+    if target != bounds {
+        apply_bounds(window, target)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_monitors(app: AppHandle) -> Result<Vec<MonitorInfo>, AppError> {
+    let primary = app.primary_monitor().ok().flatten();
+    let monitors = monitors(&app).map_err(AppError::unavailable)?;
+    Ok(monitors
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| MonitorInfo {
+            id: display_id(monitor, index),
+            name: monitor.name().cloned(),
+            bounds: Bounds {
+                x: monitor.position().x,
+                y: monitor.position().y,
+                width: monitor.size().width,
+                height: monitor.size().height,
+            },
+            work_area: work_area(monitor),
+            scale_factor: monitor.scale_factor(),
+            primary: primary
+                .as_ref()
+                .is_some_and(|primary| same_monitor(primary, monitor)),
+        })
+        .collect())
+}
+
+/// Centers the window `label` on the display `monitor_id` from `list_monitors`,
+/// keeping it maximized or fullscreen if it was.
+#[tauri::command]
+pub fn move_window_to_monitor(
+    app: AppHandle,
+    label: String,
+    monitor_id: String,
+) -> Result<(), AppError> {
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| AppError::not_found(format!("No window named '{label}'.")))?;
+    let monitors = monitors(&app).map_err(AppError::unavailable)?;
+    let monitor = monitors
+        .iter()
+        .enumerate()
+        .find(|(index, monitor)| display_id(monitor, *index) == monitor_id)
+        .map(|(_, monitor)| monitor)
+        .ok_or_else(|| AppError::not_found(format!("No display named '{monitor_id}'.")))?;
+
+    let maximized = window.is_maximized().unwrap_or(false);
+    let fullscreen = window.is_fullscreen().unwrap_or(false);
+    // The OS keeps maximized and fullscreen windows on their display until released.
+    if fullscreen {
+        let _ = window.set_fullscreen(false);
+    }
+    if maximized {
+        let _ = window.unmaximize();
+    }
+    let bounds = window_bounds(&window).map_err(AppError::from)?;
+    apply_bounds(&window, bounds.centered_in(&work_area(monitor))).map_err(AppError::from)?;
+    if maximized {
+        let _ = window.maximize();
+    }
+    if fullscreen {
+        let _ = window.set_fullscreen(true);
+    }
+    Ok(())
+}
+
+/// Forgets every saved window position and puts the main window back at its
+/// default size in the middle of the primary display.
+#[tauri::command]
+pub fn reset_window_state(
+    app: AppHandle,
+    store: tauri::State<SettingsStore>,
+) -> Result<(), AppError> {
+    // Saves already waiting would write the old geometry back.
+    if let Some(saves) = app.try_state::<GeometrySaves>() {
+        if let Ok(mut pending) = saves.0.lock() {
+            pending.clear();
+        }
+    }
+    store.update(|settings| settings.window_geometry.clear())?;
+    let Some(window) = app.get_webview_window(main_window::LABEL) else {
+        return Ok(());
+    };
+    let (width, height) = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|config| config.label == main_window::LABEL)
+        .map_or(DEFAULT_MAIN_SIZE, |config| (config.width, config.height));
+    let _ = window.set_fullscreen(false);
+    let _ = window.unmaximize();
+    window
+        .set_size(LogicalSize::new(width, height))
+        .and_then(|_| window.center())
+        .map_err(|err| AppError::from(format!("Failed to reset the window: {err}")))
+}
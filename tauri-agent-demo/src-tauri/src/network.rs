@@ -0,0 +1,184 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use serde::Serialize;
+
+use crate::{
+    error::AppError,
+    settings::{BackendTransport, SettingsStore},
+};
+
+pub const DEFAULT_BIND_HOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BindHostInfo {
+    host: String,
+    loopback: bool,
+    warning: Option<String>,
+}
+
+impl BindHostInfo {
+    fn new(host: IpAddr) -> Self {
+        Self {
+            host: host.to_string(),
+            loopback: host.is_loopback(),
+            warning: exposure_warning(host),
+        }
+    }
+}
+
+/// Accepts a bare IPv4/IPv6 literal (brackets optional) or `localhost`.
+pub fn parse_bind_host(value: &str) -> Result<IpAddr, String> {
+    let trimmed = value.trim();
+    let unbracketed = trimmed
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .unwrap_or(trimmed);
+    if unbracketed.eq_ignore_ascii_case("localhost") {
+        return Ok(DEFAULT_BIND_HOST);
+    }
+    unbracketed.parse::<IpAddr>().map_err(|_| {
+        format!("Invalid bind address '{value}'; expected an IP address such as 127.0.0.1 or ::1.")
+    })
+}
+
+fn exposure_warning(host: IpAddr) -> Option<String> {
+    if host.is_loopback() {
+        return None;
+    }
+    Some(format!(
+        "Backend will listen on {host}, which is reachable from other machines on the network. \
+         Anyone who can reach it can read your chat history."
+    ))
+}
+
+/// Resolves the backend bind host from `TAURI_AGENT_BIND_HOST`, then shell settings,
+/// falling back to 127.0.0.1 when the configured value is invalid.
+pub fn resolve_bind_host(settings: Option<&SettingsStore>) -> IpAddr {
+    let configured = std::env::var("TAURI_AGENT_BIND_HOST")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .or_else(|| settings.and_then(|store| store.get().backend.bind_host));
+    let Some(value) = configured else {
+        return DEFAULT_BIND_HOST;
+    };
+    match parse_bind_host(&value) {
+        Ok(host) => {
+            if let Some(warning) = exposure_warning(host) {
+                tracing::warn!("[Backend] WARNING: {warning}");
+            }
+            host
+        }
+        Err(err) => {
+            tracing::warn!("[Backend] {err} Falling back to {DEFAULT_BIND_HOST}.");
+            DEFAULT_BIND_HOST
+        }
+    }
+}
+
+/// The remote backend profile from shell settings, if one is configured.
+pub fn resolve_remote_url(settings: Option<&SettingsStore>) -> Option<String> {
+    settings
+        .and_then(|store| store.get().backend.remote_url)
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+}
+
+/// The sidecar transport from `TAURI_AGENT_BACKEND_TRANSPORT`, then shell settings.
+pub fn resolve_transport(settings: Option<&SettingsStore>) -> BackendTransport {
+    match std::env::var("TAURI_AGENT_BACKEND_TRANSPORT")
+        .map(|value| value.trim().to_ascii_lowercase())
+        .as_deref()
+    {
+        Ok("stdio") => BackendTransport::Stdio,
+        Ok("tcp") => BackendTransport::Tcp,
+        _ => settings
+            .map(|store| store.get().backend.transport)
+            .unwrap_or_default(),
+    }
+}
+
+/// Address clients should dial: a wildcard bind is reached through loopback.
+pub fn connect_host(bind_host: IpAddr) -> IpAddr {
+    match bind_host {
+        IpAddr::V4(addr) if addr.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(addr) if addr.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        other => other,
+    }
+}
+
+#[tauri::command]
+pub fn get_backend_bind_host(store: tauri::State<SettingsStore>) -> BindHostInfo {
+    BindHostInfo::new(resolve_bind_host(Some(&store)))
+}
+
+/// Persists the bind host; it takes effect the next time the backend is spawned.
+#[tauri::command]
+pub fn set_backend_bind_host(
+    store: tauri::State<SettingsStore>,
+    host: Option<String>,
+) -> Result<BindHostInfo, AppError> {
+    let parsed = match host
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        Some(value) => Some(parse_bind_host(value).map_err(AppError::invalid_input)?),
+        None => None,
+    };
+    store.update(|settings| settings.backend.bind_host = parsed.map(|host| host.to_string()))?;
+    Ok(BindHostInfo::new(parsed.unwrap_or(DEFAULT_BIND_HOST)))
+}
+
+/// Persists the remote backend URL (`None` returns to the bundled sidecar) and clears
+/// the selected profile; it takes effect on the next launch.
+#[tauri::command]
+pub fn set_backend_remote_url(
+    store: tauri::State<SettingsStore>,
+    url: Option<String>,
+) -> Result<Option<String>, AppError> {
+    let url = url
+        .map(|value| value.trim().trim_end_matches('/').to_string())
+        .filter(|value| !value.is_empty());
+    if let Some(value) = url.as_deref() {
+        let parsed = reqwest::Url::parse(value).map_err(|err| {
+            AppError::invalid_input(format!("Invalid backend URL '{value}'.")).with_details(err)
+        })?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(AppError::invalid_input(
+                "The backend URL must start with http:// or https://.",
+            ));
+        }
+    }
+    store.update(|settings| {
+        settings.backend.remote_url = url.clone();
+        // The legacy setting only decides when no profile was picked.
+        settings.backend.profile = None;
+    })?;
+    Ok(url)
+}
+
+#[tauri::command]
+pub fn get_backend_transport(store: tauri::State<SettingsStore>) -> BackendTransport {
+    resolve_transport(Some(&store))
+}
+
+/// Persists whether the webview goes through the shell's proxy; it takes effect on
+/// the next launch.
+#[tauri::command]
+pub fn set_backend_proxy(
+    store: tauri::State<SettingsStore>,
+    enabled: bool,
+) -> Result<bool, AppError> {
+    store.update(|settings| settings.backend.proxy = enabled)?;
+    Ok(enabled)
+}
+
+/// Persists the sidecar transport; it takes effect on the next launch.
+#[tauri::command]
+pub fn set_backend_transport(
+    store: tauri::State<SettingsStore>,
+    transport: BackendTransport,
+) -> Result<BackendTransport, AppError> {
+    store.update(|settings| settings.backend.transport = transport)?;
+    Ok(transport)
+}
@@ -0,0 +1,591 @@
+use std::{collections::HashMap, sync::Mutex, thread};
+
+use chrono::{Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Listener, Manager, Runtime};
+
+use crate::{error::AppError, events, locale, main_window, settings::SettingsStore};
+
+/// Action id for a click on the notification body.
+pub const ACTION_DEFAULT: &str = "default";
+/// Opens the conversation with the composer focused.
+pub const ACTION_REPLY: &str = "reply";
+/// Opens the conversation scrolled to the latest result.
+pub const ACTION_VIEW_RESULT: &str = "view_result";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    TaskComplete,
+    ToolError,
+    BackendCrashed,
+    ScheduledJob,
+    InboxFile,
+    Budget,
+    Test,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    /// Platform sound name (e.g. "Glass" on macOS); `None` uses the system default.
+    pub sound: Option<String>,
+    pub quiet_hours: Option<QuietHours>,
+    pub task_complete: bool,
+    pub tool_error: bool,
+    pub backend_crashed: bool,
+    pub scheduled_job: bool,
+    pub inbox_file: bool,
+    /// Spend crossing one of the monthly budget's alert thresholds.
+    pub budget: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sound: None,
+            quiet_hours: None,
+            task_complete: true,
+            tool_error: true,
+            backend_crashed: true,
+            scheduled_job: true,
+            inbox_file: true,
+            budget: true,
+        }
+    }
+}
+
+impl NotificationSettings {
+    fn allows(&self, kind: NotificationKind) -> bool {
+        match kind {
+            NotificationKind::Test => true,
+            NotificationKind::TaskComplete => self.enabled && self.task_complete,
+            NotificationKind::ToolError => self.enabled && self.tool_error,
+            NotificationKind::BackendCrashed => self.enabled && self.backend_crashed,
+            NotificationKind::ScheduledJob => self.enabled && self.scheduled_job,
+            NotificationKind::InboxFile => self.enabled && self.inbox_file,
+            NotificationKind::Budget => self.enabled && self.budget,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationAction {
+    /// Returned in `notification://action`; use "default" for a click on the body.
+    pub id: String,
+    pub label: String,
+}
+
+impl NotificationAction {
+    fn new(id: &str, label: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            label: label.to_string(),
+        }
+    }
+}
+
+events::payload! {
+    /// A notification action the shell left to the webview.
+    pub struct NotificationActionEvent {
+        /// The OS notification id, where the platform reports one.
+        pub id: Option<u32>,
+        pub action: String,
+        pub session_id: Option<String>,
+    }
+}
+
+events::payload! {
+    /// Sent to the main window on `notification://navigate` when a notification about
+    /// a conversation is acted on.
+    pub struct NavigateEvent {
+        session_id: String,
+        action: String,
+    }
+}
+
+/// The most recent notification shown, for the tray quick-reply popup.
+#[derive(Debug, Clone, Serialize)]
+pub struct LatestNotification {
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    /// Conversation a quick reply goes to; `None` starts a new one.
+    pub session_id: Option<String>,
+    pub shown_at: String,
+}
+
+#[derive(Default)]
+pub struct RecentNotification(Mutex<Option<LatestNotification>>);
+
+/// Do-not-disturb window in local time, as "HH:MM". `start > end` wraps past midnight.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+impl QuietHours {
+    fn validate(&self) -> Result<(), String> {
+        parse_time(&self.start)?;
+        parse_time(&self.end)?;
+        Ok(())
+    }
+
+    fn contains(&self, now: NaiveTime) -> bool {
+        let (Ok(start), Ok(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        if start <= end {
+            now >= start && now < end
+        } else {
+            now >= start || now < end
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid time '{value}'; expected HH:MM."))
+}
+
+pub fn current_settings<R: Runtime>(app: &AppHandle<R>) -> NotificationSettings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().notifications)
+        .unwrap_or_default()
+}
+
+/// Whether the user's toggles and quiet hours let a `kind` notification through now.
+pub fn allowed(settings: &NotificationSettings, kind: NotificationKind) -> bool {
+    let in_quiet_hours = settings
+        .quiet_hours
+        .as_ref()
+        .is_some_and(|quiet| quiet.contains(Local::now().time()));
+    settings.allows(kind) && (kind == NotificationKind::Test || !in_quiet_hours)
+}
+
+pub fn remember<R: Runtime>(
+    app: &AppHandle<R>,
+    kind: NotificationKind,
+    title: &str,
+    body: &str,
+    session_id: Option<String>,
+) {
+    let Some(recent) = app.try_state::<RecentNotification>() else {
+        return;
+    };
+    if let Ok(mut latest) = recent.0.lock() {
+        *latest = Some(LatestNotification {
+            kind,
+            title: title.to_string(),
+            body: body.to_string(),
+            session_id,
+            shown_at: Local::now().to_rfc3339(),
+        });
+    };
+}
+
+pub fn latest<R: Runtime>(app: &AppHandle<R>) -> Option<LatestNotification> {
+    app.try_state::<RecentNotification>()?
+        .0
+        .lock()
+        .ok()?
+        .clone()
+}
+
+/// Buttons offered on a notification about `session_id`.
+pub fn conversation_actions(session_id: Option<&str>) -> Vec<NotificationAction> {
+    if session_id.is_none() {
+        return Vec::new();
+    }
+    vec![
+        NotificationAction::new(ACTION_REPLY, "Reply"),
+        NotificationAction::new(ACTION_VIEW_RESULT, "View result"),
+    ]
+}
+
+/// Runs what a notification click or button asks for: brings the main window
+/// forward and, when the notification is about a conversation, tells it which one
+/// to open. Returns `false` for actions the frontend has to handle itself.
+pub fn handle_action<R: Runtime>(
+    app: &AppHandle<R>,
+    action: &str,
+    session_id: Option<&str>,
+) -> bool {
+    if ![ACTION_DEFAULT, ACTION_REPLY, ACTION_VIEW_RESULT].contains(&action) {
+        return false;
+    }
+    main_window::reveal(app);
+    if let Some(session_id) = session_id {
+        let action = if action == ACTION_DEFAULT {
+            ACTION_VIEW_RESULT
+        } else {
+            action
+        };
+        events::notification_navigate(
+            app,
+            main_window::LABEL,
+            &NavigateEvent {
+                session_id: session_id.to_string(),
+                action: action.to_string(),
+            },
+        );
+    }
+    true
+}
+
+/// Sets the identity toasts are shown under, the same way the notification plugin
+/// does, so both paths group together.
+fn prepare<R: Runtime>(app: &AppHandle<R>, notification: &mut notify_rust::Notification) {
+    #[cfg(windows)]
+    {
+        // Unpackaged dev builds have no registered AppUserModelID to show under.
+        let installed = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()))
+            .is_some_and(|dir| !dir.ends_with("target/debug") && !dir.ends_with("target/release"));
+        if installed {
+            notification.app_id(&app.config().identifier);
+        }
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let _ = notification;
+        let _ = notify_rust::set_application(if tauri::is_dev() {
+            "com.apple.Terminal"
+        } else {
+            &app.config().identifier
+        });
+    }
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = app;
+        notification.auto_icon();
+    }
+}
+
+/// Shows a notification with `actions` as buttons and handles the response on a
+/// background thread. Callers check preferences first.
+fn show<R: Runtime>(
+    app: &AppHandle<R>,
+    settings: &NotificationSettings,
+    title: &str,
+    body: &str,
+    session_id: Option<String>,
+    actions: Vec<NotificationAction>,
+) -> Result<(), String> {
+    let mut notification = notify_rust::Notification::new();
+    notification.summary(title).body(body);
+    if let Some(sound) = settings.sound.as_deref().filter(|sound| !sound.is_empty()) {
+        notification.sound_name(sound);
+    }
+    for action in &actions {
+        notification.action(&action.id, &action.label);
+    }
+    prepare(app, &mut notification);
+    let app = app.clone();
+    thread::spawn(move || {
+        let handle = match notification.show() {
+            Ok(handle) => handle,
+            Err(err) => {
+                return tracing::warn!("[Notifications] Failed to show notification: {err}")
+            }
+        };
+        handle.wait_for_action(|response| {
+            // macOS reports the button label rather than its id.
+            let action = actions
+                .iter()
+                .find(|action| action.id == response || action.label == response)
+                .map_or(response, |action| action.id.as_str());
+            if action == "__closed" {
+                return;
+            }
+            if !handle_action(&app, action, session_id.as_deref()) {
+                events::notification_action(
+                    &app,
+                    &NotificationActionEvent {
+                        id: None,
+                        action: action.to_string(),
+                        session_id: session_id.clone(),
+                    },
+                );
+            }
+        });
+    });
+    Ok(())
+}
+
+/// Shows a native notification unless the user's preferences suppress it.
+/// Returns whether the notification was actually shown.
+pub fn notify<R: Runtime>(
+    app: &AppHandle<R>,
+    kind: NotificationKind,
+    title: &str,
+    body: &str,
+) -> Result<bool, String> {
+    notify_for(app, kind, title, body, None)
+}
+
+/// Like `notify`, remembering which conversation the notification is about and
+/// offering to reply to it or view the result.
+pub fn notify_for<R: Runtime>(
+    app: &AppHandle<R>,
+    kind: NotificationKind,
+    title: &str,
+    body: &str,
+    session_id: Option<String>,
+) -> Result<bool, String> {
+    let actions = conversation_actions(session_id.as_deref());
+    notify_with_actions(app, kind, title, body, session_id, actions)
+}
+
+/// Like `notify_for` with explicit buttons. Clicks on the body and the built-in
+/// actions are handled here; any other action is emitted on `notification://action`.
+pub fn notify_with_actions<R: Runtime>(
+    app: &AppHandle<R>,
+    kind: NotificationKind,
+    title: &str,
+    body: &str,
+    session_id: Option<String>,
+    actions: Vec<NotificationAction>,
+) -> Result<bool, String> {
+    let settings = current_settings(app);
+    if !allowed(&settings, kind) {
+        return Ok(false);
+    }
+    show(app, &settings, title, body, session_id.clone(), actions)?;
+    remember(app, kind, title, body, session_id);
+    Ok(true)
+}
+
+/// What has been seen of a run streaming through the `chat://` bridge.
+#[derive(Debug, Default)]
+struct RunProgress {
+    session_id: Option<String>,
+    /// One tool error notification per run is enough to bring the user back.
+    tool_error_shown: bool,
+}
+
+/// Runs in flight on the chat bridge, keyed by request id.
+#[derive(Default)]
+pub struct RunNotices(Mutex<HashMap<String, RunProgress>>);
+
+#[derive(Debug, Deserialize)]
+struct RunDelta {
+    request_id: String,
+    data: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct RunDone {
+    request_id: String,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BackendCrash {
+    reason: String,
+    will_restart: bool,
+}
+
+/// Automatic notifications are only worth it while the user is looking elsewhere.
+fn window_in_background<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let Some(window) = app.get_webview_window(main_window::LABEL) else {
+        return true;
+    };
+    !window.is_visible().unwrap_or(false)
+        || window.is_minimized().unwrap_or(false)
+        || !window.is_focused().unwrap_or(false)
+}
+
+fn notify_in_background<R: Runtime>(
+    app: &AppHandle<R>,
+    kind: NotificationKind,
+    title: &str,
+    body: &str,
+    session_id: Option<String>,
+) {
+    if !window_in_background(app) {
+        return;
+    }
+    if let Err(err) = notify_for(app, kind, title, body, session_id) {
+        tracing::warn!("[Notifications] {err}");
+    }
+}
+
+/// A failed tool shows up as an observation carrying the error, or as an error step.
+fn tool_error(data: &Value) -> Option<String> {
+    let metadata = data.get("metadata");
+    let tool = metadata
+        .and_then(|metadata| {
+            metadata
+                .get("tool_display")
+                .or_else(|| metadata.get("tool"))
+        })
+        .and_then(Value::as_str);
+    match data.get("step_type").and_then(Value::as_str)? {
+        "observation" => {
+            let error = metadata?.get("error")?.as_str()?;
+            Some(match tool {
+                Some(tool) => format!("{tool}: {error}"),
+                None => error.to_string(),
+            })
+        }
+        "error" => data
+            .get("content")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+fn on_run_delta<R: Runtime>(app: &AppHandle<R>, delta: RunDelta) {
+    let Some(notices) = app.try_state::<RunNotices>() else {
+        return;
+    };
+    let (session_id, error) = {
+        let Ok(mut runs) = notices.0.lock() else {
+            return;
+        };
+        let run = runs.entry(delta.request_id).or_default();
+        if let Some(session_id) = delta.data.get("session_id").and_then(Value::as_str) {
+            run.session_id = Some(session_id.to_string());
+        }
+        let error = tool_error(&delta.data).filter(|_| !run.tool_error_shown);
+        if error.is_some() {
+            run.tool_error_shown = true;
+        }
+        (run.session_id.clone(), error)
+    };
+    if let Some(error) = error {
+        notify_in_background(
+            app,
+            NotificationKind::ToolError,
+            "A tool failed during the agent run",
+            &error,
+            session_id,
+        );
+    }
+}
+
+fn on_run_done<R: Runtime>(app: &AppHandle<R>, done: RunDone) {
+    let Some(notices) = app.try_state::<RunNotices>() else {
+        return;
+    };
+    let run = notices
+        .0
+        .lock()
+        .ok()
+        .and_then(|mut runs| runs.remove(&done.request_id))
+        .unwrap_or_default();
+    // Stopping a run is the user's own doing.
+    if done.cancelled {
+        return;
+    }
+    let (title, body) = match &done.error {
+        Some(error) => ("The agent run failed", error.as_str()),
+        None => ("The agent run finished", "The result is ready to view."),
+    };
+    notify_in_background(
+        app,
+        NotificationKind::TaskComplete,
+        title,
+        body,
+        run.session_id,
+    );
+}
+
+/// Turns backend events into notifications: runs finishing or hitting a tool error
+/// on the `chat://` bridge, and the backend crashing. Run notifications are skipped
+/// while the main window has focus.
+pub fn watch<R: Runtime>(app: &AppHandle<R>) {
+    let handle = app.clone();
+    app.listen_any("chat://delta", move |event| {
+        if let Ok(delta) = serde_json::from_str(event.payload()) {
+            on_run_delta(&handle, delta);
+        }
+    });
+    let handle = app.clone();
+    app.listen_any("chat://done", move |event| {
+        if let Ok(done) = serde_json::from_str(event.payload()) {
+            on_run_done(&handle, done);
+        }
+    });
+    let handle = app.clone();
+    app.listen_any("backend://crashed", move |event| {
+        let Ok(crash) = serde_json::from_str::<BackendCrash>(event.payload()) else {
+            return;
+        };
+        let body = locale::t_with(
+            &handle,
+            if crash.will_restart {
+                "notification.backend_restarting"
+            } else {
+                "notification.backend_not_restarting"
+            },
+            &[("reason", &crash.reason)],
+        );
+        if let Err(err) = notify(
+            &handle,
+            NotificationKind::BackendCrashed,
+            &locale::t(&handle, "notification.backend_stopped"),
+            &body,
+        ) {
+            tracing::warn!("[Notifications] {err}");
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_notification_settings(store: tauri::State<SettingsStore>) -> NotificationSettings {
+    store.get().notifications
+}
+
+#[tauri::command]
+pub fn set_notification_settings(
+    store: tauri::State<SettingsStore>,
+    settings: NotificationSettings,
+) -> Result<NotificationSettings, AppError> {
+    if let Some(quiet) = &settings.quiet_hours {
+        quiet.validate().map_err(AppError::invalid_input)?;
+    }
+    store
+        .update(|current| current.notifications = settings)
+        .map(|updated| updated.notifications)
+}
+
+#[tauri::command]
+pub fn get_latest_notification(app: AppHandle) -> Option<LatestNotification> {
+    latest(&app)
+}
+
+/// Shows a native notification from the webview, subject to the same mutes and
+/// quiet hours as the shell's own. Returns whether it was shown.
+#[tauri::command]
+pub fn notify_user(
+    app: AppHandle,
+    title: String,
+    body: String,
+    kind: NotificationKind,
+    session_id: Option<String>,
+) -> Result<bool, AppError> {
+    if title.trim().is_empty() {
+        return Err(AppError::invalid_input("A notification needs a title."));
+    }
+    notify_for(&app, kind, &title, &body, session_id).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn test_notification(app: AppHandle) -> Result<bool, AppError> {
+    notify(
+        &app,
+        NotificationKind::Test,
+        &locale::t(&app, "notification.test_title"),
+        &locale::t(&app, "notification.test_body"),
+    )
+    .map_err(AppError::from)
+}
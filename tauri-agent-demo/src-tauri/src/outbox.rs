@@ -0,0 +1,300 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{atomic_file, error::AppError, events, health, proxy, rpc, watchdog, BackendState};
+
+const REPLAY_INTERVAL: Duration = Duration::from_secs(5);
+/// Replays run a whole agent turn before the backend answers.
+const REPLAY_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const CHAT_PATH: &str = "/chat/agent/stream";
+const PREVIEW_CHARS: usize = 80;
+
+/// A chat request held until the backend can take it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedRequest {
+    id: String,
+    queued_at: String,
+    session_id: Option<String>,
+    /// Start of the message, for listing the queue without the full payload.
+    preview: String,
+    attempts: u32,
+    last_error: Option<String>,
+    /// The `ChatRequest` body exactly as the webview built it.
+    request: Value,
+}
+
+events::payload_enum! {
+    pub enum OutboxStatus {
+        Queued,
+        Sending,
+        /// Not delivered this time; stays at the head of the queue.
+        Retrying,
+        Sent,
+        /// The backend refused it; dropped from the queue.
+        Failed,
+        Cancelled,
+    }
+}
+
+events::payload! {
+    pub struct OutboxStatusEvent {
+        id: String,
+        session_id: Option<String>,
+        status: OutboxStatus,
+        error: Option<String>,
+        /// Requests still queued, this one included unless it left the queue.
+        remaining: usize,
+    }
+}
+
+/// Durable FIFO of chat requests made while the backend was unreachable.
+pub struct Outbox {
+    path: PathBuf,
+    queue: Mutex<VecDeque<QueuedRequest>>,
+    next_id: AtomicU64,
+}
+
+impl Outbox {
+    pub fn load(path: PathBuf) -> Self {
+        let queue: VecDeque<QueuedRequest> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        if !queue.is_empty() {
+            tracing::info!(
+                "[Outbox] {} queued request(s) waiting to replay.",
+                queue.len()
+            );
+        }
+        Self {
+            path,
+            queue: Mutex::new(queue),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    fn save(&self, queue: &VecDeque<QueuedRequest>) -> Result<(), String> {
+        let raw = serde_json::to_string(queue)
+            .map_err(|err| format!("Failed to serialize the outbox: {err}"))?;
+        atomic_file::write(&self.path, raw)
+            .map_err(|err| format!("Failed to save the outbox: {err}"))
+    }
+
+    fn front(&self) -> Option<QueuedRequest> {
+        self.queue.lock().ok()?.front().cloned()
+    }
+
+    fn len(&self) -> usize {
+        self.queue.lock().map(|queue| queue.len()).unwrap_or(0)
+    }
+
+    /// Applies `change` to the queue and persists it before returning.
+    fn update<T>(
+        &self,
+        change: impl FnOnce(&mut VecDeque<QueuedRequest>) -> T,
+    ) -> Result<(T, usize), String> {
+        let mut queue = self
+            .queue
+            .lock()
+            .map_err(|_| "Outbox is unavailable.".to_string())?;
+        let result = change(&mut queue);
+        self.save(&queue)?;
+        Ok((result, queue.len()))
+    }
+}
+
+fn emit<R: Runtime>(
+    app: &AppHandle<R>,
+    entry: &QueuedRequest,
+    status: OutboxStatus,
+    remaining: usize,
+    error: Option<&str>,
+) {
+    events::outbox_status(
+        app,
+        &OutboxStatusEvent {
+            id: entry.id.clone(),
+            session_id: entry.session_id.clone(),
+            status,
+            error: error.map(str::to_string),
+            remaining,
+        },
+    );
+}
+
+/// Persists `request` at the tail of the outbox; it is sent once the backend is healthy.
+pub fn enqueue<R: Runtime>(app: &AppHandle<R>, request: Value) -> Result<QueuedRequest, String> {
+    let outbox = app
+        .try_state::<Outbox>()
+        .ok_or_else(|| "Outbox is unavailable.".to_string())?;
+    let message = request
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let now = Local::now();
+    let entry = QueuedRequest {
+        id: format!(
+            "{}-{}",
+            now.timestamp_millis(),
+            outbox.next_id.fetch_add(1, Ordering::SeqCst)
+        ),
+        queued_at: now.to_rfc3339(),
+        session_id: request
+            .get("session_id")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        preview: message.chars().take(PREVIEW_CHARS).collect(),
+        attempts: 0,
+        last_error: None,
+        request,
+    };
+    let (_, remaining) = outbox.update(|queue| queue.push_back(entry.clone()))?;
+    tracing::info!("[Outbox] Queued {} ({remaining} waiting).", entry.id);
+    emit(app, &entry, OutboxStatus::Queued, remaining, None);
+    Ok(entry)
+}
+
+/// `Ok` carries the backend's definitive answer; `Err` means it never got there.
+async fn deliver<R: Runtime>(app: &AppHandle<R>, request: &Value) -> Result<u16, String> {
+    let session_id = request
+        .get("session_id")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let _run = watchdog::track(app, CHAT_PATH, None, session_id);
+    if rpc::is_attached(app) {
+        let handle = app.clone();
+        let body = request.clone();
+        let (status, _) = tauri::async_runtime::spawn_blocking(move || {
+            rpc::post(&handle, CHAT_PATH, &body, REPLAY_TIMEOUT)
+        })
+        .await
+        .map_err(|err| format!("Replay task failed: {err}"))??;
+        return Ok(status);
+    }
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| "Backend is not configured yet.".to_string())?;
+    let response = proxy::client(app)
+        .post(format!("{base_url}{CHAT_PATH}"))
+        .timeout(REPLAY_TIMEOUT)
+        .json(request)
+        .send()
+        .await
+        .map_err(|err| format!("Backend is unreachable: {err}"))?;
+    let status = response.status().as_u16();
+    // The backend saves the turn as it streams; reading to the end lets it finish.
+    if let Err(err) = response.bytes().await {
+        tracing::warn!("[Outbox] Replay stream ended early: {err}");
+    }
+    Ok(status)
+}
+
+/// Sends the head of the queue. Returns whether the loop should try the next one now.
+async fn replay_one<R: Runtime>(app: &AppHandle<R>, outbox: &Outbox) -> Result<bool, String> {
+    let Some(entry) = outbox.front() else {
+        return Ok(false);
+    };
+    emit(app, &entry, OutboxStatus::Sending, outbox.len(), None);
+    let outcome = match deliver(app, &entry.request).await {
+        Ok(status) if status < 300 => Ok(()),
+        Ok(status) if status < 500 => Err((false, format!("Backend refused it with {status}."))),
+        Ok(status) => Err((true, format!("Backend answered {status}."))),
+        Err(err) => Err((true, err)),
+    };
+    let id = entry.id.clone();
+    match outcome {
+        Ok(()) => {
+            let (_, remaining) = outbox.update(|queue| queue.retain(|queued| queued.id != id))?;
+            tracing::info!("[Outbox] Replayed {id}.");
+            emit(app, &entry, OutboxStatus::Sent, remaining, None);
+            Ok(true)
+        }
+        Err((true, err)) => {
+            let (_, remaining) = outbox.update(|queue| {
+                if let Some(queued) = queue.iter_mut().find(|queued| queued.id == id) {
+                    queued.attempts += 1;
+                    queued.last_error = Some(err.clone());
+                }
+            })?;
+            emit(app, &entry, OutboxStatus::Retrying, remaining, Some(&err));
+            Ok(false)
+        }
+        Err((false, err)) => {
+            let (_, remaining) = outbox.update(|queue| queue.retain(|queued| queued.id != id))?;
+            tracing::warn!("[Outbox] Dropped {id}: {err}");
+            emit(app, &entry, OutboxStatus::Failed, remaining, Some(&err));
+            Ok(true)
+        }
+    }
+}
+
+/// Replays queued requests in order whenever the backend reports healthy.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(REPLAY_INTERVAL).await;
+            let Some(outbox) = app.try_state::<Outbox>() else {
+                continue;
+            };
+            if outbox.front().is_none() || !health::probe(&app).await {
+                continue;
+            }
+            loop {
+                match replay_one(&app, &outbox).await {
+                    Ok(true) => continue,
+                    Ok(false) => break,
+                    Err(err) => {
+                        tracing::warn!("[Outbox] {err}");
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Queues a chat request the webview could not deliver; replayed once the backend is back.
+#[tauri::command]
+pub fn queue_chat_request(app: AppHandle, request: Value) -> Result<QueuedRequest, AppError> {
+    if !request.get("message").is_some_and(Value::is_string) {
+        return Err(AppError::invalid_input("A chat request needs a message."));
+    }
+    Ok(enqueue(&app, request)?)
+}
+
+/// Requests still waiting, oldest first.
+#[tauri::command]
+pub fn get_outbox(outbox: tauri::State<Outbox>) -> Result<Vec<QueuedRequest>, AppError> {
+    let queue = outbox
+        .queue
+        .lock()
+        .map_err(|_| AppError::unavailable("Outbox is unavailable."))?;
+    Ok(queue.iter().cloned().collect())
+}
+
+#[tauri::command]
+pub fn cancel_queued_request(app: AppHandle, id: String) -> Result<(), AppError> {
+    let outbox = app.state::<Outbox>();
+    let (removed, remaining) = outbox.update(|queue| {
+        let position = queue.iter().position(|queued| queued.id == id)?;
+        queue.remove(position)
+    })?;
+    let entry = removed.ok_or_else(|| AppError::not_found("That request is no longer queued."))?;
+    emit(&app, &entry, OutboxStatus::Cancelled, remaining, None);
+    Ok(())
+}
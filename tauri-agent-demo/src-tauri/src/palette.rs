@@ -0,0 +1,286 @@
+use std::path::Path;
+
+use chrono::Local;
+use rusqlite::OpenFlags;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::{
+    archive,
+    dialogs::{self, DialogPurpose},
+    encryption,
+    error::AppError,
+    kiosk, workspace,
+};
+
+const MAX_ITEMS: usize = 50;
+const RECENT_CONVERSATIONS: u32 = 200;
+const RECENT_WORKSPACES: u32 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaletteAction {
+    RestartBackend,
+    OpenDataDir,
+    ExportDatabase,
+}
+
+impl PaletteAction {
+    const ALL: [PaletteAction; 3] = [
+        PaletteAction::RestartBackend,
+        PaletteAction::OpenDataDir,
+        PaletteAction::ExportDatabase,
+    ];
+
+    fn id(self) -> &'static str {
+        match self {
+            PaletteAction::RestartBackend => "restart_backend",
+            PaletteAction::OpenDataDir => "open_data_dir",
+            PaletteAction::ExportDatabase => "export_database",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            PaletteAction::RestartBackend => "Restart backend",
+            PaletteAction::OpenDataDir => "Open data folder",
+            PaletteAction::ExportDatabase => "Export database…",
+        }
+    }
+
+    fn subtitle(self) -> &'static str {
+        match self {
+            PaletteAction::RestartBackend => "Stop and relaunch the Python sidecar",
+            PaletteAction::OpenDataDir => "Show settings, attachments and the chat database",
+            PaletteAction::ExportDatabase => "Save a consistent copy of chat_app.db",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaletteKind {
+    Action,
+    Conversation,
+    Workspace,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PaletteItem {
+    id: String,
+    kind: PaletteKind,
+    title: String,
+    subtitle: Option<String>,
+    /// The action for `run_palette_action`, the session id, or the workspace path.
+    target: String,
+    score: u32,
+}
+
+/// Ranks `text` against `query`: prefix, word start, substring, then in-order letters.
+fn match_score(query: &str, text: &str) -> Option<u32> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let text = text.to_lowercase();
+    if text.starts_with(query) {
+        return Some(100);
+    }
+    if let Some(index) = text.find(query) {
+        let word_start = text[..index]
+            .chars()
+            .next_back()
+            .is_some_and(|before| !before.is_alphanumeric());
+        return Some(if word_start { 80 } else { 60 });
+    }
+    let mut remaining = query.chars().peekable();
+    for ch in text.chars() {
+        if remaining.peek() == Some(&ch) {
+            remaining.next();
+        }
+    }
+    remaining.peek().is_none().then_some(20)
+}
+
+fn best_score(query: &str, fields: &[&str]) -> Option<u32> {
+    fields
+        .iter()
+        .filter_map(|field| match_score(query, field))
+        .max()
+}
+
+struct Conversation {
+    id: String,
+    title: String,
+    work_path: Option<String>,
+}
+
+fn load_recent(db_path: &Path) -> Result<(Vec<Conversation>, Vec<String>), String> {
+    if !db_path.exists() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    let conn = encryption::open(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|err| format!("Failed to open chat database: {err}"))?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, work_path FROM chat_sessions
+             WHERE parent_session_id IS NULL
+             ORDER BY updated_at DESC LIMIT ?1",
+        )
+        .map_err(|err| format!("Failed to load conversations: {err}"))?;
+    let conversations = stmt
+        .query_map([RECENT_CONVERSATIONS], |row| {
+            Ok(Conversation {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                work_path: row.get(2)?,
+            })
+        })
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| format!("Failed to load conversations: {err}"))?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT work_path FROM chat_sessions
+             WHERE work_path IS NOT NULL AND work_path <> ''
+             GROUP BY work_path ORDER BY MAX(updated_at) DESC LIMIT ?1",
+        )
+        .map_err(|err| format!("Failed to load workspaces: {err}"))?;
+    let workspaces = stmt
+        .query_map([RECENT_WORKSPACES], |row| row.get(0))
+        .and_then(|rows| rows.collect::<Result<Vec<String>, _>>())
+        .map_err(|err| format!("Failed to load workspaces: {err}"))?;
+    Ok((conversations, workspaces))
+}
+
+fn rank(query: &str, db_path: &Path, kiosk: bool) -> Result<Vec<PaletteItem>, String> {
+    let query = query.trim().to_lowercase();
+    let (conversations, workspaces) = load_recent(db_path)?;
+    let mut items = Vec::new();
+
+    let actions: &[PaletteAction] = if kiosk { &[] } else { &PaletteAction::ALL };
+    for &action in actions {
+        if let Some(score) = best_score(&query, &[action.title(), action.subtitle()]) {
+            items.push(PaletteItem {
+                id: format!("action:{}", action.id()),
+                kind: PaletteKind::Action,
+                title: action.title().to_string(),
+                subtitle: Some(action.subtitle().to_string()),
+                target: action.id().to_string(),
+                score: score + 5,
+            });
+        }
+    }
+    let total = conversations.len() as u32;
+    for (index, conversation) in conversations.into_iter().enumerate() {
+        let fields = [
+            conversation.title.as_str(),
+            conversation.work_path.as_deref().unwrap_or(""),
+        ];
+        let Some(score) = best_score(&query, &fields) else {
+            continue;
+        };
+        // Newer conversations win ties, by up to 10 points.
+        let recency = (total - index as u32) * 10 / total.max(1);
+        items.push(PaletteItem {
+            id: format!("conversation:{}", conversation.id),
+            kind: PaletteKind::Conversation,
+            title: conversation.title,
+            subtitle: conversation.work_path,
+            target: conversation.id,
+            score: score + recency,
+        });
+    }
+    for path in workspaces {
+        let name = Path::new(&path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.clone());
+        let Some(score) = best_score(&query, &[&name, &path]) else {
+            continue;
+        };
+        items.push(PaletteItem {
+            id: format!("workspace:{path}"),
+            kind: PaletteKind::Workspace,
+            title: name,
+            subtitle: Some(path.clone()),
+            target: path,
+            score: score + 2,
+        });
+    }
+
+    items.sort_by_key(|item| std::cmp::Reverse(item.score));
+    items.truncate(MAX_ITEMS);
+    Ok(items)
+}
+
+fn export_database<R: Runtime>(
+    app: &AppHandle<R>,
+    db_path: &Path,
+) -> Result<Option<String>, String> {
+    let mut dialog = app.dialog().file();
+    if let Some(dir) = dialogs::default_dir(app, DialogPurpose::Exports) {
+        dialog = dialog.set_directory(dir);
+    }
+    let Some(destination) = dialog
+        .set_file_name(format!(
+            "chat_app-{}.db",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ))
+        .add_filter("SQLite database", &["db"])
+        .blocking_save_file()
+    else {
+        return Ok(None);
+    };
+    let destination = destination
+        .into_path()
+        .map_err(|err| format!("Invalid export path: {err}"))?;
+    dialogs::remember(app, DialogPurpose::Exports, &destination);
+    archive::export_database(db_path, &destination)?;
+    Ok(Some(destination.to_string_lossy().into_owned()))
+}
+
+/// Native actions, recent conversations and workspaces ranked for the Cmd+K palette.
+#[tauri::command]
+pub async fn get_palette_items(
+    app: AppHandle,
+    query: String,
+) -> Result<Vec<PaletteItem>, AppError> {
+    let db_path = workspace::db_path(&app)?;
+    let kiosk = kiosk::is_enabled(&app);
+    tauri::async_runtime::spawn_blocking(move || rank(&query, &db_path, kiosk))
+        .await
+        .map_err(|err| AppError::from(format!("Palette task failed: {err}")))?
+        .map_err(AppError::from)
+}
+
+/// Runs a palette action. Returns the exported file for `export_database`, or
+/// `None` when the action has no result or the user cancelled.
+#[tauri::command]
+pub async fn run_palette_action(
+    app: AppHandle,
+    action: PaletteAction,
+) -> Result<Option<String>, AppError> {
+    let app_data_dir = workspace::data_dir(&app)?;
+    match action {
+        PaletteAction::OpenDataDir => {
+            app.opener()
+                .open_path(app_data_dir.to_string_lossy(), None::<&str>)
+                .map_err(|err| AppError::from(format!("Failed to open data folder: {err}")))?;
+            Ok(None)
+        }
+        PaletteAction::RestartBackend => {
+            tauri::async_runtime::spawn_blocking(move || crate::restart_backend(&app))
+                .await
+                .map_err(|err| AppError::from(format!("Restart task failed: {err}")))??;
+            Ok(None)
+        }
+        PaletteAction::ExportDatabase => {
+            let db_path = workspace::db_path(&app)?;
+            tauri::async_runtime::spawn_blocking(move || export_database(&app, &db_path))
+                .await
+                .map_err(|err| AppError::from(format!("Export task failed: {err}")))?
+                .map_err(AppError::from)
+        }
+    }
+}
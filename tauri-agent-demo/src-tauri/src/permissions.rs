@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_notification::{NotificationExt, PermissionState};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionKind {
+    Microphone,
+    ScreenRecording,
+    Accessibility,
+    Notifications,
+}
+
+// Not every platform can report every status.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionStatus {
+    Granted,
+    Denied,
+    /// The OS has not asked the user yet.
+    NotDetermined,
+    /// Blocked by policy (parental controls, MDM); the user cannot change it.
+    Restricted,
+    /// This platform does not gate the capability.
+    NotRequired,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionReport {
+    kind: PermissionKind,
+    status: PermissionStatus,
+    /// Set when the user has to finish granting the permission in system settings.
+    settings_opened: bool,
+}
+
+fn notification_status<R: Runtime>(app: &AppHandle<R>) -> PermissionStatus {
+    match app.notification().permission_state() {
+        Ok(PermissionState::Granted) => PermissionStatus::Granted,
+        Ok(PermissionState::Denied) => PermissionStatus::Denied,
+        Ok(_) => PermissionStatus::NotDetermined,
+        Err(_) => PermissionStatus::Unknown,
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc2::{class, msg_send, runtime::AnyObject};
+
+    use super::{PermissionKind, PermissionStatus};
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGPreflightScreenCaptureAccess() -> bool;
+        fn CGRequestScreenCaptureAccess() -> bool;
+    }
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+    }
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        static AVMediaTypeAudio: *const AnyObject;
+    }
+
+    fn microphone() -> PermissionStatus {
+        // AVAuthorizationStatus: 0 not determined, 1 restricted, 2 denied, 3 authorized.
+        let status: isize = unsafe {
+            msg_send![class!(AVCaptureDevice), authorizationStatusForMediaType: AVMediaTypeAudio]
+        };
+        match status {
+            0 => PermissionStatus::NotDetermined,
+            1 => PermissionStatus::Restricted,
+            2 => PermissionStatus::Denied,
+            3 => PermissionStatus::Granted,
+            _ => PermissionStatus::Unknown,
+        }
+    }
+
+    pub fn status(kind: PermissionKind) -> PermissionStatus {
+        match kind {
+            PermissionKind::Microphone => microphone(),
+            PermissionKind::ScreenRecording if unsafe { CGPreflightScreenCaptureAccess() } => {
+                PermissionStatus::Granted
+            }
+            PermissionKind::Accessibility if unsafe { AXIsProcessTrusted() } => {
+                PermissionStatus::Granted
+            }
+            // macOS does not reveal whether these were refused or never asked.
+            PermissionKind::ScreenRecording | PermissionKind::Accessibility => {
+                PermissionStatus::NotDetermined
+            }
+            PermissionKind::Notifications => PermissionStatus::Unknown,
+        }
+    }
+
+    /// Triggers the native prompt where one exists; returns the settings pane to open
+    /// when the user has to flip the switch manually.
+    pub fn request(kind: PermissionKind) -> Option<&'static str> {
+        match kind {
+            PermissionKind::Microphone if microphone() == PermissionStatus::NotDetermined => {
+                // Opening an input stream is what makes macOS show the microphone prompt.
+                crate::audio::probe_input_device();
+                None
+            }
+            PermissionKind::Microphone => {
+                Some("x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone")
+            }
+            PermissionKind::ScreenRecording => {
+                if unsafe { CGRequestScreenCaptureAccess() } {
+                    None
+                } else {
+                    Some("x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture")
+                }
+            }
+            PermissionKind::Accessibility => Some(
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility",
+            ),
+            PermissionKind::Notifications => None,
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::process::Command;
+
+    use super::{PermissionKind, PermissionStatus};
+
+    const MICROPHONE_CONSENT_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone";
+
+    fn microphone() -> PermissionStatus {
+        let Ok(output) = Command::new("reg")
+            .args(["query", MICROPHONE_CONSENT_KEY, "/v", "Value"])
+            .output()
+        else {
+            return PermissionStatus::Unknown;
+        };
+        let text = String::from_utf8_lossy(&output.stdout);
+        if text.contains("Allow") {
+            PermissionStatus::Granted
+        } else if text.contains("Deny") {
+            PermissionStatus::Denied
+        } else {
+            PermissionStatus::Unknown
+        }
+    }
+
+    pub fn status(kind: PermissionKind) -> PermissionStatus {
+        match kind {
+            PermissionKind::Microphone => microphone(),
+            PermissionKind::ScreenRecording | PermissionKind::Accessibility => {
+                PermissionStatus::NotRequired
+            }
+            PermissionKind::Notifications => PermissionStatus::Unknown,
+        }
+    }
+
+    pub fn request(kind: PermissionKind) -> Option<&'static str> {
+        match kind {
+            PermissionKind::Microphone => Some("ms-settings:privacy-microphone"),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::{PermissionKind, PermissionStatus};
+
+    pub fn status(_kind: PermissionKind) -> PermissionStatus {
+        PermissionStatus::NotRequired
+    }
+
+    pub fn request(_kind: PermissionKind) -> Option<&'static str> {
+        None
+    }
+}
+
+pub fn status<R: Runtime>(app: &AppHandle<R>, kind: PermissionKind) -> PermissionStatus {
+    match kind {
+        PermissionKind::Notifications => notification_status(app),
+        other => platform::status(other),
+    }
+}
+
+#[tauri::command]
+pub fn get_permission_status(app: AppHandle, kind: PermissionKind) -> PermissionReport {
+    PermissionReport {
+        kind,
+        status: status(&app, kind),
+        settings_opened: false,
+    }
+}
+
+/// Asks the OS for `kind`, showing the native prompt when possible and otherwise
+/// opening the matching privacy pane in system settings.
+#[tauri::command]
+pub fn request_permission(
+    app: AppHandle,
+    kind: PermissionKind,
+) -> Result<PermissionReport, AppError> {
+    let mut settings_opened = false;
+    if kind == PermissionKind::Notifications {
+        app.notification().request_permission().map_err(|err| {
+            AppError::unavailable("Failed to request notification permission.").with_details(err)
+        })?;
+    } else if status(&app, kind) != PermissionStatus::Granted {
+        if let Some(pane) = platform::request(kind) {
+            app.opener().open_url(pane, None::<&str>).map_err(|err| {
+                AppError::unavailable("Failed to open system settings.").with_details(err)
+            })?;
+            settings_opened = true;
+        }
+    }
+    Ok(PermissionReport {
+        kind,
+        status: status(&app, kind),
+        settings_opened,
+    })
+}
@@ -0,0 +1,472 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{Cursor, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, Runtime};
+use zip::ZipArchive;
+
+use crate::{
+    atomic_file, error::AppError, proxy, settings::SettingsStore, workspace, BackendState,
+};
+
+const MANIFEST_FILE: &str = "plugin.json";
+const TOOLS_CONFIG_FILE: &str = "tools_config.json";
+const MAX_PACKAGE_BYTES: usize = 200 * 1024 * 1024;
+/// Cap on everything a package extracts to, so a small zip cannot fill the disk.
+const MAX_UNPACKED_BYTES: u64 = 1024 * 1024 * 1024;
+const MAX_ID_LEN: usize = 64;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginSettings {
+    /// Base64 Ed25519 public keys whose signatures `install_plugin` accepts.
+    pub trusted_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginKind {
+    Native,
+    Wasm,
+}
+
+/// `plugin.json` at the root of a package.
+#[derive(Debug, Clone, Deserialize)]
+struct PluginManifest {
+    id: String,
+    name: String,
+    version: String,
+    kind: PluginKind,
+    /// Executable or `.wasm` module, relative to the package root.
+    entry: String,
+    #[serde(default)]
+    tools: Vec<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// A plugin as registered under `plugins` in `tools_config.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    id: String,
+    name: String,
+    version: String,
+    kind: PluginKind,
+    path: PathBuf,
+    tools: Vec<String>,
+    description: Option<String>,
+    enabled: bool,
+    /// SHA-256 of the key that signed the package, hex-encoded.
+    signer: String,
+}
+
+fn validate_id(id: &str) -> Result<(), String> {
+    let valid = !id.is_empty()
+        && id.len() <= MAX_ID_LEN
+        && id
+            .chars()
+            .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || matches!(ch, '-' | '_'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Plugin ids must be 1-{MAX_ID_LEN} lowercase letters, digits, '-' or '_'."
+        ))
+    }
+}
+
+fn plugins_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = crate::resolve_app_data_dir(app)?.join("plugins");
+    fs::create_dir_all(&dir).map_err(|err| format!("Failed to create plugins directory: {err}"))?;
+    Ok(dir)
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("http://")
+}
+
+/// Reads a package or signature, giving up as soon as it passes `MAX_PACKAGE_BYTES`.
+async fn fetch(source: &str) -> Result<Vec<u8>, String> {
+    let too_large = || {
+        format!(
+            "{source} is larger than {} MB.",
+            MAX_PACKAGE_BYTES / (1024 * 1024)
+        )
+    };
+    if !is_url(source) {
+        let file =
+            fs::File::open(source).map_err(|err| format!("Failed to read {source}: {err}"))?;
+        let mut bytes = Vec::new();
+        file.take(MAX_PACKAGE_BYTES as u64 + 1)
+            .read_to_end(&mut bytes)
+            .map_err(|err| format!("Failed to read {source}: {err}"))?;
+        if bytes.len() > MAX_PACKAGE_BYTES {
+            return Err(too_large());
+        }
+        return Ok(bytes);
+    }
+    let mut response = reqwest::get(source)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| format!("Failed to download {source}: {err}"))?;
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_PACKAGE_BYTES as u64)
+    {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|err| format!("Failed to download {source}: {err}"))?
+    {
+        if bytes.len() + chunk.len() > MAX_PACKAGE_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// Checks the detached signature against every trusted key and returns the signer.
+fn verify(package: &[u8], signature: &[u8], trusted_keys: &[String]) -> Result<String, String> {
+    if trusted_keys.is_empty() {
+        return Err("No trusted plugin keys are configured.".to_string());
+    }
+    // Accept both raw 64-byte signatures and base64 text files.
+    let signature = if signature.len() == 64 {
+        signature.to_vec()
+    } else {
+        let text = String::from_utf8_lossy(signature);
+        STANDARD
+            .decode(text.trim())
+            .map_err(|err| format!("Invalid plugin signature: {err}"))?
+    };
+    for key in trusted_keys {
+        let Ok(public_key) = STANDARD.decode(key.trim()) else {
+            tracing::warn!("[Plugins] Ignoring malformed trusted key {key}");
+            continue;
+        };
+        if UnparsedPublicKey::new(&ED25519, &public_key)
+            .verify(package, &signature)
+            .is_ok()
+        {
+            let digest = Sha256::digest(&public_key);
+            return Ok(digest.iter().map(|byte| format!("{byte:02x}")).collect());
+        }
+    }
+    Err("The plugin signature does not match any trusted key.".to_string())
+}
+
+fn read_manifest(archive: &mut ZipArchive<Cursor<&[u8]>>) -> Result<PluginManifest, String> {
+    let file = archive
+        .by_name(MANIFEST_FILE)
+        .map_err(|_| format!("The package has no {MANIFEST_FILE}."))?;
+    let manifest: PluginManifest =
+        serde_json::from_reader(file).map_err(|err| format!("Invalid {MANIFEST_FILE}: {err}"))?;
+    validate_id(&manifest.id)?;
+    let entry_is_relative = Path::new(&manifest.entry)
+        .components()
+        .all(|part| matches!(part, Component::Normal(_)));
+    if manifest.entry.is_empty() || !entry_is_relative {
+        return Err("`entry` must be a path inside the package.".to_string());
+    }
+    if manifest.kind == PluginKind::Wasm && !manifest.entry.ends_with(".wasm") {
+        return Err("WASM plugins must point `entry` at a .wasm module.".to_string());
+    }
+    Ok(manifest)
+}
+
+/// Extracts into a staging folder beside `target` and swaps it in once `entry` is
+/// there, so a broken package leaves the installed version alone.
+fn unpack(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    target: &Path,
+    entry: &str,
+) -> Result<(), String> {
+    let staging = target.with_file_name(format!(
+        ".{}.partial",
+        target.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    let _ = fs::remove_dir_all(&staging);
+    let result = (|| {
+        let mut remaining = MAX_UNPACKED_BYTES;
+        for index in 0..archive.len() {
+            let mut entry = archive
+                .by_index(index)
+                .map_err(|err| format!("Failed to read package: {err}"))?;
+            // Rejects absolute paths and `..` so entries cannot escape the plugin folder.
+            let Some(relative) = entry.enclosed_name() else {
+                return Err(format!("Unsafe path in package: {}", entry.name()));
+            };
+            let path = staging.join(relative);
+            if entry.is_dir() {
+                fs::create_dir_all(&path)
+                    .map_err(|err| format!("Failed to unpack plugin: {err}"))?;
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|err| format!("Failed to unpack plugin: {err}"))?;
+            }
+            let mut out =
+                fs::File::create(&path).map_err(|err| format!("Failed to unpack plugin: {err}"))?;
+            // Sizes in the zip headers can lie, so count what actually comes out.
+            let written = std::io::copy(&mut (&mut entry).take(remaining + 1), &mut out)
+                .map_err(|err| format!("Failed to unpack plugin: {err}"))?;
+            if written > remaining {
+                return Err(format!(
+                    "The package unpacks to more than {} MB.",
+                    MAX_UNPACKED_BYTES / (1024 * 1024)
+                ));
+            }
+            remaining -= written;
+            #[cfg(unix)]
+            if let Some(mode) = entry.unix_mode() {
+                use std::os::unix::fs::PermissionsExt;
+                let _ = fs::set_permissions(&path, fs::Permissions::from_mode(mode & 0o755));
+            }
+        }
+        if !staging.join(entry).is_file() {
+            return Err(format!("The package has no entry file {entry}."));
+        }
+        let _ = fs::remove_dir_all(target);
+        fs::rename(&staging, target).map_err(|err| format!("Failed to install plugin: {err}"))
+    })();
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result
+}
+
+fn not_installed(id: &str) -> AppError {
+    AppError::not_found(format!("No plugin named '{id}' is installed."))
+}
+
+fn tools_config_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    Ok(workspace::data_dir(app)?.join(TOOLS_CONFIG_FILE))
+}
+
+fn read_tools_config(path: &Path) -> Result<Map<String, Value>, AppError> {
+    match fs::read_to_string(path) {
+        Ok(raw) => serde_json::from_str(&raw).map_err(|err| {
+            AppError::tool(format!("Failed to parse {TOOLS_CONFIG_FILE}.")).with_details(err)
+        }),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Map::new()),
+        Err(err) => {
+            Err(AppError::tool(format!("Failed to read {TOOLS_CONFIG_FILE}.")).with_details(err))
+        }
+    }
+}
+
+/// Edits `tools_config.json` in place, then asks a running backend to reload it.
+async fn update_tools_config<R: Runtime, T>(
+    app: &AppHandle<R>,
+    edit: impl FnOnce(&mut Map<String, Value>) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let path = tools_config_path(app)?;
+    let mut config = read_tools_config(&path)?;
+    let result = edit(&mut config)?;
+    let raw = serde_json::to_string_pretty(&config).map_err(|err| {
+        AppError::tool(format!("Failed to serialize {TOOLS_CONFIG_FILE}.")).with_details(err)
+    })?;
+    atomic_file::write(&path, raw).map_err(|err| {
+        AppError::tool(format!("Failed to write {TOOLS_CONFIG_FILE}.")).with_details(err)
+    })?;
+    if let Some(base_url) = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+    {
+        // An empty patch makes the backend re-read the file and re-register its tools.
+        let reloaded = proxy::client(app)
+            .put(format!("{base_url}/tools/config"))
+            .json(&json!({}))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(err) = reloaded {
+            tracing::warn!("[Plugins] Backend did not reload tools: {err}");
+        }
+    }
+    Ok(result)
+}
+
+/// Plugins registered in the `plugins` section, keyed by id.
+fn registered(config: &Map<String, Value>) -> BTreeMap<String, PluginInfo> {
+    config
+        .get("plugins")
+        .cloned()
+        .and_then(|plugins| serde_json::from_value(plugins).ok())
+        .unwrap_or_default()
+}
+
+fn store_plugins(
+    config: &mut Map<String, Value>,
+    plugins: &BTreeMap<String, PluginInfo>,
+) -> Result<(), AppError> {
+    let value = serde_json::to_value(plugins)
+        .map_err(|err| AppError::tool("Failed to record plugin.").with_details(err))?;
+    config.insert("plugins".to_string(), value);
+    Ok(())
+}
+
+/// Sets the backend's per-tool flags, or removes them when `enabled` is `None`.
+fn set_tools_enabled(config: &mut Map<String, Value>, tools: &[String], enabled: Option<bool>) {
+    let mut flags = match config.remove("enabled") {
+        Some(Value::Object(flags)) => flags,
+        _ => Map::new(),
+    };
+    for tool in tools {
+        match enabled {
+            Some(enabled) => flags.insert(tool.clone(), Value::Bool(enabled)),
+            None => flags.remove(tool),
+        };
+    }
+    config.insert("enabled".to_string(), Value::Object(flags));
+}
+
+/// Installs a signed plugin package from a URL or local path. The signature is read
+/// from the same location with `.sig` appended.
+#[tauri::command]
+pub async fn install_plugin(app: AppHandle, source: String) -> Result<PluginInfo, AppError> {
+    let source = source.trim().to_string();
+    let package = fetch(&source).await.map_err(AppError::tool)?;
+    let signature = fetch(&format!("{source}.sig"))
+        .await
+        .map_err(AppError::tool)?;
+    let trusted_keys = app.state::<SettingsStore>().get().plugins.trusted_keys;
+    let signer = verify(&package, &signature, &trusted_keys).map_err(AppError::tool)?;
+
+    let root = plugins_dir(&app)?;
+    let info = tauri::async_runtime::spawn_blocking(move || -> Result<PluginInfo, String> {
+        let mut archive = ZipArchive::new(Cursor::new(package.as_slice()))
+            .map_err(|err| format!("Not a valid plugin package: {err}"))?;
+        let manifest = read_manifest(&mut archive)?;
+        let target = root.join(&manifest.id);
+        unpack(&mut archive, &target, &manifest.entry)?;
+        let path = target.join(&manifest.entry);
+        Ok(PluginInfo {
+            id: manifest.id,
+            name: manifest.name,
+            version: manifest.version,
+            kind: manifest.kind,
+            path,
+            tools: manifest.tools,
+            description: manifest.description,
+            enabled: true,
+            signer,
+        })
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Install task failed: {err}")))?
+    .map_err(AppError::tool)?;
+
+    update_tools_config(&app, |config| {
+        let mut plugins = registered(config);
+        plugins.insert(info.id.clone(), info.clone());
+        store_plugins(config, &plugins)?;
+        set_tools_enabled(config, &info.tools, Some(true));
+        Ok(())
+    })
+    .await?;
+    tracing::info!("[Plugins] Installed {} {}", info.id, info.version);
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn list_plugins(app: AppHandle) -> Result<Vec<PluginInfo>, AppError> {
+    let config = read_tools_config(&tools_config_path(&app)?)?;
+    Ok(registered(&config).into_values().collect())
+}
+
+#[tauri::command]
+pub async fn set_plugin_enabled(app: AppHandle, id: String, enabled: bool) -> Result<(), AppError> {
+    update_tools_config(&app, |config| {
+        let mut plugins = registered(config);
+        let info = plugins.get_mut(&id).ok_or_else(|| not_installed(&id))?;
+        info.enabled = enabled;
+        let tools = info.tools.clone();
+        store_plugins(config, &plugins)?;
+        set_tools_enabled(config, &tools, Some(enabled));
+        Ok(())
+    })
+    .await
+}
+
+/// Whether a workspace other than the active one still registers plugin `id`. The
+/// files under `plugins/` are shared by every workspace.
+fn used_elsewhere<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<bool, String> {
+    let active = workspace::data_dir(app)?;
+    Ok(workspace::data_dirs(app)
+        .into_iter()
+        .filter(|dir| *dir != active)
+        .any(|dir| {
+            read_tools_config(&dir.join(TOOLS_CONFIG_FILE))
+                .is_ok_and(|config| registered(&config).contains_key(id))
+        }))
+}
+
+/// Removes the plugin from the active workspace, and its files once no other
+/// workspace has it installed.
+#[tauri::command]
+pub async fn uninstall_plugin(app: AppHandle, id: String) -> Result<(), AppError> {
+    validate_id(&id).map_err(AppError::invalid_input)?;
+    update_tools_config(&app, |config| {
+        let mut plugins = registered(config);
+        let info = plugins.remove(&id).ok_or_else(|| not_installed(&id))?;
+        store_plugins(config, &plugins)?;
+        set_tools_enabled(config, &info.tools, None);
+        Ok(())
+    })
+    .await?;
+    if used_elsewhere(&app, &id)? {
+        return Ok(());
+    }
+    let dir = plugins_dir(&app)?.join(&id);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)
+            .map_err(|err| AppError::tool("Failed to remove plugin files.").with_details(err))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_trusted_plugin_keys(store: tauri::State<SettingsStore>) -> Vec<String> {
+    store.get().plugins.trusted_keys
+}
+
+#[tauri::command]
+pub fn set_trusted_plugin_keys(
+    store: tauri::State<SettingsStore>,
+    keys: Vec<String>,
+) -> Result<(), AppError> {
+    let mut trusted = Vec::new();
+    for key in keys
+        .iter()
+        .map(|key| key.trim())
+        .filter(|key| !key.is_empty())
+    {
+        let bytes = STANDARD
+            .decode(key)
+            .map_err(|err| AppError::invalid_input(format!("Invalid public key {key}: {err}")))?;
+        if bytes.len() != 32 {
+            return Err(AppError::invalid_input(format!(
+                "{key} is not an Ed25519 public key."
+            )));
+        }
+        if !trusted.iter().any(|existing| existing == key) {
+            trusted.push(key.to_string());
+        }
+    }
+    store.update(|settings| settings.plugins.trusted_keys = trusted)?;
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use std::{
+    process::{Child, Command},
+    time::Duration,
+};
+
+/// A sidecar or command together with the processes it starts, such as the
+/// backend's tool subprocesses, which `Child::kill` alone would leave running: a
+/// job object on Windows, a process group elsewhere.
+pub struct ProcessTree(platform::Tree);
+
+/// Makes the process about to be spawned the root of a tree of its own.
+pub fn prepare(command: &mut Command) {
+    platform::prepare(command);
+}
+
+impl ProcessTree {
+    /// `None` when the OS refuses, in which case only `child` itself can be stopped.
+    pub fn attach(child: &Child) -> Option<Self> {
+        match platform::Tree::attach(child) {
+            Ok(tree) => Some(Self(tree)),
+            Err(err) => {
+                tracing::warn!("[Sidecar] {err}");
+                None
+            }
+        }
+    }
+
+    /// Stops whatever in the tree outlived its root: asked to exit first where the
+    /// OS can, killed once `grace` is up.
+    pub fn terminate(self, grace: Duration) {
+        self.0.terminate(grace);
+    }
+}
+
+/// Kills a process this app did not spawn, along with its children where the OS
+/// keeps track of them.
+pub fn kill(pid: u32) {
+    platform::kill(pid);
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::{
+        os::unix::process::CommandExt,
+        process::{Child, Command},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    const POLL: Duration = Duration::from_millis(100);
+
+    pub fn prepare(command: &mut Command) {
+        command.process_group(0);
+    }
+
+    /// Sidecars lead their own group, so its id is the pid; an older one that does
+    /// not is killed alone.
+    pub fn kill(pid: u32) {
+        let Ok(pid) = libc::pid_t::try_from(pid) else {
+            return;
+        };
+        unsafe {
+            if libc::killpg(pid, libc::SIGKILL) != 0 {
+                libc::kill(pid, libc::SIGKILL);
+            }
+        }
+    }
+
+    pub struct Tree {
+        pgid: libc::pid_t,
+    }
+
+    impl Tree {
+        pub fn attach(child: &Child) -> Result<Self, String> {
+            let pgid = libc::pid_t::try_from(child.id())
+                .map_err(|_| format!("Process id {} is out of range.", child.id()))?;
+            Ok(Self { pgid })
+        }
+
+        /// False once no process is left in the group.
+        fn signal(&self, signal: libc::c_int) -> bool {
+            unsafe { libc::killpg(self.pgid, signal) == 0 }
+        }
+
+        pub fn terminate(self, grace: Duration) {
+            if !self.signal(libc::SIGTERM) {
+                return;
+            }
+            let deadline = Instant::now() + grace;
+            while Instant::now() < deadline {
+                thread::sleep(POLL);
+                if !self.signal(0) {
+                    return;
+                }
+            }
+            tracing::warn!(
+                "[Sidecar] Processes left in group {} after {}s; killing them.",
+                self.pgid,
+                grace.as_secs()
+            );
+            self.signal(libc::SIGKILL);
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::{
+        ffi::c_void,
+        os::windows::{io::AsRawHandle, process::CommandExt},
+        process::{Child, Command},
+        time::Duration,
+    };
+
+    use windows::{
+        core::PCWSTR,
+        Win32::{
+            Foundation::{CloseHandle, HANDLE},
+            System::JobObjects::{
+                AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+                SetInformationJobObject, TerminateJobObject, JOBOBJECT_BASIC_LIMIT_INFORMATION,
+                JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+            },
+        },
+    };
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    pub fn prepare(_command: &mut Command) {}
+
+    pub fn kill(pid: u32) {
+        let _ = Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .status();
+    }
+
+    /// Kill-on-close, so the tree also goes down if the app itself crashes.
+    pub struct Tree(HANDLE);
+
+    // The handle is owned by this value and only used through the job APIs.
+    unsafe impl Send for Tree {}
+
+    impl Tree {
+        /// Processes the child starts before this runs stay outside the job; the
+        /// backend only starts tools once it is serving.
+        pub fn attach(child: &Child) -> Result<Self, String> {
+            let job = unsafe { CreateJobObjectW(None, PCWSTR::null()) }
+                .map_err(|err| format!("Failed to create a job object: {err}"))?;
+            let tree = Self(job);
+            let limits = JOBOBJECT_EXTENDED_LIMIT_INFORMATION {
+                BasicLimitInformation: JOBOBJECT_BASIC_LIMIT_INFORMATION {
+                    LimitFlags: JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            unsafe {
+                SetInformationJobObject(
+                    job,
+                    JobObjectExtendedLimitInformation,
+                    &limits as *const _ as *const c_void,
+                    std::mem::size_of_val(&limits) as u32,
+                )
+            }
+            .map_err(|err| format!("Failed to configure the job object: {err}"))?;
+            unsafe { AssignProcessToJobObject(job, HANDLE(child.as_raw_handle())) }
+                .map_err(|err| format!("Failed to add the sidecar to a job object: {err}"))?;
+            Ok(tree)
+        }
+
+        /// Console-less processes cannot be asked to exit, so they are killed.
+        pub fn terminate(self, _grace: Duration) {
+            let _ = unsafe { TerminateJobObject(self.0, 1) };
+        }
+    }
+
+    impl Drop for Tree {
+        fn drop(&mut self) {
+            let _ = unsafe { CloseHandle(self.0) };
+        }
+    }
+}
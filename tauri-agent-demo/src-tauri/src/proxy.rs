@@ -0,0 +1,204 @@
+use std::time::Duration;
+
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::json;
+use tauri::{
+    http::{HeaderMap, HeaderValue, Request, Response},
+    AppHandle, Manager, Runtime, UriSchemeContext, UriSchemeResponder,
+};
+
+use crate::{costs, replay, sessions, watchdog, BackendState};
+
+/// The webview reaches a TCP backend through this scheme when proxying is on.
+pub const SCHEME: &str = "agent-proxy";
+/// Checked by the backend on every request while `TOKEN_ENV` is set; anything else
+/// on the machine that finds the port gets a 401.
+pub const TOKEN_HEADER: &str = "x-agent-token";
+pub const TOKEN_ENV: &str = "TAURI_AGENT_AUTH_TOKEN";
+/// Streamed chat responses only come back once the run finishes.
+const CALL_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// Headers that describe the original connection rather than the buffered body.
+const DROPPED_HEADERS: &[&str] = &["host", "content-length", "transfer-encoding", "connection"];
+
+/// Whether the webview goes through `SCHEME`, and the token the sidecar was given.
+/// Chosen once at startup; the proxy setting applies from the next launch, while the
+/// token is always generated on the TCP transport, in case the sidecar is selected.
+#[derive(Default)]
+pub struct Proxy {
+    enabled: bool,
+    token: Option<String>,
+}
+
+impl Proxy {
+    /// `with_token` is false for the stdio transport, since nothing else can reach
+    /// a pipe.
+    pub fn new(enabled: bool, with_token: bool) -> Result<Self, String> {
+        let token = if with_token {
+            let mut bytes = [0u8; 32];
+            SystemRandom::new()
+                .fill(&mut bytes)
+                .map_err(|_| "Failed to generate a backend token.".to_string())?;
+            Some(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+        } else {
+            None
+        };
+        Ok(Self { enabled, token })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+}
+
+/// The selected backend's token: the sidecar's own, or the one an external or
+/// remote profile was given.
+pub fn token<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    if let Some(target) = app
+        .try_state::<BackendState>()
+        .map(|state| state.target())
+        .filter(|target| target.url.is_some())
+    {
+        return target.token;
+    }
+    app.try_state::<Proxy>()?.token().map(str::to_string)
+}
+
+pub fn is_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.try_state::<Proxy>()
+        .is_some_and(|proxy| proxy.enabled())
+}
+
+/// A client for the shell's own calls to the backend, carrying the token if there is one.
+pub fn client<R: Runtime>(app: &AppHandle<R>) -> reqwest::Client {
+    client_with_token(token(app).as_deref())
+}
+
+/// Like `client`, for a backend with a token of its own, such as a session worker.
+pub fn client_with_token(token: Option<&str>) -> reqwest::Client {
+    let mut headers = HeaderMap::new();
+    if let Some(value) = token.and_then(|token| HeaderValue::from_str(token).ok()) {
+        headers.insert(TOKEN_HEADER, value);
+    }
+    reqwest::Client::builder()
+        .default_headers(headers)
+        .build()
+        .unwrap_or_default()
+}
+
+/// For the webview when it calls the backend directly rather than through `SCHEME`.
+#[tauri::command]
+pub fn get_backend_token(app: AppHandle) -> Option<String> {
+    token(&app)
+}
+
+/// The base URL the webview uses for the proxy scheme on this platform.
+pub fn base_url() -> String {
+    // WebView2 and Android only route custom schemes dressed up as http hosts.
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{SCHEME}.localhost")
+    } else {
+        format!("{SCHEME}://localhost")
+    }
+}
+
+fn error_response(status: u16, message: String) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .header("Access-Control-Allow-Origin", "*")
+        .body(json!({ "detail": message }).to_string().into_bytes())
+        .unwrap_or_default()
+}
+
+async fn forward<R: Runtime>(app: &AppHandle<R>, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let (parts, body) = request.into_parts();
+    let path = parts
+        .uri
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or("/");
+    if let Some(response) = replay::serve(app, &parts.method, path, &body) {
+        return response;
+    }
+    // Requests under a session's prefix go to its own worker.
+    let (base_url, token, target) = match sessions::route(app, path) {
+        Some(Ok(route)) => (route.base_url, Some(route.token), route.path),
+        Some(Err(err)) => return error_response(404, err),
+        None => {
+            let Some(base_url) = app
+                .try_state::<BackendState>()
+                .map(|state| state.base_url())
+            else {
+                return error_response(503, "Backend is not configured yet.".to_string());
+            };
+            (base_url, token(app), path.to_string())
+        }
+    };
+    if costs::is_llm_call(parts.method.as_str(), &target) && costs::is_paused(app) {
+        return error_response(
+            402,
+            "This month's budget is spent; LLM calls are paused until you resume them.".to_string(),
+        );
+    }
+    let recording = replay::begin(app, &parts.method, path, &body);
+    let _run = watchdog::track_request(app, &parts, &body);
+    let mut builder = client_with_token(token.as_deref())
+        .request(parts.method.clone(), format!("{base_url}{target}"))
+        .timeout(CALL_TIMEOUT)
+        .body(body);
+    // Origin is passed through so the backend's CORS middleware answers as usual.
+    for (name, value) in &parts.headers {
+        if !DROPPED_HEADERS.contains(&name.as_str()) && name.as_str() != TOKEN_HEADER {
+            builder = builder.header(name, value);
+        }
+    }
+    let upstream = match builder.send().await {
+        Ok(response) => response,
+        Err(err) => {
+            tracing::warn!("[Proxy] {err}");
+            return error_response(502, format!("Backend is unreachable: {err}"));
+        }
+    };
+    let status = upstream.status().as_u16();
+    let headers = upstream.headers().clone();
+    let mut response = Response::builder().status(status);
+    for (name, value) in &headers {
+        if !DROPPED_HEADERS.contains(&name.as_str()) {
+            response = response.header(name, value);
+        }
+    }
+    let body = match upstream.bytes().await {
+        Ok(body) => body.to_vec(),
+        Err(err) => return error_response(502, format!("Backend response was cut off: {err}")),
+    };
+    if let Some(exchange) = recording {
+        let exchange = exchange.respond(
+            status,
+            &headers,
+            |name| !DROPPED_HEADERS.contains(&name),
+            &body,
+        );
+        replay::record(app, exchange);
+    }
+    response
+        .body(body)
+        .unwrap_or_else(|err| error_response(502, format!("Backend sent a bad response: {err}")))
+}
+
+/// Handler for `SCHEME`: replays webview HTTP requests onto the sidecar's port with
+/// the token attached, so ordinary requests need neither the address nor the token.
+///
+/// Responses are buffered, the same as over the stdio transport. Development builds
+/// can record them to a tape or answer from one instead; see `replay`.
+pub fn handle<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    tauri::async_runtime::spawn(async move { responder.respond(forward(&app, request).await) });
+}
@@ -0,0 +1,432 @@
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{error::AppError, settings::SettingsStore};
+
+/// Names the backend's HTTP clients read; both spellings, since Python checks the
+/// lowercase ones first.
+const PROXY_VARS: &[&str] = &[
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+const CA_VARS: &[&str] = &["SSL_CERT_FILE", "REQUESTS_CA_BUNDLE"];
+/// The sidecar's own loopback traffic never goes through a proxy.
+const LOOPBACK_HOSTS: &str = "localhost,127.0.0.1,::1";
+const DEFAULT_TEST_URL: &str = "https://api.openai.com/v1/models";
+const TEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How the backend reaches LLM providers and other outside services.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyMode {
+    /// Whatever the environment or the OS proxy settings say.
+    #[default]
+    System,
+    Manual,
+    /// Direct connections, even when the OS has a proxy configured.
+    Off,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxySettings {
+    pub mode: ProxyMode,
+    /// `http://`, `https://`, `socks5://` or `socks5h://`, with credentials if the
+    /// proxy needs them. Used in manual mode only.
+    pub url: Option<String>,
+    /// Comma-separated hosts to reach directly, on top of loopback.
+    pub no_proxy: Option<String>,
+    /// PEM file the backend trusts instead of its bundled roots, for proxies that
+    /// re-sign TLS traffic. It must hold the public roots as well as the corporate one.
+    pub ca_bundle: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemProxy {
+    url: String,
+    /// `environment`, or the OS settings it was read from.
+    source: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyTestResult {
+    ok: bool,
+    url: String,
+    /// The proxy the request went through; `None` for a direct connection.
+    proxy: Option<String>,
+    status: Option<u16>,
+    elapsed_ms: u64,
+    error: Option<String>,
+}
+
+fn validate_url(value: &str) -> Result<(), AppError> {
+    let parsed = reqwest::Url::parse(value).map_err(|err| {
+        AppError::invalid_input(format!("Invalid proxy URL '{value}'.")).with_details(err)
+    })?;
+    if !matches!(parsed.scheme(), "http" | "https" | "socks5" | "socks5h") {
+        return Err(AppError::invalid_input(
+            "The proxy URL must start with http://, https://, socks5:// or socks5h://.",
+        ));
+    }
+    if parsed.host_str().is_none() {
+        return Err(AppError::invalid_input(format!(
+            "The proxy URL '{value}' has no host."
+        )));
+    }
+    Ok(())
+}
+
+fn normalize(settings: ProxySettings) -> Result<ProxySettings, AppError> {
+    let trimmed = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let settings = ProxySettings {
+        mode: settings.mode,
+        url: trimmed(settings.url),
+        no_proxy: trimmed(settings.no_proxy),
+        ca_bundle: trimmed(settings.ca_bundle),
+    };
+    if let Some(url) = settings.url.as_deref() {
+        validate_url(url)?;
+    }
+    if settings.mode == ProxyMode::Manual && settings.url.is_none() {
+        return Err(AppError::invalid_input("Manual mode needs a proxy URL."));
+    }
+    if let Some(path) = settings.ca_bundle.as_deref() {
+        if !Path::new(path).is_file() {
+            return Err(AppError::not_found(format!(
+                "The CA bundle '{path}' does not exist."
+            )));
+        }
+    }
+    Ok(settings)
+}
+
+/// `url` with any password replaced, for logs and diagnostics bundles.
+pub fn strip_credentials(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) if parsed.password().is_some() => {
+            let _ = parsed.set_password(Some("redacted"));
+            parsed.to_string()
+        }
+        _ => url.to_string(),
+    }
+}
+
+fn from_environment() -> Option<String> {
+    [
+        "HTTPS_PROXY",
+        "https_proxy",
+        "ALL_PROXY",
+        "all_proxy",
+        "HTTP_PROXY",
+        "http_proxy",
+    ]
+    .iter()
+    .filter_map(|name| std::env::var(name).ok())
+    .map(|value| value.trim().to_string())
+    .find(|value| !value.is_empty())
+}
+
+/// The proxy the environment or the OS points at, if any.
+pub fn detect_system() -> Option<SystemProxy> {
+    if let Some(url) = from_environment() {
+        return Some(SystemProxy {
+            url,
+            source: "environment",
+        });
+    }
+    platform::detect().map(|(url, source)| SystemProxy { url, source })
+}
+
+/// The proxy `settings` resolve to right now; `None` for a direct connection.
+/// May run the OS's proxy tools, so keep it off the async runtime.
+pub fn effective_url(settings: &ProxySettings) -> Option<String> {
+    match settings.mode {
+        ProxyMode::System => detect_system().map(|proxy| proxy.url),
+        ProxyMode::Manual => settings.url.clone(),
+        ProxyMode::Off => None,
+    }
+}
+
+fn no_proxy_list(settings: &ProxySettings) -> String {
+    match settings.no_proxy.as_deref() {
+        Some(extra) => format!("{LOOPBACK_HOSTS},{extra}"),
+        None => LOOPBACK_HOSTS.to_string(),
+    }
+}
+
+/// Variables for the sidecar from the saved proxy settings. In system mode a proxy
+/// already in the inherited environment is left alone.
+pub fn environment<R: Runtime>(app: &AppHandle<R>) -> Vec<(&'static str, String)> {
+    let settings = saved(app);
+    let mut vars = Vec::new();
+    let url = match settings.mode {
+        ProxyMode::System if from_environment().is_some() => None,
+        ProxyMode::Off => {
+            vars.extend(PROXY_VARS.iter().map(|name| (*name, String::new())));
+            vars.push(("NO_PROXY", "*".to_string()));
+            vars.push(("no_proxy", "*".to_string()));
+            None
+        }
+        _ => effective_url(&settings),
+    };
+    if let Some(url) = url {
+        vars.extend(PROXY_VARS.iter().map(|name| (*name, url.clone())));
+        let no_proxy = no_proxy_list(&settings);
+        vars.push(("NO_PROXY", no_proxy.clone()));
+        vars.push(("no_proxy", no_proxy));
+    }
+    if let Some(path) = settings.ca_bundle {
+        if Path::new(&path).is_file() {
+            vars.extend(CA_VARS.iter().map(|name| (*name, path.clone())));
+        } else {
+            tracing::warn!("[Proxy] CA bundle {path} is missing; using the default roots.");
+        }
+    }
+    vars
+}
+
+pub fn saved<R: Runtime>(app: &AppHandle<R>) -> ProxySettings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().outbound_proxy)
+        .unwrap_or_default()
+}
+
+/// A client that reaches outside services the way the backend does: through
+/// `proxy`, trusting the configured CA bundle.
+pub fn client(
+    settings: &ProxySettings,
+    proxy: Option<&str>,
+    timeout: Duration,
+) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder().timeout(timeout).no_proxy();
+    if let Some(url) = proxy {
+        let proxy = reqwest::Proxy::all(url)
+            .map_err(|err| format!("Invalid proxy URL: {err}"))?
+            .no_proxy(reqwest::NoProxy::from_string(&no_proxy_list(settings)));
+        builder = builder.proxy(proxy);
+    }
+    if let Some(path) = settings.ca_bundle.as_deref() {
+        let pem =
+            std::fs::read(path).map_err(|err| format!("Failed to read the CA bundle: {err}"))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .map_err(|err| format!("The CA bundle is not valid PEM: {err}"))?;
+        // Python replaces its roots with SSL_CERT_FILE, so the test does too.
+        builder = builder.tls_built_in_root_certs(false);
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    builder
+        .build()
+        .map_err(|err| format!("Failed to set up the connection: {err}"))
+}
+
+#[tauri::command]
+pub fn get_proxy_settings(store: tauri::State<SettingsStore>) -> ProxySettings {
+    store.get().outbound_proxy
+}
+
+/// Persists the backend's proxy settings; they take effect the next time the
+/// backend is spawned.
+#[tauri::command]
+pub fn set_proxy_settings(
+    store: tauri::State<SettingsStore>,
+    settings: ProxySettings,
+) -> Result<ProxySettings, AppError> {
+    let settings = normalize(settings)?;
+    let saved = store.update(|current| current.outbound_proxy = settings)?;
+    Ok(saved.outbound_proxy)
+}
+
+#[tauri::command]
+pub fn detect_system_proxy() -> Option<SystemProxy> {
+    detect_system()
+}
+
+/// Requests `url` (an LLM provider by default) the way the backend would with
+/// `settings`, or with the saved settings when none are given. Any HTTP status
+/// counts as reachable; only connection and TLS failures fail the test.
+#[tauri::command]
+pub async fn test_proxy_connection(
+    store: tauri::State<'_, SettingsStore>,
+    settings: Option<ProxySettings>,
+    url: Option<String>,
+) -> Result<ProxyTestResult, AppError> {
+    let settings = match settings {
+        Some(settings) => normalize(settings)?,
+        None => store.get().outbound_proxy,
+    };
+    let url = url
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| DEFAULT_TEST_URL.to_string());
+    let proxy = tauri::async_runtime::spawn_blocking({
+        let settings = settings.clone();
+        move || effective_url(&settings)
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Proxy detection failed: {err}")))?;
+    let client =
+        client(&settings, proxy.as_deref(), TEST_TIMEOUT).map_err(AppError::invalid_input)?;
+    let started = Instant::now();
+    let outcome = client.get(&url).send().await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    let (status, error) = match outcome {
+        Ok(response) => (Some(response.status().as_u16()), None),
+        Err(err) => (None, Some(format_error(&err))),
+    };
+    Ok(ProxyTestResult {
+        ok: error.is_none(),
+        url,
+        proxy,
+        status,
+        elapsed_ms,
+        error,
+    })
+}
+
+/// reqwest's top-level message hides the cause, usually the useful part.
+fn format_error(err: &reqwest::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(err);
+    while let Some(cause) = source {
+        message.push_str(&format!(": {cause}"));
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::{os::windows::process::CommandExt, process::Command};
+
+    const INTERNET_SETTINGS_KEY: &str =
+        r"HKCU\Software\Microsoft\Windows\CurrentVersion\Internet Settings";
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    fn query(value: &str) -> Option<String> {
+        let output = Command::new("reg")
+            .args(["query", INTERNET_SETTINGS_KEY, "/v", value])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        text.lines()
+            .find(|line| line.trim_start().starts_with(value))
+            .and_then(|line| line.split_whitespace().nth(2))
+            .map(str::to_string)
+    }
+
+    /// `ProxyServer` is either `host:port` for every protocol or a list such as
+    /// `http=host:port;https=host:port;socks=host:port`.
+    pub fn detect() -> Option<(String, &'static str)> {
+        if query("ProxyEnable")? != "0x1" {
+            return None;
+        }
+        let server = query("ProxyServer")?;
+        let url = if server.contains('=') {
+            let entries: Vec<(&str, &str)> = server
+                .split(';')
+                .filter_map(|entry| entry.split_once('='))
+                .collect();
+            let pick = |scheme: &str| {
+                entries
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(scheme))
+                    .map(|(_, address)| *address)
+            };
+            if let Some(address) = pick("https").or_else(|| pick("http")) {
+                format!("http://{address}")
+            } else {
+                format!("socks5://{}", pick("socks")?)
+            }
+        } else if server.contains("://") {
+            server
+        } else {
+            format!("http://{server}")
+        };
+        Some((url, "windows_internet_settings"))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::{collections::HashMap, process::Command};
+
+    /// Reads `scutil --proxy`, which reports the active network service's settings.
+    pub fn detect() -> Option<(String, &'static str)> {
+        let output = Command::new("scutil").arg("--proxy").output().ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let values: HashMap<&str, &str> = text
+            .lines()
+            .filter_map(|line| line.split_once(" : "))
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .collect();
+        let pick = |prefix: &str| {
+            (values.get(format!("{prefix}Enable").as_str()) == Some(&"1"))
+                .then(|| {
+                    let host = values.get(format!("{prefix}Proxy").as_str())?;
+                    let port = values.get(format!("{prefix}Port").as_str())?;
+                    Some(format!("{host}:{port}"))
+                })
+                .flatten()
+        };
+        let url = if let Some(address) = pick("HTTPS").or_else(|| pick("HTTP")) {
+            format!("http://{address}")
+        } else {
+            format!("socks5://{}", pick("SOCKS")?)
+        };
+        Some((url, "macos_network_settings"))
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use std::process::Command;
+
+    fn gsettings(schema: &str, key: &str) -> Option<String> {
+        let output = Command::new("gsettings")
+            .args(["get", schema, key])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        let value = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .trim_matches('\'')
+            .to_string();
+        (!value.is_empty()).then_some(value)
+    }
+
+    fn address(protocol: &str) -> Option<String> {
+        let schema = format!("org.gnome.system.proxy.{protocol}");
+        let host = gsettings(&schema, "host")?;
+        let port = gsettings(&schema, "port").filter(|port| port != "0")?;
+        Some(format!("{host}:{port}"))
+    }
+
+    /// GNOME's proxy settings; other desktops are covered by the environment.
+    pub fn detect() -> Option<(String, &'static str)> {
+        if gsettings("org.gnome.system.proxy", "mode")? != "manual" {
+            return None;
+        }
+        let url = if let Some(address) = address("https").or_else(|| address("http")) {
+            format!("http://{address}")
+        } else {
+            format!("socks5://{}", address("socks")?)
+        };
+        Some((url, "gnome_proxy_settings"))
+    }
+}
@@ -0,0 +1,359 @@
+//! Runs the backend from its Python sources in a virtualenv under app data instead
+//! of the bundled PyInstaller binary, so working on the backend or a plugin needs
+//! no sidecar rebuild. The process gets the same arguments and environment.
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    backend, error::AppError, events, scan, settings::SettingsStore, sidecar::SidecarSpec,
+};
+
+/// Under the app data dir, shared by every workspace.
+const VENV_DIR: &str = "python-runtime";
+/// Digest of the requirements the virtualenv was last installed from.
+const INSTALLED_FILE: &str = "requirements.sha256";
+const REQUIREMENTS: &str = "requirements.txt";
+/// `python -m backend` runs `main.py` as the bundled binary does.
+const ENTRY_MODULE: &str = "backend";
+
+/// Whether a setup is running; a second one is refused rather than queued.
+static SETTING_UP: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PythonRuntimeSettings {
+    /// Run the backend from `source_dir` in the virtualenv instead of the binary.
+    pub enabled: bool,
+    /// Folder holding `main.py` and `requirements.txt`; `None` uses the checkout's
+    /// `python-backend/` in dev builds.
+    pub source_dir: Option<PathBuf>,
+    /// Interpreter the virtualenv is created with; `None` looks for `python3`, then
+    /// `python`, on the PATH.
+    pub interpreter: Option<PathBuf>,
+}
+
+events::payload_enum! {
+    pub enum SetupStage {
+        Venv,
+        Install,
+        Done,
+        Failed,
+    }
+}
+
+events::payload! {
+    pub struct SetupProgress {
+        stage: SetupStage,
+        /// A line of the interpreter's or pip's output, or the error when failed.
+        line: Option<String>,
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PythonRuntimeStatus {
+    enabled: bool,
+    source_dir: Option<String>,
+    venv_dir: Option<String>,
+    /// The virtualenv exists and its requirements were installed.
+    installed: bool,
+    /// `requirements.txt` changed since they were; run the setup again.
+    outdated: bool,
+    setting_up: bool,
+}
+
+fn settings<R: Runtime>(app: &AppHandle<R>) -> PythonRuntimeSettings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().backend.python)
+        .unwrap_or_default()
+}
+
+fn source_dir(settings: &PythonRuntimeSettings) -> Option<PathBuf> {
+    if let Some(dir) = &settings.source_dir {
+        return Some(dir.clone());
+    }
+    if tauri::is_dev() {
+        let checkout = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join("python-backend");
+        if checkout.is_dir() {
+            return Some(checkout);
+        }
+    }
+    None
+}
+
+fn venv_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    Ok(crate::resolve_app_data_dir(app)?.join(VENV_DIR))
+}
+
+fn venv_python(venv: &Path) -> PathBuf {
+    if cfg!(windows) {
+        venv.join("Scripts").join("python.exe")
+    } else {
+        venv.join("bin").join("python")
+    }
+}
+
+fn requirements_digest(source: &Path) -> Result<String, String> {
+    let path = source.join(REQUIREMENTS);
+    let data =
+        fs::read(&path).map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    Ok(Sha256::digest(&data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+fn installed_digest(venv: &Path) -> Option<String> {
+    fs::read_to_string(venv.join(INSTALLED_FILE))
+        .ok()
+        .map(|digest| digest.trim().to_string())
+}
+
+fn status<R: Runtime>(app: &AppHandle<R>) -> PythonRuntimeStatus {
+    let settings = settings(app);
+    let source = source_dir(&settings);
+    let venv = venv_dir(app).ok();
+    let installed = venv
+        .as_deref()
+        .filter(|venv| venv_python(venv).is_file())
+        .and_then(installed_digest);
+    let current = source
+        .as_deref()
+        .and_then(|source| requirements_digest(source).ok());
+    PythonRuntimeStatus {
+        enabled: settings.enabled,
+        source_dir: source.map(|dir| dir.display().to_string()),
+        venv_dir: venv.map(|dir| dir.display().to_string()),
+        outdated: matches!((&installed, &current), (Some(installed), Some(current)) if installed != current),
+        installed: installed.is_some(),
+        setting_up: SETTING_UP.load(Ordering::SeqCst),
+    }
+}
+
+/// The interpreter and source folder to run the backend with, or `None` for the
+/// bundled binary.
+pub fn launch<R: Runtime>(app: &AppHandle<R>) -> Result<Option<(PathBuf, PathBuf)>, String> {
+    let settings = settings(app);
+    if !settings.enabled {
+        return Ok(None);
+    }
+    let source = source_dir(&settings)
+        .filter(|dir| dir.join("main.py").is_file())
+        .ok_or("The Python backend sources were not found; choose their folder again.")?;
+    let python = venv_python(&venv_dir(app)?);
+    if !python.is_file() {
+        return Err("The Python backend is not set up; run its setup again.".to_string());
+    }
+    if requirements_digest(&source).ok() != installed_digest(&venv_dir(app)?) {
+        tracing::warn!(
+            "[Python] {REQUIREMENTS} changed since the last setup; the backend may miss packages."
+        );
+    }
+    Ok(Some((python, source)))
+}
+
+/// Makes `spec` run `python -m backend` from `source` instead of the binary. Goes
+/// before the spec's own arguments, which `main.py` parses after the module.
+pub fn run_from_source(spec: SidecarSpec, python: &Path, source: &Path) -> SidecarSpec {
+    spec.program(python)
+        .arg("-m")
+        .arg(ENTRY_MODULE)
+        .env("PYTHONPATH", source)
+        .env("PYTHONUNBUFFERED", "1")
+}
+
+fn progress<R: Runtime>(app: &AppHandle<R>, stage: SetupStage, line: Option<&str>) {
+    events::python_runtime_progress(
+        app,
+        &SetupProgress {
+            stage,
+            line: line.map(str::to_string),
+        },
+    );
+}
+
+/// Runs `command`, passing each line it prints on as progress.
+fn run<R: Runtime>(
+    app: &AppHandle<R>,
+    stage: SetupStage,
+    mut command: Command,
+    what: &str,
+) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Failed to run {what}: {err}"))?;
+    let forward = |stream: Box<dyn Read + Send>| {
+        let app = app.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                progress(&app, stage, Some(&line));
+            }
+        })
+    };
+    let readers: Vec<_> = [
+        child
+            .stdout
+            .take()
+            .map(|out| Box::new(out) as Box<dyn Read + Send>),
+        child
+            .stderr
+            .take()
+            .map(|err| Box::new(err) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .flatten()
+    .map(forward)
+    .collect();
+    let status = child
+        .wait()
+        .map_err(|err| format!("Failed to wait for {what}: {err}"))?;
+    for reader in readers {
+        let _ = reader.join();
+    }
+    if !status.success() {
+        return Err(format!("{what} failed ({status})."));
+    }
+    Ok(())
+}
+
+fn set_up<R: Runtime>(app: &AppHandle<R>, settings: &PythonRuntimeSettings) -> Result<(), String> {
+    let source = source_dir(settings).ok_or("Choose the folder with the backend's sources.")?;
+    if !source.join("main.py").is_file() || !source.join(REQUIREMENTS).is_file() {
+        return Err(format!(
+            "{} does not hold the backend's main.py and {REQUIREMENTS}.",
+            source.display()
+        ));
+    }
+    let venv = venv_dir(app)?;
+    let python = venv_python(&venv);
+    if !python.is_file() {
+        let interpreter = settings
+            .interpreter
+            .clone()
+            .or_else(|| scan::find_program("python3"))
+            .or_else(|| scan::find_program("python"))
+            .ok_or("No Python interpreter was found on the PATH; choose one.")?;
+        tracing::info!(
+            "[Python] Creating a virtualenv at {} with {}.",
+            venv.display(),
+            interpreter.display()
+        );
+        let mut create = Command::new(&interpreter);
+        create.arg("-m").arg("venv").arg(&venv);
+        run(app, SetupStage::Venv, create, "python -m venv")?;
+    }
+    let digest = requirements_digest(&source)?;
+    tracing::info!("[Python] Installing the backend's requirements.");
+    let mut install = Command::new(&python);
+    install
+        .args(["-m", "pip", "install", "--disable-pip-version-check", "-r"])
+        .arg(source.join(REQUIREMENTS))
+        .current_dir(&source);
+    run(app, SetupStage::Install, install, "pip install")?;
+    fs::write(venv.join(INSTALLED_FILE), digest)
+        .map_err(|err| format!("Failed to record the installed requirements: {err}"))
+}
+
+/// Restarts the sidecar on the runtime just chosen, or starts it when the last
+/// start failed, as it does in a build without the bundled binary.
+fn apply<R: Runtime>(app: &AppHandle<R>) -> Result<(), AppError> {
+    if crate::runs_sidecar(app) {
+        return crate::restart_backend(app).map_err(AppError::from);
+    }
+    backend::retry(app)
+}
+
+#[tauri::command]
+pub fn get_python_runtime_status(app: AppHandle) -> PythonRuntimeStatus {
+    status(&app)
+}
+
+/// Creates the virtualenv if it is missing and installs the backend's pinned
+/// requirements into it, then runs the backend from source from now on. Output
+/// follows on `python-runtime://progress`. `source_dir` and `interpreter` are kept
+/// for later starts; without them the saved ones, or the defaults, are used.
+#[tauri::command]
+pub async fn setup_python_backend(
+    app: AppHandle,
+    source_dir: Option<String>,
+    interpreter: Option<String>,
+) -> Result<PythonRuntimeStatus, AppError> {
+    if SETTING_UP.swap(true, Ordering::SeqCst) {
+        return Err(AppError::unavailable(
+            "The Python backend is already being set up.",
+        ));
+    }
+    let mut settings = settings(&app);
+    if let Some(dir) = source_dir.filter(|dir| !dir.trim().is_empty()) {
+        settings.source_dir = Some(PathBuf::from(dir));
+    }
+    if let Some(program) = interpreter.filter(|program| !program.trim().is_empty()) {
+        settings.interpreter = Some(PathBuf::from(program));
+    }
+    let handle = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        set_up(&handle, &settings)?;
+        settings.enabled = true;
+        handle
+            .state::<SettingsStore>()
+            .update(|stored| stored.backend.python = settings)?;
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|err| format!("Setup task failed: {err}"))
+    .and_then(|result| result);
+    SETTING_UP.store(false, Ordering::SeqCst);
+    if let Err(err) = result {
+        tracing::error!("[Python] Setup failed: {err}");
+        progress(&app, SetupStage::Failed, Some(&err));
+        return Err(AppError::from(err));
+    }
+    progress(&app, SetupStage::Done, None);
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || apply(&handle))
+        .await
+        .map_err(|err| AppError::from(format!("Restart task failed: {err}")))??;
+    Ok(status(&app))
+}
+
+/// Switches between the sources and the bundled binary and restarts the backend.
+/// Turning the sources on needs a finished setup.
+#[tauri::command]
+pub async fn set_python_backend(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<PythonRuntimeStatus, AppError> {
+    if enabled && !status(&app).installed {
+        return Err(AppError::invalid_input(
+            "Set up the Python backend before running it from source.",
+        ));
+    }
+    app.state::<SettingsStore>()
+        .update(|settings| settings.backend.python.enabled = enabled)?;
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || apply(&handle))
+        .await
+        .map_err(|err| AppError::from(format!("Restart task failed: {err}")))??;
+    Ok(status(&app))
+}
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+use crate::{
+    appearance,
+    error::{AppError, ErrorCode},
+    events, locale, main_window, proxy, BackendState,
+};
+
+/// Starts with `appearance::QUICK_CHAT_PREFIX` so the window effect and the
+/// quick-chat capability apply to it.
+pub const LABEL: &str = "quick-chat-ask";
+const WIDTH: f64 = 560.0;
+const HEIGHT: f64 = 320.0;
+
+/// The backend's answer to a quick ask, in the conversation it started.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickAnswer {
+    session_id: String,
+    message_id: i64,
+    reply: String,
+}
+
+events::payload! {
+    pub struct OpenConversation {
+        session_id: String,
+    }
+}
+
+/// Shows the quick ask window centered on the screen, or hides it when it is
+/// already showing. Bound to the `quick_ask` global shortcut.
+pub fn toggle<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let window = match app.get_webview_window(LABEL) {
+        Some(window) => {
+            if window.is_visible().unwrap_or(false) {
+                let _ = window.hide();
+                return Ok(());
+            }
+            window
+        }
+        None => {
+            let window = WebviewWindowBuilder::new(
+                app,
+                LABEL,
+                WebviewUrl::App("index.html?window=quick-ask".into()),
+            )
+            .title(locale::t(app, "window.quick_ask"))
+            .inner_size(WIDTH, HEIGHT)
+            .resizable(false)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .center()
+            .visible(false)
+            .build()
+            .map_err(|err| format!("Failed to open quick ask: {err}"))?;
+            if let Err(err) = appearance::apply(&window) {
+                tracing::warn!("[QuickAsk] {err}");
+            }
+            window
+        }
+    };
+    events::quick_ask_refresh(app, LABEL, &events::Empty {});
+    window
+        .show()
+        .and_then(|_| window.set_focus())
+        .map_err(|err| format!("Failed to show quick ask: {err}"))
+}
+
+/// Hides the window once it loses focus, like a launcher.
+pub fn blurred<R: Runtime>(window: &WebviewWindow<R>) {
+    let _ = window.hide();
+}
+
+/// Sends `prompt` to the backend as a new conversation and resolves with the answer.
+#[tauri::command]
+pub async fn quick_ask(app: AppHandle, prompt: String) -> Result<QuickAnswer, AppError> {
+    let prompt = prompt.trim().to_string();
+    if prompt.is_empty() {
+        return Err(AppError::invalid_input("Type a question first."));
+    }
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| AppError::unavailable("Backend is not available."))?;
+    let network_error =
+        |err: reqwest::Error| AppError::new(ErrorCode::Network, "Failed to ask.").with_details(err);
+    proxy::client(&app)
+        .post(format!("{base_url}/chat"))
+        .json(&json!({ "message": prompt }))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(network_error)?
+        .json::<QuickAnswer>()
+        .await
+        .map_err(network_error)
+}
+
+/// Hides the quick ask window and opens `session_id` in the main window, which
+/// hears about it on `quick-ask://open`.
+#[tauri::command]
+pub fn open_quick_answer(app: AppHandle, session_id: String) {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        let _ = window.hide();
+    }
+    main_window::reveal(&app);
+    events::quick_ask_open(&app, main_window::LABEL, &OpenConversation { session_id });
+}
@@ -0,0 +1,303 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{
+    http::{HeaderMap, Method, Response},
+    AppHandle, Manager, Runtime,
+};
+
+/// `--record <session>` writes every exchange through the proxy to a tape.
+pub const RECORD_FLAG: &str = "--record";
+/// `--replay <session>` answers from a tape and never spawns the sidecar.
+pub const REPLAY_FLAG: &str = "--replay";
+const RECORD_ENV: &str = "TAURI_AGENT_RECORD";
+const REPLAY_ENV: &str = "TAURI_AGENT_REPLAY";
+/// Tapes named without a path live here, as `<session>.jsonl`.
+const TAPES_DIR: &str = "recordings";
+
+/// One request through the proxy and what the backend answered. Bodies are base64
+/// so streamed and binary responses survive the round trip.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exchange {
+    method: String,
+    path: String,
+    /// SHA-256 of the request body, so the same call with other input is told apart.
+    request_digest: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Exchange {
+    /// Fills in the answer, keeping only the headers `keep` accepts.
+    pub fn respond(
+        mut self,
+        status: u16,
+        headers: &HeaderMap,
+        keep: impl Fn(&str) -> bool,
+        body: &[u8],
+    ) -> Self {
+        self.status = status;
+        self.headers = headers
+            .iter()
+            .filter(|(name, _)| keep(name.as_str()))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        self.body = STANDARD.encode(body);
+        self
+    }
+}
+
+/// A loaded tape; exchanges are handed out in recorded order per request, and the
+/// last one repeats once they run out.
+struct Tape {
+    exchanges: Vec<Exchange>,
+    served: Vec<bool>,
+}
+
+enum Mode {
+    Off,
+    Record(Mutex<File>),
+    Replay(Mutex<Tape>),
+}
+
+/// Development-only capture and playback of backend traffic, for frontend work
+/// without a backend and deterministic integration tests. Resolved once at startup.
+pub struct Replay(Mode);
+
+fn launch_value(flag: &str, env: &str) -> Option<String> {
+    let mut args = std::env::args();
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next();
+        }
+        if let Some(value) = arg
+            .strip_prefix(flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_string());
+        }
+    }
+    std::env::var(env).ok()
+}
+
+/// A bare name is a tape in the app data folder; anything path-like is used as is,
+/// so tests can keep tapes next to themselves.
+fn tape_path(app_data_dir: &Path, session: &str) -> Result<PathBuf, String> {
+    if session.contains(['/', '\\']) || session.ends_with(".jsonl") {
+        return Ok(PathBuf::from(session));
+    }
+    if !session
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+    {
+        return Err(format!("'{session}' is not a valid session name."));
+    }
+    Ok(app_data_dir
+        .join(TAPES_DIR)
+        .join(format!("{session}.jsonl")))
+}
+
+fn load(path: &Path) -> Result<Tape, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    let exchanges = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            serde_json::from_str::<Exchange>(line).map_err(|err| {
+                format!("Line {} of {} is invalid: {err}", index + 1, path.display())
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Tape {
+        served: vec![false; exchanges.len()],
+        exchanges,
+    })
+}
+
+impl Replay {
+    /// Replay wins when both are asked for; release builds ignore either.
+    pub fn init(app_data_dir: &Path) -> Self {
+        let replay = launch_value(REPLAY_FLAG, REPLAY_ENV).filter(|value| !value.trim().is_empty());
+        let record = launch_value(RECORD_FLAG, RECORD_ENV).filter(|value| !value.trim().is_empty());
+        if replay.is_none() && record.is_none() {
+            return Self(Mode::Off);
+        }
+        if !(tauri::is_dev() || cfg!(debug_assertions)) {
+            tracing::warn!(
+                "[Replay] Recording and replay are only available in development builds."
+            );
+            return Self(Mode::Off);
+        }
+        let mode = match (replay, record) {
+            (Some(session), _) => tape_path(app_data_dir, session.trim()).and_then(|path| {
+                let tape = load(&path)?;
+                tracing::info!(
+                    "[Replay] Serving {} recorded exchange(s) from {}.",
+                    tape.exchanges.len(),
+                    path.display()
+                );
+                Ok(Mode::Replay(Mutex::new(tape)))
+            }),
+            (None, Some(session)) => tape_path(app_data_dir, session.trim()).and_then(|path| {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+                }
+                let file = File::create(&path)
+                    .map_err(|err| format!("Failed to create {}: {err}", path.display()))?;
+                tracing::info!("[Replay] Recording backend traffic to {}.", path.display());
+                Ok(Mode::Record(Mutex::new(file)))
+            }),
+            (None, None) => Ok(Mode::Off),
+        };
+        Self(mode.unwrap_or_else(|err| {
+            tracing::warn!("[Replay] {err}");
+            Mode::Off
+        }))
+    }
+}
+
+pub fn is_replaying<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.try_state::<Replay>()
+        .is_some_and(|replay| matches!(replay.0, Mode::Replay(_)))
+}
+
+/// Recording only sees what goes through the proxy; startup turns it on for this.
+pub fn is_recording<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.try_state::<Replay>()
+        .is_some_and(|replay| matches!(replay.0, Mode::Record(_)))
+}
+
+fn request_digest(body: &[u8]) -> String {
+    digest(&SHA256, body)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The request half of an exchange while recording; finish it with
+/// `Exchange::respond` and pass it to `record`.
+pub fn begin<R: Runtime>(
+    app: &AppHandle<R>,
+    method: &Method,
+    path: &str,
+    body: &[u8],
+) -> Option<Exchange> {
+    let replay = app.try_state::<Replay>()?;
+    matches!(replay.0, Mode::Record(_)).then(|| Exchange {
+        method: method.to_string(),
+        path: path.to_string(),
+        request_digest: request_digest(body),
+        status: 0,
+        headers: Vec::new(),
+        body: String::new(),
+    })
+}
+
+pub fn record<R: Runtime>(app: &AppHandle<R>, exchange: Exchange) {
+    let Some(replay) = app.try_state::<Replay>() else {
+        return;
+    };
+    let Mode::Record(file) = &replay.0 else {
+        return;
+    };
+    let Ok(mut line) = serde_json::to_string(&exchange) else {
+        return;
+    };
+    line.push('\n');
+    let written = file
+        .lock()
+        .map_err(|_| "the tape is unavailable".to_string())
+        .and_then(|mut file| {
+            file.write_all(line.as_bytes())
+                .map_err(|err| err.to_string())
+        });
+    if let Err(err) = written {
+        tracing::warn!(
+            "[Replay] Failed to record {} {}: {err}",
+            exchange.method,
+            exchange.path
+        );
+    }
+}
+
+fn pick(tape: &mut Tape, method: &str, path: &str, digest: &str) -> Option<Exchange> {
+    let same_call = |exchange: &Exchange| exchange.method == method && exchange.path == path;
+    let exact: Vec<usize> = (0..tape.exchanges.len())
+        .filter(|&index| {
+            same_call(&tape.exchanges[index]) && tape.exchanges[index].request_digest == digest
+        })
+        .collect();
+    // A body never seen while recording still gets an answer for the same route.
+    let candidates = if exact.is_empty() {
+        (0..tape.exchanges.len())
+            .filter(|&index| same_call(&tape.exchanges[index]))
+            .collect()
+    } else {
+        exact
+    };
+    let index = candidates
+        .iter()
+        .copied()
+        .find(|&index| !tape.served[index])
+        .or_else(|| candidates.last().copied())?;
+    tape.served[index] = true;
+    Some(tape.exchanges[index].clone())
+}
+
+fn not_recorded(method: &Method, path: &str) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(404)
+        .header("Content-Type", "application/json")
+        .header("Access-Control-Allow-Origin", "*")
+        .body(
+            json!({ "detail": format!("Nothing was recorded for {method} {path}.") })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_or_default()
+}
+
+/// The recorded answer while replaying, or a 404 when there is none; `None` when
+/// not replaying, so the request goes to the backend.
+pub fn serve<R: Runtime>(
+    app: &AppHandle<R>,
+    method: &Method,
+    path: &str,
+    body: &[u8],
+) -> Option<Response<Vec<u8>>> {
+    let replay = app.try_state::<Replay>()?;
+    let Mode::Replay(tape) = &replay.0 else {
+        return None;
+    };
+    let picked = tape
+        .lock()
+        .ok()
+        .and_then(|mut tape| pick(&mut tape, method.as_str(), path, &request_digest(body)));
+    let Some(exchange) = picked else {
+        tracing::debug!("[Replay] Nothing recorded for {method} {path}.");
+        return Some(not_recorded(method, path));
+    };
+    let body = STANDARD.decode(&exchange.body).unwrap_or_default();
+    let response = exchange.headers.iter().fold(
+        Response::builder().status(exchange.status),
+        |builder, (name, value)| builder.header(name, value),
+    );
+    Some(
+        response
+            .body(body)
+            .unwrap_or_else(|_| not_recorded(method, path)),
+    )
+}
@@ -0,0 +1,394 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Read, Write},
+    process::{ChildStdin, ChildStdout},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{
+    http::{Request, Response},
+    AppHandle, Manager, Runtime, UriSchemeContext, UriSchemeResponder,
+};
+
+use crate::{events, watchdog};
+
+/// The webview reaches a stdio backend through this scheme instead of a port.
+pub const SCHEME: &str = "agent-backend";
+/// Printed by the backend once it owns its stdout; anything before it is log output.
+const HANDSHAKE: &str = "GYY-RPC/1";
+const MAX_FRAME_BYTES: usize = 256 * 1024 * 1024;
+/// Streamed chat responses only come back once the run finishes.
+const CALL_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// Headers that describe the original framing rather than the buffered body.
+const DROPPED_HEADERS: &[&str] = &["content-length", "transfer-encoding", "connection"];
+
+#[derive(Debug, Serialize)]
+struct HttpRequest {
+    method: String,
+    path: String,
+    query: String,
+    headers: Vec<(String, String)>,
+    /// Base64, so binary uploads survive the JSON envelope.
+    body: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct HttpReply {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+/// A response to one of our calls, or a notification pushed by the backend.
+#[derive(Debug, Deserialize)]
+struct Incoming {
+    id: Option<u64>,
+    method: Option<String>,
+    params: Option<Value>,
+    result: Option<HttpReply>,
+    error: Option<RpcError>,
+}
+
+events::payload! {
+    /// A WebSocket hub event, as the backend would have sent it on `/ws`.
+    #[derive(Deserialize)]
+    pub struct RpcEvent {
+        session_id: String,
+        payload: Value,
+    }
+}
+
+type Waiter = mpsc::Sender<Result<HttpReply, String>>;
+
+/// The JSON-RPC channel to a sidecar started with `--stdio`. Frames are a 4-byte
+/// big-endian length followed by a UTF-8 JSON-RPC 2.0 message.
+#[derive(Default)]
+pub struct RpcBridge {
+    writer: Mutex<Option<ChildStdin>>,
+    pending: Mutex<HashMap<u64, Waiter>>,
+    next_id: AtomicU64,
+    /// Bumped on every spawn so a dying process's reader cannot tear down its successor.
+    generation: AtomicU64,
+}
+
+impl RpcBridge {
+    fn reset(&self, writer: Option<ChildStdin>) {
+        if let Ok(mut current) = self.writer.lock() {
+            *current = writer;
+        }
+        let waiters: Vec<Waiter> = self
+            .pending
+            .lock()
+            .map(|mut pending| pending.drain().map(|(_, waiter)| waiter).collect())
+            .unwrap_or_default();
+        for waiter in waiters {
+            let _ = waiter.send(Err("The backend stopped before answering.".to_string()));
+        }
+    }
+
+    fn write_frame(&self, message: &Value) -> Result<(), String> {
+        let body = serde_json::to_vec(message)
+            .map_err(|err| format!("Failed to encode backend request: {err}"))?;
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| "Backend channel is unavailable.".to_string())?;
+        let stdin = writer
+            .as_mut()
+            .ok_or_else(|| "The backend is not running.".to_string())?;
+        stdin
+            .write_all(&(body.len() as u32).to_be_bytes())
+            .and_then(|_| stdin.write_all(&body))
+            .and_then(|_| stdin.flush())
+            .map_err(|err| format!("Failed to write to the backend: {err}"))
+    }
+
+    /// Sends one HTTP request to the backend and waits for the whole response.
+    fn call(&self, request: HttpRequest, timeout: Duration) -> Result<HttpReply, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let (sender, receiver) = mpsc::channel();
+        self.pending
+            .lock()
+            .map_err(|_| "Backend channel is unavailable.".to_string())?
+            .insert(id, sender);
+        let forget = || {
+            if let Ok(mut pending) = self.pending.lock() {
+                pending.remove(&id);
+            }
+        };
+        let message = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "http.request",
+            "params": request,
+        });
+        if let Err(err) = self.write_frame(&message) {
+            forget();
+            return Err(err);
+        }
+        match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(_) => {
+                forget();
+                Err(format!(
+                    "The backend did not answer {} {} within {}s.",
+                    request_label(&message, "method"),
+                    request_label(&message, "path"),
+                    timeout.as_secs()
+                ))
+            }
+        }
+    }
+
+    fn resolve(&self, id: u64, result: Result<HttpReply, String>) {
+        let waiter = self
+            .pending
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.remove(&id));
+        if let Some(waiter) = waiter {
+            let _ = waiter.send(result);
+        }
+    }
+}
+
+fn request_label<'a>(message: &'a Value, field: &str) -> &'a str {
+    message["params"][field].as_str().unwrap_or_default()
+}
+
+/// Skips whatever the backend printed before it took over stdout.
+fn await_handshake(reader: &mut impl BufRead) -> io::Result<bool> {
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(false);
+        }
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim_end();
+        if text == HANDSHAKE {
+            return Ok(true);
+        }
+        if !text.is_empty() {
+            tracing::info!("[backend] {text}");
+        }
+    }
+}
+
+fn read_frame(reader: &mut impl Read) -> Result<Option<Vec<u8>>, String> {
+    let mut header = [0u8; 4];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(format!("Failed to read from the backend: {err}")),
+    }
+    let length = u32::from_be_bytes(header) as usize;
+    if length > MAX_FRAME_BYTES {
+        return Err(format!("Backend sent a {length}-byte frame; giving up."));
+    }
+    let mut body = vec![0u8; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|err| format!("Failed to read from the backend: {err}"))?;
+    Ok(Some(body))
+}
+
+fn dispatch<R: Runtime>(app: &AppHandle<R>, bridge: &RpcBridge, message: Incoming) {
+    match (message.method.as_deref(), message.id) {
+        // WebSocket hub traffic; the frontend filters it by session.
+        (Some("event"), _) => match message.params.map(serde_json::from_value::<RpcEvent>) {
+            Some(Ok(event)) => events::backend_rpc_event(app, &event),
+            _ => tracing::warn!("[Rpc] Ignoring an event without a session and payload."),
+        },
+        (None, Some(id)) => {
+            let result = match (message.result, message.error) {
+                (Some(reply), _) => Ok(reply),
+                (None, Some(error)) => Err(error.message),
+                (None, None) => Err("The backend sent an empty response.".to_string()),
+            };
+            bridge.resolve(id, result);
+        }
+        (method, _) => tracing::warn!("[Rpc] Ignoring unexpected message {method:?}."),
+    }
+}
+
+/// Takes over a freshly spawned backend's pipes; used as its `on_pipes` handler.
+pub fn attach<R: Runtime>(app: &AppHandle<R>, stdin: ChildStdin, stdout: ChildStdout) {
+    let Some(bridge) = app.try_state::<RpcBridge>() else {
+        return;
+    };
+    let generation = bridge.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    bridge.reset(Some(stdin));
+    let app = app.clone();
+    thread::spawn(move || {
+        let bridge = app.state::<RpcBridge>();
+        let mut reader = BufReader::new(stdout);
+        match await_handshake(&mut reader) {
+            Ok(true) => loop {
+                let body = match read_frame(&mut reader) {
+                    Ok(Some(body)) => body,
+                    Ok(None) => break,
+                    Err(err) => {
+                        tracing::warn!("[Rpc] {err}");
+                        break;
+                    }
+                };
+                match serde_json::from_slice::<Incoming>(&body) {
+                    Ok(message) => dispatch(&app, &bridge, message),
+                    Err(err) => tracing::warn!("[Rpc] Dropping malformed frame: {err}"),
+                }
+            },
+            Ok(false) => tracing::warn!("[Rpc] Backend exited before opening its channel."),
+            Err(err) => tracing::warn!("[Rpc] Failed to read from the backend: {err}"),
+        }
+        if bridge.generation.load(Ordering::SeqCst) == generation {
+            bridge.reset(None);
+        }
+    });
+}
+
+/// Whether a stdio backend is attached and able to take calls.
+pub fn is_attached<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.try_state::<RpcBridge>()
+        .and_then(|bridge| bridge.writer.lock().ok().map(|writer| writer.is_some()))
+        .unwrap_or(false)
+}
+
+fn request<R: Runtime>(
+    app: &AppHandle<R>,
+    method: &str,
+    path: &str,
+    body: String,
+    timeout: Duration,
+) -> Result<(u16, Vec<u8>), String> {
+    let bridge = app
+        .try_state::<RpcBridge>()
+        .ok_or_else(|| "Backend channel is unavailable.".to_string())?;
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    let headers = if body.is_empty() {
+        Vec::new()
+    } else {
+        vec![("content-type".to_string(), "application/json".to_string())]
+    };
+    let reply = bridge.call(
+        HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query.to_string(),
+            headers,
+            body: STANDARD.encode(body),
+        },
+        timeout,
+    )?;
+    let body = STANDARD
+        .decode(reply.body)
+        .map_err(|err| format!("Backend sent an unreadable body: {err}"))?;
+    Ok((reply.status, body))
+}
+
+/// A GET from the shell itself, for callers that would use `reqwest` over TCP.
+/// Blocks until the backend answers or `timeout` passes.
+pub fn get<R: Runtime>(
+    app: &AppHandle<R>,
+    path: &str,
+    timeout: Duration,
+) -> Result<(u16, Vec<u8>), String> {
+    request(app, "GET", path, String::new(), timeout)
+}
+
+/// Like `get`, with a JSON body.
+pub fn post<R: Runtime>(
+    app: &AppHandle<R>,
+    path: &str,
+    body: &Value,
+    timeout: Duration,
+) -> Result<(u16, Vec<u8>), String> {
+    request(app, "POST", path, body.to_string(), timeout)
+}
+
+/// The base URL the webview uses for the custom scheme on this platform.
+pub fn base_url() -> String {
+    // WebView2 and Android only route custom schemes dressed up as http hosts.
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{SCHEME}.localhost")
+    } else {
+        format!("{SCHEME}://localhost")
+    }
+}
+
+fn error_response(status: u16, message: String) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .header("Access-Control-Allow-Origin", "*")
+        .body(json!({ "detail": message }).to_string().into_bytes())
+        .unwrap_or_default()
+}
+
+fn forward<R: Runtime>(app: &AppHandle<R>, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let Some(bridge) = app.try_state::<RpcBridge>() else {
+        return error_response(503, "Backend channel is unavailable.".to_string());
+    };
+    let (parts, body) = request.into_parts();
+    let _run = watchdog::track_request(app, &parts, &body);
+    // Origin is passed through so the backend's CORS middleware answers as usual.
+    let headers = parts
+        .headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let request = HttpRequest {
+        method: parts.method.to_string(),
+        path: parts.uri.path().to_string(),
+        query: parts.uri.query().unwrap_or_default().to_string(),
+        headers,
+        body: STANDARD.encode(body),
+    };
+    let reply = match bridge.call(request, CALL_TIMEOUT) {
+        Ok(reply) => reply,
+        Err(err) => {
+            tracing::warn!("[Rpc] {err}");
+            return error_response(502, err);
+        }
+    };
+    let body = match STANDARD.decode(reply.body) {
+        Ok(body) => body,
+        Err(err) => return error_response(502, format!("Backend sent an unreadable body: {err}")),
+    };
+    let mut response = Response::builder().status(reply.status);
+    for (name, value) in &reply.headers {
+        if !DROPPED_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+            response = response.header(name, value);
+        }
+    }
+    response
+        .body(body)
+        .unwrap_or_else(|err| error_response(502, format!("Backend sent a bad response: {err}")))
+}
+
+/// Handler for `SCHEME`: replays webview HTTP requests onto the stdio channel.
+///
+/// Responses are buffered, so streamed endpoints arrive in one piece when they
+/// finish. Shell-side callers that use `reqwest` directly still need the TCP
+/// transport.
+pub fn handle<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    tauri::async_runtime::spawn_blocking(move || responder.respond(forward(&app, request)));
+}
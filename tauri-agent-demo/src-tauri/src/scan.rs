@@ -0,0 +1,207 @@
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Largest file accepted as an attachment from any source.
+const MAX_ATTACHMENT_BYTES: u64 = 100 * 1024 * 1024;
+/// Extensions that run code when opened; the agent has no use for them.
+const BLOCKED_EXTENSIONS: &[&str] = &[
+    "apk", "app", "bat", "cmd", "com", "cpl", "dll", "dmg", "exe", "hta", "jar", "lnk", "msi",
+    "pif", "pkg", "ps1", "scr", "vbe", "vbs", "wsf",
+];
+/// Leading bytes of PE, ELF and Mach-O binaries, caught whatever the file is named.
+const EXECUTABLE_MAGIC: &[&[u8]] = &[
+    b"MZ",
+    b"\x7fELF",
+    b"\xfe\xed\xfa\xce",
+    b"\xfe\xed\xfa\xcf",
+    b"\xce\xfa\xed\xfe",
+    b"\xcf\xfa\xed\xfe",
+    b"\xca\xfe\xba\xbe",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Clean,
+    /// Flagged with a reason the user can read.
+    Flagged(String),
+}
+
+/// One check a file goes through before it becomes an attachment. A step that cannot
+/// run, such as a scanner that is not installed, returns `Err` and is skipped.
+pub trait ScanStep: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn scan(&self, path: &Path, size: u64) -> Result<Verdict, String>;
+}
+
+/// Runs every step in order and stops at the first that flags the file.
+pub struct Scanner {
+    steps: Vec<Box<dyn ScanStep>>,
+}
+
+impl Default for Scanner {
+    /// The size and type policy, then ClamAV and the OS scanner where installed.
+    fn default() -> Self {
+        let scanner = Self { steps: Vec::new() }
+            .with_step(Policy)
+            .with_step(ClamAv);
+        #[cfg(target_os = "windows")]
+        let scanner = scanner.with_step(Defender);
+        scanner
+    }
+}
+
+impl Scanner {
+    pub fn with_step(mut self, step: impl ScanStep + 'static) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    pub fn scan(&self, path: &Path) -> Verdict {
+        let size = match std::fs::metadata(path) {
+            Ok(metadata) => metadata.len(),
+            Err(err) => return Verdict::Flagged(format!("File could not be read: {err}")),
+        };
+        for step in &self.steps {
+            match step.scan(path, size) {
+                Ok(Verdict::Clean) => {}
+                Ok(flagged) => {
+                    tracing::warn!("[Scan] {} flagged {}", step.name(), path.display());
+                    return flagged;
+                }
+                Err(err) => tracing::warn!("[Scan] {} skipped: {err}", step.name()),
+            }
+        }
+        Verdict::Clean
+    }
+}
+
+struct Policy;
+
+impl ScanStep for Policy {
+    fn name(&self) -> &'static str {
+        "policy"
+    }
+
+    fn scan(&self, path: &Path, size: u64) -> Result<Verdict, String> {
+        if size > MAX_ATTACHMENT_BYTES {
+            return Ok(Verdict::Flagged(format!(
+                "Larger than {} MB.",
+                MAX_ATTACHMENT_BYTES / (1024 * 1024)
+            )));
+        }
+        // Stored attachments are renamed by content hash, so the original extension
+        // is all that is left of the name.
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        if BLOCKED_EXTENSIONS.contains(&extension.as_str()) {
+            return Ok(Verdict::Flagged(format!(
+                ".{extension} files are not accepted."
+            )));
+        }
+        let mut head = [0u8; 4];
+        let read = File::open(path)
+            .and_then(|mut file| file.read(&mut head))
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        if EXECUTABLE_MAGIC
+            .iter()
+            .any(|magic| head[..read].starts_with(magic))
+        {
+            return Ok(Verdict::Flagged("File contains a program.".to_string()));
+        }
+        Ok(Verdict::Clean)
+    }
+}
+
+/// Prefers the clamd client, which reuses the daemon's loaded signatures.
+struct ClamAv;
+
+impl ScanStep for ClamAv {
+    fn name(&self) -> &'static str {
+        "clamav"
+    }
+
+    fn scan(&self, path: &Path, _size: u64) -> Result<Verdict, String> {
+        let (program, args): (PathBuf, &[&str]) = if let Some(program) = find_program("clamdscan") {
+            (program, &["--no-summary", "--fdpass"])
+        } else if let Some(program) = find_program("clamscan") {
+            (program, &["--no-summary"])
+        } else {
+            return Err("ClamAV is not installed.".to_string());
+        };
+        let output = Command::new(program)
+            .args(args)
+            .arg(path)
+            .output()
+            .map_err(|err| format!("Failed to run ClamAV: {err}"))?;
+        match output.status.code() {
+            Some(0) => Ok(Verdict::Clean),
+            Some(1) => {
+                // Reports look like "<path>: Eicar-Signature FOUND".
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let signature = stdout
+                    .lines()
+                    .find_map(|line| line.rsplit_once(": ")?.1.strip_suffix(" FOUND"))
+                    .unwrap_or("malware");
+                Ok(Verdict::Flagged(format!("ClamAV found {signature}.")))
+            }
+            code => Err(format!("ClamAV exited with {code:?}.")),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+struct Defender;
+
+#[cfg(target_os = "windows")]
+impl ScanStep for Defender {
+    fn name(&self) -> &'static str {
+        "defender"
+    }
+
+    fn scan(&self, path: &Path, _size: u64) -> Result<Verdict, String> {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+        let program = std::env::var_os("ProgramFiles")
+            .map(|dir| {
+                PathBuf::from(dir)
+                    .join("Windows Defender")
+                    .join("MpCmdRun.exe")
+            })
+            .filter(|program| program.is_file())
+            .ok_or_else(|| "Microsoft Defender is not installed.".to_string())?;
+        let status = Command::new(program)
+            .args(["-Scan", "-ScanType", "3", "-DisableRemediation", "-File"])
+            .arg(path)
+            .creation_flags(CREATE_NO_WINDOW)
+            .status()
+            .map_err(|err| format!("Failed to run Microsoft Defender: {err}"))?;
+        match status.code() {
+            Some(0) => Ok(Verdict::Clean),
+            Some(2) => Ok(Verdict::Flagged(
+                "Microsoft Defender found a threat.".to_string(),
+            )),
+            code => Err(format!("Microsoft Defender exited with {code:?}.")),
+        }
+    }
+}
+
+pub fn find_program(name: &str) -> Option<PathBuf> {
+    let file_name = if cfg!(windows) {
+        format!("{name}.exe")
+    } else {
+        name.to_string()
+    };
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(&file_name))
+            .find(|candidate| candidate.is_file())
+    })
+}
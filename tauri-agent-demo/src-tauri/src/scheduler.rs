@@ -0,0 +1,626 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, TimeZone, Timelike,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    atomic_file, chat_stream,
+    error::AppError,
+    events, health, locale,
+    notifications::{self, NotificationKind},
+    proxy, rpc, watchdog, BackendState,
+};
+
+const CHAT_PATH: &str = "/chat/agent/stream";
+/// Schedules have minute resolution; checking twice a minute never skips one.
+const TICK: Duration = Duration::from_secs(30);
+const RUN_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const PREVIEW_CHARS: usize = 160;
+/// A schedule that matches nothing within this many days (say, February 30th) is refused.
+const SEARCH_DAYS: i64 = 366 * 4;
+
+/// A five-field cron expression: minute, hour, day of month, month, day of week.
+/// Each field takes `*`, numbers, ranges, lists and `/step`; Sunday is 0 or 7.
+#[derive(Debug, Clone)]
+struct Schedule {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    /// Cron matches either day field when both are restricted, otherwise both.
+    any_day: bool,
+    any_weekday: bool,
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<(u64, bool), String> {
+    let mut bits = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("'{item}' has an invalid step."))?;
+                (range, step)
+            }
+            None => (item, 1),
+        };
+        let number = |value: &str| -> Result<u32, String> {
+            value
+                .parse()
+                .ok()
+                .filter(|value| (min..=max).contains(value))
+                .ok_or_else(|| format!("'{value}' is outside {min}-{max}."))
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (number(start)?, number(end)?),
+                // `5/15` runs from 5 to the end of the range.
+                None if step > 1 => (number(range)?, max),
+                None => {
+                    let value = number(range)?;
+                    (value, value)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("'{range}' runs backwards."));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    // `*/2` still leaves the field open as far as the day rule goes.
+    Ok((bits, field.starts_with('*')))
+}
+
+impl Schedule {
+    fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "'{expression}' needs five fields: minute hour day month weekday."
+            ));
+        };
+        let (minutes, _) = parse_field(minute, 0, 59)?;
+        let (hours, _) = parse_field(hour, 0, 23)?;
+        let (days, any_day) = parse_field(day, 1, 31)?;
+        let (months, _) = parse_field(month, 1, 12)?;
+        let (mut weekdays, any_weekday) = parse_field(weekday, 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        let schedule = Self {
+            minutes,
+            hours: hours as u32,
+            days: days as u32,
+            months: months as u16,
+            weekdays: weekdays as u8,
+            any_day,
+            any_weekday,
+        };
+        if schedule.next_after(Local::now()).is_none() {
+            return Err(format!("'{expression}' never comes around."));
+        }
+        Ok(schedule)
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// The first matching minute after `after`, in local time. A time skipped by a
+    /// DST change is skipped here too.
+    fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.naive_local() + ChronoDuration::minutes(1);
+        let start = start.with_second(0)?.with_nanosecond(0)?;
+        for offset in 0..SEARCH_DAYS {
+            let date = start.date() + ChronoDuration::days(offset);
+            if !self.matches_day(date) {
+                continue;
+            }
+            let (first_hour, first_minute) = if offset == 0 {
+                (start.hour(), start.minute())
+            } else {
+                (0, 0)
+            };
+            for hour in first_hour..24 {
+                if self.hours & (1 << hour) == 0 {
+                    continue;
+                }
+                let from = if hour == first_hour { first_minute } else { 0 };
+                for minute in from..60 {
+                    if self.minutes & (1 << minute) == 0 {
+                        continue;
+                    }
+                    let naive = date.and_hms_opt(hour, minute, 0)?;
+                    if let Some(time) = Local.from_local_datetime(&naive).earliest() {
+                        return Some(time);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRun {
+    started_at: String,
+    finished_at: Option<String>,
+    /// The conversation the run created.
+    session_id: Option<String>,
+    error: Option<String>,
+}
+
+/// A prompt sent to the agent on a schedule, as a new conversation each time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTask {
+    id: String,
+    name: String,
+    prompt: String,
+    /// Cron expression in local time, or `@hourly`, `@daily`, `@weekly`, `@monthly`.
+    schedule: String,
+    /// Workspace folder the agent runs in; the backend's default without one.
+    work_path: Option<String>,
+    created_at: String,
+    /// A run missed while the app was closed fires once at the next start.
+    next_run_at: Option<String>,
+    last_run: Option<TaskRun>,
+}
+
+events::payload_enum! {
+    pub enum RunStatus {
+        Started,
+        Finished,
+        Failed,
+    }
+}
+
+events::payload! {
+    pub struct ScheduledRun {
+        id: String,
+        status: RunStatus,
+        /// The conversation the run posted to, once it has one.
+        session_id: Option<String>,
+        error: Option<String>,
+    }
+}
+
+/// Task definitions in app data, and which of them are running right now.
+pub struct Scheduler {
+    path: PathBuf,
+    tasks: Mutex<Vec<ScheduledTask>>,
+    running: Mutex<HashSet<String>>,
+    next_id: AtomicU64,
+}
+
+impl Scheduler {
+    pub fn load(path: PathBuf) -> Self {
+        let tasks: Vec<ScheduledTask> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        if !tasks.is_empty() {
+            tracing::info!("[Scheduler] {} scheduled task(s).", tasks.len());
+        }
+        Self {
+            path,
+            tasks: Mutex::new(tasks),
+            running: Mutex::new(HashSet::new()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Applies `change` to the tasks and persists them before returning.
+    fn update<T>(&self, change: impl FnOnce(&mut Vec<ScheduledTask>) -> T) -> Result<T, String> {
+        let mut tasks = self
+            .tasks
+            .lock()
+            .map_err(|_| "Scheduled tasks are unavailable.".to_string())?;
+        let result = change(&mut tasks);
+        let raw = serde_json::to_string_pretty(&*tasks)
+            .map_err(|err| format!("Failed to serialize scheduled tasks: {err}"))?;
+        atomic_file::write(&self.path, raw)
+            .map_err(|err| format!("Failed to save scheduled tasks: {err}"))?;
+        Ok(result)
+    }
+
+    /// Tasks due at `now` that are not already running, rescheduled and marked
+    /// running.
+    fn take_due(&self, now: DateTime<Local>) -> Result<Vec<ScheduledTask>, String> {
+        let Ok(mut running) = self.running.lock() else {
+            return Ok(Vec::new());
+        };
+        self.update(|tasks| {
+            let mut due = Vec::new();
+            for task in tasks.iter_mut() {
+                let is_due = task
+                    .next_run_at
+                    .as_deref()
+                    .and_then(|next| DateTime::parse_from_rfc3339(next).ok())
+                    .is_some_and(|next| next <= now);
+                if !is_due || running.contains(&task.id) {
+                    continue;
+                }
+                task.next_run_at = Schedule::parse(&task.schedule)
+                    .ok()
+                    .and_then(|schedule| schedule.next_after(now))
+                    .map(|next| next.to_rfc3339());
+                running.insert(task.id.clone());
+                due.push(task.clone());
+            }
+            due
+        })
+    }
+
+    fn finish(&self, id: &str, run: TaskRun) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(id);
+        }
+        let saved = self.update(|tasks| {
+            if let Some(task) = tasks.iter_mut().find(|task| task.id == id) {
+                task.last_run = Some(run);
+            }
+        });
+        if let Err(err) = saved {
+            tracing::warn!("[Scheduler] {err}");
+        }
+    }
+}
+
+/// Sends the run and waits for the whole stream; the backend saves the turn as it goes.
+async fn send<R: Runtime>(app: &AppHandle<R>, request: Value) -> Result<(u16, Vec<u8>), String> {
+    if rpc::is_attached(app) {
+        let handle = app.clone();
+        return tauri::async_runtime::spawn_blocking(move || {
+            rpc::post(&handle, CHAT_PATH, &request, RUN_TIMEOUT)
+        })
+        .await
+        .map_err(|err| format!("Run task failed: {err}"))?;
+    }
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| "Backend is not configured yet.".to_string())?;
+    let response = proxy::client(app)
+        .post(format!("{base_url}{CHAT_PATH}"))
+        .timeout(RUN_TIMEOUT)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|err| format!("Backend is unreachable: {err}"))?;
+    let status = response.status().as_u16();
+    let body = response
+        .bytes()
+        .await
+        .map_err(|err| format!("The run's stream was cut off: {err}"))?;
+    Ok((status, body.to_vec()))
+}
+
+/// The conversation and the final answer, or the error the run ended with.
+fn outcome(body: Vec<u8>) -> (Option<String>, Result<String, String>) {
+    let mut buffer = body;
+    buffer.push(b'\n');
+    let mut session_id = None;
+    let mut result = Err("The run ended without an answer.".to_string());
+    for event in chat_stream::drain_events(&mut buffer) {
+        if let Some(id) = event.get("session_id").and_then(Value::as_str) {
+            session_id = Some(id.to_string());
+        }
+        let content = event
+            .get("content")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        match event.get("step_type").and_then(Value::as_str) {
+            Some("answer") => result = Ok(content.to_string()),
+            Some("error") => result = Err(content.to_string()),
+            _ => {}
+        }
+    }
+    (session_id, result)
+}
+
+fn emit<R: Runtime>(
+    app: &AppHandle<R>,
+    task: &ScheduledTask,
+    status: RunStatus,
+    session_id: Option<&str>,
+    error: Option<&str>,
+) {
+    events::scheduler_run(
+        app,
+        &ScheduledRun {
+            id: task.id.clone(),
+            status,
+            session_id: session_id.map(str::to_string),
+            error: error.map(str::to_string),
+        },
+    );
+}
+
+async fn run<R: Runtime>(app: &AppHandle<R>, task: ScheduledTask) {
+    let started_at = Local::now().to_rfc3339();
+    tracing::info!("[Scheduler] Running {} ({}).", task.name, task.id);
+    emit(app, &task, RunStatus::Started, None, None);
+    let (session_id, result) = if !health::probe(app).await {
+        (None, Err("The backend was not running.".to_string()))
+    } else {
+        let _run = watchdog::track(app, CHAT_PATH, None, None);
+        let request = json!({ "message": task.prompt, "work_path": task.work_path });
+        match send(app, request).await {
+            Ok((status, body)) if status < 300 => outcome(body),
+            Ok((status, _)) => (None, Err(format!("Backend answered {status}."))),
+            Err(err) => (None, Err(err)),
+        }
+    };
+    let (status, title, body) = match &result {
+        Ok(answer) => (
+            RunStatus::Finished,
+            locale::t_with(
+                app,
+                "notification.scheduled_finished",
+                &[("name", &task.name)],
+            ),
+            answer.chars().take(PREVIEW_CHARS).collect::<String>(),
+        ),
+        Err(err) => {
+            tracing::warn!("[Scheduler] {} failed: {err}", task.name);
+            (
+                RunStatus::Failed,
+                locale::t_with(
+                    app,
+                    "notification.scheduled_failed",
+                    &[("name", &task.name)],
+                ),
+                err.clone(),
+            )
+        }
+    };
+    let error = result.err();
+    emit(app, &task, status, session_id.as_deref(), error.as_deref());
+    if let Err(err) = notifications::notify_for(
+        app,
+        NotificationKind::ScheduledJob,
+        &title,
+        &body,
+        session_id.clone(),
+    ) {
+        tracing::warn!("[Scheduler] {err}");
+    }
+    if let Some(scheduler) = app.try_state::<Scheduler>() {
+        scheduler.finish(
+            &task.id,
+            TaskRun {
+                started_at,
+                finished_at: Some(Local::now().to_rfc3339()),
+                session_id,
+                error,
+            },
+        );
+    }
+}
+
+/// Fires due tasks for as long as the app runs, window open or not; runs that
+/// overlap their next slot skip it.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TICK).await;
+            let Some(scheduler) = app.try_state::<Scheduler>() else {
+                continue;
+            };
+            let due = match scheduler.take_due(Local::now()) {
+                Ok(due) => due,
+                Err(err) => {
+                    tracing::warn!("[Scheduler] {err}");
+                    continue;
+                }
+            };
+            for task in due {
+                let handle = app.clone();
+                tauri::async_runtime::spawn(async move { run(&handle, task).await });
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn create_scheduled_task(
+    scheduler: tauri::State<Scheduler>,
+    name: Option<String>,
+    prompt: String,
+    schedule: String,
+    work_path: Option<String>,
+) -> Result<ScheduledTask, AppError> {
+    let prompt = prompt.trim().to_string();
+    if prompt.is_empty() {
+        return Err(AppError::invalid_input("A scheduled task needs a prompt."));
+    }
+    let schedule = schedule.trim().to_string();
+    let next_run_at = Schedule::parse(&schedule)
+        .map_err(AppError::invalid_input)?
+        .next_after(Local::now())
+        .map(|next| next.to_rfc3339());
+    let work_path = work_path.filter(|path| !path.trim().is_empty());
+    if let Some(path) = &work_path {
+        if !std::path::Path::new(path).is_dir() {
+            return Err(AppError::not_found(format!("{path} is not a folder.")));
+        }
+    }
+    let now = Local::now();
+    let task = ScheduledTask {
+        id: format!(
+            "{}-{}",
+            now.timestamp_millis(),
+            scheduler.next_id.fetch_add(1, Ordering::SeqCst)
+        ),
+        name: name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| prompt.chars().take(40).collect()),
+        prompt,
+        schedule,
+        work_path,
+        created_at: now.to_rfc3339(),
+        next_run_at,
+        last_run: None,
+    };
+    scheduler.update(|tasks| tasks.push(task.clone()))?;
+    tracing::info!("[Scheduler] Scheduled {} ({}).", task.name, task.schedule);
+    Ok(task)
+}
+
+/// Oldest first.
+#[tauri::command]
+pub fn list_scheduled_tasks(
+    scheduler: tauri::State<Scheduler>,
+) -> Result<Vec<ScheduledTask>, AppError> {
+    let tasks = scheduler
+        .tasks
+        .lock()
+        .map_err(|_| AppError::unavailable("Scheduled tasks are unavailable."))?;
+    Ok(tasks.clone())
+}
+
+/// A run already under way finishes; its result is still notified.
+#[tauri::command]
+pub fn delete_scheduled_task(
+    scheduler: tauri::State<Scheduler>,
+    id: String,
+) -> Result<(), AppError> {
+    let removed = scheduler.update(|tasks| {
+        let before = tasks.len();
+        tasks.retain(|task| task.id != id);
+        tasks.len() != before
+    })?;
+    if !removed {
+        return Err(AppError::not_found("That task is no longer scheduled."));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .earliest()
+            .expect("valid local time")
+    }
+
+    fn bits(values: &[u32]) -> u64 {
+        values.iter().fold(0, |bits, value| bits | 1 << value)
+    }
+
+    #[test]
+    fn parse_field_reads_steps_ranges_and_lists() {
+        assert_eq!(
+            parse_field("*/15", 0, 59),
+            Ok((bits(&[0, 15, 30, 45]), true))
+        );
+        assert_eq!(
+            parse_field("1-5,10", 0, 59),
+            Ok((bits(&[1, 2, 3, 4, 5, 10]), false))
+        );
+        assert_eq!(parse_field("5/20", 0, 59), Ok((bits(&[5, 25, 45]), false)));
+        assert_eq!(
+            parse_field("10-20/5", 0, 59),
+            Ok((bits(&[10, 15, 20]), false))
+        );
+    }
+
+    #[test]
+    fn parse_field_rejects_bad_values() {
+        assert!(parse_field("60", 0, 59).is_err());
+        assert!(parse_field("0", 1, 31).is_err());
+        assert!(parse_field("5-1", 0, 59).is_err());
+        assert!(parse_field("*/0", 0, 59).is_err());
+        assert!(parse_field("x", 0, 59).is_err());
+        assert!(parse_field("", 0, 59).is_err());
+    }
+
+    #[test]
+    fn schedule_parse_handles_aliases_and_sunday_as_seven() {
+        let daily = Schedule::parse("@daily").expect("daily");
+        assert_eq!((daily.minutes, daily.hours), (1, 1));
+        let sunday = Schedule::parse("0 0 * * 7").expect("sunday");
+        assert_eq!(sunday.weekdays, 1);
+        assert!(Schedule::parse("0 0 * *").is_err());
+        let never = Schedule::parse("0 0 30 2 *").expect_err("February 30th");
+        assert!(never.contains("never comes around"), "{never}");
+    }
+
+    #[test]
+    fn next_after_finds_the_next_matching_minute() {
+        let weekdays = Schedule::parse("0 9 * * 1-5").expect("weekdays");
+        // 2026-10-12 is a Monday.
+        assert_eq!(
+            weekdays.next_after(at(2026, 10, 12, 8, 30)),
+            Some(at(2026, 10, 12, 9, 0))
+        );
+        assert_eq!(
+            weekdays.next_after(at(2026, 10, 12, 9, 0)),
+            Some(at(2026, 10, 13, 9, 0))
+        );
+        assert_eq!(
+            weekdays.next_after(at(2026, 10, 16, 10, 0)),
+            Some(at(2026, 10, 19, 9, 0))
+        );
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // The 13th or any Friday.
+        let either = Schedule::parse("0 0 13 * 5").expect("either");
+        assert_eq!(
+            either.next_after(at(2026, 10, 12, 0, 0)),
+            Some(at(2026, 10, 13, 0, 0))
+        );
+        assert_eq!(
+            either.next_after(at(2026, 10, 13, 0, 0)),
+            Some(at(2026, 10, 16, 0, 0))
+        );
+        // A stepped `*` still counts as open, so both fields must match.
+        let both = Schedule::parse("0 0 */2 * 1").expect("both");
+        assert_eq!(
+            both.next_after(at(2026, 10, 12, 0, 0)),
+            Some(at(2026, 10, 19, 0, 0))
+        );
+    }
+}
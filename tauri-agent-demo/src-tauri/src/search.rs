@@ -0,0 +1,469 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use rusqlite::{params, params_from_iter, types::Value as SqlValue, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Listener, Manager, Runtime};
+
+use crate::{encryption, error::AppError, workspace};
+
+/// Lives in the workspace folder next to the database it indexes.
+const INDEX_FILE: &str = "search_index.sqlite";
+/// Searches sync first anyway; this keeps the first one after a long run fast.
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 200;
+/// Trigram tokens, so roughly characters.
+const SNIPPET_TOKENS: i64 = 48;
+/// Characters either side of the first hit when the snippet is cut in Rust.
+const EXCERPT_RADIUS: usize = 60;
+/// Marks matched text inside a snippet; never present in stored messages.
+const MARK_START: char = '\u{2}';
+const MARK_END: char = '\u{3}';
+
+/// Messages of the active workspace in an FTS5 table, kept current by tailing the
+/// backend's `message_changes` log instead of asking the backend.
+#[derive(Default)]
+pub struct SearchIndex(Mutex<Option<(PathBuf, Connection)>>);
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchFilters {
+    session_id: Option<String>,
+    /// `user` or `assistant`; only those are indexed.
+    role: Option<String>,
+    /// ISO 8601 bounds on the message timestamp, `until` exclusive.
+    since: Option<String>,
+    until: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnippetPart {
+    text: String,
+    highlight: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageHit {
+    message_id: i64,
+    session_id: String,
+    session_title: Option<String>,
+    role: String,
+    timestamp: String,
+    snippet: Vec<SnippetPart>,
+}
+
+/// Keyed like the database it indexes, since it holds the same text.
+fn open_index(path: &Path) -> Result<Connection, String> {
+    let conn = encryption::open(path, OpenFlags::default())
+        .map_err(|err| format!("Failed to open the search index: {err}"))?;
+    // Trigrams match inside words and need no word breaks, which CJK text lacks.
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS messages USING fts5(
+             content, session_id UNINDEXED, role UNINDEXED, timestamp UNINDEXED,
+             tokenize = 'trigram'
+         );
+         CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value INTEGER NOT NULL);",
+    )
+    .map_err(|err| format!("Failed to create the search index: {err}"))?;
+    Ok(conn)
+}
+
+fn open_source(db_path: &Path) -> Result<Connection, String> {
+    if !db_path.exists() {
+        return Err("Search needs the local backend's database.".to_string());
+    }
+    encryption::open(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|err| format!("Failed to open chat database: {err}"))
+}
+
+impl SearchIndex {
+    /// Runs `f` on the active workspace's index, reopening it after a switch.
+    fn with<R: Runtime, T>(
+        &self,
+        app: &AppHandle<R>,
+        f: impl FnOnce(&mut Connection) -> Result<T, String>,
+    ) -> Result<T, String> {
+        if encryption::is_locked(app) {
+            return Err("Search is unavailable while the chat database is locked.".to_string());
+        }
+        let path = workspace::data_dir(app)?.join(INDEX_FILE);
+        let mut guard = self
+            .0
+            .lock()
+            .map_err(|_| "Search index is unavailable.".to_string())?;
+        if guard.as_ref().is_none_or(|(open, _)| *open != path) {
+            *guard = Some((path.clone(), open_index(&path)?));
+        }
+        let (_, conn) = guard.as_mut().ok_or("Search index is unavailable.")?;
+        f(conn)
+    }
+}
+
+fn copy_message(source: &Connection, index: &Connection, id: i64) -> rusqlite::Result<bool> {
+    let mut select = source.prepare_cached(
+        "SELECT session_id, role, content, timestamp FROM chat_messages
+         WHERE id = ?1 AND role IN ('user', 'assistant')",
+    )?;
+    let mut rows = select.query(params![id])?;
+    let Some(row) = rows.next()? else {
+        return Ok(false);
+    };
+    index.execute(
+        "INSERT INTO messages (rowid, session_id, role, content, timestamp)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            id,
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?
+        ],
+    )?;
+    Ok(true)
+}
+
+/// Applies the changes since the last sync, or rebuilds when the log no longer
+/// reaches back that far (pruned, or the backend recreated its schema).
+fn sync<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let source = open_source(&workspace::db_path(app)?)?;
+    let has_log = source
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'message_changes'",
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .map_err(|err| format!("Failed to read chat database: {err}"))?
+        > 0;
+    if !has_log {
+        return Err("The backend does not keep a change log yet; restart it.".to_string());
+    }
+    let (first, last): (Option<i64>, Option<i64>) = source
+        .query_row(
+            "SELECT MIN(seq), MAX(seq) FROM message_changes",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|err| format!("Failed to read the change log: {err}"))?;
+    let last = last.unwrap_or(0);
+    let app_state = app.state::<SearchIndex>();
+    app_state.with(app, |index| {
+        let cursor: Option<i64> = index
+            .query_row("SELECT value FROM meta WHERE key = 'cursor'", [], |row| {
+                row.get(0)
+            })
+            .ok();
+        if cursor == Some(last) {
+            return Ok(());
+        }
+        let rebuild = match cursor {
+            None => true,
+            Some(cursor) => last < cursor || first.is_some_and(|first| first > cursor + 1),
+        };
+        let mut run = || -> rusqlite::Result<usize> {
+            let tx = index.transaction()?;
+            let mut touched = 0;
+            if rebuild {
+                tx.execute("DELETE FROM messages", [])?;
+                let mut ids = source
+                    .prepare("SELECT id FROM chat_messages WHERE role IN ('user', 'assistant')")?;
+                let ids: Vec<i64> = ids
+                    .query_map([], |row| row.get(0))?
+                    .collect::<rusqlite::Result<_>>()?;
+                for id in ids {
+                    touched += usize::from(copy_message(&source, &tx, id)?);
+                }
+            } else {
+                let mut changed = source
+                    .prepare("SELECT DISTINCT message_id FROM message_changes WHERE seq > ?1")?;
+                let ids: Vec<i64> = changed
+                    .query_map(params![cursor.unwrap_or(0)], |row| row.get(0))?
+                    .collect::<rusqlite::Result<_>>()?;
+                for id in ids {
+                    tx.execute("DELETE FROM messages WHERE rowid = ?1", params![id])?;
+                    copy_message(&source, &tx, id)?;
+                    touched += 1;
+                }
+            }
+            tx.execute(
+                "INSERT INTO meta (key, value) VALUES ('cursor', ?1)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![last],
+            )?;
+            tx.commit()?;
+            Ok(touched)
+        };
+        let touched = run().map_err(|err| format!("Failed to update the search index: {err}"))?;
+        if rebuild {
+            tracing::info!("[Search] Rebuilt the index with {touched} message(s).");
+        }
+        Ok(())
+    })
+}
+
+/// A quoted FTS5 phrase, so operators in the query are taken literally.
+fn phrase(term: &str) -> String {
+    format!("\"{}\"", term.replace('"', "\"\""))
+}
+
+fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+/// Around the first of `terms` in `content`, with every occurrence marked; used
+/// when only terms too short for trigram matching were given.
+fn excerpt(content: &str, terms: &[&str]) -> String {
+    // Lowercasing can change byte lengths; match case-sensitively if it did, so
+    // offsets stay valid in both strings.
+    let lower = Some(content.to_lowercase())
+        .filter(|lower| lower.len() == content.len())
+        .unwrap_or_else(|| content.to_string());
+    let first = terms
+        .iter()
+        .filter_map(|term| lower.find(&term.to_lowercase()))
+        .min()
+        .unwrap_or(0);
+    let chars: Vec<(usize, char)> = content.char_indices().collect();
+    let at = chars.partition_point(|(index, _)| *index < first);
+    let start = at.saturating_sub(EXCERPT_RADIUS);
+    let end = (at + EXCERPT_RADIUS).min(chars.len());
+    let (start_byte, end_byte) = (
+        chars.get(start).map_or(content.len(), |(index, _)| *index),
+        chars.get(end).map_or(content.len(), |(index, _)| *index),
+    );
+    let window = &content[start_byte..end_byte];
+    let window_lower = &lower[start_byte..end_byte];
+    let mut marked = String::new();
+    if start > 0 {
+        marked.push('…');
+    }
+    let mut cursor = 0;
+    while cursor < window.len() {
+        let hit = terms
+            .iter()
+            .filter_map(|term| {
+                let term = term.to_lowercase();
+                window_lower[cursor..]
+                    .find(&term)
+                    .map(|offset| (cursor + offset, term.len()))
+            })
+            .min();
+        let Some((position, len)) = hit else {
+            marked.push_str(&window[cursor..]);
+            break;
+        };
+        marked.push_str(&window[cursor..position]);
+        marked.push(MARK_START);
+        marked.push_str(&window[position..position + len]);
+        marked.push(MARK_END);
+        cursor = position + len;
+    }
+    if end < chars.len() {
+        marked.push('…');
+    }
+    marked
+}
+
+fn parts(marked: &str) -> Vec<SnippetPart> {
+    let mut parts = Vec::new();
+    let mut highlight = false;
+    let mut text = String::new();
+    for ch in marked.chars() {
+        if ch == MARK_START || ch == MARK_END {
+            if !text.is_empty() {
+                parts.push(SnippetPart {
+                    text: std::mem::take(&mut text),
+                    highlight,
+                });
+            }
+            highlight = ch == MARK_START;
+        } else {
+            text.push(ch);
+        }
+    }
+    if !text.is_empty() {
+        parts.push(SnippetPart { text, highlight });
+    }
+    parts
+}
+
+fn session_titles(source: &Connection, ids: &[String]) -> HashMap<String, String> {
+    if ids.is_empty() {
+        return HashMap::new();
+    }
+    let placeholders = vec!["?"; ids.len()].join(", ");
+    let sql = format!("SELECT id, title FROM chat_sessions WHERE id IN ({placeholders})");
+    let Ok(mut stmt) = source.prepare(&sql) else {
+        return HashMap::new();
+    };
+    stmt.query_map(params_from_iter(ids), |row| Ok((row.get(0)?, row.get(1)?)))
+        .and_then(|rows| rows.collect())
+        .unwrap_or_default()
+}
+
+fn search<R: Runtime>(
+    app: &AppHandle<R>,
+    query: &str,
+    filters: SearchFilters,
+) -> Result<Vec<MessageHit>, AppError> {
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if terms.is_empty() {
+        return Err(AppError::invalid_input("Type something to search for."));
+    }
+    // Trigram matching needs three characters; shorter terms are matched with LIKE.
+    let (long, short): (Vec<&str>, Vec<&str>) =
+        terms.iter().partition(|term| term.chars().count() >= 3);
+    if let Err(err) = sync(app) {
+        // Stale results beat none; the next sync catches up.
+        tracing::warn!("[Search] {err}");
+    }
+
+    let mut sql = String::from("SELECT rowid, session_id, role, timestamp, ");
+    let mut args: Vec<SqlValue> = Vec::new();
+    if long.is_empty() {
+        sql.push_str("content FROM messages WHERE 1 = 1");
+    } else {
+        sql.push_str(&format!(
+            "snippet(messages, 0, char(2), char(3), '…', {SNIPPET_TOKENS})
+             FROM messages WHERE messages MATCH ?"
+        ));
+        let phrases: Vec<String> = long.iter().map(|term| phrase(term)).collect();
+        args.push(SqlValue::Text(phrases.join(" ")));
+    }
+    for term in &short {
+        sql.push_str(" AND content LIKE ? ESCAPE '\\'");
+        args.push(SqlValue::Text(like_pattern(term)));
+    }
+    let conditions = [
+        ("session_id = ?", filters.session_id),
+        ("role = ?", filters.role),
+        ("timestamp >= ?", filters.since),
+        ("timestamp < ?", filters.until),
+    ];
+    for (condition, value) in conditions {
+        if let Some(value) = value {
+            sql.push_str(" AND ");
+            sql.push_str(condition);
+            args.push(SqlValue::Text(value));
+        }
+    }
+    sql.push_str(if long.is_empty() {
+        " ORDER BY timestamp DESC"
+    } else {
+        " ORDER BY bm25(messages)"
+    });
+    sql.push_str(" LIMIT ? OFFSET ?");
+    let limit = filters.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    args.push(SqlValue::Integer(limit as i64));
+    args.push(SqlValue::Integer(filters.offset.unwrap_or(0) as i64));
+
+    let rows: Vec<(i64, String, String, String, String)> =
+        app.state::<SearchIndex>().with(app, |index| {
+            let mut stmt = index
+                .prepare(&sql)
+                .map_err(|err| format!("Failed to search: {err}"))?;
+            stmt.query_map(params_from_iter(args), |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            })
+            .and_then(|rows| rows.collect())
+            .map_err(|err| format!("Failed to search: {err}"))
+        })?;
+
+    let mut session_ids: Vec<String> = rows.iter().map(|row| row.1.clone()).collect();
+    session_ids.sort();
+    session_ids.dedup();
+    let titles = workspace::db_path(app)
+        .and_then(|path| open_source(&path))
+        .map(|source| session_titles(&source, &session_ids))
+        .unwrap_or_default();
+    Ok(rows
+        .into_iter()
+        .map(|(message_id, session_id, role, timestamp, text)| {
+            let marked = if long.is_empty() {
+                excerpt(&text, &short)
+            } else {
+                text
+            };
+            MessageHit {
+                message_id,
+                session_title: titles.get(&session_id).cloned(),
+                session_id,
+                role,
+                timestamp,
+                snippet: parts(&marked),
+            }
+        })
+        .collect())
+}
+
+/// Deletes every workspace's index, so each is rebuilt under the current key on its
+/// next sync; for when the chat databases were encrypted or rekeyed.
+pub fn discard<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(index) = app.try_state::<SearchIndex>() {
+        if let Ok(mut guard) = index.0.lock() {
+            *guard = None;
+        }
+    }
+    for dir in workspace::data_dirs(app) {
+        let path = dir.join(INDEX_FILE);
+        if path.exists() {
+            if let Err(err) = fs::remove_file(&path) {
+                tracing::warn!("[Search] Failed to remove {}: {err}", path.display());
+            }
+        }
+    }
+}
+
+/// Keeps the index current: after every run on the chat bridge and every
+/// `SYNC_INTERVAL`, for runs the webview streamed itself.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let handle = app.clone();
+    app.listen_any("chat://done", move |_| {
+        let handle = handle.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Err(err) = sync(&handle) {
+                tracing::debug!("[Search] {err}");
+            }
+        });
+    });
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let handle = app.clone();
+            let synced = tauri::async_runtime::spawn_blocking(move || sync(&handle)).await;
+            if let Ok(Err(err)) = synced {
+                tracing::debug!("[Search] {err}");
+            }
+            tokio::time::sleep(SYNC_INTERVAL).await;
+        }
+    });
+}
+
+/// Best matches first; with only one- and two-character terms, newest first.
+/// Snippets come split into highlighted and plain parts, never as HTML.
+#[tauri::command]
+pub async fn search_messages(
+    app: AppHandle,
+    query: String,
+    filters: Option<SearchFilters>,
+) -> Result<Vec<MessageHit>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || search(&app, &query, filters.unwrap_or_default()))
+        .await
+        .map_err(|err| AppError::from(format!("Search task failed: {err}")))?
+}
@@ -0,0 +1,179 @@
+use keyring::Entry;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{error::AppError, settings::SettingsStore};
+
+const MAX_SECRET_BYTES: usize = 16 * 1024;
+/// Names the shell sets itself when spawning the sidecar.
+const RESERVED_PREFIX: &str = "TAURI_AGENT_";
+const RESERVED_KEYS: &[&str] = &["APP_CONFIG_PATH", "TOOLS_CONFIG_PATH", "PATH"];
+
+/// Secrets are handed to the sidecar as environment variables, so their names
+/// have to be valid ones.
+fn validate_key(key: &str) -> Result<(), AppError> {
+    let mut chars = key.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|first| first.is_ascii_uppercase() || first == '_')
+        && chars.all(|char| char.is_ascii_uppercase() || char.is_ascii_digit() || char == '_');
+    if !valid {
+        return Err(AppError::invalid_input(format!(
+            "'{key}' is not a valid secret name; use upper-case letters, digits and underscores."
+        )));
+    }
+    if key.starts_with(RESERVED_PREFIX) || RESERVED_KEYS.contains(&key) {
+        return Err(AppError::invalid_input(format!(
+            "'{key}' is set by the app itself and cannot be a secret."
+        )));
+    }
+    Ok(())
+}
+
+/// Keychain entries are grouped under the app identifier, one per secret name.
+fn entry<R: Runtime>(app: &AppHandle<R>, key: &str) -> Result<Entry, AppError> {
+    Entry::new(&app.config().identifier, key).map_err(|err| {
+        AppError::unavailable("The system keychain is unavailable.").with_details(err)
+    })
+}
+
+/// Stored secrets by name, for the sidecar's environment. Secrets the keychain can
+/// no longer produce are skipped with a warning rather than failing the spawn.
+pub fn environment<R: Runtime>(app: &AppHandle<R>) -> Vec<(String, String)> {
+    let Some(store) = app.try_state::<SettingsStore>() else {
+        return Vec::new();
+    };
+    store
+        .get()
+        .secret_keys
+        .into_iter()
+        .filter_map(|key| {
+            let value = entry(app, &key).and_then(|entry| {
+                entry.get_password().map_err(|err| {
+                    AppError::unavailable("Failed to read a secret.").with_details(err)
+                })
+            });
+            match value {
+                Ok(value) => Some((key, value)),
+                Err(err) => {
+                    tracing::warn!("[Secrets] Skipping {key}: {err}");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Writes `value` to the keychain without listing it in settings; callers add the
+/// name to `secret_keys` themselves.
+pub fn store<R: Runtime>(app: &AppHandle<R>, key: &str, value: &str) -> Result<(), AppError> {
+    validate_key(key)?;
+    if value.is_empty() {
+        return Err(AppError::invalid_input("A secret cannot be empty."));
+    }
+    if value.len() > MAX_SECRET_BYTES {
+        return Err(AppError::invalid_input(format!(
+            "Secrets are limited to {} KB.",
+            MAX_SECRET_BYTES / 1024
+        )));
+    }
+    entry(app, key)?
+        .set_password(value)
+        .map_err(|err| AppError::unavailable("Failed to store the secret.").with_details(err))
+}
+
+/// Stores `value` in the OS keychain; the backend sees it as `$key` from its next start.
+#[tauri::command]
+pub fn set_secret(
+    app: AppHandle,
+    store: tauri::State<SettingsStore>,
+    key: String,
+    value: String,
+) -> Result<(), AppError> {
+    self::store(&app, &key, &value)?;
+    store.update(|settings| {
+        settings.secret_keys.insert(key);
+    })?;
+    Ok(())
+}
+
+/// Whether a secret is stored under `key`. Values are only read for the sidecar's
+/// environment and never returned to a window.
+#[tauri::command]
+pub fn has_secret(app: AppHandle, key: String) -> Result<bool, AppError> {
+    validate_key(&key)?;
+    match entry(&app, &key)?.get_password() {
+        Ok(_) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(err) => Err(AppError::unavailable("Failed to read the secret.").with_details(err)),
+    }
+}
+
+/// Removes a secret; returns false if there was none by that name.
+#[tauri::command]
+pub fn delete_secret(
+    app: AppHandle,
+    store: tauri::State<SettingsStore>,
+    key: String,
+) -> Result<bool, AppError> {
+    validate_key(&key)?;
+    let existed = match entry(&app, &key)?.delete_credential() {
+        Ok(()) => true,
+        Err(keyring::Error::NoEntry) => false,
+        Err(err) => {
+            return Err(AppError::unavailable("Failed to delete the secret.").with_details(err))
+        }
+    };
+    let mut listed = false;
+    store.update(|settings| listed = settings.secret_keys.remove(&key))?;
+    Ok(existed || listed)
+}
+
+/// Names of the stored secrets; keychains cannot be listed, so the names are kept
+/// in shell settings while the values stay in the keychain.
+#[tauri::command]
+pub fn list_secret_keys(store: tauri::State<SettingsStore>) -> Vec<String> {
+    store.get().secret_keys.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+
+    #[test]
+    fn environment_variable_names_are_accepted() {
+        for key in ["TAVILY_API_KEY", "_PRIVATE", "KEY2", "A"] {
+            assert!(validate_key(key).is_ok(), "{key}");
+        }
+    }
+
+    #[test]
+    fn names_that_are_not_variable_names_are_rejected() {
+        for key in [
+            "",
+            "tavily_api_key",
+            "2KEY",
+            "API-KEY",
+            "API KEY",
+            "KEY=1",
+            "ÄPI",
+        ] {
+            let error = validate_key(key).expect_err(key);
+            assert_eq!(error.code, ErrorCode::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn names_the_app_sets_itself_are_rejected() {
+        for key in [
+            "TAURI_AGENT_TOOL_HOST",
+            "TAURI_AGENT_",
+            "APP_CONFIG_PATH",
+            "TOOLS_CONFIG_PATH",
+            "PATH",
+        ] {
+            assert!(validate_key(key).is_err(), "{key}");
+        }
+        assert!(validate_key("PATHS").is_ok());
+    }
+}
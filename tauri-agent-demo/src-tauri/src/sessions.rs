@@ -0,0 +1,316 @@
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, SocketAddr},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::Local;
+use ring::rand::{SecureRandom, SystemRandom};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    backend::{self, SHUTDOWN_PATH},
+    error::AppError,
+    events, network, proxy,
+    settings::BackendTransport,
+    sidecar, stale_backend,
+    temp_files::{self, TempDir},
+    BackendState,
+};
+
+/// Proxied requests under `/_session/<id>/` go to that session's worker. The
+/// underscore keeps it clear of the backend's own `/sessions` routes.
+const ROUTE_PREFIX: &str = "/_session/";
+const SIDECAR_PREFIX: &str = "session-";
+/// Each worker is a full backend process; past a few they mostly compete for the
+/// same CPU and model quota.
+const MAX_SESSIONS: usize = 4;
+const MAX_LABEL_CHARS: usize = 80;
+const READY_POLL: Duration = Duration::from_millis(500);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+const SESSION_ENV: &str = "TAURI_AGENT_SESSION_ID";
+
+/// A backend worker started next to the main one, so a heavy run there does not
+/// hold up the main chat. It shares the database and settings but has its own
+/// port, token and scratch directory.
+struct Session {
+    label: Option<String>,
+    created_at: String,
+    host: IpAddr,
+    port: u16,
+    token: String,
+    /// Removed along with the session.
+    scratch: TempDir,
+}
+
+impl Session {
+    fn base_url(&self) -> String {
+        format!(
+            "http://{}",
+            SocketAddr::new(network::connect_host(self.host), self.port)
+        )
+    }
+}
+
+/// The running sessions by id; they end with the app.
+#[derive(Default)]
+pub struct Sessions(Mutex<BTreeMap<String, Session>>);
+
+events::payload! {
+    pub struct SessionInfo {
+        id: String,
+        label: Option<String>,
+        created_at: String,
+        port: u16,
+        /// False while the worker restarts after a crash.
+        running: bool,
+        /// Where the webview sends this session's requests.
+        url: String,
+        /// Needed on `url` unless it goes through the shell's proxy.
+        token: Option<String>,
+        scratch_dir: String,
+    }
+}
+
+events::payload! {
+    pub struct SessionList {
+        sessions: Vec<SessionInfo>,
+    }
+}
+
+/// Where a proxied request for a session goes, with the prefix taken off its path.
+pub struct Route {
+    pub base_url: String,
+    pub token: String,
+    pub path: String,
+}
+
+fn sidecar_name(id: &str) -> String {
+    format!("{SIDECAR_PREFIX}{id}")
+}
+
+fn random_hex(bytes: usize) -> Result<String, AppError> {
+    let mut buffer = vec![0u8; bytes];
+    SystemRandom::new()
+        .fill(&mut buffer)
+        .map_err(|_| AppError::from("Failed to generate a session id.".to_string()))?;
+    Ok(buffer.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+fn info<R: Runtime>(app: &AppHandle<R>, id: &str, session: &Session) -> SessionInfo {
+    let proxied = proxy::is_enabled(app);
+    SessionInfo {
+        id: id.to_string(),
+        label: session.label.clone(),
+        created_at: session.created_at.clone(),
+        port: session.port,
+        running: sidecar::is_running(app, &sidecar_name(id)),
+        url: if proxied {
+            format!("{}{ROUTE_PREFIX}{id}", proxy::base_url())
+        } else {
+            session.base_url()
+        },
+        token: (!proxied).then(|| session.token.clone()),
+        scratch_dir: session.scratch.path().display().to_string(),
+    }
+}
+
+fn list<R: Runtime>(app: &AppHandle<R>) -> Vec<SessionInfo> {
+    app.try_state::<Sessions>()
+        .and_then(|sessions| {
+            let sessions = sessions.0.lock().ok()?;
+            Some(
+                sessions
+                    .iter()
+                    .map(|(id, session)| info(app, id, session))
+                    .collect(),
+            )
+        })
+        .unwrap_or_default()
+}
+
+fn announce<R: Runtime>(app: &AppHandle<R>) {
+    events::sessions_changed(
+        app,
+        &SessionList {
+            sessions: list(app),
+        },
+    );
+}
+
+/// Each session worker's address and token, for requests the shell makes itself.
+pub fn endpoints<R: Runtime>(app: &AppHandle<R>) -> Vec<(String, String)> {
+    app.try_state::<Sessions>()
+        .and_then(|sessions| {
+            let sessions = sessions.0.lock().ok()?;
+            Some(
+                sessions
+                    .values()
+                    .map(|session| (session.base_url(), session.token.clone()))
+                    .collect(),
+            )
+        })
+        .unwrap_or_default()
+}
+
+pub fn route<R: Runtime>(app: &AppHandle<R>, path: &str) -> Option<Result<Route, String>> {
+    let rest = path.strip_prefix(ROUTE_PREFIX)?;
+    let (id, rest) = rest.split_at(rest.find(['/', '?']).unwrap_or(rest.len()));
+    let sessions = app.try_state::<Sessions>()?;
+    let sessions = sessions.0.lock().ok()?;
+    let Some(session) = sessions.get(id) else {
+        return Some(Err(format!("Session {id} does not exist.")));
+    };
+    Some(Ok(Route {
+        base_url: session.base_url(),
+        token: session.token.clone(),
+        path: if rest.starts_with('/') {
+            rest.to_string()
+        } else {
+            format!("/{rest}")
+        },
+    }))
+}
+
+/// Asks a worker to exit on its own, the way `backend::request_shutdown` does for
+/// the main one.
+fn request_shutdown(base_url: String, token: String) -> bool {
+    thread::spawn(move || {
+        tauri::async_runtime::block_on(async move {
+            proxy::client_with_token(Some(&token))
+                .post(format!("{base_url}{SHUTDOWN_PATH}"))
+                .timeout(REQUEST_TIMEOUT)
+                .send()
+                .await
+                .is_ok_and(|response| response.status().is_success())
+        })
+    })
+    .join()
+    .unwrap_or(false)
+}
+
+async fn wait_ready(base_url: &str, token: &str, timeout: Duration) -> Result<(), String> {
+    let client = proxy::client_with_token(Some(token));
+    let started = Instant::now();
+    while started.elapsed() < timeout {
+        let healthy = client
+            .get(format!("{base_url}/health"))
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .is_ok_and(|response| response.status().is_success());
+        if healthy {
+            return Ok(());
+        }
+        tokio::time::sleep(READY_POLL).await;
+    }
+    Err(format!(
+        "The session backend did not answer /health within {}s.",
+        timeout.as_secs()
+    ))
+}
+
+/// Starts another backend worker on a port of its own and waits for it to answer.
+/// Only for the bundled backend; external and remote ones are not launched here.
+#[tauri::command]
+pub async fn create_session(
+    app: AppHandle,
+    label: Option<String>,
+) -> Result<SessionInfo, AppError> {
+    let state = app
+        .try_state::<BackendState>()
+        .ok_or_else(|| AppError::unavailable("Backend is not configured yet."))?;
+    if !crate::runs_sidecar(&app) {
+        return Err(AppError::unavailable(
+            "Sessions need the bundled backend, and this app is not running one.",
+        ));
+    }
+    let host = state.host;
+    let label = label
+        .map(|label| {
+            label
+                .trim()
+                .chars()
+                .take(MAX_LABEL_CHARS)
+                .collect::<String>()
+        })
+        .filter(|label| !label.is_empty());
+    let sessions = app.state::<Sessions>();
+    let count = sessions
+        .0
+        .lock()
+        .map_err(|_| AppError::unavailable("Sessions are unavailable."))?
+        .len();
+    if count >= MAX_SESSIONS {
+        return Err(AppError::invalid_input(format!(
+            "At most {MAX_SESSIONS} sessions can run at once; terminate one first."
+        )));
+    }
+    let id = random_hex(6)?;
+    let token = random_hex(32)?;
+    let port = crate::pick_backend_port(host)?;
+    let scratch = temp_files::scoped(&app, "sessions")?;
+    let session = Session {
+        label,
+        created_at: Local::now().to_rfc3339(),
+        host,
+        port,
+        token: token.clone(),
+        scratch,
+    };
+    let base_url = session.base_url();
+    let stop_url = base_url.clone();
+    let stop_token = token.clone();
+    let mut spec = crate::backend_spec(&app, host, port, BackendTransport::Tcp)?
+        .on_stop(move || request_shutdown(stop_url.clone(), stop_token.clone()))
+        .env(proxy::TOKEN_ENV, &token)
+        .env("TAURI_AGENT_TEMP_DIR", session.scratch.path())
+        .env(SESSION_ENV, &id)
+        // Stale-backend takeover only tracks the main backend.
+        .env(stale_backend::PID_FILE_ENV, "");
+    spec.name = sidecar_name(&id);
+    tracing::info!("[Sessions] Starting session {id} on port {port}.");
+    sidecar::start(&app, spec)?;
+    let timeout = Duration::from_secs(backend::readiness_settings(&app).timeout_secs.max(1));
+    if let Err(err) = wait_ready(&base_url, &token, timeout).await {
+        let _ = sidecar::remove(&app, &sidecar_name(&id));
+        return Err(AppError::unavailable(err));
+    }
+    let created = info(&app, &id, &session);
+    sessions
+        .0
+        .lock()
+        .map_err(|_| AppError::unavailable("Sessions are unavailable."))?
+        .insert(id, session);
+    announce(&app);
+    Ok(created)
+}
+
+#[tauri::command]
+pub fn list_sessions(app: AppHandle) -> Vec<SessionInfo> {
+    list(&app)
+}
+
+/// Stops the session's worker and removes its scratch directory. Runs still going
+/// there are cut off.
+#[tauri::command]
+pub async fn terminate_session(app: AppHandle, id: String) -> Result<(), AppError> {
+    let session = app
+        .state::<Sessions>()
+        .0
+        .lock()
+        .map_err(|_| AppError::unavailable("Sessions are unavailable."))?
+        .remove(&id)
+        .ok_or_else(|| AppError::not_found(format!("Session {id} does not exist.")))?;
+    let handle = app.clone();
+    let name = sidecar_name(&id);
+    tauri::async_runtime::spawn_blocking(move || sidecar::remove(&handle, &name))
+        .await
+        .map_err(|err| AppError::from(format!("Session task failed: {err}")))??;
+    drop(session);
+    tracing::info!("[Sessions] Terminated session {id}.");
+    announce(&app);
+    Ok(())
+}
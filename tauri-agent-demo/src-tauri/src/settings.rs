@@ -0,0 +1,233 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_lock::LockSettings,
+    appearance::AppearanceSettings,
+    atomic_file,
+    attachments::AttachmentSettings,
+    autostart::AutostartSettings,
+    backend::{BackendEnvSettings, ReadinessSettings, ShutdownSettings},
+    backup::BackupSettings,
+    costs::CostSettings,
+    diagnostics::DiagnosticsSettings,
+    dialogs::DialogPurpose,
+    embeddings::EmbeddingSettings,
+    encryption::EncryptionSettings,
+    error::{AppError, ErrorCode},
+    events,
+    indexer::IndexingSettings,
+    monitor::MonitorSettings,
+    monitors::WindowGeometry,
+    notifications::NotificationSettings,
+    plugins::PluginSettings,
+    proxy_config::ProxySettings,
+    python_runtime::PythonRuntimeSettings,
+    shortcuts::ShortcutAction,
+    sidecar::RestartPolicy,
+    speech::SpeechSettings,
+    tray::TraySettings,
+    updater::UpdateSettings,
+    watchdog::WatchdogSettings,
+    workspace::WorkspaceSettings,
+};
+
+/// Preferences owned by the shell itself. Kept apart from `app_config.json`, which the
+/// backend reads and rewrites on its own schedule.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellSettings {
+    pub notifications: NotificationSettings,
+    pub backend: BackendSettings,
+    pub costs: CostSettings,
+    pub inbox: InboxSettings,
+    /// Accelerators the user changed; actions missing here use their defaults.
+    pub shortcuts: BTreeMap<ShortcutAction, String>,
+    /// Read-only kiosk mode; only launching with `--no-kiosk` clears it.
+    pub kiosk: bool,
+    pub webview: WebviewSettings,
+    pub embeddings: EmbeddingSettings,
+    pub indexing: IndexingSettings,
+    pub plugins: PluginSettings,
+    pub appearance: AppearanceSettings,
+    pub lock: LockSettings,
+    pub backup: BackupSettings,
+    /// Last closed position of each window, keyed by label.
+    pub window_geometry: BTreeMap<String, WindowGeometry>,
+    /// Folder each kind of file dialog last ended up in.
+    pub dialog_dirs: BTreeMap<DialogPurpose, String>,
+    pub tray: TraySettings,
+    /// Names of the secrets held in the OS keychain; the values never touch disk.
+    pub secret_keys: BTreeSet<String>,
+    /// Whether the chat database is encrypted, and how to check the passphrase.
+    pub encryption: EncryptionSettings,
+    pub updates: UpdateSettings,
+    pub diagnostics: DiagnosticsSettings,
+    /// How the backend reaches LLM providers: proxy and extra root certificates.
+    pub outbound_proxy: ProxySettings,
+    /// Resource sampling of the sidecar and its memory ceiling.
+    pub monitor: MonitorSettings,
+    /// Time limit for agent runs and how a stuck one is ended.
+    pub watchdog: WatchdogSettings,
+    /// Created workspaces and the one the backend runs on.
+    pub workspaces: WorkspaceSettings,
+    /// How a launch at login behaves; whether it happens is up to the OS entry.
+    pub autostart: AutostartSettings,
+    /// BCP 47 tag the user picked; `None` follows the OS.
+    pub locale: Option<String>,
+    /// Quota of the shell's attachment store.
+    pub attachments: AttachmentSettings,
+    /// Which engine reads replies aloud, and in what voice.
+    pub speech: SpeechSettings,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendSettings {
+    /// Interface the sidecar listens on; `None` means 127.0.0.1.
+    pub bind_host: Option<String>,
+    /// Base URL of a backend run elsewhere; while set no sidecar is spawned.
+    pub remote_url: Option<String>,
+    /// Selected entry of `backends.json`; `None` goes by `remote_url`.
+    pub profile: Option<String>,
+    pub transport: BackendTransport,
+    pub readiness: ReadinessSettings,
+    /// Applied when the sidecar crashes mid-session.
+    pub restart: RestartPolicy,
+    pub shutdown: ShutdownSettings,
+    /// Passed to the sidecar on top of the built-in allowlist.
+    pub env: BackendEnvSettings,
+    /// Route webview requests through the shell's `agent-proxy` scheme, which adds
+    /// the sidecar's per-launch token for it. TCP transport only.
+    pub proxy: bool,
+    /// Run the backend from its sources in a virtualenv instead of the binary.
+    pub python: PythonRuntimeSettings,
+}
+
+events::payload_enum! {
+    /// How the shell talks to the bundled sidecar.
+    #[derive(Default, Deserialize)]
+    pub enum BackendTransport {
+        /// HTTP and WebSocket on a loopback port.
+        #[default]
+        Tcp,
+        /// Length-prefixed JSON-RPC over the sidecar's stdin/stdout, for machines where
+        /// even loopback sockets are blocked.
+        Stdio,
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InboxSettings {
+    /// Folder watched for new files to stage as attachments; `None` disables the inbox.
+    pub folder: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebviewSettings {
+    /// Drop the HTTP cache and service workers when the app version changes.
+    pub clear_on_upgrade: bool,
+    /// Version seen on the previous launch, used to detect upgrades.
+    pub last_version: Option<String>,
+    /// Spellchecker languages; empty follows the OS.
+    pub spellcheck_languages: Vec<String>,
+}
+
+impl Default for WebviewSettings {
+    fn default() -> Self {
+        Self {
+            clear_on_upgrade: true,
+            last_version: None,
+            spellcheck_languages: Vec::new(),
+        }
+    }
+}
+
+/// A change made with `SettingsStore::amend`, laid over the saved settings.
+type Override = Box<dyn Fn(&mut ShellSettings) + Send + Sync>;
+
+struct StoreState {
+    /// What is on disk.
+    saved: ShellSettings,
+    overrides: Vec<Override>,
+}
+
+impl StoreState {
+    fn effective(&self) -> ShellSettings {
+        let mut settings = self.saved.clone();
+        for apply in &self.overrides {
+            apply(&mut settings);
+        }
+        settings
+    }
+}
+
+pub struct SettingsStore {
+    path: PathBuf,
+    state: Mutex<StoreState>,
+}
+
+impl SettingsStore {
+    pub fn load(path: PathBuf) -> Self {
+        let saved = fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| match serde_json::from_str(&raw) {
+                Ok(settings) => Some(settings),
+                Err(err) => {
+                    tracing::warn!("[Settings] Ignoring unreadable {}: {err}", path.display());
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            path,
+            state: Mutex::new(StoreState {
+                saved,
+                overrides: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn get(&self) -> ShellSettings {
+        self.state
+            .lock()
+            .map(|guard| guard.effective())
+            .unwrap_or_default()
+    }
+
+    /// Overrides settings for this process only. `update` changes what is saved
+    /// underneath and never writes the override, which still wins afterwards.
+    pub fn amend(&self, apply: impl Fn(&mut ShellSettings) + Send + Sync + 'static) {
+        if let Ok(mut guard) = self.state.lock() {
+            guard.overrides.push(Box::new(apply));
+        }
+    }
+
+    pub fn update<F>(&self, apply: F) -> Result<ShellSettings, AppError>
+    where
+        F: FnOnce(&mut ShellSettings),
+    {
+        let mut guard = self
+            .state
+            .lock()
+            .map_err(|_| AppError::unavailable("Settings are unavailable."))?;
+        let mut next = guard.saved.clone();
+        apply(&mut next);
+        let raw = serde_json::to_string_pretty(&next).map_err(|err| {
+            AppError::new(ErrorCode::Settings, "Failed to serialize settings.").with_details(err)
+        })?;
+        atomic_file::write(&self.path, raw).map_err(|err| {
+            AppError::new(ErrorCode::Settings, "Failed to write settings.").with_details(err)
+        })?;
+        guard.saved = next;
+        Ok(guard.effective())
+    }
+}
@@ -0,0 +1,425 @@
+use std::{collections::HashMap, str::FromStr, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use crate::{
+    app_menu,
+    desktop::{self, PortalShortcut},
+    error::AppError,
+    events, main_window, quick_ask,
+    settings::SettingsStore,
+};
+
+/// Accelerators owned by the application menu, besides the bindings it shows.
+const MENU_ACCELERATORS: &[&str] = &[
+    app_menu::EXPORT_ACCELERATOR,
+    app_menu::ZOOM_IN_ACCELERATOR,
+    app_menu::ZOOM_OUT_ACCELERATOR,
+    app_menu::ZOOM_RESET_ACCELERATOR,
+    app_menu::FULLSCREEN_ACCELERATOR,
+    app_menu::DEVTOOLS_ACCELERATOR,
+    "CmdOrCtrl+Q",
+    "CmdOrCtrl+W",
+    "CmdOrCtrl+H",
+    "CmdOrCtrl+M",
+    "CmdOrCtrl+Z",
+    "CmdOrCtrl+Shift+Z",
+    "CmdOrCtrl+X",
+    "CmdOrCtrl+C",
+    "CmdOrCtrl+V",
+    "CmdOrCtrl+A",
+];
+
+events::payload_enum! {
+    #[derive(Hash, PartialOrd, Ord, Deserialize)]
+    pub enum ShortcutAction {
+        NewChat,
+        SummonWindow,
+        QuickAsk,
+        ToggleLogs,
+        OpenSettings,
+    }
+}
+
+events::payload_enum! {
+    /// Global shortcuts are registered with the OS and fire while the app is in the
+    /// background; app shortcuts are handled by the focused webview.
+    pub enum ShortcutScope {
+        Global,
+        App,
+    }
+}
+
+impl ShortcutAction {
+    const ALL: [ShortcutAction; 5] = [
+        ShortcutAction::NewChat,
+        ShortcutAction::SummonWindow,
+        ShortcutAction::QuickAsk,
+        ShortcutAction::ToggleLogs,
+        ShortcutAction::OpenSettings,
+    ];
+
+    fn default_accelerator(self) -> &'static str {
+        match self {
+            ShortcutAction::NewChat => "CmdOrCtrl+N",
+            ShortcutAction::SummonWindow => "CmdOrCtrl+Shift+Space",
+            ShortcutAction::QuickAsk => "CmdOrCtrl+Alt+Space",
+            ShortcutAction::ToggleLogs => "CmdOrCtrl+Shift+L",
+            ShortcutAction::OpenSettings => "CmdOrCtrl+Comma",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            ShortcutAction::NewChat => "Start a new chat",
+            ShortcutAction::SummonWindow => "Show the GYY window",
+            ShortcutAction::QuickAsk => "Ask GYY a quick question",
+            ShortcutAction::ToggleLogs => "Toggle the log panel",
+            ShortcutAction::OpenSettings => "Open settings",
+        }
+    }
+
+    fn scope(self) -> ShortcutScope {
+        match self {
+            ShortcutAction::SummonWindow | ShortcutAction::QuickAsk => ShortcutScope::Global,
+            _ => ShortcutScope::App,
+        }
+    }
+}
+
+events::payload! {
+    pub struct ShortcutBinding {
+        action: ShortcutAction,
+        accelerator: String,
+        default_accelerator: &'static str,
+        scope: ShortcutScope,
+    }
+}
+
+/// How global shortcuts reach the app in the current session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutBackend {
+    /// Keys are grabbed directly (macOS, Windows, X11).
+    Native,
+    /// Bound through the XDG GlobalShortcuts portal; the compositor confirms the keys.
+    Portal,
+    /// Nothing works here; the UI should offer its own way to summon the window.
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortcutSupport {
+    session_type: String,
+    backend: ShortcutBackend,
+    /// False when the accelerators in settings are only a suggestion to the compositor.
+    exact_accelerators: bool,
+    detail: Option<String>,
+}
+
+events::payload! {
+    pub struct ShortcutTriggered {
+        action: ShortcutAction,
+    }
+}
+
+events::payload! {
+    pub struct ShortcutBindings {
+        bindings: Vec<ShortcutBinding>,
+    }
+}
+
+/// Global shortcut ids currently registered with the OS.
+#[derive(Default)]
+pub struct ShortcutRegistry(Mutex<HashMap<u32, ShortcutAction>>);
+
+fn session_type() -> String {
+    if !cfg!(target_os = "linux") {
+        return std::env::consts::OS.to_string();
+    }
+    std::env::var("XDG_SESSION_TYPE")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| {
+            if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+                "wayland".to_string()
+            } else {
+                "x11".to_string()
+            }
+        })
+}
+
+/// Wayland compositors do not let clients grab keys, so global shortcuts go
+/// through the desktop portal instead.
+pub fn is_wayland() -> bool {
+    cfg!(target_os = "linux") && session_type().eq_ignore_ascii_case("wayland")
+}
+
+/// Converts an accelerator to the XDG shortcut trigger syntax, e.g. `CTRL+SHIFT+space`.
+fn portal_trigger(accelerator: &str) -> String {
+    accelerator
+        .split('+')
+        .map(|part| match part.trim().to_ascii_lowercase().as_str() {
+            "cmdorctrl" | "commandorcontrol" | "ctrl" | "control" => "CTRL".to_string(),
+            "shift" => "SHIFT".to_string(),
+            "alt" | "option" => "ALT".to_string(),
+            "super" | "cmd" | "command" | "meta" => "LOGO".to_string(),
+            key if key.len() > 1 && key.starts_with('f') && key[1..].parse::<u8>().is_ok() => {
+                key.to_ascii_uppercase()
+            }
+            key => key.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+fn parse_accelerator(value: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(value.trim()).map_err(|err| format!("Invalid shortcut '{value}': {err}"))
+}
+
+fn bindings(store: &SettingsStore) -> Vec<ShortcutBinding> {
+    let overrides = store.get().shortcuts;
+    ShortcutAction::ALL
+        .iter()
+        .map(|&action| ShortcutBinding {
+            action,
+            accelerator: overrides
+                .get(&action)
+                .cloned()
+                .unwrap_or_else(|| action.default_accelerator().to_string()),
+            default_accelerator: action.default_accelerator(),
+            scope: action.scope(),
+        })
+        .collect()
+}
+
+/// The effective binding for `action`, for the menu item that shows it.
+pub fn accelerator(store: &SettingsStore, action: ShortcutAction) -> Option<String> {
+    bindings(store)
+        .into_iter()
+        .find(|binding| binding.action == action)
+        .map(|binding| binding.accelerator)
+}
+
+fn check_conflicts(
+    store: &SettingsStore,
+    action: ShortcutAction,
+    shortcut: Shortcut,
+) -> Result<(), String> {
+    for reserved in MENU_ACCELERATORS {
+        if parse_accelerator(reserved).is_ok_and(|menu| menu.id() == shortcut.id()) {
+            return Err(format!("{reserved} is used by the application menu."));
+        }
+    }
+    for binding in bindings(store) {
+        if binding.action == action {
+            continue;
+        }
+        if parse_accelerator(&binding.accelerator).is_ok_and(|other| other.id() == shortcut.id()) {
+            return Err(format!(
+                "{} is already assigned to {:?}.",
+                binding.accelerator, binding.action
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Re-registers global shortcuts from settings. Called at startup and after edits.
+pub fn apply<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let (Some(store), Some(registry)) = (
+        app.try_state::<SettingsStore>(),
+        app.try_state::<ShortcutRegistry>(),
+    ) else {
+        return Ok(());
+    };
+    let mut registered = registry
+        .0
+        .lock()
+        .map_err(|_| "Shortcut registry is unavailable.".to_string())?;
+    let global = app.global_shortcut();
+    global
+        .unregister_all()
+        .map_err(|err| format!("Failed to clear global shortcuts: {err}"))?;
+    registered.clear();
+    if is_wayland() {
+        let portal = bindings(&store)
+            .into_iter()
+            .filter(|binding| binding.scope == ShortcutScope::Global)
+            .map(|binding| PortalShortcut {
+                id: action_id(binding.action),
+                description: binding.action.description().to_string(),
+                preferred_trigger: portal_trigger(&binding.accelerator),
+            })
+            .collect();
+        desktop::bind_portal_shortcuts(app, portal);
+        return Ok(());
+    }
+    for binding in bindings(&store) {
+        if binding.scope != ShortcutScope::Global {
+            continue;
+        }
+        let shortcut = match parse_accelerator(&binding.accelerator) {
+            Ok(shortcut) => shortcut,
+            Err(err) => {
+                tracing::warn!("[Shortcuts] {err}");
+                continue;
+            }
+        };
+        match global.register(shortcut) {
+            Ok(()) => {
+                registered.insert(shortcut.id(), binding.action);
+            }
+            Err(err) => tracing::warn!(
+                "[Shortcuts] Could not register {} for {:?}: {err}",
+                binding.accelerator,
+                binding.action
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn action_id(action: ShortcutAction) -> String {
+    serde_json::to_value(action)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn dispatch<R: Runtime>(app: &AppHandle<R>, action: ShortcutAction) {
+    match action {
+        ShortcutAction::SummonWindow => main_window::reveal(app),
+        ShortcutAction::QuickAsk => {
+            if let Err(err) = quick_ask::toggle(app) {
+                tracing::warn!("[Shortcuts] {err}");
+            }
+        }
+        action => {
+            events::shortcut_triggered(app, &ShortcutTriggered { action });
+        }
+    }
+}
+
+/// Runs the action for a portal shortcut id (the action's snake_case name).
+#[cfg(target_os = "linux")]
+pub fn trigger<R: Runtime>(app: &AppHandle<R>, id: &str) {
+    if let Ok(action) = serde_json::from_value(serde_json::Value::String(id.to_string())) {
+        dispatch(app, action);
+    }
+}
+
+/// Handler for the global-shortcut plugin.
+pub fn handle<R: Runtime>(app: &AppHandle<R>, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let action = app.try_state::<ShortcutRegistry>().and_then(|registry| {
+        registry
+            .0
+            .lock()
+            .ok()
+            .and_then(|registered| registered.get(&shortcut.id()).copied())
+    });
+    if let Some(action) = action {
+        dispatch(app, action);
+    }
+}
+
+/// Reports whether global shortcuts can work in this session so the UI can fall
+/// back to a visible summon control when they cannot.
+#[tauri::command]
+pub fn get_shortcut_support(app: AppHandle) -> ShortcutSupport {
+    let session_type = session_type();
+    if !is_wayland() {
+        return ShortcutSupport {
+            session_type,
+            backend: ShortcutBackend::Native,
+            exact_accelerators: true,
+            detail: None,
+        };
+    }
+    let (backend, detail) = match desktop::shortcut_portal_status(&app) {
+        Ok(true) => (ShortcutBackend::Portal, None),
+        Ok(false) => (
+            ShortcutBackend::Unavailable,
+            Some("The desktop shortcut portal has not confirmed the shortcuts yet.".to_string()),
+        ),
+        Err(err) => (
+            ShortcutBackend::Unavailable,
+            Some(format!(
+                "Global shortcuts are unavailable in this Wayland session: {err}"
+            )),
+        ),
+    };
+    ShortcutSupport {
+        session_type,
+        backend,
+        exact_accelerators: false,
+        detail,
+    }
+}
+
+#[tauri::command]
+pub fn get_shortcuts(store: tauri::State<SettingsStore>) -> Vec<ShortcutBinding> {
+    bindings(&store)
+}
+
+/// Rebinds `action`; `None` restores its default accelerator.
+#[tauri::command]
+pub fn set_shortcut(
+    app: AppHandle,
+    store: tauri::State<SettingsStore>,
+    action: ShortcutAction,
+    accelerator: Option<String>,
+) -> Result<Vec<ShortcutBinding>, AppError> {
+    let accelerator = accelerator
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let shortcut = parse_accelerator(
+        accelerator
+            .as_deref()
+            .unwrap_or(action.default_accelerator()),
+    )
+    .map_err(AppError::invalid_input)?;
+    check_conflicts(&store, action, shortcut).map_err(AppError::invalid_input)?;
+    if action.scope() == ShortcutScope::Global && !is_wayland() {
+        let global = app.global_shortcut();
+        let ours = app
+            .state::<ShortcutRegistry>()
+            .0
+            .lock()
+            .map(|registered| registered.contains_key(&shortcut.id()))
+            .unwrap_or(false);
+        // Probe the OS before persisting so a combination held by another app is rejected.
+        if !ours {
+            global.register(shortcut).map_err(|err| {
+                AppError::invalid_input("That shortcut is already in use by another application.")
+                    .with_details(err)
+            })?;
+            let _ = global.unregister(shortcut);
+        }
+    }
+    store.update(|settings| match accelerator {
+        Some(value) => {
+            settings.shortcuts.insert(action, value);
+        }
+        None => {
+            settings.shortcuts.remove(&action);
+        }
+    })?;
+    apply(&app)?;
+    if let Err(err) = app_menu::install(&app) {
+        tracing::warn!("[Shortcuts] {err}");
+    }
+    let updated = bindings(&store);
+    events::shortcuts_changed(
+        &app,
+        &ShortcutBindings {
+            bindings: updated.clone(),
+        },
+    );
+    Ok(updated)
+}
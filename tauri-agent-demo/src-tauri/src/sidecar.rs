@@ -0,0 +1,1001 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    net::{SocketAddr, TcpStream},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    arch::{self, Arch},
+    error::{AppError, ErrorCode},
+    events,
+    process_tree::{self, ProcessTree},
+    proxy_config,
+};
+
+const READY_POLL: Duration = Duration::from_millis(200);
+const CRASH_POLL: Duration = Duration::from_secs(1);
+const EXIT_POLL: Duration = Duration::from_millis(100);
+/// How long a sidecar's own subprocesses get to exit once it is gone.
+const TREE_GRACE: Duration = Duration::from_secs(2);
+/// A sidecar that stays up this long has its restart count reset.
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+/// Parts of a variable name that mark its value as a credential in logs.
+/// Environment and config names containing any of these hold secrets.
+pub const SECRET_MARKERS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "CREDENTIAL", "AUTH"];
+
+/// How a sidecar that exits on its own is brought back.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    /// Restarts in a row before giving up; 0 turns them off.
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 30_000,
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Doubles from `initial_backoff_ms` with each attempt already made.
+    fn backoff(&self, attempts: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempts.min(16));
+        Duration::from_millis(
+            self.initial_backoff_ms
+                .saturating_mul(factor)
+                .min(self.max_backoff_ms.max(self.initial_backoff_ms)),
+        )
+    }
+}
+
+/// How to tell a freshly spawned sidecar is accepting work.
+#[derive(Debug, Clone)]
+pub enum Readiness {
+    /// Considered ready as soon as it is spawned.
+    Spawned,
+    /// Ready once `addr` accepts TCP connections.
+    Tcp(SocketAddr),
+}
+
+events::payload_enum! {
+    pub enum OutputStream {
+        Stdout,
+        Stderr,
+    }
+}
+
+/// Where a variable in the sidecar's environment came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvSource {
+    /// Passed through from the app's own environment.
+    Inherited,
+    /// Set by the spec.
+    Set,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvVar {
+    name: String,
+    /// Credentials are replaced by `<redacted>` and stripped from proxy URLs.
+    value: String,
+    source: EnvSource,
+}
+
+/// Receives each line the sidecar prints.
+pub type LineHandler = Arc<dyn Fn(OutputStream, &str) + Send + Sync>;
+
+/// Takes over the sidecar's stdin and stdout as a message channel.
+pub type PipeHandler = Arc<dyn Fn(ChildStdin, ChildStdout) + Send + Sync>;
+/// Asks the sidecar to exit on its own; returns whether the request got through.
+pub type StopHandler = Arc<dyn Fn() -> bool + Send + Sync>;
+
+/// Everything needed to (re)launch one helper process.
+#[derive(Clone)]
+pub struct SidecarSpec {
+    /// Stable id used for logs, events and lookups, e.g. `backend`.
+    pub name: String,
+    /// Executable name without extension; `.exe` is added on Windows.
+    pub binary: String,
+    /// When set, this is run instead of looking `binary` up in the bundle.
+    pub program: Option<PathBuf>,
+    pub args: Vec<OsString>,
+    pub env: Vec<(String, OsString)>,
+    pub current_dir: Option<PathBuf>,
+    /// Discard stdout/stderr; release builds have no console to show them in.
+    pub quiet: bool,
+    pub readiness: Readiness,
+    pub ready_timeout: Duration,
+    /// When set, output is piped through this instead of inherited or discarded.
+    pub on_line: Option<LineHandler>,
+    /// When set, stdin and stdout are handed to this on every spawn and only
+    /// stderr goes to `on_line`.
+    pub on_pipes: Option<PipeHandler>,
+    /// When set, the parent environment is dropped except for these names; an
+    /// entry ending in `*` is a prefix. Variables from `env` are always passed.
+    pub inherit_env: Option<Vec<String>>,
+    /// When set, the supervisor relaunches the sidecar after it crashes.
+    pub restart: Option<RestartPolicy>,
+    /// When set, stopping asks the process to exit first and only kills it if it
+    /// is still running after `stop_timeout`.
+    pub on_stop: Option<StopHandler>,
+    pub stop_timeout: Duration,
+    /// SHA-256 digests, in hex, the binary may have; empty when none were recorded.
+    pub expected_sha256: Vec<String>,
+    /// Refuse a binary that cannot be verified instead of warning about it.
+    pub enforce_sha256: bool,
+}
+
+impl SidecarSpec {
+    pub fn new(name: &str, binary: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            binary: binary.to_string(),
+            program: None,
+            args: Vec::new(),
+            env: Vec::new(),
+            current_dir: None,
+            quiet: !tauri::is_dev(),
+            readiness: Readiness::Spawned,
+            ready_timeout: Duration::from_secs(30),
+            on_line: None,
+            on_pipes: None,
+            inherit_env: None,
+            restart: None,
+            on_stop: None,
+            stop_timeout: Duration::from_secs(5),
+            expected_sha256: Vec::new(),
+            enforce_sha256: false,
+        }
+    }
+
+    pub fn program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.program = Some(program.into());
+        self
+    }
+
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn env(mut self, key: &str, value: impl Into<OsString>) -> Self {
+        self.env.push((key.to_string(), value.into()));
+        self
+    }
+
+    /// Starts the sidecar from a clean environment plus the allowed names.
+    pub fn inherit_only<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.inherit_env = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    pub fn readiness(mut self, readiness: Readiness) -> Self {
+        self.readiness = readiness;
+        self
+    }
+
+    pub fn restart(mut self, policy: RestartPolicy) -> Self {
+        self.restart = Some(policy);
+        self
+    }
+
+    pub fn on_stop(mut self, handler: impl Fn() -> bool + Send + Sync + 'static) -> Self {
+        self.on_stop = Some(Arc::new(handler));
+        self
+    }
+
+    pub fn stop_timeout(mut self, timeout: Duration) -> Self {
+        self.stop_timeout = timeout;
+        self
+    }
+
+    pub fn on_line(mut self, handler: impl Fn(OutputStream, &str) + Send + Sync + 'static) -> Self {
+        self.on_line = Some(Arc::new(handler));
+        self
+    }
+
+    pub fn on_pipes(
+        mut self,
+        handler: impl Fn(ChildStdin, ChildStdout) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_pipes = Some(Arc::new(handler));
+        self
+    }
+
+    /// Checks the binary against `digests` before every spawn; with `enforce` a
+    /// mismatch, or having no digests at all, stops the spawn.
+    pub fn expect_sha256<S: Into<String>>(
+        mut self,
+        digests: impl IntoIterator<Item = S>,
+        enforce: bool,
+    ) -> Self {
+        self.expected_sha256 = digests.into_iter().map(Into::into).collect();
+        self.enforce_sha256 = enforce;
+        self
+    }
+
+    fn verify(&self, path: &Path) -> Result<(), AppError> {
+        if self.expected_sha256.is_empty() {
+            if self.enforce_sha256 {
+                return Err(AppError::new(
+                    ErrorCode::Integrity,
+                    format!(
+                        "This build has no recorded checksum for the {} sidecar, so it cannot be verified.",
+                        self.name
+                    ),
+                ));
+            }
+            return Ok(());
+        }
+        let digest = sha256_file(path).map_err(|err| {
+            AppError::new(
+                ErrorCode::Integrity,
+                format!("Failed to verify the {} sidecar.", self.name),
+            )
+            .with_details(err)
+        })?;
+        if self
+            .expected_sha256
+            .iter()
+            .any(|expected| expected.eq_ignore_ascii_case(&digest))
+        {
+            return Ok(());
+        }
+        if !self.enforce_sha256 {
+            tracing::warn!(
+                "[Sidecar] {} does not match the checksum recorded at build time; running it anyway.",
+                path.display()
+            );
+            return Ok(());
+        }
+        Err(AppError::new(
+            ErrorCode::Integrity,
+            format!(
+                "The {} sidecar at {} has been modified or replaced and will not be started. Reinstall the app to repair it.",
+                self.name,
+                path.display()
+            ),
+        )
+        .with_details(format!("SHA-256 {digest}")))
+    }
+
+    /// `<binary>-<arch>` for each architecture the host runs, best first, then the
+    /// plain name used by single-arch and universal builds.
+    fn file_names(&self, runnable: &[Arch]) -> Vec<String> {
+        let extension = if cfg!(windows) { ".exe" } else { "" };
+        runnable
+            .iter()
+            .map(|arch| format!("{}-{arch}{extension}", self.binary))
+            .chain(std::iter::once(format!("{}{extension}", self.binary)))
+            .collect()
+    }
+
+    /// Looks in the bundle's resource dir, then next to the app executable, and
+    /// takes the first binary built for an architecture this machine can run.
+    pub fn resolve_path<R: Runtime>(&self, app: &AppHandle<R>) -> Result<PathBuf, AppError> {
+        if let Some(program) = &self.program {
+            return Ok(program.clone());
+        }
+        let resource_dir = app.path().resource_dir().map_err(|err| {
+            AppError::new(ErrorCode::Spawn, "Failed to resolve resource directory.")
+                .with_details(err)
+        })?;
+        let dirs: Vec<PathBuf> = std::iter::once(resource_dir)
+            .chain(
+                std::env::current_exe()
+                    .ok()
+                    .and_then(|path| path.parent().map(PathBuf::from)),
+            )
+            .collect();
+        let host = arch::host();
+        let runnable = host.map(arch::runnable).unwrap_or_default();
+        let mut rejected: Option<(PathBuf, Vec<Arch>)> = None;
+        for name in self.file_names(&runnable) {
+            for dir in &dirs {
+                let candidate = dir.join(&name);
+                if !candidate.is_file() {
+                    continue;
+                }
+                let built_for = match arch::of_binary(&candidate) {
+                    // Scripts and unknown formats are left for the OS to judge.
+                    Ok(None) => return Ok(candidate),
+                    Ok(Some(built_for)) => built_for,
+                    Err(err) => {
+                        tracing::warn!("[Sidecar] {err}");
+                        continue;
+                    }
+                };
+                if host.is_none() || built_for.iter().any(|arch| runnable.contains(arch)) {
+                    if let (Some(host), Some(arch)) = (host, built_for.first()) {
+                        if !built_for.contains(&host) {
+                            tracing::info!(
+                                "[Sidecar] Running {} ({arch}) under emulation.",
+                                self.name
+                            );
+                        }
+                    }
+                    return Ok(candidate);
+                }
+                rejected.get_or_insert((candidate, built_for));
+            }
+        }
+        if let (Some(host), Some((path, built_for))) = (host, rejected) {
+            let hint = arch::mismatch_hint(host, &built_for);
+            let built_for = if built_for.is_empty() {
+                "an unsupported processor".to_string()
+            } else {
+                built_for
+                    .iter()
+                    .map(|arch| arch.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" + ")
+            };
+            return Err(AppError::new(
+                ErrorCode::Spawn,
+                format!(
+                    "Sidecar {} is built for {built_for}, but this computer is {host}. {hint}",
+                    self.name
+                ),
+            )
+            .with_details(path.display()));
+        }
+        Err(AppError::new(
+            ErrorCode::Spawn,
+            format!(
+                "Sidecar {} not found at {}.",
+                self.name,
+                dirs[0].join(self.file_names(&[]).remove(0)).display()
+            ),
+        ))
+    }
+
+    /// Parent variables that pass `inherit_env`; with no allowlist, all of them.
+    fn inherited(&self) -> Vec<(String, OsString)> {
+        std::env::vars_os()
+            .filter_map(|(key, value)| {
+                let name = key.into_string().ok()?;
+                let allowed = self
+                    .inherit_env
+                    .as_ref()
+                    .is_none_or(|allowed| is_allowed(allowed, &name));
+                allowed.then_some((name, value))
+            })
+            .collect()
+    }
+
+    /// The environment a spawn hands the process, sorted by name and redacted. A
+    /// variable set by the spec replaces an inherited one, and a later `env` entry
+    /// an earlier one, as they do on the command.
+    pub fn effective_env(&self) -> Vec<EnvVar> {
+        let mut vars = BTreeMap::new();
+        let inherited = self
+            .inherited()
+            .into_iter()
+            .map(|(name, value)| (name, value, EnvSource::Inherited));
+        let set = self
+            .env
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone(), EnvSource::Set));
+        for (name, value, source) in inherited.chain(set) {
+            let key = if cfg!(windows) {
+                name.to_ascii_uppercase()
+            } else {
+                name.clone()
+            };
+            let value = redact(&name, &value);
+            vars.insert(
+                key,
+                EnvVar {
+                    name,
+                    value,
+                    source,
+                },
+            );
+        }
+        vars.into_values().collect()
+    }
+
+    fn spawn<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+    ) -> Result<(Child, Option<ProcessTree>), AppError> {
+        let path = self.resolve_path(app)?;
+        self.verify(&path)?;
+        let mut command = Command::new(path);
+        command.args(&self.args);
+        if self.inherit_env.is_some() {
+            command.env_clear();
+            let mut inherited = Vec::new();
+            for (name, value) in self.inherited() {
+                command.env(&name, value);
+                inherited.push(name);
+            }
+            let injected: Vec<String> = self
+                .env
+                .iter()
+                .map(|(key, value)| format!("{key}={}", redact(key, value)))
+                .collect();
+            tracing::info!(
+                "[Sidecar] {} environment: inherited [{}]; set [{}].",
+                self.name,
+                inherited.join(", "),
+                injected.join(", ")
+            );
+        }
+        for (key, value) in &self.env {
+            command.env(key, value);
+        }
+        if let Some(dir) = &self.current_dir {
+            command.current_dir(dir);
+        }
+        if self.on_line.is_some() {
+            command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        } else if self.quiet {
+            command.stdout(Stdio::null()).stderr(Stdio::null());
+        }
+        if self.on_pipes.is_some() {
+            command.stdin(Stdio::piped()).stdout(Stdio::piped());
+        }
+        process_tree::prepare(&mut command);
+        let mut child = command.spawn().map_err(|err| {
+            AppError::new(
+                ErrorCode::Spawn,
+                format!("Failed to spawn {} sidecar.", self.name),
+            )
+            .with_details(err)
+        })?;
+        let tree = ProcessTree::attach(&child);
+        if let Some(handler) = &self.on_pipes {
+            if let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) {
+                handler(stdin, stdout);
+            }
+        }
+        if let Some(handler) = &self.on_line {
+            if let Some(stdout) = child.stdout.take() {
+                self.read_lines(stdout, OutputStream::Stdout, handler.clone());
+            }
+            if let Some(stderr) = child.stderr.take() {
+                self.read_lines(stderr, OutputStream::Stderr, handler.clone());
+            }
+        }
+        Ok((child, tree))
+    }
+
+    /// Forwards lines until the pipe closes, echoing them in dev builds where the
+    /// output used to go straight to the console.
+    fn read_lines(
+        &self,
+        pipe: impl Read + Send + 'static,
+        stream: OutputStream,
+        handler: LineHandler,
+    ) {
+        let name = self.name.clone();
+        let echo = !self.quiet;
+        thread::spawn(move || {
+            for line in BufReader::new(pipe).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if echo {
+                    tracing::info!("[{name}] {line}");
+                }
+                handler(stream, &line);
+            }
+        });
+    }
+
+    fn wait_ready(&self) -> Result<(), String> {
+        let Readiness::Tcp(addr) = self.readiness else {
+            return Ok(());
+        };
+        let deadline = Instant::now() + self.ready_timeout;
+        while Instant::now() < deadline {
+            if TcpStream::connect_timeout(&addr, READY_POLL).is_ok() {
+                return Ok(());
+            }
+            thread::sleep(READY_POLL);
+        }
+        Err(format!(
+            "{} did not start listening on {addr} within {}s.",
+            self.name,
+            self.ready_timeout.as_secs()
+        ))
+    }
+}
+
+fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+/// Environment names are case-insensitive on Windows (`Path`, `SystemRoot`).
+fn env_matches(pattern: &str, name: &str) -> bool {
+    let (pattern, name) = if cfg!(windows) {
+        (pattern.to_ascii_uppercase(), name.to_ascii_uppercase())
+    } else {
+        (pattern.to_string(), name.to_string())
+    };
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => name == pattern,
+    }
+}
+
+/// Whether `name` is on `allowed`, where an entry ending in `*` is a prefix.
+pub fn is_allowed(allowed: &[String], name: &str) -> bool {
+    allowed.iter().any(|pattern| env_matches(pattern, name))
+}
+
+fn redact(key: &str, value: &OsString) -> String {
+    let upper = key.to_ascii_uppercase();
+    if SECRET_MARKERS.iter().any(|marker| upper.contains(marker)) {
+        "<redacted>".to_string()
+    } else if upper.ends_with("_PROXY") {
+        proxy_config::strip_credentials(&value.to_string_lossy())
+    } else {
+        value.to_string_lossy().into_owned()
+    }
+}
+
+struct Supervised {
+    spec: SidecarSpec,
+    child: Option<Child>,
+    /// What `child` started; stopped along with it.
+    tree: Option<ProcessTree>,
+    started_at: Instant,
+    /// Automatic restarts since the sidecar last stayed up for `STABLE_AFTER`.
+    attempts: u32,
+    /// Set after a crash while another restart is due.
+    retry_at: Option<Instant>,
+    /// Every relaunch of a running process this session, automatic or requested.
+    restarts: u32,
+    /// How the previous process ended, e.g. `exit status: 1`.
+    last_exit: Option<String>,
+}
+
+impl Supervised {
+    fn new(spec: SidecarSpec, (child, tree): (Child, Option<ProcessTree>)) -> Self {
+        Self {
+            spec,
+            child: Some(child),
+            tree,
+            started_at: Instant::now(),
+            attempts: 0,
+            retry_at: None,
+            restarts: 0,
+            last_exit: None,
+        }
+    }
+
+    fn replace_child(&mut self, (child, tree): (Child, Option<ProcessTree>)) {
+        self.child = Some(child);
+        self.tree = tree;
+        self.started_at = Instant::now();
+        self.retry_at = None;
+    }
+}
+
+type Slot = Arc<Mutex<Supervised>>;
+
+/// Every sidecar this app launched, keyed by `SidecarSpec::name`.
+#[derive(Default)]
+pub struct Sidecars(Mutex<BTreeMap<String, Slot>>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SidecarStatus {
+    name: String,
+    running: bool,
+    pid: Option<u32>,
+}
+
+/// One sidecar in detail, for the settings UI.
+#[derive(Debug, Clone, Serialize)]
+pub struct SidecarInfo {
+    running: bool,
+    pid: Option<u32>,
+    uptime_secs: Option<u64>,
+    binary_path: Option<String>,
+    restarts: u32,
+    last_exit: Option<String>,
+}
+
+impl SidecarInfo {
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+}
+
+events::payload! {
+    #[derive(Deserialize)]
+    pub struct SidecarEvent {
+        pub name: String,
+        pub error: Option<String>,
+    }
+}
+
+events::payload! {
+    #[derive(Deserialize)]
+    pub struct SidecarCrash {
+        pub name: String,
+        reason: String,
+        /// Automatic restarts made so far in this run of crashes.
+        attempts: u32,
+        will_restart: bool,
+    }
+}
+
+events::payload! {
+    #[derive(Deserialize)]
+    pub struct SidecarRestart {
+        pub name: String,
+        attempt: u32,
+    }
+}
+
+fn slot<R: Runtime>(app: &AppHandle<R>, name: &str) -> Option<Slot> {
+    app.try_state::<Sidecars>()?
+        .0
+        .lock()
+        .ok()?
+        .get(name)
+        .cloned()
+}
+
+/// Stops the current process, gracefully when the spec knows how, killing it as
+/// a last resort.
+fn stop_child(supervised: &mut Supervised) {
+    let Some(mut child) = supervised.child.take() else {
+        return;
+    };
+    let spec = &supervised.spec;
+    let mut exited = None;
+    if let Some(request) = &spec.on_stop {
+        if request() {
+            let deadline = Instant::now() + spec.stop_timeout;
+            while exited.is_none() && Instant::now() < deadline {
+                match child.try_wait() {
+                    Ok(Some(status)) => exited = Some(status),
+                    _ => thread::sleep(EXIT_POLL),
+                }
+            }
+            if exited.is_some() {
+                tracing::info!("[Sidecar] {} exited cleanly.", spec.name);
+            } else {
+                tracing::warn!(
+                    "[Sidecar] {} still running after {}s; killing it.",
+                    spec.name,
+                    spec.stop_timeout.as_secs()
+                );
+            }
+        }
+    }
+    let status = exited.or_else(|| {
+        let _ = child.kill();
+        child.wait().ok()
+    });
+    if let Some(status) = status {
+        supervised.last_exit = Some(status.to_string());
+    }
+    if let Some(tree) = supervised.tree.take() {
+        tree.terminate(TREE_GRACE);
+    }
+}
+
+/// Emits `sidecar://ready` or `sidecar://failed` once the readiness check settles.
+fn watch_ready<R: Runtime>(app: &AppHandle<R>, spec: &SidecarSpec) {
+    let app = app.clone();
+    let spec = spec.clone();
+    thread::spawn(move || {
+        let error = spec.wait_ready().err();
+        match &error {
+            Some(err) => tracing::warn!("[Sidecar] {err}"),
+            None => tracing::info!("[Sidecar] {} is ready.", spec.name),
+        }
+        let failed = error.is_some();
+        let event = SidecarEvent {
+            name: spec.name,
+            error,
+        };
+        if failed {
+            events::sidecar_failed(&app, &event);
+        } else {
+            events::sidecar_ready(&app, &event);
+        }
+    });
+}
+
+fn report_crash<R: Runtime>(
+    app: &AppHandle<R>,
+    name: &str,
+    supervised: &mut Supervised,
+    reason: String,
+) {
+    let Some(policy) = supervised.spec.restart else {
+        return;
+    };
+    let will_restart = supervised.attempts < policy.max_attempts;
+    supervised.retry_at =
+        will_restart.then(|| Instant::now() + policy.backoff(supervised.attempts));
+    if will_restart {
+        tracing::warn!("[Sidecar] {name} stopped ({reason}); restarting.");
+    } else {
+        tracing::warn!(
+            "[Sidecar] {name} stopped ({reason}); giving up after {} restarts.",
+            supervised.attempts
+        );
+    }
+    events::sidecar_crashed(
+        app,
+        &SidecarCrash {
+            name: name.to_string(),
+            reason,
+            attempts: supervised.attempts,
+            will_restart,
+        },
+    );
+}
+
+/// Checks once a second whether `name` exited on its own and relaunches it with
+/// exponential backoff, as long as its spec has a restart policy.
+fn supervise<R: Runtime>(app: &AppHandle<R>, name: String, slot: Slot) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(CRASH_POLL);
+        // `remove` dropped the only other reference.
+        if Arc::strong_count(&slot) == 1 {
+            return;
+        }
+        let Ok(mut supervised) = slot.lock() else {
+            return;
+        };
+        if supervised.spec.restart.is_none() {
+            continue;
+        }
+        if let Some(child) = supervised.child.as_mut() {
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    supervised.child = None;
+                    // Tool subprocesses would otherwise outlive the crash.
+                    if let Some(tree) = supervised.tree.take() {
+                        tree.terminate(TREE_GRACE);
+                    }
+                    supervised.last_exit = Some(status.to_string());
+                    report_crash(&app, &name, &mut supervised, status.to_string());
+                }
+                _ if supervised.started_at.elapsed() >= STABLE_AFTER => supervised.attempts = 0,
+                _ => {}
+            }
+            continue;
+        }
+        if supervised.retry_at.is_none_or(|at| Instant::now() < at) {
+            continue;
+        }
+        supervised.attempts += 1;
+        let attempt = supervised.attempts;
+        match supervised.spec.spawn(&app) {
+            Ok(spawned) => {
+                supervised.replace_child(spawned);
+                supervised.restarts += 1;
+                watch_ready(&app, &supervised.spec);
+                tracing::info!("[Sidecar] Restarted {name} (attempt {attempt}).");
+                events::sidecar_restarted(
+                    &app,
+                    &SidecarRestart {
+                        name: name.clone(),
+                        attempt,
+                    },
+                );
+            }
+            Err(err) => report_crash(&app, &name, &mut supervised, err.message),
+        }
+    });
+}
+
+/// Launches `spec` and supervises it under its name, replacing any previous process.
+/// A spec that fails to spawn the first time is not supervised.
+pub fn start<R: Runtime>(app: &AppHandle<R>, spec: SidecarSpec) -> Result<(), AppError> {
+    if is_supervised(app, &spec.name) {
+        let name = spec.name.clone();
+        return with_stopped(app, &name, || Some(Ok(spec)), || Ok(()));
+    }
+    let sidecars = app
+        .try_state::<Sidecars>()
+        .ok_or_else(|| AppError::unavailable("Sidecar supervisor is unavailable."))?;
+    tracing::info!("[Sidecar] Spawning {}.", spec.name);
+    let spawned = spec.spawn(app)?;
+    watch_ready(app, &spec);
+    let name = spec.name.clone();
+    let slot = Arc::new(Mutex::new(Supervised::new(spec, spawned)));
+    sidecars
+        .0
+        .lock()
+        .map_err(|_| AppError::unavailable("Sidecar supervisor is unavailable."))?
+        .insert(name.clone(), Arc::clone(&slot));
+    supervise(app, name, slot);
+    Ok(())
+}
+
+pub fn is_supervised<R: Runtime>(app: &AppHandle<R>, name: &str) -> bool {
+    slot(app, name).is_some()
+}
+
+/// Stops `name`, runs `work`, then relaunches it from `respawn`, or from its last
+/// spec when `respawn` is `None`. Without a supervised `name`, `work` just runs.
+pub fn with_stopped<R: Runtime, T, E: From<AppError>>(
+    app: &AppHandle<R>,
+    name: &str,
+    respawn: impl FnOnce() -> Option<Result<SidecarSpec, E>>,
+    work: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    let Some(slot) = slot(app, name) else {
+        return work();
+    };
+    let mut supervised = slot
+        .lock()
+        .map_err(|_| AppError::unavailable(format!("Sidecar {name} is unavailable.")))?;
+    let was_running = supervised.child.is_some();
+    stop_child(&mut supervised);
+    let result = work();
+    if let Some(spec) = respawn() {
+        supervised.spec = spec?;
+    }
+    // A deliberate restart starts the crash count over.
+    supervised.attempts = 0;
+    supervised.retry_at = None;
+    let spawned = supervised.spec.spawn(app)?;
+    supervised.replace_child(spawned);
+    if was_running {
+        supervised.restarts += 1;
+        tracing::info!("[Sidecar] Restarted {name}.");
+    } else {
+        tracing::info!("[Sidecar] Started {name}.");
+    }
+    watch_ready(app, &supervised.spec);
+    result
+}
+
+/// Stops `name` and keeps it down: it stays supervised, but is not restarted until
+/// `with_stopped` launches it again. Returns whether it was running.
+pub fn stop<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<bool, AppError> {
+    let slot = slot(app, name).ok_or_else(|| {
+        AppError::not_found(format!("Sidecar {name} is not managed by this app."))
+    })?;
+    let mut supervised = slot
+        .lock()
+        .map_err(|_| AppError::unavailable(format!("Sidecar {name} is unavailable.")))?;
+    supervised.retry_at = None;
+    supervised.attempts = 0;
+    let was_running = supervised
+        .child
+        .as_mut()
+        .is_some_and(|child| matches!(child.try_wait(), Ok(None)));
+    stop_child(&mut supervised);
+    tracing::info!("[Sidecar] Stopped {name}.");
+    Ok(was_running)
+}
+
+/// Stops `name` and stops supervising it, for sidecars that come and go such as
+/// session workers. Returns whether it was running.
+pub fn remove<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<bool, AppError> {
+    let was_running = stop(app, name)?;
+    if let Some(sidecars) = app.try_state::<Sidecars>() {
+        if let Ok(mut map) = sidecars.0.lock() {
+            map.remove(name);
+        }
+    }
+    Ok(was_running)
+}
+
+pub fn is_running<R: Runtime>(app: &AppHandle<R>, name: &str) -> bool {
+    slot(app, name)
+        .and_then(|slot| {
+            let mut supervised = slot.lock().ok()?;
+            let child = supervised.child.as_mut()?;
+            Some(matches!(child.try_wait(), Ok(None)))
+        })
+        .unwrap_or(false)
+}
+
+pub fn info<R: Runtime>(app: &AppHandle<R>, name: &str) -> Option<SidecarInfo> {
+    let slot = slot(app, name)?;
+    let mut supervised = slot.lock().ok()?;
+    let mut pid = None;
+    if let Some(child) = supervised.child.as_mut() {
+        match child.try_wait() {
+            Ok(None) => pid = Some(child.id()),
+            Ok(Some(status)) => supervised.last_exit = Some(status.to_string()),
+            Err(_) => {}
+        }
+    }
+    Some(SidecarInfo {
+        running: pid.is_some(),
+        pid,
+        uptime_secs: pid.map(|_| supervised.started_at.elapsed().as_secs()),
+        binary_path: supervised
+            .spec
+            .resolve_path(app)
+            .ok()
+            .map(|path| path.display().to_string()),
+        restarts: supervised.restarts,
+        last_exit: supervised.last_exit.clone(),
+    })
+}
+
+/// What the sidecar was last started with, or will be on its next start.
+pub fn environment<R: Runtime>(app: &AppHandle<R>, name: &str) -> Option<Vec<EnvVar>> {
+    let slot = slot(app, name)?;
+    let supervised = slot.lock().ok()?;
+    Some(supervised.spec.effective_env())
+}
+
+/// Stops every supervised sidecar; used on exit.
+pub fn stop_all<R: Runtime>(app: &AppHandle<R>) {
+    let Some(sidecars) = app.try_state::<Sidecars>() else {
+        return;
+    };
+    let Ok(map) = sidecars.0.lock() else {
+        return;
+    };
+    for slot in map.values() {
+        if let Ok(mut supervised) = slot.lock() {
+            supervised.retry_at = None;
+            stop_child(&mut supervised);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn list_sidecars(sidecars: tauri::State<Sidecars>) -> Result<Vec<SidecarStatus>, AppError> {
+    let map = sidecars
+        .0
+        .lock()
+        .map_err(|_| AppError::unavailable("Sidecar supervisor is unavailable."))?;
+    Ok(map
+        .iter()
+        .map(|(name, slot)| {
+            let mut pid = None;
+            if let Ok(mut supervised) = slot.lock() {
+                // A process that exited on its own is reported as stopped.
+                if let Some(child) = supervised.child.as_mut() {
+                    if matches!(child.try_wait(), Ok(None)) {
+                        pid = Some(child.id());
+                    }
+                }
+            }
+            SidecarStatus {
+                name: name.clone(),
+                running: pid.is_some(),
+                pid,
+            }
+        })
+        .collect())
+}
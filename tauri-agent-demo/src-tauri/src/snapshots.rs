@@ -0,0 +1,230 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::Local;
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    archive, atomic_file,
+    error::AppError,
+    events, inbox,
+    settings::{SettingsStore, ShellSettings},
+    shortcuts, workspace,
+};
+
+const SNAPSHOT_VERSION: u32 = 1;
+pub const MANIFEST_FILE: &str = "snapshot.json";
+const DATABASE_FILE: &str = "chat_app.db";
+const SETTINGS_FILE: &str = "shell_settings.json";
+/// Backend-owned files copied verbatim.
+const CONFIG_FILES: &[&str] = &["app_config.json", "tools_config.json"];
+const MAX_NAME_LEN: usize = 64;
+
+events::payload! {
+    #[derive(Deserialize)]
+    pub struct SnapshotInfo {
+        version: u32,
+        pub name: String,
+        pub created_at: String,
+        /// Files captured next to the manifest; configs missing here did not exist yet.
+        files: Vec<String>,
+    }
+}
+
+pub fn snapshots_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = workspace::data_dir(app)?.join("snapshots");
+    fs::create_dir_all(&dir)
+        .map_err(|err| format!("Failed to create snapshot directory: {err}"))?;
+    Ok(dir)
+}
+
+fn validate_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(format!(
+            "Snapshot names must be 1-{MAX_NAME_LEN} characters."
+        ));
+    }
+    if name.starts_with('.')
+        || !name
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || matches!(ch, '-' | '_' | '.' | ' '))
+    {
+        return Err(
+            "Snapshot names may only contain letters, digits, spaces, '-', '_' and '.'."
+                .to_string(),
+        );
+    }
+    Ok(name)
+}
+
+pub fn read_info(dir: &Path) -> Result<SnapshotInfo, String> {
+    let raw = fs::read_to_string(dir.join(MANIFEST_FILE))
+        .map_err(|err| format!("Failed to read snapshot manifest: {err}"))?;
+    serde_json::from_str(&raw).map_err(|err| format!("Invalid snapshot manifest: {err}"))
+}
+
+pub fn capture<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<SnapshotInfo, String> {
+    let name = validate_name(name)?;
+    let app_data_dir = workspace::data_dir(app)?;
+    let root = snapshots_dir(app)?;
+    let dir = root.join(name);
+    if dir.exists() {
+        return Err(format!("A snapshot named '{name}' already exists."));
+    }
+    // Build in a hidden staging folder so a failed capture never looks like a snapshot.
+    let staging = root.join(format!(".{name}.partial"));
+    let _ = fs::remove_dir_all(&staging);
+    fs::create_dir_all(&staging)
+        .map_err(|err| format!("Failed to create snapshot directory: {err}"))?;
+    let result = (|| {
+        let mut files = Vec::new();
+        let db_path = workspace::db_path(app)?;
+        if db_path.exists() {
+            archive::export_database(&db_path, &staging.join(DATABASE_FILE))?;
+            files.push(DATABASE_FILE.to_string());
+        }
+        if let Some(store) = app.try_state::<SettingsStore>() {
+            let raw = serde_json::to_string_pretty(&store.get())
+                .map_err(|err| format!("Failed to serialize settings: {err}"))?;
+            fs::write(staging.join(SETTINGS_FILE), raw)
+                .map_err(|err| format!("Failed to write settings: {err}"))?;
+            files.push(SETTINGS_FILE.to_string());
+        }
+        for config in CONFIG_FILES {
+            let source = app_data_dir.join(config);
+            if source.exists() {
+                fs::copy(&source, staging.join(config))
+                    .map_err(|err| format!("Failed to copy {config}: {err}"))?;
+                files.push(config.to_string());
+            }
+        }
+        let info = SnapshotInfo {
+            version: SNAPSHOT_VERSION,
+            name: name.to_string(),
+            created_at: Local::now().to_rfc3339(),
+            files,
+        };
+        let raw = serde_json::to_string_pretty(&info)
+            .map_err(|err| format!("Failed to serialize snapshot manifest: {err}"))?;
+        fs::write(staging.join(MANIFEST_FILE), raw)
+            .map_err(|err| format!("Failed to write snapshot manifest: {err}"))?;
+        fs::rename(&staging, &dir).map_err(|err| format!("Failed to finish snapshot: {err}"))?;
+        Ok(info)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result
+}
+
+pub fn replace_database(snapshot: &Path, db_path: &Path) -> Result<(), String> {
+    // Stale WAL pages from the old database would be replayed onto the restored one.
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = db_path.as_os_str().to_owned();
+        sidecar.push(suffix);
+        let _ = fs::remove_file(PathBuf::from(sidecar));
+    }
+    fs::copy(snapshot, db_path).map_err(|err| format!("Failed to restore database: {err}"))?;
+    Ok(())
+}
+
+fn apply_snapshot<R: Runtime>(
+    app: &AppHandle<R>,
+    dir: &Path,
+    info: &SnapshotInfo,
+) -> Result<(), String> {
+    let app_data_dir = workspace::data_dir(app)?;
+    let has = |file: &str| info.files.iter().any(|captured| captured == file);
+    if has(DATABASE_FILE) {
+        replace_database(&dir.join(DATABASE_FILE), &workspace::db_path(app)?)?;
+    }
+    for config in CONFIG_FILES {
+        let target = app_data_dir.join(config);
+        if has(config) {
+            atomic_file::copy(&dir.join(config), &target)
+                .map_err(|err| format!("Failed to restore {config}: {err}"))?;
+        } else if target.exists() {
+            fs::remove_file(&target).map_err(|err| format!("Failed to remove {config}: {err}"))?;
+        }
+    }
+    if has(SETTINGS_FILE) {
+        let raw = fs::read_to_string(dir.join(SETTINGS_FILE))
+            .map_err(|err| format!("Failed to read snapshot settings: {err}"))?;
+        let restored: ShellSettings = serde_json::from_str(&raw)
+            .map_err(|err| format!("Invalid snapshot settings: {err}"))?;
+        if let Some(store) = app.try_state::<SettingsStore>() {
+            store.update(|settings| *settings = restored)?;
+        }
+    }
+    Ok(())
+}
+
+fn restore<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<SnapshotInfo, String> {
+    let name = validate_name(name)?;
+    let dir = snapshots_dir(app)?.join(name);
+    let info = read_info(&dir)?;
+    if info.version > SNAPSHOT_VERSION {
+        return Err(format!(
+            "Snapshot '{name}' was made by a newer version of the app."
+        ));
+    }
+    // Restoring is itself risky, so keep a way back.
+    let safety = format!("before-restore-{}", Local::now().format("%Y%m%d-%H%M%S"));
+    capture(app, &safety)?;
+    crate::with_backend_stopped(app, || apply_snapshot(app, &dir, &info))?;
+    if let Err(err) = shortcuts::apply(app) {
+        tracing::warn!("[Shortcuts] {err}");
+    }
+    if let Err(err) = inbox::restart(app) {
+        tracing::warn!("[Inbox] {err}");
+    }
+    tracing::info!("[Snapshots] Restored '{name}'; previous state saved as '{safety}'.");
+    events::snapshot_restored(app, &info);
+    Ok(info)
+}
+
+#[tauri::command]
+pub async fn list_snapshots(app: AppHandle) -> Result<Vec<SnapshotInfo>, AppError> {
+    let dir = snapshots_dir(&app)?;
+    let entries = fs::read_dir(&dir)
+        .map_err(|err| AppError::from("Failed to list snapshots.".to_string()).with_details(err))?;
+    let mut snapshots: Vec<SnapshotInfo> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| read_info(&entry.path()).ok())
+        .collect();
+    snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(snapshots)
+}
+
+/// Captures the chat database, shell settings and backend configs under `name`.
+#[tauri::command]
+pub async fn create_snapshot(app: AppHandle, name: String) -> Result<SnapshotInfo, AppError> {
+    tauri::async_runtime::spawn_blocking(move || capture(&app, &name))
+        .await
+        .map_err(|err| AppError::from(format!("Snapshot task failed: {err}")))?
+        .map_err(AppError::from)
+}
+
+/// Rolls the app back to `name`, restarting the backend around the swap. The
+/// current state is first saved as a `before-restore-*` snapshot.
+#[tauri::command]
+pub async fn restore_snapshot(app: AppHandle, name: String) -> Result<SnapshotInfo, AppError> {
+    tauri::async_runtime::spawn_blocking(move || restore(&app, &name))
+        .await
+        .map_err(|err| AppError::from(format!("Restore task failed: {err}")))?
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn delete_snapshot(app: AppHandle, name: String) -> Result<(), AppError> {
+    let name = validate_name(&name).map_err(AppError::invalid_input)?;
+    let dir = snapshots_dir(&app)?.join(name);
+    read_info(&dir).map_err(AppError::not_found)?;
+    fs::remove_dir_all(&dir)
+        .map_err(|err| AppError::from("Failed to delete snapshot.".to_string()).with_details(err))
+}
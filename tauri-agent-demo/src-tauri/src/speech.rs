@@ -0,0 +1,469 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+
+use crate::{audio, error::AppError, events, settings::SettingsStore};
+
+const SPEECH_PATH: &str = "/audio/speech";
+const SYNTHESIS_TIMEOUT: Duration = Duration::from_secs(60);
+/// The backend's provider limit for one request.
+const MAX_TEXT_CHARS: usize = 4096;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeechEngine {
+    /// SAPI, AVSpeechSynthesizer or speech-dispatcher; works offline.
+    #[default]
+    System,
+    /// The default LLM config's speech endpoint, played by the shell.
+    Backend,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeechSettings {
+    pub engine: SpeechEngine,
+    /// Used when `speak` names none; each engine has its own voice names.
+    pub voice: Option<String>,
+}
+
+struct Utterance {
+    id: u64,
+    text: String,
+    voice: Option<String>,
+    engine: SpeechEngine,
+}
+
+events::payload! {
+    pub struct SpeechFinished {
+        id: u64,
+        /// Cut short or dropped from the queue by `stop_speaking`.
+        interrupted: bool,
+        error: Option<String>,
+    }
+}
+
+/// One utterance at a time, in the order `speak` was called; audio stays out of
+/// the webview, whose codec support varies by platform.
+#[derive(Default)]
+pub struct Speaker {
+    queue: Mutex<Option<mpsc::Sender<Utterance>>>,
+    next_id: AtomicU64,
+    /// Utterances with a lower id were stopped.
+    stopped_before: Arc<AtomicU64>,
+}
+
+/// Waits for utterances; they only arrive after setup, so the thread idles until then.
+pub fn start(app: &AppHandle) {
+    let Some(speaker) = app.try_state::<Speaker>() else {
+        return;
+    };
+    let (tx, rx) = mpsc::channel::<Utterance>();
+    if let Ok(mut queue) = speaker.queue.lock() {
+        *queue = Some(tx);
+    }
+    let stopped_before = speaker.stopped_before.clone();
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for utterance in rx {
+            let interrupted = || utterance.id < stopped_before.load(Ordering::SeqCst);
+            let result = if interrupted() {
+                Ok(true)
+            } else {
+                match utterance.engine {
+                    SpeechEngine::System => {
+                        system_speak(&utterance.text, utterance.voice.as_deref(), &interrupted)
+                    }
+                    SpeechEngine::Backend => backend_speak(&app, &utterance, &interrupted),
+                }
+            };
+            if let Err(err) = &result {
+                tracing::warn!("[Speech] {err}");
+            }
+            events::tts_finished(
+                &app,
+                &SpeechFinished {
+                    id: utterance.id,
+                    interrupted: *result.as_ref().unwrap_or(&false),
+                    error: result.err(),
+                },
+            );
+        }
+    });
+}
+
+/// Speaks through the system voice. `Ok(true)` if it was interrupted.
+#[cfg(target_os = "windows")]
+fn system_speak(
+    text: &str,
+    voice: Option<&str>,
+    interrupted: &dyn Fn() -> bool,
+) -> Result<bool, String> {
+    use windows::{
+        core::{HSTRING, PCWSTR},
+        Win32::{
+            Foundation::WAIT_OBJECT_0,
+            Media::Speech::{
+                ISpObjectToken, ISpObjectTokenCategory, ISpVoice, SpObjectTokenCategory, SpVoice,
+                SPCAT_VOICES, SPF_ASYNC, SPF_IS_NOT_XML, SPF_PURGEBEFORESPEAK,
+            },
+            System::{
+                Com::{
+                    CoCreateInstance, CoInitializeEx, CoTaskMemFree, CLSCTX_ALL,
+                    COINIT_MULTITHREADED,
+                },
+                Threading::WaitForSingleObject,
+            },
+        },
+    };
+
+    /// The first installed voice whose description contains `wanted`.
+    unsafe fn find_voice(wanted: &str) -> windows::core::Result<Option<ISpObjectToken>> {
+        let category: ISpObjectTokenCategory =
+            CoCreateInstance(&SpObjectTokenCategory, None, CLSCTX_ALL)?;
+        category.SetId(SPCAT_VOICES, false)?;
+        let tokens = category.EnumTokens(PCWSTR::null(), PCWSTR::null())?;
+        let mut count = 0;
+        tokens.GetCount(&mut count)?;
+        let wanted = wanted.to_lowercase();
+        for index in 0..count {
+            let token = tokens.Item(index)?;
+            let description = token.GetStringValue(PCWSTR::null())?;
+            let name = description.to_string().unwrap_or_default();
+            CoTaskMemFree(Some(description.0 as _));
+            if name.to_lowercase().contains(&wanted) {
+                return Ok(Some(token));
+            }
+        }
+        Ok(None)
+    }
+
+    unsafe {
+        // Harmless if the thread already joined an apartment.
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        let speaker: ISpVoice = CoCreateInstance(&SpVoice, None, CLSCTX_ALL)
+            .map_err(|err| format!("SAPI is unavailable: {err}"))?;
+        if let Some(wanted) = voice {
+            match find_voice(wanted) {
+                Ok(Some(token)) => {
+                    let _ = speaker.SetVoice(&token);
+                }
+                Ok(None) => tracing::warn!("[Speech] No SAPI voice matches {wanted}."),
+                Err(err) => tracing::warn!("[Speech] Failed to list SAPI voices: {err}"),
+            }
+        }
+        speaker
+            .Speak(
+                &HSTRING::from(text),
+                (SPF_ASYNC.0 | SPF_IS_NOT_XML.0) as u32,
+                None,
+            )
+            .map_err(|err| format!("SAPI failed to speak: {err}"))?;
+        let done = speaker.SpeakCompleteEvent();
+        loop {
+            if WaitForSingleObject(done, POLL_INTERVAL.as_millis() as u32) == WAIT_OBJECT_0 {
+                return Ok(false);
+            }
+            if interrupted() {
+                let _ = speaker.Speak(PCWSTR::null(), SPF_PURGEBEFORESPEAK.0 as u32, None);
+                return Ok(true);
+            }
+        }
+    }
+}
+
+/// `voice` is an identifier such as `com.apple.voice.compact.en-US.Samantha`;
+/// without one the system default voice is used.
+#[cfg(target_os = "macos")]
+fn system_speak(
+    text: &str,
+    voice: Option<&str>,
+    interrupted: &dyn Fn() -> bool,
+) -> Result<bool, String> {
+    use objc2_avf_audio::{
+        AVSpeechBoundary, AVSpeechSynthesisVoice, AVSpeechSynthesizer, AVSpeechUtterance,
+    };
+    use objc2_foundation::NSString;
+
+    unsafe {
+        let synthesizer = AVSpeechSynthesizer::new();
+        let utterance = AVSpeechUtterance::speechUtteranceWithString(&NSString::from_str(text));
+        let chosen = voice.and_then(|voice| {
+            AVSpeechSynthesisVoice::voiceWithIdentifier(&NSString::from_str(voice))
+        });
+        if chosen.is_none() && voice.is_some() {
+            tracing::warn!(
+                "[Speech] The voice {} is not installed.",
+                voice.unwrap_or_default()
+            );
+        }
+        utterance.setVoice(chosen.as_deref());
+        synthesizer.speakUtterance(&utterance);
+        // Speaking starts asynchronously; give it a moment before taking idle as done.
+        let mut started = false;
+        let mut waited = Duration::ZERO;
+        loop {
+            if interrupted() {
+                synthesizer.stopSpeakingAtBoundary(AVSpeechBoundary::Immediate);
+                return Ok(true);
+            }
+            if synthesizer.isSpeaking() {
+                started = true;
+            } else if started || waited >= Duration::from_secs(2) {
+                return Ok(false);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+            waited += POLL_INTERVAL;
+        }
+    }
+}
+
+/// Through speech-dispatcher's `spd-say`, which most desktops ship.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn system_speak(
+    text: &str,
+    voice: Option<&str>,
+    interrupted: &dyn Fn() -> bool,
+) -> Result<bool, String> {
+    use std::process::{Command, Stdio};
+
+    let mut command = Command::new("spd-say");
+    command.arg("--wait");
+    if let Some(voice) = voice {
+        command.args(["--synthesis-voice", voice]);
+    }
+    let mut child = command
+        .arg("--")
+        .arg(text)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("speech-dispatcher is unavailable (spd-say): {err}"))?;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(false),
+            Ok(Some(status)) => return Err(format!("spd-say exited with {status}.")),
+            Ok(None) => {}
+            Err(err) => return Err(format!("Failed to wait for spd-say: {err}")),
+        }
+        if interrupted() {
+            let _ = child.kill();
+            let _ = child.wait();
+            // Killing the client leaves the server talking.
+            let _ = Command::new("spd-say").arg("--cancel").status();
+            return Ok(true);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// PCM from a WAV file, as f32 frames.
+struct Clip {
+    sample_rate: u32,
+    channels: usize,
+    samples: Vec<f32>,
+}
+
+/// 16-bit integer and 32-bit float PCM; other encodings are refused.
+fn decode_wav(bytes: &[u8]) -> Result<Clip, String> {
+    if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("The backend did not send a WAV file.".to_string());
+    }
+    let mut format = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let declared = u32::from_le_bytes([
+            bytes[offset + 4],
+            bytes[offset + 5],
+            bytes[offset + 6],
+            bytes[offset + 7],
+        ]) as usize;
+        let body = offset + 8;
+        // Streamed WAVs leave the data size unset; take the rest of the file.
+        let end = body.saturating_add(declared).min(bytes.len());
+        match id {
+            b"fmt " if end - body >= 16 => {
+                let field =
+                    |at: usize| u16::from_le_bytes([bytes[body + at], bytes[body + at + 1]]);
+                let rate = u32::from_le_bytes([
+                    bytes[body + 4],
+                    bytes[body + 5],
+                    bytes[body + 6],
+                    bytes[body + 7],
+                ]);
+                format = Some((field(0), field(2), rate, field(14)));
+            }
+            b"data" => {
+                let (encoding, channels, sample_rate, bits) =
+                    format.ok_or_else(|| "The WAV file has no format chunk.".to_string())?;
+                let data = &bytes[body..end];
+                let samples = match (encoding, bits) {
+                    (1, 16) => data
+                        .chunks_exact(2)
+                        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]) as f32 / 32768.0)
+                        .collect(),
+                    (3, 32) => data
+                        .chunks_exact(4)
+                        .map(|quad| f32::from_le_bytes([quad[0], quad[1], quad[2], quad[3]]))
+                        .collect(),
+                    _ => {
+                        return Err(format!(
+                            "WAV encoding {encoding} at {bits} bits is not supported."
+                        ))
+                    }
+                };
+                return Ok(Clip {
+                    sample_rate: sample_rate.max(1),
+                    channels: channels.max(1) as usize,
+                    samples,
+                });
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even size.
+        offset = end + (declared & 1);
+    }
+    Err("The WAV file has no audio data.".to_string())
+}
+
+/// Plays `clip` on the default output device, resampled and spread over its
+/// channels. `Ok(true)` if it was interrupted.
+fn play(clip: Clip, interrupted: &dyn Fn() -> bool) -> Result<bool, String> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or_else(|| "No audio output device is available.".to_string())?;
+    let config = device
+        .default_output_config()
+        .map_err(|err| format!("Failed to read the output config: {err}"))?;
+    if config.sample_format() != cpal::SampleFormat::F32 {
+        return Err(format!(
+            "Unsupported output sample format '{}'.",
+            config.sample_format()
+        ));
+    }
+    let out_channels = config.channels().max(1) as usize;
+    let step = clip.sample_rate as f64 / config.sample_rate().0 as f64;
+    let frames = clip.samples.len() / clip.channels;
+    let finished = Arc::new(AtomicBool::new(false));
+    let done = finished.clone();
+    let mut position = 0.0f64;
+    let stream = device
+        .build_output_stream(
+            &config.config(),
+            move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                for frame in data.chunks_mut(out_channels) {
+                    let index = position as usize;
+                    if index + 1 >= frames {
+                        frame.fill(0.0);
+                        done.store(true, Ordering::SeqCst);
+                        continue;
+                    }
+                    let fraction = (position - index as f64) as f32;
+                    for (channel, out) in frame.iter_mut().enumerate() {
+                        // Mono is copied to every channel, extra input channels dropped.
+                        let source = channel.min(clip.channels - 1);
+                        let current = clip.samples[index * clip.channels + source];
+                        let next = clip.samples[(index + 1) * clip.channels + source];
+                        *out = current + (next - current) * fraction;
+                    }
+                    position += step;
+                }
+            },
+            |err| tracing::warn!("[Speech] Output stream error: {err}"),
+            None,
+        )
+        .map_err(|err| format!("Failed to open the audio output: {err}"))?;
+    stream
+        .play()
+        .map_err(|err| format!("Failed to start the audio output: {err}"))?;
+    loop {
+        if interrupted() {
+            return Ok(true);
+        }
+        if finished.load(Ordering::SeqCst) {
+            // Let the device drain what it has buffered.
+            std::thread::sleep(Duration::from_millis(200));
+            return Ok(false);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn backend_speak(
+    app: &AppHandle,
+    utterance: &Utterance,
+    interrupted: &dyn Fn() -> bool,
+) -> Result<bool, String> {
+    let bytes = audio::post_bytes(
+        app,
+        SPEECH_PATH,
+        &json!({ "text": utterance.text, "voice": utterance.voice }),
+        SYNTHESIS_TIMEOUT,
+    )?;
+    if interrupted() {
+        return Ok(true);
+    }
+    play(decode_wav(&bytes)?, interrupted)
+}
+
+/// Queues `text` behind anything already speaking and returns its id, which
+/// `tts://finished` reports once it is done.
+#[tauri::command]
+pub fn speak(
+    store: tauri::State<SettingsStore>,
+    speaker: tauri::State<Speaker>,
+    text: String,
+    voice: Option<String>,
+) -> Result<u64, AppError> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(AppError::invalid_input("There is nothing to speak."));
+    }
+    if text.chars().count() > MAX_TEXT_CHARS {
+        return Err(AppError::invalid_input(format!(
+            "Text to speak is limited to {MAX_TEXT_CHARS} characters."
+        )));
+    }
+    let settings = store.get().speech;
+    let id = speaker.next_id.fetch_add(1, Ordering::SeqCst);
+    let utterance = Utterance {
+        id,
+        text,
+        voice: voice.filter(|voice| !voice.is_empty()).or(settings.voice),
+        engine: settings.engine,
+    };
+    speaker
+        .queue
+        .lock()
+        .ok()
+        .and_then(|queue| queue.as_ref()?.send(utterance).ok())
+        .ok_or_else(|| AppError::unavailable("Speech is not available yet."))?;
+    Ok(id)
+}
+
+/// Cuts off the current utterance and drops the queued ones; each still gets its
+/// `tts://finished`.
+#[tauri::command]
+pub fn stop_speaking(speaker: tauri::State<Speaker>) {
+    let next = speaker.next_id.load(Ordering::SeqCst);
+    speaker.stopped_before.store(next, Ordering::SeqCst);
+}
+
+#[tauri::command]
+pub fn set_speech_settings(
+    store: tauri::State<SettingsStore>,
+    settings: SpeechSettings,
+) -> Result<SpeechSettings, AppError> {
+    Ok(store.update(|current| current.speech = settings)?.speech)
+}
@@ -0,0 +1,252 @@
+use std::{
+    fs,
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{backend, network, process_tree, proxy, BACKEND_BINARY};
+
+const FILE_NAME: &str = "backend.pid";
+pub const PID_FILE_ENV: &str = "TAURI_AGENT_PID_FILE";
+pub const INSTANCE_ENV: &str = "TAURI_AGENT_INSTANCE_ID";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+const KILL_TIMEOUT: Duration = Duration::from_secs(3);
+const EXIT_POLL: Duration = Duration::from_millis(200);
+
+/// Written by the backend as it starts and removed when it exits, so a file left
+/// over names a backend that outlived the app, e.g. after a force-quit.
+#[derive(Debug, Deserialize)]
+struct PidFile {
+    pid: u32,
+    #[serde(default)]
+    host: Option<String>,
+    /// `None` on the stdio transport, which leaves nothing to probe.
+    #[serde(default)]
+    port: Option<u16>,
+    #[serde(default)]
+    instance: Option<String>,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TakeoverAction {
+    /// The recorded process is gone, or the pid now belongs to something else.
+    Cleared,
+    /// It answered as the recorded instance and exited when asked to.
+    ShutDown,
+    /// It did not answer, or did not exit in time, and was killed.
+    Killed,
+    /// It is still running; the new sidecar may find the database locked.
+    Failed,
+}
+
+/// What startup did about a backend left by an earlier launch. It is never
+/// adopted: without a child handle the supervisor could neither restart nor stop it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Takeover {
+    action: TakeoverAction,
+    pid: Option<u32>,
+    detail: String,
+}
+
+/// The last takeover this session, for `backend_info`.
+#[derive(Default)]
+pub struct StaleBackend(Mutex<Option<Takeover>>);
+
+pub fn path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    Ok(crate::resolve_app_data_dir(app)?.join(FILE_NAME))
+}
+
+/// A fresh id for each spawn, echoed by the backend's `/health`.
+pub fn instance_id() -> String {
+    let mut bytes = [0u8; 16];
+    let _ = SystemRandom::new().fill(&mut bytes);
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub fn last<R: Runtime>(app: &AppHandle<R>) -> Option<Takeover> {
+    app.try_state::<StaleBackend>()?.0.lock().ok()?.clone()
+}
+
+fn refresh(system: &mut System, pid: Pid) {
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet),
+    );
+}
+
+/// Whether `pid` still runs the sidecar binary rather than whatever reused the id.
+fn is_backend(system: &mut System, pid: Pid) -> bool {
+    refresh(system, pid);
+    system
+        .process(pid)
+        .and_then(|process| process.exe())
+        .and_then(|exe| exe.file_stem())
+        .is_some_and(|stem| stem.to_string_lossy().starts_with(BACKEND_BINARY))
+}
+
+fn wait_exit(system: &mut System, pid: Pid, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        refresh(system, pid);
+        if system.process(pid).is_none() {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(EXIT_POLL);
+    }
+}
+
+/// Blocking, on a thread of its own like `backend::request_shutdown`. The old
+/// backend's token, not this launch's, opens it.
+fn call(method: reqwest::Method, url: String, token: Option<String>) -> Option<Value> {
+    thread::spawn(move || {
+        tauri::async_runtime::block_on(async move {
+            let client = reqwest::Client::builder()
+                .no_proxy()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .ok()?;
+            let mut request = client.request(method, url);
+            if let Some(token) = token {
+                request = request.header(proxy::TOKEN_HEADER, token);
+            }
+            let response = request.send().await.ok()?;
+            if !response.status().is_success() {
+                return None;
+            }
+            response.json::<Value>().await.ok()
+        })
+    })
+    .join()
+    .ok()
+    .flatten()
+}
+
+/// The recorded address, when `/health` there answers as the recorded instance.
+/// A backend without a token answers anyone, so the instance id is what proves it.
+fn reachable(file: &PidFile) -> Option<String> {
+    let host: IpAddr = file.host.as_deref()?.parse().ok()?;
+    let addr = SocketAddr::new(network::connect_host(host), file.port?);
+    let base_url = format!("http://{addr}");
+    let instance = file.instance.as_deref()?;
+    let health = call(
+        reqwest::Method::GET,
+        format!("{base_url}/health"),
+        file.token.clone(),
+    )?;
+    (health.get("instance").and_then(Value::as_str) == Some(instance)).then_some(base_url)
+}
+
+/// The address and token of the backend a running app owns, for the command line
+/// to share rather than starting a second one on the same database.
+pub fn running<R: Runtime>(app: &AppHandle<R>) -> Option<(String, Option<String>)> {
+    let raw = fs::read_to_string(path(app).ok()?).ok()?;
+    let file: PidFile = serde_json::from_str(&raw).ok()?;
+    reachable(&file).map(|base_url| (base_url, file.token))
+}
+
+fn stop<R: Runtime>(app: &AppHandle<R>, file: &PidFile) -> Takeover {
+    let pid = Pid::from_u32(file.pid);
+    let mut system = System::new();
+    let takeover = |action, detail: String| Takeover {
+        action,
+        pid: Some(file.pid),
+        detail,
+    };
+    if !is_backend(&mut system, pid) {
+        return takeover(
+            TakeoverAction::Cleared,
+            format!("Process {} is gone or is no longer the backend.", file.pid),
+        );
+    }
+    if let Some(base_url) = reachable(file) {
+        let timeout = Duration::from_secs(backend::shutdown_settings(app).timeout_secs);
+        let asked = call(
+            reqwest::Method::POST,
+            format!("{base_url}/shutdown"),
+            file.token.clone(),
+        )
+        .is_some();
+        if asked && wait_exit(&mut system, pid, timeout) {
+            return takeover(
+                TakeoverAction::ShutDown,
+                format!("The backend at {base_url} exited when asked to."),
+            );
+        }
+    }
+    process_tree::kill(file.pid);
+    if wait_exit(&mut system, pid, KILL_TIMEOUT) {
+        takeover(
+            TakeoverAction::Killed,
+            "The backend did not exit on request and was killed.".to_string(),
+        )
+    } else {
+        takeover(
+            TakeoverAction::Failed,
+            "The backend could not be stopped; the database may stay locked.".to_string(),
+        )
+    }
+}
+
+/// Stops a backend an earlier launch left running before a new one is spawned, so
+/// the two do not share the database. The single-instance plugin already rules out
+/// a live app owning it.
+pub fn take_over<R: Runtime>(app: &AppHandle<R>) {
+    let path = match path(app) {
+        Ok(path) => path,
+        Err(err) => {
+            tracing::warn!("[Backend] {err}");
+            return;
+        }
+    };
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return,
+        Err(err) => {
+            tracing::warn!("[Backend] Failed to read {}: {err}", path.display());
+            return;
+        }
+    };
+    let takeover = match serde_json::from_str::<PidFile>(&raw) {
+        Ok(file) => stop(app, &file),
+        Err(err) => Takeover {
+            action: TakeoverAction::Cleared,
+            pid: None,
+            detail: format!("Unreadable pid file: {err}"),
+        },
+    };
+    let _ = fs::remove_file(&path);
+    let pid = takeover
+        .pid
+        .map(|pid| pid.to_string())
+        .unwrap_or_else(|| "?".to_string());
+    match takeover.action {
+        TakeoverAction::Cleared => {
+            tracing::info!("[Backend] Stale pid file for {pid}: {}", takeover.detail)
+        }
+        TakeoverAction::Failed => {
+            tracing::error!("[Backend] Leftover backend {pid}: {}", takeover.detail)
+        }
+        _ => tracing::warn!("[Backend] Leftover backend {pid}: {}", takeover.detail),
+    }
+    if let Some(state) = app.try_state::<StaleBackend>() {
+        if let Ok(mut last) = state.0.lock() {
+            *last = Some(takeover);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{appearance, autostart, backend, error::AppError, events, main_window};
+
+const SPLASH_LABEL: &str = "splash";
+/// Added to the backend's readiness timeout before the window is shown anyway, so a
+/// broken frontend cannot leave the app invisible either.
+const SHOW_MARGIN: Duration = Duration::from_secs(5);
+const SPLASH_POLL: Duration = Duration::from_secs(1);
+
+events::payload_enum! {
+    /// Where launch stands, in the order the stages happen.
+    pub enum Stage {
+        /// Settings, data folders and workspaces.
+        Resolving,
+        /// Importing data from an older install.
+        Migrating,
+        /// Verifying the sidecar binary and launching it.
+        Spawning,
+        /// Polling `/health`.
+        Waiting,
+        Ready,
+        Failed,
+    }
+}
+
+events::payload! {
+    pub struct StageUpdate {
+        stage: Stage,
+        /// The error for `Failed`.
+        detail: Option<String>,
+    }
+}
+
+/// The main window starts hidden and is shown once the webview has rendered and the
+/// backend answers, so the user never sees a blank page or a failed first request.
+#[derive(Default)]
+pub struct StartupGate {
+    frontend: AtomicBool,
+    backend: AtomicBool,
+    shown: AtomicBool,
+    stage: Mutex<Option<StageUpdate>>,
+}
+
+pub fn splash_open<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.get_webview_window(SPLASH_LABEL).is_some()
+}
+
+fn current<R: Runtime>(app: &AppHandle<R>) -> Option<StageUpdate> {
+    app.try_state::<StartupGate>()?.stage.lock().ok()?.clone()
+}
+
+/// Announces progress on `startup://stage`.
+pub fn stage<R: Runtime>(app: &AppHandle<R>, stage: Stage, detail: Option<String>) {
+    let update = StageUpdate { stage, detail };
+    if let Some(gate) = app.try_state::<StartupGate>() {
+        if let Ok(mut current) = gate.stage.lock() {
+            *current = Some(update.clone());
+        }
+    }
+    tracing::info!("[Startup] Stage {stage:?}.");
+    events::startup_stage(app, &update);
+}
+
+/// Shown from the first moment of setup when `backend.readiness.splash` is on, and
+/// closed when the main window appears. Not for a launch at login in background mode.
+pub fn open_splash<R: Runtime>(app: &AppHandle<R>) {
+    if !backend::readiness_settings(app).splash || autostart::starts_hidden(app) {
+        return;
+    }
+    let built = WebviewWindowBuilder::new(app, SPLASH_LABEL, WebviewUrl::App("splash.html".into()))
+        .title("GYY")
+        .inner_size(360.0, 200.0)
+        .resizable(false)
+        .decorations(false)
+        .center()
+        .skip_taskbar(true)
+        .theme(appearance::theme_override(app))
+        .build();
+    if let Err(err) = built {
+        tracing::warn!("[Startup] Failed to open the splash window: {err}");
+    }
+}
+
+fn show_main<R: Runtime>(app: &AppHandle<R>, gate: &StartupGate) {
+    if gate.shown.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    if let Some(splash) = app.get_webview_window(SPLASH_LABEL) {
+        let _ = splash.close();
+    }
+    if autostart::starts_hidden(app) {
+        tracing::info!("[Startup] Started at login; staying in the tray.");
+        return;
+    }
+    main_window::reveal(app);
+}
+
+fn settle<R: Runtime>(app: &AppHandle<R>, ready: impl Fn(&StartupGate) -> &AtomicBool) {
+    let Some(gate) = app.try_state::<StartupGate>() else {
+        return;
+    };
+    ready(&gate).store(true, Ordering::SeqCst);
+    if gate.frontend.load(Ordering::SeqCst) && gate.backend.load(Ordering::SeqCst) {
+        show_main(app, &gate);
+    }
+}
+
+/// Arms the fallback that shows the window if either signal never arrives.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let timeout = Duration::from_secs(backend::readiness_settings(app).timeout_secs) + SHOW_MARGIN;
+    let app = app.clone();
+    thread::spawn(move || {
+        thread::sleep(timeout);
+        // The splash shows progress or a retry button meanwhile, so the app is not
+        // invisible; only a frontend that never renders is waited out.
+        while splash_open(&app) && current(&app).is_none_or(|update| update.stage != Stage::Ready) {
+            thread::sleep(SPLASH_POLL);
+        }
+        if let Some(gate) = app.try_state::<StartupGate>() {
+            if !gate.shown.load(Ordering::SeqCst) {
+                tracing::warn!("[Startup] Ready signals timed out; showing the window.");
+                show_main(&app, &gate);
+            }
+        }
+    });
+}
+
+/// Called by `backend` once `/health` answered or it gave up. Without a splash a
+/// failure counts as settled too, so the main window can show the error; with one,
+/// the splash keeps it on screen with a retry button instead.
+pub fn backend_settled<R: Runtime>(app: &AppHandle<R>, ready: bool) {
+    if !ready && splash_open(app) {
+        return;
+    }
+    settle(app, |gate| &gate.backend);
+}
+
+/// Called by the main webview once its first render is on screen.
+#[tauri::command]
+pub fn frontend_ready(app: AppHandle) {
+    settle(&app, |gate| &gate.frontend);
+}
+
+/// For the splash, which may load after the first stages were announced.
+#[tauri::command]
+pub fn get_startup_stage(app: AppHandle) -> Option<StageUpdate> {
+    current(&app)
+}
+
+/// The splash's retry button: relaunches the sidecar, or polls the selected
+/// backend again, and waits for it as at launch.
+#[tauri::command]
+pub async fn retry_startup(app: AppHandle) -> Result<(), AppError> {
+    tauri::async_runtime::spawn_blocking(move || backend::retry(&app))
+        .await
+        .map_err(|err| AppError::from(format!("Retry task failed: {err}")))?
+}
+
+/// The splash's other button: opens the main window despite the failure, e.g. to
+/// pick another backend profile.
+#[tauri::command]
+pub fn dismiss_splash(app: AppHandle) {
+    if let Some(splash) = app.get_webview_window(SPLASH_LABEL) {
+        let _ = splash.close();
+    }
+    settle(&app, |gate| &gate.backend);
+}
@@ -0,0 +1,443 @@
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    error::{AppError, ErrorCode},
+    events, proxy, BackendState,
+};
+
+events::payload_enum! {
+    /// The backend cannot suspend a run, so `Pause` stops the reply stream and keeps the
+    /// conversation to be continued with the next message. `Abort` also cancels every
+    /// open task of the conversation, delegated subtasks included.
+    // Only the Windows and macOS buttons send controls so far.
+    #[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+    #[derive(Deserialize)]
+    pub enum TaskControl {
+        Pause,
+        Abort,
+    }
+}
+
+events::payload! {
+    #[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+    pub struct TaskControlApplied {
+        session_id: String,
+        control: TaskControl,
+    }
+}
+
+/// The conversation the main window is currently streaming, as reported by the frontend.
+#[derive(Default)]
+pub struct ActiveRun(Mutex<Option<String>>);
+
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+fn active_session(app: &AppHandle) -> Option<String> {
+    app.try_state::<ActiveRun>()
+        .and_then(|state| state.0.lock().ok()?.clone())
+}
+
+/// Adds the task buttons to the main window's taskbar thumbnail on Windows and the
+/// dock menu on macOS. Both stay disabled while nothing is running.
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    install_platform(app)
+}
+
+#[tauri::command]
+pub fn set_active_run(app: AppHandle, session_id: Option<String>) {
+    let session_id = session_id.filter(|id| !id.is_empty());
+    let active = session_id.is_some();
+    if let Some(state) = app.try_state::<ActiveRun>() {
+        if let Ok(mut current) = state.0.lock() {
+            *current = session_id;
+        }
+    }
+    if let Err(err) = refresh_platform(&app, active) {
+        tracing::warn!("[Taskbar] {err}");
+    }
+}
+
+/// Runs `control` from a platform button without blocking the UI thread.
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+fn dispatch(app: &AppHandle, control: TaskControl) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = apply(&app, control).await {
+            tracing::warn!("[Taskbar] {err}");
+        }
+    });
+}
+
+/// Sends `control` for the active run to the backend and tells the webview through
+/// `task-control://applied` so it can settle the stream as if stopped by hand.
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+async fn apply(app: &AppHandle, control: TaskControl) -> Result<(), AppError> {
+    let session_id = active_session(app)
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "No task is running."))?;
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| AppError::unavailable("Backend is not available."))?;
+    let client = proxy::client(app);
+    let network = |err: reqwest::Error| {
+        AppError::new(ErrorCode::Network, "Failed to reach the backend.").with_details(err)
+    };
+
+    if control == TaskControl::Abort {
+        let tasks: Vec<Value> = client
+            .get(format!("{base_url}/tasks"))
+            .query(&[("session_id", session_id.as_str())])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(network)?
+            .json()
+            .await
+            .map_err(network)?;
+        let open = tasks.iter().filter(|task| {
+            matches!(
+                task.get("status").and_then(Value::as_str),
+                Some("pending" | "running" | "blocked")
+            )
+        });
+        for task_id in open.filter_map(|task| task.get("id").and_then(Value::as_str)) {
+            client
+                .post(format!("{base_url}/tasks/{task_id}/cancel"))
+                .json(&json!({ "reason": "Aborted by user", "propagate": true }))
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(network)?;
+        }
+    }
+    client
+        .post(format!("{base_url}/chat/stop"))
+        .json(&json!({ "session_id": session_id }))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(network)?;
+    events::task_control_applied(
+        app,
+        &TaskControlApplied {
+            session_id,
+            control,
+        },
+    );
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+mod thumbbar {
+    use std::{cell::RefCell, sync::OnceLock};
+
+    use tauri::AppHandle;
+    use windows::{
+        core::w,
+        Win32::{
+            Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+            System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER},
+            UI::{
+                Shell::{
+                    DefSubclassProc, ITaskbarList3, SetWindowSubclass, TaskbarList, THBF_DISABLED,
+                    THBF_ENABLED, THBN_CLICKED, THB_FLAGS, THB_ICON, THB_TOOLTIP, THUMBBUTTON,
+                },
+                WindowsAndMessaging::{CreateIcon, RegisterWindowMessageW, HICON, WM_COMMAND},
+            },
+        },
+    };
+
+    use super::{active_session, dispatch, TaskControl};
+
+    const SUBCLASS_ID: usize = 0x7461_736b;
+    const PAUSE_ID: u32 = 1;
+    const ABORT_ID: u32 = 2;
+    const ICON_SIZE: usize = 16;
+
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+
+    thread_local! {
+        /// Set once the taskbar has created the thumbnail; only touched on the UI thread.
+        static TASKBAR: RefCell<Option<(HWND, ITaskbarList3)>> = const { RefCell::new(None) };
+    }
+
+    /// Subclasses the main window so the buttons can be added once the taskbar button
+    /// exists and their clicks, which arrive as `WM_COMMAND`, are seen.
+    pub fn install(app: &AppHandle, hwnd: HWND) -> Result<(), String> {
+        let _ = APP.set(app.clone());
+        let installed = unsafe { SetWindowSubclass(hwnd, Some(subclass_proc), SUBCLASS_ID, 0) };
+        if installed.as_bool() {
+            Ok(())
+        } else {
+            Err("Failed to hook the main window for taskbar buttons.".to_string())
+        }
+    }
+
+    pub fn refresh(active: bool) -> Result<(), String> {
+        TASKBAR.with(|taskbar| {
+            let Some((hwnd, list)) = taskbar.borrow().clone() else {
+                return Ok(());
+            };
+            unsafe { list.ThumbBarUpdateButtons(hwnd, &buttons(active, false)) }
+                .map_err(|err| format!("Failed to update taskbar buttons: {err}"))
+        })
+    }
+
+    fn taskbar_created() -> u32 {
+        static MESSAGE: OnceLock<u32> = OnceLock::new();
+        *MESSAGE.get_or_init(|| unsafe { RegisterWindowMessageW(w!("TaskbarButtonCreated")) })
+    }
+
+    unsafe extern "system" fn subclass_proc(
+        hwnd: HWND,
+        message: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+        _id: usize,
+        _data: usize,
+    ) -> LRESULT {
+        if message == taskbar_created() {
+            if let Err(err) = add_buttons(hwnd) {
+                tracing::warn!("[Taskbar] {err}");
+            }
+        } else if message == WM_COMMAND && (wparam.0 >> 16) as u32 & 0xffff == THBN_CLICKED {
+            let control = match (wparam.0 & 0xffff) as u32 {
+                PAUSE_ID => Some(TaskControl::Pause),
+                ABORT_ID => Some(TaskControl::Abort),
+                _ => None,
+            };
+            if let (Some(control), Some(app)) = (control, APP.get()) {
+                dispatch(app, control);
+                return LRESULT(0);
+            }
+        }
+        unsafe { DefSubclassProc(hwnd, message, wparam, lparam) }
+    }
+
+    /// Explorer sends `TaskbarButtonCreated` again after it restarts, so the list is
+    /// rebuilt each time.
+    fn add_buttons(hwnd: HWND) -> Result<(), String> {
+        let list: ITaskbarList3 =
+            unsafe { CoCreateInstance(&TaskbarList, None, CLSCTX_INPROC_SERVER) }
+                .and_then(|list: ITaskbarList3| unsafe { list.HrInit() }.map(|_| list))
+                .map_err(|err| format!("Failed to reach the taskbar: {err}"))?;
+        let active = APP.get().and_then(active_session).is_some();
+        unsafe { list.ThumbBarAddButtons(hwnd, &buttons(active, true)) }
+            .map_err(|err| format!("Failed to add taskbar buttons: {err}"))?;
+        TASKBAR.with(|taskbar| *taskbar.borrow_mut() = Some((hwnd, list)));
+        Ok(())
+    }
+
+    fn buttons(active: bool, with_icons: bool) -> [THUMBBUTTON; 2] {
+        let flags = if active { THBF_ENABLED } else { THBF_DISABLED };
+        let button = |id: u32, tip: &str, icon: fn(usize, usize) -> bool| {
+            let mut button = THUMBBUTTON {
+                dwMask: THB_FLAGS | THB_TOOLTIP,
+                iId: id,
+                dwFlags: flags,
+                ..Default::default()
+            };
+            for (slot, unit) in button.szTip.iter_mut().zip(tip.encode_utf16()) {
+                *slot = unit;
+            }
+            if with_icons {
+                match glyph(icon) {
+                    Ok(handle) => {
+                        button.dwMask |= THB_ICON;
+                        button.hIcon = handle;
+                    }
+                    Err(err) => tracing::warn!("[Taskbar] Failed to draw button icon: {err}"),
+                }
+            }
+            button
+        };
+        [
+            button(PAUSE_ID, "Pause run", |x, y| {
+                (3..13).contains(&y) && ((4..7).contains(&x) || (9..12).contains(&x))
+            }),
+            button(ABORT_ID, "Abort task", |x, y| {
+                (4..12).contains(&x) && (4..12).contains(&y)
+            }),
+        ]
+    }
+
+    /// Draws a white 16x16 glyph; the taskbar has no stock pause or stop icons.
+    fn glyph(filled: fn(usize, usize) -> bool) -> windows::core::Result<HICON> {
+        let mut color = Vec::with_capacity(ICON_SIZE * ICON_SIZE * 4);
+        for y in 0..ICON_SIZE {
+            for x in 0..ICON_SIZE {
+                let value = if filled(x, y) { 0xff } else { 0 };
+                color.extend([value; 4]);
+            }
+        }
+        let mask = [0u8; ICON_SIZE * ICON_SIZE / 8];
+        let size = ICON_SIZE as i32;
+        unsafe { CreateIcon(None, size, size, 1, 32, mask.as_ptr(), color.as_ptr()) }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn install_platform(app: &AppHandle) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window is not available.".to_string())?;
+    let hwnd = window
+        .hwnd()
+        .map_err(|err| format!("Failed to access the main window: {err}"))?;
+    thumbbar::install(app, hwnd)
+}
+
+#[cfg(target_os = "windows")]
+fn refresh_platform(app: &AppHandle, active: bool) -> Result<(), String> {
+    app.run_on_main_thread(move || {
+        if let Err(err) = thumbbar::refresh(active) {
+            tracing::warn!("[Taskbar] {err}");
+        }
+    })
+    .map_err(|err| format!("Failed to update taskbar buttons: {err}"))
+}
+
+#[cfg(target_os = "macos")]
+mod dock {
+    use std::{cell::OnceCell, sync::OnceLock};
+
+    use objc2::{
+        class, define_class, ffi, msg_send,
+        rc::Retained,
+        runtime::{AnyClass, AnyObject, Imp, NSObject, Sel},
+        sel, ClassType,
+    };
+    use objc2_foundation::NSString;
+    use tauri::AppHandle;
+
+    use super::{active_session, dispatch, TaskControl};
+
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+
+    struct DockMenu {
+        menu: Retained<AnyObject>,
+        items: Vec<Retained<AnyObject>>,
+        _target: Retained<DockTarget>,
+    }
+
+    thread_local! {
+        /// AppKit objects stay on the main thread, which is the only one that asks.
+        static MENU: OnceCell<DockMenu> = const { OnceCell::new() };
+    }
+
+    define_class!(
+        #[unsafe(super(NSObject))]
+        #[name = "AgentPracticeDockTarget"]
+        struct DockTarget;
+
+        impl DockTarget {
+            #[unsafe(method(pauseRun:))]
+            fn pause_run(&self, _sender: Option<&AnyObject>) {
+                if let Some(app) = APP.get() {
+                    dispatch(app, TaskControl::Pause);
+                }
+            }
+
+            #[unsafe(method(abortRun:))]
+            fn abort_run(&self, _sender: Option<&AnyObject>) {
+                if let Some(app) = APP.get() {
+                    dispatch(app, TaskControl::Abort);
+                }
+            }
+        }
+    );
+
+    /// Builds the menu and teaches the app delegate `applicationDockMenu:`, which AppKit
+    /// asks every time the dock icon is right-clicked.
+    pub unsafe fn install(app: &AppHandle) -> Result<(), String> {
+        let _ = APP.set(app.clone());
+        let target: Retained<DockTarget> = msg_send![DockTarget::class(), new];
+        let menu: Retained<AnyObject> = msg_send![class!(NSMenu), new];
+        let _: () = msg_send![&*menu, setAutoenablesItems: false];
+        let mut items = Vec::new();
+        for (title, action) in [
+            ("Pause Run", sel!(pauseRun:)),
+            ("Abort Task", sel!(abortRun:)),
+        ] {
+            let item: Retained<AnyObject> = msg_send![class!(NSMenuItem), new];
+            let _: () = msg_send![&*item, setTitle: &*NSString::from_str(title)];
+            let _: () = msg_send![&*item, setAction: action];
+            let _: () = msg_send![&*item, setTarget: &*target];
+            let _: () = msg_send![&*menu, addItem: &*item];
+            items.push(item);
+        }
+        MENU.with(|slot| {
+            let _ = slot.set(DockMenu {
+                menu,
+                items,
+                _target: target,
+            });
+        });
+
+        let ns_app: Retained<AnyObject> = msg_send![class!(NSApplication), sharedApplication];
+        let delegate: Option<Retained<AnyObject>> = msg_send![&*ns_app, delegate];
+        let delegate = delegate.ok_or_else(|| "The app has no delegate yet.".to_string())?;
+        let imp = std::mem::transmute::<
+            unsafe extern "C-unwind" fn(&AnyObject, Sel, &AnyObject) -> *mut AnyObject,
+            Imp,
+        >(dock_menu);
+        let added = ffi::class_addMethod(
+            delegate.class() as *const AnyClass as *mut AnyClass,
+            sel!(applicationDockMenu:),
+            imp,
+            c"@@:@".as_ptr(),
+        );
+        if added.as_bool() {
+            Ok(())
+        } else {
+            Err("The app delegate already provides a dock menu.".to_string())
+        }
+    }
+
+    unsafe extern "C-unwind" fn dock_menu(
+        _this: &AnyObject,
+        _cmd: Sel,
+        _sender: &AnyObject,
+    ) -> *mut AnyObject {
+        let active = APP.get().and_then(active_session).is_some();
+        MENU.with(|slot| match slot.get() {
+            Some(dock) => {
+                for item in &dock.items {
+                    let _: () = msg_send![&**item, setEnabled: active];
+                }
+                Retained::as_ptr(&dock.menu) as *mut AnyObject
+            }
+            None => std::ptr::null_mut(),
+        })
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn install_platform(app: &AppHandle) -> Result<(), String> {
+    let handle = app.clone();
+    app.run_on_main_thread(move || {
+        if let Err(err) = unsafe { dock::install(&handle) } {
+            tracing::warn!("[Taskbar] {err}");
+        }
+    })
+    .map_err(|err| format!("Failed to add the dock menu: {err}"))
+}
+
+/// The dock menu reads the active run whenever it opens.
+#[cfg(target_os = "macos")]
+fn refresh_platform(_app: &AppHandle, _active: bool) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn install_platform(_app: &AppHandle) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn refresh_platform(_app: &AppHandle, _active: bool) -> Result<(), String> {
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Subfolder handed to the backend, which keys its own dirs by task underneath.
+const BACKEND_DIR: &str = "backend";
+
+/// Scratch space for the shell and the backend. Everything under `root` is
+/// disposable: it is emptied on exit and again on startup in case the last run
+/// crashed before it could clean up.
+pub struct TempFiles {
+    root: PathBuf,
+    live: Arc<Mutex<BTreeSet<PathBuf>>>,
+    next_id: AtomicU64,
+}
+
+/// A directory that is removed, with its contents, when dropped.
+pub struct TempDir {
+    path: PathBuf,
+    live: Arc<Mutex<BTreeSet<PathBuf>>>,
+}
+
+impl TempDir {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_dir_all(&self.path) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("[Temp] Failed to remove {}: {err}", self.path.display());
+            }
+        }
+        if let Ok(mut live) = self.live.lock() {
+            live.remove(&self.path);
+        }
+    }
+}
+
+impl TempFiles {
+    pub fn new(root: PathBuf) -> Self {
+        let files = Self {
+            root,
+            live: Arc::default(),
+            next_id: AtomicU64::new(0),
+        };
+        files.purge();
+        files
+    }
+
+    /// Where the backend allocates per-task scratch dirs.
+    pub fn backend_dir(&self) -> PathBuf {
+        self.root.join(BACKEND_DIR)
+    }
+
+    /// A fresh directory under `namespace` (e.g. `archive`), gone once the guard drops.
+    pub fn scoped(&self, namespace: &str) -> Result<TempDir, String> {
+        let path = self.root.join(namespace).join(format!(
+            "{}-{}",
+            std::process::id(),
+            self.next_id.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::create_dir_all(&path)
+            .map_err(|err| format!("Failed to create a temp folder: {err}"))?;
+        if let Ok(mut live) = self.live.lock() {
+            live.insert(path.clone());
+        }
+        Ok(TempDir {
+            path,
+            live: Arc::clone(&self.live),
+        })
+    }
+
+    /// Removes everything under the root, including dirs still held elsewhere.
+    pub fn purge(&self) {
+        let held = self.live.lock().map(|live| live.len()).unwrap_or(0);
+        if held > 0 {
+            tracing::info!("[Temp] Removing {held} temp folder(s) still in use.");
+        }
+        match fs::remove_dir_all(&self.root) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => tracing::warn!("[Temp] Failed to clear {}: {err}", self.root.display()),
+        }
+        if let Ok(mut live) = self.live.lock() {
+            live.clear();
+        }
+    }
+}
+
+/// The scratch root for this app: the OS cache dir, so it is never backed up.
+pub fn resolve_root<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    app.path()
+        .app_cache_dir()
+        .map(|dir| dir.join("tmp"))
+        .map_err(|err| format!("Failed to resolve the cache directory: {err}"))
+}
+
+pub fn scoped<R: Runtime>(app: &AppHandle<R>, namespace: &str) -> Result<TempDir, String> {
+    app.try_state::<TempFiles>()
+        .ok_or_else(|| "Temp files are unavailable.".to_string())?
+        .scoped(namespace)
+}
+
+pub fn purge<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(files) = app.try_state::<TempFiles>() {
+        files.purge();
+    }
+}
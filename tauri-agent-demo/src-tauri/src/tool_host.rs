@@ -0,0 +1,510 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    attachments::AttachmentStore,
+    clipboard,
+    process_tree::{self, ProcessTree},
+    tool_policy::{self, PermissionRequest, Verdict},
+};
+
+/// Where the sidecar finds the host, as `127.0.0.1:<port>`.
+pub const ADDRESS_ENV: &str = "TAURI_AGENT_TOOL_HOST";
+/// Sent with every call; anything else on the machine that finds the port is refused.
+pub const TOKEN_ENV: &str = "TAURI_AGENT_TOOL_HOST_TOKEN";
+/// The whole request has to arrive within this; approval prompts are not counted.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_REQUEST_BYTES: u64 = 1024 * 1024;
+const MAX_SEARCH_RESULTS: usize = 200;
+const MAX_SEARCH_DEPTH: usize = 12;
+/// Folders file search never descends into.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "dist", "build", "__pycache__"];
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+/// Output kept from each stream of a command.
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+/// How long output is still read once the command has exited or been killed.
+const OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+/// The native tools the sidecar can call. Each call goes through the workspace's
+/// tool policy, like the backend's own file writes and shell commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum NativeTool {
+    ClipboardRead,
+    ClipboardWrite,
+    Screenshot,
+    FileSearch,
+    RunCommand,
+}
+
+impl NativeTool {
+    /// The name grants are kept under.
+    fn name(self) -> &'static str {
+        match self {
+            NativeTool::ClipboardRead => "clipboard_read",
+            NativeTool::ClipboardWrite => "clipboard_write",
+            NativeTool::Screenshot => "screenshot",
+            NativeTool::FileSearch => "file_search",
+            NativeTool::RunCommand => "run_command",
+        }
+    }
+}
+
+/// The listener address and token handed to the sidecar. Chosen once at startup so
+/// a restarted backend finds the same host.
+pub struct ToolHost {
+    address: SocketAddr,
+    token: String,
+}
+
+/// Asked for by the backend before its own file writes and shell commands.
+const REQUEST_PERMISSION: &str = "request_permission";
+
+#[derive(Debug, Deserialize)]
+struct ToolCall {
+    token: String,
+    /// A [`NativeTool`], or [`REQUEST_PERMISSION`].
+    tool: String,
+    #[serde(default)]
+    args: Value,
+    /// The work path the agent is running in, which grants are scoped to.
+    #[serde(default)]
+    workspace: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ClipboardWriteArgs {
+    text: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FileSearchArgs {
+    root: String,
+    /// Matched case-insensitively against file names.
+    query: String,
+    max_results: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RunCommandArgs {
+    command: String,
+    cwd: Option<String>,
+}
+
+fn approve<R: Runtime>(
+    app: &AppHandle<R>,
+    tool: NativeTool,
+    workspace: &str,
+    detail: &str,
+) -> Result<(), String> {
+    let request = PermissionRequest::new(tool.name(), workspace, detail);
+    // A grant could not tell one command line from the next.
+    let request = match tool {
+        NativeTool::RunCommand => request.every_time(),
+        _ => request,
+    };
+    match tool_policy::decide(app, request)? {
+        Verdict::Allowed | Verdict::Granted => Ok(()),
+        Verdict::Denied => Err("The user denied this call.".to_string()),
+    }
+}
+
+fn parse_args<T: for<'de> Deserialize<'de> + Default>(args: Value) -> Result<T, String> {
+    if args.is_null() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(args).map_err(|err| format!("Invalid arguments: {err}"))
+}
+
+fn clipboard_read() -> Result<Value, String> {
+    let text = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|err| format!("Failed to read the clipboard: {err}"))?;
+    Ok(json!({ "text": text }))
+}
+
+fn clipboard_write(args: ClipboardWriteArgs) -> Result<Value, String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(args.text))
+        .map_err(|err| format!("Failed to write the clipboard: {err}"))?;
+    Ok(json!({ "written": true }))
+}
+
+/// Captures the primary display into the attachment store, so the picture passes
+/// the same scan as any other attachment.
+fn screenshot<R: Runtime>(app: &AppHandle<R>) -> Result<Value, String> {
+    let image = xcap::Monitor::all()
+        .map_err(|err| format!("Failed to list screens: {err}"))?
+        .into_iter()
+        .find(|monitor| monitor.is_primary().unwrap_or(false))
+        .ok_or_else(|| "No primary screen is connected.".to_string())?
+        .capture_image()
+        .map_err(|err| format!("Failed to take a screenshot: {err}"))?;
+    let (width, height) = image.dimensions();
+    let png = clipboard::encode_png(width, height, image.into_raw())?;
+    let name = format!(
+        "screenshot-{}.png",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let attachment = app
+        .state::<AttachmentStore>()
+        .store_bytes(&png, &name, "image/png")?;
+    if let Some(reason) = attachment.quarantine {
+        return Err(format!("The screenshot was quarantined: {reason}"));
+    }
+    Ok(json!({
+        "path": attachment.path,
+        "width": width,
+        "height": height,
+    }))
+}
+
+fn search_dir(dir: &Path, query: &str, depth: usize, limit: usize, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if found.len() >= limit {
+            return;
+        }
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        if name.starts_with('.') {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if depth < MAX_SEARCH_DEPTH && !SKIPPED_DIRS.contains(&name.as_str()) {
+                search_dir(&entry.path(), query, depth + 1, limit, found);
+            }
+        } else if name.contains(query) {
+            found.push(entry.path());
+        }
+    }
+}
+
+fn file_search(args: FileSearchArgs) -> Result<Value, String> {
+    let root = PathBuf::from(args.root.trim());
+    if !root.is_absolute() || !root.is_dir() {
+        return Err("root must be an absolute path to a folder.".to_string());
+    }
+    let query = args.query.trim().to_lowercase();
+    if query.is_empty() {
+        return Err("query must not be empty.".to_string());
+    }
+    let limit = args
+        .max_results
+        .unwrap_or(MAX_SEARCH_RESULTS)
+        .clamp(1, MAX_SEARCH_RESULTS);
+    let mut found = Vec::new();
+    search_dir(&root, &query, 0, limit, &mut found);
+    Ok(json!({ "matches": found, "truncated": found.len() >= limit }))
+}
+
+/// One output stream of a command, drained on its own thread so a chatty command
+/// cannot fill the pipe and stall. Reading goes on past the cap, keeping nothing.
+struct Capture {
+    bytes: Arc<Mutex<Vec<u8>>>,
+    done: mpsc::Receiver<()>,
+}
+
+impl Capture {
+    fn start(mut stream: impl Read + Send + 'static) -> Self {
+        let bytes = Arc::new(Mutex::new(Vec::new()));
+        let (finished, done) = mpsc::channel();
+        let sink = bytes.clone();
+        thread::spawn(move || {
+            let mut chunk = [0u8; 8192];
+            while let Ok(read @ 1..) = stream.read(&mut chunk) {
+                if let Ok(mut bytes) = sink.lock() {
+                    let room = (MAX_OUTPUT_BYTES + 1).saturating_sub(bytes.len());
+                    bytes.extend_from_slice(&chunk[..read.min(room)]);
+                }
+            }
+            let _ = finished.send(());
+        });
+        Self { bytes, done }
+    }
+
+    /// What was read by `deadline`. A process the command left in the background can
+    /// hold the pipe open long after the command itself is gone.
+    fn finish(self, deadline: Instant) -> String {
+        let _ = self
+            .done
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()));
+        let mut bytes = self
+            .bytes
+            .lock()
+            .map(|bytes| bytes.clone())
+            .unwrap_or_default();
+        let truncated = bytes.len() > MAX_OUTPUT_BYTES;
+        bytes.truncate(MAX_OUTPUT_BYTES);
+        let mut text = String::from_utf8_lossy(&bytes).into_owned();
+        if truncated {
+            text.push_str("\n… (truncated)");
+        }
+        text
+    }
+}
+
+/// Runs `args.command` in the platform shell, killing it and whatever it started
+/// once `timeout` is up.
+fn run_command(args: RunCommandArgs, timeout: Duration) -> Result<Value, String> {
+    let command = args.command.trim();
+    if command.is_empty() {
+        return Err("command must not be empty.".to_string());
+    }
+    let mut child = if cfg!(windows) {
+        let mut child = Command::new("cmd");
+        child.arg("/C").arg(command);
+        child
+    } else {
+        let mut child = Command::new("sh");
+        child.arg("-c").arg(command);
+        child
+    };
+    if let Some(cwd) = args.cwd.as_deref().filter(|cwd| !cwd.trim().is_empty()) {
+        child.current_dir(cwd);
+    }
+    // Its own process group or job, so a timeout also ends what the shell started.
+    process_tree::prepare(&mut child);
+    let mut child = child
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Failed to start the command: {err}"))?;
+    let tree = ProcessTree::attach(&child);
+    let stdout = child.stdout.take().map(Capture::start);
+    let stderr = child.stderr.take().map(Capture::start);
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                if let Some(tree) = tree {
+                    tree.terminate(Duration::ZERO);
+                }
+                break None;
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(err) => return Err(format!("Failed to wait for the command: {err}")),
+        }
+    };
+    let deadline = Instant::now() + OUTPUT_GRACE;
+    let collect = |capture: Option<Capture>| {
+        capture
+            .map(|capture| capture.finish(deadline))
+            .unwrap_or_default()
+    };
+    Ok(json!({
+        "exit_code": status.and_then(|status| status.code()),
+        "timed_out": status.is_none(),
+        "stdout": collect(stdout),
+        "stderr": collect(stderr),
+    }))
+}
+
+fn dispatch<R: Runtime>(app: &AppHandle<R>, call: ToolCall) -> Result<Value, String> {
+    if call.tool == REQUEST_PERMISSION {
+        let verdict = tool_policy::decide(app, parse_args(call.args)?)?;
+        return Ok(json!({ "verdict": verdict }));
+    }
+    let tool = serde_json::from_value::<NativeTool>(Value::String(call.tool.clone()))
+        .map_err(|_| format!("Unknown tool '{}'.", call.tool))?;
+    run(app, tool, &call.workspace, call.args)
+}
+
+fn run<R: Runtime>(
+    app: &AppHandle<R>,
+    tool: NativeTool,
+    workspace: &str,
+    args: Value,
+) -> Result<Value, String> {
+    match tool {
+        NativeTool::ClipboardRead => {
+            approve(
+                app,
+                tool,
+                workspace,
+                "It will see whatever text is on the clipboard.",
+            )?;
+            clipboard_read()
+        }
+        NativeTool::ClipboardWrite => {
+            let args: ClipboardWriteArgs = parse_args(args)?;
+            approve(app, tool, workspace, &args.text)?;
+            clipboard_write(args)
+        }
+        NativeTool::Screenshot => {
+            approve(
+                app,
+                tool,
+                workspace,
+                "It will see everything on your primary screen.",
+            )?;
+            screenshot(app)
+        }
+        NativeTool::FileSearch => {
+            let args: FileSearchArgs = parse_args(args)?;
+            approve(
+                app,
+                tool,
+                workspace,
+                &format!("Find \"{}\" under {}", args.query, args.root),
+            )?;
+            file_search(args)
+        }
+        NativeTool::RunCommand => {
+            let args: RunCommandArgs = parse_args(args)?;
+            let cwd = args.cwd.as_deref().filter(|cwd| !cwd.trim().is_empty());
+            let detail = match cwd {
+                Some(cwd) => format!("{}\n\nin {cwd}", args.command),
+                None => args.command.clone(),
+            };
+            approve(app, tool, cwd.unwrap_or(workspace), &detail)?;
+            run_command(args, COMMAND_TIMEOUT)
+        }
+    }
+}
+
+fn serve<R: Runtime>(app: &AppHandle<R>, token: &str, stream: TcpStream) -> Result<(), String> {
+    stream
+        .set_read_timeout(Some(READ_TIMEOUT))
+        .map_err(|err| format!("Failed to configure the connection: {err}"))?;
+    let mut line = String::new();
+    BufReader::new(&stream)
+        .take(MAX_REQUEST_BYTES)
+        .read_line(&mut line)
+        .map_err(|err| format!("Failed to read a tool call: {err}"))?;
+    let reply = match serde_json::from_str::<ToolCall>(&line) {
+        Ok(call) if call.token == token => match dispatch(app, call) {
+            Ok(result) => json!({ "ok": true, "result": result }),
+            Err(err) => json!({ "ok": false, "error": err }),
+        },
+        Ok(_) => json!({ "ok": false, "error": "Invalid tool host token." }),
+        Err(err) => json!({ "ok": false, "error": format!("Malformed tool call: {err}") }),
+    };
+    let mut stream = stream;
+    stream
+        .write_all(format!("{reply}\n").as_bytes())
+        .and_then(|_| stream.flush())
+        .map_err(|err| format!("Failed to answer a tool call: {err}"))
+}
+
+/// Opens the loopback listener the sidecar calls native tools through, and asks
+/// for tool permissions on. Each connection carries one JSON line
+/// `{token, tool, args}` and gets one JSON line back, `{ok, result}` or `{ok, error}`.
+pub fn start<R: Runtime>(app: &AppHandle<R>) -> Result<ToolHost, String> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .map_err(|err| format!("Failed to open the tool host: {err}"))?;
+    let address = listener
+        .local_addr()
+        .map_err(|err| format!("Failed to read the tool host address: {err}"))?;
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Failed to generate a tool host token.".to_string())?;
+    let token: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    let (handle, accept_token) = (app.clone(), token.clone());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    tracing::warn!("[ToolHost] {err}");
+                    continue;
+                }
+            };
+            let (app, token) = (handle.clone(), accept_token.clone());
+            thread::spawn(move || {
+                if let Err(err) = serve(&app, &token, stream) {
+                    tracing::warn!("[ToolHost] {err}");
+                }
+            });
+        }
+    });
+    tracing::info!("[ToolHost] Listening on {address}");
+    Ok(ToolHost { address, token })
+}
+
+/// The variables that point a spawned sidecar at the host.
+pub fn environment<R: Runtime>(app: &AppHandle<R>) -> Vec<(&'static str, String)> {
+    match app.try_state::<ToolHost>() {
+        Some(host) => vec![
+            (ADDRESS_ENV, host.address.to_string()),
+            (TOKEN_ENV, host.token.clone()),
+        ],
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(command: &str) -> RunCommandArgs {
+        RunCommandArgs {
+            command: command.to_string(),
+            cwd: None,
+        }
+    }
+
+    #[test]
+    fn output_past_the_cap_is_dropped_and_marked() {
+        let capture = Capture::start(std::io::Cursor::new(vec![b'a'; MAX_OUTPUT_BYTES * 2]));
+        let text = capture.finish(Instant::now() + Duration::from_secs(5));
+        let kept = text
+            .strip_suffix("\n… (truncated)")
+            .expect("marked as truncated");
+        assert_eq!(kept.len(), MAX_OUTPUT_BYTES);
+
+        let capture = Capture::start(std::io::Cursor::new(b"short".to_vec()));
+        assert_eq!(
+            capture.finish(Instant::now() + Duration::from_secs(5)),
+            "short"
+        );
+    }
+
+    #[test]
+    fn finished_commands_report_their_exit_code_and_output() {
+        let result = run_command(command("echo hello && exit 3"), COMMAND_TIMEOUT).unwrap();
+        assert_eq!(result["exit_code"], 3);
+        assert_eq!(result["timed_out"], false);
+        assert_eq!(result["stdout"].as_str().unwrap().trim(), "hello");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commands_past_the_timeout_are_killed() {
+        let started = Instant::now();
+        let result = run_command(command("sleep 30"), Duration::from_millis(200)).unwrap();
+        assert_eq!(result["timed_out"], true);
+        assert!(result["exit_code"].is_null());
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn empty_commands_are_refused() {
+        assert!(run_command(command("  "), COMMAND_TIMEOUT).is_err());
+    }
+}
@@ -0,0 +1,328 @@
+use std::{fs, path::PathBuf, sync::Mutex};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    approvals::{self, Decision},
+    atomic_file,
+    error::AppError,
+    events, kiosk, locale,
+};
+
+/// Longest tool name or workspace path accepted from the backend.
+const MAX_FIELD_CHARS: usize = 4096;
+
+/// "Always allow" answers: `tool` may run in `workspace` without asking again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolGrant {
+    tool: String,
+    workspace: String,
+    granted_at: String,
+}
+
+/// What the backend sends before a file write or shell command runs.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PermissionRequest {
+    tool: String,
+    /// The work path the agent is running in; grants are scoped to it.
+    workspace: String,
+    /// What exactly will happen, shown in the dialog: the command, or the file.
+    detail: String,
+    /// Asked about every time: no grant covers it and "Always allow" is not offered.
+    #[serde(skip)]
+    every_time: bool,
+}
+
+impl PermissionRequest {
+    /// The trimmed tool and workspace. Both are required: a grant for an empty
+    /// workspace would cover every folder.
+    fn scope(&self) -> Result<(&str, &str), String> {
+        let tool = self.tool.trim();
+        let workspace = self.workspace.trim();
+        if tool.is_empty()
+            || workspace.is_empty()
+            || tool.len() > MAX_FIELD_CHARS
+            || workspace.len() > MAX_FIELD_CHARS
+        {
+            return Err("A permission request needs a tool and a workspace.".to_string());
+        }
+        Ok((tool, workspace))
+    }
+
+    pub fn new(tool: &str, workspace: &str, detail: &str) -> Self {
+        Self {
+            tool: tool.to_string(),
+            workspace: workspace.to_string(),
+            detail: detail.to_string(),
+            every_time: false,
+        }
+    }
+
+    /// For calls whose detail is chosen by the agent each time, where a grant for
+    /// one would cover any other.
+    pub fn every_time(mut self) -> Self {
+        self.every_time = true;
+        self
+    }
+}
+
+events::payload! {
+    /// Sent as the approval dialog opens, so windows can show what is waiting on it.
+    pub struct PermissionPrompt {
+        tool: String,
+        workspace: String,
+        detail: String,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Allowed,
+    /// Allowed by an earlier "always allow" without asking.
+    Granted,
+    Denied,
+}
+
+/// Grants kept in `tool_policy.json`, beside the shell settings.
+pub struct ToolPolicy {
+    path: PathBuf,
+    grants: Mutex<Vec<ToolGrant>>,
+}
+
+impl ToolPolicy {
+    pub fn load(path: PathBuf) -> Self {
+        let grants = fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            grants: Mutex::new(grants),
+        }
+    }
+
+    fn list(&self) -> Vec<ToolGrant> {
+        self.grants
+            .lock()
+            .map(|grants| grants.clone())
+            .unwrap_or_default()
+    }
+
+    fn is_granted(&self, tool: &str, workspace: &str) -> bool {
+        self.grants.lock().is_ok_and(|grants| {
+            grants
+                .iter()
+                .any(|grant| grant.tool == tool && grant.workspace == workspace)
+        })
+    }
+
+    /// Applies `change` to the grants and persists them before returning.
+    fn update(&self, change: impl FnOnce(&mut Vec<ToolGrant>)) -> Result<Vec<ToolGrant>, AppError> {
+        let mut grants = self
+            .grants
+            .lock()
+            .map_err(|_| AppError::unavailable("Tool policy is unavailable."))?;
+        change(&mut grants);
+        let raw = serde_json::to_string_pretty(&*grants)
+            .map_err(|err| AppError::from(format!("Failed to serialize the tool policy: {err}")))?;
+        atomic_file::write(&self.path, raw)
+            .map_err(|err| AppError::from(format!("Failed to save the tool policy: {err}")))?;
+        Ok(grants.clone())
+    }
+}
+
+/// The verdict when `decide` need not ask: always no in kiosk mode, yes when
+/// a grant covers the call.
+fn settled(
+    policy: &ToolPolicy,
+    request: &PermissionRequest,
+    kiosk: bool,
+) -> Result<Option<Verdict>, String> {
+    let (tool, workspace) = request.scope()?;
+    if kiosk {
+        return Ok(Some(Verdict::Denied));
+    }
+    if !request.every_time && policy.is_granted(tool, workspace) {
+        return Ok(Some(Verdict::Granted));
+    }
+    Ok(None)
+}
+
+/// Decides whether the backend may run `request`, asking the user unless a grant
+/// already covers it. Blocks on the dialog, so it must run off the main thread.
+pub fn decide<R: Runtime>(
+    app: &AppHandle<R>,
+    request: PermissionRequest,
+) -> Result<Verdict, String> {
+    let policy = app.state::<ToolPolicy>();
+    if let Some(verdict) = settled(&policy, &request, kiosk::is_enabled(app))? {
+        return Ok(verdict);
+    }
+    let (tool, workspace) = request.scope()?;
+    let title = locale::t_with(app, "permission.title", &[("tool", tool)]);
+    let summary = locale::t_with(
+        app,
+        "permission.summary_in_workspace",
+        &[("workspace", workspace)],
+    );
+    events::permission_request(
+        app,
+        &PermissionPrompt {
+            tool: tool.to_string(),
+            workspace: workspace.to_string(),
+            detail: request.detail.clone(),
+        },
+    );
+    match approvals::ask(app, &title, &summary, &request.detail, !request.every_time)? {
+        Decision::AllowOnce => Ok(Verdict::Allowed),
+        Decision::AlwaysAllow => {
+            let grant = ToolGrant {
+                tool: tool.to_string(),
+                workspace: workspace.to_string(),
+                granted_at: Local::now().to_rfc3339(),
+            };
+            if let Err(err) = policy.update(|grants| grants.push(grant)) {
+                tracing::warn!("[ToolPolicy] {}", err.message);
+            }
+            Ok(Verdict::Allowed)
+        }
+        Decision::Deny => Ok(Verdict::Denied),
+    }
+}
+
+#[tauri::command]
+pub fn list_tool_grants(policy: tauri::State<ToolPolicy>) -> Vec<ToolGrant> {
+    policy.list()
+}
+
+/// Forgets the "always allow" for `tool` in `workspace`; the next call asks again.
+#[tauri::command]
+pub fn revoke_tool_grant(
+    policy: tauri::State<ToolPolicy>,
+    tool: String,
+    workspace: String,
+) -> Result<Vec<ToolGrant>, AppError> {
+    policy.update(|grants| {
+        grants.retain(|grant| !(grant.tool == tool && grant.workspace == workspace));
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A policy stored in a fresh folder under the system temp dir, removed when dropped.
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir()
+                .join(format!("tool-policy-test-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).expect("create scratch dir");
+            Self(dir)
+        }
+
+        fn policy(&self) -> ToolPolicy {
+            ToolPolicy::load(self.0.join("tool_policy.json"))
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn grant(policy: &ToolPolicy, tool: &str, workspace: &str) {
+        policy
+            .update(|grants| {
+                grants.push(ToolGrant {
+                    tool: tool.to_string(),
+                    workspace: workspace.to_string(),
+                    granted_at: String::new(),
+                })
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn requests_need_a_tool_and_a_workspace() {
+        let request = PermissionRequest::new(" write_file ", " /work ", "");
+        assert_eq!(request.scope().unwrap(), ("write_file", "/work"));
+
+        assert!(PermissionRequest::new("write_file", "", "")
+            .scope()
+            .is_err());
+        assert!(PermissionRequest::new("write_file", "  ", "")
+            .scope()
+            .is_err());
+        assert!(PermissionRequest::new("", "/work", "").scope().is_err());
+        let long = "x".repeat(MAX_FIELD_CHARS + 1);
+        assert!(PermissionRequest::new(&long, "/work", "").scope().is_err());
+        assert!(PermissionRequest::new("write_file", &long, "")
+            .scope()
+            .is_err());
+    }
+
+    #[test]
+    fn grants_are_keyed_on_tool_and_workspace() {
+        let scratch = Scratch::new("keying");
+        let policy = scratch.policy();
+        grant(&policy, "write_file", "/work");
+
+        assert!(policy.is_granted("write_file", "/work"));
+        assert!(!policy.is_granted("write_file", "/other"));
+        assert!(!policy.is_granted("run_shell", "/work"));
+        assert!(!policy.is_granted("write_file", ""));
+    }
+
+    #[test]
+    fn grants_survive_a_reload() {
+        let scratch = Scratch::new("reload");
+        grant(&scratch.policy(), "write_file", "/work");
+        let reloaded = scratch.policy();
+        assert!(reloaded.is_granted("write_file", "/work"));
+
+        reloaded
+            .update(|grants| grants.retain(|grant| grant.tool != "write_file"))
+            .unwrap();
+        assert!(!scratch.policy().is_granted("write_file", "/work"));
+    }
+
+    #[test]
+    fn settled_without_asking_only_when_granted_or_in_kiosk_mode() {
+        let scratch = Scratch::new("settled");
+        let policy = scratch.policy();
+        let request = || PermissionRequest::new("write_file", "/work", "notes.txt");
+
+        assert!(settled(&policy, &request(), false).unwrap().is_none());
+        grant(&policy, "write_file", "/work");
+        assert!(matches!(
+            settled(&policy, &request(), false).unwrap(),
+            Some(Verdict::Granted)
+        ));
+        // A grant does not cover calls that must be asked about every time.
+        assert!(settled(&policy, &request().every_time(), false)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn kiosk_mode_denies_even_granted_calls() {
+        let scratch = Scratch::new("kiosk");
+        let policy = scratch.policy();
+        grant(&policy, "write_file", "/work");
+        let request = PermissionRequest::new("write_file", "/work", "notes.txt");
+
+        assert!(matches!(
+            settled(&policy, &request, true).unwrap(),
+            Some(Verdict::Denied)
+        ));
+        assert!(settled(&policy, &PermissionRequest::new("write_file", "", ""), true).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Duration, Local};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    archive,
+    attachments::hex_digest,
+    error::{AppError, ErrorCode},
+    proxy, temp_files, workspace, BackendState,
+};
+
+/// Deleted conversations stay restorable for this long.
+const RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashEntry {
+    id: String,
+    title: String,
+    deleted_at: String,
+    purge_at: String,
+    size: u64,
+}
+
+pub fn trash_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = workspace::data_dir(app)?.join("trash");
+    fs::create_dir_all(&dir).map_err(|err| format!("Failed to create trash directory: {err}"))?;
+    Ok(dir)
+}
+
+/// Session ids come from the frontend, so they never become file names directly.
+fn entry_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.zip", hex_digest(id.as_bytes())))
+}
+
+fn read_entry(path: &Path) -> Result<(TrashEntry, DateTime<Local>), String> {
+    let manifest = archive::read_manifest(path)?;
+    let conversation = manifest
+        .conversations
+        .first()
+        .ok_or_else(|| "Trash entry has no conversation.".to_string())?;
+    let deleted_at = DateTime::parse_from_rfc3339(&manifest.created_at)
+        .map_err(|err| format!("Invalid deletion time in trash entry: {err}"))?
+        .with_timezone(&Local);
+    let entry = TrashEntry {
+        id: conversation.id.clone(),
+        title: conversation.title.clone(),
+        deleted_at: deleted_at.to_rfc3339(),
+        purge_at: (deleted_at + Duration::days(RETENTION_DAYS)).to_rfc3339(),
+        size: fs::metadata(path)
+            .map(|meta| meta.len())
+            .unwrap_or_default(),
+    };
+    Ok((entry, deleted_at))
+}
+
+/// Permanently removes entries past the retention window and returns what is left.
+pub fn purge_expired(dir: &Path) -> Vec<TrashEntry> {
+    let Ok(files) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let cutoff = Local::now() - Duration::days(RETENTION_DAYS);
+    let mut remaining = Vec::new();
+    for path in files.flatten().map(|file| file.path()) {
+        if path.extension().and_then(|ext| ext.to_str()) != Some("zip") {
+            continue;
+        }
+        match read_entry(&path) {
+            Ok((_, deleted_at)) if deleted_at < cutoff => {
+                if let Err(err) = fs::remove_file(&path) {
+                    tracing::warn!("[Trash] Failed to purge {}: {err}", path.display());
+                }
+            }
+            Ok((entry, _)) => remaining.push(entry),
+            Err(err) => tracing::warn!("[Trash] Skipping {}: {err}", path.display()),
+        }
+    }
+    remaining.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    remaining
+}
+
+/// Moves a conversation into the trash instead of deleting it outright.
+#[tauri::command]
+pub async fn trash_conversation(app: AppHandle, id: String) -> Result<TrashEntry, AppError> {
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| AppError::unavailable("Backend is not available."))?;
+    let db_path = workspace::db_path(&app)?;
+    let path = entry_path(&trash_dir(&app)?, &id);
+    let summary = tauri::async_runtime::spawn_blocking(move || {
+        archive::export_bundle(&db_path, std::slice::from_ref(&id), &path)
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Trash task failed: {err}")))??;
+    archive::remove_from_backend(&proxy::client(&app), &base_url, &summary)
+        .await
+        .map_err(|err| AppError::new(ErrorCode::Network, err))?;
+    Ok(read_entry(&summary.path).map(|(entry, _)| entry)?)
+}
+
+#[tauri::command]
+pub fn list_trash(app: AppHandle) -> Result<Vec<TrashEntry>, AppError> {
+    Ok(purge_expired(&trash_dir(&app)?))
+}
+
+#[tauri::command]
+pub async fn restore_from_trash(app: AppHandle, id: String) -> Result<String, AppError> {
+    let db_path = workspace::db_path(&app)?;
+    let path = entry_path(&trash_dir(&app)?, &id);
+    if !path.exists() {
+        return Err(AppError::not_found(format!(
+            "Conversation {id} is not in the trash."
+        )));
+    }
+    let scratch = temp_files::scoped(&app, "archive")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        archive::import_bundle(&db_path, &path, scratch.path())?;
+        fs::remove_file(&path).map_err(|err| format!("Failed to remove trash entry: {err}"))
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Restore task failed: {err}")))??;
+    Ok(id)
+}
+
+/// Empties one entry (or the whole trash when `id` is `None`) immediately.
+#[tauri::command]
+pub fn purge_trash(app: AppHandle, id: Option<String>) -> Result<(), AppError> {
+    let dir = trash_dir(&app)?;
+    let targets = match id {
+        Some(id) => vec![entry_path(&dir, &id)],
+        None => fs::read_dir(&dir)
+            .map_err(|err| AppError::from("Failed to read trash.".to_string()).with_details(err))?
+            .flatten()
+            .map(|file| file.path())
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("zip"))
+            .collect(),
+    };
+    for path in targets {
+        if path.exists() {
+            fs::remove_file(&path).map_err(|err| {
+                AppError::from("Failed to purge trash entry.".to_string()).with_details(err)
+            })?;
+        }
+    }
+    Ok(())
+}
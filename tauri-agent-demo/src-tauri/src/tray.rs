@@ -0,0 +1,342 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{
+    image::Image,
+    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Manager, PhysicalPosition, Rect, Runtime, Theme, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder,
+};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::{
+    appearance,
+    error::{AppError, ErrorCode},
+    events, kiosk, locale, main_window, notifications, proxy,
+    settings::SettingsStore,
+    BackendState,
+};
+
+/// Starts with `appearance::QUICK_CHAT_PREFIX` so the window effect applies to it.
+pub const POPUP_LABEL: &str = "quick-chat-reply";
+const POPUP_WIDTH: f64 = 360.0;
+const POPUP_HEIGHT: f64 = 200.0;
+/// Gap between the tray icon and the popup, in logical pixels.
+const POPUP_MARGIN: f64 = 8.0;
+/// Clicking the tray icon blurs the open popup first; a click this soon after the
+/// blur closes it instead of showing it again.
+const REOPEN_GRACE: Duration = Duration::from_millis(300);
+const TRAY_ID: &str = "main";
+const MENU_TOGGLE: &str = "tray-toggle-window";
+const MENU_RESTART_BACKEND: &str = "tray-restart-backend";
+const MENU_OPEN_DATA_DIR: &str = "tray-open-data-dir";
+const MENU_QUIT: &str = "tray-quit";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TraySettings {
+    /// Closing the main window hides it and keeps the backend running; Quit in
+    /// the tray menu exits.
+    pub close_to_tray: bool,
+}
+
+/// Tracks the quick-reply popup anchored to the tray icon.
+#[derive(Default)]
+pub struct QuickReply {
+    hidden_at: Mutex<Option<Instant>>,
+}
+
+/// Kiosk mode leaves out the maintenance items, as the command palette does.
+fn menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Menu<R>> {
+    let menu = Menu::with_items(
+        app,
+        &[&MenuItem::with_id(
+            app,
+            MENU_TOGGLE,
+            locale::t(app, "tray.toggle"),
+            true,
+            None::<&str>,
+        )?],
+    )?;
+    if !kiosk::is_enabled(app) {
+        menu.append_items(&[
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(
+                app,
+                MENU_RESTART_BACKEND,
+                locale::t(app, "tray.restart_backend"),
+                true,
+                None::<&str>,
+            )?,
+            &MenuItem::with_id(
+                app,
+                MENU_OPEN_DATA_DIR,
+                locale::t(app, "tray.open_data_dir"),
+                true,
+                None::<&str>,
+            )?,
+        ])?;
+    }
+    menu.append_items(&[
+        &PredefinedMenuItem::separator(app)?,
+        &MenuItem::with_id(
+            app,
+            MENU_QUIT,
+            locale::t(app, "tray.quit"),
+            true,
+            None::<&str>,
+        )?,
+    ])?;
+    Ok(menu)
+}
+
+fn toggle_main_window<R: Runtime>(app: &AppHandle<R>) {
+    let Some(window) = app.get_webview_window(main_window::LABEL) else {
+        return;
+    };
+    let shown = window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false);
+    if shown {
+        let _ = window.hide();
+    } else {
+        main_window::reveal(app);
+    }
+}
+
+fn handle_menu<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+    match event.id().as_ref() {
+        MENU_TOGGLE => toggle_main_window(app),
+        MENU_RESTART_BACKEND => {
+            let app = app.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                if let Err(err) = crate::restart_backend(&app) {
+                    tracing::warn!("[Tray] {err}");
+                }
+            });
+        }
+        MENU_OPEN_DATA_DIR => {
+            let opened = crate::resolve_app_data_dir(app).and_then(|dir| {
+                app.opener()
+                    .open_path(dir.to_string_lossy(), None::<&str>)
+                    .map_err(|err| format!("Failed to open data folder: {err}"))
+            });
+            if let Err(err) = opened {
+                tracing::warn!("[Tray] {err}");
+            }
+        }
+        MENU_QUIT => app.exit(0),
+        _ => {}
+    }
+}
+
+/// Whether closing the main window should only hide it. Never without a tray
+/// icon, which would leave no way back to the window.
+pub fn close_to_tray<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.tray_by_id(TRAY_ID).is_some()
+        && app
+            .try_state::<SettingsStore>()
+            .is_some_and(|store| store.get().tray.close_to_tray)
+}
+
+pub fn init<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let menu = menu(app).map_err(|err| format!("Failed to build the tray menu: {err}"))?;
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(&app.package_info().name)
+        .menu(&menu)
+        // Left click opens the quick reply popup; the menu is on right click.
+        .show_menu_on_left_click(false)
+        .on_menu_event(handle_menu);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    // A template image follows the menu bar rather than the app's theme.
+    #[cfg(target_os = "macos")]
+    {
+        builder = builder.icon_as_template(true);
+    }
+    builder
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                rect,
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                if let Err(err) = toggle_popup(tray.app_handle(), rect) {
+                    tracing::warn!("[Tray] {err}");
+                }
+            }
+        })
+        .build(app)
+        .map_err(|err| format!("Failed to create tray icon: {err}"))?;
+    Ok(())
+}
+
+/// Rebuilds the menu, e.g. for a new locale.
+pub fn refresh_menu<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+    let menu = menu(app).map_err(|err| format!("Failed to build the tray menu: {err}"))?;
+    tray.set_menu(Some(menu))
+        .map_err(|err| format!("Failed to update the tray menu: {err}"))
+}
+
+/// Swaps in a monochrome icon that stands out against `theme`: light on dark, dark
+/// on light. macOS tints its template image itself.
+pub fn follow_theme<R: Runtime>(app: &AppHandle<R>, theme: Theme) {
+    if cfg!(target_os = "macos") {
+        return;
+    }
+    let (Some(tray), Some(icon)) = (app.tray_by_id(TRAY_ID), app.default_window_icon()) else {
+        return;
+    };
+    let tint = match theme {
+        Theme::Dark => 0xf2,
+        _ => 0x1f,
+    };
+    let rgba = icon
+        .rgba()
+        .chunks_exact(4)
+        .flat_map(|pixel| [tint, tint, tint, pixel[3]])
+        .collect::<Vec<u8>>();
+    let variant = Image::new_owned(rgba, icon.width(), icon.height());
+    if let Err(err) = tray.set_icon(Some(variant)) {
+        tracing::warn!("[Tray] Failed to update the icon for the theme: {err}");
+    }
+}
+
+/// Hides the popup once it loses focus, like a menu.
+pub fn popup_blurred<R: Runtime>(window: &WebviewWindow<R>) {
+    let _ = window.hide();
+    if let Some(state) = window.try_state::<QuickReply>() {
+        if let Ok(mut hidden_at) = state.hidden_at.lock() {
+            *hidden_at = Some(Instant::now());
+        }
+    }
+}
+
+fn toggle_popup<R: Runtime>(app: &AppHandle<R>, anchor: Rect) -> Result<(), String> {
+    let just_hidden = app
+        .try_state::<QuickReply>()
+        .and_then(|state| state.hidden_at.lock().ok()?.take())
+        .is_some_and(|at| at.elapsed() < REOPEN_GRACE);
+    let window = match app.get_webview_window(POPUP_LABEL) {
+        Some(window) => {
+            if just_hidden || window.is_visible().unwrap_or(false) {
+                let _ = window.hide();
+                return Ok(());
+            }
+            window
+        }
+        None => {
+            let window = WebviewWindowBuilder::new(
+                app,
+                POPUP_LABEL,
+                WebviewUrl::App("index.html?window=quick-reply".into()),
+            )
+            .title(locale::t(app, "window.quick_reply"))
+            .inner_size(POPUP_WIDTH, POPUP_HEIGHT)
+            .resizable(false)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .visible(false)
+            .build()
+            .map_err(|err| format!("Failed to open quick reply: {err}"))?;
+            if let Err(err) = appearance::apply(&window) {
+                tracing::warn!("[Tray] {err}");
+            }
+            window
+        }
+    };
+    place(&window, anchor)?;
+    events::quick_reply_refresh(app, POPUP_LABEL, &events::Empty {});
+    window
+        .show()
+        .and_then(|_| window.set_focus())
+        .map_err(|err| format!("Failed to show quick reply: {err}"))
+}
+
+/// Centers the popup on the tray icon, below it for menu bars at the top of the
+/// screen and above it for taskbars at the bottom, kept inside the work area.
+fn place<R: Runtime>(window: &WebviewWindow<R>, anchor: Rect) -> Result<(), String> {
+    let scale = window
+        .scale_factor()
+        .map_err(|err| format!("Failed to read display scale: {err}"))?;
+    let icon = anchor.position.to_physical::<f64>(scale);
+    let icon_size = anchor.size.to_physical::<f64>(scale);
+    let size = window
+        .outer_size()
+        .map_err(|err| format!("Failed to read popup size: {err}"))?;
+    let (width, height) = (f64::from(size.width), f64::from(size.height));
+    let margin = POPUP_MARGIN * scale;
+    let center_x = icon.x + icon_size.width / 2.0;
+
+    let mut x = center_x - width / 2.0;
+    let mut y = icon.y + icon_size.height + margin;
+    if let Some(monitor) = window.monitor_from_point(center_x, icon.y).ok().flatten() {
+        let area = monitor.work_area();
+        let (left, top) = (f64::from(area.position.x), f64::from(area.position.y));
+        let (right, bottom) = (
+            left + f64::from(area.size.width),
+            top + f64::from(area.size.height),
+        );
+        if icon.y > (top + bottom) / 2.0 {
+            y = icon.y - height - margin;
+        }
+        x = x.clamp(left + margin, (right - width - margin).max(left + margin));
+        y = y.clamp(top + margin, (bottom - height - margin).max(top + margin));
+    }
+    window
+        .set_position(PhysicalPosition::new(x.round() as i32, y.round() as i32))
+        .map_err(|err| format!("Failed to position quick reply: {err}"))
+}
+
+/// Sends `message` to the conversation of the latest notification (or a new one) and
+/// hides the popup. Resolves once the backend has answered.
+#[tauri::command]
+pub async fn send_quick_reply(app: AppHandle, message: String) -> Result<(), AppError> {
+    let message = message.trim().to_string();
+    if message.is_empty() {
+        return Err(AppError::invalid_input("Type a reply first."));
+    }
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| AppError::unavailable("Backend is not available."))?;
+    let session_id = notifications::latest(&app).and_then(|latest| latest.session_id);
+    proxy::client(&app)
+        .post(format!("{base_url}/chat"))
+        .json(&json!({ "message": message, "session_id": session_id }))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| {
+            AppError::new(ErrorCode::Network, "Failed to send the reply.").with_details(err)
+        })?;
+    if let Some(window) = app.get_webview_window(POPUP_LABEL) {
+        let _ = window.hide();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_tray_settings(store: tauri::State<SettingsStore>) -> TraySettings {
+    store.get().tray
+}
+
+#[tauri::command]
+pub fn set_tray_settings(
+    store: tauri::State<SettingsStore>,
+    tray: TraySettings,
+) -> Result<TraySettings, AppError> {
+    let saved = tray.clone();
+    store.update(|settings| settings.tray = tray)?;
+    Ok(saved)
+}
@@ -0,0 +1,284 @@
+use std::sync::Mutex;
+
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Listener, Manager, Runtime};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::{db_backup, error::AppError, events, settings::SettingsStore, sidecar};
+
+/// Share of installs, in percent, a release in the update manifest is offered to;
+/// read from its `rollout` field and 100 when absent.
+const ROLLOUT_FIELD: &str = "rollout";
+/// Version of the backend bundled in the release, which has to match the release.
+const SIDECAR_VERSION_FIELD: &str = "sidecar_version";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateSettings {
+    /// Check once the backend is up after each launch.
+    pub auto_check: bool,
+    /// Where this install falls in a staged rollout, 0..100; drawn on first check.
+    pub rollout_bucket: Option<u8>,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            auto_check: true,
+            rollout_bucket: None,
+        }
+    }
+}
+
+/// The update found by the last check, waiting for `install_update`.
+#[derive(Default)]
+pub struct PendingUpdate(Mutex<Option<Update>>);
+
+events::payload! {
+    pub struct UpdateInfo {
+        version: String,
+        current_version: String,
+        notes: Option<String>,
+        date: Option<String>,
+        rollout: u8,
+        sidecar_version: Option<String>,
+    }
+}
+
+impl UpdateInfo {
+    fn new(update: &Update) -> Self {
+        Self {
+            version: update.version.clone(),
+            current_version: update.current_version.clone(),
+            notes: update.body.clone(),
+            date: update.date.map(|date| date.to_string()),
+            rollout: rollout(update),
+            sidecar_version: sidecar_version(update),
+        }
+    }
+}
+
+events::payload_enum! {
+    pub enum UpdatePhase {
+        Backup,
+        Download,
+        Install,
+    }
+}
+
+events::payload! {
+    pub struct UpdateProgress {
+        phase: UpdatePhase,
+        downloaded: u64,
+        total: Option<u64>,
+    }
+}
+
+fn emit_progress<R: Runtime>(
+    app: &AppHandle<R>,
+    phase: UpdatePhase,
+    downloaded: u64,
+    total: Option<u64>,
+) {
+    events::update_progress(
+        app,
+        &UpdateProgress {
+            phase,
+            downloaded,
+            total,
+        },
+    );
+}
+
+fn rollout(update: &Update) -> u8 {
+    update
+        .raw_json
+        .get(ROLLOUT_FIELD)
+        .and_then(Value::as_u64)
+        .map_or(100, |percent| percent.min(100) as u8)
+}
+
+fn sidecar_version(update: &Update) -> Option<String> {
+    update
+        .raw_json
+        .get(SIDECAR_VERSION_FIELD)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// This install's rollout bucket, drawn once and kept so it sees a staged release
+/// consistently.
+fn rollout_bucket<R: Runtime>(app: &AppHandle<R>) -> Result<u8, AppError> {
+    let store = app.state::<SettingsStore>();
+    if let Some(bucket) = store.get().updates.rollout_bucket {
+        return Ok(bucket);
+    }
+    let mut bytes = [0u8; 4];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| AppError::from("Failed to draw a rollout bucket.".to_string()))?;
+    let bucket = (u32::from_le_bytes(bytes) % 100) as u8;
+    store.update(|settings| settings.updates.rollout_bucket = Some(bucket))?;
+    Ok(bucket)
+}
+
+fn updater_error(context: &str, err: tauri_plugin_updater::Error) -> AppError {
+    match err {
+        tauri_plugin_updater::Error::EmptyEndpoints => {
+            AppError::unavailable("Updates are not configured for this build.")
+        }
+        err => AppError::unavailable(context).with_details(err),
+    }
+}
+
+/// Release builds pass `plugins.updater` with their signing key and endpoints via
+/// `tauri build --config`; without a key nothing could be verified.
+fn configured<R: Runtime>(app: &AppHandle<R>) -> bool {
+    let Some(config) = app.config().plugins.0.get("updater") else {
+        return false;
+    };
+    let has_pubkey = config
+        .get("pubkey")
+        .and_then(Value::as_str)
+        .is_some_and(|pubkey| !pubkey.trim().is_empty());
+    let has_endpoints = config
+        .get("endpoints")
+        .and_then(Value::as_array)
+        .is_some_and(|endpoints| !endpoints.is_empty());
+    has_pubkey && has_endpoints
+}
+
+async fn check<R: Runtime>(app: &AppHandle<R>) -> Result<Option<UpdateInfo>, AppError> {
+    if !configured(app) {
+        return Err(AppError::unavailable(
+            "Updates are not configured for this build.",
+        ));
+    }
+    let update = app
+        .updater()
+        .map_err(|err| updater_error("The updater is unavailable.", err))?
+        .check()
+        .await
+        .map_err(|err| updater_error("Failed to check for updates.", err))?;
+    let pending = app.state::<PendingUpdate>();
+    let Some(update) = update else {
+        if let Ok(mut pending) = pending.0.lock() {
+            *pending = None;
+        }
+        return Ok(None);
+    };
+    let info = UpdateInfo::new(&update);
+    let bucket = rollout_bucket(app)?;
+    if bucket >= info.rollout {
+        tracing::info!(
+            "[Updater] {} is rolling out to {}% of installs; not this one yet.",
+            info.version,
+            info.rollout
+        );
+        return Ok(None);
+    }
+    pending
+        .0
+        .lock()
+        .map_err(|_| AppError::unavailable("Update state is unavailable."))?
+        .replace(update);
+    tracing::info!("[Updater] {} is available.", info.version);
+    events::update_available(app, &info);
+    Ok(Some(info))
+}
+
+/// Refuses releases whose bundled backend was built from another version; the shell
+/// and sidecar are only ever tested as a pair.
+fn verify_sidecar(update: &Update) -> Result<(), AppError> {
+    match sidecar_version(update) {
+        Some(version) if version == update.version => Ok(()),
+        Some(version) => Err(AppError::invalid_input(format!(
+            "Update {} bundles backend {version}; refusing a mismatched release.",
+            update.version
+        ))),
+        None => Err(AppError::invalid_input(format!(
+            "Update {} does not say which backend it bundles.",
+            update.version
+        ))),
+    }
+}
+
+/// Registers the updater plugin on builds configured for it, then checks for an
+/// update once the backend is up, if the user has not turned that off.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    if !configured(app) {
+        tracing::info!("[Updater] Updates are not configured for this build.");
+        return;
+    }
+    if let Err(err) = app.plugin(tauri_plugin_updater::Builder::new().build()) {
+        tracing::warn!("[Updater] Failed to register the updater: {err}");
+        return;
+    }
+    let auto_check = app
+        .try_state::<SettingsStore>()
+        .is_some_and(|store| store.get().updates.auto_check);
+    if !auto_check {
+        return;
+    }
+    let handle = app.clone();
+    app.once_any("backend://ready", move |_| {
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = check(&handle).await {
+                tracing::warn!("[Updater] {err}");
+            }
+        });
+    });
+}
+
+/// Asks the update endpoints for a newer release. Emits `update://available` and
+/// returns it when one is offered to this install; staged releases only reach the
+/// share of installs their `rollout` allows.
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<Option<UpdateInfo>, AppError> {
+    check(&app).await
+}
+
+/// Installs the update found by `check_for_updates` and restarts into it. The
+/// database is backed up and the backend stopped cleanly first; a failed install
+/// starts the backend again. Progress arrives on `update://progress`.
+#[tauri::command]
+pub async fn install_update(app: AppHandle) -> Result<(), AppError> {
+    let update = app
+        .state::<PendingUpdate>()
+        .0
+        .lock()
+        .map_err(|_| AppError::unavailable("Update state is unavailable."))?
+        .clone()
+        .ok_or_else(|| AppError::not_found("No update is waiting; check for updates first."))?;
+    verify_sidecar(&update)?;
+    let mut downloaded = 0u64;
+    let bytes = update
+        .download(
+            |chunk, total| {
+                downloaded += chunk as u64;
+                emit_progress(&app, UpdatePhase::Download, downloaded, total);
+            },
+            || {},
+        )
+        .await
+        .map_err(|err| updater_error("Failed to download the update.", err))?;
+    emit_progress(&app, UpdatePhase::Backup, 0, None);
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || db_backup::backup_now(&handle))
+        .await
+        .map_err(|err| AppError::from(format!("Backup task failed: {err}")))??;
+    let was_running = sidecar::stop(&app, crate::BACKEND_SIDECAR).unwrap_or(false);
+    emit_progress(&app, UpdatePhase::Install, 0, None);
+    if let Err(err) = update.install(bytes) {
+        if was_running {
+            if let Err(err) = crate::start_backend(&app) {
+                tracing::warn!("[Updater] {err}");
+            }
+        }
+        return Err(updater_error("Failed to install the update.", err));
+    }
+    tracing::info!("[Updater] Installed {}; restarting.", update.version);
+    app.restart()
+}
@@ -0,0 +1,438 @@
+use std::{collections::HashMap, path::PathBuf, sync::Mutex};
+
+use chrono::{DateTime, Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Listener, Manager};
+
+use crate::{
+    costs,
+    error::{AppError, ErrorCode},
+    proxy, BackendState,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
+
+/// Extracts token counts from a provider response as relayed by the backend.
+/// Understands OpenAI (chat + responses), Anthropic and Gemini shapes.
+pub fn parse_usage(response: &Value) -> Option<TokenUsage> {
+    let read = |value: &Value, keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| value.get(*key).and_then(Value::as_u64))
+    };
+    if let Some(usage) = response.get("usage").filter(|usage| usage.is_object()) {
+        let prompt = read(usage, &["prompt_tokens", "input_tokens"]).unwrap_or(0);
+        let completion = read(usage, &["completion_tokens", "output_tokens"]).unwrap_or(0);
+        let total = read(usage, &["total_tokens"]).unwrap_or(prompt + completion);
+        return Some(TokenUsage {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: total,
+        });
+    }
+    if let Some(usage) = response.get("usageMetadata") {
+        let prompt = read(usage, &["promptTokenCount"]).unwrap_or(0);
+        let completion = read(usage, &["candidatesTokenCount"]).unwrap_or(0);
+        let total = read(usage, &["totalTokenCount"]).unwrap_or(prompt + completion);
+        return Some(TokenUsage {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: total,
+        });
+    }
+    // Responses API streams nest the final usage inside the completed response.
+    response.get("response").and_then(parse_usage)
+}
+
+pub struct UsageStore {
+    conn: Mutex<Connection>,
+}
+
+impl UsageStore {
+    pub fn open(path: PathBuf) -> Result<Self, AppError> {
+        let conn = Connection::open(&path)
+            .map_err(|err| AppError::database("Failed to open usage store", err))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS usage_daily (
+                day TEXT NOT NULL,
+                conversation_id TEXT NOT NULL,
+                model TEXT NOT NULL,
+                prompt_tokens INTEGER NOT NULL DEFAULT 0,
+                completion_tokens INTEGER NOT NULL DEFAULT 0,
+                total_tokens INTEGER NOT NULL DEFAULT 0,
+                requests INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (day, conversation_id, model)
+            );
+            CREATE TABLE IF NOT EXISTS usage_cursor (
+                conversation_id TEXT PRIMARY KEY,
+                last_call_id INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS budget_alerts (
+                month TEXT NOT NULL,
+                threshold INTEGER NOT NULL,
+                PRIMARY KEY (month, threshold)
+            );",
+        )
+        .map_err(|err| AppError::database("Failed to initialize usage store", err))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    pub fn record(
+        &self,
+        day: NaiveDate,
+        conversation_id: &str,
+        model: &str,
+        usage: TokenUsage,
+    ) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::unavailable("Usage store is unavailable."))?;
+        conn.execute(
+            "INSERT INTO usage_daily
+                (day, conversation_id, model, prompt_tokens, completion_tokens, total_tokens, requests)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1)
+             ON CONFLICT (day, conversation_id, model) DO UPDATE SET
+                prompt_tokens = prompt_tokens + excluded.prompt_tokens,
+                completion_tokens = completion_tokens + excluded.completion_tokens,
+                total_tokens = total_tokens + excluded.total_tokens,
+                requests = requests + 1",
+            params![
+                day.to_string(),
+                conversation_id,
+                model,
+                usage.prompt_tokens as i64,
+                usage.completion_tokens as i64,
+                usage.total_tokens as i64
+            ],
+        )
+        .map_err(|err| AppError::database("Failed to record usage", err))?;
+        Ok(())
+    }
+
+    fn last_call_id(&self, conversation_id: &str) -> Result<i64, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::unavailable("Usage store is unavailable."))?;
+        conn.query_row(
+            "SELECT last_call_id FROM usage_cursor WHERE conversation_id = ?1",
+            params![conversation_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map(|value| value.unwrap_or(0))
+        .map_err(|err| AppError::database("Failed to read usage cursor", err))
+    }
+
+    fn set_last_call_id(&self, conversation_id: &str, call_id: i64) -> Result<(), AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::unavailable("Usage store is unavailable."))?;
+        conn.execute(
+            "INSERT INTO usage_cursor (conversation_id, last_call_id) VALUES (?1, ?2)
+             ON CONFLICT (conversation_id) DO UPDATE SET last_call_id = excluded.last_call_id",
+            params![conversation_id, call_id],
+        )
+        .map_err(|err| AppError::database("Failed to update usage cursor", err))?;
+        Ok(())
+    }
+
+    /// Prompt/completion totals per model for a `YYYY-MM` month.
+    pub fn month_tokens_by_model(&self, month: &str) -> Result<Vec<(String, u64, u64)>, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::unavailable("Usage store is unavailable."))?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT model, SUM(prompt_tokens), SUM(completion_tokens) FROM usage_daily
+                 WHERE substr(day, 1, 7) = ?1 GROUP BY model ORDER BY model",
+            )
+            .map_err(|err| AppError::database("Failed to query usage", err))?;
+        let rows = stmt
+            .query_map(params![month], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)? as u64,
+                    row.get::<_, i64>(2)? as u64,
+                ))
+            })
+            .map_err(|err| AppError::database("Failed to query usage", err))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|err| AppError::database("Failed to read usage", err))
+    }
+
+    /// Records that a budget alert fired; returns `false` if it already had this month.
+    pub fn mark_budget_alert(&self, month: &str, threshold: u8) -> Result<bool, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::unavailable("Usage store is unavailable."))?;
+        conn.execute(
+            "INSERT OR IGNORE INTO budget_alerts (month, threshold) VALUES (?1, ?2)",
+            params![month, threshold],
+        )
+        .map(|inserted| inserted > 0)
+        .map_err(|err| AppError::database("Failed to record budget alert", err))
+    }
+
+    /// The daily rows in `range`, oldest first.
+    pub fn rows(&self, range: &UsageRange) -> Result<Vec<UsageRow>, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::unavailable("Usage store is unavailable."))?;
+        let (from, to, conversation) = range.bounds();
+        let mut stmt = conn
+            .prepare(
+                "SELECT day, conversation_id, model, prompt_tokens, completion_tokens, total_tokens, requests
+                 FROM usage_daily
+                 WHERE day >= ?1 AND day <= ?2 AND (?3 = '' OR conversation_id = ?3)
+                 ORDER BY day, conversation_id, model",
+            )
+            .map_err(|err| AppError::database("Failed to query usage", err))?;
+        let rows = stmt
+            .query_map(params![from, to, conversation], |row| {
+                Ok(UsageRow {
+                    day: row.get(0)?,
+                    conversation_id: row.get(1)?,
+                    model: row.get(2)?,
+                    prompt_tokens: row.get::<_, i64>(3)? as u64,
+                    completion_tokens: row.get::<_, i64>(4)? as u64,
+                    total_tokens: row.get::<_, i64>(5)? as u64,
+                    requests: row.get::<_, i64>(6)? as u64,
+                })
+            })
+            .map_err(|err| AppError::database("Failed to query usage", err))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|err| AppError::database("Failed to read usage", err))
+    }
+
+    pub fn query(&self, range: &UsageRange) -> Result<UsageReport, AppError> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| AppError::unavailable("Usage store is unavailable."))?;
+        let (from, to, conversation) = range.bounds();
+        let grouped = |select: &str, group: &str| -> Result<Vec<UsageBucket>, AppError> {
+            let sql = format!(
+                "SELECT {select}, SUM(prompt_tokens), SUM(completion_tokens), SUM(total_tokens), SUM(requests)
+                 FROM usage_daily
+                 WHERE day >= ?1 AND day <= ?2 AND (?3 = '' OR conversation_id = ?3)
+                 GROUP BY {group} ORDER BY {group}"
+            );
+            let mut stmt = conn
+                .prepare(&sql)
+                .map_err(|err| AppError::database("Failed to query usage", err))?;
+            let rows = stmt
+                .query_map(params![from, to, conversation], |row| {
+                    Ok(UsageBucket {
+                        key: row.get(0)?,
+                        prompt_tokens: row.get::<_, i64>(1)? as u64,
+                        completion_tokens: row.get::<_, i64>(2)? as u64,
+                        total_tokens: row.get::<_, i64>(3)? as u64,
+                        requests: row.get::<_, i64>(4)? as u64,
+                    })
+                })
+                .map_err(|err| AppError::database("Failed to query usage", err))?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|err| AppError::database("Failed to read usage", err))
+        };
+        let days = grouped("day", "day")?;
+        let conversations = grouped("conversation_id", "conversation_id")?;
+        let models = grouped("model", "model")?;
+        Ok(UsageReport {
+            days,
+            conversations,
+            models,
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UsageRange {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    conversation_id: Option<String>,
+}
+
+impl UsageRange {
+    /// `from`, `to` and the conversation as the queries bind them; empty for any.
+    fn bounds(&self) -> (String, String, String) {
+        (
+            self.from.map(|day| day.to_string()).unwrap_or_default(),
+            self.to
+                .map(|day| day.to_string())
+                .unwrap_or_else(|| "9999-12-31".to_string()),
+            self.conversation_id.clone().unwrap_or_default(),
+        )
+    }
+}
+
+/// One day of one model's usage in one conversation, as stored.
+#[derive(Debug, Clone)]
+pub struct UsageRow {
+    pub day: String,
+    pub conversation_id: String,
+    pub model: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub requests: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageBucket {
+    key: String,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    total_tokens: u64,
+    requests: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    days: Vec<UsageBucket>,
+    conversations: Vec<UsageBucket>,
+    models: Vec<UsageBucket>,
+}
+
+#[tauri::command]
+pub fn get_usage(
+    store: tauri::State<UsageStore>,
+    range: Option<UsageRange>,
+) -> Result<UsageReport, AppError> {
+    store.query(&range.unwrap_or_default())
+}
+
+/// Pulls LLM calls the backend logged for a conversation and meters the ones not seen yet.
+/// Returns the number of newly metered calls.
+#[tauri::command]
+pub async fn sync_conversation_usage(
+    app: tauri::AppHandle,
+    conversation_id: String,
+) -> Result<usize, AppError> {
+    sync(&app, &conversation_id).await
+}
+
+async fn sync(app: &AppHandle, conversation_id: &str) -> Result<usize, AppError> {
+    let base_url = app
+        .try_state::<BackendState>()
+        .map(|state| state.base_url())
+        .ok_or_else(|| AppError::unavailable("Backend is not available."))?;
+    let calls: Vec<Value> = proxy::client(app)
+        .get(format!("{base_url}/sessions/{conversation_id}/llm_calls"))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| {
+            AppError::new(ErrorCode::Network, "Failed to fetch LLM calls.").with_details(err)
+        })?
+        .json()
+        .await
+        .map_err(|err| {
+            AppError::new(ErrorCode::Network, "Failed to parse LLM calls.").with_details(err)
+        })?;
+
+    let store = app.state::<UsageStore>();
+    let mut cursor = store.last_call_id(conversation_id)?;
+    let mut metered = 0;
+    for call in calls {
+        let Some(call_id) = call.get("id").and_then(Value::as_i64) else {
+            continue;
+        };
+        if call_id <= cursor {
+            continue;
+        }
+        cursor = cursor.max(call_id);
+        let Some(usage) = call.get("response_json").and_then(parse_usage) else {
+            continue;
+        };
+        let day = call
+            .get("created_at")
+            .and_then(Value::as_str)
+            .and_then(parse_day)
+            .unwrap_or_else(|| Local::now().date_naive());
+        let model = call
+            .get("model")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown");
+        store.record(day, conversation_id, model, usage)?;
+        metered += 1;
+    }
+    store.set_last_call_id(conversation_id, cursor)?;
+    if metered > 0 {
+        costs::check_budget(app)?;
+    }
+    Ok(metered)
+}
+
+/// The conversation of each run in flight on the chat bridge, keyed by request id.
+#[derive(Default)]
+pub struct Metering(Mutex<HashMap<String, String>>);
+
+#[derive(Debug, Deserialize)]
+struct RunDelta {
+    request_id: String,
+    data: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct RunDone {
+    request_id: String,
+}
+
+/// Meters each run on the `chat://` bridge once it is done, so spend is counted
+/// and budget alerts fire without the frontend syncing conversations itself.
+pub fn watch(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen_any("chat://delta", move |event| {
+        let Ok(delta) = serde_json::from_str::<RunDelta>(event.payload()) else {
+            return;
+        };
+        let Some(session_id) = delta.data.get("session_id").and_then(Value::as_str) else {
+            return;
+        };
+        if let Ok(mut runs) = handle.state::<Metering>().0.lock() {
+            runs.insert(delta.request_id, session_id.to_string());
+        }
+    });
+    let handle = app.clone();
+    app.listen_any("chat://done", move |event| {
+        let Ok(done) = serde_json::from_str::<RunDone>(event.payload()) else {
+            return;
+        };
+        let session_id = handle
+            .state::<Metering>()
+            .0
+            .lock()
+            .ok()
+            .and_then(|mut runs| runs.remove(&done.request_id));
+        let Some(session_id) = session_id else {
+            return;
+        };
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = sync(&handle, &session_id).await {
+                tracing::warn!("[Usage] Failed to meter {session_id}: {err}");
+            }
+        });
+    });
+}
+
+fn parse_day(value: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|stamp| stamp.with_timezone(&Local).date_naive())
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok())
+}
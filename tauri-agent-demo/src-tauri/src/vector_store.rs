@@ -0,0 +1,372 @@
+use std::{
+    path::PathBuf,
+    sync::{Mutex, Once},
+};
+
+use chrono::Local;
+use rusqlite::{ffi::sqlite3_auto_extension, params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::AppError;
+
+const MAX_DIMENSIONS: usize = 8192;
+const MAX_RESULTS: usize = 100;
+
+static REGISTER_VEC: Once = Once::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionInfo {
+    name: String,
+    dimensions: usize,
+    documents: u64,
+    created_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbeddingItem {
+    /// Caller-chosen id, unique within the collection; upserting it again replaces it.
+    pub key: String,
+    pub text: String,
+    #[serde(default)]
+    pub metadata: Option<Value>,
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarDocument {
+    key: String,
+    text: String,
+    metadata: Option<Value>,
+    /// Cosine distance: 0 is identical, 2 is opposite.
+    distance: f64,
+}
+
+pub struct VectorStore {
+    conn: Mutex<Connection>,
+}
+
+fn to_blob(embedding: &[f32]) -> Vec<u8> {
+    embedding
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+/// Each collection keeps its vectors in its own vec0 table, named by id so
+/// user-supplied names never reach the SQL.
+fn vec_table(collection_id: i64) -> String {
+    format!("vec_{collection_id}")
+}
+
+impl VectorStore {
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        REGISTER_VEC.call_once(|| unsafe {
+            #[allow(clippy::missing_transmute_annotations)]
+            sqlite3_auto_extension(Some(std::mem::transmute(
+                sqlite_vec::sqlite3_vec_init as *const (),
+            )));
+        });
+        let conn =
+            Connection::open(&path).map_err(|err| format!("Failed to open vector store: {err}"))?;
+        conn.execute_batch(
+            "PRAGMA foreign_keys = ON;
+            CREATE TABLE IF NOT EXISTS collections (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                dimensions INTEGER NOT NULL,
+                created_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS documents (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                collection_id INTEGER NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+                key TEXT NOT NULL,
+                text TEXT NOT NULL,
+                metadata TEXT,
+                updated_at TEXT NOT NULL,
+                UNIQUE (collection_id, key)
+            );",
+        )
+        .map_err(|err| format!("Failed to initialize vector store: {err}"))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>, String> {
+        self.conn
+            .lock()
+            .map_err(|_| "Vector store lock poisoned".to_string())
+    }
+
+    fn find(conn: &Connection, name: &str) -> Result<(i64, usize), String> {
+        conn.query_row(
+            "SELECT id, dimensions FROM collections WHERE name = ?1",
+            [name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|err| format!("Failed to read collection: {err}"))?
+        .ok_or_else(|| format!("No vector collection named '{name}'."))
+    }
+
+    /// Dimensions of `name`, or `None` when it does not exist.
+    pub fn dimensions(&self, name: &str) -> Result<Option<usize>, String> {
+        let conn = self.lock()?;
+        conn.query_row(
+            "SELECT dimensions FROM collections WHERE name = ?1",
+            [name],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|err| format!("Failed to read collection: {err}"))
+    }
+
+    pub fn list(&self) -> Result<Vec<CollectionInfo>, String> {
+        let conn = self.lock()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT c.name, c.dimensions, c.created_at, COUNT(d.id)
+                 FROM collections c LEFT JOIN documents d ON d.collection_id = c.id
+                 GROUP BY c.id ORDER BY c.name",
+            )
+            .map_err(|err| format!("Failed to list collections: {err}"))?;
+        stmt.query_map([], |row| {
+            Ok(CollectionInfo {
+                name: row.get(0)?,
+                dimensions: row.get(1)?,
+                created_at: row.get(2)?,
+                documents: row.get(3)?,
+            })
+        })
+        .and_then(|rows| rows.collect())
+        .map_err(|err| format!("Failed to list collections: {err}"))
+    }
+
+    /// Creates `name` unless it exists with the same dimensions.
+    pub fn create(&self, name: &str, dimensions: usize) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Collection names cannot be empty.".to_string());
+        }
+        if dimensions == 0 || dimensions > MAX_DIMENSIONS {
+            return Err(format!(
+                "Embeddings must have 1-{MAX_DIMENSIONS} dimensions."
+            ));
+        }
+        let mut conn = self.lock()?;
+        if let Ok((_, existing)) = Self::find(&conn, name) {
+            return if existing == dimensions {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Collection '{name}' already exists with {existing} dimensions."
+                ))
+            };
+        }
+        let tx = conn
+            .transaction()
+            .map_err(|err| format!("Failed to create collection: {err}"))?;
+        tx.execute(
+            "INSERT INTO collections (name, dimensions, created_at) VALUES (?1, ?2, ?3)",
+            params![name, dimensions, Local::now().to_rfc3339()],
+        )
+        .map_err(|err| format!("Failed to create collection: {err}"))?;
+        let table = vec_table(tx.last_insert_rowid());
+        tx.execute_batch(&format!(
+            "CREATE VIRTUAL TABLE {table} USING vec0(
+                embedding float[{dimensions}] distance_metric=cosine
+            );"
+        ))
+        .map_err(|err| format!("Failed to create collection index: {err}"))?;
+        tx.commit()
+            .map_err(|err| format!("Failed to create collection: {err}"))
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        let mut conn = self.lock()?;
+        let (id, _) = Self::find(&conn, name)?;
+        let tx = conn
+            .transaction()
+            .map_err(|err| format!("Failed to delete collection: {err}"))?;
+        tx.execute_batch(&format!("DROP TABLE IF EXISTS {};", vec_table(id)))
+            .map_err(|err| format!("Failed to delete collection index: {err}"))?;
+        tx.execute("DELETE FROM collections WHERE id = ?1", [id])
+            .map_err(|err| format!("Failed to delete collection: {err}"))?;
+        tx.commit()
+            .map_err(|err| format!("Failed to delete collection: {err}"))
+    }
+
+    pub fn upsert(&self, name: &str, items: &[EmbeddingItem]) -> Result<usize, String> {
+        let mut conn = self.lock()?;
+        let (id, dimensions) = Self::find(&conn, name)?;
+        if let Some(item) = items.iter().find(|item| item.embedding.len() != dimensions) {
+            return Err(format!(
+                "Embedding for '{}' has {} dimensions; '{name}' expects {dimensions}.",
+                item.key,
+                item.embedding.len()
+            ));
+        }
+        let table = vec_table(id);
+        let now = Local::now().to_rfc3339();
+        let tx = conn
+            .transaction()
+            .map_err(|err| format!("Failed to store embeddings: {err}"))?;
+        for item in items {
+            let metadata = item.metadata.as_ref().map(Value::to_string);
+            let doc_id: i64 = tx
+                .query_row(
+                    "INSERT INTO documents (collection_id, key, text, metadata, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)
+                     ON CONFLICT (collection_id, key) DO UPDATE SET
+                         text = excluded.text,
+                         metadata = excluded.metadata,
+                         updated_at = excluded.updated_at
+                     RETURNING id",
+                    params![id, item.key, item.text, metadata, now],
+                    |row| row.get(0),
+                )
+                .map_err(|err| format!("Failed to store document: {err}"))?;
+            // vec0 has no upsert, so replace the row outright.
+            tx.execute(&format!("DELETE FROM {table} WHERE rowid = ?1"), [doc_id])
+                .and_then(|_| {
+                    tx.execute(
+                        &format!("INSERT INTO {table} (rowid, embedding) VALUES (?1, ?2)"),
+                        params![doc_id, to_blob(&item.embedding)],
+                    )
+                })
+                .map_err(|err| format!("Failed to store embedding: {err}"))?;
+        }
+        tx.commit()
+            .map_err(|err| format!("Failed to store embeddings: {err}"))?;
+        Ok(items.len())
+    }
+
+    pub fn remove(&self, name: &str, keys: &[String]) -> Result<usize, String> {
+        let mut conn = self.lock()?;
+        let (id, _) = Self::find(&conn, name)?;
+        let table = vec_table(id);
+        let tx = conn
+            .transaction()
+            .map_err(|err| format!("Failed to delete embeddings: {err}"))?;
+        let mut removed = 0;
+        for key in keys {
+            let doc_id: Option<i64> = tx
+                .query_row(
+                    "DELETE FROM documents WHERE collection_id = ?1 AND key = ?2 RETURNING id",
+                    params![id, key],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|err| format!("Failed to delete document: {err}"))?;
+            if let Some(doc_id) = doc_id {
+                tx.execute(&format!("DELETE FROM {table} WHERE rowid = ?1"), [doc_id])
+                    .map_err(|err| format!("Failed to delete embedding: {err}"))?;
+                removed += 1;
+            }
+        }
+        tx.commit()
+            .map_err(|err| format!("Failed to delete embeddings: {err}"))?;
+        Ok(removed)
+    }
+
+    pub fn query(
+        &self,
+        name: &str,
+        embedding: &[f32],
+        limit: usize,
+    ) -> Result<Vec<SimilarDocument>, String> {
+        let conn = self.lock()?;
+        let (id, dimensions) = Self::find(&conn, name)?;
+        if embedding.len() != dimensions {
+            return Err(format!(
+                "Query has {} dimensions; '{name}' expects {dimensions}.",
+                embedding.len()
+            ));
+        }
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT d.key, d.text, d.metadata, v.distance
+                 FROM {} v JOIN documents d ON d.id = v.rowid
+                 WHERE v.embedding MATCH ?1 AND k = ?2
+                 ORDER BY v.distance",
+                vec_table(id)
+            ))
+            .map_err(|err| format!("Failed to query embeddings: {err}"))?;
+        stmt.query_map(
+            params![to_blob(embedding), limit.clamp(1, MAX_RESULTS)],
+            |row| {
+                let metadata: Option<String> = row.get(2)?;
+                Ok(SimilarDocument {
+                    key: row.get(0)?,
+                    text: row.get(1)?,
+                    metadata: metadata.and_then(|raw| serde_json::from_str(&raw).ok()),
+                    distance: row.get(3)?,
+                })
+            },
+        )
+        .and_then(|rows| rows.collect())
+        .map_err(|err| format!("Failed to query embeddings: {err}"))
+    }
+}
+
+#[tauri::command]
+pub fn list_vector_collections(store: State<VectorStore>) -> Result<Vec<CollectionInfo>, AppError> {
+    Ok(store.list()?)
+}
+
+#[tauri::command]
+pub fn create_vector_collection(
+    store: State<VectorStore>,
+    name: String,
+    dimensions: usize,
+) -> Result<(), AppError> {
+    Ok(store.create(&name, dimensions)?)
+}
+
+#[tauri::command]
+pub fn delete_vector_collection(store: State<VectorStore>, name: String) -> Result<(), AppError> {
+    Ok(store.delete(&name)?)
+}
+
+/// Stores precomputed embeddings with their source text; returns how many were written.
+#[tauri::command]
+pub async fn upsert_embeddings(
+    app: AppHandle,
+    collection: String,
+    items: Vec<EmbeddingItem>,
+) -> Result<usize, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        app.state::<VectorStore>().upsert(&collection, &items)
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Embedding task failed: {err}")))?
+    .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn delete_embeddings(
+    store: State<VectorStore>,
+    collection: String,
+    keys: Vec<String>,
+) -> Result<usize, AppError> {
+    Ok(store.remove(&collection, &keys)?)
+}
+
+/// Nearest documents to `embedding` by cosine distance, closest first.
+#[tauri::command]
+pub async fn query_similar(
+    app: AppHandle,
+    collection: String,
+    embedding: Vec<f32>,
+    limit: Option<usize>,
+) -> Result<Vec<SimilarDocument>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        app.state::<VectorStore>()
+            .query(&collection, &embedding, limit.unwrap_or(8))
+    })
+    .await
+    .map_err(|err| AppError::from(format!("Query task failed: {err}")))?
+    .map_err(AppError::from)
+}
@@ -0,0 +1,396 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{
+    http::{request::Parts, Method},
+    AppHandle, Manager, Runtime,
+};
+
+use crate::{error::AppError, events, proxy, rpc, settings::SettingsStore, sidecar, BackendState};
+
+/// Set by the webview to name a run it may want to kill; one is made up otherwise.
+pub const REQUEST_ID_HEADER: &str = "x-agent-request-id";
+/// Endpoints that run a model turn, possibly with tools, before answering.
+const RUN_PATHS: &[&str] = &["/chat", "/chat/stream", "/chat/agent/stream"];
+const STOP_PATH: &str = "/chat/stop";
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+const EXIT_POLL: Duration = Duration::from_millis(250);
+/// The shell gives up on any backend call after 30 minutes regardless.
+const MAX_RUN_MINS: u64 = 30;
+const MAX_GRACE_SECS: u64 = 300;
+
+static NEXT_RUN: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogSettings {
+    /// Wall-clock limit per run; `None` leaves only the transport timeout.
+    pub max_run_mins: Option<u64>,
+    /// How long a stopped run may take to wind down before the sidecar is recycled.
+    pub grace_secs: u64,
+}
+
+impl Default for WatchdogSettings {
+    fn default() -> Self {
+        Self {
+            max_run_mins: Some(20),
+            grace_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveRun {
+    request_id: String,
+    path: String,
+    /// What `/chat/stop` needs to find the run on the backend.
+    session_id: Option<String>,
+    started_at: String,
+    elapsed_secs: u64,
+    /// Set once a stop was sent; the run is left alone until it ends or is recycled.
+    stopping: bool,
+    #[serde(skip)]
+    started: Instant,
+}
+
+events::payload_enum! {
+    pub enum KillOutcome {
+        /// The backend ended the run when asked.
+        Stopped,
+        /// The run ignored the stop, so the sidecar was restarted; every other run
+        /// in flight ended with it.
+        Recycled,
+        /// No such run, e.g. it finished in the meantime.
+        NotFound,
+    }
+}
+
+events::payload! {
+    pub struct RunKilled {
+        request_id: String,
+        reason: String,
+        outcome: KillOutcome,
+    }
+}
+
+/// Runs going through the shell, keyed by request id.
+#[derive(Default)]
+pub struct Watchdog(Mutex<HashMap<String, ActiveRun>>);
+
+/// Keeps a run registered while it is alive; dropping it marks the run finished.
+pub struct RunGuard<R: Runtime> {
+    app: AppHandle<R>,
+    request_id: String,
+}
+
+impl<R: Runtime> Drop for RunGuard<R> {
+    fn drop(&mut self) {
+        if let Some(watchdog) = self.app.try_state::<Watchdog>() {
+            if let Ok(mut runs) = watchdog.0.lock() {
+                runs.remove(&self.request_id);
+            }
+        }
+    }
+}
+
+fn settings<R: Runtime>(app: &AppHandle<R>) -> WatchdogSettings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().watchdog)
+        .unwrap_or_default()
+}
+
+/// The `session_id` of a JSON chat request body, if it has one.
+pub fn session_of(body: &[u8]) -> Option<String> {
+    let body: Value = serde_json::from_slice(body).ok()?;
+    body.get("session_id")?.as_str().map(str::to_string)
+}
+
+/// Registers a call to `path` if it starts an agent run. Hold the guard until
+/// the backend has answered.
+pub fn track<R: Runtime>(
+    app: &AppHandle<R>,
+    path: &str,
+    request_id: Option<String>,
+    session_id: Option<String>,
+) -> Option<RunGuard<R>> {
+    if !RUN_PATHS.contains(&path) {
+        return None;
+    }
+    let watchdog = app.try_state::<Watchdog>()?;
+    let mut runs = watchdog.0.lock().ok()?;
+    let request_id = request_id
+        .filter(|id| !id.is_empty() && !runs.contains_key(id))
+        .unwrap_or_else(|| format!("run-{}", NEXT_RUN.fetch_add(1, Ordering::Relaxed)));
+    runs.insert(
+        request_id.clone(),
+        ActiveRun {
+            request_id: request_id.clone(),
+            path: path.to_string(),
+            session_id,
+            started_at: Local::now().to_rfc3339(),
+            elapsed_secs: 0,
+            stopping: false,
+            started: Instant::now(),
+        },
+    );
+    Some(RunGuard {
+        app: app.clone(),
+        request_id,
+    })
+}
+
+/// [`track`] for a request the webview sends through one of the shell's schemes.
+pub fn track_request<R: Runtime>(
+    app: &AppHandle<R>,
+    parts: &Parts,
+    body: &[u8],
+) -> Option<RunGuard<R>> {
+    if parts.method != Method::POST {
+        return None;
+    }
+    let request_id = parts
+        .headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    track(app, parts.uri.path(), request_id, session_of(body))
+}
+
+fn is_active<R: Runtime>(app: &AppHandle<R>, request_id: &str) -> bool {
+    app.try_state::<Watchdog>()
+        .and_then(|watchdog| Some(watchdog.0.lock().ok()?.contains_key(request_id)))
+        .unwrap_or(false)
+}
+
+pub fn has_active_runs<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.try_state::<Watchdog>()
+        .and_then(|watchdog| Some(!watchdog.0.lock().ok()?.is_empty()))
+        .unwrap_or(false)
+}
+
+/// Marks the run as stopping and returns its session; `None` if it is not running.
+fn begin_stop<R: Runtime>(app: &AppHandle<R>, request_id: &str) -> Option<Option<String>> {
+    let watchdog = app.try_state::<Watchdog>()?;
+    let mut runs = watchdog.0.lock().ok()?;
+    let run = runs.get_mut(request_id)?;
+    run.stopping = true;
+    Some(run.session_id.clone())
+}
+
+async fn request_stop<R: Runtime>(app: &AppHandle<R>, session_id: &str) -> Result<(), String> {
+    let body = json!({ "session_id": session_id });
+    let status = if rpc::is_attached(app) {
+        let handle = app.clone();
+        let (status, _) = tauri::async_runtime::spawn_blocking(move || {
+            rpc::post(&handle, STOP_PATH, &body, STOP_TIMEOUT)
+        })
+        .await
+        .map_err(|err| format!("Stop task failed: {err}"))??;
+        status
+    } else {
+        let base_url = app
+            .try_state::<BackendState>()
+            .map(|state| state.base_url())
+            .ok_or_else(|| "Backend is not configured yet.".to_string())?;
+        proxy::client(app)
+            .post(format!("{base_url}{STOP_PATH}"))
+            .timeout(STOP_TIMEOUT)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|err| format!("Failed to reach the backend: {err}"))?
+            .status()
+            .as_u16()
+    };
+    match status {
+        200..=299 => Ok(()),
+        _ => Err(format!("Backend answered {STOP_PATH} with {status}.")),
+    }
+}
+
+/// Asks the backend to stop the run, waits out the grace period, then restarts
+/// the sidecar if the run is still going.
+async fn kill<R: Runtime>(
+    app: &AppHandle<R>,
+    request_id: &str,
+    reason: &str,
+) -> Result<KillOutcome, String> {
+    let Some(session_id) = begin_stop(app, request_id) else {
+        return Ok(KillOutcome::NotFound);
+    };
+    tracing::warn!("[Watchdog] Stopping run {request_id}: {reason}");
+    match session_id.as_deref() {
+        Some(session_id) => {
+            if let Err(err) = request_stop(app, session_id).await {
+                tracing::warn!("[Watchdog] {err}");
+            }
+        }
+        None => tracing::warn!("[Watchdog] Run {request_id} has no session to stop."),
+    }
+    let deadline = Instant::now() + Duration::from_secs(settings(app).grace_secs);
+    while Instant::now() < deadline {
+        if !is_active(app, request_id) {
+            return Ok(KillOutcome::Stopped);
+        }
+        tokio::time::sleep(EXIT_POLL).await;
+    }
+    if !is_active(app, request_id) {
+        return Ok(KillOutcome::Stopped);
+    }
+    if !sidecar::is_supervised(app, crate::BACKEND_SIDECAR) {
+        return Err(format!(
+            "Run {request_id} did not stop, and the backend is not managed by this app."
+        ));
+    }
+    tracing::warn!("[Watchdog] Run {request_id} did not stop; recycling the backend.");
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || crate::restart_backend(&handle))
+        .await
+        .map_err(|err| format!("Restart task failed: {err}"))??;
+    Ok(KillOutcome::Recycled)
+}
+
+async fn kill_and_report<R: Runtime>(
+    app: &AppHandle<R>,
+    request_id: &str,
+    reason: &str,
+) -> Result<KillOutcome, String> {
+    let outcome = kill(app, request_id, reason).await?;
+    if !matches!(outcome, KillOutcome::NotFound) {
+        events::watchdog_killed(
+            app,
+            &RunKilled {
+                request_id: request_id.to_string(),
+                reason: reason.to_string(),
+                outcome,
+            },
+        );
+    }
+    Ok(outcome)
+}
+
+/// Ids of runs over the time limit that have not been told to stop yet.
+fn overdue<R: Runtime>(app: &AppHandle<R>) -> Vec<String> {
+    let Some(limit) = settings(app).max_run_mins else {
+        return Vec::new();
+    };
+    let limit = Duration::from_secs(limit * 60);
+    app.try_state::<Watchdog>()
+        .and_then(|watchdog| {
+            let runs = watchdog.0.lock().ok()?;
+            Some(
+                runs.values()
+                    .filter(|run| !run.stopping && run.started.elapsed() > limit)
+                    .map(|run| run.request_id.clone())
+                    .collect(),
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Enforces the per-run time limit for as long as the app runs.
+pub fn start<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            for request_id in overdue(&app) {
+                let handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(err) = kill_and_report(&handle, &request_id, "time limit").await {
+                        tracing::error!("[Watchdog] {err}");
+                    }
+                });
+            }
+        }
+    });
+}
+
+/// Stops every run in flight, each in the background like the time limit does.
+pub fn stop_all<R: Runtime>(app: &AppHandle<R>, reason: &'static str) {
+    let running: Vec<String> = app
+        .try_state::<Watchdog>()
+        .and_then(|watchdog| {
+            let runs = watchdog.0.lock().ok()?;
+            Some(
+                runs.values()
+                    .filter(|run| !run.stopping)
+                    .map(|run| run.request_id.clone())
+                    .collect(),
+            )
+        })
+        .unwrap_or_default();
+    for request_id in running {
+        let handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = kill_and_report(&handle, &request_id, reason).await {
+                tracing::error!("[Watchdog] {err}");
+            }
+        });
+    }
+}
+
+/// Runs in flight through the shell, longest-running first.
+#[tauri::command]
+pub fn list_active_runs(watchdog: tauri::State<Watchdog>) -> Result<Vec<ActiveRun>, AppError> {
+    let runs = watchdog
+        .0
+        .lock()
+        .map_err(|_| AppError::unavailable("Run tracking is unavailable."))?;
+    let mut runs: Vec<ActiveRun> = runs
+        .values()
+        .map(|run| ActiveRun {
+            elapsed_secs: run.started.elapsed().as_secs(),
+            ..run.clone()
+        })
+        .collect();
+    runs.sort_by_key(|run| run.started);
+    Ok(runs)
+}
+
+/// Tells the backend to abort the run and, if it is still going after the grace
+/// period, restarts the sidecar.
+#[tauri::command]
+pub async fn kill_active_run(app: AppHandle, request_id: String) -> Result<KillOutcome, AppError> {
+    kill_and_report(&app, &request_id, "stopped by the user")
+        .await
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn get_watchdog_settings(store: tauri::State<SettingsStore>) -> WatchdogSettings {
+    store.get().watchdog
+}
+
+/// Applies to runs already in flight from the next check.
+#[tauri::command]
+pub fn set_watchdog_settings(
+    store: tauri::State<SettingsStore>,
+    settings: WatchdogSettings,
+) -> Result<WatchdogSettings, AppError> {
+    if settings
+        .max_run_mins
+        .is_some_and(|mins| !(1..=MAX_RUN_MINS).contains(&mins))
+    {
+        return Err(AppError::invalid_input(format!(
+            "The run limit must be between 1 and {MAX_RUN_MINS} minutes."
+        )));
+    }
+    if settings.grace_secs > MAX_GRACE_SECS {
+        return Err(AppError::invalid_input(format!(
+            "The grace period must be at most {MAX_GRACE_SECS} seconds."
+        )));
+    }
+    let saved = store.update(|current| current.watchdog = settings)?;
+    Ok(saved.watchdog)
+}
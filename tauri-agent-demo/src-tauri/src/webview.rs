@@ -0,0 +1,416 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, WebviewWindow};
+
+use crate::{error::AppError, settings::SettingsStore};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebviewDataKind {
+    HttpCache,
+    /// localStorage and sessionStorage.
+    LocalStorage,
+    IndexedDb,
+    /// Service worker registrations and their Cache Storage.
+    ServiceWorkers,
+}
+
+impl WebviewDataKind {
+    const ALL: [WebviewDataKind; 4] = [
+        WebviewDataKind::HttpCache,
+        WebviewDataKind::LocalStorage,
+        WebviewDataKind::IndexedDb,
+        WebviewDataKind::ServiceWorkers,
+    ];
+}
+
+/// What an upgrade clears: enough to drop a stale frontend, never the user's data.
+const UPGRADE_KINDS: &[WebviewDataKind] =
+    &[WebviewDataKind::HttpCache, WebviewDataKind::ServiceWorkers];
+
+#[cfg(target_os = "linux")]
+fn clear_platform<R: Runtime>(
+    window: &WebviewWindow<R>,
+    kinds: Vec<WebviewDataKind>,
+    done: impl FnOnce() + Send + 'static,
+) -> Result<(), String> {
+    use webkit2gtk::{WebViewExt, WebsiteDataManagerExtManual, WebsiteDataTypes};
+
+    let mut types = WebsiteDataTypes::empty();
+    for kind in kinds {
+        types |= match kind {
+            WebviewDataKind::HttpCache => {
+                WebsiteDataTypes::DISK_CACHE | WebsiteDataTypes::MEMORY_CACHE
+            }
+            WebviewDataKind::LocalStorage => {
+                WebsiteDataTypes::LOCAL_STORAGE | WebsiteDataTypes::SESSION_STORAGE
+            }
+            WebviewDataKind::IndexedDb => WebsiteDataTypes::INDEXEDDB_DATABASES,
+            WebviewDataKind::ServiceWorkers => {
+                WebsiteDataTypes::SERVICE_WORKER_REGISTRATIONS | WebsiteDataTypes::DOM_CACHE
+            }
+        };
+    }
+    window
+        .with_webview(move |platform| {
+            let Some(manager) = platform.inner().website_data_manager() else {
+                tracing::info!("[Webview] No website data manager; nothing cleared.");
+                return;
+            };
+            // A zero timespan clears data of any age.
+            manager.clear(
+                types,
+                webkit2gtk::glib::TimeSpan(0),
+                None::<&webkit2gtk::gio::Cancellable>,
+                move |result| {
+                    if let Err(err) = result {
+                        tracing::warn!("[Webview] Failed to clear data: {err}");
+                    }
+                    done();
+                },
+            );
+        })
+        .map_err(|err| format!("Failed to access the webview: {err}"))
+}
+
+#[cfg(target_os = "windows")]
+fn clear_platform<R: Runtime>(
+    window: &WebviewWindow<R>,
+    kinds: Vec<WebviewDataKind>,
+    done: impl FnOnce() + Send + 'static,
+) -> Result<(), String> {
+    use webview2_com::{ClearBrowsingDataCompletedHandler, Microsoft::Web::WebView2::Win32::*};
+    use windows_core::Interface;
+
+    let mut data_kinds = COREWEBVIEW2_BROWSING_DATA_KINDS(0);
+    for kind in kinds {
+        data_kinds |= match kind {
+            WebviewDataKind::HttpCache => COREWEBVIEW2_BROWSING_DATA_KINDS_DISK_CACHE,
+            WebviewDataKind::LocalStorage => COREWEBVIEW2_BROWSING_DATA_KINDS_LOCAL_STORAGE,
+            WebviewDataKind::IndexedDb => COREWEBVIEW2_BROWSING_DATA_KINDS_INDEXED_DB,
+            WebviewDataKind::ServiceWorkers => {
+                COREWEBVIEW2_BROWSING_DATA_KINDS_SERVICE_WORKERS
+                    | COREWEBVIEW2_BROWSING_DATA_KINDS_CACHE_STORAGE
+            }
+        };
+    }
+    window
+        .with_webview(move |platform| {
+            let handler = ClearBrowsingDataCompletedHandler::create(Box::new(move |_| {
+                done();
+                Ok(())
+            }));
+            // ICoreWebView2Profile2 needs WebView2 runtime 1.0.1245 or newer.
+            let result = unsafe {
+                platform.controller().CoreWebView2().and_then(|webview| {
+                    webview
+                        .cast::<ICoreWebView2_13>()?
+                        .Profile()?
+                        .cast::<ICoreWebView2Profile2>()?
+                        .ClearBrowsingData(data_kinds, &handler)
+                })
+            };
+            if let Err(err) = result {
+                tracing::warn!("[Webview] Failed to clear data: {err}");
+            }
+        })
+        .map_err(|err| format!("Failed to access the webview: {err}"))
+}
+
+#[cfg(target_os = "macos")]
+fn clear_platform<R: Runtime>(
+    window: &WebviewWindow<R>,
+    kinds: Vec<WebviewDataKind>,
+    done: impl FnOnce() + Send + 'static,
+) -> Result<(), String> {
+    use std::sync::Mutex;
+
+    use block2::RcBlock;
+    use objc2_foundation::{NSDate, NSSet, NSString};
+    use objc2_web_kit::{
+        WKWebView, WKWebsiteDataTypeDiskCache, WKWebsiteDataTypeFetchCache,
+        WKWebsiteDataTypeIndexedDBDatabases, WKWebsiteDataTypeLocalStorage,
+        WKWebsiteDataTypeMemoryCache, WKWebsiteDataTypeServiceWorkerRegistrations,
+        WKWebsiteDataTypeSessionStorage,
+    };
+
+    window
+        .with_webview(move |platform| unsafe {
+            let mut types: Vec<&NSString> = Vec::new();
+            for kind in kinds {
+                match kind {
+                    WebviewDataKind::HttpCache => {
+                        types.extend([WKWebsiteDataTypeDiskCache, WKWebsiteDataTypeMemoryCache])
+                    }
+                    WebviewDataKind::LocalStorage => types.extend([
+                        WKWebsiteDataTypeLocalStorage,
+                        WKWebsiteDataTypeSessionStorage,
+                    ]),
+                    WebviewDataKind::IndexedDb => types.push(WKWebsiteDataTypeIndexedDBDatabases),
+                    WebviewDataKind::ServiceWorkers => types.extend([
+                        WKWebsiteDataTypeServiceWorkerRegistrations,
+                        WKWebsiteDataTypeFetchCache,
+                    ]),
+                }
+            }
+            let webview = &*platform.inner().cast::<WKWebView>();
+            let store = webview.configuration().websiteDataStore();
+            // The handler is a `Fn` block, so hand the one-shot callback over through a slot.
+            let done = Mutex::new(Some(done));
+            let handler = RcBlock::new(move || {
+                if let Some(done) = done.lock().ok().and_then(|mut slot| slot.take()) {
+                    done();
+                }
+            });
+            store.removeDataOfTypes_modifiedSince_completionHandler(
+                &NSSet::from_slice(&types),
+                &NSDate::distantPast(),
+                &handler,
+            );
+        })
+        .map_err(|err| format!("Failed to access the webview: {err}"))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+fn clear_platform<R: Runtime>(
+    _window: &WebviewWindow<R>,
+    _kinds: Vec<WebviewDataKind>,
+    _done: impl FnOnce() + Send + 'static,
+) -> Result<(), String> {
+    Err("Clearing webview data is not supported on this platform.".to_string())
+}
+
+fn clear_and_reload<R: Runtime>(
+    window: &WebviewWindow<R>,
+    kinds: Vec<WebviewDataKind>,
+) -> Result<(), String> {
+    let reloading = window.clone();
+    clear_platform(window, kinds, move || {
+        // Reload once the data is gone so the page does not write it straight back.
+        if let Err(err) = reloading.reload() {
+            tracing::warn!("[Webview] Failed to reload after clearing data: {err}");
+        }
+    })
+}
+
+/// Clears cached frontend assets after an app upgrade when the setting allows it.
+pub fn clear_after_upgrade<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let Some(store) = app.try_state::<SettingsStore>() else {
+        return Ok(());
+    };
+    let version = app.package_info().version.to_string();
+    let settings = store.get().webview;
+    if settings.last_version.as_deref() == Some(version.as_str()) {
+        return Ok(());
+    }
+    // A first launch has nothing stale to clear.
+    let upgraded = settings.last_version.is_some();
+    store.update(|settings| settings.webview.last_version = Some(version.clone()))?;
+    if upgraded && settings.clear_on_upgrade {
+        if let Some(window) = app.get_webview_window("main") {
+            tracing::info!("[Webview] App updated to {version}; clearing cached frontend.");
+            clear_and_reload(&window, UPGRADE_KINDS.to_vec())?;
+        }
+    }
+    Ok(())
+}
+
+/// Clears the main webview's data of the given kinds (all kinds when empty) and
+/// reloads it.
+#[tauri::command]
+pub fn clear_webview_data(app: AppHandle, kinds: Vec<WebviewDataKind>) -> Result<(), AppError> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| AppError::unavailable("Main window is not available."))?;
+    let kinds = if kinds.is_empty() {
+        WebviewDataKind::ALL.to_vec()
+    } else {
+        kinds
+    };
+    Ok(clear_and_reload(&window, kinds)?)
+}
+
+#[tauri::command]
+pub fn set_clear_on_upgrade(
+    store: tauri::State<SettingsStore>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    store.update(|settings| settings.webview.clear_on_upgrade = enabled)?;
+    Ok(())
+}
+
+/// How much control the platform webview gives over spellchecking.
+// Each build only ever reports its own platform's variant.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpellcheckSupport {
+    /// Several languages can be checked at once (WebKitGTK).
+    Multiple,
+    /// One language, or automatic detection (macOS).
+    Single,
+    /// The webview always follows the OS languages (WebView2).
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpellcheckInfo {
+    support: SpellcheckSupport,
+    /// The saved choice; empty means the OS default.
+    languages: Vec<String>,
+    /// Languages the platform reports as installed, when it can list them.
+    available: Vec<String>,
+}
+
+#[cfg(target_os = "linux")]
+const SPELLCHECK_SUPPORT: SpellcheckSupport = SpellcheckSupport::Multiple;
+#[cfg(target_os = "macos")]
+const SPELLCHECK_SUPPORT: SpellcheckSupport = SpellcheckSupport::Single;
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+const SPELLCHECK_SUPPORT: SpellcheckSupport = SpellcheckSupport::Unsupported;
+
+#[cfg(target_os = "linux")]
+fn apply_spellcheck<R: Runtime>(app: &AppHandle<R>, languages: Vec<String>) -> Result<(), String> {
+    use webkit2gtk::{WebContextExt, WebViewExt};
+
+    let Some(window) = app.get_webview_window("main") else {
+        return Ok(());
+    };
+    let languages = if languages.is_empty() {
+        // WebKitGTK has no "follow the OS" value, so derive it from the locale.
+        let locale = std::env::var("LANG").unwrap_or_default();
+        let code = locale.split('.').next().unwrap_or_default();
+        vec![if code.is_empty() || code == "C" {
+            "en_US".to_string()
+        } else {
+            code.to_string()
+        }]
+    } else {
+        languages
+    };
+    window
+        .with_webview(move |platform| {
+            if let Some(context) = platform.inner().context() {
+                let codes: Vec<&str> = languages.iter().map(String::as_str).collect();
+                context.set_spell_checking_enabled(true);
+                context.set_spell_checking_languages(&codes);
+            }
+        })
+        .map_err(|err| format!("Failed to access the webview: {err}"))
+}
+
+#[cfg(target_os = "macos")]
+fn apply_spellcheck<R: Runtime>(app: &AppHandle<R>, languages: Vec<String>) -> Result<(), String> {
+    use objc2::{class, msg_send, rc::Retained, runtime::AnyObject};
+    use objc2_foundation::NSString;
+
+    app.run_on_main_thread(move || unsafe {
+        let checker: Retained<AnyObject> = msg_send![class!(NSSpellChecker), sharedSpellChecker];
+        match languages.first() {
+            Some(language) => {
+                let _: () = msg_send![&*checker, setAutomaticallyIdentifiesLanguages: false];
+                let accepted: bool =
+                    msg_send![&*checker, setLanguage: &*NSString::from_str(language)];
+                if !accepted {
+                    tracing::warn!("[Webview] Spellchecker does not know '{language}'.");
+                }
+            }
+            None => {
+                let _: () = msg_send![&*checker, setAutomaticallyIdentifiesLanguages: true];
+            }
+        }
+    })
+    .map_err(|err| format!("Failed to update the spellchecker: {err}"))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn apply_spellcheck<R: Runtime>(
+    _app: &AppHandle<R>,
+    _languages: Vec<String>,
+) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn available_languages<R: Runtime>(app: &AppHandle<R>) -> Vec<String> {
+    use objc2::{class, msg_send, rc::Retained, runtime::AnyObject};
+    use objc2_foundation::{NSArray, NSString};
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let queued = app.run_on_main_thread(move || unsafe {
+        let checker: Retained<AnyObject> = msg_send![class!(NSSpellChecker), sharedSpellChecker];
+        let languages: Retained<NSArray<NSString>> = msg_send![&*checker, availableLanguages];
+        let _ = sender.send(
+            languages
+                .iter()
+                .map(|language| language.to_string())
+                .collect(),
+        );
+    });
+    if queued.is_err() {
+        return Vec::new();
+    }
+    receiver
+        .recv_timeout(std::time::Duration::from_secs(2))
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn available_languages<R: Runtime>(_app: &AppHandle<R>) -> Vec<String> {
+    Vec::new()
+}
+
+fn spellcheck_info<R: Runtime>(app: &AppHandle<R>, languages: Vec<String>) -> SpellcheckInfo {
+    SpellcheckInfo {
+        support: SPELLCHECK_SUPPORT,
+        languages,
+        available: available_languages(app),
+    }
+}
+
+/// Applies the saved spellchecker languages at startup.
+pub fn restore_spellcheck<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let Some(store) = app.try_state::<SettingsStore>() else {
+        return Ok(());
+    };
+    let languages = store.get().webview.spellcheck_languages;
+    if languages.is_empty() {
+        return Ok(());
+    }
+    apply_spellcheck(app, languages)
+}
+
+// Async so the macOS lookup can wait on the main thread without blocking it.
+#[tauri::command]
+pub async fn get_spellcheck_languages(app: AppHandle) -> SpellcheckInfo {
+    let languages = app
+        .try_state::<SettingsStore>()
+        .map(|store| store.get().webview.spellcheck_languages)
+        .unwrap_or_default();
+    spellcheck_info(&app, languages)
+}
+
+/// Saves and applies spellchecker languages (e.g. `en_US`, `de_DE`); an empty list
+/// returns to the OS default.
+#[tauri::command]
+pub async fn set_spellcheck_languages(
+    app: AppHandle,
+    languages: Vec<String>,
+) -> Result<SpellcheckInfo, AppError> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for language in languages {
+        let language = language.trim().replace('-', "_");
+        if !language.is_empty() && !cleaned.contains(&language) {
+            cleaned.push(language);
+        }
+    }
+    if SPELLCHECK_SUPPORT == SpellcheckSupport::Single && cleaned.len() > 1 {
+        return Err(AppError::invalid_input(
+            "This platform can only spellcheck one language at a time.",
+        ));
+    }
+    app.state::<SettingsStore>().update(|settings| {
+        settings.webview.spellcheck_languages = cleaned.clone();
+    })?;
+    apply_spellcheck(&app, cleaned.clone())?;
+    Ok(spellcheck_info(&app, cleaned))
+}
@@ -0,0 +1,249 @@
+use std::{fs, path::PathBuf};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{
+    attachments::AttachmentStore, config_files, error::AppError, events, settings::SettingsStore,
+    watchdog,
+};
+
+/// The data that predates workspaces, kept directly in `app_data_dir`.
+pub const DEFAULT_ID: &str = "default";
+const DEFAULT_NAME: &str = "Default";
+/// Every other workspace lives in `app_data_dir/workspaces/<id>`.
+const WORKSPACES_DIR: &str = "workspaces";
+const MAX_NAME_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceSettings {
+    /// `None` is the default workspace.
+    pub active: Option<String>,
+    /// Created workspaces; the default one is implied.
+    pub list: Vec<Workspace>,
+}
+
+events::payload! {
+    pub struct WorkspaceInfo {
+        id: String,
+        name: String,
+        created_at: Option<String>,
+        active: bool,
+        data_dir: String,
+    }
+}
+
+fn active_id<R: Runtime>(app: &AppHandle<R>) -> String {
+    app.try_state::<SettingsStore>()
+        .and_then(|store| store.get().workspaces.active)
+        .unwrap_or_else(|| DEFAULT_ID.to_string())
+}
+
+fn dir_for<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<PathBuf, String> {
+    let base = crate::resolve_app_data_dir(app)?;
+    if id == DEFAULT_ID {
+        return Ok(base);
+    }
+    let dir = base.join(WORKSPACES_DIR).join(id);
+    fs::create_dir_all(&dir)
+        .map_err(|err| format!("Failed to create the workspace folder: {err}"))?;
+    Ok(dir)
+}
+
+/// Where the active workspace keeps the backend's database, configs, attachments,
+/// snapshots and trash. Shell preferences and logs stay in `app_data_dir`.
+pub fn data_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    dir_for(app, &active_id(app))
+}
+
+/// The active workspace's chat database. `TAURI_AGENT_DB_PATH` and the dev checkout's
+/// database only stand in for the default workspace's.
+pub fn db_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let id = active_id(app);
+    let dir = dir_for(app, &id)?;
+    if id == DEFAULT_ID {
+        Ok(crate::resolve_db_path(&dir))
+    } else {
+        Ok(dir.join("chat_app.db"))
+    }
+}
+
+fn created<R: Runtime>(app: &AppHandle<R>) -> Vec<Workspace> {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get().workspaces.list)
+        .unwrap_or_default()
+}
+
+/// Every workspace's data folder that exists, the default one's included.
+pub fn data_dirs<R: Runtime>(app: &AppHandle<R>) -> Vec<PathBuf> {
+    let Ok(base) = crate::resolve_app_data_dir(app) else {
+        return Vec::new();
+    };
+    let created = created(app)
+        .into_iter()
+        .map(|workspace| base.join(WORKSPACES_DIR).join(workspace.id));
+    std::iter::once(base.clone())
+        .chain(created)
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// Every workspace's chat database that exists, the default one's included.
+pub fn db_paths<R: Runtime>(app: &AppHandle<R>) -> Vec<PathBuf> {
+    let Ok(base) = crate::resolve_app_data_dir(app) else {
+        return Vec::new();
+    };
+    let created = created(app).into_iter().map(|workspace| {
+        base.join(WORKSPACES_DIR)
+            .join(workspace.id)
+            .join("chat_app.db")
+    });
+    std::iter::once(crate::resolve_db_path(&base))
+        .chain(created)
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+fn info<R: Runtime>(
+    app: &AppHandle<R>,
+    workspace: Option<&Workspace>,
+    active: &str,
+) -> WorkspaceInfo {
+    let id = workspace.map_or(DEFAULT_ID, |workspace| workspace.id.as_str());
+    let data_dir = dir_for(app, id)
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_default();
+    WorkspaceInfo {
+        id: id.to_string(),
+        name: workspace.map_or(DEFAULT_NAME.to_string(), |workspace| workspace.name.clone()),
+        created_at: workspace.map(|workspace| workspace.created_at.clone()),
+        active: id == active,
+        data_dir,
+    }
+}
+
+fn list<R: Runtime>(app: &AppHandle<R>) -> Vec<WorkspaceInfo> {
+    let settings = app
+        .try_state::<SettingsStore>()
+        .map(|store| store.get().workspaces)
+        .unwrap_or_default();
+    let active = settings.active.as_deref().unwrap_or(DEFAULT_ID);
+    std::iter::once(info(app, None, active))
+        .chain(
+            settings
+                .list
+                .iter()
+                .map(|workspace| info(app, Some(workspace), active)),
+        )
+        .collect()
+}
+
+/// Points the shell's own readers of workspace data at the active workspace.
+fn repoint<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let dir = data_dir(app)?;
+    app.state::<AttachmentStore>()
+        .set_root(dir.join("attachments"));
+    config_files::watch(app, dir)
+}
+
+fn switch<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<WorkspaceInfo, AppError> {
+    let store = app.state::<SettingsStore>();
+    let settings = store.get().workspaces;
+    if id != DEFAULT_ID && !settings.list.iter().any(|workspace| workspace.id == id) {
+        return Err(AppError::not_found(format!("No workspace with id {id}.")));
+    }
+    let active = if id == DEFAULT_ID {
+        None
+    } else {
+        Some(id.to_string())
+    };
+    if settings.active == active {
+        return list(app)
+            .into_iter()
+            .find(|workspace| workspace.active)
+            .ok_or_else(|| AppError::from("The active workspace is missing.".to_string()));
+    }
+    // Remote and external backends keep their own data.
+    if !crate::runs_sidecar(app) {
+        return Err(AppError::unavailable(
+            "Workspaces only apply to the backend this app runs.",
+        ));
+    }
+    if watchdog::has_active_runs(app) {
+        return Err(AppError::invalid_input(
+            "An agent run is still going; stop it before switching workspaces.",
+        ));
+    }
+    // The spec is rebuilt on the way back up, so the sidecar gets the new paths.
+    crate::with_backend_stopped(app, || -> Result<(), AppError> {
+        store.update(|settings| settings.workspaces.active = active.clone())?;
+        repoint(app).map_err(AppError::from)
+    })?;
+    let switched = list(app)
+        .into_iter()
+        .find(|workspace| workspace.active)
+        .ok_or_else(|| AppError::from("The active workspace is missing.".to_string()))?;
+    tracing::info!(
+        "[Workspace] Switched to {} ({}).",
+        switched.name,
+        switched.id
+    );
+    events::workspace_switched(app, &switched);
+    Ok(switched)
+}
+
+/// The default workspace first, then the others in the order they were made.
+#[tauri::command]
+pub fn list_workspaces(app: AppHandle) -> Vec<WorkspaceInfo> {
+    list(&app)
+}
+
+/// Adds an empty workspace with its own data folder; it is not switched to.
+#[tauri::command]
+pub fn create_workspace(
+    app: AppHandle,
+    store: tauri::State<SettingsStore>,
+    name: String,
+) -> Result<WorkspaceInfo, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Err(AppError::invalid_input(format!(
+            "A workspace name must be 1 to {MAX_NAME_CHARS} characters."
+        )));
+    }
+    let now = Local::now();
+    let taken = store.get().workspaces.list;
+    let mut id = format!("ws-{}", now.format("%Y%m%d-%H%M%S"));
+    let mut suffix = 1;
+    while taken.iter().any(|workspace| workspace.id == id) {
+        suffix += 1;
+        id = format!("ws-{}-{suffix}", now.format("%Y%m%d-%H%M%S"));
+    }
+    let workspace = Workspace {
+        id,
+        name,
+        created_at: now.to_rfc3339(),
+    };
+    dir_for(&app, &workspace.id).map_err(AppError::from)?;
+    let settings = store.update(|settings| settings.workspaces.list.push(workspace.clone()))?;
+    let active = settings.workspaces.active.as_deref().unwrap_or(DEFAULT_ID);
+    Ok(info(&app, Some(&workspace), active))
+}
+
+/// Restarts the sidecar on `id`'s data and emits `workspace://switched`; the
+/// webview should reload, since every conversation it holds belongs to the old one.
+#[tauri::command]
+pub async fn switch_workspace(app: AppHandle, id: String) -> Result<WorkspaceInfo, AppError> {
+    tauri::async_runtime::spawn_blocking(move || switch(&app, &id))
+        .await
+        .map_err(|err| AppError::from(format!("Switch task failed: {err}")))?
+}
@@ -1,7 +1,7 @@
 import { useState, useEffect, useLayoutEffect, useRef, useMemo, useCallback } from 'react';
 import { createPortal } from 'react-dom';
 import { open as openDialog } from '@tauri-apps/plugin-dialog';
-import { watchImmediate, type UnwatchFn } from '@tauri-apps/plugin-fs';
+import { readFile, watchImmediate, type UnwatchFn } from '@tauri-apps/plugin-fs';
 import { openPath, revealItemInDir } from '@tauri-apps/plugin-opener';
 import { getCurrentWindow, LogicalSize } from '@tauri-apps/api/window';
 import { WebviewWindow } from '@tauri-apps/api/webviewWindow';
@@ -55,6 +55,26 @@ import {
   getAstSettings,
   updateAstSettings,
   notifyAstChanges,
+  getWindowEffect,
+  type WindowEffect,
+  setActiveRun,
+  type TaskControl,
+  stageAttachment,
+  getUploadUrl,
+  type AttachmentAdded,
+  type AttachmentRejected,
+  getLockStatus,
+  type LockReason,
+  type LockStatus,
+  dialogDefaultPath,
+  rememberDialogDir,
+  queueChatRequest,
+  type OutboxStatusEvent,
+  restartBackend,
+  type BackendCrash,
+  takePendingDeepLinks,
+  type DeepLink,
+  onShellEvent,
 } from './api';
 import ConfigManager from './components/ConfigManager';
 import SessionList from './components/SessionList';
@@ -63,6 +83,7 @@ import PtyPanel from './components/PtyPanel';
 import AgentStepView from './components/AgentStepView';
 import ConfirmDialog from './components/ConfirmDialog';
 import TaskWorkbench from './components/TaskWorkbench';
+import LockScreen from './components/LockScreen';
 import type { PtyInteractionController, ResolveStepPtyBinding } from './components/ptyInteraction';
 import { loadExtraWorkPaths, migrateExtraWorkPaths, saveExtraWorkPaths } from './workdirStorage';
 import { ptyStore } from './ptyStore';
@@ -77,7 +98,6 @@ import {
   DRAFT_SESSION_KEY,
   IS_MAC,
   LEGACY_USE_TASK_CENTER_KEY,
-  MAIN_WINDOW_BOUNDS_KEY,
   MAX_CONCURRENT_STREAMS,
   MESSAGE_ESTIMATED_HEIGHT,
   MESSAGE_LOAD_THRESHOLD_PX,
@@ -101,7 +121,6 @@ import {
   formatTokenCount,
   formatWorkPath,
   getLatestRequestPayload,
-  getMainWindowBounds,
   getParentPath,
   getWorkdirWindowBounds,
   isAbsolutePath,
@@ -124,6 +143,13 @@ import type {
 import { useSkillCommands } from './hooks/useSkillCommands';
 import { useSessionWebSocket } from './hooks/useSessionWebSocket';
 
+// Set in a conversation detached into its own window; that window keeps to the one
+// conversation and leaves main-window state such as bounds and deep links alone.
+const DETACHED_SESSION_ID = (() => {
+  const params = new URLSearchParams(window.location.search);
+  return params.get('window') === 'conversation' ? params.get('session') : null;
+})();
+
 function App() {
   const [inputMsg, setInputMsg] = useState('');
   const [skills, setSkills] = useState<SkillSummary[]>([]);
@@ -138,7 +164,10 @@ function App() {
   const [currentWorkPath, setCurrentWorkPath] = useState('');
   const [showConfigManager, setShowConfigManager] = useState(false);
   const [sessionRefreshTrigger, setSessionRefreshTrigger] = useState(0);
+  const [backendCrash, setBackendCrash] = useState<BackendCrash | null>(null);
+  const [backendRestarting, setBackendRestarting] = useState(false);
   const [showSidebar, setShowSidebar] = useState(() => {
+    if (DETACHED_SESSION_ID) return false;
     try {
       const raw = localStorage.getItem(SIDEBAR_OPEN_KEY);
       if (raw !== null) {
@@ -195,6 +224,14 @@ function App() {
   const [permissionTick, setPermissionTick] = useState(0);
   const [unreadBySession, setUnreadBySession] = useState<Record<string, boolean>>({});
   const [patchRevertBusy, setPatchRevertBusy] = useState(false);
+  const [appLocked, setAppLocked] = useState(false);
+  const [lockReason, setLockReason] = useState<LockReason>('manual');
+  const [lockStatus, setLockStatus] = useState<LockStatus | null>(null);
+  const [notificationTarget, setNotificationTarget] = useState<{
+    session_id: string;
+    action: 'reply' | 'view_result';
+  } | null>(null);
+  const [deepLinks, setDeepLinks] = useState<DeepLink[]>([]);
   const [rollbackTarget, setRollbackTarget] = useState<{ messageId: number; keepInput?: boolean } | null>(null);
   const [workPathMenu, setWorkPathMenu] = useState<{ x: number; y: number } | null>(null);
   const [workPathMenuPlacement, setWorkPathMenuPlacement] = useState<{ x: number; y: number } | null>(null);
@@ -266,6 +303,7 @@ function App() {
   const ptyResyncInFlightRef = useRef<Record<string, boolean>>({});
 
   useEffect(() => {
+    if (DETACHED_SESSION_ID) return;
     try {
       localStorage.setItem(SIDEBAR_OPEN_KEY, showSidebar ? '1' : '0');
     } catch {
@@ -384,10 +422,26 @@ function App() {
   }, [appWindow]);
 
   useEffect(() => {
-    const bounds = getMainWindowBounds();
-    if (!bounds?.width || !bounds?.height) return;
-    appWindow.setSize(new LogicalSize(bounds.width, bounds.height)).catch(() => undefined);
-  }, [appWindow]);
+    const applyEffect = (effect: WindowEffect) => {
+      if (effect === 'none') {
+        delete document.body.dataset.windowEffect;
+      } else {
+        document.body.dataset.windowEffect = effect;
+      }
+    };
+    let unlisten: (() => void) | null = null;
+    getWindowEffect()
+      .then((info) => applyEffect(info.effect))
+      .catch(() => undefined);
+    onShellEvent('appearance://window-effect', (payload) => applyEffect(payload.effect))
+      .then((stop) => {
+        unlisten = stop;
+      })
+      .catch(() => undefined);
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, []);
 
   useEffect(() => {
     currentSessionIdRef.current = currentSessionId;
@@ -426,6 +480,12 @@ function App() {
       }
       const match = /^data:(.*?);base64,/i.exec(dataUrl);
       const mime = file.type || (match ? match[1] : 'application/octet-stream');
+      const staged = await stageAttachment(file.name || 'image', mime, base64).catch(() => null);
+      if (staged?.quarantine) {
+        URL.revokeObjectURL(previewUrl);
+        alert(`${file.name || 'image'} 已被隔离：${staged.quarantine}`);
+        return null;
+      }
       const dims = await getImageDimensions(previewUrl);
       const width = dims.width || undefined;
       const height = dims.height || undefined;
@@ -455,6 +515,40 @@ function App() {
     }
   };
 
+  useEffect(() => {
+    // Files dropped onto the window are stored and uploaded by the shell.
+    const stops: Array<() => void> = [];
+    onShellEvent('attachment://added', (payload) => {
+      const { attachment, upload_id: uploadId } = payload;
+      const isImage = attachment.mime.startsWith('image/');
+      setPendingAttachments((prev) => {
+        if (prev.some((item) => item.uploadId === uploadId)) return prev;
+        return [
+          ...prev,
+          {
+            id: `${Date.now()}-${Math.random().toString(16).slice(2)}`,
+            name: attachment.name,
+            mime: attachment.mime,
+            size: attachment.size,
+            previewUrl: isImage ? getUploadUrl(uploadId) : '',
+            uploadId,
+          },
+        ];
+      });
+    })
+      .then((stop) => stops.push(stop))
+      .catch(() => undefined);
+    onShellEvent('attachment://rejected', (payload) => {
+      const name = payload.source.split(/[\\/]/).pop() || payload.source;
+      alert(`${name} 未添加：${payload.reason}`);
+    })
+      .then((stop) => stops.push(stop))
+      .catch(() => undefined);
+    return () => {
+      stops.forEach((stop) => stop());
+    };
+  }, []);
+
   const removePendingAttachment = (attachmentId: string) => {
     setPendingAttachments((prev) => {
       const target = prev.find((item) => item.id === attachmentId);
@@ -481,6 +575,7 @@ function App() {
       name: item.name,
       mime: item.mime,
       data_base64: item.dataBase64,
+      upload_id: item.uploadId,
       width: item.width,
       height: item.height,
       size: item.size,
@@ -523,44 +618,6 @@ function App() {
     };
   }, [appWindow]);
 
-  useEffect(() => {
-    let timer: number | null = null;
-    let unlisten: (() => void) | null = null;
-
-    const saveBounds = async () => {
-      try {
-        const isMax = await appWindow.isMaximized();
-        if (isMax) return;
-        const size = await appWindow.outerSize();
-        const payload = {
-          width: Math.max(800, Math.round(size.width)),
-          height: Math.max(600, Math.round(size.height)),
-        };
-        localStorage.setItem(MAIN_WINDOW_BOUNDS_KEY, JSON.stringify(payload));
-      } catch {
-        // ignore
-      }
-    };
-
-    const scheduleSave = () => {
-      if (timer) window.clearTimeout(timer);
-      timer = window.setTimeout(() => {
-        void saveBounds();
-      }, 300);
-    };
-
-    appWindow.onResized(scheduleSave).then((stop) => {
-      unlisten = stop;
-    });
-    window.addEventListener('beforeunload', scheduleSave);
-
-    return () => {
-      if (timer) window.clearTimeout(timer);
-      if (unlisten) unlisten();
-      window.removeEventListener('beforeunload', scheduleSave);
-    };
-  }, [appWindow]);
-
   const handleTitlebarMinimize = async () => {
     try {
       await appWindow.minimize();
@@ -1323,9 +1380,11 @@ function App() {
       const selected = await openDialog({
         directory: true,
         multiple: false,
-        title: '\u9009\u62e9\u5de5\u4f5c\u8def\u5f84'
+        title: '\u9009\u62e9\u5de5\u4f5c\u8def\u5f84',
+        defaultPath: await dialogDefaultPath('workspace')
       });
       if (!selected) return '';
+      void rememberDialogDir('workspace', selected);
       return Array.isArray(selected) ? (selected[0] || '') : selected;
     } catch (error) {
       console.error('Failed to pick work path:', error);
@@ -1746,24 +1805,24 @@ function App() {
     clearPendingContextForSession(activeSessionKey);
     updateSessionMessages(activeSessionKey, (prev) => [...prev, tempUserMsg, tempAssistantMsg]);
 
+    const chatRequest = {
+      message: userMessage,
+      session_id: targetSessionId || undefined,
+      config_id: item.configId,
+      agent_mode: item.agentMode,
+      agent_profile: item.agentProfileId || undefined,
+      work_path: item.workPath || undefined,
+      extra_work_paths: item.extraWorkPaths && item.extraWorkPaths.length > 0 ? item.extraWorkPaths : undefined,
+      use_task_center: item.useTaskCenter,
+      attachments: pendingItems.length ? mapPendingToPayload(pendingItems) : undefined,
+    };
+    let receivedChunk = false;
+
     try {
-      const attachmentPayload = pendingItems.length ? mapPendingToPayload(pendingItems) : undefined;
-      const streamGenerator = sendMessageAgentStream(
-        {
-          message: userMessage,
-          session_id: targetSessionId || undefined,
-          config_id: item.configId,
-          agent_mode: item.agentMode,
-          agent_profile: item.agentProfileId || undefined,
-          work_path: item.workPath || undefined,
-          extra_work_paths: item.extraWorkPaths && item.extraWorkPaths.length > 0 ? item.extraWorkPaths : undefined,
-          use_task_center: item.useTaskCenter,
-          attachments: attachmentPayload,
-        },
-        abortController.signal
-      );
+      const streamGenerator = sendMessageAgentStream(chatRequest, abortController.signal);
 
       for await (const chunk of streamGenerator) {
+        receivedChunk = true;
         const inflightState = inFlightBySessionRef.current[activeSessionKey];
         if (inflightState) {
           inflightState.lastEventAt = Date.now();
@@ -2148,8 +2207,21 @@ function App() {
     } catch (error: any) {
       const stopped = inFlightBySessionRef.current[activeSessionKey]?.stopRequested;
       const aborted = abortController.signal.aborted || error?.name === 'AbortError';
+      // fetch rejects with a TypeError when the backend cannot be reached at all.
+      const queued = !aborted && !stopped && !receivedChunk && error instanceof TypeError
+        ? await queueChatRequest(chatRequest).catch(() => null)
+        : null;
       if (aborted || stopped) {
         // User stopped streaming or aborted
+      } else if (queued) {
+        const queuedMsg: Message = {
+          id: Date.now() + 2,
+          session_id: targetSessionId || '',
+          role: 'assistant',
+          content: '\u540e\u7aef\u6682\u65f6\u65e0\u6cd5\u8fde\u63a5\uff0c\u6d88\u606f\u5df2\u52a0\u5165\u79bb\u7ebf\u961f\u5217\uff0c\u6062\u590d\u540e\u4f1a\u81ea\u52a8\u53d1\u9001\u3002',
+          timestamp: new Date().toISOString(),
+        };
+        updateSessionMessages(activeSessionKey, (prev) => [...prev.filter((m) => m.id !== currentAssistantId), queuedMsg]);
       } else {
         const inflight = inFlightBySessionRef.current[activeSessionKey];
         const lastEventAt = inflight?.lastEventAt;
@@ -2719,6 +2791,29 @@ function App() {
     }
   };
 
+  const handleAttachImages = async () => {
+    try {
+      const selected = await openDialog({
+        multiple: true,
+        title: '\u6dfb\u52a0\u56fe\u7247',
+        defaultPath: await dialogDefaultPath('attachments'),
+        filters: [{ name: 'Images', extensions: ['png', 'jpg', 'jpeg', 'gif', 'bmp', 'webp', 'svg', 'heic', 'avif'] }]
+      });
+      if (!selected) return;
+      const paths = Array.isArray(selected) ? selected : [selected];
+      void rememberDialogDir('attachments', paths);
+      const files = await Promise.all(paths.map(async (path) => {
+        const name = path.split(/[\\/]/).pop() || 'image';
+        const ext = name.split('.').pop()?.toLowerCase() || '';
+        const type = ext === 'svg' ? 'image/svg+xml' : ext === 'jpg' ? 'image/jpeg' : `image/${ext}`;
+        return new File([await readFile(path)], name, { type });
+      }));
+      await addPendingAttachments(files);
+    } catch (error) {
+      console.error('Failed to attach images:', error);
+    }
+  };
+
   const handleAttachmentPreview = (attachment: MessageAttachment) => {
     const src = getAttachmentFullSrc(attachment);
     if (!src) return;
@@ -3203,6 +3298,164 @@ function App() {
     () => Boolean(inFlightBySessionRef.current[currentSessionKey]),
     [currentSessionKey, inFlightTick]
   );
+
+  useEffect(() => {
+    setActiveRun(isStreamingCurrent ? currentSessionId : null).catch(() => undefined);
+  }, [isStreamingCurrent, currentSessionId]);
+
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    // The shell already told the backend; settle the stream like handleStop does.
+    onShellEvent('task-control://applied', (payload) => {
+      const sessionKey = getSessionKey(payload.session_id);
+      const inflight = inFlightBySessionRef.current[sessionKey];
+      if (!inflight) return;
+      inflight.stopRequested = true;
+      inflight.abortController.abort();
+      const assistantId = inflight.activeAssistantId ?? inflight.tempAssistantId;
+      updateSessionMessages(sessionKey, (prev) =>
+        prev.map((msg) => (msg.id === assistantId ? applyStopNoteToMessage(msg) : msg))
+      );
+    })
+      .then((stop) => {
+        unlisten = stop;
+      })
+      .catch(() => undefined);
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, []);
+  useEffect(() => {
+    const stops: Array<() => void> = [];
+    let disposed = false;
+    const subscribe = async () => {
+      const onCrash = await onShellEvent('backend://crashed', (payload) => setBackendCrash(payload));
+      const onRestart = await onShellEvent('backend://restarted', () => setBackendCrash(null));
+      stops.push(onCrash, onRestart);
+      if (disposed) stops.forEach((stop) => stop());
+    };
+    subscribe().catch(() => undefined);
+    return () => {
+      disposed = true;
+      stops.forEach((stop) => stop());
+    };
+  }, []);
+  const handleRestartBackend = async () => {
+    setBackendRestarting(true);
+    try {
+      await restartBackend();
+      setBackendCrash(null);
+    } catch (error: any) {
+      alert(`\u91cd\u542f\u540e\u7aef\u5931\u8d25: ${error?.message || String(error)}`);
+    } finally {
+      setBackendRestarting(false);
+    }
+  };
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    // The shell already brought the window forward; open the conversation it named.
+    onShellEvent('notification://navigate', (payload) => {
+      setNotificationTarget(payload);
+    })
+      .then((stop) => {
+        unlisten = stop;
+      })
+      .catch(() => undefined);
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, []);
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    // A conversation started from the quick ask window, to continue here.
+    onShellEvent('quick-ask://open', (payload) => {
+      setNotificationTarget({ session_id: payload.session_id, action: 'reply' });
+    })
+      .then((stop) => {
+        unlisten = stop;
+      })
+      .catch(() => undefined);
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, []);
+  useEffect(() => {
+    if (!notificationTarget) return;
+    setNotificationTarget(null);
+    const focusComposer = () => {
+      if (notificationTarget.action === 'reply') {
+        window.requestAnimationFrame(() => inputRef.current?.focus());
+      }
+    };
+    if (notificationTarget.session_id === currentSessionId) {
+      scheduleScrollToBottom('auto');
+      focusComposer();
+      return;
+    }
+    handleSelectSession(notificationTarget.session_id).then(focusComposer).catch(() => undefined);
+  }, [notificationTarget]);
+  useEffect(() => {
+    if (DETACHED_SESSION_ID) {
+      handleSelectSession(DETACHED_SESSION_ID).catch(() => undefined);
+      return;
+    }
+    let unlisten: (() => void) | null = null;
+    // Listen first so nothing falls between the pending links and the live ones.
+    onShellEvent('deeplink://open', (payload) => {
+      setDeepLinks((current) => [...current, payload]);
+    })
+      .then((stop) => {
+        unlisten = stop;
+        return takePendingDeepLinks();
+      })
+      .then((pending) => {
+        if (pending.length > 0) setDeepLinks((current) => [...current, ...pending]);
+      })
+      .catch(() => undefined);
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, []);
+  useEffect(() => {
+    if (deepLinks.length === 0) return;
+    const [link, ...rest] = deepLinks;
+    setDeepLinks(rest);
+    if (link.action === 'open_conversation') {
+      handleSelectSession(link.session_id).catch(() => undefined);
+      return;
+    }
+    handleNewChat()
+      .then(() => {
+        if (link.prompt) setInputMsg(link.prompt);
+        window.requestAnimationFrame(() => inputRef.current?.focus());
+      })
+      .catch(() => undefined);
+  }, [deepLinks]);
+  useEffect(() => {
+    getLockStatus()
+      .then((status) => {
+        setLockStatus(status);
+        setAppLocked(status.locked);
+      })
+      .catch(() => undefined);
+    const unlisteners: Array<() => void> = [];
+    onShellEvent('app-lock://locked', (payload) => {
+      setLockReason(payload.reason);
+      setAppLocked(true);
+      // Which ways back in are offered can change with the settings.
+      getLockStatus()
+        .then(setLockStatus)
+        .catch(() => undefined);
+    })
+      .then((stop) => unlisteners.push(stop))
+      .catch(() => undefined);
+    onShellEvent('app-lock://unlocked', () => setAppLocked(false))
+      .then((stop) => unlisteners.push(stop))
+      .catch(() => undefined);
+    return () => {
+      unlisteners.forEach((stop) => stop());
+    };
+  }, []);
   const displayMessages = useMemo(
     () =>
       messages.filter((msg) => {
@@ -3323,6 +3576,27 @@ function App() {
     [hydrateMessagesWithSteps]
   );
 
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    // Requests queued while the backend was down finish in the background.
+    onShellEvent('outbox://status', (payload) => {
+      const { status, session_id: sessionId, error } = payload;
+      if (status === 'sent') {
+        setSessionRefreshTrigger((prev) => prev + 1);
+        if (sessionId) refreshSessionMessages(sessionId).catch(() => undefined);
+      } else if (status === 'failed') {
+        alert(`\u79bb\u7ebf\u6d88\u606f\u53d1\u9001\u5931\u8d25: ${error || 'Unknown error'}`);
+      }
+    })
+      .then((stop) => {
+        unlisten = stop;
+      })
+      .catch(() => undefined);
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, [refreshSessionMessages]);
+
   useEffect(() => {
     const timer = window.setInterval(() => {
       const now = Date.now();
@@ -3665,6 +3939,20 @@ function App() {
       )}
 
       <div className="main-content">
+        {backendCrash && (
+          <div className="backend-crash-banner" role="alert">
+            <span>
+              {backendCrash.will_restart
+                ? `\u540e\u7aef\u5df2\u505c\u6b62 (${backendCrash.reason})\uff0c\u6b63\u5728\u81ea\u52a8\u91cd\u542f\u2026`
+                : `\u540e\u7aef\u5df2\u505c\u6b62 (${backendCrash.reason})\uff0c\u81ea\u52a8\u91cd\u542f ${backendCrash.attempts} \u6b21\u540e\u4ecd\u672a\u6062\u590d\u3002`}
+            </span>
+            {!backendCrash.will_restart && (
+              <button type="button" onClick={handleRestartBackend} disabled={backendRestarting}>
+                {backendRestarting ? '\u91cd\u542f\u4e2d\u2026' : '\u91cd\u542f\u540e\u7aef'}
+              </button>
+            )}
+          </div>
+        )}
         <div className="chat-container">
           <div className="messages-wrapper">
             <div
@@ -3870,15 +4158,21 @@ function App() {
                     >
                       ×
                     </button>
-                    <button
-                      type="button"
-                      className="attachment-thumb"
-                      onClick={() => setImagePreview({ src: attachment.previewUrl, name: attachment.name })}
-                      aria-label={attachment.name || 'image'}
-                      title={attachment.name || 'image'}
-                    >
-                      <img src={attachment.previewUrl} alt={attachment.name || 'attachment'} />
-                    </button>
+                    {attachment.previewUrl ? (
+                      <button
+                        type="button"
+                        className="attachment-thumb"
+                        onClick={() => setImagePreview({ src: attachment.previewUrl, name: attachment.name })}
+                        aria-label={attachment.name || 'image'}
+                        title={attachment.name || 'image'}
+                      >
+                        <img src={attachment.previewUrl} alt={attachment.name || 'attachment'} />
+                      </button>
+                    ) : (
+                      <div className="attachment-thumb attachment-file" title={attachment.name}>
+                        {attachment.name}
+                      </div>
+                    )}
                   </div>
                 ))}
               </div>
@@ -4186,6 +4480,15 @@ function App() {
                     )}
                   </div>
 
+                  <button
+                    type="button"
+                    className="attach-image-btn"
+                    onClick={handleAttachImages}
+                    title={'\u6dfb\u52a0\u56fe\u7247'}
+                    aria-label={'\u6dfb\u52a0\u56fe\u7247'}
+                  >
+                    {'+'}
+                  </button>
                     <button
                       type="button"
                       className="work-path-row"
@@ -4551,6 +4854,15 @@ function App() {
         </div>
       )}
 
+      {appLocked && (
+        <LockScreen
+          reason={lockReason}
+          verificationAvailable={lockStatus?.verification_available ?? true}
+          passphraseAvailable={lockStatus?.passphrase_available ?? false}
+          onUnlocked={() => setAppLocked(false)}
+        />
+      )}
+
       <ConfirmDialog
         open={Boolean(rollbackTarget)}
         title="回撤消息"
@@ -0,0 +1,87 @@
+import { useEffect, useRef, useState } from 'react';
+import { finishScreenSelection, type ScreenSelection } from './api';
+import './CaptureSelectionWindow.css';
+
+interface Point {
+  x: number;
+  y: number;
+}
+
+/** Drags below this many pixels count as a click. */
+const MIN_DRAG = 4;
+
+/** Transparent overlay over one screen; the shell captures the region dragged out on it. */
+export default function CaptureSelectionWindow() {
+  const [start, setStart] = useState<Point | null>(null);
+  const [end, setEnd] = useState<Point | null>(null);
+  const done = useRef(false);
+
+  const finish = (selection: ScreenSelection | null) => {
+    if (done.current) return;
+    done.current = true;
+    void finishScreenSelection(selection);
+  };
+
+  useEffect(() => {
+    const onKey = (event: KeyboardEvent) => {
+      if (event.key === 'Escape') finish(null);
+      // Enter takes the whole screen.
+      if (event.key === 'Enter') finish({ x: 0, y: 0, width: 1, height: 1 });
+    };
+    window.addEventListener('keydown', onKey);
+    return () => window.removeEventListener('keydown', onKey);
+  }, []);
+
+  const rect =
+    start && end
+      ? {
+          left: Math.min(start.x, end.x),
+          top: Math.min(start.y, end.y),
+          width: Math.abs(end.x - start.x),
+          height: Math.abs(end.y - start.y),
+        }
+      : null;
+
+  const release = () => {
+    if (!rect || rect.width < MIN_DRAG || rect.height < MIN_DRAG) {
+      setStart(null);
+      setEnd(null);
+      return;
+    }
+    finish({
+      x: rect.left / window.innerWidth,
+      y: rect.top / window.innerHeight,
+      width: rect.width / window.innerWidth,
+      height: rect.height / window.innerHeight,
+    });
+  };
+
+  return (
+    <div
+      className={rect ? 'capture-selection selecting' : 'capture-selection'}
+      onMouseDown={(event) => {
+        if (event.button !== 0) return;
+        setStart({ x: event.clientX, y: event.clientY });
+        setEnd({ x: event.clientX, y: event.clientY });
+      }}
+      onMouseMove={(event) => {
+        if (start) setEnd({ x: event.clientX, y: event.clientY });
+      }}
+      onMouseUp={release}
+      onContextMenu={(event) => {
+        event.preventDefault();
+        finish(null);
+      }}
+    >
+      {rect ? (
+        <div className="capture-selection-rect" style={rect}>
+          <span className="capture-selection-size">
+            {Math.round(rect.width)} × {Math.round(rect.height)}
+          </span>
+        </div>
+      ) : (
+        <div className="capture-selection-hint">拖动选择截图区域 · Enter 截取整个屏幕 · Esc 取消</div>
+      )}
+    </div>
+  );
+}
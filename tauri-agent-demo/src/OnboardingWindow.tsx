@@ -0,0 +1,124 @@
+import { useEffect, useState } from 'react';
+import {
+  dismissLegacyData,
+  finishOnboarding,
+  getOnboardingState,
+  importLegacyData,
+  onShellEvent,
+  type LegacyImport,
+  type OnboardingProgress,
+  type OnboardingState,
+} from './api';
+import './OnboardingWindow.css';
+
+const formatSize = (bytes: number) => {
+  if (bytes < 1024 * 1024) return `${Math.max(1, Math.round(bytes / 1024))} KB`;
+  return `${(bytes / 1024 / 1024).toFixed(1)} MB`;
+};
+
+export default function OnboardingWindow() {
+  const [state, setState] = useState<OnboardingState | null>(null);
+  const [progress, setProgress] = useState<OnboardingProgress | null>(null);
+  const [importing, setImporting] = useState(false);
+  const [result, setResult] = useState<LegacyImport | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    getOnboardingState()
+      .then(setState)
+      .catch(() => setState(null));
+    let unlisten: (() => void) | null = null;
+    onShellEvent('onboarding://progress', setProgress)
+      .then((stop) => {
+        unlisten = stop;
+      })
+      .catch(() => undefined);
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, []);
+
+  const legacy = result ? [] : state?.legacy ?? [];
+
+  const handleImport = async (dir: string) => {
+    setImporting(true);
+    setError(null);
+    try {
+      setResult(await importLegacyData(dir));
+    } catch (err: any) {
+      setError(String(err?.message ?? err));
+    } finally {
+      setImporting(false);
+      setProgress(null);
+    }
+  };
+
+  const handleDismiss = async () => {
+    setError(null);
+    try {
+      await dismissLegacyData();
+      setState((current) => (current ? { ...current, legacy: [] } : current));
+    } catch (err: any) {
+      setError(String(err?.message ?? err));
+    }
+  };
+
+  const handleFinish = async () => {
+    try {
+      await finishOnboarding();
+    } catch (err: any) {
+      setError(String(err?.message ?? err));
+    }
+  };
+
+  return (
+    <div className="onboarding">
+      <h1 className="onboarding-title">欢迎使用 GYY</h1>
+      {state && (
+        <div className="onboarding-text">
+          数据将保存在 <code>{state.app_data_dir}</code>
+        </div>
+      )}
+      {legacy.length > 0 && (
+        <div className="onboarding-legacy">
+          <div className="onboarding-text">发现旧版本留下的数据，是否迁移到新的数据目录？</div>
+          {legacy.map((item) => (
+            <div className="onboarding-legacy-item" key={item.dir}>
+              <div className="onboarding-legacy-path">{item.dir}</div>
+              <div className="onboarding-legacy-detail">
+                会话数据库 {formatSize(item.database_size)}
+                {item.configs.length > 0 && `，配置文件 ${item.configs.length} 个`}
+              </div>
+              <button type="button" disabled={importing} onClick={() => void handleImport(item.dir)}>
+                {importing ? '迁移中' : '迁移'}
+              </button>
+            </div>
+          ))}
+          {progress && progress.total > 0 && (
+            <div className="onboarding-progress">
+              <progress value={progress.done} max={progress.total} />
+              <span>{progress.step}</span>
+            </div>
+          )}
+          <button type="button" className="onboarding-secondary" disabled={importing} onClick={() => void handleDismiss()}>
+            不迁移
+          </button>
+        </div>
+      )}
+      {result && (
+        <div className="onboarding-text">
+          迁移完成{result.database ? '，会话数据库已导入' : ''}
+          {result.configs.length > 0 && `，已导入 ${result.configs.join('、')}`}
+          {result.skipped.length > 0 && `；已保留现有的 ${result.skipped.join('、')}`}
+          {result.replaced && `；原数据库已备份到 ${result.replaced}`}
+        </div>
+      )}
+      {error && <div className="onboarding-error">{error}</div>}
+      <div className="onboarding-actions">
+        <button type="button" disabled={importing} onClick={() => void handleFinish()}>
+          开始使用
+        </button>
+      </div>
+    </div>
+  );
+}
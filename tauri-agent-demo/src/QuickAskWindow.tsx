@@ -0,0 +1,96 @@
+import { useEffect, useRef, useState } from 'react';
+import { getCurrentWindow } from '@tauri-apps/api/window';
+import { getWindowEffect, onShellEvent, openQuickAnswer, quickAsk, type QuickAnswer } from './api';
+import './QuickAskWindow.css';
+
+export default function QuickAskWindow() {
+  const [prompt, setPrompt] = useState('');
+  const [answer, setAnswer] = useState<QuickAnswer | null>(null);
+  const [asking, setAsking] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const inputRef = useRef<HTMLTextAreaElement | null>(null);
+
+  useEffect(() => {
+    getWindowEffect()
+      .then((info) => {
+        if (info.effect !== 'none') document.body.dataset.windowEffect = info.effect;
+      })
+      .catch(() => undefined);
+  }, []);
+
+  useEffect(() => {
+    const refresh = () => {
+      setError(null);
+      inputRef.current?.focus();
+    };
+    refresh();
+    let unlisten: (() => void) | null = null;
+    onShellEvent('quick-ask://refresh', refresh)
+      .then((stop) => {
+        unlisten = stop;
+      })
+      .catch(() => undefined);
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, []);
+
+  const ask = async (openInMain: boolean) => {
+    if (!prompt.trim() || asking) return;
+    setAsking(true);
+    setError(null);
+    setAnswer(null);
+    try {
+      const result = await quickAsk(prompt);
+      setPrompt('');
+      if (openInMain) {
+        await openQuickAnswer(result.session_id);
+      } else {
+        setAnswer(result);
+      }
+    } catch (err: any) {
+      setError(String(err?.message ?? err));
+    } finally {
+      setAsking(false);
+    }
+  };
+
+  const handleKeyDown = (event: React.KeyboardEvent) => {
+    if (event.key === 'Escape') {
+      void getCurrentWindow().hide();
+      return;
+    }
+    if (event.key === 'Enter' && !event.shiftKey) {
+      event.preventDefault();
+      // Ctrl/Cmd+Enter continues in the main window instead of answering here.
+      void ask(event.ctrlKey || event.metaKey);
+    }
+  };
+
+  return (
+    <div className="quick-ask" onKeyDown={handleKeyDown}>
+      <textarea
+        ref={inputRef}
+        className="quick-ask-input"
+        value={prompt}
+        onChange={(e) => setPrompt(e.target.value)}
+        placeholder="问点什么…（Enter 在此回答，Ctrl+Enter 在主窗口打开）"
+        disabled={asking}
+        autoFocus
+      />
+      <div className="quick-ask-answer">
+        {asking && <div className="quick-ask-hint">思考中…</div>}
+        {answer && <div className="quick-ask-reply">{answer.reply}</div>}
+        {!asking && !answer && !error && <div className="quick-ask-hint">回答会显示在这里。</div>}
+        {error && <div className="quick-ask-error">{error}</div>}
+      </div>
+      {answer && (
+        <div className="quick-ask-actions">
+          <button type="button" onClick={() => void openQuickAnswer(answer.session_id)}>
+            在主窗口继续
+          </button>
+        </div>
+      )}
+    </div>
+  );
+}
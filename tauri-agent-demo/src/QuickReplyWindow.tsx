@@ -0,0 +1,88 @@
+import { useEffect, useRef, useState } from 'react';
+import { getCurrentWindow } from '@tauri-apps/api/window';
+import { getLatestNotification, getWindowEffect, onShellEvent, sendQuickReply, type LatestNotification } from './api';
+import './QuickReplyWindow.css';
+
+export default function QuickReplyWindow() {
+  const [latest, setLatest] = useState<LatestNotification | null>(null);
+  const [reply, setReply] = useState('');
+  const [sending, setSending] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const inputRef = useRef<HTMLInputElement | null>(null);
+
+  useEffect(() => {
+    getWindowEffect()
+      .then((info) => {
+        if (info.effect !== 'none') document.body.dataset.windowEffect = info.effect;
+      })
+      .catch(() => undefined);
+  }, []);
+
+  useEffect(() => {
+    const refresh = () => {
+      setError(null);
+      getLatestNotification()
+        .then(setLatest)
+        .catch(() => setLatest(null));
+      inputRef.current?.focus();
+    };
+    refresh();
+    let unlisten: (() => void) | null = null;
+    onShellEvent('quick-reply://refresh', refresh)
+      .then((stop) => {
+        unlisten = stop;
+      })
+      .catch(() => undefined);
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, []);
+
+  const handleSubmit = async (event: React.FormEvent) => {
+    event.preventDefault();
+    if (!reply.trim() || sending) return;
+    setSending(true);
+    setError(null);
+    try {
+      await sendQuickReply(reply);
+      setReply('');
+    } catch (err: any) {
+      setError(String(err?.message ?? err));
+    } finally {
+      setSending(false);
+    }
+  };
+
+  const handleKeyDown = (event: React.KeyboardEvent) => {
+    if (event.key === 'Escape') {
+      void getCurrentWindow().hide();
+    }
+  };
+
+  return (
+    <div className="quick-reply" onKeyDown={handleKeyDown}>
+      {latest ? (
+        <div className="quick-reply-notification">
+          <div className="quick-reply-title">{latest.title}</div>
+          <div className="quick-reply-body">{latest.body}</div>
+        </div>
+      ) : (
+        <div className="quick-reply-empty">暂无通知，回复将开始新的会话。</div>
+      )}
+      <form className="quick-reply-form" onSubmit={handleSubmit}>
+        <input
+          ref={inputRef}
+          value={reply}
+          onChange={(e) => setReply(e.target.value)}
+          placeholder={latest?.session_id ? '回复此会话…' : '发送新消息…'}
+          disabled={sending}
+          autoFocus
+        />
+        <button type="submit" disabled={sending || !reply.trim()}>
+          {sending ? '发送中' : '发送'}
+        </button>
+      </form>
+      {error && <div className="quick-reply-error">{error}</div>}
+    </div>
+  );
+}
@@ -0,0 +1,47 @@
+import { useState } from 'react';
+import { unlockDatabase } from './api';
+import './UnlockWindow.css';
+
+export default function UnlockWindow() {
+  const [passphrase, setPassphrase] = useState('');
+  const [unlocking, setUnlocking] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const unlock = async (event: React.FormEvent) => {
+    event.preventDefault();
+    if (!passphrase || unlocking) return;
+    setUnlocking(true);
+    setError(null);
+    try {
+      // The shell closes this window once the backend is on its way.
+      await unlockDatabase(passphrase);
+    } catch (err: any) {
+      setError(String(err?.message ?? err));
+      setPassphrase('');
+    } finally {
+      setUnlocking(false);
+    }
+  };
+
+  return (
+    <form className="unlock" onSubmit={(event) => void unlock(event)}>
+      <h1 className="unlock-title">聊天记录已加密</h1>
+      <div className="unlock-text">输入口令以解锁数据库并启动后端。</div>
+      <input
+        className="unlock-input"
+        type="password"
+        value={passphrase}
+        onChange={(e) => setPassphrase(e.target.value)}
+        placeholder="口令"
+        disabled={unlocking}
+        autoFocus
+      />
+      {error && <div className="unlock-error">{error}</div>}
+      <div className="unlock-actions">
+        <button type="submit" disabled={!passphrase || unlocking}>
+          {unlocking ? '解锁中…' : '解锁'}
+        </button>
+      </div>
+    </form>
+  );
+}
@@ -3,6 +3,7 @@ import { listen } from '@tauri-apps/api/event';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { open as openDialog } from '@tauri-apps/plugin-dialog';
 import WorkDirBrowser from './components/WorkDirBrowser';
+import { dialogDefaultPath, rememberDialogDir } from './api';
 import { loadExtraWorkPaths, saveExtraWorkPaths } from './workdirStorage';
 import './WorkDirWindow.css';
 
@@ -239,9 +240,11 @@ function WorkDirWindow() {
       const selected = await openDialog({
         directory: true,
         multiple: true,
-        title: '添加文件夹到工作区'
+        title: '添加文件夹到工作区',
+        defaultPath: await dialogDefaultPath('workspace')
       });
       if (!selected) return;
+      void rememberDialogDir('workspace', selected);
       const selections = Array.isArray(selected) ? selected : [selected];
       const next = saveExtraWorkPaths(sessionKey, [...extraRoots, ...selections], rootPath);
       setExtraRoots(next);
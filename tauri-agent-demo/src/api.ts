@@ -1,4 +1,4 @@
-﻿import {
+import {
     LLMConfig,
     LLMConfigCreate,
     LLMConfigUpdate,
@@ -24,331 +24,2138 @@
     AgentTask,
     AgentTaskEvent
 } from './types';
+import { EVENT_VERSIONS } from './events.gen';
+import type { BackendStatus, ChatDelta, ChatDone, Compatibility, SessionInfo, ShellEvents, VersionReport } from './events.gen';
+
+export type { BackendPhase, BackendStatus, SessionInfo, ShellEvents } from './events.gen';
 
 export const DEFAULT_API_BASE_URL = 'http://127.0.0.1:8000';
 
-const normalizeBaseUrl = (value: string | undefined): string | undefined => {
-    const trimmed = value?.trim();
-    if (!trimmed) return undefined;
-    return trimmed.replace(/\/+$/, '');
-};
+const normalizeBaseUrl = (value: string | undefined): string | undefined => {
+    const trimmed = value?.trim();
+    if (!trimmed) return undefined;
+    return trimmed.replace(/\/+$/, '');
+};
+
+const envBaseUrl = normalizeBaseUrl(import.meta.env.VITE_API_BASE_URL);
+export let API_BASE_URL = envBaseUrl ?? DEFAULT_API_BASE_URL;
+/** Set when the shell proxies HTTP or the backend needs a token; the event WebSocket then goes here instead. */
+export let API_EVENTS_URL: string | null = null;
+/** The sidecar's per-launch token; null for backends the shell did not spawn on a port. */
+let API_TOKEN: string | null = null;
+const API_TOKEN_HEADER = 'X-Agent-Token';
+/** The shell's locale; the webview's own Accept-Language ignores the user's override. */
+let API_LOCALE: string | null = null;
+let apiBaseUrlResolved = Boolean(envBaseUrl);
+let apiBaseUrlPromise: Promise<string> | null = null;
+
+export async function resolveApiBaseUrl(): Promise<string> {
+    if (apiBaseUrlResolved) return API_BASE_URL;
+    if (apiBaseUrlPromise) return apiBaseUrlPromise;
+    apiBaseUrlPromise = (async () => {
+        const envOverride = normalizeBaseUrl(import.meta.env.VITE_API_BASE_URL);
+        if (envOverride) {
+            API_BASE_URL = envOverride;
+            apiBaseUrlResolved = true;
+            return API_BASE_URL;
+        }
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const resolved = await invoke<string>('get_backend_base_url');
+            const normalized = normalizeBaseUrl(resolved);
+            if (normalized) {
+                API_BASE_URL = normalized;
+            }
+            const endpoint = await invoke<BackendEndpoint>('get_backend_url').catch(() => null);
+            API_EVENTS_URL = endpoint?.events_url ?? null;
+            API_TOKEN = await invoke<string | null>('get_backend_token').catch(() => null);
+            API_LOCALE = (await invoke<LocaleInfo>('get_locale').catch(() => null))?.locale ?? null;
+            // The shell moves the backend to a new port if a restart finds the old one taken.
+            await onShellEvent('backend://base-url', (payload) => {
+                const next = normalizeBaseUrl(payload.url);
+                if (next) {
+                    API_BASE_URL = next;
+                }
+            });
+            await onShellEvent('backend://url', (payload) => {
+                API_EVENTS_URL = payload.events_url ?? null;
+            });
+            await onShellEvent('locale://changed', (payload) => {
+                API_LOCALE = payload.locale;
+            });
+        } catch {
+            // Keep default base URL when Tauri is unavailable.
+        }
+        apiBaseUrlResolved = true;
+        return API_BASE_URL;
+    })();
+    return apiBaseUrlPromise;
+}
+
+/** `fetch` for backend calls; attaches the token the backend requires, if there is one,
+ * and the active locale. */
+export function backendFetch(input: string, init?: RequestInit): Promise<Response> {
+    if (!API_TOKEN && !API_LOCALE) return fetch(input, init);
+    const headers = new Headers(init?.headers);
+    if (API_TOKEN) headers.set(API_TOKEN_HEADER, API_TOKEN);
+    if (API_LOCALE && !headers.has('Accept-Language')) headers.set('Accept-Language', API_LOCALE);
+    return fetch(input, { ...init, headers });
+}
+
+/** For URLs handed to `<img>` and the like, which cannot send headers. */
+export function withBackendToken(url: string): string {
+    if (!API_TOKEN) return url;
+    return `${url}${url.includes('?') ? '&' : '?'}token=${encodeURIComponent(API_TOKEN)}`;
+}
+
+export type AppErrorCode =
+    | 'spawn'
+    | 'integrity'
+    | 'settings'
+    | 'database'
+    | 'tool'
+    | 'network'
+    | 'not_found'
+    | 'invalid_input'
+    | 'read_only'
+    | 'unauthorized'
+    | 'permission'
+    | 'unavailable'
+    | 'internal';
+
+// Rejection value of shell commands that return structured errors.
+export interface AppError {
+    code: AppErrorCode;
+    message: string;
+    details?: string | null;
+    retryable: boolean;
+}
+
+export function isAppError(value: unknown): value is AppError {
+    return (
+        typeof value === 'object' &&
+        value !== null &&
+        typeof (value as AppError).code === 'string' &&
+        typeof (value as AppError).message === 'string'
+    );
+}
+
+// Lets the shell show the main window, which starts hidden to avoid a blank flash.
+export async function notifyFrontendReady(): Promise<void> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return;
+    await invoke('frontend_ready');
+}
+
+export type StartupStageName = 'resolving' | 'migrating' | 'spawning' | 'waiting' | 'ready' | 'failed';
+
+/** Also announced on `startup://stage`; `detail` carries the error once failed. */
+export interface StartupStage {
+    stage: StartupStageName;
+    detail: string | null;
+}
+
+export async function getStartupStage(): Promise<StartupStage | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<StartupStage | null>('get_startup_stage');
+}
+
+/** Relaunches or re-polls the backend after a failed start. */
+export async function retryStartup(): Promise<void> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return;
+    await invoke('retry_startup');
+}
+
+async function buildApiError(response: Response, baseMessage: string): Promise<Error> {
+    const text = await response.text();
+    let detail = text;
+    if (text) {
+        try {
+            const data = JSON.parse(text);
+            if (typeof data?.message === 'string' && data?.code) {
+                detail = `${data.code}: ${data.message}`;
+                if (data?.details && Object.keys(data.details).length) {
+                    detail += ` | ${JSON.stringify(data.details)}`;
+                }
+            } else if (data?.detail && typeof data.detail === 'object' && data.detail?.message) {
+                detail = String(data.detail.message);
+            } else if (data?.detail) {
+                detail = String(data.detail);
+            }
+        } catch {
+            // keep raw text
+        }
+    }
+    const suffix = detail ? `: ${detail}` : '';
+    return new Error(`${baseMessage} (${response.status})${suffix}`);
+}
+
+// ==================== Config API ====================
+
+export async function getConfigs(): Promise<LLMConfig[]> {
+    const response = await backendFetch(`${API_BASE_URL}/configs`);
+    if (!response.ok) throw new Error('Failed to fetch configs');
+    return response.json();
+}
+
+export async function getDefaultConfig(): Promise<LLMConfig> {
+    const response = await backendFetch(`${API_BASE_URL}/configs/default`);
+    if (!response.ok) throw new Error('Failed to fetch default config');
+    return response.json();
+}
+
+export async function getConfig(configId: string): Promise<LLMConfig> {
+    const response = await backendFetch(`${API_BASE_URL}/configs/${configId}`);
+    if (!response.ok) throw new Error('Failed to fetch config');
+    return response.json();
+}
+
+export async function createConfig(config: LLMConfigCreate): Promise<LLMConfig> {
+    const response = await backendFetch(`${API_BASE_URL}/configs`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(config),
+    });
+    if (!response.ok) throw new Error('Failed to create config');
+    return response.json();
+}
+
+export async function updateConfig(configId: string, update: LLMConfigUpdate): Promise<LLMConfig> {
+    const response = await backendFetch(`${API_BASE_URL}/configs/${configId}`, {
+        method: 'PUT',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(update),
+    });
+    if (!response.ok) throw new Error('Failed to update config');
+    return response.json();
+}
+
+export async function deleteConfig(configId: string): Promise<void> {
+    const response = await backendFetch(`${API_BASE_URL}/configs/${configId}`, {
+        method: 'DELETE',
+    });
+    if (!response.ok) throw new Error('Failed to delete config');
+}
+
+export async function revertPatch(sessionId: string, revertPatch: string, messageId?: number): Promise<PatchRevertResponse> {
+    const response = await backendFetch(`${API_BASE_URL}/patch/revert`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ session_id: sessionId, revert_patch: revertPatch, message_id: messageId }),
+    });
+    if (!response.ok) {
+        throw await buildApiError(response, 'Failed to revert patch');
+    }
+    return response.json();
+}
+
+export async function getAppConfig(): Promise<AppConfig> {
+    const response = await backendFetch(`${API_BASE_URL}/app/config`);
+    if (!response.ok) {
+        if (response.status === 404) {
+            throw new Error('App config endpoint not found. Please restart the backend.');
+        }
+        throw await buildApiError(response, 'Failed to fetch app config');
+    }
+    return response.json();
+}
+
+export async function updateAppConfig(update: AppConfigUpdate): Promise<AppConfig> {
+    const response = await backendFetch(`${API_BASE_URL}/app/config`, {
+        method: 'PUT',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(update),
+    });
+    if (!response.ok) {
+        if (response.status === 404) {
+            throw new Error('App config endpoint not found. Please restart the backend.');
+        }
+        throw await buildApiError(response, 'Failed to update app config');
+    }
+    return response.json();
+}
+
+export async function refreshMcpTools(): Promise<{ ok: boolean; registered?: string[] }> {
+    const response = await backendFetch(`${API_BASE_URL}/mcp/refresh`, {
+        method: 'POST',
+    });
+    if (!response.ok) {
+        if (response.status === 404) {
+            throw new Error('MCP refresh endpoint not found. Please restart the backend.');
+        }
+        throw await buildApiError(response, 'Failed to refresh MCP tools');
+    }
+    return response.json();
+}
+
+export async function getTools(): Promise<ToolDefinition[]> {
+    const response = await backendFetch(`${API_BASE_URL}/tools`);
+    if (!response.ok) {
+        throw await buildApiError(response, 'Failed to fetch tools');
+    }
+    return response.json();
+}
+
+export async function getAgentPrompt(params?: {
+    profileId?: string;
+    sessionId?: string;
+    includeTools?: boolean;
+    agentType?: string;
+}): Promise<AgentPromptResponse> {
+    const query = new URLSearchParams();
+    if (params?.profileId) query.set('profile_id', params.profileId);
+    if (params?.sessionId) query.set('session_id', params.sessionId);
+    if (params?.includeTools !== undefined) query.set('include_tools', params.includeTools ? 'true' : 'false');
+    if (params?.agentType) query.set('agent_type', params.agentType);
+    const suffix = query.toString();
+    const response = await backendFetch(`${API_BASE_URL}/agent/prompt${suffix ? `?${suffix}` : ''}`);
+    if (!response.ok) {
+        throw await buildApiError(response, 'Failed to fetch agent prompt');
+    }
+    return response.json();
+}
+
+export async function getSkills(): Promise<SkillSummary[]> {
+    const response = await backendFetch(`${API_BASE_URL}/skills`);
+    if (!response.ok) {
+        throw await buildApiError(response, 'Failed to fetch skills');
+    }
+    return response.json();
+}
+
+export async function runAstTool(payload: AstRequest): Promise<any> {
+    const response = await backendFetch(`${API_BASE_URL}/tools/ast`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(payload),
+    });
+    if (!response.ok) {
+        throw await buildApiError(response, 'Failed to run AST tool');
+    }
+    return response.json();
+}
+
+export async function notifyAstChanges(root: string, paths?: string[]): Promise<void> {
+    const response = await backendFetch(`${API_BASE_URL}/ast/notify`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ root, paths: paths || [] }),
+    });
+    if (!response.ok) {
+        throw await buildApiError(response, 'Failed to notify AST changes');
+    }
+}
+
+export async function getAstCache(root: string): Promise<any> {
+    const response = await backendFetch(`${API_BASE_URL}/ast/cache?root=${encodeURIComponent(root)}`);
+    if (!response.ok) {
+        throw await buildApiError(response, 'Failed to fetch AST cache');
+    }
+    return response.json();
+}
+
+export async function getAstCacheFile(root: string, path: string): Promise<any> {
+    const query = `root=${encodeURIComponent(root)}&path=${encodeURIComponent(path)}`;
+    const response = await backendFetch(`${API_BASE_URL}/ast/cache?${query}`);
+    if (!response.ok) {
+        throw await buildApiError(response, 'Failed to fetch AST cache file');
+    }
+    return response.json();
+}
+
+export async function getAstSettings(root: string): Promise<AstSettingsResponse> {
+    const response = await backendFetch(`${API_BASE_URL}/ast/settings?root=${encodeURIComponent(root)}`);
+    if (!response.ok) {
+        throw await buildApiError(response, 'Failed to fetch AST settings');
+    }
+    return response.json();
+}
+
+export async function updateAstSettings(payload: AstPathSettings & { root: string }): Promise<AstSettingsResponse> {
+    const response = await backendFetch(`${API_BASE_URL}/ast/settings`, {
+        method: 'PUT',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(payload),
+    });
+    if (!response.ok) {
+        throw await buildApiError(response, 'Failed to update AST settings');
+    }
+    return response.json();
+}
+
+export async function getAstSettingsAll(): Promise<AstSettingsAllResponse> {
+    const response = await backendFetch(`${API_BASE_URL}/ast/settings/all`);
+    if (!response.ok) {
+        throw await buildApiError(response, 'Failed to fetch AST settings');
+    }
+    return response.json();
+}
+
+export async function getCodeMap(sessionId: string, root: string): Promise<any> {
+    const query = `session_id=${encodeURIComponent(sessionId)}&root=${encodeURIComponent(root)}`;
+    const response = await backendFetch(`${API_BASE_URL}/ast/code-map?${query}`);
+    if (!response.ok) {
+        throw await buildApiError(response, 'Failed to fetch code map');
+    }
+    return response.json();
+}
+
+// ==================== Session API ====================
+
+export async function getSessions(): Promise<ChatSession[]> {
+    const response = await backendFetch(`${API_BASE_URL}/sessions`);
+    if (!response.ok) throw new Error('Failed to fetch sessions');
+    return response.json();
+}
+
+export async function getSession(
+    sessionId: string,
+    options?: { includeCount?: boolean }
+): Promise<ChatSession> {
+    const params = new URLSearchParams();
+    if (options?.includeCount === false) {
+        params.set('include_count', 'false');
+    }
+    const query = params.toString();
+    const response = await backendFetch(`${API_BASE_URL}/sessions/${sessionId}${query ? `?${query}` : ''}`);
+    if (!response.ok) throw new Error('Failed to fetch session');
+    return response.json();
+}
+
+export async function createSession(session: ChatSessionCreate): Promise<ChatSession> {
+    const response = await backendFetch(`${API_BASE_URL}/sessions`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(session),
+    });
+    if (!response.ok) throw new Error('Failed to create session');
+    return response.json();
+}
+
+export async function updateSession(sessionId: string, update: ChatSessionUpdate): Promise<ChatSession> {
+    const response = await backendFetch(`${API_BASE_URL}/sessions/${sessionId}`, {
+        method: 'PUT',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(update),
+    });
+    if (!response.ok) throw new Error('Failed to update session');
+    return response.json();
+}
+
+export async function deleteSession(sessionId: string): Promise<void> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (isTauri()) {
+        // The desktop shell keeps deleted sessions in its trash for 30 days.
+        await invoke('trash_conversation', { id: sessionId });
+        return;
+    }
+    const response = await backendFetch(`${API_BASE_URL}/sessions/${sessionId}`, {
+        method: 'DELETE',
+    });
+    if (!response.ok) throw new Error('Failed to delete session');
+}
+
+export interface TrashEntry {
+    id: string;
+    title: string;
+    deleted_at: string;
+    purge_at: string;
+    size: number;
+}
+
+export async function listTrash(): Promise<TrashEntry[]> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<TrashEntry[]>('list_trash');
+}
+
+export async function restoreFromTrash(sessionId: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('restore_from_trash', { id: sessionId });
+}
+
+export interface BackendDebuggerInfo {
+    host: string;
+    port: number;
+    /** `host:port` for a debugpy attach configuration. */
+    address: string;
+}
+
+/** Dev builds only: restarts the backend with debugpy listening on `port` (or a free one). */
+export async function enableBackendDebugging(port?: number): Promise<BackendDebuggerInfo> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<BackendDebuggerInfo>('enable_backend_debugging', { port: port ?? null });
+}
+
+/** Whether the desktop shell runs locked down (read-only / kiosk). */
+export async function getKioskMode(): Promise<boolean> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return false;
+    const status = await invoke<{ enabled: boolean }>('get_kiosk_mode');
+    return status.enabled;
+}
+
+export interface RenderMarkdownOptions {
+    highlight?: boolean;
+    remote_images?: boolean;
+}
+
+export async function renderMarkdown(markdown: string, options?: RenderMarkdownOptions): Promise<string> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<string>('render_markdown', { markdown, options });
+}
+
+export async function getMarkdownCss(theme?: string): Promise<string> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<string>('get_markdown_css', { theme });
+}
+
+export interface IndexingStatus {
+    paused: boolean;
+    /** Held back while the user is away from the computer. */
+    idle: boolean;
+    queued: number;
+    current: string | null;
+    last_error: string | null;
+    folders: { path: string; enabled: boolean; files: number }[];
+}
+
+export async function getIndexingStatus(): Promise<IndexingStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<IndexingStatus>('get_indexing_status');
+}
+
+export async function setIndexingPaused(paused: boolean): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke(paused ? 'pause_indexing' : 'resume_indexing');
+}
+
+export async function setFolderIndexing(folder: string, enabled: boolean): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('set_folder_indexing', { folder, enabled });
+}
+
+export interface PluginInfo {
+    id: string;
+    name: string;
+    version: string;
+    kind: 'native' | 'wasm';
+    path: string;
+    tools: string[];
+    description: string | null;
+    enabled: boolean;
+    signer: string;
+}
+
+export async function listPlugins(): Promise<PluginInfo[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<PluginInfo[]>('list_plugins');
+}
+
+export async function installPlugin(source: string): Promise<PluginInfo> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<PluginInfo>('install_plugin', { source });
+}
+
+export async function setPluginEnabled(id: string, enabled: boolean): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('set_plugin_enabled', { id, enabled });
+}
+
+export async function uninstallPlugin(id: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('uninstall_plugin', { id });
+}
+
+export async function getTrustedPluginKeys(): Promise<string[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<string[]>('get_trusted_plugin_keys');
+}
+
+export async function setTrustedPluginKeys(keys: string[]): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('set_trusted_plugin_keys', { keys });
+}
+
+export type WindowEffect = 'none' | 'vibrancy' | 'acrylic' | 'mica';
+
+export interface WindowEffectInfo {
+    effect: WindowEffect;
+    supported: WindowEffect[];
+}
+
+export async function getWindowEffect(): Promise<WindowEffectInfo> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return { effect: 'none', supported: ['none'] };
+    return invoke<WindowEffectInfo>('get_window_effect');
+}
+
+export async function setWindowEffect(effect: WindowEffect): Promise<WindowEffectInfo> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<WindowEffectInfo>('set_window_effect', { effect });
+}
+
+export interface LocaleInfo {
+    locale: string;
+    system: string;
+    selected: string | null;
+    bundle: string;
+    available: string[];
+}
+
+export async function getLocale(): Promise<LocaleInfo | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<LocaleInfo>('get_locale');
+}
+
+/** `null` follows the OS again. */
+export async function setLocale(tag: string | null): Promise<LocaleInfo> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<LocaleInfo>('set_locale', { tag });
+}
+
+export type ThemePreference = 'system' | 'light' | 'dark';
+
+/** Also the payload of `theme://changed`. */
+export interface ThemeInfo {
+    preference: ThemePreference;
+    system: 'light' | 'dark';
+    theme: 'light' | 'dark';
+}
+
+export async function getSystemTheme(): Promise<ThemeInfo | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<ThemeInfo>('get_system_theme');
+}
+
+export async function setWindowTheme(theme: ThemePreference): Promise<ThemeInfo> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<ThemeInfo>('set_window_theme', { theme });
+}
+
+export interface LockStatus {
+    locked: boolean;
+    auto_lock_minutes: number | null;
+    /** Locking also stops the agent runs in flight. */
+    stop_runs: boolean;
+    verification_available: boolean;
+    /** The database passphrase can unlock the app too. */
+    passphrase_available: boolean;
+}
+
+/** Payload of `app-lock://locked`. */
+export type LockReason = 'idle' | 'manual';
+
+export async function getLockStatus(): Promise<LockStatus> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) {
+        return {
+            locked: false,
+            auto_lock_minutes: null,
+            stop_runs: false,
+            verification_available: false,
+            passphrase_available: false,
+        };
+    }
+    return invoke<LockStatus>('get_lock_status');
+}
+
+/** `minutes` of null turns auto-lock off; `stopRuns` is left as is when omitted. */
+export async function setIdleTimeout(minutes: number | null, stopRuns?: boolean): Promise<LockStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<LockStatus>('set_idle_timeout', { minutes, stopRuns: stopRuns ?? null });
+}
+
+export async function lockNow(): Promise<LockStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<LockStatus>('lock_now');
+}
+
+/** Without a passphrase the OS verifies the user instead. */
+export async function unlockApp(passphrase?: string): Promise<LockStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<LockStatus>('unlock_app', { passphrase: passphrase ?? null });
+}
+
+export interface EncryptionStatus {
+    enabled: boolean;
+    /** Encrypted without its key loaded; the backend waits for the passphrase. */
+    locked: boolean;
+}
+
+export async function getEncryptionStatus(): Promise<EncryptionStatus> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return { enabled: false, locked: false };
+    return invoke<EncryptionStatus>('get_encryption_status');
+}
+
+/** Encrypts the chat database under a key derived from `passphrase`; restarts the backend. */
+export async function enableEncryption(passphrase: string): Promise<EncryptionStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<EncryptionStatus>('enable_encryption', { passphrase });
+}
+
+export async function changePassphrase(currentPassphrase: string, newPassphrase: string): Promise<EncryptionStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<EncryptionStatus>('change_passphrase', { currentPassphrase, newPassphrase });
+}
+
+/** Locks the windows; with encryption on, also stops the backend and forgets the key. */
+export async function lockApp(): Promise<EncryptionStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<EncryptionStatus>('lock_app');
+}
+
+export async function unlockDatabase(passphrase: string): Promise<EncryptionStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<EncryptionStatus>('unlock_database', { passphrase });
+}
+
+/** Milliseconds since the last keyboard or mouse input anywhere on the system. */
+export async function getIdleTime(): Promise<number> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<number>('get_idle_time');
+}
+
+export interface BackupStatus {
+    destination: string | null;
+    interval_hours: number;
+    last_success: string | null;
+    last_attempt: string | null;
+    last_error: string | null;
+    last_snapshot: string | null;
+    mirrored: number;
+}
+
+export async function getBackupStatus(): Promise<BackupStatus | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<BackupStatus>('get_backup_status');
+}
+
+export async function setBackupDestination(path: string | null, intervalHours?: number): Promise<BackupStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<BackupStatus>('set_backup_destination', { path, intervalHours });
+}
+
+export async function runBackupNow(): Promise<BackupStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<BackupStatus>('run_backup_now');
+}
+
+export interface MonitorBounds {
+    x: number;
+    y: number;
+    width: number;
+    height: number;
+}
+
+export interface MonitorInfo {
+    id: string;
+    name: string | null;
+    bounds: MonitorBounds;
+    work_area: MonitorBounds;
+    scale_factor: number;
+    primary: boolean;
+}
+
+export async function listMonitors(): Promise<MonitorInfo[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<MonitorInfo[]>('list_monitors');
+}
+
+export async function moveWindowToMonitor(label: string, monitorId: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('move_window_to_monitor', { label, monitorId });
+}
+
+/** Forgets saved window positions and recenters the main window at its default size. */
+export async function resetWindowState(): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('reset_window_state');
+}
+
+export interface MigrationSummary {
+    version: number;
+    created_at: string;
+    app_version: string;
+    includes_secrets: boolean;
+    source_data_dir: string;
+    files: number;
+    path: string;
+    size: number;
+}
+
+/** Packs everything needed to move to another computer into one encrypted archive. */
+export async function exportEverything(path: string, passphrase: string, includeSecrets = false): Promise<MigrationSummary> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<MigrationSummary>('export_everything', { path, passphrase, includeSecrets });
+}
+
+export async function restoreEverything(path: string, passphrase: string): Promise<MigrationSummary> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<MigrationSummary>('restore_everything', { path, passphrase });
+}
+
+export type HealthRange = '15m' | '1h' | '6h' | '24h';
+
+export interface HealthSample {
+    at: string;
+    latency_ms: number | null;
+    error_rate: number | null;
+    requests: number;
+    in_flight: number;
+    queue_depth: number;
+    running_tasks: number;
+    error: string | null;
+}
+
+export interface HealthHistory {
+    samples: HealthSample[];
+    failures: number;
+    p50_latency_ms: number | null;
+    p95_latency_ms: number | null;
+    error_rate: number | null;
+    max_queue_depth: number;
+}
+
+export async function getHealthHistory(range: HealthRange = '1h'): Promise<HealthHistory | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<HealthHistory>('get_health_history', { range });
+}
+
+export interface Reachability {
+    /** null when there was nothing to check, e.g. no LLM configured yet. */
+    reachable: boolean | null;
+    latency_ms: number | null;
+    error: string | null;
+}
+
+/** Payload of `connectivity_status` and the `net://online` / `net://offline` events. */
+export interface ConnectivityStatus {
+    online: boolean;
+    backend: Reachability;
+    llm: Reachability;
+    llm_endpoint: string | null;
+    checked_at: string;
+}
+
+export async function getConnectivityStatus(refresh = false): Promise<ConnectivityStatus | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<ConnectivityStatus>('connectivity_status', { refresh });
+}
+
+/** Payload of `backend_metrics` and `metrics://sample`; covers the sidecar's child processes too. */
+export interface BackendMetrics {
+    at: string;
+    pid: number;
+    processes: number;
+    /** Summed over cores, so it can exceed 100. */
+    cpu_percent: number;
+    memory_bytes: number;
+    open_files: number | null;
+}
+
+export interface ResourceMonitorSettings {
+    interval_secs: number;
+    /** Restart the backend when memory stays above this many MiB; null never does. */
+    memory_ceiling_mb: number | null;
+}
+
+export async function getBackendMetrics(): Promise<BackendMetrics | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<BackendMetrics | null>('backend_metrics');
+}
+
+export async function getResourceMonitor(): Promise<ResourceMonitorSettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<ResourceMonitorSettings>('get_resource_monitor');
+}
+
+export async function setResourceMonitor(settings: ResourceMonitorSettings): Promise<ResourceMonitorSettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<ResourceMonitorSettings>('set_resource_monitor', { settings });
+}
+
+/** Send on a chat request through the shell to name the run for `killActiveRun`. */
+export const RUN_REQUEST_ID_HEADER = 'x-agent-request-id';
+
+export interface ActiveRun {
+    request_id: string;
+    path: string;
+    session_id: string | null;
+    started_at: string;
+    elapsed_secs: number;
+    stopping: boolean;
+}
+
+/** `recycled` means the backend was restarted, ending every other run too. */
+export type KillOutcome = 'stopped' | 'recycled' | 'not_found';
+
+export interface WatchdogSettings {
+    /** null leaves only the shell's 30-minute call timeout. */
+    max_run_mins: number | null;
+    grace_secs: number;
+}
+
+export async function listActiveRuns(): Promise<ActiveRun[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<ActiveRun[]>('list_active_runs');
+}
+
+export async function killActiveRun(requestId: string): Promise<KillOutcome | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<KillOutcome>('kill_active_run', { requestId });
+}
+
+export async function getWatchdogSettings(): Promise<WatchdogSettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<WatchdogSettings>('get_watchdog_settings');
+}
+
+export async function setWatchdogSettings(settings: WatchdogSettings): Promise<WatchdogSettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<WatchdogSettings>('set_watchdog_settings', { settings });
+}
+
+export interface WorkspaceInfo {
+    id: string;
+    name: string;
+    created_at: string | null;
+    active: boolean;
+    data_dir: string;
+}
+
+export async function listWorkspaces(): Promise<WorkspaceInfo[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<WorkspaceInfo[]>('list_workspaces');
+}
+
+export async function createWorkspace(name: string): Promise<WorkspaceInfo | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<WorkspaceInfo>('create_workspace', { name });
+}
+
+/** Restarts the backend on the workspace's data; reload on `workspace://switched`. */
+export async function switchWorkspace(id: string): Promise<WorkspaceInfo | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<WorkspaceInfo>('switch_workspace', { id });
+}
+
+export type DialogPurpose = 'attachments' | 'exports' | 'backups' | 'workspace';
+
+export async function getDialogDir(purpose: DialogPurpose): Promise<string | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<string | null>('get_dialog_dir', { purpose });
+}
+
+/** `defaultPath` for a dialog: the purpose's folder, joined with `fileName` for saves. */
+export async function dialogDefaultPath(purpose: DialogPurpose, fileName?: string): Promise<string | undefined> {
+    const dir = await getDialogDir(purpose).catch(() => null);
+    if (!dir) return fileName;
+    if (!fileName) return dir;
+    const { join } = await import('@tauri-apps/api/path');
+    return join(dir, fileName);
+}
+
+export async function rememberDialogDir(purpose: DialogPurpose, path: string | string[] | null): Promise<void> {
+    const chosen = Array.isArray(path) ? path[0] : path;
+    if (!chosen) return;
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return;
+    await invoke('remember_dialog_dir', { purpose, path: chosen }).catch(() => undefined);
+}
+
+export interface QueuedChatRequest {
+    id: string;
+    queued_at: string;
+    session_id: string | null;
+    preview: string;
+    attempts: number;
+    last_error: string | null;
+    request: ChatRequest;
+}
+
+export type OutboxStatus = 'queued' | 'sending' | 'retrying' | 'sent' | 'failed' | 'cancelled';
+
+export interface OutboxStatusEvent {
+    id: string;
+    session_id: string | null;
+    status: OutboxStatus;
+    error: string | null;
+    remaining: number;
+}
+
+/** Hands a request the backend could not take to the shell, which replays it once healthy. */
+export async function queueChatRequest(request: ChatRequest): Promise<QueuedChatRequest | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<QueuedChatRequest>('queue_chat_request', { request });
+}
+
+export async function getOutbox(): Promise<QueuedChatRequest[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<QueuedChatRequest[]>('get_outbox');
+}
+
+export async function cancelQueuedRequest(id: string): Promise<void> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return;
+    await invoke('cancel_queued_request', { id });
+}
+
+export interface ScheduledTaskRun {
+    started_at: string;
+    finished_at: string | null;
+    session_id: string | null;
+    error: string | null;
+}
+
+export interface ScheduledTask {
+    id: string;
+    name: string;
+    prompt: string;
+    /** Five-field cron in local time, or `@hourly`, `@daily`, `@weekly`, `@monthly`. */
+    schedule: string;
+    work_path: string | null;
+    created_at: string;
+    next_run_at: string | null;
+    last_run: ScheduledTaskRun | null;
+}
+
+/** Each run is a new conversation; the shell fires it while the window is closed too. */
+export async function createScheduledTask(task: {
+    name?: string;
+    prompt: string;
+    schedule: string;
+    workPath?: string;
+}): Promise<ScheduledTask> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<ScheduledTask>('create_scheduled_task', task);
+}
+
+export async function listScheduledTasks(): Promise<ScheduledTask[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<ScheduledTask[]>('list_scheduled_tasks');
+}
+
+export async function deleteScheduledTask(id: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('delete_scheduled_task', { id });
+}
+
+export interface ScheduledRunEvent {
+    id: string;
+    status: 'started' | 'finished' | 'failed';
+    session_id: string | null;
+    error: string | null;
+}
+
+export async function onScheduledRun(handler: (event: ScheduledRunEvent) => void): Promise<() => void> {
+    return onShellEvent('scheduler://run', handler);
+}
+
+export interface SearchFilters {
+    session_id?: string;
+    role?: 'user' | 'assistant';
+    /** ISO 8601; `until` is exclusive. */
+    since?: string;
+    until?: string;
+    limit?: number;
+    offset?: number;
+}
+
+export interface SnippetPart {
+    text: string;
+    highlight: boolean;
+}
+
+export interface MessageHit {
+    message_id: number;
+    session_id: string;
+    session_title: string | null;
+    role: string;
+    timestamp: string;
+    snippet: SnippetPart[];
+}
+
+/** Searches the shell's local index of the active workspace's chats. */
+export async function searchMessages(query: string, filters?: SearchFilters): Promise<MessageHit[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<MessageHit[]>('search_messages', { query, filters });
+}
+
+export type ImportFormat = 'chatgpt' | 'claude' | 'markdown';
+
+export interface ImportPreview {
+    source_id: string;
+    title: string;
+    created_at: string | null;
+    messages: number;
+    already_imported: boolean;
+}
+
+export interface ImportReport {
+    target: 'preview' | 'backend' | 'database';
+    conversations: ImportPreview[];
+    imported: number;
+    skipped: number;
+    failed: { title: string; error: string }[];
+}
+
+export interface ImportProgress {
+    done: number;
+    total: number;
+    title: string;
+}
+
+/** Imports a ChatGPT or Claude export, or Markdown transcripts; `dryRun` only previews. */
+export async function importConversations(path: string, format: ImportFormat, dryRun = false): Promise<ImportReport> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<ImportReport>('import_conversations', { path, format, dryRun });
+}
+
+export async function onImportProgress(handler: (progress: ImportProgress) => void): Promise<() => void> {
+    return onShellEvent('import://progress', handler);
+}
+
+export async function getBackendStatus(): Promise<BackendStatus | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<BackendStatus>('backend_status');
+}
+
+export interface BackendReadinessSettings {
+    timeout_secs: number;
+    splash: boolean;
+}
+
+/** Applies on the next launch. */
+export async function setBackendReadiness(readiness: BackendReadinessSettings): Promise<BackendReadinessSettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<BackendReadinessSettings>('set_backend_readiness', { readiness });
+}
+
+export interface BackendShutdownSettings {
+    timeout_secs: number;
+}
+
+/** Routes webview requests through the shell, which adds a per-launch token; applies from the next launch. */
+export async function setBackendProxy(enabled: boolean): Promise<boolean | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<boolean>('set_backend_proxy', { enabled });
+}
+
+export type OutboundProxyMode = 'system' | 'manual' | 'off';
+
+export interface OutboundProxySettings {
+    mode: OutboundProxyMode;
+    /** http://, https://, socks5:// or socks5h://; used in manual mode only. */
+    url?: string | null;
+    no_proxy?: string | null;
+    /** PEM file the backend trusts instead of its bundled roots. */
+    ca_bundle?: string | null;
+}
+
+export interface SystemProxy {
+    url: string;
+    source: string;
+}
+
+export interface ProxyTestResult {
+    ok: boolean;
+    url: string;
+    proxy: string | null;
+    status: number | null;
+    elapsed_ms: number;
+    error: string | null;
+}
+
+export async function getProxySettings(): Promise<OutboundProxySettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<OutboundProxySettings>('get_proxy_settings');
+}
+
+/** Applies the next time the backend is spawned. */
+export async function setProxySettings(settings: OutboundProxySettings): Promise<OutboundProxySettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<OutboundProxySettings>('set_proxy_settings', { settings });
+}
+
+export async function detectSystemProxy(): Promise<SystemProxy | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<SystemProxy | null>('detect_system_proxy');
+}
+
+/** Tests unsaved `settings` when given, otherwise the saved ones. */
+export async function testProxyConnection(
+    settings?: OutboundProxySettings,
+    url?: string
+): Promise<ProxyTestResult | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<ProxyTestResult>('test_proxy_connection', { settings: settings ?? null, url: url ?? null });
+}
+
+/** How long the backend may take to exit cleanly before it is killed; applies from its next start. */
+export async function setBackendShutdown(shutdown: BackendShutdownSettings): Promise<BackendShutdownSettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<BackendShutdownSettings>('set_backend_shutdown', { shutdown });
+}
+
+export interface BackendEnvSettings {
+    /** Extra parent variables passed to the backend; a trailing `*` matches a prefix. */
+    pass: string[];
+    set: Record<string, string>;
+}
+
+/** Applies from the next backend start. */
+export async function setBackendEnv(env: BackendEnvSettings): Promise<BackendEnvSettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<BackendEnvSettings>('set_backend_env', { env });
+}
+
+export interface BackendEnvVar {
+    name: string;
+    /** Credentials come back as `<redacted>`. */
+    value: string;
+    source: 'inherited' | 'set';
+}
+
+export async function getEffectiveBackendEnv(): Promise<BackendEnvVar[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<BackendEnvVar[]>('get_effective_backend_env');
+}
+
+export interface PythonRuntimeStatus {
+    enabled: boolean;
+    source_dir: string | null;
+    venv_dir: string | null;
+    installed: boolean;
+    /** `requirements.txt` changed since the last setup. */
+    outdated: boolean;
+    setting_up: boolean;
+}
+
+export interface PythonSetupProgress {
+    stage: 'venv' | 'install' | 'done' | 'failed';
+    line: string | null;
+}
+
+export async function getPythonRuntimeStatus(): Promise<PythonRuntimeStatus | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<PythonRuntimeStatus>('get_python_runtime_status');
+}
+
+/** Creates the virtualenv, installs the requirements and runs the backend from source. */
+export async function setupPythonBackend(sourceDir?: string, interpreter?: string): Promise<PythonRuntimeStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<PythonRuntimeStatus>('setup_python_backend', {
+        sourceDir: sourceDir ?? null,
+        interpreter: interpreter ?? null,
+    });
+}
+
+/** Switches between the sources and the bundled binary; restarts the backend. */
+export async function setPythonBackend(enabled: boolean): Promise<PythonRuntimeStatus> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<PythonRuntimeStatus>('set_python_backend', { enabled });
+}
+
+export async function onPythonSetupProgress(handler: (progress: PythonSetupProgress) => void): Promise<() => void> {
+    return onShellEvent('python-runtime://progress', handler);
+}
+
+/**
+ * Listens for an event from the shell with its payload typed from `events.gen.ts`. Warns
+ * once if the shell sends a schema version other than the one this build was generated for.
+ */
+export async function onShellEvent<K extends keyof ShellEvents>(
+    name: K,
+    handler: (payload: ShellEvents[K]) => void
+): Promise<() => void> {
+    const { isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return () => {};
+    const { listen } = await import('@tauri-apps/api/event');
+    let warned = false;
+    return listen<ShellEvents[K]>(name, (event) => {
+        if (!warned && event.payload.schema_version !== EVENT_VERSIONS[name]) {
+            warned = true;
+            console.warn(`[Events] ${name} sent schema ${event.payload.schema_version}, expected ${EVENT_VERSIONS[name]}.`);
+        }
+        handler(event.payload);
+    });
+}
+
+/** Resolves once the shell has seen the backend answer `/health`, or given up on it. */
+export async function waitForBackend(): Promise<BackendStatus | null> {
+    const { isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return new Promise<BackendStatus | null>((resolve) => {
+        const stops: Array<() => void> = [];
+        let settled = false;
+        const finish = (status: BackendStatus | null) => {
+            if (settled) return;
+            settled = true;
+            stops.forEach((stop) => stop());
+            resolve(status);
+        };
+        const subscribe = async () => {
+            for (const event of ['backend://ready', 'backend://failed'] as const) {
+                stops.push(await onShellEvent(event, finish));
+            }
+            // Subscribed first so a settle in between is not missed.
+            const status = await getBackendStatus();
+            if (status && status.phase !== 'starting') finish(status);
+        };
+        subscribe().catch(() => finish(null));
+    });
+}
+
+export type MenuAction = 'new_chat' | 'export' | 'open_settings' | 'diagnostics';
+
+/** Calls `handler` for application menu items the frontend carries out. */
+export async function onMenuAction(handler: (action: MenuAction) => void): Promise<() => void> {
+    return onShellEvent('menu://action', (payload) => handler(payload.action));
+}
+
+export interface BackendCrash {
+    reason: string;
+    attempts: number;
+    will_restart: boolean;
+}
+
+/** Kills and relaunches the bundled backend, also after automatic restarts gave up. */
+export async function restartBackend(): Promise<void> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return;
+    await invoke('restart_backend');
+}
+
+export interface BackendEndpoint {
+    url: string;
+    port: number | null;
+    transport: 'tcp' | 'stdio';
+    /** Only set while the shell proxies HTTP; already carries the token. */
+    events_url: string | null;
+}
+
+/** The backend address with the port the shell picked for it; `backend://url` announces moves. */
+export async function getBackendUrl(): Promise<BackendEndpoint | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<BackendEndpoint>('get_backend_url');
+}
+
+export interface BackendProfile {
+    name: string;
+    kind: 'sidecar' | 'external' | 'remote';
+    url: string | null;
+    has_token: boolean;
+    active: boolean;
+}
+
+/** Built-in profiles plus those in `backends.json` in the app data folder. */
+export async function listBackendProfiles(): Promise<BackendProfile[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<BackendProfile[]>('list_backend_profiles');
+}
+
+/** Switches backends without a restart; the new address comes on `backend://url`. */
+export async function selectBackendProfile(name: string): Promise<BackendProfile | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<BackendProfile>('select_backend_profile', { name });
+}
+
+/** The last `tail` lines the backend printed, from the shell's daily log files. */
+export async function getBackendLogs(tail = 500): Promise<string[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<string[]>('get_backend_logs', { tail });
+}
+
+export async function openLogFolder(): Promise<void> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return;
+    await invoke('open_log_folder');
+}
+
+export interface ShellLogRecord {
+    timestamp: string;
+    level: 'ERROR' | 'WARN' | 'INFO' | 'DEBUG' | 'TRACE';
+    target: string;
+    message: string;
+}
+
+export interface ShellLogFilter {
+    /** Least severe level to include, e.g. `warn`. */
+    level?: string;
+    target?: string;
+    contains?: string;
+    limit?: number;
+}
+
+/** `level` is `error`…`trace`, or a full filter such as `info,tauri_agent_demo_lib::sidecar=trace`. */
+export async function setLogLevel(level: string): Promise<string | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<string>('set_log_level', { level });
+}
+
+/** The shell's own recent log records, oldest first. */
+export async function getRecentLogs(filter?: ShellLogFilter): Promise<ShellLogRecord[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<ShellLogRecord[]>('get_recent_logs', { filter: filter ?? null });
+}
+
+export interface DiagnosticsSettings {
+    crash_reports: boolean;
+}
+
+/** Resolves with the saved zip's path, or null if the dialog was cancelled. */
+export async function createDiagnosticsBundle(): Promise<string | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<string | null>('create_diagnostics_bundle');
+}
+
+export async function setCrashReports(enabled: boolean): Promise<DiagnosticsSettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<DiagnosticsSettings>('set_crash_reports', { enabled });
+}
+
+export interface BackendInfo {
+    /** False for remote and external backends, which the app does not launch. */
+    managed: boolean;
+    running?: boolean;
+    pid?: number | null;
+    uptime_secs?: number | null;
+    binary_path?: string | null;
+    restarts?: number;
+    /** How the sidecar last exited, e.g. `exit code 1`; null if it has not. */
+    last_exit?: string | null;
+    url: string | null;
+    port: number | null;
+    /** What startup did about a backend an earlier launch left running; null if none was found. */
+    takeover: BackendTakeover | null;
+}
+
+export interface BackendTakeover {
+    action: 'cleared' | 'shut_down' | 'killed' | 'failed';
+    pid: number | null;
+    detail: string;
+}
+
+export async function getBackendInfo(): Promise<BackendInfo | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<BackendInfo>('backend_info');
+}
+
+export type BackendCompatibility = Compatibility;
+
+/** The startup handshake against the backend's `/version`, also sent on `backend://version`. API 0 stands for backends from before it. */
+export type BackendVersion = VersionReport;
+
+/** Null until the backend has answered the handshake. */
+export async function getBackendVersion(): Promise<BackendVersion | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<BackendVersion | null>('backend_version');
+}
+
+export async function startBackend(): Promise<void> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return;
+    await invoke('start_backend');
+}
+
+/** Keeps the backend down until `startBackend` or `restartBackend`; announced on `backend://stopped`. */
+export async function stopBackend(): Promise<void> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return;
+    await invoke('stop_backend');
+}
+
+export interface TraySettings {
+    close_to_tray: boolean;
+}
+
+export async function getTraySettings(): Promise<TraySettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<TraySettings>('get_tray_settings');
+}
+
+/** With `close_to_tray`, closing the main window hides it and the backend keeps running. */
+export async function setTraySettings(tray: TraySettings): Promise<TraySettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<TraySettings>('set_tray_settings', { tray });
+}
+
+export interface AutostartStatus {
+    enabled: boolean;
+    /** A launch at login stays in the tray with the backend warm until the window is opened. */
+    background: boolean;
+}
+
+export async function getAutostart(): Promise<AutostartStatus | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<AutostartStatus>('get_autostart');
+}
+
+/** Adds or removes the OS login entry; `background` is left unchanged when omitted. */
+export async function setAutostart(enabled: boolean, background?: boolean): Promise<AutostartStatus | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<AutostartStatus>('set_autostart', { enabled, background: background ?? null });
+}
+
+/**
+ * Starts another backend worker for heavy runs, so they do not hold up the main chat.
+ * Send its requests to the returned `url`, with `token` when one is given.
+ */
+export async function createSession(label?: string): Promise<SessionInfo> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<SessionInfo>('create_session', { label: label ?? null });
+}
+
+export async function listSessions(): Promise<SessionInfo[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<SessionInfo[]>('list_sessions');
+}
+
+/** Stops the worker, cutting off runs still going there, and deletes its scratch directory. */
+export async function terminateSession(id: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('terminate_session', { id });
+}
+
+export async function onSessionsChanged(handler: (sessions: SessionInfo[]) => void): Promise<() => void> {
+    return onShellEvent('sessions://changed', (payload) => handler(payload.sessions));
+}
+
+export type ChatStreamKind = 'chat' | 'agent';
+
+/** Payload of `chat://delta`: one SSE event from the backend, unchanged. */
+export type ChatStreamDelta = ChatDelta;
+
+/** Payload of `chat://done`, sent once per stream. */
+export type ChatStreamDone = ChatDone;
+
+/**
+ * Has the shell read the stream and re-emit it as `chat://delta` / `chat://done`, so it
+ * survives navigation. Listen for `requestId` before calling. Returns false outside Tauri.
+ */
+export async function streamChatViaShell(requestId: string, kind: ChatStreamKind, request: ChatRequest): Promise<boolean> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return false;
+    await invoke('stream_chat', { requestId, kind, request });
+    return true;
+}
+
+/** Stops forwarding a shell stream; the run itself is stopped with `stopAgentStream`. */
+export async function cancelChatStream(requestId: string): Promise<boolean> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return false;
+    return invoke<boolean>('cancel_stream', { requestId });
+}
+
+export type ConfigFileKind = 'app' | 'tools';
+
+/** Payload of `config://changed`, sent when a config file is edited outside the app. */
+export interface ConfigFileChanged {
+    file: ConfigFileKind;
+    path: string;
+}
+
+/** The raw contents of `app_config.json`; `{}` when the backend runs on its defaults. */
+export async function readAppConfigFile(): Promise<Record<string, any> | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<Record<string, any>>('read_app_config');
+}
+
+/** Replaces `app_config.json`; rejects with `invalid_input` if it fails the schema. */
+export async function writeAppConfigFile(config: Record<string, any>): Promise<Record<string, any> | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<Record<string, any>>('write_app_config', { config });
+}
+
+export async function readToolsConfigFile(): Promise<Record<string, any> | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<Record<string, any>>('read_tools_config');
+}
+
+export async function writeToolsConfigFile(config: Record<string, any>): Promise<Record<string, any> | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<Record<string, any>>('write_tools_config', { config });
+}
+
+/**
+ * Stores a secret in the OS keychain. The backend sees it as the environment variable
+ * `key` (e.g. `TAVILY_API_KEY`) from its next start; nothing is written to disk.
+ */
+export async function setSecret(key: string, value: string): Promise<void> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return;
+    await invoke('set_secret', { key, value });
+}
+
+/** Whether the keychain holds a secret named `key`; the value never leaves the shell. */
+export async function hasSecret(key: string): Promise<boolean> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return false;
+    return invoke<boolean>('has_secret', { key });
+}
+
+export async function deleteSecret(key: string): Promise<boolean> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return false;
+    return invoke<boolean>('delete_secret', { key });
+}
+
+export async function listSecretKeys(): Promise<string[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<string[]>('list_secret_keys');
+}
+
+export interface DatabaseBackup {
+    path: string;
+    size_bytes: number;
+    created_at: string;
+}
+
+/** Payload of `database://backup-progress`, emitted while pages are copied. */
+export interface DatabaseBackupProgress {
+    operation: 'backup' | 'restore';
+    copied_pages: number;
+    total_pages: number;
+}
+
+/** Online backup of the chat database; `dest` may be a file or folder, defaulting to `backups/`. */
+export async function backupDatabase(dest?: string): Promise<DatabaseBackup | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<DatabaseBackup>('backup_database', { dest: dest ?? null });
+}
+
+/** Replaces the chat database with `src`; resolves to the backup taken of the old one. */
+export async function restoreDatabase(src: string): Promise<DatabaseBackup | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<DatabaseBackup>('restore_database', { src });
+}
+
+export type ExportFormat = 'markdown' | 'json' | 'html';
+
+/** Payload of `export://progress`, emitted after each conversation in a bulk export. */
+export interface ExportProgress {
+    done: number;
+    total: number;
+    session_id: string;
+    error: string | null;
+}
+
+export interface ExportSummary {
+    dir: string;
+    exported: number;
+    failed: number;
+}
+
+/** Exports one conversation; without `dest` a save dialog asks where. Null if cancelled. */
+export async function exportConversation(id: string, format: ExportFormat, dest?: string): Promise<string | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<string | null>('export_conversation', { id, format, dest: dest ?? null });
+}
+
+/** Exports every conversation into a new dated folder under `dest` (or a picked folder). */
+export async function exportAllConversations(format: ExportFormat, dest?: string): Promise<ExportSummary | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<ExportSummary | null>('export_all_conversations', { format, dest: dest ?? null });
+}
+
+export type NotificationKind = 'task_complete' | 'tool_error' | 'backend_crashed' | 'scheduled_job' | 'inbox_file' | 'budget' | 'test';
+
+export interface NotificationSettings {
+    enabled: boolean;
+    sound: string | null;
+    quiet_hours: { start: string; end: string } | null;
+    task_complete: boolean;
+    tool_error: boolean;
+    backend_crashed: boolean;
+    scheduled_job: boolean;
+    inbox_file: boolean;
+    budget: boolean;
+}
+
+export async function getNotificationSettings(): Promise<NotificationSettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<NotificationSettings>('get_notification_settings');
+}
+
+export async function setNotificationSettings(settings: NotificationSettings): Promise<NotificationSettings | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<NotificationSettings>('set_notification_settings', { settings });
+}
+
+/** Shows a native notification unless its category is muted; resolves to whether it was shown. */
+export async function notifyUser(title: string, body: string, kind: NotificationKind, sessionId?: string): Promise<boolean> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return false;
+    return invoke<boolean>('notify_user', { title, body, kind, sessionId: sessionId ?? null });
+}
+
+export interface LegacyData {
+    dir: string;
+    database: string | null;
+    database_size: number;
+    configs: string[];
+}
+
+export interface OnboardingState {
+    first_run: boolean;
+    data_version: number;
+    app_data_dir: string;
+    legacy: LegacyData[];
+}
+
+/** Payload of `onboarding://progress`; `step` is the file being imported, then "done". */
+export interface OnboardingProgress {
+    step: string;
+    done: number;
+    total: number;
+}
+
+export interface LegacyImport {
+    database: boolean;
+    configs: string[];
+    skipped: string[];
+    replaced: string | null;
+}
+
+export async function getOnboardingState(): Promise<OnboardingState | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<OnboardingState | null>('get_onboarding_state');
+}
+
+/** Moves a legacy database and configs into the app data folder, restarting the backend. */
+export async function importLegacyData(dir: string): Promise<LegacyImport> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<LegacyImport>('import_legacy_data', { dir });
+}
+
+export async function dismissLegacyData(): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('dismiss_legacy_data');
+}
+
+export async function finishOnboarding(): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('finish_onboarding');
+}
+
+export interface UpdateInfo {
+    version: string;
+    current_version: string;
+    notes: string | null;
+    date: string | null;
+    /** Percentage of installs the release is offered to. */
+    rollout: number;
+    sidecar_version: string | null;
+}
+
+/** Payload of `update://progress`. */
+export interface UpdateProgress {
+    phase: 'backup' | 'download' | 'install';
+    downloaded: number;
+    total: number | null;
+}
+
+export async function checkForUpdates(): Promise<UpdateInfo | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<UpdateInfo | null>('check_for_updates');
+}
+
+/** Backs up the database, stops the backend and installs the pending update; the app restarts on success. */
+export async function installUpdate(): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('install_update');
+}
+
+/** Payload of `deeplink://open`; prompts from links are only prefilled, never sent. */
+export type DeepLink =
+    | { action: 'new_chat'; prompt: string | null }
+    | { action: 'open_conversation'; session_id: string };
+
+/** Links the app was opened with before the window listened; later ones come on `deeplink://open`. */
+export async function takePendingDeepLinks(): Promise<DeepLink[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<DeepLink[]>('take_pending_deep_links');
+}
+
+/** Opens a conversation in a window of its own, or focuses the one it already has. */
+export async function openConversationWindow(conversationId: string): Promise<string | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<string>('open_conversation_window', { conversationId });
+}
+
+export interface ToolGrant {
+    tool: string;
+    workspace: string;
+    granted_at: string;
+}
+
+/** Tools the user chose "always allow" for, by workspace. */
+export async function listToolGrants(): Promise<ToolGrant[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<ToolGrant[]>('list_tool_grants');
+}
+
+/** The next call of `tool` in `workspace` asks again. */
+export async function revokeToolGrant(tool: string, workspace: string): Promise<ToolGrant[]> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return [];
+    return invoke<ToolGrant[]>('revoke_tool_grant', { tool, workspace });
+}
+
+export interface LatestNotification {
+    kind: string;
+    title: string;
+    body: string;
+    session_id?: string | null;
+    shown_at: string;
+}
+
+export async function getLatestNotification(): Promise<LatestNotification | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<LatestNotification | null>('get_latest_notification');
+}
+
+export async function sendQuickReply(message: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('send_quick_reply', { message });
+}
+
+export interface QuickAnswer {
+    session_id: string;
+    message_id: number;
+    reply: string;
+}
+
+/** Asks the backend in a new conversation from the quick ask window. */
+export async function quickAsk(prompt: string): Promise<QuickAnswer> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<QuickAnswer>('quick_ask', { prompt });
+}
+
+/** Hides the quick ask window and opens the conversation in the main window. */
+export async function openQuickAnswer(sessionId: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('open_quick_answer', { sessionId });
+}
+
+export interface StagedAttachment {
+    id: string;
+    name: string;
+    mime: string;
+    size: number;
+    path: string;
+    /** Set when the scan flagged the file; it must not be sent to the agent. */
+    quarantine: string | null;
+}
+
+/** A file dropped onto the window, stored and uploaded by the shell (`attachment://added`). */
+export interface AttachmentAdded {
+    source: string;
+    attachment: StagedAttachment;
+    upload_id: string;
+}
+
+/** A dropped file the shell refused (`attachment://rejected`). */
+export interface AttachmentRejected {
+    source: string;
+    reason: string;
+}
 
-const envBaseUrl = normalizeBaseUrl(import.meta.env.VITE_API_BASE_URL);
-export let API_BASE_URL = envBaseUrl ?? DEFAULT_API_BASE_URL;
-let apiBaseUrlResolved = Boolean(envBaseUrl);
-let apiBaseUrlPromise: Promise<string> | null = null;
+/** Runs a file the webview read itself through the shell's attachment scan. */
+export async function stageAttachment(name: string, mime: string, dataBase64: string): Promise<StagedAttachment | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<StagedAttachment>('stage_attachment', { name, mime, dataBase64 });
+}
 
-export async function resolveApiBaseUrl(): Promise<string> {
-    if (apiBaseUrlResolved) return API_BASE_URL;
-    if (apiBaseUrlPromise) return apiBaseUrlPromise;
-    apiBaseUrlPromise = (async () => {
-        const envOverride = normalizeBaseUrl(import.meta.env.VITE_API_BASE_URL);
-        if (envOverride) {
-            API_BASE_URL = envOverride;
-            apiBaseUrlResolved = true;
-            return API_BASE_URL;
-        }
-        try {
-            const { invoke } = await import('@tauri-apps/api/core');
-            const resolved = await invoke<string>('get_backend_base_url');
-            const normalized = normalizeBaseUrl(resolved);
-            if (normalized) {
-                API_BASE_URL = normalized;
-            }
-        } catch {
-            // Keep default base URL when Tauri is unavailable.
-        }
-        apiBaseUrlResolved = true;
-        return API_BASE_URL;
-    })();
-    return apiBaseUrlPromise;
+/** A region in screen coordinates: points on macOS, pixels elsewhere. */
+export interface ScreenRect {
+    x: number;
+    y: number;
+    width: number;
+    height: number;
 }
 
-async function buildApiError(response: Response, baseMessage: string): Promise<Error> {
-    const text = await response.text();
-    let detail = text;
-    if (text) {
-        try {
-            const data = JSON.parse(text);
-            if (typeof data?.message === 'string' && data?.code) {
-                detail = `${data.code}: ${data.message}`;
-                if (data?.details && Object.keys(data.details).length) {
-                    detail += ` | ${JSON.stringify(data.details)}`;
-                }
-            } else if (data?.detail && typeof data.detail === 'object' && data.detail?.message) {
-                detail = String(data.detail.message);
-            } else if (data?.detail) {
-                detail = String(data.detail);
-            }
-        } catch {
-            // keep raw text
-        }
-    }
-    const suffix = detail ? `: ${detail}` : '';
-    return new Error(`${baseMessage} (${response.status})${suffix}`);
+/** A region of the selection overlay, as fractions of the screen it covers. */
+export interface ScreenSelection {
+    x: number;
+    y: number;
+    width: number;
+    height: number;
 }
 
-// ==================== Config API ====================
+/**
+ * Captures `region`, or lets the user drag one out over the screen under the cursor,
+ * and stores it as an attachment; the main window also gets it on `attachment://added`
+ * for the next prompt. `null` when cancelled.
+ */
+export async function captureScreen(region?: ScreenRect): Promise<StagedAttachment | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<StagedAttachment | null>('capture_screen', { region: region ?? null });
+}
 
-export async function getConfigs(): Promise<LLMConfig[]> {
-    const response = await fetch(`${API_BASE_URL}/configs`);
-    if (!response.ok) throw new Error('Failed to fetch configs');
-    return response.json();
+/** Answers from the selection overlay; `null` cancels. */
+export async function finishScreenSelection(selection: ScreenSelection | null): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('finish_screen_selection', { selection });
 }
 
-export async function getDefaultConfig(): Promise<LLMConfig> {
-    const response = await fetch(`${API_BASE_URL}/configs/default`);
-    if (!response.ok) throw new Error('Failed to fetch default config');
-    return response.json();
+/** A file in the shell's content-addressed store; `url` is served by the shell. */
+export interface StoredAttachment {
+    id: string;
+    file: string;
+    name: string;
+    mime: string;
+    size: number;
+    created_at: number;
+    last_used_at: number;
+    url: string;
+    thumbnail_url: string | null;
 }
 
-export async function getConfig(configId: string): Promise<LLMConfig> {
-    const response = await fetch(`${API_BASE_URL}/configs/${configId}`);
-    if (!response.ok) throw new Error('Failed to fetch config');
-    return response.json();
+/** Loads an artifact's file through the shell's `app-asset` protocol, with range
+ * requests for media. Detached conversations may only load their own. */
+export async function artifactAssetUrl(artifactId: number): Promise<string | null> {
+    const { convertFileSrc, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return `${convertFileSrc('', 'app-asset')}artifacts/${artifactId}`;
 }
 
-export async function createConfig(config: LLMConfigCreate): Promise<LLMConfig> {
-    const response = await fetch(`${API_BASE_URL}/configs`, {
-        method: 'POST',
-        headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify(config),
-    });
-    if (!response.ok) throw new Error('Failed to create config');
-    return response.json();
+export interface AttachmentList {
+    attachments: StoredAttachment[];
+    total_bytes: number;
+    quota_bytes: number;
 }
 
-export async function updateConfig(configId: string, update: LLMConfigUpdate): Promise<LLMConfig> {
-    const response = await fetch(`${API_BASE_URL}/configs/${configId}`, {
-        method: 'PUT',
-        headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify(update),
-    });
-    if (!response.ok) throw new Error('Failed to update config');
-    return response.json();
+export interface AttachmentGcReport {
+    removed: number;
+    freed_bytes: number;
+    adopted: number;
 }
 
-export async function deleteConfig(configId: string): Promise<void> {
-    const response = await fetch(`${API_BASE_URL}/configs/${configId}`, {
-        method: 'DELETE',
-    });
-    if (!response.ok) throw new Error('Failed to delete config');
+export async function listAttachments(): Promise<AttachmentList | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<AttachmentList>('list_attachments');
 }
 
-export async function revertPatch(sessionId: string, revertPatch: string, messageId?: number): Promise<PatchRevertResponse> {
-    const response = await fetch(`${API_BASE_URL}/patch/revert`, {
-        method: 'POST',
-        headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify({ session_id: sessionId, revert_patch: revertPatch, message_id: messageId }),
-    });
-    if (!response.ok) {
-        throw await buildApiError(response, 'Failed to revert patch');
-    }
-    return response.json();
+/** Resolves to the bytes freed. */
+export async function deleteAttachment(id: string): Promise<number> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<number>('delete_attachment', { id });
 }
 
-export async function getAppConfig(): Promise<AppConfig> {
-    const response = await fetch(`${API_BASE_URL}/app/config`);
-    if (!response.ok) {
-        if (response.status === 404) {
-            throw new Error('App config endpoint not found. Please restart the backend.');
-        }
-        throw await buildApiError(response, 'Failed to fetch app config');
-    }
-    return response.json();
+export async function setAttachmentQuota(quotaMb: number): Promise<AttachmentGcReport> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<AttachmentGcReport>('set_attachment_quota', { quotaMb });
 }
 
-export async function updateAppConfig(update: AppConfigUpdate): Promise<AppConfig> {
-    const response = await fetch(`${API_BASE_URL}/app/config`, {
-        method: 'PUT',
-        headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify(update),
-    });
-    if (!response.ok) {
-        if (response.status === 404) {
-            throw new Error('App config endpoint not found. Please restart the backend.');
-        }
-        throw await buildApiError(response, 'Failed to update app config');
-    }
-    return response.json();
+/** `attachment` saves a WAV into the attachment store; `transcribe` streams to the
+ * backend while recording. Without a mode only the summary comes back. */
+export type RecordingMode = 'attachment' | 'transcribe';
+
+export interface RecordingOptions {
+    silence_threshold?: number;
+    /** Stop after this much silence following speech; `onAudioSilence` fires. */
+    silence_timeout_ms?: number;
+    mode?: RecordingMode;
+    language?: string;
+    config_id?: string;
 }
 
-export async function refreshMcpTools(): Promise<{ ok: boolean; registered?: string[] }> {
-    const response = await fetch(`${API_BASE_URL}/mcp/refresh`, {
-        method: 'POST',
-    });
-    if (!response.ok) {
-        if (response.status === 404) {
-            throw new Error('MCP refresh endpoint not found. Please restart the backend.');
-        }
-        throw await buildApiError(response, 'Failed to refresh MCP tools');
-    }
-    return response.json();
+export interface RecordingSummary {
+    duration_ms: number;
+    sample_rate: number;
+    channels: number;
+    stopped_on_silence: boolean;
+    attachment?: StagedAttachment;
+    transcript?: string;
 }
 
-export async function getTools(): Promise<ToolDefinition[]> {
-    const response = await fetch(`${API_BASE_URL}/tools`);
-    if (!response.ok) {
-        throw await buildApiError(response, 'Failed to fetch tools');
-    }
-    return response.json();
+export interface AudioLevel {
+    rms: number;
+    peak: number;
+    silent: boolean;
 }
 
-export async function getAgentPrompt(params?: {
-    profileId?: string;
-    sessionId?: string;
-    includeTools?: boolean;
-    agentType?: string;
-}): Promise<AgentPromptResponse> {
-    const query = new URLSearchParams();
-    if (params?.profileId) query.set('profile_id', params.profileId);
-    if (params?.sessionId) query.set('session_id', params.sessionId);
-    if (params?.includeTools !== undefined) query.set('include_tools', params.includeTools ? 'true' : 'false');
-    if (params?.agentType) query.set('agent_type', params.agentType);
-    const suffix = query.toString();
-    const response = await fetch(`${API_BASE_URL}/agent/prompt${suffix ? `?${suffix}` : ''}`);
-    if (!response.ok) {
-        throw await buildApiError(response, 'Failed to fetch agent prompt');
-    }
-    return response.json();
+export async function startRecording(options?: RecordingOptions): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke('start_recording', { options });
 }
 
-export async function getSkills(): Promise<SkillSummary[]> {
-    const response = await fetch(`${API_BASE_URL}/skills`);
-    if (!response.ok) {
-        throw await buildApiError(response, 'Failed to fetch skills');
-    }
-    return response.json();
+/** Resolves once the WAV is stored or the transcript is back. */
+export async function stopRecording(): Promise<RecordingSummary> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<RecordingSummary>('stop_recording');
 }
 
-export async function runAstTool(payload: AstRequest): Promise<any> {
-    const response = await fetch(`${API_BASE_URL}/tools/ast`, {
-        method: 'POST',
-        headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify(payload),
-    });
-    if (!response.ok) {
-        throw await buildApiError(response, 'Failed to run AST tool');
-    }
-    return response.json();
+/** One level per 100ms of input, for a meter or waveform. */
+export async function onAudioLevel(handler: (level: AudioLevel) => void): Promise<() => void> {
+    return onShellEvent('audio-level', handler);
 }
 
-export async function notifyAstChanges(root: string, paths?: string[]): Promise<void> {
-    const response = await fetch(`${API_BASE_URL}/ast/notify`, {
-        method: 'POST',
-        headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify({ root, paths: paths || [] }),
-    });
-    if (!response.ok) {
-        throw await buildApiError(response, 'Failed to notify AST changes');
-    }
+export async function onAudioSilence(handler: () => void): Promise<() => void> {
+    return onShellEvent('audio-silence', () => handler());
 }
 
-export async function getAstCache(root: string): Promise<any> {
-    const response = await fetch(`${API_BASE_URL}/ast/cache?root=${encodeURIComponent(root)}`);
-    if (!response.ok) {
-        throw await buildApiError(response, 'Failed to fetch AST cache');
-    }
-    return response.json();
+export async function onTranscript(handler: (text: string) => void): Promise<() => void> {
+    return onShellEvent('audio://transcript', (payload) => handler(payload.text));
 }
 
-export async function getAstCacheFile(root: string, path: string): Promise<any> {
-    const query = `root=${encodeURIComponent(root)}&path=${encodeURIComponent(path)}`;
-    const response = await fetch(`${API_BASE_URL}/ast/cache?${query}`);
-    if (!response.ok) {
-        throw await buildApiError(response, 'Failed to fetch AST cache file');
-    }
-    return response.json();
+/** `system` is the OS voice; `backend` plays the default LLM config's speech
+ * endpoint through the shell. */
+export type SpeechEngine = 'system' | 'backend';
+
+export interface SpeechSettings {
+    engine: SpeechEngine;
+    voice: string | null;
 }
 
-export async function getAstSettings(root: string): Promise<AstSettingsResponse> {
-    const response = await fetch(`${API_BASE_URL}/ast/settings?root=${encodeURIComponent(root)}`);
-    if (!response.ok) {
-        throw await buildApiError(response, 'Failed to fetch AST settings');
-    }
-    return response.json();
+export interface SpeechFinished {
+    id: number;
+    /** Cut short or dropped from the queue by `stopSpeaking`. */
+    interrupted: boolean;
+    error: string | null;
 }
 
-export async function updateAstSettings(payload: AstPathSettings & { root: string }): Promise<AstSettingsResponse> {
-    const response = await fetch(`${API_BASE_URL}/ast/settings`, {
-        method: 'PUT',
-        headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify(payload),
-    });
-    if (!response.ok) {
-        throw await buildApiError(response, 'Failed to update AST settings');
-    }
-    return response.json();
+/** Queues `text` behind anything already speaking; resolves to its id. */
+export async function speak(text: string, voice?: string): Promise<number | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<number>('speak', { text, voice });
 }
 
-export async function getAstSettingsAll(): Promise<AstSettingsAllResponse> {
-    const response = await fetch(`${API_BASE_URL}/ast/settings/all`);
-    if (!response.ok) {
-        throw await buildApiError(response, 'Failed to fetch AST settings');
-    }
-    return response.json();
+export async function stopSpeaking(): Promise<void> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return;
+    return invoke('stop_speaking');
 }
 
-export async function getCodeMap(sessionId: string, root: string): Promise<any> {
-    const query = `session_id=${encodeURIComponent(sessionId)}&root=${encodeURIComponent(root)}`;
-    const response = await fetch(`${API_BASE_URL}/ast/code-map?${query}`);
-    if (!response.ok) {
-        throw await buildApiError(response, 'Failed to fetch code map');
-    }
-    return response.json();
+export async function setSpeechSettings(settings: SpeechSettings): Promise<SpeechSettings> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<SpeechSettings>('set_speech_settings', { settings });
 }
 
-// ==================== Session API ====================
+export async function onSpeechFinished(handler: (finished: SpeechFinished) => void): Promise<() => void> {
+    return onShellEvent('tts://finished', handler);
+}
 
-export async function getSessions(): Promise<ChatSession[]> {
-    const response = await fetch(`${API_BASE_URL}/sessions`);
-    if (!response.ok) throw new Error('Failed to fetch sessions');
-    return response.json();
+export interface ClipboardContent {
+    text: string | null;
+    /** A screenshot, already uploaded; send `upload_id` with the chat request. */
+    image: { attachment: StagedAttachment; upload_id: string } | null;
 }
 
-export async function getSession(
-    sessionId: string,
-    options?: { includeCount?: boolean }
-): Promise<ChatSession> {
-    const params = new URLSearchParams();
-    if (options?.includeCount === false) {
-        params.set('include_count', 'false');
-    }
-    const query = params.toString();
-    const response = await fetch(`${API_BASE_URL}/sessions/${sessionId}${query ? `?${query}` : ''}`);
-    if (!response.ok) throw new Error('Failed to fetch session');
-    return response.json();
+/** Copies a message natively; `html` keeps the formatting in rich editors. */
+export async function copyMessageToClipboard(text: string, html?: string): Promise<boolean> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return false;
+    await invoke('copy_message_to_clipboard', { text, html: html ?? null });
+    return true;
 }
 
-export async function createSession(session: ChatSessionCreate): Promise<ChatSession> {
-    const response = await fetch(`${API_BASE_URL}/sessions`, {
-        method: 'POST',
-        headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify(session),
-    });
-    if (!response.ok) throw new Error('Failed to create session');
-    return response.json();
+/** Reads the clipboard for a paste into the composer, without browser permission prompts. */
+export async function readClipboardForPrompt(): Promise<ClipboardContent | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<ClipboardContent>('read_clipboard_for_prompt');
 }
 
-export async function updateSession(sessionId: string, update: ChatSessionUpdate): Promise<ChatSession> {
-    const response = await fetch(`${API_BASE_URL}/sessions/${sessionId}`, {
-        method: 'PUT',
-        headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify(update),
-    });
-    if (!response.ok) throw new Error('Failed to update session');
-    return response.json();
+/** Downloads an agent-produced file and asks where to save it; `null` if cancelled. */
+export async function saveArtifact(artifactId: number, suggestedName: string): Promise<string | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<string | null>('save_artifact', { artifactId, suggestedName });
 }
 
-export async function deleteSession(sessionId: string): Promise<void> {
-    const response = await fetch(`${API_BASE_URL}/sessions/${sessionId}`, {
-        method: 'DELETE',
-    });
-    if (!response.ok) throw new Error('Failed to delete session');
+/** Shows the file selected in Finder, Explorer or the file manager. */
+export async function revealInFolder(path: string): Promise<void> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return;
+    await invoke('reveal_in_folder', { path });
+}
+
+export type TaskControl = 'pause' | 'abort';
+
+/** Tells the taskbar buttons / dock menu which conversation is streaming, or none. */
+export async function setActiveRun(sessionId: string | null): Promise<void> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return;
+    await invoke('set_active_run', { sessionId });
 }
 
 export async function copySession(sessionId: string): Promise<ChatSession> {
-    const response = await fetch(`${API_BASE_URL}/sessions/${sessionId}/copy`, {
+    const response = await backendFetch(`${API_BASE_URL}/sessions/${sessionId}/copy`, {
         method: 'POST',
     });
     if (!response.ok) throw new Error('Failed to copy session');
@@ -370,19 +2177,19 @@ export async function getSessionMessages(
     const url = query
         ? `${API_BASE_URL}/sessions/${sessionId}/messages?${query}`
         : `${API_BASE_URL}/sessions/${sessionId}/messages`;
-    const response = await fetch(url);
+    const response = await backendFetch(url);
     if (!response.ok) throw new Error('Failed to fetch messages');
     return response.json();
 }
 
 export async function getSessionLLMCalls(sessionId: string): Promise<LLMCall[]> {
-    const response = await fetch(`${API_BASE_URL}/sessions/${sessionId}/llm_calls`);
+    const response = await backendFetch(`${API_BASE_URL}/sessions/${sessionId}/llm_calls`);
     if (!response.ok) throw new Error('Failed to fetch LLM calls');
     return response.json();
 }
 
 export async function getSessionToolStats(sessionId: string): Promise<SessionToolStats> {
-    const response = await fetch(`${API_BASE_URL}/sessions/${sessionId}/tool_stats`);
+    const response = await backendFetch(`${API_BASE_URL}/sessions/${sessionId}/tool_stats`);
     if (!response.ok) throw new Error('Failed to fetch tool stats');
     return response.json();
 }
@@ -399,7 +2206,7 @@ export async function getSessionAgentSteps(
     const url = query
         ? `${API_BASE_URL}/sessions/${sessionId}/agent_steps?${query}`
         : `${API_BASE_URL}/sessions/${sessionId}/agent_steps`;
-    const response = await fetch(url);
+    const response = await backendFetch(url);
     if (!response.ok) throw new Error('Failed to fetch agent steps');
     return response.json();
 }
@@ -440,7 +2247,7 @@ export interface AgentTaskCancelPayload {
 }
 
 export async function createTask(payload: AgentTaskCreatePayload): Promise<AgentTask> {
-    const response = await fetch(`${API_BASE_URL}/tasks`, {
+    const response = await backendFetch(`${API_BASE_URL}/tasks`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(payload),
@@ -452,7 +2259,7 @@ export async function createTask(payload: AgentTaskCreatePayload): Promise<Agent
 }
 
 export async function getTask(taskId: string): Promise<AgentTask> {
-    const response = await fetch(`${API_BASE_URL}/tasks/${encodeURIComponent(taskId)}`);
+    const response = await backendFetch(`${API_BASE_URL}/tasks/${encodeURIComponent(taskId)}`);
     if (!response.ok) {
         throw await buildApiError(response, 'Failed to get task');
     }
@@ -471,7 +2278,7 @@ export async function listTasks(params?: {
     if (params?.instanceId) query.set('instance_id', params.instanceId);
     if (params?.limit) query.set('limit', String(params.limit));
     const suffix = query.toString();
-    const response = await fetch(`${API_BASE_URL}/tasks${suffix ? `?${suffix}` : ''}`);
+    const response = await backendFetch(`${API_BASE_URL}/tasks${suffix ? `?${suffix}` : ''}`);
     if (!response.ok) {
         throw await buildApiError(response, 'Failed to list tasks');
     }
@@ -483,7 +2290,7 @@ export async function getTaskEvents(taskId: string, afterSeq = 0, limit = 500):
     if (afterSeq > 0) query.set('after_seq', String(afterSeq));
     if (limit) query.set('limit', String(limit));
     const suffix = query.toString();
-    const response = await fetch(`${API_BASE_URL}/tasks/${encodeURIComponent(taskId)}/events${suffix ? `?${suffix}` : ''}`);
+    const response = await backendFetch(`${API_BASE_URL}/tasks/${encodeURIComponent(taskId)}/events${suffix ? `?${suffix}` : ''}`);
     if (!response.ok) {
         throw await buildApiError(response, 'Failed to get task events');
     }
@@ -491,7 +2298,7 @@ export async function getTaskEvents(taskId: string, afterSeq = 0, limit = 500):
 }
 
 export async function handoffTask(taskId: string, payload: AgentTaskHandoffPayload): Promise<AgentTask> {
-    const response = await fetch(`${API_BASE_URL}/tasks/${encodeURIComponent(taskId)}/handoff`, {
+    const response = await backendFetch(`${API_BASE_URL}/tasks/${encodeURIComponent(taskId)}/handoff`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(payload),
@@ -503,7 +2310,7 @@ export async function handoffTask(taskId: string, payload: AgentTaskHandoffPaylo
 }
 
 export async function cancelTask(taskId: string, payload?: AgentTaskCancelPayload): Promise<AgentTask> {
-    const response = await fetch(`${API_BASE_URL}/tasks/${encodeURIComponent(taskId)}/cancel`, {
+    const response = await backendFetch(`${API_BASE_URL}/tasks/${encodeURIComponent(taskId)}/cancel`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(payload || { reason: 'Cancelled by user', propagate: true }),
@@ -517,7 +2324,7 @@ export async function cancelTask(taskId: string, payload?: AgentTaskCancelPayloa
 // ==================== Chat API ====================
 
 export async function sendMessage(request: ChatRequest): Promise<ChatResponse> {
-    const response = await fetch(`${API_BASE_URL}/chat`, {
+    const response = await backendFetch(`${API_BASE_URL}/chat`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(request),
@@ -527,7 +2334,7 @@ export async function sendMessage(request: ChatRequest): Promise<ChatResponse> {
 }
 
 export async function* sendMessageStream(request: ChatRequest): AsyncGenerator<any, void, unknown> {
-    const response = await fetch(`${API_BASE_URL}/chat/stream`, {
+    const response = await backendFetch(`${API_BASE_URL}/chat/stream`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(request),
@@ -722,7 +2529,7 @@ export async function* sendMessageAgentStream(
 
         let response: Response;
         try {
-            response = await fetch(`${API_BASE_URL}/chat/agent/stream`, {
+            response = await backendFetch(`${API_BASE_URL}/chat/agent/stream`, {
                 method: 'POST',
                 headers: { 'Content-Type': 'application/json' },
                 body: JSON.stringify(payload),
@@ -859,7 +2666,7 @@ export async function* sendPtyStream(
 
         let response: Response;
         try {
-            response = await fetch(`${API_BASE_URL}/pty/stream`, {
+            response = await backendFetch(`${API_BASE_URL}/pty/stream`, {
                 method: 'POST',
                 headers: { 'Content-Type': 'application/json' },
                 body: JSON.stringify(payload),
@@ -985,7 +2792,7 @@ export async function stopAgentStream(params: { messageId?: number; sessionId?:
     const payload: Record<string, any> = {};
     if (typeof params.messageId === 'number') payload.message_id = params.messageId;
     if (typeof params.sessionId === 'string' && params.sessionId) payload.session_id = params.sessionId;
-    const response = await fetch(`${API_BASE_URL}/chat/stop`, {
+    const response = await backendFetch(`${API_BASE_URL}/chat/stop`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(payload)
@@ -1001,7 +2808,7 @@ export interface RollbackResponse {
 }
 
 export async function rollbackSession(sessionId: string, messageId: number): Promise<RollbackResponse> {
-    const response = await fetch(`${API_BASE_URL}/sessions/${sessionId}/rollback`, {
+    const response = await backendFetch(`${API_BASE_URL}/sessions/${sessionId}/rollback`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify({ message_id: messageId })
@@ -1024,7 +2831,7 @@ export async function listPtys(
     if (options?.maxExited !== undefined) {
         params.set('max_exited', String(options.maxExited));
     }
-    const response = await fetch(`${API_BASE_URL}/pty/list?${params.toString()}`);
+    const response = await backendFetch(`${API_BASE_URL}/pty/list?${params.toString()}`);
     if (!response.ok) throw new Error('Failed to fetch PTY list');
     const data = await response.json();
     return Array.isArray(data?.items) ? data.items : [];
@@ -1036,7 +2843,7 @@ export async function readPty(payload: {
     cursor?: number;
     max_output?: number;
 }): Promise<PtyReadResponse> {
-    const response = await fetch(`${API_BASE_URL}/pty/read`, {
+    const response = await backendFetch(`${API_BASE_URL}/pty/read`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(payload)
@@ -1050,7 +2857,7 @@ export async function sendPty(payload: {
     pty_id: string;
     input: string;
 }): Promise<{ ok: boolean; pty_id: string; bytes_written: number }> {
-    const response = await fetch(`${API_BASE_URL}/pty/send`, {
+    const response = await backendFetch(`${API_BASE_URL}/pty/send`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(payload)
@@ -1063,7 +2870,7 @@ export async function closePty(payload: {
     session_id: string;
     pty_id: string;
 }): Promise<{ ok: boolean; pty_id: string }> {
-    const response = await fetch(`${API_BASE_URL}/pty/close`, {
+    const response = await backendFetch(`${API_BASE_URL}/pty/close`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(payload)
@@ -1078,13 +2885,13 @@ export async function getToolPermissions(status?: string): Promise<ToolPermissio
     const url = status
         ? `${API_BASE_URL}/tools/permissions?status=${encodeURIComponent(status)}`
         : `${API_BASE_URL}/tools/permissions`;
-    const response = await fetch(url);
+    const response = await backendFetch(url);
     if (!response.ok) throw new Error('Failed to fetch tool permissions');
     return response.json();
 }
 
 export async function updateToolPermission(requestId: number, status: string): Promise<ToolPermissionRequest> {
-    const response = await fetch(`${API_BASE_URL}/tools/permissions/${requestId}`, {
+    const response = await backendFetch(`${API_BASE_URL}/tools/permissions/${requestId}`, {
         method: 'PUT',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify({ status })
@@ -1093,6 +2900,10 @@ export async function updateToolPermission(requestId: number, status: string): P
     return response.json();
 }
 
+export function getUploadUrl(uploadId: string): string {
+    return withBackendToken(`${API_BASE_URL}/attachments/uploads/${uploadId}`);
+}
+
 export function getAttachmentUrl(attachmentId: number, options?: { thumbnail?: boolean; maxSize?: number }): string {
     const params = new URLSearchParams();
     if (options?.thumbnail) {
@@ -1102,5 +2913,43 @@ export function getAttachmentUrl(attachmentId: number, options?: { thumbnail?: b
         params.set('max_size', String(options.maxSize));
     }
     const query = params.toString();
-    return query ? `${API_BASE_URL}/attachments/${attachmentId}?${query}` : `${API_BASE_URL}/attachments/${attachmentId}`;
+    return withBackendToken(
+        query ? `${API_BASE_URL}/attachments/${attachmentId}?${query}` : `${API_BASE_URL}/attachments/${attachmentId}`
+    );
+}
+
+export interface UsageRange {
+    /** `YYYY-MM-DD`, inclusive. */
+    from?: string | null;
+    to?: string | null;
+    conversation_id?: string | null;
+}
+
+export interface UsageCost {
+    key: string;
+    prompt_tokens: number;
+    completion_tokens: number;
+    total_tokens: number;
+    requests: number;
+    cost_usd: number;
+    /** False when some model in it has no price, so `cost_usd` is a lower bound. */
+    priced: boolean;
+}
+
+export interface UsageSummary {
+    total: UsageCost;
+    by_day: UsageCost[];
+    by_model: UsageCost[];
+}
+
+export async function getUsageSummary(range?: UsageRange): Promise<UsageSummary | null> {
+    const { invoke, isTauri } = await import('@tauri-apps/api/core');
+    if (!isTauri()) return null;
+    return invoke<UsageSummary>('get_usage_summary', { range: range ?? null });
+}
+
+/** Writes the usage in `range` as CSV; returns the file written. */
+export async function exportUsageCsv(dest: string, range?: UsageRange): Promise<string> {
+    const { invoke } = await import('@tauri-apps/api/core');
+    return invoke<string>('export_usage_csv', { dest, range: range ?? null });
 }
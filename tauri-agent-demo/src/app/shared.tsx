@@ -30,11 +30,8 @@ export const AGENT_MODE_OPTIONS: { value: AgentMode; label: string; description:
 export const IS_MAC = typeof navigator !== 'undefined' && /mac/i.test(navigator.userAgent);
 export const MAX_CONCURRENT_STREAMS = 10;
 export const WORK_PATH_MAX_LENGTH = 200;
-export const MAIN_WINDOW_BOUNDS_KEY = 'mainWindowBounds';
 export const WORKDIR_BOUNDS_KEY = 'workdirWindowBounds';
 export const SIDEBAR_OPEN_KEY = 'sessionSidebarOpen';
-export const MAIN_DEFAULT_WIDTH = 1200;
-export const MAIN_DEFAULT_HEIGHT = 950;
 export const WORKDIR_DEFAULT_WIDTH = 1200;
 export const WORKDIR_DEFAULT_HEIGHT = 800;
 export const DEFAULT_PTY_PANEL_WIDTH = 380;
@@ -105,27 +102,6 @@ export type WorkdirBounds = {
   height: number;
 };
 
-export type MainWindowBounds = {
-  width: number;
-  height: number;
-};
-
-export const getMainWindowBounds = (): MainWindowBounds => {
-  try {
-    const raw = localStorage.getItem(MAIN_WINDOW_BOUNDS_KEY);
-    if (!raw) {
-      return { width: MAIN_DEFAULT_WIDTH, height: MAIN_DEFAULT_HEIGHT };
-    }
-    const parsed = JSON.parse(raw) as Partial<MainWindowBounds>;
-    return {
-      width: Math.max(800, Number(parsed?.width) || MAIN_DEFAULT_WIDTH),
-      height: Math.max(600, Number(parsed?.height) || MAIN_DEFAULT_HEIGHT),
-    };
-  } catch {
-    return { width: MAIN_DEFAULT_WIDTH, height: MAIN_DEFAULT_HEIGHT };
-  }
-};
-
 export const getWorkdirWindowBounds = (): WorkdirBounds | null => {
   try {
     const raw = localStorage.getItem(WORKDIR_BOUNDS_KEY);
@@ -589,7 +565,8 @@ export type PendingAttachment = {
   width?: number;
   height?: number;
   previewUrl: string;
-  dataBase64: string;
+  dataBase64?: string;
+  uploadId?: string;
 };
 
 export type QueueItem = {
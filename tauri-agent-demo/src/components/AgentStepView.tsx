@@ -14,7 +14,7 @@ import texmath from 'markdown-it-texmath';
 import katex from 'katex';
 import 'katex/dist/katex.min.css';
 import { openPath, openUrl, revealItemInDir } from '@tauri-apps/plugin-opener';
-import { API_BASE_URL, AgentStep } from '../api';
+import { API_BASE_URL, AgentStep, backendFetch } from '../api';
 import { ToolPermissionRequest, AstPayload } from '../types';
 import { usePtySessionSnapshot } from '../ptyStore';
 import { stripAnsiForDisplay } from '../ptyAnsi';
@@ -1396,7 +1396,7 @@ function AgentStepView({
         const controller = new AbortController();
         const timeout = window.setTimeout(() => controller.abort(), FILE_EXISTS_TIMEOUT_MS);
         try {
-            const response = await fetch(
+            const response = await backendFetch(
                 `${API_BASE_URL}/local-file-exists?path=${encodeURIComponent(resolvedPath)}`,
                 { signal: controller.signal }
             );
@@ -1,5 +1,5 @@
 import { useState, useEffect, useMemo } from 'react';
-import { save as saveDialog } from '@tauri-apps/plugin-dialog';
+import { open as openDialog, save as saveDialog } from '@tauri-apps/plugin-dialog';
 import { writeTextFile } from '@tauri-apps/plugin-fs';
 import {
     LLMConfig,
@@ -28,7 +28,31 @@ import {
     getTools,
     getAgentPrompt,
     getAstSettingsAll,
-    updateAstSettings
+    updateAstSettings,
+    PluginInfo,
+    listPlugins,
+    installPlugin,
+    setPluginEnabled,
+    uninstallPlugin,
+    getTrustedPluginKeys,
+    setTrustedPluginKeys,
+    WindowEffect,
+    WindowEffectInfo,
+    getWindowEffect,
+    setWindowEffect,
+    LockStatus,
+    getLockStatus,
+    setIdleTimeout,
+    lockNow,
+    BackupStatus,
+    getBackupStatus,
+    setBackupDestination,
+    runBackupNow,
+    dialogDefaultPath,
+    rememberDialogDir,
+    TraySettings,
+    getTraySettings,
+    setTraySettings
 } from '../api';
 import { exportConfigFile, importConfigFile } from '../configExchange';
 import ConfirmDialog from './ConfirmDialog';
@@ -68,6 +92,16 @@ const REASONING_SUMMARY_OPTIONS: { value: ReasoningSummary; label: string }[] =
     { value: 'detailed', label: 'Detailed' }
 ];
 
+const AUTO_LOCK_OPTIONS = [5, 15, 30, 60];
+const BACKUP_INTERVAL_OPTIONS = [6, 12, 24, 72, 168];
+
+const WINDOW_EFFECT_LABELS: Record<WindowEffect, string> = {
+    none: '无',
+    vibrancy: 'Vibrancy（macOS）',
+    acrylic: 'Acrylic（亚克力）',
+    mica: 'Mica（云母）'
+};
+
 type MCPServerForm = {
     enabled: boolean;
     server_label: string;
@@ -292,7 +326,7 @@ const splitMcpToolDescription = (tool: ToolDefinition): { title: string; descrip
 };
 
 export default function ConfigManager({ onClose, onConfigCreated, currentSessionId }: ConfigManagerProps) {
-    type ConfigTab = 'models' | 'global' | 'mcp' | 'agents';
+    type ConfigTab = 'models' | 'global' | 'mcp' | 'plugins' | 'agents';
     const [configs, setConfigs] = useState<LLMConfig[]>([]);
     const [activeTab, setActiveTab] = useState<ConfigTab>('models');
     const [globalTimeoutSec, setGlobalTimeoutSec] = useState('180');
@@ -339,6 +373,16 @@ export default function ConfigManager({ onClose, onConfigCreated, currentSession
     const [mcpRefreshed, setMcpRefreshed] = useState(false);
     const [mcpRefreshError, setMcpRefreshError] = useState<string | null>(null);
     const [mcpExpanded, setMcpExpanded] = useState<Record<string, boolean>>({});
+    const [plugins, setPlugins] = useState<PluginInfo[]>([]);
+    const [pluginSource, setPluginSource] = useState('');
+    const [trustedKeys, setTrustedKeys] = useState('');
+    const [pluginBusy, setPluginBusy] = useState(false);
+    const [pluginError, setPluginError] = useState<string | null>(null);
+    const [windowEffect, setWindowEffectInfo] = useState<WindowEffectInfo>({ effect: 'none', supported: ['none'] });
+    const [lockStatus, setLockStatus] = useState<LockStatus | null>(null);
+    const [traySettings, setTraySettingsState] = useState<TraySettings | null>(null);
+    const [backupStatus, setBackupStatus] = useState<BackupStatus | null>(null);
+    const [backupRunning, setBackupRunning] = useState(false);
     const [agentConfig, setAgentConfig] = useState<AgentConfig>({});
     const [agentLoading, setAgentLoading] = useState(false);
     const [agentSaving, setAgentSaving] = useState(false);
@@ -413,8 +457,123 @@ export default function ConfigManager({ onClose, onConfigCreated, currentSession
         loadConfigs();
         loadAppConfig();
         loadTools();
+        loadPlugins();
+        getWindowEffect().then(setWindowEffectInfo).catch(() => undefined);
+        getLockStatus().then(setLockStatus).catch(() => undefined);
+        getTraySettings().then(setTraySettingsState).catch(() => undefined);
+        getBackupStatus().then(setBackupStatus).catch(() => undefined);
     }, []);
 
+    const handleWindowEffectChange = async (effect: WindowEffect) => {
+        try {
+            setWindowEffectInfo(await setWindowEffect(effect));
+        } catch (error: any) {
+            alert(String(error?.message ?? error));
+        }
+    };
+
+    const handleCloseToTrayChange = async (closeToTray: boolean) => {
+        try {
+            setTraySettingsState(await setTraySettings({ close_to_tray: closeToTray }));
+        } catch (error: any) {
+            alert(String(error?.message ?? error));
+        }
+    };
+
+    const handleAutoLockChange = async (value: string) => {
+        try {
+            setLockStatus(await setIdleTimeout(value ? Number(value) : null));
+        } catch (error: any) {
+            alert(String(error?.message ?? error));
+        }
+    };
+
+    const handleLockStopRunsChange = async (stopRuns: boolean) => {
+        try {
+            setLockStatus(await setIdleTimeout(lockStatus?.auto_lock_minutes ?? null, stopRuns));
+        } catch (error: any) {
+            alert(String(error?.message ?? error));
+        }
+    };
+
+    const handleLockNow = async () => {
+        try {
+            setLockStatus(await lockNow());
+        } catch (error: any) {
+            alert(String(error?.message ?? error));
+        }
+    };
+
+    const handleChooseBackupFolder = async () => {
+        const selected = await openDialog({
+            directory: true,
+            multiple: false,
+            defaultPath: backupStatus?.destination || (await dialogDefaultPath('backups'))
+        });
+        if (typeof selected !== 'string') return;
+        void rememberDialogDir('backups', selected);
+        try {
+            setBackupStatus(await setBackupDestination(selected, backupStatus?.interval_hours));
+        } catch (error: any) {
+            alert(String(error?.message ?? error));
+        }
+    };
+
+    const handleBackupChange = async (path: string | null, intervalHours?: number) => {
+        try {
+            setBackupStatus(await setBackupDestination(path, intervalHours));
+        } catch (error: any) {
+            alert(String(error?.message ?? error));
+        }
+    };
+
+    const handleRunBackup = async () => {
+        setBackupRunning(true);
+        try {
+            setBackupStatus(await runBackupNow());
+        } catch (error: any) {
+            alert(String(error?.message ?? error));
+            getBackupStatus().then(setBackupStatus).catch(() => undefined);
+        } finally {
+            setBackupRunning(false);
+        }
+    };
+
+    const loadPlugins = async () => {
+        try {
+            const [installed, keys] = await Promise.all([listPlugins(), getTrustedPluginKeys()]);
+            setPlugins(installed);
+            setTrustedKeys(keys.join('\n'));
+        } catch (error) {
+            console.error('Failed to load plugins:', error);
+        }
+    };
+
+    const runPluginAction = async (action: () => Promise<unknown>) => {
+        setPluginBusy(true);
+        setPluginError(null);
+        try {
+            await action();
+            setPlugins(await listPlugins());
+            await loadTools();
+        } catch (error: any) {
+            setPluginError(String(error?.message ?? error));
+        } finally {
+            setPluginBusy(false);
+        }
+    };
+
+    const handlePluginInstall = () =>
+        runPluginAction(async () => {
+            await installPlugin(pluginSource.trim());
+            setPluginSource('');
+        });
+
+    const handleTrustedKeysSave = () =>
+        runPluginAction(() =>
+            setTrustedPluginKeys(trustedKeys.split('\n').map((key) => key.trim()).filter(Boolean))
+        );
+
     const loadConfigs = async () => {
         try {
             const data = await getConfigs();
@@ -1929,10 +2088,11 @@ export default function ConfigManager({ onClose, onConfigCreated, currentSession
             const defaultName = safeName ? `agent-prompt-${safeName}.md` : 'agent-prompt.md';
             const target = await saveDialog({
                 title: '导出提示词',
-                defaultPath: defaultName,
+                defaultPath: await dialogDefaultPath('exports', defaultName),
                 filters: [{ name: 'Markdown', extensions: ['md'] }]
             });
             if (!target) return;
+            void rememberDialogDir('exports', target);
             await writeTextFile(target, promptText);
             alert('提示词已导出。');
         } catch (error: any) {
@@ -2156,6 +2316,16 @@ export default function ConfigManager({ onClose, onConfigCreated, currentSession
                         >
                             MCP配置
                         </button>
+                        <button
+                            className={`config-tab ${activeTab === 'plugins' ? 'active' : ''}`}
+                            onClick={() => {
+                                setActiveTab('plugins');
+                                setPluginError(null);
+                            }}
+                            type="button"
+                        >
+                            插件
+                        </button>
                         <button
                             className={`config-tab ${activeTab === 'agents' ? 'active' : ''}`}
                             onClick={() => {
@@ -2333,6 +2503,116 @@ export default function ConfigManager({ onClose, onConfigCreated, currentSession
                                 <small>ReAct agent 每次对话允许的最大循环步数（≥ 1）。</small>
                             </div>
 
+                            {windowEffect.supported.length > 1 && (
+                                <div className="form-group">
+                                    <label>窗口效果</label>
+                                    <select
+                                        value={windowEffect.effect}
+                                        onChange={(e) => void handleWindowEffectChange(e.target.value as WindowEffect)}
+                                    >
+                                        {windowEffect.supported.map((effect) => (
+                                            <option key={effect} value={effect}>
+                                                {WINDOW_EFFECT_LABELS[effect]}
+                                            </option>
+                                        ))}
+                                    </select>
+                                    <small>立即应用到主窗口，无需保存。</small>
+                                </div>
+                            )}
+
+                            {traySettings && (
+                                <div className="form-group checkbox-group">
+                                    <label>
+                                        <input
+                                            type="checkbox"
+                                            checked={traySettings.close_to_tray}
+                                            onChange={(e) => void handleCloseToTrayChange(e.target.checked)}
+                                        />
+                                        关闭窗口时最小化到托盘
+                                    </label>
+                                    <small>后端保持运行；从托盘菜单的“退出”完全关闭应用。立即生效，无需保存。</small>
+                                </div>
+                            )}
+
+                            {(lockStatus?.verification_available || lockStatus?.passphrase_available) && (
+                                <div className="form-group">
+                                    <label>自动锁定</label>
+                                    <select
+                                        value={lockStatus.auto_lock_minutes ?? ''}
+                                        onChange={(e) => void handleAutoLockChange(e.target.value)}
+                                    >
+                                        <option value="">关闭</option>
+                                        {AUTO_LOCK_OPTIONS.map((minutes) => (
+                                            <option key={minutes} value={minutes}>
+                                                {minutes} 分钟无操作后
+                                            </option>
+                                        ))}
+                                    </select>
+                                    <small>
+                                        锁定后窗口内容被遮挡，需通过系统身份验证（Touch ID、Windows Hello 或密码）
+                                        {lockStatus.passphrase_available ? '或聊天记录口令' : ''}解锁。
+                                    </small>
+                                </div>
+                            )}
+
+                            {(lockStatus?.verification_available || lockStatus?.passphrase_available) && (
+                                <div className="form-group checkbox-group">
+                                    <label>
+                                        <input
+                                            type="checkbox"
+                                            checked={lockStatus.stop_runs}
+                                            onChange={(e) => void handleLockStopRunsChange(e.target.checked)}
+                                        />
+                                        锁定时停止正在进行的请求
+                                    </label>
+                                    <button type="button" onClick={() => void handleLockNow()}>
+                                        立即锁定
+                                    </button>
+                                </div>
+                            )}
+
+                            {backupStatus && (
+                                <div className="form-group">
+                                    <label>备份位置</label>
+                                    <div className="backup-row">
+                                        <input value={backupStatus.destination ?? ''} placeholder="未设置（仅本地快照）" readOnly />
+                                        <button type="button" onClick={() => void handleChooseBackupFolder()}>
+                                            选择文件夹
+                                        </button>
+                                        {backupStatus.destination && (
+                                            <button type="button" onClick={() => void handleBackupChange(null)}>
+                                                停止备份
+                                            </button>
+                                        )}
+                                    </div>
+                                    {backupStatus.destination && (
+                                        <div className="backup-row">
+                                            <select
+                                                value={backupStatus.interval_hours}
+                                                onChange={(e) =>
+                                                    void handleBackupChange(backupStatus.destination, Number(e.target.value))
+                                                }
+                                            >
+                                                {BACKUP_INTERVAL_OPTIONS.map((hours) => (
+                                                    <option key={hours} value={hours}>
+                                                        每 {hours} 小时
+                                                    </option>
+                                                ))}
+                                            </select>
+                                            <button type="button" onClick={() => void handleRunBackup()} disabled={backupRunning}>
+                                                {backupRunning ? '备份中…' : '立即备份'}
+                                            </button>
+                                        </div>
+                                    )}
+                                    <small>
+                                        {backupStatus.last_success
+                                            ? `上次成功：${new Date(backupStatus.last_success).toLocaleString()}，已校验 ${backupStatus.mirrored} 个快照。`
+                                            : '可选择外部磁盘或 Dropbox / iCloud 同步文件夹，快照会按计划复制并校验。'}
+                                        {backupStatus.last_error && ` 最近一次失败：${backupStatus.last_error}`}
+                                    </small>
+                                </div>
+                            )}
+
                             <div className="config-subsection">
                                 <div className="config-subsection-header">
                                     <h4>代码分析</h4>
@@ -2798,6 +3078,114 @@ export default function ConfigManager({ onClose, onConfigCreated, currentSession
                             </div>
                             {mcpSaved && <div className="save-hint">已保存</div>}
                         </form>
+                    ) : activeTab === 'plugins' ? (
+                        <div className="config-form">
+                            <div className="config-form-header">
+                                <h3>插件</h3>
+                            </div>
+
+                            <div className="config-subsection">
+                                <div className="config-subsection-header">
+                                    <div>
+                                        <h4>安装插件</h4>
+                                        <span>URL 或本地路径；签名从同一位置的 .sig 文件读取。</span>
+                                    </div>
+                                </div>
+                                <div className="form-group">
+                                    <input
+                                        type="text"
+                                        value={pluginSource}
+                                        onChange={(e) => setPluginSource(e.target.value)}
+                                        placeholder="https://example.com/my-tool.zip"
+                                        disabled={pluginBusy}
+                                    />
+                                </div>
+                                <button
+                                    type="button"
+                                    className="add-btn add-inline"
+                                    onClick={handlePluginInstall}
+                                    disabled={pluginBusy || !pluginSource.trim()}
+                                >
+                                    {pluginBusy ? '处理中...' : '安装'}
+                                </button>
+                                {pluginError && <div className="form-error">{pluginError}</div>}
+                            </div>
+
+                            <div className="config-subsection">
+                                <div className="config-subsection-header">
+                                    <div>
+                                        <h4>已安装</h4>
+                                    </div>
+                                </div>
+                                {plugins.length === 0 ? (
+                                    <p className="mcp-empty">暂无已安装的插件。</p>
+                                ) : (
+                                    plugins.map((plugin) => (
+                                        <div key={plugin.id} className="mcp-server-card">
+                                            <div className="mcp-server-header">
+                                                <div className="mcp-server-title">
+                                                    <strong>
+                                                        {plugin.name} {plugin.version}
+                                                    </strong>
+                                                    <span>
+                                                        {plugin.kind === 'wasm' ? 'WASM' : 'Native'} ·{' '}
+                                                        {plugin.description || plugin.tools.join(', ')}
+                                                    </span>
+                                                </div>
+                                                <div className="mcp-server-actions">
+                                                    <label className="form-group checkbox-group">
+                                                        <input
+                                                            type="checkbox"
+                                                            checked={plugin.enabled}
+                                                            disabled={pluginBusy}
+                                                            onChange={(e) =>
+                                                                runPluginAction(() =>
+                                                                    setPluginEnabled(plugin.id, e.target.checked)
+                                                                )
+                                                            }
+                                                        />
+                                                        启用
+                                                    </label>
+                                                    <button
+                                                        type="button"
+                                                        className="icon-btn"
+                                                        disabled={pluginBusy}
+                                                        onClick={() => runPluginAction(() => uninstallPlugin(plugin.id))}
+                                                    >
+                                                        卸载
+                                                    </button>
+                                                </div>
+                                            </div>
+                                        </div>
+                                    ))
+                                )}
+                            </div>
+
+                            <div className="config-subsection">
+                                <div className="config-subsection-header">
+                                    <div>
+                                        <h4>受信任的公钥</h4>
+                                        <span>每行一个 Base64 编码的 Ed25519 公钥。</span>
+                                    </div>
+                                </div>
+                                <div className="form-group">
+                                    <textarea
+                                        rows={3}
+                                        value={trustedKeys}
+                                        onChange={(e) => setTrustedKeys(e.target.value)}
+                                        disabled={pluginBusy}
+                                    />
+                                </div>
+                                <button
+                                    type="button"
+                                    className="add-btn add-inline"
+                                    onClick={handleTrustedKeysSave}
+                                    disabled={pluginBusy}
+                                >
+                                    Save
+                                </button>
+                            </div>
+                        </div>
                     ) : (
                         <form onSubmit={handleAgentSave} className="config-form">
                             <div className="config-form-header">
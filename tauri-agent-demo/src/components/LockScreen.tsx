@@ -0,0 +1,80 @@
+import { useState } from 'react';
+import { unlockApp, type LockReason } from '../api';
+import './LockScreen.css';
+
+interface LockScreenProps {
+    reason: LockReason;
+    verificationAvailable: boolean;
+    passphraseAvailable: boolean;
+    onUnlocked: () => void;
+}
+
+export default function LockScreen({
+    reason,
+    verificationAvailable,
+    passphraseAvailable,
+    onUnlocked,
+}: LockScreenProps) {
+    const [passphrase, setPassphrase] = useState('');
+    const [verifying, setVerifying] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    const handleUnlock = async (withPassphrase: boolean) => {
+        if (verifying || (withPassphrase && !passphrase)) return;
+        setVerifying(true);
+        setError(null);
+        try {
+            await unlockApp(withPassphrase ? passphrase : undefined);
+            onUnlocked();
+        } catch (err: any) {
+            setError(String(err?.message ?? err));
+            setPassphrase('');
+        } finally {
+            setVerifying(false);
+        }
+    };
+
+    return (
+        <div className="lock-screen">
+            <div className="lock-panel">
+                <div className="lock-title">已锁定</div>
+                <div className="lock-hint">
+                    {reason === 'idle' ? '长时间未操作，应用已自动锁定。' : '应用已锁定。'}
+                </div>
+                {passphraseAvailable && (
+                    <form
+                        className="lock-passphrase"
+                        onSubmit={(event) => {
+                            event.preventDefault();
+                            void handleUnlock(true);
+                        }}
+                    >
+                        <input
+                            type="password"
+                            value={passphrase}
+                            onChange={(e) => setPassphrase(e.target.value)}
+                            placeholder="口令"
+                            disabled={verifying}
+                            autoFocus
+                        />
+                        <button type="submit" disabled={verifying || !passphrase}>
+                            {verifying ? '验证中…' : '解锁'}
+                        </button>
+                    </form>
+                )}
+                {verificationAvailable && (
+                    <button
+                        type="button"
+                        className={passphraseAvailable ? 'lock-secondary' : undefined}
+                        onClick={() => void handleUnlock(false)}
+                        disabled={verifying}
+                        autoFocus={!passphraseAvailable}
+                    >
+                        {verifying && !passphraseAvailable ? '验证中…' : passphraseAvailable ? '使用系统验证' : '解锁'}
+                    </button>
+                )}
+                {error && <div className="lock-error">{error}</div>}
+            </div>
+        </div>
+    );
+}
@@ -1,6 +1,6 @@
 import { useMemo, useState, useEffect, useRef, type CSSProperties } from 'react';
 import { ChatSession } from '../types';
-import { getSessions, deleteSession, updateSession, copySession } from '../api';
+import { getSessions, deleteSession, updateSession, copySession, getKioskMode, openConversationWindow } from '../api';
 import ConfirmDialog from './ConfirmDialog';
 import './SessionList.css';
 
@@ -35,6 +35,7 @@ export default function SessionList({
     const [editingId, setEditingId] = useState<string | null>(null);
     const [editTitle, setEditTitle] = useState('');
     const [deleteTarget, setDeleteTarget] = useState<ChatSession | null>(null);
+    const [kiosk, setKiosk] = useState(false);
     const [contextMenu, setContextMenu] = useState<{
         session: ChatSession;
         x: number;
@@ -88,6 +89,10 @@ export default function SessionList({
         loadSessions();
     }, [refreshTrigger]);
 
+    useEffect(() => {
+        getKioskMode().then(setKiosk).catch(() => setKiosk(false));
+    }, []);
+
     const loadSessions = async () => {
         try {
             const data = await getSessions();
@@ -128,6 +133,15 @@ export default function SessionList({
         }
     };
 
+    const handleOpenInWindow = async (session: ChatSession) => {
+        try {
+            await openConversationWindow(session.id);
+        } catch (error) {
+            console.error('Failed to open conversation window:', error);
+            alert('打开新窗口失败');
+        }
+    };
+
     const startRename = (id: string) => {
         setEditingId(id);
         const session = sessions.find(s => s.id === id);
@@ -150,6 +164,8 @@ export default function SessionList({
     const openContextMenu = (session: ChatSession, event: React.MouseEvent) => {
         event.preventDefault();
         event.stopPropagation();
+        // Every menu entry edits the session, which kiosk mode forbids.
+        if (kiosk) return;
         const menuWidth = 176;
         const menuHeight = 88;
         const padding = 8;
@@ -420,6 +436,7 @@ export default function SessionList({
                                                                 <span className="session-time">
                                                                     {formatDate(session.updated_at || session.created_at)}
                                                                 </span>
+                                                                {!kiosk && (
                                                                 <button
                                                                     className="session-action-btn delete session-delete-btn"
                                                                     onClick={(e) => handleDelete(session, e)}
@@ -440,6 +457,7 @@ export default function SessionList({
                                                                         <path d="M6 6l1 14h10l1-14" />
                                                                     </svg>
                                                                 </button>
+                                                                )}
                                                             </div>
                                                         </div>
                                                     </div>
@@ -479,6 +497,16 @@ export default function SessionList({
                     >
                         拷贝对话
                     </button>
+                    <button
+                        type="button"
+                        className="session-context-item"
+                        onClick={() => {
+                            handleOpenInWindow(contextMenu.session);
+                            setContextMenu(null);
+                        }}
+                    >
+                        在新窗口打开
+                    </button>
                 </div>
             )}
             <ConfirmDialog
@@ -16,7 +16,7 @@ import katex from 'katex';
 import 'katex/dist/katex.min.css';
 import { open as openDialog } from '@tauri-apps/plugin-dialog';
 import { mkdir, readDir, readFile, readTextFile, watchImmediate, writeTextFile, type DirEntry, type UnwatchFn } from '@tauri-apps/plugin-fs';
-import { API_BASE_URL, notifyAstChanges, getAstSettings, updateAstSettings } from '../api';
+import { API_BASE_URL, backendFetch, notifyAstChanges, getAstSettings, updateAstSettings } from '../api';
 import { openPath, openUrl, revealItemInDir } from '@tauri-apps/plugin-opener';
 import { exportConfigFile, importConfigFile } from '../configExchange';
 import './WorkDirBrowser.css';
@@ -513,7 +513,7 @@ const readTextFromBackend = async (path: string) => {
   const controller = new AbortController();
   const timeout = window.setTimeout(() => controller.abort(), READ_BACKEND_TIMEOUT_MS);
   try {
-    const response = await fetch(`${API_BASE_URL}/local-file?path=${encodeURIComponent(path)}`, {
+    const response = await backendFetch(`${API_BASE_URL}/local-file?path=${encodeURIComponent(path)}`, {
       signal: controller.signal,
     });
     if (!response.ok) {
@@ -1,5 +1,6 @@
 import { open as openDialog, save as saveDialog } from '@tauri-apps/plugin-dialog';
 import { readTextFile, writeTextFile } from '@tauri-apps/plugin-fs';
+import { dialogDefaultPath, rememberDialogDir } from './api';
 
 export const CONFIG_EXPORT_VERSION = 1;
 
@@ -35,10 +36,11 @@ export const exportConfigFile = async (
 ): Promise<boolean> => {
   const target = await saveDialog({
     title: options.title,
-    defaultPath: options.defaultName,
+    defaultPath: await dialogDefaultPath('exports', options.defaultName),
     filters: options.filters ?? [{ name: 'JSON', extensions: ['json'] }],
   });
   if (!target) return false;
+  void rememberDialogDir('exports', target);
   const envelope: ConfigExportEnvelope = {
     kind,
     version: CONFIG_EXPORT_VERSION,
@@ -55,10 +57,12 @@ export const importConfigFile = async (
   const selected = await openDialog({
     title: options.title,
     multiple: false,
+    defaultPath: await dialogDefaultPath('exports'),
     filters: options.filters ?? [{ name: 'JSON', extensions: ['json'] }],
   });
   const path = normalizeSelectedPath(selected);
   if (!path) return null;
+  void rememberDialogDir('exports', path);
   const text = await readTextFile(path);
   let parsed: unknown;
   try {
@@ -0,0 +1,682 @@
+// Generated from src-tauri/src/events.rs by `UPDATE_EVENTS=1 cargo test`; do not edit.
+
+/** Payloads carry their event's schema version next to their own fields. */
+export type Versioned<T> = T & { schema_version: number };
+
+export type LockReason = 'idle' | 'manual';
+
+export interface LockEvent {
+    reason: LockReason;
+}
+
+export interface Empty {
+}
+
+export type WindowEffect = 'none' | 'vibrancy' | 'acrylic' | 'mica';
+
+export interface WindowEffectChanged {
+    effect: WindowEffect;
+}
+
+export interface AttachmentHandle {
+    id: string;
+    name: string;
+    mime: string;
+    size: number;
+    path: string;
+    /** Why the scan flagged the file; quarantined files must not reach the agent. */
+    quarantine: string | null;
+}
+
+export interface AttachmentAdded {
+    source: string;
+    attachment: AttachmentHandle;
+    /** What chat requests pass as `upload_id` instead of the file's bytes. */
+    upload_id: string;
+}
+
+export interface AttachmentRejected {
+    source: string;
+    reason: string;
+}
+
+export interface AudioLevel {
+    rms: number;
+    peak: number;
+    silent: boolean;
+}
+
+export interface TranscriptEvent {
+    text: string;
+}
+
+export type BackendErrorKind = 'missing_api_key' | 'rate_limited' | 'database_locked' | 'port_in_use' | 'missing_dependency' | 'traceback' | 'error_record';
+
+export type OutputStream = 'stdout' | 'stderr';
+
+export interface BackendError {
+    kind: BackendErrorKind;
+    /** The final exception line, or the logged error itself. */
+    message: string;
+    /** The full traceback, when there was one. */
+    details: string | null;
+    hint: string | null;
+    stream: OutputStream;
+}
+
+export interface RpcEvent {
+    session_id: string;
+    payload: any;
+}
+
+export interface BaseUrl {
+    url: string;
+}
+
+export interface SidecarCrash {
+    name: string;
+    reason: string;
+    /** Automatic restarts made so far in this run of crashes. */
+    attempts: number;
+    will_restart: boolean;
+}
+
+export type BackendPhase = 'starting' | 'ready' | 'failed';
+
+export interface BackendStatus {
+    phase: BackendPhase;
+    /** When the phase last changed. */
+    since: string | null;
+    /** Time from launch to the first healthy answer. */
+    ready_after_ms: number | null;
+    base_url: string | null;
+    error: string | null;
+}
+
+export type BackendKind = 'sidecar' | 'external' | 'remote';
+
+export interface BackendProfileInfo {
+    name: string;
+    kind: BackendKind;
+    url: string | null;
+    /** The token itself never leaves the shell. */
+    has_token: boolean;
+    active: boolean;
+}
+
+export interface SidecarRestart {
+    name: string;
+    attempt: number;
+}
+
+export type BackendTransport = 'tcp' | 'stdio';
+
+export interface BackendEndpoint {
+    url: string;
+    /** The loopback port, when the backend is reached over one. */
+    port: number | null;
+    transport: BackendTransport;
+    /** Where to open the event WebSocket, when it is not derived from `url`. */
+    events_url: string | null;
+}
+
+export type Compatibility = 'compatible' | 'shimmed' | 'incompatible';
+
+export interface VersionReport {
+    /** The backend's own version; absent from backends that predate `/version`. */
+    version: string | null;
+    api: number;
+    min_api: number;
+    max_api: number;
+    compatibility: Compatibility;
+}
+
+export interface BackupStatus {
+    destination: string | null;
+    interval_hours: number;
+    last_success: string | null;
+    last_attempt: string | null;
+    last_error: string | null;
+    last_snapshot: string | null;
+    /** Snapshots currently verified in the destination. */
+    mirrored: number;
+}
+
+export interface BudgetThresholdEvent {
+    month: string;
+    threshold: number;
+    cost_usd: number;
+    budget_usd: number;
+    paused: boolean;
+}
+
+export interface CaptureError {
+    message: string;
+}
+
+export interface ChatDelta {
+    request_id: string;
+    /** One SSE `data:` payload, as the backend sent it. */
+    data: any;
+}
+
+export interface ChatDone {
+    request_id: string;
+    cancelled: boolean;
+    error: string | null;
+}
+
+export type ConfigFile = 'app' | 'tools';
+
+export interface ConfigChanged {
+    file: ConfigFile;
+    path: string;
+}
+
+export type Operation = 'backup' | 'restore';
+
+export interface BackupProgress {
+    operation: Operation;
+    copied_pages: number;
+    total_pages: number;
+}
+
+export interface DatabaseBackup {
+    path: string;
+    size_bytes: number;
+    created_at: string;
+}
+
+export type DeepLink =
+    | { action: 'new_chat'; prompt: string | null }
+    | { action: 'open_conversation'; session_id: string };
+
+export interface ExportProgress {
+    done: number;
+    total: number;
+    session_id: string;
+    error: string | null;
+}
+
+export interface HealthSample {
+    at: string;
+    /** Round trip of the poll itself; `None` when it failed. */
+    latency_ms: number | null;
+    /** Share of the requests answered since the previous sample that failed with a 5xx. */
+    error_rate: number | null;
+    requests: number;
+    in_flight: number;
+    /** Agent tasks waiting for a slot. */
+    queue_depth: number;
+    running_tasks: number;
+    error: string | null;
+}
+
+export interface IdleChanged {
+    away: boolean;
+}
+
+export interface ImportProgress {
+    done: number;
+    total: number;
+    title: string;
+}
+
+export interface InboxFileEvent {
+    source: string;
+    attachment: AttachmentHandle;
+}
+
+export interface IndexingError {
+    message: string;
+}
+
+export interface IndexingProgress {
+    folder: string;
+    path: string;
+    processed: number;
+    remaining: number;
+}
+
+export interface SecondLaunch {
+    /** Command line without the executable; deep links arrive here on Windows and Linux. */
+    args: string[];
+    cwd: string;
+}
+
+export interface LocaleInfo {
+    /** What the shell and backend use: the override, else the OS locale. */
+    locale: string;
+    system: string;
+    /** The user's choice; `None` follows the OS. */
+    selected: string | null;
+    /** The bundle native strings come from. */
+    bundle: string;
+    available: string[];
+}
+
+export type MenuAction = 'new_chat' | 'export' | 'open_settings' | 'diagnostics';
+
+export interface MenuActionEvent {
+    action: MenuAction;
+}
+
+export interface CeilingExceeded {
+    memory_bytes: number;
+    ceiling_bytes: number;
+}
+
+export interface BackendMetrics {
+    at: string;
+    pid: number;
+    processes: number;
+    /** Summed over cores, so a busy process on four cores can read 400. */
+    cpu_percent: number;
+    memory_bytes: number;
+    /** `None` where the OS does not report it. */
+    open_files: number | null;
+}
+
+export interface MigrationSummary {
+    version: number;
+    created_at: string;
+    app_version: string;
+    /** Whether API keys were kept; without consent they are blanked before packing. */
+    includes_secrets: boolean;
+    /** App data folder on the exporting machine, used to rebase plugin paths. */
+    source_data_dir: string;
+    files: number;
+    /** Where the archive is; blank in the manifest packed inside it. */
+    path: string;
+    size: number;
+}
+
+export interface MediaKeyEvent {
+    key: string;
+}
+
+export interface Reachability {
+    /** `None` when there was nothing to check, e.g. no LLM is configured yet. */
+    reachable: boolean | null;
+    latency_ms: number | null;
+    error: string | null;
+}
+
+export interface ConnectivityStatus {
+    /** False when the backend or the LLM endpoint cannot be reached. */
+    online: boolean;
+    backend: Reachability;
+    llm: Reachability;
+    /** Base URL of the default LLM config, as the backend would call it. */
+    llm_endpoint: string | null;
+    checked_at: string;
+}
+
+export interface NotificationActionEvent {
+    /** The OS notification id, where the platform reports one. */
+    id: number | null;
+    action: string;
+    session_id: string | null;
+}
+
+export interface NavigateEvent {
+    session_id: string;
+    action: string;
+}
+
+export interface OnboardingProgress {
+    step: string;
+    done: number;
+    total: number;
+}
+
+export type OutboxStatus = 'queued' | 'sending' | 'retrying' | 'sent' | 'failed' | 'cancelled';
+
+export interface OutboxStatusEvent {
+    id: string;
+    session_id: string | null;
+    status: OutboxStatus;
+    error: string | null;
+    /** Requests still queued, this one included unless it left the queue. */
+    remaining: number;
+}
+
+export interface PermissionPrompt {
+    tool: string;
+    workspace: string;
+    detail: string;
+}
+
+export type SetupStage = 'venv' | 'install' | 'done' | 'failed';
+
+export interface SetupProgress {
+    stage: SetupStage;
+    /** A line of the interpreter's or pip's output, or the error when failed. */
+    line: string | null;
+}
+
+export interface OpenConversation {
+    session_id: string;
+}
+
+export type RunStatus = 'started' | 'finished' | 'failed';
+
+export interface ScheduledRun {
+    id: string;
+    status: RunStatus;
+    /** The conversation the run posted to, once it has one. */
+    session_id: string | null;
+    error: string | null;
+}
+
+export interface SessionInfo {
+    id: string;
+    label: string | null;
+    created_at: string;
+    port: number;
+    /** False while the worker restarts after a crash. */
+    running: boolean;
+    /** Where the webview sends this session's requests. */
+    url: string;
+    /** Needed on `url` unless it goes through the shell's proxy. */
+    token: string | null;
+    scratch_dir: string;
+}
+
+export interface SessionList {
+    sessions: SessionInfo[];
+}
+
+export type ShortcutAction = 'new_chat' | 'summon_window' | 'quick_ask' | 'toggle_logs' | 'open_settings';
+
+export type ShortcutScope = 'global' | 'app';
+
+export interface ShortcutBinding {
+    action: ShortcutAction;
+    accelerator: string;
+    default_accelerator: string;
+    scope: ShortcutScope;
+}
+
+export interface ShortcutBindings {
+    bindings: ShortcutBinding[];
+}
+
+export interface ShortcutTriggered {
+    action: ShortcutAction;
+}
+
+export interface SidecarEvent {
+    name: string;
+    error: string | null;
+}
+
+export interface SnapshotInfo {
+    version: number;
+    name: string;
+    created_at: string;
+    /** Files captured next to the manifest; configs missing here did not exist yet. */
+    files: string[];
+}
+
+export type Stage = 'resolving' | 'migrating' | 'spawning' | 'waiting' | 'ready' | 'failed';
+
+export interface StageUpdate {
+    stage: Stage;
+    /** The error for `Failed`. */
+    detail: string | null;
+}
+
+export type TaskControl = 'pause' | 'abort';
+
+export interface TaskControlApplied {
+    session_id: string;
+    control: TaskControl;
+}
+
+export type ThemePreference = 'system' | 'light' | 'dark';
+
+export type Theme = 'light' | 'dark';
+
+export interface ThemeInfo {
+    preference: ThemePreference;
+    system: Theme;
+    /** What the windows show. */
+    theme: Theme;
+}
+
+export interface SpeechFinished {
+    id: number;
+    /** Cut short or dropped from the queue by `stop_speaking`. */
+    interrupted: boolean;
+    error: string | null;
+}
+
+export interface UpdateInfo {
+    version: string;
+    current_version: string;
+    notes: string | null;
+    date: string | null;
+    rollout: number;
+    sidecar_version: string | null;
+}
+
+export type UpdatePhase = 'backup' | 'download' | 'install';
+
+export interface UpdateProgress {
+    phase: UpdatePhase;
+    downloaded: number;
+    total: number | null;
+}
+
+export type KillOutcome = 'stopped' | 'recycled' | 'not_found';
+
+export interface RunKilled {
+    request_id: string;
+    reason: string;
+    outcome: KillOutcome;
+}
+
+export interface WorkspaceInfo {
+    id: string;
+    name: string;
+    created_at: string | null;
+    active: boolean;
+    data_dir: string;
+}
+
+export interface ShellEvents {
+    /** The windows were locked; their contents stay hidden until `app-lock://unlocked`. */
+    'app-lock://locked': Versioned<LockEvent>;
+    'app-lock://unlocked': Versioned<Empty>;
+    /** The window effect was applied, so the webview can make its background translucent. */
+    'appearance://window-effect': Versioned<WindowEffectChanged>;
+    /** A file dropped on the main window was staged for the composer. */
+    'attachment://added': Versioned<AttachmentAdded>;
+    'attachment://rejected': Versioned<AttachmentRejected>;
+    /** A level per 100ms of input while recording. */
+    'audio-level': Versioned<AudioLevel>;
+    /** The recording hit its silence timeout; the webview finishes it with `stop_recording`. */
+    'audio-silence': Versioned<Empty>;
+    'audio://transcript': Versioned<TranscriptEvent>;
+    /** A known failure recognised in the backend's output. */
+    'backend-error': Versioned<BackendError>;
+    /** Relayed hub events from a stdio backend, in place of the WebSocket. */
+    'backend-rpc://event': Versioned<RpcEvent>;
+    /** The address the webview should call moved, e.g. after a restart on a new port. */
+    'backend://base-url': Versioned<BaseUrl>;
+    /** The bundled backend exited on its own. */
+    'backend://crashed': Versioned<SidecarCrash>;
+    /** Startup gave up on the backend. */
+    'backend://failed': Versioned<BackendStatus>;
+    /** Another backend profile was selected. */
+    'backend://profile': Versioned<BackendProfileInfo>;
+    /** Startup is over and the backend answered `/health`. */
+    'backend://ready': Versioned<BackendStatus>;
+    /** The bundled backend was brought back after a crash. */
+    'backend://restarted': Versioned<SidecarRestart>;
+    /** The bundled backend was stopped on purpose. */
+    'backend://stopped': Versioned<Empty>;
+    /** How to reach the backend changed; carries the whole endpoint. */
+    'backend://url': Versioned<BackendEndpoint>;
+    /** The backend answered the version handshake. */
+    'backend://version': Versioned<VersionReport>;
+    'backup://status': Versioned<BackupStatus>;
+    /** Spending crossed one of the budget's warning thresholds. */
+    'budget-threshold': Versioned<BudgetThresholdEvent>;
+    'capture://error': Versioned<CaptureError>;
+    'capture://started': Versioned<Empty>;
+    'capture://stopped': Versioned<Empty>;
+    /** One SSE event of a stream the shell reads for the webview. */
+    'chat://delta': Versioned<ChatDelta>;
+    /** A stream the shell reads for the webview ended. */
+    'chat://done': Versioned<ChatDone>;
+    /** A backend config file was changed by something other than the shell. */
+    'config://changed': Versioned<ConfigChanged>;
+    'database://backup-progress': Versioned<BackupProgress>;
+    /** The database was restored; carries the backup taken of it beforehand. */
+    'database://restored': Versioned<DatabaseBackup>;
+    /** An `agentapp://` link arrived while the main window was listening. */
+    'deeplink://open': Versioned<DeepLink>;
+    'export://progress': Versioned<ExportProgress>;
+    /** A proxied request reported finishing, for the health panel. */
+    'health://sample': Versioned<HealthSample>;
+    'idle://changed': Versioned<IdleChanged>;
+    'import://progress': Versioned<ImportProgress>;
+    /** A file dropped in the inbox folder was staged as an attachment. */
+    'inbox://file-staged': Versioned<InboxFileEvent>;
+    'indexing://error': Versioned<IndexingError>;
+    /** The indexing queue drained. */
+    'indexing://idle': Versioned<Empty>;
+    'indexing://progress': Versioned<IndexingProgress>;
+    /** The app was launched again while running; the main window gets its arguments. */
+    'instance://second-launch': Versioned<SecondLaunch>;
+    'locale://changed': Versioned<LocaleInfo>;
+    /** An application menu item the frontend carries out. */
+    'menu://action': Versioned<MenuActionEvent>;
+    /** The backend went over its memory ceiling and is being restarted. */
+    'metrics://ceiling-exceeded': Versioned<CeilingExceeded>;
+    'metrics://sample': Versioned<BackendMetrics>;
+    /** A migration archive was restored into this install. */
+    'migration://restored': Versioned<MigrationSummary>;
+    /** Media keys grabbed for the app on Linux. */
+    'media-key': Versioned<MediaKeyEvent>;
+    'net://offline': Versioned<ConnectivityStatus>;
+    'net://online': Versioned<ConnectivityStatus>;
+    'notification://action': Versioned<NotificationActionEvent>;
+    /** A notification about a conversation was acted on. */
+    'notification://navigate': Versioned<NavigateEvent>;
+    'onboarding://progress': Versioned<OnboardingProgress>;
+    /** A queued chat request moved through the outbox. */
+    'outbox://status': Versioned<OutboxStatusEvent>;
+    /** The agent asked for a tool and the approval dialog is open. */
+    'permission://request': Versioned<PermissionPrompt>;
+    'python-runtime://progress': Versioned<SetupProgress>;
+    /** Open a conversation from the quick ask window in the main one. */
+    'quick-ask://open': Versioned<OpenConversation>;
+    /** The quick ask window is about to be shown again. */
+    'quick-ask://refresh': Versioned<Empty>;
+    /** The tray's quick reply popup is about to be shown again. */
+    'quick-reply://refresh': Versioned<Empty>;
+    /** A scheduled task started or ended a run. */
+    'scheduler://run': Versioned<ScheduledRun>;
+    /** A session was created or terminated; carries all of them. */
+    'sessions://changed': Versioned<SessionList>;
+    /** Bindings changed; carries all of them. */
+    'shortcut://changed': Versioned<ShortcutBindings>;
+    /** A global shortcut fired for an action the webview carries out. */
+    'shortcut://triggered': Versioned<ShortcutTriggered>;
+    'sidecar://crashed': Versioned<SidecarCrash>;
+    'sidecar://failed': Versioned<SidecarEvent>;
+    'sidecar://ready': Versioned<SidecarEvent>;
+    'sidecar://restarted': Versioned<SidecarRestart>;
+    'snapshot://restored': Versioned<SnapshotInfo>;
+    /** Launch moved to another stage, for the splash window. */
+    'startup://stage': Versioned<StageUpdate>;
+    /** A taskbar or dock button paused or aborted a run. */
+    'task-control://applied': Versioned<TaskControlApplied>;
+    'theme://changed': Versioned<ThemeInfo>;
+    /** An utterance ended, was interrupted or failed. */
+    'tts://finished': Versioned<SpeechFinished>;
+    'update://available': Versioned<UpdateInfo>;
+    'update://progress': Versioned<UpdateProgress>;
+    /** A run was stopped for taking too long or on request. */
+    'watchdog://killed': Versioned<RunKilled>;
+    'workspace://switched': Versioned<WorkspaceInfo>;
+}
+
+export const EVENT_VERSIONS: { [K in keyof ShellEvents]: number } = {
+    'app-lock://locked': 1,
+    'app-lock://unlocked': 1,
+    'appearance://window-effect': 1,
+    'attachment://added': 1,
+    'attachment://rejected': 1,
+    'audio-level': 1,
+    'audio-silence': 1,
+    'audio://transcript': 1,
+    'backend-error': 1,
+    'backend-rpc://event': 1,
+    'backend://base-url': 1,
+    'backend://crashed': 1,
+    'backend://failed': 1,
+    'backend://profile': 1,
+    'backend://ready': 1,
+    'backend://restarted': 1,
+    'backend://stopped': 1,
+    'backend://url': 1,
+    'backend://version': 1,
+    'backup://status': 1,
+    'budget-threshold': 1,
+    'capture://error': 1,
+    'capture://started': 1,
+    'capture://stopped': 1,
+    'chat://delta': 1,
+    'chat://done': 1,
+    'config://changed': 1,
+    'database://backup-progress': 1,
+    'database://restored': 1,
+    'deeplink://open': 1,
+    'export://progress': 1,
+    'health://sample': 1,
+    'idle://changed': 1,
+    'import://progress': 1,
+    'inbox://file-staged': 1,
+    'indexing://error': 1,
+    'indexing://idle': 1,
+    'indexing://progress': 1,
+    'instance://second-launch': 1,
+    'locale://changed': 1,
+    'menu://action': 1,
+    'metrics://ceiling-exceeded': 1,
+    'metrics://sample': 1,
+    'migration://restored': 1,
+    'media-key': 1,
+    'net://offline': 1,
+    'net://online': 1,
+    'notification://action': 1,
+    'notification://navigate': 1,
+    'onboarding://progress': 1,
+    'outbox://status': 1,
+    'permission://request': 1,
+    'python-runtime://progress': 1,
+    'quick-ask://open': 1,
+    'quick-ask://refresh': 1,
+    'quick-reply://refresh': 1,
+    'scheduler://run': 1,
+    'sessions://changed': 1,
+    'shortcut://changed': 1,
+    'shortcut://triggered': 1,
+    'sidecar://crashed': 1,
+    'sidecar://failed': 1,
+    'sidecar://ready': 1,
+    'sidecar://restarted': 1,
+    'snapshot://restored': 1,
+    'startup://stage': 1,
+    'task-control://applied': 1,
+    'theme://changed': 1,
+    'tts://finished': 1,
+    'update://available': 1,
+    'update://progress': 1,
+    'watchdog://killed': 1,
+    'workspace://switched': 1,
+};
@@ -2,19 +2,65 @@ import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
 import WorkDirWindow from "./WorkDirWindow";
-import { resolveApiBaseUrl } from "./api";
+import QuickReplyWindow from "./QuickReplyWindow";
+import QuickAskWindow from "./QuickAskWindow";
+import OnboardingWindow from "./OnboardingWindow";
+import UnlockWindow from "./UnlockWindow";
+import CaptureSelectionWindow from "./CaptureSelectionWindow";
+import { notifyFrontendReady, resolveApiBaseUrl, waitForBackend } from "./api";
 
 const params = new URLSearchParams(window.location.search);
-const isWorkdirWindow = params.get("window") === "workdir";
-const Root = isWorkdirWindow ? WorkDirWindow : App;
+const windowKind = params.get("window");
+const isWorkdirWindow = windowKind === "workdir";
+const isQuickReplyWindow = windowKind === "quick-reply";
+const isQuickAskWindow = windowKind === "quick-ask";
+// A conversation detached from the main window; it renders App for one conversation.
+const isConversationWindow = windowKind === "conversation";
+const isOnboardingWindow = windowKind === "onboarding";
+// Asks for the passphrase while the encrypted chat database is locked.
+const isUnlockWindow = windowKind === "unlock";
+// The transparent overlay a screenshot region is dragged out on.
+const isCaptureSelectionWindow = windowKind === "capture-selection";
+const isSecondaryWindow =
+  isWorkdirWindow ||
+  isQuickReplyWindow ||
+  isQuickAskWindow ||
+  isOnboardingWindow ||
+  isUnlockWindow ||
+  isCaptureSelectionWindow ||
+  isConversationWindow;
+const Root = isWorkdirWindow
+  ? WorkDirWindow
+  : isQuickReplyWindow
+    ? QuickReplyWindow
+    : isQuickAskWindow
+      ? QuickAskWindow
+      : isOnboardingWindow
+        ? OnboardingWindow
+        : isUnlockWindow
+          ? UnlockWindow
+          : isCaptureSelectionWindow
+            ? CaptureSelectionWindow
+            : App;
 
 const bootstrap = async () => {
+  // The main window stays hidden until both are ready; holding the first render back
+  // keeps its initial requests from racing the backend's startup.
+  if (!isSecondaryWindow) {
+    await waitForBackend();
+  }
   await resolveApiBaseUrl();
   ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
     <React.StrictMode>
       <Root />
     </React.StrictMode>,
   );
+  if (!isSecondaryWindow) {
+    // Wait a frame so the first render is painted before the window appears.
+    requestAnimationFrame(() => {
+      void notifyFrontendReady().catch(() => undefined);
+    });
+  }
 };
 
 void bootstrap();
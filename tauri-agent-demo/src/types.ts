@@ -525,7 +525,9 @@ export interface MessageAttachment {
 export interface ChatAttachmentInput {
     name?: string;
     mime?: string;
-    data_base64: string;
+    data_base64?: string;
+    /** A file the shell already sent to the backend; replaces `data_base64`. */
+    upload_id?: string;
     width?: number;
     height?: number;
     size?: number;
@@ -1,15 +1,23 @@
-import { API_BASE_URL } from './api';
+import { API_BASE_URL, API_EVENTS_URL, onShellEvent } from './api';
 import type { WsEvent, WsStatusListener } from './wsTypes';
 
 type EventListener = (event: WsEvent) => void;
 
+const ALL_SESSIONS = '*';
+
+// A stdio backend is reached through the shell's custom scheme and pushes events
+// as Tauri events instead of over a WebSocket.
+const usesRpcTransport = () => /^(agent-backend:|https?:\/\/agent-backend\.localhost)/i.test(API_BASE_URL);
+
 const buildWsUrl = () => {
+  if (API_EVENTS_URL) return API_EVENTS_URL;
   const base = API_BASE_URL.replace(/^http/i, (match) => (match.toLowerCase() === 'https' ? 'wss' : 'ws'));
   return `${base}/ws`;
 };
 
 class WsClient {
   private ws: WebSocket | null = null;
+  private rpc: Promise<() => void> | null = null;
   private listeners = new Set<EventListener>();
   private statusListeners = new Set<WsStatusListener>();
   private subscriptions = new Set<string>();
@@ -20,7 +28,11 @@ class WsClient {
 
   connect() {
     this.shouldReconnect = true;
-    if (this.ws) return;
+    if (this.ws || this.rpc) return;
+    if (usesRpcTransport()) {
+      this.openRpc();
+      return;
+    }
     this.open();
   }
 
@@ -35,6 +47,10 @@ class WsClient {
       }
       this.ws = null;
     }
+    if (this.rpc) {
+      this.rpc.then((unlisten) => unlisten()).catch(() => {});
+      this.rpc = null;
+    }
     this.setConnected(false);
   }
 
@@ -124,6 +140,30 @@ class WsClient {
     };
   }
 
+  private openRpc() {
+    const rpc = onShellEvent('backend-rpc://event', ({ session_id: sessionId, payload }) => {
+      if (!payload || typeof payload.type !== 'string') return;
+      if (!this.subscriptions.has(ALL_SESSIONS) && !this.subscriptions.has(sessionId)) return;
+      this.listeners.forEach((listener) => listener(payload as WsEvent));
+    });
+    this.rpc = rpc;
+    rpc.then(
+      () => {
+        if (this.rpc === rpc) {
+          this.reconnectAttempt = 0;
+          this.setConnected(true);
+        }
+      },
+      () => {
+        if (this.rpc !== rpc) return;
+        this.rpc = null;
+        if (this.shouldReconnect) {
+          this.scheduleReconnect();
+        }
+      }
+    );
+  }
+
   private send(payload: any) {
     if (!this.ws || this.ws.readyState !== WebSocket.OPEN) return;
     try {
@@ -139,7 +179,11 @@ class WsClient {
     const delay = Math.min(30_000, 1000 * 2 ** (this.reconnectAttempt - 1));
     this.reconnectTimer = window.setTimeout(() => {
       this.reconnectTimer = null;
-      this.open();
+      if (usesRpcTransport()) {
+        this.openRpc();
+      } else {
+        this.open();
+      }
     }, delay);
   }
 
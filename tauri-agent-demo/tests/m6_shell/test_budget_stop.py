@@ -0,0 +1,24 @@
+from fastapi.testclient import TestClient
+
+
+def _backend(monkeypatch):
+    import main as backend_main
+
+    # Requests from the test client carry no shell token.
+    monkeypatch.setattr(backend_main, "AUTH_TOKEN", "")
+    return backend_main
+
+
+def test_budget_stop_pauses_llm_calls_until_resumed(monkeypatch) -> None:
+    backend_main = _backend(monkeypatch)
+    monkeypatch.setattr(backend_main, "READ_ONLY_MODE", False)
+    monkeypatch.setattr(backend_main, "BUDGET_PAUSED", False)
+    client = TestClient(backend_main.app)
+
+    assert client.post("/system/budget", json={"paused": True}).json() == {"paused": True}
+    paused = client.post("/chat/agent/stream", json={})
+    assert paused.status_code == 402
+    assert client.post("/chat/stop", json={}).status_code != 402
+
+    assert client.post("/system/budget", json={"paused": False}).json() == {"paused": False}
+    assert client.post("/chat/agent/stream", json={}).status_code != 402
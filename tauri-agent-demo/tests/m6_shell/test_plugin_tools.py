@@ -0,0 +1,62 @@
+import asyncio
+import json
+import sys
+from pathlib import Path
+
+import pytest
+
+
+def _install(tmp_path: Path, monkeypatch, enabled: bool = True) -> Path:
+    import tools.config as tool_config
+
+    entry = tmp_path / "demo" / "run"
+    entry.parent.mkdir()
+    # Echoes the tool name and the arguments it was given.
+    entry.write_text(
+        f"#!{sys.executable}\n"
+        "import json, sys\n"
+        "args = json.load(sys.stdin)\n"
+        "print(json.dumps({'tool': sys.argv[1], 'args': args}))\n",
+        encoding="utf-8",
+    )
+    entry.chmod(0o755)
+    config = {
+        "project_root": str(tmp_path),
+        "enabled": {"demo_echo": True, "demo_off": False},
+        "plugins": {
+            "demo": {
+                "id": "demo",
+                "name": "Demo",
+                "kind": "native",
+                "path": str(entry),
+                "tools": ["demo_echo", "demo_off"],
+                "enabled": enabled,
+            }
+        },
+    }
+    monkeypatch.setattr(tool_config, "_TOOL_CONFIG", config)
+    return entry
+
+
+@pytest.mark.skipif(sys.platform == "win32", reason="the demo entry is a shebang script")
+def test_enabled_plugin_tools_run_their_entry(monkeypatch, tmp_path: Path) -> None:
+    from tools.builtin.plugin_tools import plugin_tools_from_config
+
+    _install(tmp_path, monkeypatch)
+    tools = plugin_tools_from_config()
+    assert [tool.name for tool in tools] == ["demo_echo"]
+
+    result = asyncio.run(tools[0].execute(json.dumps({"arguments": {"text": "hi"}})))
+    assert json.loads(result) == {"tool": "demo_echo", "args": {"text": "hi"}}
+
+
+def test_disabled_or_missing_plugins_register_nothing(monkeypatch, tmp_path: Path) -> None:
+    import tools.config as tool_config
+    from tools.builtin.plugin_tools import plugin_tools_from_config
+
+    entry = _install(tmp_path, monkeypatch, enabled=False)
+    assert plugin_tools_from_config() == []
+
+    tool_config._TOOL_CONFIG["plugins"]["demo"]["enabled"] = True
+    entry.unlink()
+    assert plugin_tools_from_config() == []
@@ -0,0 +1,32 @@
+from fastapi.testclient import TestClient
+
+
+class _FakeServer:
+    def __init__(self):
+        self.should_exit = False
+
+
+def _backend(monkeypatch):
+    import main as backend_main
+
+    # Requests from the test client carry no shell token.
+    monkeypatch.setattr(backend_main, "AUTH_TOKEN", "")
+    return backend_main
+
+
+def test_read_only_mode_refuses_writes_but_not_reads_or_stops(monkeypatch) -> None:
+    backend_main = _backend(monkeypatch)
+    monkeypatch.setattr(backend_main, "READ_ONLY_MODE", True)
+    monkeypatch.setattr(backend_main, "UVICORN_SERVER", _FakeServer())
+    monkeypatch.setattr(backend_main, "BUDGET_PAUSED", False)
+    client = TestClient(backend_main.app)
+
+    refused = client.post("/sessions", json={"title": "nope"})
+    assert refused.status_code == 403
+    assert refused.json() == {"detail": "The app is in read-only mode."}
+    assert client.put("/tools/config", json={}).status_code == 403
+    assert client.delete("/sessions/any").status_code == 403
+
+    assert client.get("/health").status_code == 200
+    assert client.post("/system/budget", json={"paused": False}).status_code == 200
+    assert client.post("/shutdown").status_code == 200
@@ -0,0 +1,41 @@
+from fastapi.testclient import TestClient
+
+
+class _FakeServer:
+    def __init__(self):
+        self.should_exit = False
+
+
+def _backend(monkeypatch):
+    import main as backend_main
+
+    # Requests from the test client carry no shell token.
+    monkeypatch.setattr(backend_main, "AUTH_TOKEN", "")
+    return backend_main
+
+
+def test_shutdown_stops_uvicorn_when_serving_over_tcp(monkeypatch) -> None:
+    backend_main = _backend(monkeypatch)
+    server = _FakeServer()
+    monkeypatch.setattr(backend_main, "UVICORN_SERVER", server)
+
+    response = TestClient(backend_main.app).post("/shutdown")
+    assert response.status_code == 200
+    assert response.json() == {"status": "stopping"}
+    assert server.should_exit is True
+
+
+def test_shutdown_over_stdio(monkeypatch) -> None:
+    import stdio_transport
+
+    backend_main = _backend(monkeypatch)
+    monkeypatch.setattr(backend_main, "UVICORN_SERVER", None)
+    client = TestClient(backend_main.app)
+
+    monkeypatch.setattr(stdio_transport, "_shutdown_hook", None)
+    assert client.post("/shutdown").json() == {"status": "unsupported"}
+
+    calls = []
+    monkeypatch.setattr(stdio_transport, "_shutdown_hook", lambda: calls.append(True))
+    assert client.post("/shutdown").json() == {"status": "stopping"}
+    assert calls == [True]
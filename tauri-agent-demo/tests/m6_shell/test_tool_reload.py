@@ -0,0 +1,40 @@
+import json
+from pathlib import Path
+
+from fastapi.testclient import TestClient
+
+
+def _backend(monkeypatch):
+    import main as backend_main
+
+    # Requests from the test client carry no shell token.
+    monkeypatch.setattr(backend_main, "AUTH_TOKEN", "")
+    return backend_main
+
+
+def test_empty_tools_config_patch_reloads_tools_from_disk(monkeypatch, tmp_path: Path) -> None:
+    import tools.config as tool_config
+    from tools.base import ToolRegistry
+
+    backend_main = _backend(monkeypatch)
+    # Put back the loaded config and registry the reload replaces.
+    monkeypatch.setattr(tool_config, "_TOOL_CONFIG", tool_config._TOOL_CONFIG)
+    monkeypatch.setattr(ToolRegistry, "_tools", dict(ToolRegistry._tools))
+    config_path = tmp_path / "tools_config.json"
+    monkeypatch.setenv("TOOLS_CONFIG_PATH", str(config_path))
+    registered = []
+    monkeypatch.setattr(backend_main, "register_builtin_tools", lambda: registered.append(True))
+    client = TestClient(backend_main.app)
+
+    # The shell edits the file itself, then sends an empty patch.
+    config_path.write_text(
+        json.dumps({"plugins": {"demo": {"path": str(tmp_path / "demo")}}}),
+        encoding="utf-8",
+    )
+    response = client.put("/tools/config", json={})
+    assert response.status_code == 200
+    assert response.json()["plugins"] == {"demo": {"path": str(tmp_path / "demo")}}
+    assert registered == [True]
+    assert client.get("/tools/config").json()["plugins"] == response.json()["plugins"]
+    on_disk = json.loads(config_path.read_text(encoding="utf-8"))
+    assert on_disk == {"plugins": {"demo": {"path": str(tmp_path / "demo")}}}